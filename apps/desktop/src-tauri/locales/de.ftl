@@ -0,0 +1,89 @@
+## Tray menu
+tray-open-dashboard = Dashboard öffnen
+tray-settings = Einstellungen…
+tray-profile = Profil
+tray-credentials = API-Schlüssel
+tray-layouts = Layouts
+tray-privacy-mode = Privatsphäre-Modus
+tray-services = Dienste
+tray-restart-service = { $service } neu starten
+tray-focus-start = Fokus starten ({ $minutes } Min.)
+tray-focus-stop = Fokus beenden
+tray-lan-stop = LAN-Zugriff beenden ({ $address })
+tray-restart-all = Alle Dienste neu starten
+tray-copy-diagnostics = Diagnose kopieren
+tray-debug-logging = Debug-Protokollierung
+tray-quit = Beenden
+
+## Tray tooltip
+tray-tooltip = Tulsbot
+tray-tooltip-status = Tulsbot — { $status }
+tray-tooltip-private = { $tooltip } (Privatsphäre-Modus)
+tray-tooltip-stalled = { $tooltip } (Überwachung hängt)
+
+## Health status
+status-healthy = fehlerfrei
+status-degraded = eingeschränkt
+status-down = ausgefallen
+
+## Window titles (read by screen readers)
+window-main-title = Tulsbot
+window-popover-title = Tulsbot-Chat
+window-pip-title = Tulsbot-Antwort
+
+## Notifications
+notify-title = Tulsbot
+notify-service-down = { $service } ist ausgefallen
+notify-service-up = { $service } läuft wieder
+notify-service-blocked = { $service } ist ausgefallen, weil { $dependency } ausgefallen ist
+notify-all-down = Alle Dienste sind ausgefallen
+notify-private-body = Neue Benachrichtigung (im Privatsphäre-Modus ausgeblendet)
+notify-dnd-summary-title = Während du fokussiert warst
+notify-dnd-summary-down =
+    { $count ->
+        [one] 1 Dienst ist ausgefallen, während du fokussiert warst
+       *[other] { $count } Dienste sind ausgefallen, während du fokussiert warst
+    }
+notify-dnd-summary-other =
+    { $count ->
+        [one] 1 Benachrichtigung ist eingegangen, während du fokussiert warst
+       *[other] { $count } Benachrichtigungen sind eingegangen, während du fokussiert warst
+    }
+notify-action-restart = Neu starten
+notify-action-ignore = Ignorieren
+notify-action-logs = Logs öffnen
+notify-budget-warning = { $provider } hat { $percent } % des Monatsbudgets verbraucht
+notify-budget-exceeded = { $provider } hat das Monatsbudget erreicht; kostenpflichtige Anfragen sind pausiert. Wechsle zu einem lokalen Modell, um weiterzumachen.
+notify-chat-probe-failed = Der Chat funktioniert nicht: { $error }
+notify-alert-firing = Alarm { $rule }: { $detail }
+notify-alert-resolved = Behoben { $rule }: { $detail }
+notify-focus-done = Fokus-Sitzung beendet – Zeit für eine Pause
+notify-focus-done-label = { $label } beendet – Zeit für eine Pause
+notify-feed-digest =
+    { $count ->
+        [one] Deine Feed-Zusammenfassung ist fertig (1 neuer Eintrag)
+       *[other] Deine Feed-Zusammenfassung ist fertig ({ $count } neue Einträge)
+    }
+notify-import-done =
+    { $count ->
+        [one] Chat-Import abgeschlossen (1 Unterhaltung hinzugefügt)
+       *[other] Chat-Import abgeschlossen ({ $count } Unterhaltungen hinzugefügt)
+    }
+notify-backups-done =
+    { $count ->
+        [one] Sicherung abgeschlossen (1 Datei hochgeladen)
+       *[other] Sicherungen abgeschlossen ({ $count } Dateien hochgeladen)
+    }
+notify-backups-failed =
+    { $count ->
+        [one] 1 Sicherung konnte nicht hochgeladen werden
+       *[other] { $count } Sicherungen konnten nicht hochgeladen werden
+    }
+notify-snapshots-done =
+    { $count ->
+        [one] Geplanter Snapshot abgeschlossen (1 Sammlung)
+       *[other] Geplanter Snapshot abgeschlossen ({ $count } Sammlungen)
+    }
+
+## Feeds
+feed-digest-title = Feed-Zusammenfassung
@@ -0,0 +1,89 @@
+## Tray menu
+tray-open-dashboard = Open Dashboard
+tray-settings = Settings…
+tray-profile = Profile
+tray-credentials = API Keys
+tray-layouts = Layouts
+tray-privacy-mode = Privacy Mode
+tray-services = Services
+tray-restart-service = Restart { $service }
+tray-focus-start = Start Focus ({ $minutes } min)
+tray-focus-stop = Stop Focus
+tray-lan-stop = Stop LAN Access ({ $address })
+tray-restart-all = Restart All Services
+tray-copy-diagnostics = Copy Diagnostics
+tray-debug-logging = Debug Logging
+tray-quit = Quit
+
+## Tray tooltip
+tray-tooltip = Tulsbot
+tray-tooltip-status = Tulsbot — { $status }
+tray-tooltip-private = { $tooltip } (privacy mode)
+tray-tooltip-stalled = { $tooltip } (monitoring stalled)
+
+## Health status
+status-healthy = healthy
+status-degraded = degraded
+status-down = down
+
+## Window titles (read by screen readers)
+window-main-title = Tulsbot
+window-popover-title = Tulsbot Chat
+window-pip-title = Tulsbot Response
+
+## Notifications
+notify-title = Tulsbot
+notify-service-down = { $service } is down
+notify-service-up = { $service } recovered
+notify-service-blocked = { $service } is down because { $dependency } is down
+notify-all-down = All services are down
+notify-private-body = New notification (hidden in privacy mode)
+notify-dnd-summary-title = While you were focused
+notify-dnd-summary-down =
+    { $count ->
+        [one] 1 service went down while you were focused
+       *[other] { $count } services went down while you were focused
+    }
+notify-dnd-summary-other =
+    { $count ->
+        [one] 1 notification arrived while you were focused
+       *[other] { $count } notifications arrived while you were focused
+    }
+notify-action-restart = Restart
+notify-action-ignore = Ignore
+notify-action-logs = Open Logs
+notify-budget-warning = { $provider } has used { $percent }% of its monthly budget
+notify-budget-exceeded = { $provider } reached its monthly budget; paid calls are paused. Switch to a local model to keep going.
+notify-chat-probe-failed = Chat isn't working: { $error }
+notify-alert-firing = Alert { $rule }: { $detail }
+notify-alert-resolved = Resolved { $rule }: { $detail }
+notify-focus-done = Focus session finished — time for a break
+notify-focus-done-label = { $label } finished — time for a break
+notify-feed-digest =
+    { $count ->
+        [one] Your feed digest is ready (1 new item)
+       *[other] Your feed digest is ready ({ $count } new items)
+    }
+notify-import-done =
+    { $count ->
+        [one] Chat import finished (1 conversation added)
+       *[other] Chat import finished ({ $count } conversations added)
+    }
+notify-backups-done =
+    { $count ->
+        [one] Backup finished (1 file uploaded)
+       *[other] Backups finished ({ $count } files uploaded)
+    }
+notify-backups-failed =
+    { $count ->
+        [one] 1 backup upload failed
+       *[other] { $count } backup uploads failed
+    }
+notify-snapshots-done =
+    { $count ->
+        [one] Scheduled snapshot finished (1 collection)
+       *[other] Scheduled snapshot finished ({ $count } collections)
+    }
+
+## Feeds
+feed-digest-title = Feed digest
@@ -0,0 +1,89 @@
+## Tray menu
+tray-open-dashboard = Abrir panel
+tray-settings = Ajustes…
+tray-profile = Perfil
+tray-credentials = Claves de API
+tray-layouts = Diseños
+tray-privacy-mode = Modo privado
+tray-services = Servicios
+tray-restart-service = Reiniciar { $service }
+tray-focus-start = Iniciar enfoque ({ $minutes } min)
+tray-focus-stop = Detener enfoque
+tray-lan-stop = Detener acceso LAN ({ $address })
+tray-restart-all = Reiniciar todos los servicios
+tray-copy-diagnostics = Copiar diagnóstico
+tray-debug-logging = Registro de depuración
+tray-quit = Salir
+
+## Tray tooltip
+tray-tooltip = Tulsbot
+tray-tooltip-status = Tulsbot — { $status }
+tray-tooltip-private = { $tooltip } (modo privado)
+tray-tooltip-stalled = { $tooltip } (monitorización detenida)
+
+## Health status
+status-healthy = operativo
+status-degraded = degradado
+status-down = caído
+
+## Window titles (read by screen readers)
+window-main-title = Tulsbot
+window-popover-title = Chat de Tulsbot
+window-pip-title = Respuesta de Tulsbot
+
+## Notifications
+notify-title = Tulsbot
+notify-service-down = { $service } está caído
+notify-service-up = { $service } se ha recuperado
+notify-service-blocked = { $service } no funciona porque { $dependency } no funciona
+notify-all-down = Todos los servicios están caídos
+notify-private-body = Nueva notificación (oculta en modo privado)
+notify-dnd-summary-title = Mientras estabas concentrado
+notify-dnd-summary-down =
+    { $count ->
+        [one] 1 servicio se cayó mientras estabas concentrado
+       *[other] { $count } servicios se cayeron mientras estabas concentrado
+    }
+notify-dnd-summary-other =
+    { $count ->
+        [one] 1 notificación llegó mientras estabas concentrado
+       *[other] { $count } notificaciones llegaron mientras estabas concentrado
+    }
+notify-action-restart = Reiniciar
+notify-action-ignore = Ignorar
+notify-action-logs = Abrir registros
+notify-budget-warning = { $provider } ha usado el { $percent } % de su presupuesto mensual
+notify-budget-exceeded = { $provider } alcanzó su presupuesto mensual; las llamadas de pago están en pausa. Cambia a un modelo local para continuar.
+notify-chat-probe-failed = El chat no funciona: { $error }
+notify-alert-firing = Alerta { $rule }: { $detail }
+notify-alert-resolved = Resuelta { $rule }: { $detail }
+notify-focus-done = Sesión de enfoque terminada: hora de un descanso
+notify-focus-done-label = { $label } terminada: hora de un descanso
+notify-feed-digest =
+    { $count ->
+        [one] Tu resumen de feeds está listo (1 elemento nuevo)
+       *[other] Tu resumen de feeds está listo ({ $count } elementos nuevos)
+    }
+notify-import-done =
+    { $count ->
+        [one] Importación de chats terminada (1 conversación añadida)
+       *[other] Importación de chats terminada ({ $count } conversaciones añadidas)
+    }
+notify-backups-done =
+    { $count ->
+        [one] Copia de seguridad terminada (1 archivo subido)
+       *[other] Copias de seguridad terminadas ({ $count } archivos subidos)
+    }
+notify-backups-failed =
+    { $count ->
+        [one] Falló la subida de 1 copia de seguridad
+       *[other] Falló la subida de { $count } copias de seguridad
+    }
+notify-snapshots-done =
+    { $count ->
+        [one] Instantánea programada terminada (1 colección)
+       *[other] Instantánea programada terminada ({ $count } colecciones)
+    }
+
+## Feeds
+feed-digest-title = Resumen de feeds
@@ -0,0 +1,89 @@
+## Tray menu
+tray-open-dashboard = Ouvrir le tableau de bord
+tray-settings = Réglages…
+tray-profile = Profil
+tray-credentials = Clés d’API
+tray-layouts = Dispositions
+tray-privacy-mode = Mode confidentialité
+tray-services = Services
+tray-restart-service = Redémarrer { $service }
+tray-focus-start = Démarrer une session de concentration ({ $minutes } min)
+tray-focus-stop = Arrêter la concentration
+tray-lan-stop = Arrêter l’accès LAN ({ $address })
+tray-restart-all = Redémarrer tous les services
+tray-copy-diagnostics = Copier le diagnostic
+tray-debug-logging = Journal de débogage
+tray-quit = Quitter
+
+## Tray tooltip
+tray-tooltip = Tulsbot
+tray-tooltip-status = Tulsbot — { $status }
+tray-tooltip-private = { $tooltip } (mode confidentialité)
+tray-tooltip-stalled = { $tooltip } (surveillance bloquée)
+
+## Health status
+status-healthy = opérationnel
+status-degraded = dégradé
+status-down = hors service
+
+## Window titles (read by screen readers)
+window-main-title = Tulsbot
+window-popover-title = Discussion Tulsbot
+window-pip-title = Réponse de Tulsbot
+
+## Notifications
+notify-title = Tulsbot
+notify-service-down = { $service } est hors service
+notify-service-up = { $service } est rétabli
+notify-service-blocked = { $service } est en panne car { $dependency } est en panne
+notify-all-down = Tous les services sont hors service
+notify-private-body = Nouvelle notification (masquée en mode confidentialité)
+notify-dnd-summary-title = Pendant votre concentration
+notify-dnd-summary-down =
+    { $count ->
+        [one] 1 service est tombé pendant votre concentration
+       *[other] { $count } services sont tombés pendant votre concentration
+    }
+notify-dnd-summary-other =
+    { $count ->
+        [one] 1 notification est arrivée pendant votre concentration
+       *[other] { $count } notifications sont arrivées pendant votre concentration
+    }
+notify-action-restart = Redémarrer
+notify-action-ignore = Ignorer
+notify-action-logs = Ouvrir les journaux
+notify-budget-warning = { $provider } a utilisé { $percent } % de son budget mensuel
+notify-budget-exceeded = { $provider } a atteint son budget mensuel ; les appels payants sont suspendus. Passez à un modèle local pour continuer.
+notify-chat-probe-failed = Le chat ne fonctionne pas : { $error }
+notify-alert-firing = Alerte { $rule } : { $detail }
+notify-alert-resolved = Résolue { $rule } : { $detail }
+notify-focus-done = Session de concentration terminée — c'est l'heure de la pause
+notify-focus-done-label = { $label } terminée — c'est l'heure de la pause
+notify-feed-digest =
+    { $count ->
+        [one] Votre résumé des flux est prêt (1 nouvel article)
+       *[other] Votre résumé des flux est prêt ({ $count } nouveaux articles)
+    }
+notify-import-done =
+    { $count ->
+        [one] Import des discussions terminé (1 conversation ajoutée)
+       *[other] Import des discussions terminé ({ $count } conversations ajoutées)
+    }
+notify-backups-done =
+    { $count ->
+        [one] Sauvegarde terminée (1 fichier envoyé)
+       *[other] Sauvegardes terminées ({ $count } fichiers envoyés)
+    }
+notify-backups-failed =
+    { $count ->
+        [one] L'envoi d'une sauvegarde a échoué
+       *[other] L'envoi de { $count } sauvegardes a échoué
+    }
+notify-snapshots-done =
+    { $count ->
+        [one] Instantané planifié terminé (1 collection)
+       *[other] Instantané planifié terminé ({ $count } collections)
+    }
+
+## Feeds
+feed-digest-title = Résumé des flux
@@ -0,0 +1,89 @@
+## Tray menu
+tray-open-dashboard = Abrir painel
+tray-settings = Configurações…
+tray-profile = Perfil
+tray-credentials = Chaves de API
+tray-layouts = Layouts
+tray-privacy-mode = Modo privado
+tray-services = Serviços
+tray-restart-service = Reiniciar { $service }
+tray-focus-start = Iniciar foco ({ $minutes } min)
+tray-focus-stop = Parar foco
+tray-lan-stop = Parar acesso LAN ({ $address })
+tray-restart-all = Reiniciar todos os serviços
+tray-copy-diagnostics = Copiar diagnóstico
+tray-debug-logging = Log de depuração
+tray-quit = Sair
+
+## Tray tooltip
+tray-tooltip = Tulsbot
+tray-tooltip-status = Tulsbot — { $status }
+tray-tooltip-private = { $tooltip } (modo privado)
+tray-tooltip-stalled = { $tooltip } (monitoramento travado)
+
+## Health status
+status-healthy = saudável
+status-degraded = degradado
+status-down = fora do ar
+
+## Window titles (read by screen readers)
+window-main-title = Tulsbot
+window-popover-title = Chat do Tulsbot
+window-pip-title = Resposta do Tulsbot
+
+## Notifications
+notify-title = Tulsbot
+notify-service-down = { $service } está fora do ar
+notify-service-up = { $service } se recuperou
+notify-service-blocked = { $service } está fora do ar porque { $dependency } está fora do ar
+notify-all-down = Todos os serviços estão fora do ar
+notify-private-body = Nova notificação (oculta no modo privado)
+notify-dnd-summary-title = Enquanto você estava concentrado
+notify-dnd-summary-down =
+    { $count ->
+        [one] 1 serviço caiu enquanto você estava concentrado
+       *[other] { $count } serviços caíram enquanto você estava concentrado
+    }
+notify-dnd-summary-other =
+    { $count ->
+        [one] 1 notificação chegou enquanto você estava concentrado
+       *[other] { $count } notificações chegaram enquanto você estava concentrado
+    }
+notify-action-restart = Reiniciar
+notify-action-ignore = Ignorar
+notify-action-logs = Abrir logs
+notify-budget-warning = { $provider } usou { $percent }% do orçamento mensal
+notify-budget-exceeded = { $provider } atingiu o orçamento mensal; chamadas pagas estão pausadas. Mude para um modelo local para continuar.
+notify-chat-probe-failed = O chat não está funcionando: { $error }
+notify-alert-firing = Alerta { $rule }: { $detail }
+notify-alert-resolved = Resolvido { $rule }: { $detail }
+notify-focus-done = Sessão de foco concluída — hora de uma pausa
+notify-focus-done-label = { $label } concluída — hora de uma pausa
+notify-feed-digest =
+    { $count ->
+        [one] Seu resumo dos feeds está pronto (1 item novo)
+       *[other] Seu resumo dos feeds está pronto ({ $count } itens novos)
+    }
+notify-import-done =
+    { $count ->
+        [one] Importação de conversas concluída (1 conversa adicionada)
+       *[other] Importação de conversas concluída ({ $count } conversas adicionadas)
+    }
+notify-backups-done =
+    { $count ->
+        [one] Backup concluído (1 arquivo enviado)
+       *[other] Backups concluídos ({ $count } arquivos enviados)
+    }
+notify-backups-failed =
+    { $count ->
+        [one] Falha ao enviar 1 backup
+       *[other] Falha ao enviar { $count } backups
+    }
+notify-snapshots-done =
+    { $count ->
+        [one] Snapshot agendado concluído (1 coleção)
+       *[other] Snapshot agendado concluído ({ $count } coleções)
+    }
+
+## Feeds
+feed-digest-title = Resumo dos feeds
@@ -0,0 +1,50 @@
+//! `#[instrumented]` runs a Tauri command's body through the app's command
+//! middleware (`crate::middleware`), which times it, turns a panic into an
+//! error response and keeps per-command metrics. `#[instrumented(privileged)]`
+//! also writes the outcome to the audit log, with the `service` argument as
+//! the service when the command has one.
+//!
+//! Put it above `#[tauri::command]`:
+//!
+//! ```ignore
+//! #[instrumented(privileged)]
+//! #[tauri::command]
+//! async fn remove_service(app: AppHandle, service: String) -> Result<(), AppError> { … }
+//! ```
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, parse_quote, FnArg, ItemFn, Pat};
+
+#[proc_macro_attribute]
+pub fn instrumented(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let privileged = match attr.to_string().as_str() {
+        "" => false,
+        "privileged" => true,
+        other => {
+            let message = format!("unknown option `{}`, expected `privileged`", other);
+            return syn::Error::new(Span::call_site(), message).to_compile_error().into();
+        }
+    };
+    let mut function = parse_macro_input!(item as ItemFn);
+    if function.sig.asyncness.is_none() {
+        let message = "#[instrumented] commands must be async";
+        return syn::Error::new_spanned(function.sig.fn_token, message).to_compile_error().into();
+    }
+
+    let name = function.sig.ident.to_string();
+    let body = &function.block;
+    let call = if privileged {
+        let has_service = function.sig.inputs.iter().any(|arg| {
+            matches!(arg, FnArg::Typed(arg)
+                if matches!(&*arg.pat, Pat::Ident(pat) if pat.ident == "service"))
+        });
+        let service = if has_service { quote!(Some(service.clone())) } else { quote!(None) };
+        quote!(crate::middleware::run_privileged(#name, #service, async move #body))
+    } else {
+        quote!(crate::middleware::run(#name, async move #body))
+    };
+    function.block = parse_quote!({ #call.await });
+    quote!(#function).into()
+}
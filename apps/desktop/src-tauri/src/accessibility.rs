@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use tauri::{AppHandle, Emitter, Manager, State, WebviewWindow};
+use tulsbot_macros::instrumented;
+
+use crate::error::AppError;
+use crate::i18n::tr;
+use crate::locks::LockExt;
+use crate::state::AppState;
+
+// ── OS accessibility preferences ────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessibilityPrefs {
+    pub reduced_motion: bool,
+    pub high_contrast: bool,
+    pub reduced_transparency: bool,
+}
+
+fn output(program: &str, args: &[&str]) -> Option<String> {
+    let out = Command::new(program).args(args).output().ok()?;
+    out.status
+        .success()
+        .then(|| String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+/// Read the current preferences. Blocking: shells out to `defaults` /
+/// `reg` / `gsettings`.
+#[cfg(target_os = "macos")]
+pub fn detect() -> AccessibilityPrefs {
+    let flag = |key: &str| {
+        output("defaults", &["read", "com.apple.universalaccess", key]).as_deref() == Some("1")
+    };
+    AccessibilityPrefs {
+        reduced_motion: flag("reduceMotion"),
+        high_contrast: flag("increaseContrast"),
+        reduced_transparency: flag("reduceTransparency"),
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub fn detect() -> AccessibilityPrefs {
+    // `reg query` prints "    <name>    REG_SZ    <value>"
+    let value = |key: &str, name: &str| {
+        output("reg", &["query", key, "/v", name])
+            .and_then(|out| out.split_whitespace().last().map(str::to_string))
+    };
+    let min_animate = value("HKCU\\Control Panel\\Desktop\\WindowMetrics", "MinAnimate");
+    let contrast_flags = value("HKCU\\Control Panel\\Accessibility\\HighContrast", "Flags")
+        .and_then(|flags| flags.parse::<u32>().ok())
+        .unwrap_or(0);
+    let transparency = value(
+        "HKCU\\Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize",
+        "EnableTransparency",
+    );
+    AccessibilityPrefs {
+        reduced_motion: min_animate.as_deref() == Some("0"),
+        // HCF_HIGHCONTRASTON
+        high_contrast: contrast_flags & 0x1 != 0,
+        reduced_transparency: transparency.as_deref() == Some("0x0"),
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn detect() -> AccessibilityPrefs {
+    let gsetting = |schema: &str, key: &str| output("gsettings", &["get", schema, key]);
+    AccessibilityPrefs {
+        reduced_motion: gsetting("org.gnome.desktop.interface", "enable-animations").as_deref()
+            == Some("false"),
+        high_contrast: gsetting("org.gnome.desktop.a11y.interface", "high-contrast").as_deref()
+            == Some("true"),
+        reduced_transparency: false,
+    }
+}
+
+/// OS reduced-motion / high-contrast / reduced-transparency preferences.
+#[instrumented]
+#[tauri::command]
+pub async fn get_accessibility_prefs(
+    state: State<'_, AppState>,
+) -> Result<AccessibilityPrefs, AppError> {
+    let prefs = state.accessibility.lock_or_recover();
+    Ok(*prefs)
+}
+
+/// Undecorated windows still need a title for screen readers.
+pub fn set_accessible_title(app: &AppHandle, window: &WebviewWindow) {
+    let title_id = match window.label() {
+        "chat-popover" => "window-popover-title",
+        "response-pip" => "window-pip-title",
+        _ => "window-main-title",
+    };
+    let _ = window.set_title(&tr(app, title_id));
+}
+
+/// The translucent popover becomes opaque when the user asked for high
+/// contrast or reduced transparency.
+pub fn apply_accessibility_to(app: &AppHandle, window: &WebviewWindow) {
+    if window.label() != "chat-popover" {
+        return;
+    }
+    let Ok(prefs) = app.state::<AppState>().accessibility.lock().map(|p| *p) else {
+        return;
+    };
+    let color = (prefs.high_contrast || prefs.reduced_transparency)
+        .then_some(tauri::window::Color(0, 0, 0, 255));
+    let _ = window.set_background_color(color);
+}
+
+pub async fn refresh_accessibility(app: &AppHandle) {
+    let Ok(prefs) = tauri::async_runtime::spawn_blocking(detect).await else {
+        return;
+    };
+    let state = app.state::<AppState>();
+    let changed = match state.accessibility.lock() {
+        Ok(mut current) if *current != prefs => {
+            *current = prefs;
+            true
+        }
+        _ => false,
+    };
+    if changed {
+        for window in app.webview_windows().values() {
+            apply_accessibility_to(app, window);
+        }
+        let _ = app.emit("accessibility-changed", &prefs);
+    }
+}
@@ -0,0 +1,591 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::Ordering;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tulsbot_macros::instrumented;
+
+use crate::{conversations, focus, settings};
+use crate::error::{AppError, ErrorKind};
+use crate::health::HealthState;
+use crate::locks::LockExt;
+use crate::notifications::{notify, Notice, NoticeKind};
+use crate::profiles::active_data_dir;
+use crate::state::AppState;
+use crate::system::{self, SystemSnapshot};
+
+// ── Alert rules ─────────────────────────────────────────────────────────────
+//
+// Rules over the metrics the app already collects: health polls, external
+// latency probes, the chat probe and system samples. Each source hands its
+// samples to `AlertEngine::evaluate` as one batch; a rule fires once its
+// condition held for `consecutive` batches of its metric in a row and
+// resolves on the first batch where it doesn't. Silences keep a firing
+// alert out of every route without stopping the evaluation, so the alert is
+// still listed and resolves normally.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Metric {
+    /// 1 while a service is down, 0 while it is up. Subject: service.
+    ServiceDown,
+    /// Milliseconds; failed probes are not sampled. Subject: endpoint.
+    ExternalLatency,
+    /// Milliseconds of the last chat probe round trip.
+    ChatProbeLatency,
+    /// 1 when the last chat probe failed, 0 when it succeeded.
+    ChatProbeFailed,
+    /// Percent of the disk in use. Subject: mount point.
+    DiskUsage,
+    /// Percent of memory in use.
+    MemoryUsage,
+    /// Percent of total CPU in use.
+    CpuUsage,
+}
+
+impl Metric {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ServiceDown => "service-down",
+            Self::ExternalLatency => "external-latency",
+            Self::ChatProbeLatency => "chat-probe-latency",
+            Self::ChatProbeFailed => "chat-probe-failed",
+            Self::DiskUsage => "disk-usage",
+            Self::MemoryUsage => "memory-usage",
+            Self::CpuUsage => "cpu-usage",
+        }
+    }
+
+    /// Sampled by the system sampler rather than by a probe of our own.
+    pub fn is_system(self) -> bool {
+        matches!(self, Self::DiskUsage | Self::MemoryUsage | Self::CpuUsage)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Comparison {
+    Above,
+    Below,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    /// Notifications break through Do Not Disturb.
+    Critical,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Route {
+    Notification,
+    /// POST the alert event as JSON.
+    Webhook { url: String },
+    /// Count the alert in the tray's badge while it fires.
+    TrayBadge,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub name: String,
+    pub metric: Metric,
+    /// Only this service, endpoint or mount point; `None` matches any.
+    #[serde(default)]
+    pub subject: Option<String>,
+    pub comparison: Comparison,
+    pub threshold: f64,
+    /// Batches in a row the condition must hold before firing (default 1).
+    #[serde(default)]
+    pub consecutive: Option<u32>,
+    pub severity: Severity,
+    pub routes: Vec<Route>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Mutes one rule, or every rule, until `ends_at`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Silence {
+    pub id: String,
+    /// `None` silences every rule.
+    pub rule: Option<String>,
+    /// Unix seconds.
+    pub starts_at: u64,
+    pub ends_at: u64,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+impl Silence {
+    pub fn covers(&self, rule: &str, now: u64) -> bool {
+        (self.starts_at..self.ends_at).contains(&now)
+            && self.rule.as_deref().is_none_or(|r| r == rule)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertSettings {
+    pub rules: Vec<AlertRule>,
+    pub silences: Vec<Silence>,
+}
+
+impl AlertSettings {
+    /// Whether any enabled rule needs the system sampler.
+    pub fn needs_system(&self) -> bool {
+        self.rules.iter().any(|r| r.enabled && r.metric.is_system())
+    }
+
+    /// Forget silences that have ended.
+    pub fn prune(&mut self, now: u64) {
+        self.silences.retain(|s| s.ends_at > now);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub metric: Metric,
+    pub subject: Option<String>,
+    pub value: f64,
+}
+
+impl Sample {
+    pub fn new(metric: Metric, subject: Option<&str>, value: f64) -> Self {
+        Self { metric, subject: subject.map(String::from), value }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    pub rule: String,
+    pub metric: Metric,
+    pub subject: Option<String>,
+    pub severity: Severity,
+    /// Latest value and the threshold it crossed.
+    pub value: f64,
+    pub threshold: f64,
+    /// Unix seconds it started firing.
+    pub since: u64,
+    pub silenced: bool,
+}
+
+impl Alert {
+    /// One line for notifications: `external-latency (openai) 2300 > 2000`
+    /// while firing, just the current value once resolved.
+    pub fn detail(&self, comparison: Comparison, state: AlertState) -> String {
+        let subject = self.subject.as_deref().map(|s| format!(" ({})", s)).unwrap_or_default();
+        let line = format!("{}{} {}", self.metric.as_str(), subject, round(self.value));
+        let op = match comparison {
+            Comparison::Above => '>',
+            Comparison::Below => '<',
+        };
+        match state {
+            AlertState::Firing => format!("{} {} {}", line, op, round(self.threshold)),
+            AlertState::Resolved => line,
+        }
+    }
+}
+
+fn round(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertState {
+    Firing,
+    Resolved,
+}
+
+/// A state change, handed to the routes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertEvent {
+    pub state: AlertState,
+    pub alert: Alert,
+    pub detail: String,
+}
+
+type Key = (String, Option<String>);
+
+/// Streaks and firing alerts, kept in memory for the running app.
+#[derive(Debug, Default)]
+pub struct AlertEngine {
+    streaks: HashMap<Key, u32>,
+    firing: BTreeMap<Key, Alert>,
+}
+
+impl AlertEngine {
+    pub fn firing(&self) -> Vec<Alert> {
+        self.firing.values().cloned().collect()
+    }
+
+    /// Firing, unsilenced alerts that route to the tray badge.
+    pub fn badge_count(&self, rules: &[AlertRule]) -> usize {
+        self.firing
+            .values()
+            .filter(|a| !a.silenced)
+            .filter(|a| {
+                rules.iter().any(|r| r.name == a.rule && r.routes.contains(&Route::TrayBadge))
+            })
+            .count()
+    }
+
+    /// Re-check which firing alerts are silenced, after silences changed.
+    pub fn refresh_silences(&mut self, silences: &[Silence], now: u64) {
+        for alert in self.firing.values_mut() {
+            alert.silenced = silences.iter().any(|s| s.covers(&alert.rule, now));
+        }
+    }
+
+    /// Drop the state of rules that were removed or changed.
+    pub fn forget(&mut self, rule: &str) {
+        self.streaks.retain(|(r, _), _| r != rule);
+        self.firing.retain(|(r, _), _| r != rule);
+    }
+
+    /// Evaluate one batch from a source. Rules whose metric isn't in the
+    /// batch keep their state. Returns the alerts that started firing or
+    /// resolved; silenced ones are included with `silenced` set.
+    pub fn evaluate(
+        &mut self,
+        settings: &AlertSettings,
+        samples: &[Sample],
+        now: u64,
+    ) -> Vec<AlertEvent> {
+        let mut events = Vec::new();
+        for rule in settings.rules.iter().filter(|r| r.enabled) {
+            let silenced = settings.silences.iter().any(|s| s.covers(&rule.name, now));
+            let matching = samples.iter().filter(|s| {
+                s.metric == rule.metric
+                    && rule.subject.as_ref().is_none_or(|want| s.subject.as_ref() == Some(want))
+            });
+            for sample in matching {
+                let key = (rule.name.clone(), sample.subject.clone());
+                let breached = match rule.comparison {
+                    Comparison::Above => sample.value > rule.threshold,
+                    Comparison::Below => sample.value < rule.threshold,
+                };
+                if !breached {
+                    self.streaks.remove(&key);
+                    if let Some(mut alert) = self.firing.remove(&key) {
+                        alert.value = sample.value;
+                        alert.silenced = silenced;
+                        let detail = alert.detail(rule.comparison, AlertState::Resolved);
+                        events.push(AlertEvent { state: AlertState::Resolved, alert, detail });
+                    }
+                    continue;
+                }
+                let streak = self.streaks.entry(key.clone()).or_insert(0);
+                *streak = streak.saturating_add(1);
+                if let Some(alert) = self.firing.get_mut(&key) {
+                    alert.value = sample.value;
+                    alert.silenced = silenced;
+                } else if *streak >= rule.consecutive.unwrap_or(1).max(1) {
+                    let alert = Alert {
+                        rule: rule.name.clone(),
+                        metric: rule.metric,
+                        subject: sample.subject.clone(),
+                        severity: rule.severity,
+                        value: sample.value,
+                        threshold: rule.threshold,
+                        since: now,
+                        silenced,
+                    };
+                    self.firing.insert(key, alert.clone());
+                    let detail = alert.detail(rule.comparison, AlertState::Firing);
+                    events.push(AlertEvent { state: AlertState::Firing, alert, detail });
+                }
+            }
+        }
+        events
+    }
+}
+
+pub fn validate(rule: &AlertRule) -> Result<(), String> {
+    if rule.name.trim().is_empty() {
+        return Err("Rule name is empty".into());
+    }
+    if !rule.threshold.is_finite() {
+        return Err("Threshold must be a number".into());
+    }
+    for route in &rule.routes {
+        if let Route::Webhook { url } = route {
+            if !url.starts_with("https://") && !url.starts_with("http://") {
+                return Err("Webhook URLs must be http(s)://".into());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// POST `event` to a webhook route.
+pub async fn send_webhook(url: &str, event: &AlertEvent) -> Result<(), String> {
+    let resp = reqwest::Client::new()
+        .post(url)
+        .json(event)
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("HTTP {}", resp.status().as_u16()));
+    }
+    Ok(())
+}
+
+/// One `service-down` sample per monitored service.
+pub fn service_samples(health: &HealthState) -> Vec<Sample> {
+    health
+        .services
+        .iter()
+        .map(|s| Sample::new(Metric::ServiceDown, Some(&s.name), f64::from(u8::from(!s.healthy))))
+        .collect()
+}
+
+/// Disk, memory and CPU usage from a system snapshot.
+pub fn system_samples(snapshot: &SystemSnapshot) -> Vec<Sample> {
+    let percent = |used: f64, total: f64| used / total * 100.0;
+    let mut samples: Vec<Sample> = snapshot
+        .disks
+        .iter()
+        .filter(|d| d.total_gb > 0.0)
+        .map(|d| {
+            let used = percent(d.total_gb - d.available_gb, d.total_gb);
+            Sample::new(Metric::DiskUsage, Some(&d.mount_point), used)
+        })
+        .collect();
+    if snapshot.memory_total_mb > 0 {
+        let used = percent(snapshot.memory_used_mb as f64, snapshot.memory_total_mb as f64);
+        samples.push(Sample::new(Metric::MemoryUsage, None, used));
+    }
+    samples.push(Sample::new(Metric::CpuUsage, None, f64::from(snapshot.cpu_percent)));
+    samples
+}
+
+/// Show the number of badge-routed alerts next to the tray icon.
+pub fn set_tray_badge(app: &AppHandle, count: usize) {
+    app.state::<AppState>().alert_badge.store(count, Ordering::SeqCst);
+    refresh_tray_title(app);
+}
+
+/// The tray title: a focus session's countdown and the alert badge.
+pub fn refresh_tray_title(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let badge = state.alert_badge.load(Ordering::SeqCst);
+    let remaining = state
+        .focus
+        .lock_or_recover()
+        .as_ref()
+        .map(|session| session.remaining(conversations::now()));
+    let mut parts = Vec::new();
+    if state.lan.lock_or_recover().is_some() {
+        parts.push("LAN".to_string());
+    }
+    parts.extend(remaining.map(focus::countdown));
+    if badge > 0 {
+        parts.push(badge.to_string());
+    }
+    let title = (!parts.is_empty()).then(|| parts.join(" · "));
+    if let Some(tray) = app.tray_by_id("main-tray") {
+        let _ = tray.set_title(title.as_deref());
+    }
+}
+
+pub fn alert_notice(app: &AppHandle, event: &AlertEvent) -> Option<Notice> {
+    let state = app.state::<AppState>();
+    let i18n = state.i18n.lock().ok()?;
+    let (kind, id) = match (event.state, event.alert.severity) {
+        (AlertState::Firing, Severity::Critical) => (NoticeKind::Critical, "notify-alert-firing"),
+        (AlertState::Firing, _) => (NoticeKind::Info, "notify-alert-firing"),
+        (AlertState::Resolved, _) => (NoticeKind::Info, "notify-alert-resolved"),
+    };
+    Some(Notice {
+        kind,
+        title: i18n.t("notify-title"),
+        body: i18n.t_args(id, &[("rule", &event.alert.rule), ("detail", &event.detail)]),
+        service: None,
+        actions: Vec::new(),
+    })
+}
+
+/// Update the tray badge and emit `alerts-changed` with the firing alerts.
+pub fn publish_alerts(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let rules = state.settings.lock().map(|s| s.alerts.rules.clone()).unwrap_or_default();
+    let (badge, firing) = {
+        let engine = state.alerts.lock_or_recover();
+        (engine.badge_count(&rules), engine.firing())
+    };
+    set_tray_badge(app, badge);
+    let _ = app.emit("alerts-changed", &firing);
+}
+
+/// Evaluate one batch of samples against the alert rules and route the
+/// alerts that started firing or resolved.
+pub fn evaluate_alerts(app: &AppHandle, samples: Vec<Sample>) {
+    let state = app.state::<AppState>();
+    let Ok(settings) = state.settings.lock().map(|s| s.alerts.clone()) else {
+        return;
+    };
+    if settings.rules.is_empty() {
+        return;
+    }
+    let events = state.alerts.lock_or_recover().evaluate(&settings, &samples, conversations::now());
+    if events.is_empty() {
+        return;
+    }
+    publish_alerts(app);
+    for event in events.into_iter().filter(|e| !e.alert.silenced) {
+        let Some(rule) = settings.rules.iter().find(|r| r.name == event.alert.rule) else {
+            continue;
+        };
+        for route in &rule.routes {
+            match route {
+                Route::Notification => {
+                    if let Some(notice) = alert_notice(app, &event) {
+                        notify(app, notice);
+                    }
+                }
+                Route::Webhook { url } => {
+                    let (url, event) = (url.clone(), event.clone());
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = send_webhook(&url, &event).await {
+                            eprintln!("[tulsbot] Alert webhook {} failed: {}", url, e);
+                        }
+                    });
+                }
+                Route::TrayBadge => {}
+            }
+        }
+    }
+}
+
+/// Sample the system for the alert rules that need it.
+pub async fn sample_system_for_alerts(app: &AppHandle) {
+    let needed = app
+        .state::<AppState>()
+        .settings
+        .lock()
+        .map(|s| s.alerts.needs_system())
+        .unwrap_or(false);
+    if !needed {
+        return;
+    }
+    if let Ok(snapshot) = tauri::async_runtime::spawn_blocking(system::snapshot).await {
+        evaluate_alerts(app, system_samples(&snapshot));
+    }
+}
+
+pub fn update_alert_settings(
+    app: &AppHandle,
+    change: impl FnOnce(&mut AlertSettings),
+) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        settings.alerts.prune(conversations::now());
+        change(&mut settings.alerts);
+        settings.clone()
+    };
+    settings::save(&active_data_dir(app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    Ok(())
+}
+
+/// Alerts firing right now, silenced ones included.
+#[instrumented]
+#[tauri::command]
+pub async fn get_alerts(state: State<'_, AppState>) -> Result<Vec<Alert>, AppError> {
+    Ok(state.alerts.lock_or_recover().firing())
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn list_alert_rules(state: State<'_, AppState>) -> Result<AlertSettings, AppError> {
+    let mut alerts = state.settings.lock_or_recover().alerts.clone();
+    alerts.prune(conversations::now());
+    Ok(alerts)
+}
+
+/// Add a rule, or replace the one with the same name. A replaced rule
+/// starts over: its streaks and firing alerts are dropped.
+#[instrumented]
+#[tauri::command]
+pub async fn save_alert_rule(app: AppHandle, rule: AlertRule) -> Result<(), AppError> {
+    validate(&rule)?;
+    let name = rule.name.clone();
+    update_alert_settings(&app, |alerts| {
+        match alerts.rules.iter_mut().find(|r| r.name == rule.name) {
+            Some(existing) => *existing = rule,
+            None => alerts.rules.push(rule),
+        }
+    })?;
+    app.state::<AppState>().alerts.lock_or_recover().forget(&name);
+    publish_alerts(&app);
+    Ok(())
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn delete_alert_rule(app: AppHandle, name: String) -> Result<(), AppError> {
+    update_alert_settings(&app, |alerts| {
+        alerts.rules.retain(|r| r.name != name);
+        alerts.silences.retain(|s| s.rule.as_ref() != Some(&name));
+    })?;
+    app.state::<AppState>().alerts.lock_or_recover().forget(&name);
+    publish_alerts(&app);
+    Ok(())
+}
+
+/// Silence rule `rule` (every rule when `None`) for `minutes` from now.
+#[instrumented]
+#[tauri::command]
+pub async fn silence_alerts(
+    app: AppHandle,
+    rule: Option<String>,
+    minutes: u32,
+    comment: Option<String>,
+) -> Result<Silence, AppError> {
+    if minutes == 0 {
+        return Err(AppError::new(ErrorKind::InvalidInput, "A silence must last at least a minute"));
+    }
+    let now = conversations::now();
+    let silence = Silence {
+        id: conversations::new_id(),
+        rule,
+        starts_at: now,
+        ends_at: now + u64::from(minutes) * 60,
+        comment: comment.filter(|c| !c.trim().is_empty()),
+    };
+    let added = silence.clone();
+    let mut silences = Vec::new();
+    update_alert_settings(&app, |alerts| {
+        alerts.silences.push(added);
+        silences = alerts.silences.clone();
+    })?;
+    let state = app.state::<AppState>();
+    state.alerts.lock_or_recover().refresh_silences(&silences, now);
+    publish_alerts(&app);
+    Ok(silence)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn remove_silence(app: AppHandle, id: String) -> Result<(), AppError> {
+    let mut silences = Vec::new();
+    update_alert_settings(&app, |alerts| {
+        alerts.silences.retain(|s| s.id != id);
+        silences = alerts.silences.clone();
+    })?;
+    let state = app.state::<AppState>();
+    let now = conversations::now();
+    state.alerts.lock_or_recover().refresh_silences(&silences, now);
+    publish_alerts(&app);
+    Ok(())
+}
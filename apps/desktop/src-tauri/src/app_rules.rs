@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+use crate::context::ActiveContext;
+use crate::conversations::ConversationConfig;
+
+// ── Per-application rules ───────────────────────────────────────────────────
+//
+// "When the frontmost app is Xcode, use the coding prompt and the local
+// model." The active-context watcher picks the first rule matching the app
+// the user is in; its provider settings sit between the profile defaults and
+// a conversation's own overrides, and its system prompt replaces the
+// configured one. Rules follow the frontmost app, so they only switch while
+// `track_active_context` is on or the popover asks for the context.
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppRule {
+    pub name: String,
+    /// App name as the OS reports it, compared case-insensitively.
+    pub app: String,
+    /// Only while the window title contains this, e.g. a project name.
+    pub window_contains: Option<String>,
+    /// Provider, model, temperature and credential while the rule applies.
+    pub config: ConversationConfig,
+    /// Used over the profile's default system prompt while the rule applies;
+    /// a prompt picked for the conversation still wins.
+    pub system_prompt: Option<String>,
+    pub disabled: bool,
+}
+
+impl AppRule {
+    pub fn matches(&self, context: &ActiveContext) -> bool {
+        let title = context.window_title.as_deref().unwrap_or_default().to_lowercase();
+        !self.disabled
+            && self.app.trim().eq_ignore_ascii_case(context.app_name.trim())
+            && self
+                .window_contains
+                .as_deref()
+                .is_none_or(|part| title.contains(&part.to_lowercase()))
+    }
+}
+
+/// The first rule for the app in `context`.
+pub fn matching<'a>(rules: &'a [AppRule], context: &ActiveContext) -> Option<&'a AppRule> {
+    rules.iter().find(|rule| rule.matches(context))
+}
+
+pub fn validate(rules: &[AppRule]) -> Result<(), String> {
+    for rule in rules {
+        if rule.app.trim().is_empty() {
+            return Err(format!("Rule '{}' names no application", rule.name));
+        }
+        crate::conversations::validate_config(&rule.config)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+
+use crate::locks::{LockExt, LOGS};
+
+// ── Audit log (actions taken on services) ───────────────────────────────────
+//
+// Append-only `audit.jsonl` in the profile's data dir. Records what was done
+// to services by the user or automatically, and whether it worked.
+
+pub const FILE_NAME: &str = "audit.jsonl";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Unix seconds.
+    pub at: u64,
+    /// `user` or `remediation`.
+    pub actor: String,
+    /// e.g. `restart`, `remove-file`, `script`.
+    pub action: String,
+    pub service: Option<String>,
+    pub ok: bool,
+    /// Error message or command output, shortened.
+    pub detail: Option<String>,
+}
+
+/// Characters of `detail` kept per entry.
+const DETAIL_LIMIT: usize = 2000;
+
+pub fn record(dir: &Path, mut entry: AuditEntry) {
+    if let Some(detail) = &entry.detail {
+        if detail.chars().count() > DETAIL_LIMIT {
+            entry.detail = Some(detail.chars().take(DETAIL_LIMIT).collect::<String>() + "…");
+        }
+    }
+    let line = match serde_json::to_string(&entry) {
+        Ok(line) => line + "\n",
+        Err(_) => return,
+    };
+    let _appending = LOGS.lock_or_recover();
+    let appended = std::fs::create_dir_all(dir).and_then(|_| {
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(FILE_NAME))?
+            .write_all(line.as_bytes())
+    });
+    if let Err(e) = appended {
+        eprintln!("[tulsbot] Failed to write audit log: {}", e);
+    }
+}
+
+/// The most recent `limit` entries, newest first.
+pub fn recent(dir: &Path, limit: usize) -> Vec<AuditEntry> {
+    let text = std::fs::read_to_string(dir.join(FILE_NAME)).unwrap_or_default();
+    text.lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .take(limit)
+        .collect()
+}
@@ -0,0 +1,699 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use reqwest::{Client, Method, Response, Url};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::io::AsyncReadExt;
+use tulsbot_macros::instrumented;
+
+use crate::{conversations, keychain, settings, users};
+use crate::env::active_profile_name;
+use crate::error::{AppError, ErrorKind};
+use crate::locks::LockExt;
+use crate::profiles::active_data_dir;
+use crate::qdrant::{self, qdrant_target, SnapshotFile};
+use crate::relay::{relay_info, RelayCategory};
+use crate::state::AppState;
+use crate::tray::begin_work;
+
+// ── Off-machine backup destinations ─────────────────────────────────────────
+//
+// Snapshots are first written locally (see `qdrant`); each enabled target
+// then gets a copy under `<key prefix>/<collection>/<file>`. Targets are a
+// local directory (a NAS mount, an external disk), a WebDAV collection or an
+// S3-compatible bucket. Passwords and S3 secret keys live in the keychain,
+// never in settings. Every upload is checked afterwards by its SHA256: local
+// and WebDAV copies are read back, S3 reports the checksum it verified.
+
+/// Files larger than this go to S3 as a multipart upload.
+const MULTIPART_THRESHOLD: u64 = 64 * 1024 * 1024;
+/// S3 parts must be at least 5 MiB; 16 MiB allows 160 GiB in 10 000 parts.
+const PART_SIZE: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Destination {
+    Local {
+        path: PathBuf,
+    },
+    Webdav {
+        /// Collection URL the backups go under.
+        url: String,
+        username: Option<String>,
+    },
+    S3 {
+        /// e.g. `https://s3.eu-west-1.amazonaws.com` or a MinIO URL.
+        endpoint: String,
+        region: String,
+        bucket: String,
+        access_key_id: String,
+        /// `endpoint/bucket/key` instead of `bucket.endpoint/key`; most
+        /// self-hosted S3 servers need this.
+        #[serde(default)]
+        path_style: bool,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupTarget {
+    pub id: String,
+    pub name: String,
+    pub destination: Destination,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupUpload {
+    /// Target id.
+    pub target: String,
+    pub key: String,
+    pub size: u64,
+    pub multipart: bool,
+    pub error: Option<String>,
+}
+
+/// Keychain account of the password / secret key of target `id`.
+pub fn keychain_account(profile: &str, id: &str) -> String {
+    format!("backup:{}:{}", profile, id)
+}
+
+pub fn validate(target: &BackupTarget) -> Result<(), String> {
+    let valid_id = !target.id.is_empty()
+        && target.id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+    if !valid_id {
+        return Err(format!("Invalid backup target id: {}", target.id));
+    }
+    match &target.destination {
+        Destination::Local { path } if !path.is_absolute() => {
+            Err("Backup directory must be an absolute path".into())
+        }
+        Destination::Webdav { url, .. } => parse_url(url).map(|_| ()),
+        Destination::S3 { endpoint, region, bucket, access_key_id, .. } => {
+            parse_url(endpoint)?;
+            if region.is_empty() || bucket.is_empty() || access_key_id.is_empty() {
+                return Err("S3 targets need a region, bucket and access key id".into());
+            }
+            Ok(())
+        }
+        Destination::Local { .. } => Ok(()),
+    }
+}
+
+fn parse_url(url: &str) -> Result<Url, String> {
+    let parsed = Url::parse(url).map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+    if !matches!(parsed.scheme(), "https" | "http") {
+        return Err(format!("Not an http(s) URL: {}", url));
+    }
+    Ok(parsed)
+}
+
+async fn check(resp: Response) -> Result<Response, String> {
+    let status = resp.status();
+    if status.is_success() {
+        Ok(resp)
+    } else {
+        let text = resp.text().await.unwrap_or_default();
+        Err(format!("HTTP {}: {}", status.as_u16(), text.trim()))
+    }
+}
+
+fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).map_err(|e| e.to_string())?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Copy `file` to `target` as `key` and verify the copy. `secret` is the
+/// keychain entry of remote targets.
+pub async fn upload(
+    target: &BackupTarget,
+    secret: Option<&str>,
+    file: &Path,
+    key: &str,
+) -> Result<BackupUpload, String> {
+    let size = tokio::fs::metadata(file).await.map_err(|e| e.to_string())?.len();
+    let multipart = match &target.destination {
+        Destination::Local { path } => {
+            upload_local(path, file, key).await?;
+            false
+        }
+        Destination::Webdav { url, username } => {
+            webdav_upload(url, username.as_deref(), secret, file, key, size).await?;
+            false
+        }
+        Destination::S3 { .. } => {
+            let s3 = S3::new(&target.destination, secret)?;
+            s3.upload(file, key, size).await?
+        }
+    };
+    Ok(BackupUpload {
+        target: target.id.clone(),
+        key: key.to_string(),
+        size,
+        multipart,
+        error: None,
+    })
+}
+
+/// Delete `key` from `target`; used to clean up after a connection test.
+pub async fn remove(target: &BackupTarget, secret: Option<&str>, key: &str) -> Result<(), String> {
+    match &target.destination {
+        Destination::Local { path } => {
+            tokio::fs::remove_file(path.join(key)).await.map_err(|e| e.to_string())
+        }
+        Destination::Webdav { url, username } => {
+            let client = Client::new();
+            let resp = webdav(&client, Method::DELETE, url, key, username.as_deref(), secret)?
+                .send()
+                .await
+                .map_err(|e| e.to_string())?;
+            check(resp).await.map(|_| ())
+        }
+        Destination::S3 { .. } => {
+            let s3 = S3::new(&target.destination, secret)?;
+            s3.send(Method::DELETE, key, &[], &[], Vec::new()).await.map(|_| ())
+        }
+    }
+}
+
+pub fn backup_target(app: &AppHandle, id: &str) -> Result<BackupTarget, String> {
+    let state = app.state::<AppState>();
+    let settings = state.settings.lock_or_recover();
+    settings
+        .backup_targets
+        .iter()
+        .find(|t| t.id == id)
+        .cloned()
+        .ok_or_else(|| format!("Unknown backup target: {}", id))
+}
+
+pub async fn backup_secret(
+    app: &AppHandle,
+    target: &BackupTarget,
+) -> Result<Option<String>, String> {
+    if matches!(target.destination, Destination::Local { .. }) {
+        return Ok(None);
+    }
+    let account = keychain_account(&active_profile_name(app)?, &target.id);
+    tauri::async_runtime::spawn_blocking(move || keychain::get(&account))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Copy fresh snapshots to every enabled target and emit `backups-uploaded`.
+/// Failures are reported per file and target, not returned.
+pub async fn upload_backups(app: &AppHandle, snapshots: &[SnapshotFile]) -> Vec<BackupUpload> {
+    let targets: Vec<BackupTarget> = app
+        .state::<AppState>()
+        .settings
+        .lock_or_recover()
+        .backup_targets
+        .iter()
+        .filter(|t| t.enabled)
+        .cloned()
+        .collect();
+    let _work = (!targets.is_empty()).then(|| begin_work(app));
+    let mut uploads = Vec::new();
+    for target in &targets {
+        let secret = backup_secret(app, target).await;
+        for snapshot in snapshots {
+            let key = format!("qdrant/{}/{}", snapshot.collection, snapshot.name);
+            let result = match &secret {
+                Ok(secret) => upload(target, secret.as_deref(), &snapshot.path, &key).await,
+                Err(e) => Err(e.clone()),
+            };
+            uploads.push(result.unwrap_or_else(|e| {
+                eprintln!("[tulsbot] Backup of {} to {} failed: {}", key, target.name, e);
+                BackupUpload {
+                    target: target.id.clone(),
+                    key,
+                    size: snapshot.size,
+                    multipart: false,
+                    error: Some(e),
+                }
+            }));
+        }
+    }
+    if !uploads.is_empty() {
+        let _ = app.emit("backups-uploaded", &uploads);
+        let failed = uploads.iter().filter(|u| u.error.is_some()).count();
+        let state = app.state::<AppState>();
+        let body = {
+            let i18n = state.i18n.lock_or_recover();
+            if failed > 0 {
+                i18n.t_count("notify-backups-failed", failed)
+            } else {
+                i18n.t_count("notify-backups-done", uploads.len())
+            }
+        };
+        relay_info(app, RelayCategory::Jobs, body);
+    }
+    uploads
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn list_backup_targets(
+    state: State<'_, AppState>,
+) -> Result<Vec<BackupTarget>, AppError> {
+    Ok(state.settings.lock_or_recover().backup_targets.clone())
+}
+
+/// Add or replace a backup target. `secret` (WebDAV password or S3 secret
+/// access key) goes to the keychain; `None` keeps the stored one.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn save_backup_target(
+    app: AppHandle,
+    target: BackupTarget,
+    secret: Option<String>,
+) -> Result<(), AppError> {
+    validate(&target)?;
+    if let Some(secret) = secret {
+        let account = keychain_account(&active_profile_name(&app)?, &target.id);
+        tauri::async_runtime::spawn_blocking(move || keychain::set(&account, &secret))
+            .await
+            .map_err(|e| e.to_string())??;
+    }
+    let settings = {
+        let state = app.state::<AppState>();
+        let mut settings = state.settings.lock_or_recover();
+        match settings.backup_targets.iter_mut().find(|t| t.id == target.id) {
+            Some(existing) => *existing = target,
+            None => settings.backup_targets.push(target),
+        }
+        settings.clone()
+    };
+    settings::save(&active_data_dir(&app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    Ok(())
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn remove_backup_target(app: AppHandle, id: String) -> Result<(), AppError> {
+    let account = keychain_account(&active_profile_name(&app)?, &id);
+    tauri::async_runtime::spawn_blocking(move || keychain::delete(&account))
+        .await
+        .map_err(|e| e.to_string())??;
+    let settings = {
+        let state = app.state::<AppState>();
+        let mut settings = state.settings.lock_or_recover();
+        settings.backup_targets.retain(|t| t.id != id);
+        settings.clone()
+    };
+    settings::save(&active_data_dir(&app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    Ok(())
+}
+
+/// Upload a small probe file to target `id`, verify it and delete it again.
+#[instrumented]
+#[tauri::command]
+pub async fn test_backup_target(app: AppHandle, id: String) -> Result<BackupUpload, AppError> {
+    let target = backup_target(&app, &id)?;
+    let secret = backup_secret(&app, &target).await?;
+    let probe = users::scratch_dir()?.join(format!("backup-probe-{}", conversations::new_id()));
+    std::fs::write(&probe, b"tulsbot backup probe\n").map_err(|e| e.to_string())?;
+    let key = "tulsbot-probe.txt";
+    let result = upload(&target, secret.as_deref(), &probe, key).await;
+    let _ = std::fs::remove_file(&probe);
+    let upload = result?;
+    remove(&target, secret.as_deref(), key).await?;
+    Ok(upload)
+}
+
+/// Copy existing local snapshots to every enabled target.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn upload_backup(
+    app: AppHandle,
+    files: Vec<String>,
+) -> Result<Vec<BackupUpload>, AppError> {
+    let (_, dir) = qdrant_target(&app)?;
+    let snapshots: Vec<SnapshotFile> = qdrant::list(&dir)
+        .into_iter()
+        .filter(|s| files.iter().any(|f| std::path::Path::new(f) == s.path))
+        .collect();
+    if snapshots.is_empty() {
+        return Err(AppError::new(ErrorKind::NotFound, "No matching local snapshots"));
+    }
+    Ok(upload_backups(&app, &snapshots).await)
+}
+
+// ── Local directory ─────────────────────────────────────────────────────────
+
+async fn upload_local(dir: &Path, file: &Path, key: &str) -> Result<(), String> {
+    let dest = dir.join(key);
+    if let Some(parent) = dest.parent() {
+        tokio::fs::create_dir_all(parent).await.map_err(|e| e.to_string())?;
+    }
+    tokio::fs::copy(file, &dest).await.map_err(|e| e.to_string())?;
+    let (source, copy) = (file.to_path_buf(), dest.clone());
+    let same = tokio::task::spawn_blocking(move || {
+        Ok::<_, String>(sha256_file(&source)? == sha256_file(&copy)?)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    if !same {
+        let _ = tokio::fs::remove_file(&dest).await;
+        return Err(format!("Verification failed: {} differs from the source", dest.display()));
+    }
+    Ok(())
+}
+
+// ── WebDAV ──────────────────────────────────────────────────────────────────
+
+fn webdav_url(base: &str, key: &str) -> String {
+    format!("{}/{}", base.trim_end_matches('/'), key)
+}
+
+fn webdav(
+    client: &Client,
+    method: Method,
+    base: &str,
+    key: &str,
+    username: Option<&str>,
+    password: Option<&str>,
+) -> Result<reqwest::RequestBuilder, String> {
+    let url = parse_url(&webdav_url(base, key))?;
+    let request = client.request(method, url);
+    Ok(match username {
+        Some(user) => request.basic_auth(user, password),
+        None => request,
+    })
+}
+
+async fn webdav_upload(
+    base: &str,
+    username: Option<&str>,
+    password: Option<&str>,
+    file: &Path,
+    key: &str,
+    size: u64,
+) -> Result<(), String> {
+    let client = Client::new();
+    let mkcol = Method::from_bytes(b"MKCOL").map_err(|e| e.to_string())?;
+    let segments: Vec<&str> = key.split('/').collect();
+    for depth in 1..segments.len() {
+        let collection = segments[..depth].join("/");
+        let resp = webdav(&client, mkcol.clone(), base, &collection, username, password)?
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        // 405: the collection already exists
+        if !resp.status().is_success() && resp.status().as_u16() != 405 {
+            return Err(format!("Could not create {}: HTTP {}", collection, resp.status()));
+        }
+    }
+
+    let body = tokio::fs::File::open(file).await.map_err(|e| e.to_string())?;
+    let resp = webdav(&client, Method::PUT, base, key, username, password)?
+        .header(reqwest::header::CONTENT_LENGTH, size)
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    check(resp).await?;
+
+    // WebDAV has no standard way to ask for a checksum: read the file back
+    let source = file.to_path_buf();
+    let expected = tokio::task::spawn_blocking(move || sha256_file(&source))
+        .await
+        .map_err(|e| e.to_string())??;
+    let resp = webdav(&client, Method::GET, base, key, username, password)?
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut resp = check(resp).await?;
+    let mut hasher = Sha256::new();
+    while let Some(chunk) = resp.chunk().await.map_err(|e| e.to_string())? {
+        hasher.update(&chunk);
+    }
+    let stored = hex::encode(hasher.finalize());
+    if stored != expected {
+        return Err(format!(
+            "Verification failed: server has SHA256 {}, expected {}",
+            stored, expected
+        ));
+    }
+    Ok(())
+}
+
+// ── S3 (Signature Version 4) ────────────────────────────────────────────────
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, key);
+    ring::hmac::sign(&key, data).as_ref().to_vec()
+}
+
+/// RFC 3986 encoding as SigV4 wants it; `/` is kept in paths.
+fn uri_encode(value: &str, keep_slash: bool) -> String {
+    let mut out = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            b'/' if keep_slash => out.push('/'),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+struct S3 {
+    endpoint: Url,
+    region: String,
+    bucket: String,
+    access_key_id: String,
+    secret: String,
+    path_style: bool,
+    client: Client,
+}
+
+impl S3 {
+    fn new(destination: &Destination, secret: Option<&str>) -> Result<Self, String> {
+        let Destination::S3 { endpoint, region, bucket, access_key_id, path_style } = destination
+        else {
+            return Err("Not an S3 target".into());
+        };
+        Ok(Self {
+            endpoint: parse_url(endpoint)?,
+            region: region.clone(),
+            bucket: bucket.clone(),
+            access_key_id: access_key_id.clone(),
+            secret: secret.ok_or("No secret access key in the keychain")?.to_string(),
+            path_style: *path_style,
+            client: Client::new(),
+        })
+    }
+
+    /// Host header value and canonical path of `key`.
+    fn locate(&self, key: &str) -> Result<(String, String), String> {
+        let host = self.endpoint.host_str().ok_or("S3 endpoint has no host")?;
+        let host = match self.endpoint.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        };
+        let key = uri_encode(key, true);
+        Ok(if self.path_style {
+            (host, format!("/{}/{}", uri_encode(&self.bucket, false), key))
+        } else {
+            (format!("{}.{}", self.bucket, host), format!("/{}", key))
+        })
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(&str, &str)],
+        headers: &[(&str, &str)],
+        body: Vec<u8>,
+    ) -> Result<Response, String> {
+        let (host, path) = self.locate(key)?;
+        let mut params: Vec<(String, String)> =
+            query.iter().map(|(k, v)| (uri_encode(k, false), uri_encode(v, false))).collect();
+        params.sort();
+        let query: String =
+            params.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("&");
+
+        let now = chrono::Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        // Every x-amz-* header has to be signed
+        let mut signed = vec![
+            ("host", host.as_str()),
+            ("x-amz-content-sha256", payload_hash.as_str()),
+            ("x-amz-date", amz_date.as_str()),
+        ];
+        signed.extend_from_slice(headers);
+        signed.sort();
+        let canonical_headers: String =
+            signed.iter().map(|(name, value)| format!("{}:{}\n", name, value)).collect();
+        let signed_headers =
+            signed.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+        let canonical = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            method, path, query, canonical_headers, signed_headers, payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical.as_bytes()))
+        );
+        let mut key_bytes = hmac(format!("AWS4{}", self.secret).as_bytes(), date.as_bytes());
+        for part in [self.region.as_str(), "s3", "aws4_request"] {
+            key_bytes = hmac(&key_bytes, part.as_bytes());
+        }
+        let signature = hex::encode(hmac(&key_bytes, to_sign.as_bytes()));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key_id, scope, signed_headers, signature
+        );
+
+        let mut url = format!("{}://{}{}", self.endpoint.scheme(), host, path);
+        if !query.is_empty() {
+            url = format!("{}?{}", url, query);
+        }
+        let mut request = self
+            .client
+            .request(method, url)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", amz_date)
+            .header(reqwest::header::AUTHORIZATION, authorization);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let resp = request
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| format!("S3 request failed: {}", e))?;
+        check(resp).await
+    }
+
+    /// Upload `file` as `key`; returns whether it went multipart.
+    async fn upload(&self, file: &Path, key: &str, size: u64) -> Result<bool, String> {
+        let multipart = size > MULTIPART_THRESHOLD;
+        let expected = if multipart {
+            self.upload_parts(file, key).await?
+        } else {
+            let body = tokio::fs::read(file).await.map_err(|e| e.to_string())?;
+            let checksum = BASE64.encode(Sha256::digest(&body));
+            let headers = [("x-amz-checksum-sha256", checksum.as_str())];
+            self.send(Method::PUT, key, &[], &headers, body).await?;
+            checksum
+        };
+
+        let headers = [("x-amz-checksum-mode", "ENABLED")];
+        let resp = self.send(Method::HEAD, key, &[], &headers, Vec::new()).await?;
+        let header = |name: &str| resp.headers().get(name).and_then(|v| v.to_str().ok());
+        let stored = header("content-length").and_then(|v| v.parse::<u64>().ok());
+        if stored != Some(size) {
+            return Err(format!(
+                "Verification failed: bucket has {} of {} bytes",
+                stored.map_or("?".into(), |s| s.to_string()),
+                size
+            ));
+        }
+        // Multipart checksums come back as `<checksum of part checksums>-<parts>`
+        match header("x-amz-checksum-sha256").map(|v| v.split('-').next().unwrap_or(v)) {
+            Some(stored) if stored == expected => Ok(multipart),
+            Some(stored) => Err(format!(
+                "Verification failed: bucket has SHA256 {}, expected {}",
+                stored, expected
+            )),
+            None => Err("Verification failed: the bucket reported no SHA256 checksum".into()),
+        }
+    }
+
+    /// Multipart upload of `file` as `key`; returns the SHA256 S3 computes
+    /// over the parts' checksums, base64-encoded.
+    async fn upload_parts(&self, file: &Path, key: &str) -> Result<String, String> {
+        let headers = [("x-amz-checksum-algorithm", "SHA256")];
+        let resp = self.send(Method::POST, key, &[("uploads", "")], &headers, Vec::new()).await?;
+        let text = resp.text().await.map_err(|e| e.to_string())?;
+        let upload_id = xml_value(&text, "UploadId").ok_or("S3 returned no UploadId")?;
+        let result = self.send_parts(file, key, &upload_id).await;
+        if result.is_err() {
+            // Abandoned parts are billed until aborted
+            let abort = [("uploadId", upload_id.as_str())];
+            if let Err(e) = self.send(Method::DELETE, key, &abort, &[], Vec::new()).await {
+                eprintln!("[tulsbot] Could not abort multipart upload of {}: {}", key, e);
+            }
+        }
+        result
+    }
+
+    async fn send_parts(&self, file: &Path, key: &str, upload_id: &str) -> Result<String, String> {
+        let mut input = tokio::fs::File::open(file).await.map_err(|e| e.to_string())?;
+        let mut parts = Vec::new();
+        let mut digests = Vec::new();
+        loop {
+            let mut part = Vec::with_capacity(PART_SIZE);
+            (&mut input)
+                .take(PART_SIZE as u64)
+                .read_to_end(&mut part)
+                .await
+                .map_err(|e| e.to_string())?;
+            if part.is_empty() {
+                break;
+            }
+            let digest = Sha256::digest(&part);
+            let checksum = BASE64.encode(digest);
+            digests.extend_from_slice(&digest);
+            let number = (parts.len() + 1).to_string();
+            let query = [("partNumber", number.as_str()), ("uploadId", upload_id)];
+            let headers = [("x-amz-checksum-sha256", checksum.as_str())];
+            let resp = self.send(Method::PUT, key, &query, &headers, part).await?;
+            let etag = resp
+                .headers()
+                .get(reqwest::header::ETAG)
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| format!("S3 returned no ETag for part {}", number))?
+                .to_string();
+            parts.push((etag, checksum));
+        }
+        let body: String = parts
+            .iter()
+            .enumerate()
+            .map(|(i, (etag, checksum))| {
+                format!(
+                    "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag>\
+                     <ChecksumSHA256>{}</ChecksumSHA256></Part>",
+                    i + 1,
+                    etag,
+                    checksum
+                )
+            })
+            .collect();
+        let body = format!("<CompleteMultipartUpload>{}</CompleteMultipartUpload>", body);
+        let query = [("uploadId", upload_id)];
+        let resp = self.send(Method::POST, key, &query, &[], body.into_bytes()).await?;
+        // Completion can fail after a 200 with an error document
+        let text = resp.text().await.map_err(|e| e.to_string())?;
+        if text.contains("<Error>") {
+            return Err(format!("S3 could not complete the upload: {}", text.trim()));
+        }
+        Ok(BASE64.encode(Sha256::digest(&digests)))
+    }
+}
+
+fn xml_value(xml: &str, tag: &str) -> Option<String> {
+    let start = xml.find(&format!("<{}>", tag))? + tag.len() + 2;
+    let end = xml[start..].find(&format!("</{}>", tag))? + start;
+    Some(xml[start..end].to_string())
+}
@@ -0,0 +1,237 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use tulsbot_macros::instrumented;
+
+use crate::conversations;
+use crate::error::{AppError, ErrorKind};
+use crate::profiles::active_data_dir;
+
+// ── Attachment blob store (content-addressed) ───────────────────────────────
+//
+// Blobs live in `<data dir>/blobs/<first two hex chars>/<sha256>`, so the
+// same file attached twice is stored once. Messages reference blobs by hash;
+// `collect_garbage` removes blobs no message points at any more.
+
+const DIR_NAME: &str = "blobs";
+/// Blobs younger than this are kept even if unreferenced: they may have been
+/// uploaded for a message that hasn't been saved yet.
+const GC_GRACE_SECS: u64 = 60 * 60;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    /// SHA-256 of the content, hex.
+    pub id: String,
+    pub name: String,
+    pub mime: String,
+    pub size: u64,
+}
+
+fn root(data_dir: &Path) -> PathBuf {
+    data_dir.join(DIR_NAME)
+}
+
+pub fn blob_path(data_dir: &Path, id: &str) -> Result<PathBuf, String> {
+    let valid = id.len() == 64 && id.chars().all(|c| c.is_ascii_hexdigit());
+    if !valid {
+        return Err(format!("Invalid attachment id: {}", id));
+    }
+    Ok(root(data_dir).join(&id[..2]).join(id))
+}
+
+pub fn mime_for(name: &str) -> &'static str {
+    let ext = Path::new(name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        "json" => "application/json",
+        "txt" | "log" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Copy `reader` into the store, hashing as it goes. Blocking.
+pub fn store(
+    data_dir: &Path,
+    name: &str,
+    mime: Option<&str>,
+    mut reader: impl Read,
+) -> Result<Attachment, String> {
+    let tmp_dir = root(data_dir).join("tmp");
+    std::fs::create_dir_all(&tmp_dir).map_err(|e| e.to_string())?;
+    let tmp = tmp_dir.join(uuid::Uuid::new_v4().to_string());
+    let mut out = std::fs::File::create(&tmp).map_err(|e| e.to_string())?;
+
+    let mut hasher = Sha256::new();
+    let mut size = 0u64;
+    let mut buf = [0u8; 64 * 1024];
+    let copied = loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break Ok(()),
+            Ok(n) => n,
+            Err(e) => break Err(e),
+        };
+        hasher.update(&buf[..n]);
+        size += n as u64;
+        if let Err(e) = out.write_all(&buf[..n]) {
+            break Err(e);
+        }
+    };
+    drop(out);
+    if let Err(e) = copied {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(e.to_string());
+    }
+
+    let id = hex::encode(hasher.finalize());
+    let path = blob_path(data_dir, &id)?;
+    if path.exists() {
+        let _ = std::fs::remove_file(&tmp);
+    } else {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        std::fs::rename(&tmp, &path).map_err(|e| e.to_string())?;
+    }
+    Ok(Attachment {
+        id,
+        name: name.to_string(),
+        mime: mime.unwrap_or_else(|| mime_for(name)).to_string(),
+        size,
+    })
+}
+
+/// Store the file at `path`. Blocking.
+pub fn store_file(data_dir: &Path, path: &Path) -> Result<Attachment, String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "attachment".into());
+    store(data_dir, &name, None, file)
+}
+
+pub fn exists(data_dir: &Path, id: &str) -> bool {
+    blob_path(data_dir, id).is_ok_and(|p| p.is_file())
+}
+
+/// Blobs not in `referenced` and older than the grace period. Blocking.
+pub fn garbage(data_dir: &Path, referenced: &HashSet<String>) -> Vec<PathBuf> {
+    let now = std::time::SystemTime::now();
+    let mut garbage = Vec::new();
+    let shards = std::fs::read_dir(root(data_dir)).into_iter().flatten().flatten();
+    for shard in shards {
+        // `tmp` holds uploads in progress and interrupted ones
+        let is_tmp = shard.file_name() == "tmp";
+        for blob in std::fs::read_dir(shard.path()).into_iter().flatten().flatten() {
+            let name = blob.file_name().to_string_lossy().into_owned();
+            if !is_tmp && referenced.contains(&name) {
+                continue;
+            }
+            let old_enough = blob
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| now.duration_since(t).ok())
+                .is_some_and(|age| age.as_secs() >= GC_GRACE_SECS);
+            if old_enough {
+                garbage.push(blob.path());
+            }
+        }
+    }
+    garbage
+}
+
+/// Delete blobs not in `referenced` (and older than the grace period).
+/// Returns the number of blobs removed. Blocking.
+pub fn collect_garbage(data_dir: &Path, referenced: &HashSet<String>) -> usize {
+    let garbage = garbage(data_dir, referenced);
+    garbage.iter().filter(|path| std::fs::remove_file(path).is_ok()).count()
+}
+
+/// Add a dropped file to the blob store.
+#[instrumented]
+#[tauri::command]
+pub async fn add_attachment(app: AppHandle, path: String) -> Result<Attachment, AppError> {
+    let dir = active_data_dir(&app)?;
+    Ok(tauri::async_runtime::spawn_blocking(move || {
+        store_file(&dir, std::path::Path::new(&path))
+    })
+    .await
+    .map_err(|e| e.to_string())??)
+}
+
+/// Add raw bytes sent by the webview (`invoke` with a `Uint8Array` body);
+/// the `x-file-name` header names the file and `content-type` its type.
+#[instrumented]
+#[tauri::command]
+pub async fn upload_attachment(
+    app: AppHandle,
+    request: tauri::ipc::Request<'_>,
+) -> Result<Attachment, AppError> {
+    let tauri::ipc::InvokeBody::Raw(bytes) = request.body() else {
+        return Err(AppError::new(ErrorKind::InvalidInput, "Expected a binary body"));
+    };
+    let header = |name: &str| {
+        request
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from)
+    };
+    let name = header("x-file-name").unwrap_or_else(|| "attachment".into());
+    let mime = header("content-type").filter(|m| m != "application/octet-stream");
+    let (dir, bytes) = (active_data_dir(&app)?, bytes.clone());
+    Ok(tauri::async_runtime::spawn_blocking(move || {
+        store(&dir, &name, mime.as_deref(), bytes.as_slice())
+    })
+    .await
+    .map_err(|e| e.to_string())??)
+}
+
+/// Attachment content as a binary response (an `ArrayBuffer` in the webview).
+#[instrumented]
+#[tauri::command]
+pub async fn get_attachment(app: AppHandle, id: String) -> Result<tauri::ipc::Response, AppError> {
+    let path = blob_path(&active_data_dir(&app)?, &id)?;
+    let bytes = tokio::fs::read(&path)
+        .await
+        .map_err(|_| format!("Unknown attachment: {}", id))?;
+    Ok(tauri::ipc::Response::new(bytes))
+}
+
+/// Remove blobs that no message references any more.
+pub async fn collect_attachment_garbage(app: &AppHandle) -> Result<usize, String> {
+    let dir = active_data_dir(app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let referenced = conversations::list(&dir)
+            .into_iter()
+            .flat_map(|c| c.messages)
+            .flat_map(|m| m.attachments)
+            .map(|a| a.id)
+            .collect();
+        collect_garbage(&dir, &referenced)
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn gc_attachments(app: AppHandle) -> Result<usize, AppError> {
+    Ok(collect_attachment_garbage(&app).await?)
+}
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// ── What windows do when they lose focus ────────────────────────────────────
+//
+// The popover used to always hide on blur, which makes copying from another
+// app into it impossible. Each window now has its own behavior, looked up
+// when focus changes so a new choice applies to open windows right away.
+// Dimming is drawn by the webview, which gets `window-dimmed` events.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlurBehavior {
+    Hide,
+    Dim,
+    Nothing,
+}
+
+/// Behavior of window `label` given the per-window settings: the popover
+/// hides by default, every other window stays as it is.
+pub fn behavior(settings: &BTreeMap<String, BlurBehavior>, label: &str) -> BlurBehavior {
+    settings.get(label).copied().unwrap_or(if label == "chat-popover" {
+        BlurBehavior::Hide
+    } else {
+        BlurBehavior::Nothing
+    })
+}
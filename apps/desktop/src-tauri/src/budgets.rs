@@ -0,0 +1,198 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tulsbot_macros::instrumented;
+
+use crate::providers;
+use crate::error::AppError;
+use crate::notifications::{notify, Notice, NoticeKind};
+use crate::profiles::active_data_dir;
+use crate::state::AppState;
+use crate::usage::{self, UsageRecord};
+
+// ── Monthly budgets per provider ────────────────────────────────────────────
+//
+// Usage is read back from the usage log for the current calendar month.
+// Spend is estimated from token counts and the per-budget prices, since the
+// providers don't report cost.
+
+/// Warn at these fractions of a budget unless the budget sets its own.
+const DEFAULT_WARN_AT: &[u8] = &[80];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Budget {
+    pub provider: String,
+    pub monthly_tokens: Option<u64>,
+    pub monthly_spend_usd: Option<f64>,
+    /// Price in USD per million input / output tokens, for spend estimates.
+    pub input_usd_per_mtok: Option<f64>,
+    pub output_usd_per_mtok: Option<f64>,
+    /// Percentages of the budget at which to warn; empty uses 80%.
+    pub warn_at: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetStatus {
+    pub provider: String,
+    pub tokens_used: u64,
+    pub spend_usd: f64,
+    pub monthly_tokens: Option<u64>,
+    pub monthly_spend_usd: Option<f64>,
+    /// Highest share of either limit used, as a percentage.
+    pub percent_used: f64,
+    pub exceeded: bool,
+}
+
+/// Start of the current local calendar month, in unix seconds.
+pub fn month_start() -> u64 {
+    use chrono::{Datelike, TimeZone};
+    let now = chrono::Local::now();
+    chrono::Local
+        .with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .earliest()
+        .map_or(0, |t| t.timestamp().max(0) as u64)
+}
+
+/// Current month key, `YYYY-MM`, for remembering which warnings were sent.
+pub fn month_key() -> String {
+    chrono::Local::now().format("%Y-%m").to_string()
+}
+
+pub fn status(budget: &Budget, records: &[UsageRecord]) -> BudgetStatus {
+    let (mut input, mut output) = (0u64, 0u64);
+    for record in records.iter().filter(|r| r.provider == budget.provider) {
+        input += record.input_tokens.unwrap_or(0);
+        output += record.output_tokens.unwrap_or(0);
+    }
+    let spend_usd = (input as f64 * budget.input_usd_per_mtok.unwrap_or(0.0)
+        + output as f64 * budget.output_usd_per_mtok.unwrap_or(0.0))
+        / 1_000_000.0;
+    let tokens_used = input + output;
+
+    let token_share = budget
+        .monthly_tokens
+        .map(|limit| tokens_used as f64 / limit.max(1) as f64);
+    let spend_share = budget
+        .monthly_spend_usd
+        .map(|limit| if limit > 0.0 { spend_usd / limit } else { f64::INFINITY });
+    let share = token_share.into_iter().chain(spend_share).fold(0.0, f64::max);
+    BudgetStatus {
+        provider: budget.provider.clone(),
+        tokens_used,
+        spend_usd,
+        monthly_tokens: budget.monthly_tokens,
+        monthly_spend_usd: budget.monthly_spend_usd,
+        percent_used: (share * 100.0).min(999.0),
+        exceeded: share >= 1.0,
+    }
+}
+
+/// Thresholds crossed by `status`, highest first.
+pub fn crossed(budget: &Budget, status: &BudgetStatus) -> Vec<u8> {
+    let warn_at = if budget.warn_at.is_empty() { DEFAULT_WARN_AT } else { &budget.warn_at };
+    let mut crossed: Vec<u8> = warn_at
+        .iter()
+        .copied()
+        .filter(|p| status.percent_used >= f64::from(*p))
+        .collect();
+    crossed.sort_unstable_by_key(|p| std::cmp::Reverse(*p));
+    crossed
+}
+
+/// This month's status for `provider`'s budget, if it has one. Blocking.
+pub fn budget_status(app: &AppHandle, provider: &str) -> Option<(Budget, BudgetStatus)> {
+    let budget = app
+        .state::<AppState>()
+        .settings
+        .lock()
+        .ok()?
+        .budgets
+        .iter()
+        .find(|b| b.provider == provider)
+        .cloned()?;
+    let records = usage::load_since(&active_data_dir(app).ok()?, month_start());
+    let status = status(&budget, &records);
+    Some((budget, status))
+}
+
+/// Refuse paid calls to a provider whose monthly budget is used up.
+pub async fn check_budget(app: &AppHandle, provider: &str) -> Result<(), String> {
+    if !providers::needs_key(provider) {
+        return Ok(());
+    }
+    let (app, name) = (app.clone(), provider.to_string());
+    let status = tauri::async_runtime::spawn_blocking(move || budget_status(&app, &name))
+        .await
+        .map_err(|e| e.to_string())?;
+    match status {
+        Some((_, status)) if status.exceeded => Err(format!(
+            "Monthly budget for {} is used up; switch to a local model (ollama) or raise \
+             the budget in settings",
+            provider
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Notify once per month for each budget threshold crossed, and when the
+/// budget runs out. Blocking.
+pub fn warn_about_budget(app: &AppHandle, provider: &str) {
+    let Some((budget, status)) = budget_status(app, provider) else {
+        return;
+    };
+    let (id, percent) = if status.exceeded {
+        ("notify-budget-exceeded", 100)
+    } else {
+        match crossed(&budget, &status).first() {
+            Some(percent) => ("notify-budget-warning", *percent),
+            None => return,
+        }
+    };
+    let key = format!("{}:{}:{}", provider, month_key(), percent);
+    let state = app.state::<AppState>();
+    let first = state
+        .budget_warnings
+        .lock()
+        .map(|mut sent| sent.insert(key))
+        .unwrap_or(false);
+    if !first {
+        return;
+    }
+    let _ = app.emit("budget-threshold", &status);
+    let notice = {
+        let Ok(i18n) = state.i18n.lock() else {
+            return;
+        };
+        Notice {
+            kind: NoticeKind::Info,
+            title: i18n.t("notify-title"),
+            body: i18n.t_args(id, &[("provider", provider), ("percent", &percent.to_string())]),
+            service: None,
+            actions: Vec::new(),
+        }
+    };
+    notify(app, notice);
+}
+
+/// This month's usage against every configured budget.
+#[instrumented]
+#[tauri::command]
+pub async fn get_budget_status(app: AppHandle) -> Result<Vec<BudgetStatus>, AppError> {
+    let providers: Vec<String> = app
+        .state::<AppState>()
+        .settings
+        .lock()
+        .map_err(|e| e.to_string())?
+        .budgets
+        .iter()
+        .map(|b| b.provider.clone())
+        .collect();
+    Ok(tauri::async_runtime::spawn_blocking(move || {
+        providers
+            .iter()
+            .filter_map(|p| budget_status(&app, p).map(|(_, status)| status))
+            .collect()
+    })
+    .await
+    .map_err(|e| e.to_string())?)
+}
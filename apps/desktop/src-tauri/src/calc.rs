@@ -0,0 +1,626 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::{AppHandle, Manager};
+use tulsbot_macros::instrumented;
+
+use crate::conversations;
+use crate::error::{AppError, ErrorKind};
+use crate::locks::LockExt;
+use crate::profiles::active_data_dir;
+use crate::state::AppState;
+
+// ── Calculator and unit conversion ──────────────────────────────────────────
+//
+// Answers "2^10 / 3", "5 km to mi", "72 °F in C" or "120 usd to eur" locally,
+// without a round-trip to a model. An input is either arithmetic or a
+// conversion, `<expression> <unit> to|in|as <unit>`. Units are matched
+// case-insensitively; anything else of three letters is taken for an ISO
+// currency code. Exchange rates come from Frankfurter (ECB reference rates)
+// and are reused for a day, in memory and in `currency_rates.json`.
+
+const RATES_FILE: &str = "currency_rates.json";
+const RATES_URL: &str = "https://api.frankfurter.app/latest";
+/// Rates are refetched after this long.
+pub const RATES_MAX_AGE_SECS: u64 = 24 * 60 * 60;
+const TIMEOUT_SECS: u64 = 15;
+/// Nested unary operators and atoms the parser descends into before giving
+/// up, so `((((…` can't overflow the stack.
+const MAX_DEPTH: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dimension {
+    Length,
+    Mass,
+    Volume,
+    Area,
+    Time,
+    Speed,
+    Data,
+    Temperature,
+}
+
+/// Names, dimension and size in the dimension's base unit. Temperatures are
+/// converted separately, their factor is unused.
+const UNITS: &[(&[&str], Dimension, f64)] = &[
+    (&["mm", "millimeter", "millimeters"], Dimension::Length, 0.001),
+    (&["cm", "centimeter", "centimeters"], Dimension::Length, 0.01),
+    (&["m", "meter", "meters", "metre", "metres"], Dimension::Length, 1.0),
+    (&["km", "kilometer", "kilometers"], Dimension::Length, 1000.0),
+    (&["in", "inch", "inches"], Dimension::Length, 0.0254),
+    (&["ft", "foot", "feet"], Dimension::Length, 0.3048),
+    (&["yd", "yard", "yards"], Dimension::Length, 0.9144),
+    (&["mi", "mile", "miles"], Dimension::Length, 1609.344),
+    (&["nmi"], Dimension::Length, 1852.0),
+    (&["mg", "milligram", "milligrams"], Dimension::Mass, 1e-6),
+    (&["g", "gram", "grams"], Dimension::Mass, 0.001),
+    (&["kg", "kilogram", "kilograms"], Dimension::Mass, 1.0),
+    (&["t", "tonne", "tonnes"], Dimension::Mass, 1000.0),
+    (&["oz", "ounce", "ounces"], Dimension::Mass, 0.028_349_523_125),
+    (&["lb", "lbs", "pound", "pounds"], Dimension::Mass, 0.453_592_37),
+    (&["st", "stone"], Dimension::Mass, 6.350_293_18),
+    (&["ml", "milliliter", "milliliters"], Dimension::Volume, 0.001),
+    (&["cl"], Dimension::Volume, 0.01),
+    (&["dl"], Dimension::Volume, 0.1),
+    (&["l", "liter", "liters", "litre", "litres"], Dimension::Volume, 1.0),
+    (&["m3"], Dimension::Volume, 1000.0),
+    (&["tsp", "teaspoon", "teaspoons"], Dimension::Volume, 0.004_928_921_593_75),
+    (&["tbsp", "tablespoon", "tablespoons"], Dimension::Volume, 0.014_786_764_781_25),
+    (&["floz"], Dimension::Volume, 0.029_573_529_562_5),
+    (&["cup", "cups"], Dimension::Volume, 0.236_588_236_5),
+    (&["pt", "pint", "pints"], Dimension::Volume, 0.473_176_473),
+    (&["qt", "quart", "quarts"], Dimension::Volume, 0.946_352_946),
+    (&["gal", "gallon", "gallons"], Dimension::Volume, 3.785_411_784),
+    (&["m2"], Dimension::Area, 1.0),
+    (&["km2"], Dimension::Area, 1e6),
+    (&["ft2", "sqft"], Dimension::Area, 0.092_903_04),
+    (&["ha", "hectare", "hectares"], Dimension::Area, 1e4),
+    (&["acre", "acres"], Dimension::Area, 4_046.856_422_4),
+    (&["ms", "millisecond", "milliseconds"], Dimension::Time, 0.001),
+    (&["s", "sec", "second", "seconds"], Dimension::Time, 1.0),
+    (&["min", "minute", "minutes"], Dimension::Time, 60.0),
+    (&["h", "hr", "hour", "hours"], Dimension::Time, 3600.0),
+    (&["d", "day", "days"], Dimension::Time, 86_400.0),
+    (&["wk", "week", "weeks"], Dimension::Time, 604_800.0),
+    (&["yr", "year", "years"], Dimension::Time, 31_557_600.0),
+    (&["m/s"], Dimension::Speed, 1.0),
+    (&["km/h", "kph"], Dimension::Speed, 1.0 / 3.6),
+    (&["mph"], Dimension::Speed, 0.447_04),
+    (&["kn", "knot", "knots"], Dimension::Speed, 1852.0 / 3600.0),
+    (&["bit", "bits"], Dimension::Data, 0.125),
+    (&["b", "byte", "bytes"], Dimension::Data, 1.0),
+    (&["kb"], Dimension::Data, 1e3),
+    (&["mb"], Dimension::Data, 1e6),
+    (&["gb"], Dimension::Data, 1e9),
+    (&["tb"], Dimension::Data, 1e12),
+    (&["kib"], Dimension::Data, 1024.0),
+    (&["mib"], Dimension::Data, 1_048_576.0),
+    (&["gib"], Dimension::Data, 1_073_741_824.0),
+    (&["c", "°c", "celsius"], Dimension::Temperature, 0.0),
+    (&["f", "°f", "fahrenheit"], Dimension::Temperature, 0.0),
+    (&["k", "kelvin"], Dimension::Temperature, 0.0),
+];
+
+/// A parsed input.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    Arithmetic(f64),
+    Convert { value: f64, from: String, to: String },
+}
+
+impl Query {
+    /// The conversion is between currencies, so it needs `Rates`.
+    pub fn needs_rates(&self) -> bool {
+        matches!(self, Query::Convert { from, .. } if unit(from).is_none())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Evaluation {
+    pub expression: String,
+    pub value: f64,
+    /// Unit or currency code of `value`; `None` for plain numbers.
+    pub unit: Option<String>,
+    /// `value` and unit, formatted for display.
+    pub display: String,
+    /// Day of the exchange rates used, for currency conversions.
+    pub rates_date: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rates {
+    pub base: String,
+    /// Publication day of the rates.
+    pub date: String,
+    /// Units of each currency per one `base`.
+    pub rates: HashMap<String, f64>,
+    /// Unix seconds.
+    pub fetched_at: u64,
+}
+
+impl Rates {
+    pub fn fresh(&self, now: u64) -> bool {
+        now.saturating_sub(self.fetched_at) < RATES_MAX_AGE_SECS
+    }
+
+    fn rate(&self, code: &str) -> Result<f64, String> {
+        if code == self.base {
+            return Ok(1.0);
+        }
+        self.rates.get(code).copied().ok_or_else(|| format!("Unknown currency '{}'", code))
+    }
+}
+
+pub fn parse(input: &str) -> Result<Query, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("Nothing to evaluate".into());
+    }
+    let lower = input.to_ascii_lowercase();
+    let split = [" to ", " in ", " as ", " -> "]
+        .iter()
+        .find_map(|sep| lower.rfind(sep).map(|at| (at, sep.len())));
+    let Some((at, len)) = split else {
+        return Ok(Query::Arithmetic(arithmetic(input)?));
+    };
+    let (left, to) = (input[..at].trim(), input[at + len..].trim());
+    let (expression, from) = split_unit(left)?;
+    let to = normalize_unit(to)?;
+    let from = normalize_unit(from)?;
+    Ok(Query::Convert { value: arithmetic(expression)?, from, to })
+}
+
+pub fn evaluate(
+    expression: &str,
+    query: &Query,
+    rates: Option<&Rates>,
+) -> Result<Evaluation, String> {
+    let (value, unit, rates_date) = match query {
+        Query::Arithmetic(value) => (*value, None, None),
+        Query::Convert { value, from, to } => match (unit(from), unit(to)) {
+            (Some(from_unit), Some(to_unit)) => {
+                let value = convert(*value, from, from_unit, to, to_unit)?;
+                (value, Some(label(to)), None)
+            }
+            (None, None) => {
+                let rates = rates.ok_or("Exchange rates are not available")?;
+                let value = value / rates.rate(from)? * rates.rate(to)?;
+                (value, Some(to.clone()), Some(rates.date.clone()))
+            }
+            _ => return Err(format!("Can't convert {} to {}", from, to)),
+        },
+    };
+    if !value.is_finite() {
+        return Err("The result is not a finite number".into());
+    }
+    let number = if rates_date.is_some() { format!("{:.2}", value) } else { format_number(value) };
+    let display = match &unit {
+        Some(unit) => format!("{} {}", number, unit),
+        None => number,
+    };
+    Ok(Evaluation { expression: expression.trim().to_string(), value, unit, display, rates_date })
+}
+
+pub fn load_rates(dir: &Path) -> Option<Rates> {
+    let text = std::fs::read_to_string(dir.join(RATES_FILE)).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+pub fn save_rates(dir: &Path, rates: &Rates) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let text = serde_json::to_string_pretty(rates).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(RATES_FILE), text).map_err(|e| e.to_string())
+}
+
+pub async fn fetch_rates(now: u64) -> Result<Rates, String> {
+    let resp = reqwest::Client::new()
+        .get(RATES_URL)
+        .timeout(std::time::Duration::from_secs(TIMEOUT_SECS))
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("HTTP {}", resp.status().as_u16()));
+    }
+    let json: serde_json::Value = resp.json().await.map_err(|e| e.to_string())?;
+    let rates: HashMap<String, f64> = serde_json::from_value(json["rates"].clone())
+        .map_err(|e| format!("Unexpected exchange rate response: {}", e))?;
+    Ok(Rates {
+        base: json["base"].as_str().unwrap_or("EUR").to_string(),
+        date: json["date"].as_str().unwrap_or_default().to_string(),
+        rates,
+        fetched_at: now,
+    })
+}
+
+fn unit(name: &str) -> Option<(Dimension, f64)> {
+    UNITS
+        .iter()
+        .find(|(names, ..)| names.contains(&name))
+        .map(|(_, dimension, factor)| (*dimension, *factor))
+}
+
+/// How a unit is shown in results: degree signs for temperatures.
+fn label(unit: &str) -> String {
+    match unit.trim_start_matches('°') {
+        "c" | "celsius" => "°C".into(),
+        "f" | "fahrenheit" => "°F".into(),
+        "k" | "kelvin" => "K".into(),
+        _ => unit.to_string(),
+    }
+}
+
+/// The canonical spelling of a unit: its lowercase name, or an uppercase
+/// currency code.
+fn normalize_unit(name: &str) -> Result<String, String> {
+    let lower = name.trim().to_lowercase();
+    if unit(&lower).is_some() {
+        return Ok(lower);
+    }
+    if lower.len() == 3 && lower.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Ok(lower.to_uppercase());
+    }
+    Err(format!("Unknown unit '{}'", name.trim()))
+}
+
+/// Split `5 km` or `(2+3)km` into the expression and the unit.
+fn split_unit(text: &str) -> Result<(&str, &str), String> {
+    if let Some((expression, last)) = text.rsplit_once(char::is_whitespace) {
+        if normalize_unit(last).is_ok() {
+            return Ok((expression, last));
+        }
+    }
+    let start = text
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_alphanumeric() || matches!(c, '°' | '/'))
+        .filter(|(_, c)| c.is_alphabetic() || *c == '°')
+        .last()
+        .map(|(i, _)| i)
+        .ok_or_else(|| format!("No unit to convert from in '{}'", text))?;
+    Ok((&text[..start], &text[start..]))
+}
+
+fn convert(
+    value: f64,
+    from: &str,
+    (from_dimension, from_factor): (Dimension, f64),
+    to: &str,
+    (to_dimension, to_factor): (Dimension, f64),
+) -> Result<f64, String> {
+    if from_dimension != to_dimension {
+        return Err(format!("Can't convert {} to {}", from, to));
+    }
+    if from_dimension != Dimension::Temperature {
+        return Ok(value * from_factor / to_factor);
+    }
+    let kelvin = match from.trim_start_matches('°') {
+        "c" | "celsius" => value + 273.15,
+        "f" | "fahrenheit" => (value - 32.0) * 5.0 / 9.0 + 273.15,
+        _ => value,
+    };
+    Ok(match to.trim_start_matches('°') {
+        "c" | "celsius" => kelvin - 273.15,
+        "f" | "fahrenheit" => (kelvin - 273.15) * 9.0 / 5.0 + 32.0,
+        _ => kelvin,
+    })
+}
+
+/// Up to ten significant digits, without trailing zeros.
+fn format_number(value: f64) -> String {
+    if value != 0.0 && !(1e-6..1e15).contains(&value.abs()) {
+        return format!("{:e}", value);
+    }
+    let digits = 9i32.saturating_sub(value.abs().log10().floor() as i32).clamp(0, 15) as usize;
+    let text = format!("{:.*}", digits, value);
+    let text = if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        &text
+    };
+    if text == "-0" {
+        "0".into()
+    } else {
+        text.to_string()
+    }
+}
+
+/// Exchange rates from memory, the profile's copy on disk, or a fetch, the
+/// first of them less than a day old. Stale rates beat none when offline.
+pub async fn currency_rates(app: &AppHandle) -> Result<Rates, String> {
+    let now = conversations::now();
+    let dir = active_data_dir(app)?;
+    let known = app.state::<AppState>().currency_rates.lock_or_recover().clone();
+    let known = known.or_else(|| load_rates(&dir));
+    if let Some(rates) = known.as_ref().filter(|r| r.fresh(now)) {
+        return Ok(rates.clone());
+    }
+    let rates = match fetch_rates(now).await {
+        Ok(rates) => {
+            if let Err(e) = save_rates(&dir, &rates) {
+                eprintln!("[tulsbot] Failed to cache exchange rates: {}", e);
+            }
+            rates
+        }
+        Err(e) => {
+            eprintln!("[tulsbot] Failed to fetch exchange rates: {}", e);
+            known.ok_or(e)?
+        }
+    };
+    *app.state::<AppState>().currency_rates.lock_or_recover() = Some(rates.clone());
+    Ok(rates)
+}
+
+/// Evaluate arithmetic or a unit or currency conversion locally, for the
+/// popover's input and the assistant.
+#[instrumented]
+#[tauri::command]
+pub async fn evaluate_expression(
+    app: AppHandle,
+    expression: String,
+) -> Result<Evaluation, AppError> {
+    let query =
+        parse(&expression).map_err(|e| AppError::new(ErrorKind::InvalidInput, e))?;
+    let rates = if query.needs_rates() { Some(currency_rates(&app).await?) } else { None };
+    evaluate(&expression, &query, rates.as_ref())
+        .map_err(|e| AppError::new(ErrorKind::InvalidInput, e))
+}
+
+// ── Arithmetic ──────────────────────────────────────────────────────────────
+//
+// A recursive-descent evaluator for `+ - * / % ^`, parentheses, the
+// constants `pi` and `e`, and one-argument functions. `^` binds tighter than
+// a leading minus and groups to the right, so `-2^2` is -4 and `2^3^2` 512.
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Op(char),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number(value) => write!(f, "{}", value),
+            Token::Name(name) => f.write_str(name),
+            Token::Op(op) => write!(f, "{}", op),
+        }
+    }
+}
+
+fn arithmetic(text: &str) -> Result<f64, String> {
+    let tokens = tokenize(text)?;
+    let mut parser = Parser { tokens: &tokens, pos: 0, depth: 0 };
+    let value = parser.expression()?;
+    if let Some(token) = parser.tokens.get(parser.pos) {
+        return Err(format!("Unexpected '{}' in expression", token));
+    }
+    Ok(value)
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() || c == '_' {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut number = String::new();
+            let digit = |d: &&char| d.is_ascii_digit() || matches!(d, '.' | '_');
+            while let Some(&d) = chars.peek().filter(digit) {
+                if d != '_' {
+                    number.push(d);
+                }
+                chars.next();
+            }
+            let value = number.parse().map_err(|_| format!("Invalid number '{}'", number))?;
+            tokens.push(Token::Number(value));
+        } else if c.is_alphabetic() {
+            let mut name = String::new();
+            while let Some(&l) = chars.peek().filter(|l| l.is_alphanumeric()) {
+                name.push(l);
+                chars.next();
+            }
+            tokens.push(Token::Name(name.to_lowercase()));
+        } else {
+            let op = match c {
+                '×' => '*',
+                '÷' => '/',
+                '+' | '-' | '*' | '/' | '%' | '^' | '(' | ')' => c,
+                _ => return Err(format!("Unexpected '{}' in expression", c)),
+            };
+            tokens.push(Token::Op(op));
+            chars.next();
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    /// Run `parse` one level deeper, failing past `MAX_DEPTH`.
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<f64, String>) -> Result<f64, String> {
+        if self.depth >= MAX_DEPTH {
+            return Err("Expression too deeply nested".into());
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn eat(&mut self, op: char) -> bool {
+        let found = self.tokens.get(self.pos) == Some(&Token::Op(op));
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expression(&mut self) -> Result<f64, String> {
+        let mut value = self.term()?;
+        loop {
+            if self.eat('+') {
+                value += self.term()?;
+            } else if self.eat('-') {
+                value -= self.term()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<f64, String> {
+        let mut value = self.unary()?;
+        loop {
+            if self.eat('*') {
+                value *= self.unary()?;
+            } else if self.eat('/') {
+                let divisor = self.unary()?;
+                if divisor == 0.0 {
+                    return Err("Division by zero".into());
+                }
+                value /= divisor;
+            } else if self.eat('%') {
+                value %= self.unary()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<f64, String> {
+        self.nested(Self::signed)
+    }
+
+    fn signed(&mut self) -> Result<f64, String> {
+        if self.eat('-') {
+            return Ok(-self.unary()?);
+        }
+        if self.eat('+') {
+            return self.unary();
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<f64, String> {
+        let base = self.atom()?;
+        if self.eat('^') {
+            return Ok(base.powf(self.unary()?));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<f64, String> {
+        self.nested(Self::primary)
+    }
+
+    fn primary(&mut self) -> Result<f64, String> {
+        let token = self.tokens.get(self.pos).cloned().ok_or("Incomplete expression")?;
+        self.pos += 1;
+        match token {
+            Token::Number(value) => Ok(value),
+            Token::Op('(') => {
+                let value = self.expression()?;
+                if !self.eat(')') {
+                    return Err("Missing ')'".into());
+                }
+                Ok(value)
+            }
+            Token::Name(name) if name == "pi" => Ok(std::f64::consts::PI),
+            Token::Name(name) if name == "e" => Ok(std::f64::consts::E),
+            Token::Name(name) => {
+                if self.pos == self.tokens.len() {
+                    return Err(format!("Unknown name '{}'", name));
+                }
+                let arg = self.atom()?;
+                Ok(match name.as_str() {
+                    "sqrt" => arg.sqrt(),
+                    "abs" => arg.abs(),
+                    "ln" => arg.ln(),
+                    "log" => arg.log10(),
+                    "sin" => arg.sin(),
+                    "cos" => arg.cos(),
+                    "tan" => arg.tan(),
+                    "round" => arg.round(),
+                    "floor" => arg.floor(),
+                    "ceil" => arg.ceil(),
+                    _ => return Err(format!("Unknown function '{}'", name)),
+                })
+            }
+            token => Err(format!("Unexpected '{}' in expression", token)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn precedence() {
+        assert_eq!(arithmetic("1 + 2 * 3").unwrap(), 7.0);
+        assert_eq!(arithmetic("(1 + 2) * 3").unwrap(), 9.0);
+        assert_eq!(arithmetic("10 - 4 - 3").unwrap(), 3.0);
+        assert_eq!(arithmetic("2 * 3 % 4").unwrap(), 2.0);
+        assert_eq!(arithmetic("2 × 3 ÷ 4").unwrap(), 1.5);
+    }
+
+    #[test]
+    fn unary_minus() {
+        assert_eq!(arithmetic("-2^2").unwrap(), -4.0);
+        assert_eq!(arithmetic("--3").unwrap(), 3.0);
+        assert_eq!(arithmetic("2 * -3").unwrap(), -6.0);
+        assert_eq!(arithmetic("(-2)^2").unwrap(), 4.0);
+    }
+
+    #[test]
+    fn power_groups_to_the_right() {
+        assert_eq!(arithmetic("2^3^2").unwrap(), 512.0);
+        assert_eq!(arithmetic("2^-1").unwrap(), 0.5);
+    }
+
+    #[test]
+    fn names_and_functions() {
+        assert_eq!(arithmetic("sqrt 16 + abs(-2)").unwrap(), 6.0);
+        assert_eq!(arithmetic("pi").unwrap(), std::f64::consts::PI);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(arithmetic("1 / 0").unwrap_err(), "Division by zero");
+        assert_eq!(arithmetic("(1 + 2").unwrap_err(), "Missing ')'");
+        assert_eq!(arithmetic("1 +").unwrap_err(), "Incomplete expression");
+        assert_eq!(arithmetic("1 2").unwrap_err(), "Unexpected '2' in expression");
+        assert_eq!(arithmetic("foo(2)").unwrap_err(), "Unknown function 'foo'");
+        assert_eq!(arithmetic("1 $ 2").unwrap_err(), "Unexpected '$' in expression");
+    }
+
+    #[test]
+    fn deep_nesting_is_refused() {
+        let parens = format!("{}1{}", "(".repeat(10_000), ")".repeat(10_000));
+        assert_eq!(arithmetic(&parens).unwrap_err(), "Expression too deeply nested");
+        let minuses = format!("{}1", "-".repeat(10_000));
+        assert_eq!(arithmetic(&minuses).unwrap_err(), "Expression too deeply nested");
+        let nested = format!("{}1{}", "(".repeat(50), ")".repeat(50));
+        assert_eq!(arithmetic(&nested).unwrap(), 1.0);
+    }
+
+    #[test]
+    fn number_formatting() {
+        assert_eq!(format_number(0.1 + 0.2), "0.3");
+        assert_eq!(format_number(1.0 / 3.0), "0.3333333333");
+        assert_eq!(format_number(1_234_567.0), "1234567");
+        assert_eq!(format_number(2.5), "2.5");
+        assert_eq!(format_number(0.0), "0");
+        assert_eq!(format_number(-0.0), "0");
+        assert_eq!(format_number(1e20), "1e20");
+        assert_eq!(format_number(1e-9), "1e-9");
+    }
+}
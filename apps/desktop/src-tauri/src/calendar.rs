@@ -0,0 +1,470 @@
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use tauri::{AppHandle, Emitter, Manager};
+use tulsbot_macros::instrumented;
+
+use crate::{conversations, keychain, settings};
+use crate::env::active_profile_name;
+use crate::error::{AppError, ErrorKind};
+use crate::locks::LockExt;
+use crate::profiles::active_data_dir;
+use crate::state::AppState;
+
+// ── Calendar (system calendar or ICS feeds) ─────────────────────────────────
+//
+// Upcoming events come from the OS calendar (EventKit through JXA on macOS,
+// Outlook's calendar through COM on Windows) and from ICS feed URLs. Feed
+// URLs usually embed a private token, so they live in the keychain and are
+// fetched here; the webview only ever sees feed names and events.
+//
+// ICS support is deliberately small: SUMMARY/LOCATION/DTSTART/DTEND, UTC or
+// floating times (a TZID is read as local time), all-day dates, EXDATE, and
+// RRULE with FREQ=DAILY or WEEKLY (INTERVAL, COUNT, UNTIL, BYDAY). Other
+// recurrences only yield their first occurrence.
+
+/// Occurrences returned per recurring event, at most.
+const MAX_OCCURRENCES: usize = 1000;
+/// Days a recurrence is followed from its first occurrence.
+const MAX_RECURRENCE_DAYS: i64 = 366 * 30;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalendarSettings {
+    /// Read the OS calendar (asks for calendar permission on macOS).
+    pub system: bool,
+    /// ICS feed names; their URLs are in the keychain.
+    pub feeds: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarEvent {
+    pub title: String,
+    /// Unix seconds.
+    pub start: i64,
+    pub end: i64,
+    pub all_day: bool,
+    pub location: Option<String>,
+    /// Feed name, or the OS calendar's name.
+    pub calendar: String,
+}
+
+pub fn keychain_account(profile: &str, feed: &str) -> String {
+    format!("calendar:{}:{}", profile, feed)
+}
+
+pub fn validate_feed_url(url: &str) -> Result<String, String> {
+    let url = match url.strip_prefix("webcal://") {
+        Some(rest) => format!("https://{}", rest),
+        None => url.to_string(),
+    };
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err("Calendar feeds must be http(s):// or webcal:// URLs".into());
+    }
+    Ok(url)
+}
+
+pub async fn fetch_feed(url: &str) -> Result<String, String> {
+    let resp = reqwest::Client::new()
+        .get(url)
+        .timeout(std::time::Duration::from_secs(20))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !resp.status().is_success() {
+        return Err(format!("HTTP {}", resp.status().as_u16()));
+    }
+    resp.text().await.map_err(|e| e.to_string())
+}
+
+pub fn update_calendar_feeds(
+    app: &AppHandle,
+    change: impl FnOnce(&mut Vec<String>),
+) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        change(&mut settings.calendar.feeds);
+        settings.clone()
+    };
+    settings::save(&active_data_dir(app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    Ok(())
+}
+
+/// Add an ICS feed. The URL goes to the keychain; settings keep the name.
+#[instrumented]
+#[tauri::command]
+pub async fn add_calendar_feed(app: AppHandle, name: String, url: String) -> Result<(), AppError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::new(ErrorKind::InvalidInput, "Feed name is empty"));
+    }
+    let url = validate_feed_url(url.trim())?;
+    let account = keychain_account(&active_profile_name(&app)?, &name);
+    tauri::async_runtime::spawn_blocking(move || keychain::set(&account, &url))
+        .await
+        .map_err(|e| e.to_string())??;
+    Ok(update_calendar_feeds(&app, |feeds| {
+        if !feeds.contains(&name) {
+            feeds.push(name);
+        }
+    })?)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn remove_calendar_feed(app: AppHandle, name: String) -> Result<(), AppError> {
+    let account = keychain_account(&active_profile_name(&app)?, &name);
+    tauri::async_runtime::spawn_blocking(move || keychain::delete(&account))
+        .await
+        .map_err(|e| e.to_string())??;
+    Ok(update_calendar_feeds(&app, |feeds| feeds.retain(|feed| *feed != name))?)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpcomingEvents {
+    pub events: Vec<CalendarEvent>,
+    /// Sources that could not be read, as `source: error`.
+    pub errors: Vec<String>,
+}
+
+/// Events in the next `hours` (default 24) from the OS calendar and every
+/// ICS feed, sorted by start. A failing source is reported, not fatal.
+#[instrumented]
+#[tauri::command]
+pub async fn get_upcoming_events(
+    app: AppHandle,
+    hours: Option<u32>,
+) -> Result<UpcomingEvents, AppError> {
+    let hours = hours.unwrap_or(24).clamp(1, 24 * 31);
+    let config = {
+        let state = app.state::<AppState>();
+        let settings = state.settings.lock_or_recover();
+        settings.calendar.clone()
+    };
+    let profile = active_profile_name(&app)?;
+    let now = conversations::now() as i64;
+    let to = now + i64::from(hours) * 3600;
+    let mut events = Vec::new();
+    let mut errors = Vec::new();
+
+    if config.system {
+        match tauri::async_runtime::spawn_blocking(move || system_events(hours)).await {
+            Ok(Ok(found)) => events.extend(found),
+            Ok(Err(e)) => errors.push(format!("system: {}", e)),
+            Err(e) => errors.push(format!("system: {}", e)),
+        }
+    }
+    for feed in config.feeds {
+        let account = keychain_account(&profile, &feed);
+        let url = tauri::async_runtime::spawn_blocking(move || keychain::get(&account))
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|r| r)
+            .and_then(|url| url.ok_or_else(|| "URL missing from the keychain".to_string()));
+        let text = match url {
+            Ok(url) => fetch_feed(&url).await,
+            Err(e) => Err(e),
+        };
+        match text {
+            Ok(text) => events.extend(parse_ics(&text, &feed, now, to)),
+            Err(e) => errors.push(format!("{}: {}", feed, e)),
+        }
+    }
+    events.sort_by_key(|e| e.start);
+    Ok(UpcomingEvents { events, errors })
+}
+
+// ── ICS parsing ─────────────────────────────────────────────────────────────
+
+struct Property<'a> {
+    name: String,
+    value: &'a str,
+}
+
+fn property(line: &str) -> Option<Property<'_>> {
+    let (head, value) = line.split_once(':')?;
+    let name = head.split(';').next().unwrap_or(head);
+    Some(Property { name: name.to_ascii_uppercase(), value })
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+/// `(unix seconds, all day)` of a DATE or DATE-TIME value. A TZID
+/// parameter is ignored and the time read as local.
+fn parse_time(value: &str) -> Option<(i64, bool)> {
+    let value = value.trim();
+    if !value.contains('T') {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        let local = Local.from_local_datetime(&date.and_hms_opt(0, 0, 0)?).earliest()?;
+        return Some((local.timestamp(), true));
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let time = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some((Utc.from_utc_datetime(&time).timestamp(), false));
+    }
+    let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    Some((Local.from_local_datetime(&time).earliest()?.timestamp(), false))
+}
+
+#[derive(Default)]
+struct Rule {
+    weekly: bool,
+    interval: i64,
+    count: Option<usize>,
+    until: Option<i64>,
+    by_day: Vec<Weekday>,
+}
+
+fn parse_rule(value: &str) -> Option<Rule> {
+    let mut rule = Rule { interval: 1, ..Default::default() };
+    let mut supported = false;
+    for part in value.split(';') {
+        let (key, val) = part.split_once('=')?;
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                supported = matches!(val, "DAILY" | "WEEKLY");
+                rule.weekly = val == "WEEKLY";
+            }
+            "INTERVAL" => rule.interval = val.parse().ok().filter(|i| *i > 0)?,
+            "COUNT" => rule.count = val.parse().ok(),
+            "UNTIL" => rule.until = parse_time(val).map(|(t, _)| t),
+            "BYDAY" => {
+                // A numeric prefix ("1MO") only makes sense monthly; drop it
+                let day = |d: &str| match d.trim_start_matches(|c: char| !c.is_alphabetic()) {
+                    "MO" => Some(Weekday::Mon),
+                    "TU" => Some(Weekday::Tue),
+                    "WE" => Some(Weekday::Wed),
+                    "TH" => Some(Weekday::Thu),
+                    "FR" => Some(Weekday::Fri),
+                    "SA" => Some(Weekday::Sat),
+                    "SU" => Some(Weekday::Sun),
+                    _ => None,
+                };
+                rule.by_day = val.split(',').filter_map(day).collect()
+            }
+            _ => {}
+        }
+    }
+    supported.then_some(rule)
+}
+
+/// Start times of a recurring event between `from` and `to`, in order.
+fn occurrences(start: i64, rule: &Rule, from: i64, to: i64) -> Vec<i64> {
+    let Some(first) = Local.timestamp_opt(start, 0).single() else {
+        return vec![start];
+    };
+    let mut starts = Vec::new();
+    let mut count = 0;
+    let mut day = 0i64;
+    while starts.len() < MAX_OCCURRENCES && day <= MAX_RECURRENCE_DAYS {
+        let Some(candidate) = first
+            .date_naive()
+            .checked_add_signed(Duration::days(day))
+            .and_then(|date| date.and_time(first.time()).and_local_timezone(Local).earliest())
+        else {
+            break;
+        };
+        let time = candidate.timestamp();
+        if time > to || rule.until.is_some_and(|until| time > until) {
+            break;
+        }
+        let matches = if rule.weekly {
+            let week = day / 7;
+            let weekday_ok = if rule.by_day.is_empty() {
+                day % 7 == 0
+            } else {
+                rule.by_day.contains(&candidate.weekday())
+            };
+            week % rule.interval == 0 && weekday_ok
+        } else {
+            day % rule.interval == 0
+        };
+        if matches {
+            count += 1;
+            if time >= from {
+                starts.push(time);
+            }
+            if rule.count.is_some_and(|max| count >= max) {
+                break;
+            }
+        }
+        day += 1;
+    }
+    starts
+}
+
+/// Events of an ICS document overlapping `[from, to]` (Unix seconds).
+pub fn parse_ics(text: &str, calendar: &str, from: i64, to: i64) -> Vec<CalendarEvent> {
+    // Unfold continuation lines (RFC 5545 §3.1)
+    let mut lines: Vec<String> = Vec::new();
+    for raw in text.lines() {
+        let raw = raw.trim_end_matches('\r');
+        match raw.strip_prefix(' ').or_else(|| raw.strip_prefix('\t')) {
+            Some(rest) if !lines.is_empty() => lines.last_mut().unwrap().push_str(rest),
+            _ => lines.push(raw.to_string()),
+        }
+    }
+
+    let mut events = Vec::new();
+    let mut current: Option<Vec<Property<'_>>> = None;
+    for line in &lines {
+        match line.as_str() {
+            "BEGIN:VEVENT" => current = Some(Vec::new()),
+            "END:VEVENT" => {
+                if let Some(props) = current.take() {
+                    expand(&props, calendar, from, to, &mut events);
+                }
+            }
+            _ => {
+                if let (Some(props), Some(prop)) = (current.as_mut(), property(line)) {
+                    props.push(prop);
+                }
+            }
+        }
+    }
+    events
+}
+
+fn expand(
+    props: &[Property<'_>],
+    calendar: &str,
+    from: i64,
+    to: i64,
+    events: &mut Vec<CalendarEvent>,
+) {
+    let get = |name: &str| props.iter().find(|p| p.name == name);
+    let Some((start, all_day)) = get("DTSTART").and_then(|p| parse_time(p.value)) else {
+        return;
+    };
+    let end = get("DTEND")
+        .and_then(|p| parse_time(p.value))
+        .map(|(end, _)| end)
+        .unwrap_or(if all_day { start + 86_400 } else { start });
+    let duration = (end - start).max(0);
+    let excluded: Vec<i64> = props
+        .iter()
+        .filter(|p| p.name == "EXDATE")
+        .flat_map(|p| p.value.split(',').filter_map(parse_time))
+        .map(|(t, _)| t)
+        .collect();
+    let starts = match get("RRULE").and_then(|p| parse_rule(p.value)) {
+        Some(rule) => occurrences(start, &rule, from - duration, to),
+        None => vec![start],
+    };
+    let title = get("SUMMARY").map(|p| unescape(p.value)).unwrap_or_default();
+    let location = get("LOCATION").map(|p| unescape(p.value)).filter(|l| !l.is_empty());
+    for start in starts {
+        if start + duration < from || start > to || excluded.contains(&start) {
+            continue;
+        }
+        events.push(CalendarEvent {
+            title: title.clone(),
+            start,
+            end: start + duration,
+            all_day,
+            location: location.clone(),
+            calendar: calendar.to_string(),
+        });
+    }
+}
+
+// ── OS calendar ─────────────────────────────────────────────────────────────
+
+#[cfg(target_os = "macos")]
+const EVENTKIT_SCRIPT: &str = r#"
+ObjC.import('EventKit');
+function run(argv) {
+    if ($.EKEventStore.authorizationStatusForEntityType($.EKEntityTypeEvent) !== 3) {
+        throw new Error('Calendar access not granted');
+    }
+    const store = $.EKEventStore.alloc.init;
+    const from = $.NSDate.date;
+    const to = $.NSDate.dateWithTimeIntervalSinceNow(Number(argv[0]));
+    const predicate = store.predicateForEventsWithStartDateEndDateCalendars(from, to, $());
+    const events = store.eventsMatchingPredicate(predicate);
+    const out = [];
+    for (let i = 0; i < events.count; i++) {
+        const e = events.objectAtIndex(i);
+        out.push({
+            title: e.title.js || '',
+            start: Math.floor(e.startDate.timeIntervalSince1970),
+            end: Math.floor(e.endDate.timeIntervalSince1970),
+            all_day: e.allDay,
+            location: e.location.js || null,
+            calendar: e.calendar.title.js || 'Calendar',
+        });
+    }
+    return JSON.stringify(out);
+}
+"#;
+
+#[cfg(windows)]
+const OUTLOOK_SCRIPT: &str = r#"
+$hours = [double]$args[0]
+$ns = (New-Object -ComObject Outlook.Application).GetNamespace('MAPI')
+$items = $ns.GetDefaultFolder(9).Items
+$items.IncludeRecurrences = $true
+$items.Sort('[Start]')
+$from = (Get-Date).ToString('g'); $to = (Get-Date).AddHours($hours).ToString('g')
+ConvertTo-Json -InputObject @($items.Restrict("[End] >= '$from' AND [Start] <= '$to'") |
+ForEach-Object {
+    [pscustomobject]@{
+        title = [string]$_.Subject
+        start = ([DateTimeOffset]$_.Start).ToUnixTimeSeconds()
+        end = ([DateTimeOffset]$_.End).ToUnixTimeSeconds()
+        all_day = [bool]$_.AllDayEvent
+        location = if ($_.Location) { [string]$_.Location } else { $null }
+        calendar = 'Outlook'
+    }
+})
+"#;
+
+#[cfg_attr(not(any(target_os = "macos", windows)), allow(dead_code))]
+fn run_json(program: &str, args: &[&str]) -> Result<Vec<CalendarEvent>, String> {
+    let out = Command::new(program).args(args).output().map_err(|e| e.to_string())?;
+    if !out.status.success() {
+        let err = String::from_utf8_lossy(&out.stderr);
+        return Err(format!("{}: {}", program, err.trim()));
+    }
+    let text = String::from_utf8_lossy(&out.stdout);
+    if text.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_str(text.trim()).map_err(|e| e.to_string())
+}
+
+/// Events in the next `hours` from the OS calendar. Blocking.
+pub fn system_events(hours: u32) -> Result<Vec<CalendarEvent>, String> {
+    #[cfg(target_os = "macos")]
+    return run_json(
+        "osascript",
+        &["-l", "JavaScript", "-e", EVENTKIT_SCRIPT, &(u64::from(hours) * 3600).to_string()],
+    );
+    #[cfg(windows)]
+    return run_json(
+        "powershell",
+        &["-NoProfile", "-Command", &format!("& {{{}}} {}", OUTLOOK_SCRIPT, hours)],
+    );
+    #[cfg(not(any(target_os = "macos", windows)))]
+    {
+        let _ = hours;
+        Err("No system calendar on this platform; add an ICS feed instead".into())
+    }
+}
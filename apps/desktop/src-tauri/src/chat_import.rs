@@ -0,0 +1,508 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager};
+use tulsbot_macros::instrumented;
+
+use crate::conversations::{self, Conversation, ConversationConfig, Message};
+use crate::error::AppError;
+use crate::locks::LockExt;
+use crate::profiles::active_data_dir;
+use crate::relay::{relay_info, RelayCategory};
+use crate::state::AppState;
+use crate::tray::begin_work;
+
+// ── Chat history import ─────────────────────────────────────────────────────
+//
+// Reads the exports of other chat tools into the conversation store: the
+// ChatGPT data export (`conversations.json`, with its message tree), the
+// Claude export (`conversations.json`, linear `chat_messages`) and a generic
+// JSONL format. Exports may be given as the zip the service sends, the
+// extracted folder or the JSON file itself.
+//
+// Each imported conversation records where it came from in `imported_from`
+// (`chatgpt:<id>`, `claude:<uuid>`, `jsonl:<id or content hash>`), which is
+// how a second import of the same archive skips what is already there.
+
+const EXPORT_FILE: &str = "conversations.json";
+/// Tag added to every imported conversation.
+const TAG: &str = "imported";
+/// Titles listed in a report.
+const MAX_TITLES: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    Chatgpt,
+    Claude,
+    /// One object per line: either a whole conversation
+    /// (`{"title", "messages": [{"role", "content"}]}`) or a single message
+    /// (`{"conversation_id", "role", "content", "created_at"}`).
+    Jsonl,
+}
+
+impl Source {
+    fn key(self) -> &'static str {
+        match self {
+            Source::Chatgpt => "chatgpt",
+            Source::Claude => "claude",
+            Source::Jsonl => "jsonl",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Progress {
+    pub processed: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatImportReport {
+    pub source: Source,
+    /// Nothing was written; the counts say what an import would do.
+    pub dry_run: bool,
+    /// Conversations (and their messages) new to the store.
+    pub conversations: usize,
+    pub messages: usize,
+    /// Conversations already imported earlier.
+    pub duplicates: usize,
+    /// `(title or id, reason)` of conversations that could not be read.
+    pub skipped: Vec<(String, String)>,
+    /// Titles of the first new conversations.
+    pub titles: Vec<String>,
+}
+
+/// Text of the export at `path`: a zip, a folder or a file. Blocking.
+fn read_input(path: &Path) -> Result<String, String> {
+    if path.is_dir() {
+        let file = path.join(EXPORT_FILE);
+        return std::fs::read_to_string(&file)
+            .map_err(|e| format!("{}: {}", file.display(), e));
+    }
+    let is_zip = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("zip"));
+    if !is_zip {
+        return std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e));
+    }
+    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("Invalid zip: {}", e))?;
+    let name = archive
+        .file_names()
+        .filter(|n| n.rsplit('/').next() == Some(EXPORT_FILE) || n.ends_with(".jsonl"))
+        .min_by_key(|n| n.len())
+        .map(String::from)
+        .ok_or_else(|| format!("No {} or .jsonl file in the archive", EXPORT_FILE))?;
+    let mut text = String::new();
+    archive
+        .by_name(&name)
+        .map_err(|e| e.to_string())?
+        .read_to_string(&mut text)
+        .map_err(|e| format!("{}: {}", name, e))?;
+    Ok(text)
+}
+
+/// Guess the format from the content.
+fn detect(text: &str) -> Result<Source, String> {
+    let trimmed = text.trim_start();
+    if !trimmed.starts_with('[') {
+        return Ok(Source::Jsonl);
+    }
+    let value: Value = serde_json::from_str(trimmed).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let first = value.as_array().and_then(|a| a.first());
+    match first {
+        Some(c) if c.get("mapping").is_some() => Ok(Source::Chatgpt),
+        Some(c) if c.get("chat_messages").is_some() => Ok(Source::Claude),
+        None => Err("The export holds no conversations".into()),
+        Some(_) => Err("Unrecognized export format".into()),
+    }
+}
+
+fn role(value: &str) -> Option<&'static str> {
+    match value.to_lowercase().as_str() {
+        "user" | "human" => Some("user"),
+        "assistant" | "ai" | "bot" | "model" => Some("assistant"),
+        "system" => Some("system"),
+        _ => None,
+    }
+}
+
+/// Unix seconds from a number or an RFC 3339 string.
+fn timestamp(value: Option<&Value>) -> Option<u64> {
+    match value? {
+        Value::Number(n) => n.as_f64().filter(|t| *t > 0.0).map(|t| t as u64),
+        Value::String(s) => chrono::DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(|t| t.timestamp().max(0) as u64),
+        _ => None,
+    }
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    value.get(key).and_then(Value::as_str).filter(|s| !s.trim().is_empty())
+}
+
+fn message(id: String, role: &str, content: String, created_at: u64) -> Message {
+    Message {
+        id,
+        role: role.to_string(),
+        content,
+        created_at,
+        provider: None,
+        model: None,
+        parent: None,
+        attachments: Vec::new(),
+        pinned: false,
+        rating: None,
+        metadata: None,
+    }
+}
+
+fn conversation(
+    source: Source,
+    external_id: &str,
+    title: Option<&str>,
+    created_at: u64,
+    messages: Vec<Message>,
+) -> Result<Conversation, String> {
+    if messages.is_empty() {
+        return Err("No messages".into());
+    }
+    let mut conversation =
+        Conversation::new(title.map(String::from), ConversationConfig::default());
+    let last = messages.iter().map(|m| m.created_at).max().unwrap_or(created_at);
+    conversation.created_at = created_at;
+    conversation.updated_at = last.max(created_at);
+    conversation.titled = title.is_some();
+    conversation.tags = vec![TAG.into()];
+    conversation.imported_from = Some(format!("{}:{}", source.key(), external_id));
+    conversation.messages = messages;
+    Ok(conversation)
+}
+
+/// Text of a ChatGPT message, or `None` for tool output, hidden system
+/// prompts, browsing results and other content that isn't conversation.
+fn chatgpt_text(message: &Value) -> Option<String> {
+    let hidden = message
+        .pointer("/metadata/is_visually_hidden_from_conversation")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    if hidden {
+        return None;
+    }
+    let content = message.get("content")?;
+    let text = match content.get("content_type").and_then(Value::as_str)? {
+        "text" | "multimodal_text" => content
+            .get("parts")?
+            .as_array()?
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        "code" => format!("```\n{}\n```", content.get("text")?.as_str()?),
+        _ => return None,
+    };
+    Some(text).filter(|t| !t.trim().is_empty())
+}
+
+/// One ChatGPT conversation. Its `mapping` is the full message tree,
+/// including regenerated replies, so branches carry over; nodes that are
+/// dropped hand their children to the nearest kept ancestor.
+fn chatgpt(value: &Value) -> Result<Conversation, String> {
+    let id = str_field(value, "conversation_id")
+        .or_else(|| str_field(value, "id"))
+        .ok_or("No conversation id")?;
+    let created_at = timestamp(value.get("create_time")).unwrap_or_else(conversations::now);
+    let mapping = value.get("mapping").and_then(Value::as_object).ok_or("No messages")?;
+
+    let mut kept: HashMap<&str, Message> = HashMap::new();
+    for (node_id, node) in mapping {
+        let Some(raw) = node.get("message").filter(|m| !m.is_null()) else {
+            continue;
+        };
+        let Some(role) = raw.pointer("/author/role").and_then(Value::as_str).and_then(role) else {
+            continue;
+        };
+        if let Some(text) = chatgpt_text(raw) {
+            let at = timestamp(raw.get("create_time")).unwrap_or(created_at);
+            kept.insert(node_id, message(node_id.clone(), role, text, at));
+        }
+    }
+    let parent_of = |node: &str| mapping.get(node).and_then(|n| str_field(n, "parent"));
+    let nearest_kept = |start: Option<&str>| {
+        let mut next = start;
+        let mut steps = 0;
+        while let Some(node) = next.filter(|_| steps <= mapping.len()) {
+            if kept.contains_key(node) {
+                return Some(node.to_string());
+            }
+            next = parent_of(node);
+            steps += 1;
+        }
+        None
+    };
+
+    let parents: HashMap<String, Option<String>> = kept
+        .keys()
+        .map(|node| (node.to_string(), nearest_kept(parent_of(node))))
+        .collect();
+    let active_leaf = nearest_kept(str_field(value, "current_node"));
+    let mut messages: Vec<Message> = kept.into_values().collect();
+    for message in &mut messages {
+        message.parent = parents.get(&message.id).cloned().flatten();
+    }
+    messages.sort_by(|a, b| (a.created_at, &a.id).cmp(&(b.created_at, &b.id)));
+
+    let mut conversation =
+        conversation(Source::Chatgpt, id, str_field(value, "title"), created_at, messages)?;
+    conversation.active_leaf = active_leaf;
+    Ok(conversation)
+}
+
+/// One Claude conversation. Messages name their parent when the export is
+/// recent enough; otherwise they are taken as linear.
+fn claude(value: &Value) -> Result<Conversation, String> {
+    let id = str_field(value, "uuid").ok_or("No conversation uuid")?;
+    let created_at = timestamp(value.get("created_at")).unwrap_or_else(conversations::now);
+    let raw = value.get("chat_messages").and_then(Value::as_array).ok_or("No messages")?;
+
+    let mut messages: Vec<Message> = Vec::new();
+    for (i, item) in raw.iter().enumerate() {
+        let Some(role) = str_field(item, "sender").and_then(role) else {
+            continue;
+        };
+        let text = match str_field(item, "text") {
+            Some(text) => text.to_string(),
+            None => item
+                .get("content")
+                .and_then(Value::as_array)
+                .map(|parts| {
+                    parts
+                        .iter()
+                        .filter(|p| p.get("type").and_then(Value::as_str) == Some("text"))
+                        .filter_map(|p| p.get("text").and_then(Value::as_str))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                })
+                .unwrap_or_default(),
+        };
+        if text.trim().is_empty() {
+            continue;
+        }
+        let message_id =
+            str_field(item, "uuid").map_or_else(|| format!("{}-{}", id, i), String::from);
+        let at = timestamp(item.get("created_at")).unwrap_or(created_at);
+        let mut message = message(message_id, role, text, at);
+        let named = str_field(item, "parent_message_uuid")
+            .filter(|p| messages.iter().any(|m| m.id == *p))
+            .map(String::from);
+        message.parent = named.or_else(|| messages.last().map(|m| m.id.clone()));
+        messages.push(message);
+    }
+    conversation(Source::Claude, id, str_field(value, "name"), created_at, messages)
+}
+
+/// Short content hash standing in for a missing JSONL conversation id.
+fn content_id(title: Option<&str>, messages: &[Message]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(title.unwrap_or_default());
+    for message in messages {
+        hasher.update([0]);
+        hasher.update(&message.role);
+        hasher.update([0]);
+        hasher.update(&message.content);
+    }
+    hex::encode(hasher.finalize())[..16].to_string()
+}
+
+/// Linear messages from `{"role", "content", "created_at"}` objects.
+fn jsonl_messages(items: &[&Value], fallback_time: u64) -> Vec<Message> {
+    let mut messages: Vec<Message> = Vec::new();
+    for item in items {
+        let Some(role) = str_field(item, "role").and_then(role) else {
+            continue;
+        };
+        let Some(content) = str_field(item, "content") else {
+            continue;
+        };
+        let at = timestamp(item.get("created_at")).unwrap_or(fallback_time);
+        let mut message = message(conversations::new_id(), role, content.to_string(), at);
+        message.parent = messages.last().map(|m| m.id.clone());
+        messages.push(message);
+    }
+    messages
+}
+
+/// Conversations of a JSONL file; single-message lines are grouped by
+/// `conversation_id`, in file order.
+fn jsonl(text: &str) -> Vec<(String, Result<Conversation, String>)> {
+    let now = conversations::now();
+    let mut results = Vec::new();
+    let mut groups: Vec<(String, Vec<Value>)> = Vec::new();
+    for (n, line) in text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+        let label = format!("line {}", n + 1);
+        let value: Value = match serde_json::from_str(line) {
+            Ok(value) => value,
+            Err(e) => {
+                results.push((label, Err(format!("Invalid JSON: {}", e))));
+                continue;
+            }
+        };
+        if let Some(items) = value.get("messages").and_then(Value::as_array) {
+            let title = str_field(&value, "title");
+            let created_at = timestamp(value.get("created_at")).unwrap_or(now);
+            let messages = jsonl_messages(&items.iter().collect::<Vec<_>>(), created_at);
+            let id = str_field(&value, "id")
+                .map_or_else(|| content_id(title, &messages), String::from);
+            let label = title.map_or(label, String::from);
+            results.push((label, conversation(Source::Jsonl, &id, title, created_at, messages)));
+            continue;
+        }
+        let group = str_field(&value, "conversation_id")
+            .or_else(|| str_field(&value, "conversation"))
+            .unwrap_or("default")
+            .to_string();
+        match groups.iter_mut().find(|(id, _)| *id == group) {
+            Some((_, lines)) => lines.push(value),
+            None => groups.push((group, vec![value])),
+        }
+    }
+    for (group, lines) in groups {
+        let title = lines.iter().find_map(|l| str_field(l, "title"));
+        let created_at = lines.iter().find_map(|l| timestamp(l.get("created_at"))).unwrap_or(now);
+        let messages = jsonl_messages(&lines.iter().collect::<Vec<_>>(), created_at);
+        let id = if group == "default" { content_id(title, &messages) } else { group.clone() };
+        let label = title.map_or(group, String::from);
+        results.push((label, conversation(Source::Jsonl, &id, title, created_at, messages)));
+    }
+    results
+}
+
+/// Read the export at `path` and add its conversations to `data_dir`,
+/// calling `on_progress` after each one. With `dry_run` nothing is written.
+/// Blocking.
+pub fn import(
+    data_dir: &Path,
+    path: &Path,
+    source: Option<Source>,
+    dry_run: bool,
+    on_progress: impl Fn(Progress),
+) -> Result<ChatImportReport, String> {
+    let text = read_input(path)?;
+    let source = match source {
+        Some(source) => source,
+        None => detect(&text)?,
+    };
+    let parsed: Vec<(String, Result<Conversation, String>)> = match source {
+        Source::Jsonl => jsonl(&text),
+        Source::Chatgpt | Source::Claude => {
+            let value: Value =
+                serde_json::from_str(&text).map_err(|e| format!("Invalid JSON: {}", e))?;
+            let items = value.as_array().ok_or("Expected a list of conversations")?;
+            items
+                .iter()
+                .map(|item| {
+                    let label = str_field(item, "title")
+                        .or_else(|| str_field(item, "name"))
+                        .or_else(|| str_field(item, "id"))
+                        .or_else(|| str_field(item, "uuid"))
+                        .unwrap_or("(untitled)")
+                        .to_string();
+                    let result =
+                        if source == Source::Chatgpt { chatgpt(item) } else { claude(item) };
+                    (label, result)
+                })
+                .collect()
+        }
+    };
+
+    let mut known: HashSet<String> = conversations::list(data_dir)
+        .into_iter()
+        .filter_map(|c| c.imported_from)
+        .collect();
+    let mut report = ChatImportReport {
+        source,
+        dry_run,
+        conversations: 0,
+        messages: 0,
+        duplicates: 0,
+        skipped: Vec::new(),
+        titles: Vec::new(),
+    };
+    let total = parsed.len();
+    for (processed, (label, result)) in parsed.into_iter().enumerate() {
+        match result {
+            Err(reason) => report.skipped.push((label, reason)),
+            Ok(conversation) => {
+                let key = conversation.imported_from.clone().unwrap_or_default();
+                if !known.insert(key) {
+                    report.duplicates += 1;
+                } else {
+                    if !dry_run {
+                        conversations::save(data_dir, &conversation)?;
+                    }
+                    report.conversations += 1;
+                    report.messages += conversation.messages.len();
+                    if report.titles.len() < MAX_TITLES {
+                        report.titles.push(conversation.title);
+                    }
+                }
+            }
+        }
+        on_progress(Progress { processed: processed + 1, total });
+    }
+    Ok(report)
+}
+
+pub async fn run_chat_import(
+    app: AppHandle,
+    path: String,
+    source: Option<Source>,
+    dry_run: bool,
+) -> Result<ChatImportReport, String> {
+    let data_dir = active_data_dir(&app)?;
+    let _work = begin_work(&app);
+    tauri::async_runtime::spawn_blocking(move || {
+        import(&data_dir, &PathBuf::from(path), source, dry_run, |progress| {
+            // Large archives hold thousands of conversations
+            if progress.processed % 25 == 0 || progress.processed == progress.total {
+                let _ = app.emit("chat-import-progress", progress);
+            }
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// What importing the export at `path` would add, without writing anything.
+#[instrumented]
+#[tauri::command]
+pub async fn preview_chat_import(
+    app: AppHandle,
+    path: String,
+    source: Option<Source>,
+) -> Result<ChatImportReport, AppError> {
+    Ok(run_chat_import(app, path, source, true).await?)
+}
+
+/// Import a ChatGPT or Claude export (zip, folder or `conversations.json`)
+/// or a JSONL file; `source` is detected when not given. Conversations
+/// imported before are skipped.
+#[instrumented]
+#[tauri::command]
+pub async fn import_chat_history(
+    app: AppHandle,
+    path: String,
+    source: Option<Source>,
+) -> Result<ChatImportReport, AppError> {
+    let report = run_chat_import(app.clone(), path, source, false).await?;
+    let body = app
+        .state::<AppState>()
+        .i18n
+        .lock_or_recover()
+        .t_count("notify-import-done", report.conversations);
+    relay_info(&app, RelayCategory::Jobs, body);
+    Ok(report)
+}
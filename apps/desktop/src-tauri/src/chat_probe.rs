@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tulsbot_macros::instrumented;
+
+use crate::alerts::{evaluate_alerts, Metric, Sample};
+use crate::context_builder::build_context;
+use crate::conversations::{self, effective_config, Conversation, ConversationConfig, Message};
+use crate::error::AppError;
+use crate::notifications::{notify, Notice, NoticeKind};
+use crate::providers::{run_completion, ChatRequest};
+use crate::state::AppState;
+
+// ── Synthetic chat probe ────────────────────────────────────────────────────
+//
+// Every service can answer its port check while chat is still broken: an
+// expired key, a model that was renamed, a retrieval step that hangs. The
+// probe sends a trivial prompt through the same path as a real conversation
+// (context builder, then provider) and records whether a reply came back and
+// how long the round trip took. It is opt-in because every run is a real,
+// possibly billed, provider call. The probe conversation is never saved.
+
+pub const DEFAULT_INTERVAL_MINUTES: u32 = 15;
+const DEFAULT_PROMPT: &str = "Reply with the single word OK.";
+/// Replies are not inspected beyond being non-empty, so keep them short.
+pub const MAX_TOKENS: u32 = 16;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatProbeSettings {
+    pub enabled: bool,
+    /// Minutes between probes; `None` probes every 15 minutes.
+    pub interval_minutes: Option<u32>,
+    /// Provider, model and credential; unset fields follow the conversation
+    /// defaults.
+    pub config: ConversationConfig,
+    /// `None` uses a short built-in prompt.
+    pub prompt: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatProbeResult {
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub ok: bool,
+    /// Full round trip, context building included.
+    pub latency_ms: u64,
+    /// Part of the round trip spent building the context.
+    pub context_ms: u64,
+    pub error: Option<String>,
+    /// Unix seconds.
+    pub checked_at: u64,
+}
+
+/// Whether a scheduled probe is due after one at `last` (Unix seconds).
+pub fn due(settings: &ChatProbeSettings, last: Option<u64>, now: u64) -> bool {
+    let interval = settings.interval_minutes.unwrap_or(DEFAULT_INTERVAL_MINUTES).max(1);
+    settings.enabled && last.is_none_or(|at| now.saturating_sub(at) >= u64::from(interval) * 60)
+}
+
+/// Throwaway conversation holding the probe prompt.
+pub fn conversation(settings: &ChatProbeSettings, config: ConversationConfig) -> Conversation {
+    let mut conversation = Conversation::new(Some("Chat probe".into()), config);
+    let prompt = settings.prompt.as_deref().filter(|p| !p.trim().is_empty());
+    conversation.messages.push(Message {
+        id: conversations::new_id(),
+        role: "user".into(),
+        content: prompt.unwrap_or(DEFAULT_PROMPT).into(),
+        created_at: conversation.created_at,
+        provider: None,
+        model: None,
+        parent: None,
+        attachments: Vec::new(),
+        pinned: false,
+        rating: None,
+        metadata: None,
+    });
+    conversation
+}
+
+/// Send the probe prompt through the context builder and the provider.
+/// `context_ms` is set once the context is built.
+pub async fn chat_probe_round_trip(
+    app: &AppHandle,
+    settings: &ChatProbeSettings,
+    config: &ConversationConfig,
+    started: std::time::Instant,
+    context_ms: &mut u64,
+) -> Result<(), String> {
+    let provider = config.provider.clone().ok_or("No provider configured for the chat probe")?;
+    let model = config.model.clone().ok_or("No model configured for the chat probe")?;
+    let conversation = conversation(settings, config.clone());
+    let context = build_context(app, &conversation).await?;
+    *context_ms = started.elapsed().as_millis() as u64;
+    let request = ChatRequest {
+        provider,
+        model,
+        temperature: config.temperature,
+        max_tokens: Some(MAX_TOKENS),
+        messages: context.messages,
+        credential: config.credential.clone(),
+    };
+    let reply = run_completion(app, &request, None).await?;
+    if reply.content.trim().is_empty() {
+        return Err("The provider returned an empty reply".into());
+    }
+    Ok(())
+}
+
+/// Probe the chat pipeline once, store the result in the health state and
+/// emit `chat-probe`. Notifies when a probe fails after one that succeeded.
+pub async fn run_chat_probe(app: &AppHandle) -> ChatProbeResult {
+    let state = app.state::<AppState>();
+    let settings = state.settings.lock().map(|s| s.chat_probe.clone()).unwrap_or_default();
+    let config = effective_config(&state, &settings.config)
+        .unwrap_or_else(|_| settings.config.clone());
+    let started = std::time::Instant::now();
+    let mut context_ms = 0;
+    let outcome =
+        chat_probe_round_trip(app, &settings, &config, started, &mut context_ms).await;
+    let result = ChatProbeResult {
+        provider: config.provider,
+        model: config.model,
+        ok: outcome.is_ok(),
+        latency_ms: started.elapsed().as_millis() as u64,
+        context_ms,
+        error: outcome.err(),
+        checked_at: conversations::now(),
+    };
+    let previous = state
+        .health
+        .lock()
+        .map(|mut health| health.chat_probe.replace(result.clone()))
+        .unwrap_or_default();
+    if let (Some(error), true) = (&result.error, previous.is_some_and(|p| p.ok)) {
+        eprintln!("[tulsbot] Chat probe failed: {}", error);
+        let notice = state.i18n.lock().ok().map(|i18n| Notice {
+            kind: NoticeKind::Info,
+            title: i18n.t("notify-title"),
+            body: i18n.t_args("notify-chat-probe-failed", &[("error", error)]),
+            service: None,
+            actions: Vec::new(),
+        });
+        if let Some(notice) = notice {
+            notify(app, notice);
+        }
+    }
+    let _ = app.emit("chat-probe", &result);
+    let failed = f64::from(u8::from(!result.ok));
+    let mut samples = vec![Sample::new(Metric::ChatProbeFailed, None, failed)];
+    if result.ok {
+        samples.push(Sample::new(Metric::ChatProbeLatency, None, result.latency_ms as f64));
+    }
+    evaluate_alerts(app, samples);
+    result
+}
+
+/// Scheduled probe, when enabled and the interval has elapsed.
+pub async fn run_chat_probe_job(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let Ok(settings) = state.settings.lock().map(|s| s.chat_probe.clone()) else {
+        return;
+    };
+    let last = state
+        .health
+        .lock()
+        .ok()
+        .and_then(|h| h.chat_probe.as_ref().map(|p| p.checked_at));
+    if due(&settings, last, conversations::now()) {
+        run_chat_probe(app).await;
+    }
+}
+
+/// Run the chat probe now, whether or not it is scheduled.
+#[instrumented]
+#[tauri::command]
+pub async fn probe_chat(app: AppHandle) -> Result<ChatProbeResult, AppError> {
+    Ok(run_chat_probe(&app).await)
+}
@@ -0,0 +1,83 @@
+use std::io::{Read, Write};
+
+// ── Proxy compression ───────────────────────────────────────────────────────
+//
+// Proxied responses are requested with `Accept-Encoding: gzip, deflate,
+// br` and decoded here rather than inside reqwest, which would drop the
+// `Content-Encoding` header and with it any sign of whether compression
+// happened. Request bodies are gzipped when `proxy_compress_requests` is on
+// and they are big enough to gain from it; only turn that on for backends
+// that accept gzip request bodies (the Context Manager does).
+
+pub const ACCEPT_ENCODING: &str = "gzip, deflate, br";
+
+/// Smaller request bodies are sent as is.
+pub const COMPRESS_MIN_BYTES: usize = 1024;
+
+pub fn gzip(body: &[u8]) -> Result<Vec<u8>, String> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder
+        .write_all(body)
+        .and_then(|_| encoder.finish())
+        .map_err(|e| format!("Failed to compress the request body: {}", e))
+}
+
+/// Decode a body that arrived with `Content-Encoding: encoding`.
+pub fn decode(encoding: Option<&str>, body: &[u8]) -> Result<Vec<u8>, String> {
+    let encoding = encoding.map(|e| e.trim().to_ascii_lowercase()).unwrap_or_default();
+    let mut decoded = Vec::new();
+    let read = match encoding.as_str() {
+        "" | "identity" => return Ok(body.to_vec()),
+        "gzip" | "x-gzip" => flate2::read::GzDecoder::new(body).read_to_end(&mut decoded),
+        "deflate" => flate2::read::ZlibDecoder::new(body).read_to_end(&mut decoded),
+        "br" => brotli::Decompressor::new(body, 4096).read_to_end(&mut decoded),
+        other => return Err(format!("Unsupported content encoding: {}", other)),
+    };
+    read.map_err(|e| format!("Failed to decode the {} response: {}", encoding, e))?;
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &[u8] = b"{\"status\":\"ok\",\"services\":[\"api\",\"qdrant\"]}";
+
+    #[test]
+    fn decode_passes_identity_through() {
+        assert_eq!(decode(None, BODY).unwrap(), BODY);
+        assert_eq!(decode(Some("identity"), BODY).unwrap(), BODY);
+    }
+
+    #[test]
+    fn decode_gzip() {
+        let body = gzip(BODY).unwrap();
+        assert_eq!(decode(Some("gzip"), &body).unwrap(), BODY);
+        assert_eq!(decode(Some(" GZIP "), &body).unwrap(), BODY);
+    }
+
+    #[test]
+    fn decode_deflate() {
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(BODY).unwrap();
+        let body = encoder.finish().unwrap();
+        assert_eq!(decode(Some("deflate"), &body).unwrap(), BODY);
+    }
+
+    #[test]
+    fn decode_brotli() {
+        let mut body = Vec::new();
+        {
+            let mut encoder = brotli::CompressorWriter::new(&mut body, 4096, 5, 22);
+            encoder.write_all(BODY).unwrap();
+        }
+        assert_eq!(decode(Some("br"), &body).unwrap(), BODY);
+    }
+
+    #[test]
+    fn decode_rejects_unknown_and_corrupt_bodies() {
+        assert!(decode(Some("zstd"), BODY).is_err());
+        assert!(decode(Some("gzip"), BODY).is_err());
+    }
+}
@@ -0,0 +1,268 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tulsbot_macros::instrumented;
+
+use crate::app_rules::{self, AppRule};
+use crate::error::{AppError, ErrorKind};
+use crate::locks::LockExt;
+use crate::selection::{self, SelectedText};
+use crate::state::AppState;
+use crate::typing::{self, InsertMode};
+
+// ── Frontmost application / document detection ─────────────────────────────
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActiveContext {
+    pub app_name: String,
+    pub pid: Option<u32>,
+    pub window_title: Option<String>,
+    /// Only populated when document tracking is enabled and the OS exposes it.
+    pub document_path: Option<String>,
+    /// Nearest ancestor of `document_path` that looks like a project root.
+    pub project_root: Option<String>,
+}
+
+/// Files/dirs whose presence marks a directory as a project root.
+const PROJECT_MARKERS: &[&str] = &[
+    ".git",
+    "Cargo.toml",
+    "package.json",
+    "pyproject.toml",
+    "go.mod",
+    ".hg",
+];
+
+/// Query the OS for the frontmost application. Blocking: shells out to
+/// `osascript` / PowerShell / `xdotool`, so call from a blocking task.
+pub fn detect(include_document: bool) -> Option<ActiveContext> {
+    let mut ctx = detect_platform()?;
+    if include_document {
+        ctx.project_root = ctx
+            .document_path
+            .as_deref()
+            .and_then(|doc| project_root(Path::new(doc)))
+            .map(|root| root.display().to_string());
+    } else {
+        ctx.document_path = None;
+    }
+    Some(ctx)
+}
+
+pub fn project_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .skip(1)
+        .find(|dir| PROJECT_MARKERS.iter().any(|m| dir.join(m).exists()))
+        .map(Path::to_path_buf)
+}
+
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn non_empty(line: Option<&str>) -> Option<String> {
+    line.map(str::trim)
+        .filter(|s| !s.is_empty() && *s != "missing value")
+        .map(str::to_string)
+}
+
+#[cfg(target_os = "macos")]
+fn detect_platform() -> Option<ActiveContext> {
+    const SCRIPT: &str = r#"
+tell application "System Events"
+    set p to first application process whose frontmost is true
+    set out to (name of p) & linefeed & (unix id of p)
+    try
+        set w to front window of p
+        set out to out & linefeed & (name of w)
+        set out to out & linefeed & (value of attribute "AXDocument" of w)
+    end try
+    return out
+end tell"#;
+
+    let out = run("osascript", &["-e", SCRIPT])?;
+    let mut lines = out.lines();
+    let app_name = non_empty(lines.next())?;
+    let pid = lines.next().and_then(|l| l.trim().parse().ok());
+    let window_title = non_empty(lines.next());
+    // AXDocument is a file:// URL
+    let document_path = non_empty(lines.next())
+        .and_then(|url| reqwest::Url::parse(&url).ok())
+        .and_then(|url| url.to_file_path().ok())
+        .map(|path| path.display().to_string());
+
+    Some(ActiveContext { app_name, pid, window_title, document_path, project_root: None })
+}
+
+#[cfg(target_os = "windows")]
+fn detect_platform() -> Option<ActiveContext> {
+    const SCRIPT: &str = r#"
+Add-Type @"
+using System; using System.Runtime.InteropServices; using System.Text;
+public class TulsFg {
+  [DllImport("user32.dll")] public static extern IntPtr GetForegroundWindow();
+  [DllImport("user32.dll")] public static extern int GetWindowThreadProcessId(IntPtr h, out int pid);
+  [DllImport("user32.dll", CharSet=CharSet.Unicode)] public static extern int GetWindowText(IntPtr h, StringBuilder s, int n);
+}
+"@
+$h = [TulsFg]::GetForegroundWindow(); $p = 0
+[void][TulsFg]::GetWindowThreadProcessId($h, [ref]$p)
+$sb = New-Object Text.StringBuilder 512; [void][TulsFg]::GetWindowText($h, $sb, 512)
+(Get-Process -Id $p).ProcessName; $p; $sb.ToString()"#;
+
+    let out = run("powershell", &["-NoProfile", "-NonInteractive", "-Command", SCRIPT])?;
+    let mut lines = out.lines();
+    let app_name = non_empty(lines.next())?;
+    let pid = lines.next().and_then(|l| l.trim().parse().ok());
+    let window_title = non_empty(lines.next());
+
+    Some(ActiveContext { app_name, pid, window_title, document_path: None, project_root: None })
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn detect_platform() -> Option<ActiveContext> {
+    let pid: Option<u32> =
+        run("xdotool", &["getactivewindow", "getwindowpid"]).and_then(|p| p.parse().ok());
+    let window_title = non_empty(run("xdotool", &["getactivewindow", "getwindowname"]).as_deref());
+    let app_name = pid
+        .and_then(|pid| std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok())
+        .map(|name| name.trim().to_string())
+        .or_else(|| window_title.clone())?;
+
+    Some(ActiveContext { app_name, pid, window_title, document_path: None, project_root: None })
+}
+
+/// Return the frontmost application (and, if enabled, its document and
+/// project). While Tulsbot itself is frontmost the last external app is
+/// returned, so the popover sees where the user came from.
+#[instrumented]
+#[tauri::command]
+pub async fn get_active_context(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Option<ActiveContext>, AppError> {
+    refresh_active_context(&app).await;
+    let ctx = state.active_context.lock_or_recover();
+    Ok(ctx.clone())
+}
+
+pub async fn refresh_active_context(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let include_document = state
+        .settings
+        .lock()
+        .map(|s| s.share_document_path)
+        .unwrap_or(false);
+
+    let detected = tauri::async_runtime::spawn_blocking(move || detect(include_document))
+        .await
+        .ok()
+        .flatten();
+    let Some(ctx) = detected else {
+        return;
+    };
+    if ctx.pid == Some(std::process::id()) {
+        return;
+    }
+
+    let changed = match state.active_context.lock() {
+        Ok(mut current) if current.as_ref() != Some(&ctx) => {
+            *current = Some(ctx.clone());
+            true
+        }
+        _ => false,
+    };
+    if changed {
+        let _ = app.emit("active-context-changed", &ctx);
+        apply_app_rules(app);
+    }
+}
+
+/// Pick the rule for the frontmost app and emit `app-rule-changed` when it
+/// differs from the one in effect.
+pub fn apply_app_rules(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let context = state.active_context.lock_or_recover().clone();
+    let rule = context.and_then(|context| {
+        let settings = state.settings.lock_or_recover();
+        app_rules::matching(&settings.app_rules, &context).cloned()
+    });
+    let mut active = state.active_rule.lock_or_recover();
+    if *active != rule {
+        active.clone_from(&rule);
+        drop(active);
+        let _ = app.emit("app-rule-changed", &rule);
+    }
+}
+
+/// The rule for the frontmost app in effect, if any.
+#[instrumented]
+#[tauri::command]
+pub async fn get_active_rule(state: State<'_, AppState>) -> Result<Option<AppRule>, AppError> {
+    let rule = state.active_rule.lock_or_recover();
+    Ok(rule.clone())
+}
+
+/// Fail with `forbidden` and `details.permission` unless the OS lets us read
+/// from and type into other apps; on macOS the Accessibility settings are
+/// opened for the user to grant it.
+pub async fn check_input_permission() -> Result<(), AppError> {
+    if tauri::async_runtime::spawn_blocking(typing::permitted).await.unwrap_or(false) {
+        return Ok(());
+    }
+    tauri::async_runtime::spawn_blocking(typing::request_permission);
+    let message = "Tulsbot needs the Accessibility permission to work with other apps";
+    Err(AppError::new(ErrorKind::Forbidden, message)
+        .with_details(serde_json::json!({ "permission": "accessibility" })))
+}
+
+/// Insert `text` at the cursor of the app the user was in before the
+/// popover, pasting it (default) or typing it key by key.
+#[instrumented]
+#[tauri::command]
+pub async fn insert_text_at_cursor(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    text: String,
+    mode: Option<InsertMode>,
+) -> Result<(), AppError> {
+    if text.is_empty() {
+        return Err(AppError::new(ErrorKind::InvalidInput, "Nothing to insert"));
+    }
+    check_input_permission().await?;
+    let target = state.active_context.lock_or_recover().as_ref().and_then(|c| c.pid);
+    // Hand focus back to the app the text is meant for
+    if let Some(popover) = app.get_webview_window("chat-popover") {
+        let _ = popover.hide();
+    }
+    let mode = mode.unwrap_or_default();
+    Ok(tauri::async_runtime::spawn_blocking(move || typing::insert(&text, target, mode))
+        .await
+        .map_err(|e| e.to_string())??)
+}
+
+/// Text selected in the frontmost app, or in the app before the popover
+/// while Tulsbot is in front, without the user copying it first. Apps that
+/// don't expose their selection get a simulated copy unless `copy_fallback`
+/// is false; that needs the app in front, so not while Tulsbot is.
+#[instrumented]
+#[tauri::command]
+pub async fn get_selected_text(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    copy_fallback: Option<bool>,
+) -> Result<Option<SelectedText>, AppError> {
+    check_input_permission().await?;
+    refresh_active_context(&app).await;
+    let target = state.active_context.lock_or_recover().as_ref().and_then(|c| c.pid);
+    let in_front = app.webview_windows().values().any(|w| w.is_focused().unwrap_or(false));
+    let copy_fallback = copy_fallback.unwrap_or(true) && !in_front;
+    Ok(tauri::async_runtime::spawn_blocking(move || selection::read(target, copy_fallback))
+        .await
+        .map_err(|e| e.to_string())?)
+}
@@ -0,0 +1,253 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use tulsbot_macros::instrumented;
+
+use crate::conversations::{self, append_message, effective_config, Conversation, Message};
+use crate::embeddings::embed;
+use crate::error::AppError;
+use crate::locks::LockExt;
+use crate::memories::approved_memories;
+use crate::profiles::active_data_dir;
+use crate::prompts::resolve_prompt;
+use crate::providers::{run_completion, ChatMessage, ChatRequest};
+use crate::qdrant::{self, qdrant_target, ScoredChunk};
+use crate::state::AppState;
+
+// ── Prompt assembly under a token budget ────────────────────────────────────
+//
+// Priority when the budget runs out: system prompt and the latest message
+// always go in, then facts (memories, pinned messages), then retrieved chunks by score, then
+// older history newest-first.
+
+pub const DEFAULT_BUDGET_TOKENS: usize = 8000;
+pub const DEFAULT_RAG_LIMIT: usize = 5;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContextSettings {
+    /// Prompt size limit in (estimated) tokens.
+    pub budget_tokens: Option<usize>,
+    pub system_prompt: Option<String>,
+    /// Qdrant collections searched for relevant chunks; empty disables RAG.
+    pub rag_collections: Vec<String>,
+    /// Chunks retrieved per collection.
+    pub rag_limit: Option<usize>,
+}
+
+/// Rough token count: about four characters per token.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4) + 4
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkRef {
+    pub collection: String,
+    pub id: String,
+    pub score: f32,
+}
+
+/// What was assembled and what was left out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuiltContext {
+    pub messages: Vec<ChatMessage>,
+    pub estimated_tokens: usize,
+    pub budget_tokens: usize,
+    pub history_included: usize,
+    pub history_dropped: usize,
+    pub facts_included: usize,
+    pub facts_dropped: usize,
+    pub chunks: Vec<ChunkRef>,
+    pub chunks_dropped: usize,
+}
+
+pub fn assemble(
+    system_prompt: Option<&str>,
+    facts: &[String],
+    chunks: &[ScoredChunk],
+    thread: &[&Message],
+    budget: usize,
+) -> BuiltContext {
+    let mut used = system_prompt.map(estimate_tokens).unwrap_or(0);
+    let (latest, history) = thread.split_last().map_or((None, &[][..]), |(l, h)| (Some(*l), h));
+    used += latest.map(|m| estimate_tokens(&m.content)).unwrap_or(0);
+    let mut fits = |text: &str| {
+        let cost = estimate_tokens(text);
+        if used + cost > budget {
+            return false;
+        }
+        used += cost;
+        true
+    };
+
+    let kept_facts: Vec<&String> = facts.iter().filter(|f| fits(f)).collect();
+    let mut ranked: Vec<&ScoredChunk> = chunks.iter().collect();
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+    let kept_chunks: Vec<&ScoredChunk> = ranked.into_iter().filter(|c| fits(&c.text)).collect();
+    // Newest first, stopping at the first message that doesn't fit so the
+    // history stays contiguous
+    let kept_history = history
+        .iter()
+        .rev()
+        .take_while(|m| fits(&m.content))
+        .count();
+
+    let mut sections: Vec<String> = system_prompt
+        .filter(|s| !s.trim().is_empty())
+        .map(|s| vec![s.trim().to_string()])
+        .unwrap_or_default();
+    if !kept_facts.is_empty() {
+        let facts: Vec<String> = kept_facts.iter().map(|f| format!("- {}", f)).collect();
+        sections.push(format!("Facts to keep in mind:\n{}", facts.join("\n")));
+    }
+    if !kept_chunks.is_empty() {
+        // Numbered so replies can cite them; `ChunkRef`s keep this order
+        let chunks: Vec<String> = kept_chunks
+            .iter()
+            .enumerate()
+            .map(|(i, c)| format!("[{}] {}", i + 1, c.text))
+            .collect();
+        sections.push(format!(
+            "Relevant context (cite as [n] where you use it):\n---\n{}",
+            chunks.join("\n---\n")
+        ));
+    }
+
+    let mut messages = Vec::new();
+    if !sections.is_empty() {
+        messages.push(ChatMessage::new("system", sections.join("\n\n")));
+    }
+    for message in history[history.len() - kept_history..].iter().chain(latest.as_ref()) {
+        messages.push(ChatMessage::new(&message.role, message.content.clone()));
+    }
+
+    BuiltContext {
+        estimated_tokens: messages.iter().map(|m| estimate_tokens(&m.content)).sum(),
+        messages,
+        budget_tokens: budget,
+        history_included: kept_history,
+        history_dropped: history.len() - kept_history,
+        facts_included: kept_facts.len(),
+        facts_dropped: facts.len() - kept_facts.len(),
+        chunks: kept_chunks
+            .iter()
+            .map(|c| ChunkRef {
+                collection: c.collection.clone(),
+                id: c.id.clone(),
+                score: c.score,
+            })
+            .collect(),
+        chunks_dropped: chunks.len() - kept_chunks.len(),
+    }
+}
+
+/// Assemble the provider prompt for the conversation's active thread:
+/// recent history, approved memories and pinned messages as facts, and
+/// chunks retrieved from the configured Qdrant collections, under the token
+/// budget.
+pub async fn build_context(
+    app: &AppHandle,
+    conversation: &Conversation,
+) -> Result<BuiltContext, String> {
+    let state = app.state::<AppState>();
+    let settings = state.settings.lock_or_recover().context.clone();
+    let system_prompt = resolve_prompt(app, Some(conversation))?.map(|p| p.content);
+    let thread = conversation.active_thread();
+    let mut facts = approved_memories(app)?;
+    facts.extend(
+        conversation
+            .messages
+            .iter()
+            .filter(|m| m.pinned)
+            .map(|m| m.content.clone()),
+    );
+
+    let mut chunks = Vec::new();
+    let query = thread.iter().rev().find(|m| m.role == "user");
+    if let (Some(query), false) = (query, settings.rag_collections.is_empty()) {
+        match (qdrant_target(app), embed(app, vec![query.content.clone()]).await) {
+            (Ok((port, _)), Ok(embedded)) => {
+                let limit = settings.rag_limit.unwrap_or(DEFAULT_RAG_LIMIT);
+                let vector = embedded.vectors.into_iter().next().unwrap_or_default();
+                for collection in &settings.rag_collections {
+                    match qdrant::search(port, collection, &vector, limit).await {
+                        Ok(found) => chunks.extend(found),
+                        Err(e) => eprintln!("[tulsbot] Search in {} failed: {}", collection, e),
+                    }
+                }
+            }
+            (Err(e), _) | (_, Err(e)) => eprintln!("[tulsbot] Retrieval skipped: {}", e),
+        }
+    }
+
+    Ok(assemble(
+        system_prompt.as_deref(),
+        &facts,
+        &chunks,
+        &thread,
+        settings.budget_tokens.unwrap_or(DEFAULT_BUDGET_TOKENS),
+    ))
+}
+
+/// What would be sent for the conversation right now.
+#[instrumented]
+#[tauri::command]
+pub async fn preview_context(
+    app: AppHandle,
+    conversation: String,
+) -> Result<BuiltContext, AppError> {
+    let stored = conversations::load(&active_data_dir(&app)?, &conversation)?;
+    Ok(build_context(&app, &stored).await?)
+}
+
+/// Exactly what was last sent to the provider for the conversation.
+#[instrumented]
+#[tauri::command]
+pub async fn get_last_context(
+    state: State<'_, AppState>,
+    conversation: String,
+) -> Result<Option<BuiltContext>, AppError> {
+    let last = state.last_context.lock_or_recover();
+    Ok(last.get(&conversation).cloned())
+}
+
+/// Answer the active thread with the conversation's provider and append
+/// the reply.
+#[instrumented]
+#[tauri::command]
+pub async fn complete_conversation(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    conversation: String,
+) -> Result<Message, AppError> {
+    let stored = conversations::load(&active_data_dir(&app)?, &conversation)?;
+    let config = effective_config(&state, &stored.config)?;
+    let provider = config.provider.ok_or("No provider configured for this conversation")?;
+    let model = config.model.ok_or("No model configured for this conversation")?;
+
+    let context = build_context(&app, &stored).await?;
+    let request = ChatRequest {
+        provider: provider.clone(),
+        model: model.clone(),
+        temperature: config.temperature,
+        max_tokens: None,
+        messages: context.messages.clone(),
+        credential: config.credential,
+    };
+    state
+        .last_context
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(conversation.clone(), context);
+    let reply = run_completion(&app, &request, Some(&conversation)).await?;
+    append_message(
+        app,
+        state,
+        conversation,
+        "assistant".into(),
+        reply.content,
+        Some(provider),
+        Some(model),
+        None,
+    )
+    .await
+}
@@ -0,0 +1,259 @@
+use std::path::PathBuf;
+
+// ── "Ask Tulsbot about this file" file-manager integration ──────────────────
+//
+// Every platform entry launches us with `--ask <file>...`; the share/activation
+// path relays that to a running instance, which ingests the files and opens
+// the popover on a new conversation.
+
+pub const MENU_TITLE: &str = "Ask Tulsbot about this file";
+
+fn exe_path() -> Result<PathBuf, String> {
+    std::env::current_exe().map_err(|e| e.to_string())
+}
+
+// ── macOS: Finder Quick Action (Services menu) ──────────────────────────────
+
+#[cfg(target_os = "macos")]
+fn workflow_dir() -> Result<PathBuf, String> {
+    let home = dirs::home_dir().ok_or("No home directory")?;
+    Ok(home.join("Library/Services").join(format!("{}.workflow", MENU_TITLE)))
+}
+
+#[cfg(target_os = "macos")]
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(target_os = "macos")]
+const INFO_PLIST: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>NSServices</key>
+	<array>
+		<dict>
+			<key>NSMenuItem</key>
+			<dict>
+				<key>default</key>
+				<string>{title}</string>
+			</dict>
+			<key>NSMessage</key>
+			<string>runWorkflowAsService</string>
+			<key>NSRequiredContext</key>
+			<dict>
+				<key>NSApplicationIdentifier</key>
+				<string>com.apple.finder</string>
+			</dict>
+			<key>NSSendFileTypes</key>
+			<array>
+				<string>public.item</string>
+			</array>
+		</dict>
+	</array>
+</dict>
+</plist>
+"#;
+
+#[cfg(target_os = "macos")]
+const WORKFLOW: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>AMApplicationBuild</key>
+	<string>521</string>
+	<key>AMApplicationVersion</key>
+	<string>2.10</string>
+	<key>AMDocumentVersion</key>
+	<string>2</string>
+	<key>actions</key>
+	<array>
+		<dict>
+			<key>action</key>
+			<dict>
+				<key>AMAccepts</key>
+				<dict>
+					<key>Container</key>
+					<string>List</string>
+					<key>Optional</key>
+					<true/>
+					<key>Types</key>
+					<array>
+						<string>com.apple.cocoa.path</string>
+					</array>
+				</dict>
+				<key>AMActionVersion</key>
+				<string>2.0.3</string>
+				<key>AMProvides</key>
+				<dict>
+					<key>Container</key>
+					<string>List</string>
+					<key>Types</key>
+					<array>
+						<string>com.apple.cocoa.string</string>
+					</array>
+				</dict>
+				<key>ActionBundlePath</key>
+				<string>/System/Library/Automator/Run Shell Script.action</string>
+				<key>ActionName</key>
+				<string>Run Shell Script</string>
+				<key>ActionParameters</key>
+				<dict>
+					<key>COMMAND_STRING</key>
+					<string>{command}</string>
+					<key>CheckedForUserDefaultShell</key>
+					<true/>
+					<key>inputMethod</key>
+					<integer>1</integer>
+					<key>shell</key>
+					<string>/bin/sh</string>
+					<key>source</key>
+					<string></string>
+				</dict>
+				<key>BundleIdentifier</key>
+				<string>com.apple.RunShellScript</string>
+				<key>CFBundleVersion</key>
+				<string>2.0.3</string>
+				<key>Class Name</key>
+				<string>RunShellScriptAction</string>
+			</dict>
+		</dict>
+	</array>
+	<key>connectors</key>
+	<dict/>
+	<key>workflowMetaData</key>
+	<dict>
+		<key>serviceInputTypeIdentifier</key>
+		<string>com.apple.Automator.fileSystemObject</string>
+		<key>serviceOutputTypeIdentifier</key>
+		<string>com.apple.Automator.nothing</string>
+		<key>workflowTypeIdentifier</key>
+		<string>com.apple.Automator.servicesMenu</string>
+	</dict>
+</dict>
+</plist>
+"#;
+
+#[cfg(target_os = "macos")]
+pub fn install() -> Result<(), String> {
+    let exe = exe_path()?;
+    let dir = workflow_dir()?.join("Contents");
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let command = format!("'{}' --ask \"$@\"", exe.display().to_string().replace('\'', "'\\''"));
+    let info = INFO_PLIST.replace("{title}", &xml_escape(MENU_TITLE));
+    let workflow = WORKFLOW.replace("{command}", &xml_escape(&command));
+    std::fs::write(dir.join("Info.plist"), info).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join("document.wflow"), workflow).map_err(|e| e.to_string())?;
+
+    // Refresh the Services menu so the entry appears without logging out
+    let _ = std::process::Command::new("/System/Library/CoreServices/pbs")
+        .arg("-update")
+        .status();
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+pub fn uninstall() -> Result<(), String> {
+    let dir = workflow_dir()?;
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
+    }
+    let _ = std::process::Command::new("/System/Library/CoreServices/pbs")
+        .arg("-update")
+        .status();
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+pub fn is_installed() -> bool {
+    workflow_dir().map(|dir| dir.exists()).unwrap_or(false)
+}
+
+// ── Windows: Explorer shell verb for all files ──────────────────────────────
+
+#[cfg(target_os = "windows")]
+const SHELL_KEY: &str = "HKCU\\Software\\Classes\\*\\shell\\AskTulsbot";
+
+#[cfg(target_os = "windows")]
+fn reg(args: &[&str]) -> Result<(), String> {
+    let status = std::process::Command::new("reg")
+        .args(args)
+        .status()
+        .map_err(|e| e.to_string())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("reg {} failed", args.first().unwrap_or(&"")))
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub fn install() -> Result<(), String> {
+    let exe = exe_path()?.display().to_string();
+    let command = format!("\"{}\" --ask \"%1\"", exe);
+    let command_key = format!("{}\\command", SHELL_KEY);
+    reg(&["add", SHELL_KEY, "/ve", "/d", MENU_TITLE, "/f"])?;
+    reg(&["add", SHELL_KEY, "/v", "Icon", "/d", &exe, "/f"])?;
+    reg(&["add", &command_key, "/ve", "/d", &command, "/f"])
+}
+
+#[cfg(target_os = "windows")]
+pub fn uninstall() -> Result<(), String> {
+    if is_installed() {
+        reg(&["delete", SHELL_KEY, "/f"])?;
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+pub fn is_installed() -> bool {
+    std::process::Command::new("reg")
+        .args(["query", SHELL_KEY])
+        .output()
+        .map(|out| out.status.success())
+        .unwrap_or(false)
+}
+
+// ── Linux: Nautilus script ──────────────────────────────────────────────────
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn script_path() -> Result<PathBuf, String> {
+    let data = dirs::data_dir().ok_or("No data directory")?;
+    Ok(data.join("nautilus/scripts").join(MENU_TITLE))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn install() -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let exe = exe_path()?;
+    let path = script_path()?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let script = format!(
+        "#!/bin/sh\nexec '{}' --ask \"$@\"\n",
+        exe.display().to_string().replace('\'', "'\\''")
+    );
+    std::fs::write(&path, script).map_err(|e| e.to_string())?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn uninstall() -> Result<(), String> {
+    let path = script_path()?;
+    if path.exists() {
+        std::fs::remove_file(&path).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn is_installed() -> bool {
+    script_path().map(|path| path.exists()).unwrap_or(false)
+}
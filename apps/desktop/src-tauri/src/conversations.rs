@@ -0,0 +1,653 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, State};
+use tulsbot_macros::instrumented;
+
+use crate::blobs::{self, collect_attachment_garbage, Attachment};
+use crate::error::{AppError, ErrorKind};
+use crate::guardrails::{guard_response, report_guardrails};
+use crate::jobs::{self, spawn_title_job};
+use crate::locks::LockExt;
+use crate::memories::propose_memories;
+use crate::notes::{self, update_notes};
+use crate::profiles::active_data_dir;
+use crate::state::AppState;
+use crate::transforms::{spawn_unfurl_job, transform_response, MessageMetadata};
+
+// ── Conversation store ──────────────────────────────────────────────────────
+//
+// One JSON file per conversation in `<data dir>/conversations/`. Fields added
+// later are `#[serde(default)]` so older files keep loading.
+//
+// Messages form a tree through `parent`: regenerating a reply or branching
+// from an earlier message adds a sibling instead of overwriting anything.
+// `active_leaf` is the tip of the branch the UI is showing.
+
+const DIR_NAME: &str = "conversations";
+
+/// Provider settings for a conversation. Unset fields inherit the profile
+/// default (`Settings::conversation_defaults`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConversationConfig {
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+    /// Credential label for the provider's API key.
+    pub credential: Option<String>,
+}
+
+impl ConversationConfig {
+    /// `self` with unset fields taken from `fallback`.
+    pub fn merged(&self, fallback: &ConversationConfig) -> ConversationConfig {
+        ConversationConfig {
+            provider: self.provider.clone().or_else(|| fallback.provider.clone()),
+            model: self.model.clone().or_else(|| fallback.model.clone()),
+            temperature: self.temperature.or(fallback.temperature),
+            credential: self.credential.clone().or_else(|| fallback.credential.clone()),
+        }
+    }
+}
+
+pub fn validate_config(config: &ConversationConfig) -> Result<(), String> {
+    match config.temperature {
+        Some(t) if !(0.0..=2.0).contains(&t) => {
+            Err(format!("Temperature must be between 0 and 2, got {}", t))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// What the UI shows: the conversation's own overrides and the result of
+/// applying them to the defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigView {
+    pub overrides: ConversationConfig,
+    pub effective: ConversationConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Message {
+    pub id: String,
+    /// `user`, `assistant` or `system`.
+    pub role: String,
+    pub content: String,
+    /// Unix seconds.
+    pub created_at: u64,
+    /// Provider and model that produced an assistant message.
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Message this one follows; `None` for the first message. Files from
+    /// before branching are linear and get parents on load.
+    #[serde(default)]
+    pub parent: Option<String>,
+    /// Files in the blob store, referenced by content hash.
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    #[serde(default)]
+    pub pinned: bool,
+    /// The user's thumbs up/down on a response.
+    #[serde(default)]
+    pub rating: Option<Rating>,
+    /// What post-processing found out about an assistant message.
+    #[serde(default)]
+    pub metadata: Option<MessageMetadata>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rating {
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Conversation {
+    pub id: String,
+    pub title: String,
+    pub created_at: u64,
+    pub updated_at: u64,
+    #[serde(default)]
+    pub config: ConversationConfig,
+    #[serde(default)]
+    pub messages: Vec<Message>,
+    #[serde(default)]
+    pub active_leaf: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub summary: Option<String>,
+    /// Set once the title was generated or chosen by the user, so the
+    /// titling job leaves it alone.
+    #[serde(default)]
+    pub titled: bool,
+    /// Origin of a conversation brought in by `chat_import`, e.g.
+    /// `chatgpt:<id>`; used to skip it on the next import.
+    #[serde(default)]
+    pub imported_from: Option<String>,
+    /// Id of the stored system prompt picked for this conversation.
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+}
+
+/// One path from the root to a leaf.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchInfo {
+    pub leaf: String,
+    /// Where this branch leaves the active one; `None` for the active branch.
+    pub fork_point: Option<String>,
+    pub message_count: usize,
+    pub last_message: String,
+    pub updated_at: u64,
+    pub active: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationSummary {
+    pub id: String,
+    pub title: String,
+    pub updated_at: u64,
+    pub message_count: usize,
+    pub tags: Vec<String>,
+    pub pinned_count: usize,
+    pub summary: Option<String>,
+}
+
+pub fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Unix milliseconds.
+pub fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+pub fn new_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+impl Conversation {
+    pub fn new(title: Option<String>, config: ConversationConfig) -> Self {
+        let now = now();
+        Self {
+            id: new_id(),
+            title: title.unwrap_or_else(|| "New conversation".into()),
+            created_at: now,
+            updated_at: now,
+            config,
+            messages: Vec::new(),
+            active_leaf: None,
+            tags: Vec::new(),
+            summary: None,
+            titled: false,
+            imported_from: None,
+            system_prompt: None,
+        }
+    }
+
+    /// Give parentless messages of a pre-branching file their linear parent.
+    fn link_legacy(&mut self) {
+        let mut previous: Option<String> = None;
+        for (i, message) in self.messages.iter_mut().enumerate() {
+            if i > 0 && message.parent.is_none() {
+                message.parent = previous.clone();
+            }
+            previous = Some(message.id.clone());
+        }
+    }
+
+    pub fn message(&self, id: &str) -> Option<&Message> {
+        self.messages.iter().find(|m| m.id == id)
+    }
+
+    pub fn message_mut(&mut self, id: &str) -> Result<&mut Message, String> {
+        self.messages
+            .iter_mut()
+            .find(|m| m.id == id)
+            .ok_or_else(|| format!("Unknown message: {}", id))
+    }
+
+    /// The active leaf, or the newest message.
+    pub fn leaf(&self) -> Option<&str> {
+        self.active_leaf
+            .as_deref()
+            .filter(|id| self.message(id).is_some())
+            .or_else(|| self.messages.last().map(|m| m.id.as_str()))
+    }
+
+    /// Messages from the root down to `leaf`.
+    pub fn thread(&self, leaf: &str) -> Vec<&Message> {
+        let mut thread = Vec::new();
+        let mut next = self.message(leaf);
+        while let Some(message) = next {
+            thread.push(message);
+            next = message.parent.as_deref().and_then(|p| self.message(p));
+        }
+        thread.reverse();
+        thread
+    }
+
+    /// The branch the UI is showing.
+    pub fn active_thread(&self) -> Vec<&Message> {
+        self.leaf().map(|leaf| self.thread(leaf)).unwrap_or_default()
+    }
+
+    pub fn branches(&self) -> Vec<BranchInfo> {
+        let active: Vec<&str> = self.active_thread().iter().map(|m| m.id.as_str()).collect();
+        let active_leaf = self.leaf();
+        let mut branches: Vec<BranchInfo> = self
+            .messages
+            .iter()
+            .filter(|m| !self.messages.iter().any(|c| c.parent.as_ref() == Some(&m.id)))
+            .map(|leaf| {
+                let thread = self.thread(&leaf.id);
+                let fork_point = thread
+                    .iter()
+                    .take_while(|m| active.contains(&m.id.as_str()))
+                    .last()
+                    .map(|m| m.id.clone())
+                    .filter(|_| active_leaf != Some(leaf.id.as_str()));
+                BranchInfo {
+                    leaf: leaf.id.clone(),
+                    fork_point,
+                    message_count: thread.len(),
+                    last_message: leaf.content.chars().take(120).collect(),
+                    updated_at: leaf.created_at,
+                    active: active_leaf == Some(leaf.id.as_str()),
+                }
+            })
+            .collect();
+        branches.sort_by_key(|b| std::cmp::Reverse(b.updated_at));
+        branches
+    }
+
+    pub fn summary(&self) -> ConversationSummary {
+        ConversationSummary {
+            id: self.id.clone(),
+            title: self.title.clone(),
+            updated_at: self.updated_at,
+            message_count: self.messages.len(),
+            tags: self.tags.clone(),
+            pinned_count: self.messages.iter().filter(|m| m.pinned).count(),
+            summary: self.summary.clone(),
+        }
+    }
+}
+
+/// Trimmed, lowercased and deduplicated tags; empty ones are dropped.
+pub fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut tags: Vec<String> = tags
+        .into_iter()
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+fn path(data_dir: &Path, id: &str) -> Result<PathBuf, String> {
+    let valid = !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    if !valid {
+        return Err(format!("Invalid conversation id: {}", id));
+    }
+    Ok(data_dir.join(DIR_NAME).join(format!("{}.json", id)))
+}
+
+pub fn load(data_dir: &Path, id: &str) -> Result<Conversation, String> {
+    let path = path(data_dir, id)?;
+    let text = std::fs::read_to_string(&path).map_err(|_| format!("Unknown conversation: {}", id))?;
+    let mut conversation: Conversation =
+        serde_json::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+    conversation.link_legacy();
+    Ok(conversation)
+}
+
+pub fn save(data_dir: &Path, conversation: &Conversation) -> Result<(), String> {
+    let path = path(data_dir, &conversation.id)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string_pretty(conversation).map_err(|e| e.to_string())?;
+    std::fs::write(&path, text).map_err(|e| e.to_string())
+}
+
+pub fn delete(data_dir: &Path, id: &str) -> Result<(), String> {
+    std::fs::remove_file(path(data_dir, id)?).map_err(|e| e.to_string())
+}
+
+/// All conversations, most recently updated first. Unreadable files are
+/// skipped.
+pub fn list(data_dir: &Path) -> Vec<Conversation> {
+    let mut conversations: Vec<Conversation> = std::fs::read_dir(data_dir.join(DIR_NAME))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|e| e == "json"))
+        .filter_map(|entry| std::fs::read_to_string(entry.path()).ok())
+        .filter_map(|text| serde_json::from_str(&text).ok())
+        .collect();
+    conversations.sort_by_key(|c| std::cmp::Reverse(c.updated_at));
+    conversations
+}
+
+/// Conversations, newest first, optionally only those tagged `tag` and/or
+/// with pinned messages.
+#[instrumented]
+#[tauri::command]
+pub async fn list_conversations(
+    app: AppHandle,
+    tag: Option<String>,
+    pinned_only: Option<bool>,
+) -> Result<Vec<ConversationSummary>, AppError> {
+    let dir = active_data_dir(&app)?;
+    let tag = tag.map(|t| t.trim().to_lowercase());
+    Ok(list(&dir)
+        .iter()
+        .map(Conversation::summary)
+        .filter(|c| tag.as_ref().is_none_or(|t| c.tags.contains(t)))
+        .filter(|c| !pinned_only.unwrap_or(false) || c.pinned_count > 0)
+        .collect())
+}
+
+/// Every tag in use with its number of conversations.
+#[instrumented]
+#[tauri::command]
+pub async fn list_tags(app: AppHandle) -> Result<Vec<(String, usize)>, AppError> {
+    let mut counts = std::collections::BTreeMap::new();
+    for conversation in list(&active_data_dir(&app)?) {
+        for tag in conversation.tags {
+            *counts.entry(tag).or_insert(0) += 1;
+        }
+    }
+    Ok(counts.into_iter().collect())
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn set_conversation_tags(
+    app: AppHandle,
+    conversation: String,
+    tags: Vec<String>,
+) -> Result<Vec<String>, AppError> {
+    let dir = active_data_dir(&app)?;
+    let mut stored = load(&dir, &conversation)?;
+    stored.tags = normalize_tags(tags);
+    save(&dir, &stored)?;
+    Ok(stored.tags)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn pin_message(
+    app: AppHandle,
+    conversation: String,
+    message: String,
+    pinned: bool,
+) -> Result<(), AppError> {
+    let dir = active_data_dir(&app)?;
+    let mut stored = load(&dir, &conversation)?;
+    stored.message_mut(&message)?.pinned = pinned;
+    Ok(save(&dir, &stored)?)
+}
+
+/// Thumbs up/down on a response; `None` clears the rating.
+#[instrumented]
+#[tauri::command]
+pub async fn rate_message(
+    app: AppHandle,
+    conversation: String,
+    message: String,
+    rating: Option<Rating>,
+) -> Result<(), AppError> {
+    let dir = active_data_dir(&app)?;
+    let mut stored = load(&dir, &conversation)?;
+    let target = stored.message_mut(&message)?;
+    if target.role != "assistant" {
+        return Err(AppError::new(ErrorKind::InvalidInput, "Only responses can be rated"));
+    }
+    target.rating = rating;
+    Ok(save(&dir, &stored)?)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn get_conversation(app: AppHandle, id: String) -> Result<Conversation, AppError> {
+    Ok(load(&active_data_dir(&app)?, &id)?)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn create_conversation(
+    app: AppHandle,
+    title: Option<String>,
+    config: Option<ConversationConfig>,
+) -> Result<Conversation, AppError> {
+    let config = config.unwrap_or_default();
+    validate_config(&config)?;
+    let conversation = Conversation::new(title, config);
+    save(&active_data_dir(&app)?, &conversation)?;
+    Ok(conversation)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn delete_conversation(app: AppHandle, id: String) -> Result<(), AppError> {
+    let dir = active_data_dir(&app)?;
+    delete(&dir, &id)?;
+    if notes::load(&dir).iter().any(|n| n.conversations.contains(&id)) {
+        update_notes(&app, |notes| {
+            notes.iter_mut().for_each(|n| n.conversations.retain(|c| *c != id));
+            Ok(())
+        })?;
+    }
+    collect_attachment_garbage(&app).await?;
+    Ok(())
+}
+
+/// Add a message after the active leaf. Assistant messages record the provider and model they
+/// came from, defaulting to the conversation's effective config.
+#[instrumented]
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn append_message(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    conversation: String,
+    role: String,
+    content: String,
+    provider: Option<String>,
+    model: Option<String>,
+    attachments: Option<Vec<Attachment>>,
+) -> Result<Message, AppError> {
+    let dir = active_data_dir(&app)?;
+    let attachments = attachments.unwrap_or_default();
+    if let Some(missing) = attachments.iter().find(|a| !blobs::exists(&dir, &a.id)) {
+        let message = format!("Unknown attachment: {}", missing.id);
+        return Err(AppError::new(ErrorKind::NotFound, message));
+    }
+    let mut stored = load(&dir, &conversation)?;
+    let effective = effective_config(&state, &stored.config)?;
+    let assistant = role == "assistant";
+    let (content, metadata) = if assistant {
+        let filtered = guard_response(&app, &content);
+        report_guardrails(&app, &filtered, &conversation);
+        transform_response(&app, Some(&conversation), filtered.text)
+    } else {
+        (content, None)
+    };
+    let message = Message {
+        id: new_id(),
+        role,
+        content,
+        created_at: now(),
+        provider: provider.or_else(|| effective.provider.filter(|_| assistant)),
+        model: model.or_else(|| effective.model.filter(|_| assistant)),
+        parent: stored.leaf().map(String::from),
+        attachments,
+        pinned: false,
+        rating: None,
+        metadata,
+    };
+    stored.messages.push(message.clone());
+    stored.active_leaf = Some(message.id.clone());
+    stored.updated_at = message.created_at;
+    save(&dir, &stored)?;
+    if message.role == "user" {
+        propose_memories(&app, &dir, &stored.id, &message);
+    }
+    if !stored.titled && stored.messages.len() >= jobs::TITLE_AFTER_MESSAGES {
+        spawn_title_job(&app, stored.id.clone());
+    }
+    spawn_unfurl_job(&app, &stored.id, &message);
+    Ok(message)
+}
+
+/// Set the title by hand; the titling job won't replace it.
+#[instrumented]
+#[tauri::command]
+pub async fn rename_conversation(
+    app: AppHandle,
+    id: String,
+    title: String,
+) -> Result<(), AppError> {
+    let dir = active_data_dir(&app)?;
+    let mut stored = load(&dir, &id)?;
+    stored.title = title;
+    stored.titled = true;
+    Ok(save(&dir, &stored)?)
+}
+
+/// Move the active leaf to `leaf` and save, returning the new thread.
+pub fn set_active_leaf(
+    app: &AppHandle,
+    conversation: &str,
+    leaf: Option<String>,
+) -> Result<Vec<Message>, String> {
+    let dir = active_data_dir(app)?;
+    let mut stored = load(&dir, conversation)?;
+    if let Some(id) = &leaf {
+        stored
+            .message(id)
+            .ok_or_else(|| format!("Unknown message: {}", id))?;
+    }
+    stored.active_leaf = leaf;
+    save(&dir, &stored)?;
+    Ok(stored.active_thread().into_iter().cloned().collect())
+}
+
+/// Prepare a new answer to the prompt behind assistant message `message`:
+/// the active leaf moves to that prompt and the thread up to it is returned
+/// for the provider call. The reply is then added with `append_message` as
+/// a sibling, keeping the old answer as another branch.
+#[instrumented]
+#[tauri::command]
+pub async fn regenerate_message(
+    app: AppHandle,
+    conversation: String,
+    message: String,
+) -> Result<Vec<Message>, AppError> {
+    let stored = load(&active_data_dir(&app)?, &conversation)?;
+    let target = stored
+        .message(&message)
+        .ok_or_else(|| format!("Unknown message: {}", message))?;
+    if target.role != "assistant" {
+        let message = "Only assistant messages can be regenerated";
+        return Err(AppError::new(ErrorKind::InvalidInput, message));
+    }
+    Ok(set_active_leaf(&app, &conversation, target.parent.clone())?)
+}
+
+/// Continue the conversation from `from_message`; the next appended message
+/// starts a new branch there.
+#[instrumented]
+#[tauri::command]
+pub async fn branch_conversation(
+    app: AppHandle,
+    conversation: String,
+    from_message: String,
+) -> Result<Vec<Message>, AppError> {
+    Ok(set_active_leaf(&app, &conversation, Some(from_message))?)
+}
+
+/// Show the branch ending at `leaf`.
+#[instrumented]
+#[tauri::command]
+pub async fn select_branch(
+    app: AppHandle,
+    conversation: String,
+    leaf: String,
+) -> Result<Vec<Message>, AppError> {
+    Ok(set_active_leaf(&app, &conversation, Some(leaf))?)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn list_branches(
+    app: AppHandle,
+    conversation: String,
+) -> Result<Vec<BranchInfo>, AppError> {
+    Ok(load(&active_data_dir(&app)?, &conversation)?.branches())
+}
+
+/// `overrides` applied over the profile's conversation defaults.
+pub fn effective_config(
+    state: &AppState,
+    overrides: &ConversationConfig,
+) -> Result<ConversationConfig, String> {
+    let settings = state.settings.lock_or_recover();
+    let rule = state.active_rule.lock_or_recover();
+    let defaults = match rule.as_ref() {
+        Some(rule) => rule.config.merged(&settings.conversation_defaults),
+        None => settings.conversation_defaults.clone(),
+    };
+    Ok(overrides.merged(&defaults))
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn get_conversation_config(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    conversation: String,
+) -> Result<ConfigView, AppError> {
+    let stored = load(&active_data_dir(&app)?, &conversation)?;
+    Ok(ConfigView {
+        effective: effective_config(&state, &stored.config)?,
+        overrides: stored.config,
+    })
+}
+
+/// Replace the conversation's overrides; unset fields follow the defaults.
+#[instrumented]
+#[tauri::command]
+pub async fn set_conversation_config(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    conversation: String,
+    config: ConversationConfig,
+) -> Result<ConfigView, AppError> {
+    validate_config(&config)?;
+    let dir = active_data_dir(&app)?;
+    let mut stored = load(&dir, &conversation)?;
+    stored.config = config;
+    stored.updated_at = now();
+    save(&dir, &stored)?;
+    let view = ConfigView {
+        effective: effective_config(&state, &stored.config)?,
+        overrides: stored.config,
+    };
+    let _ = app.emit(
+        "conversation-config-changed",
+        serde_json::json!({ "conversation": conversation, "config": view }),
+    );
+    Ok(view)
+}
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+use crate::providers;
+
+// ── Named provider credentials (work / personal keys) ───────────────────────
+//
+// Secrets stay in the keychain; `credentials.json` in the app data dir only
+// lists which labels exist, since keychains can't be enumerated portably.
+// The `default` label uses the original single-key account so keys saved
+// before labels existed keep working.
+
+pub const DEFAULT_LABEL: &str = "default";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Credential {
+    pub provider: String,
+    pub label: String,
+}
+
+/// A credential as listed to the UI and tray.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialInfo {
+    pub provider: String,
+    pub label: String,
+    /// Selected for this provider in the active profile.
+    pub selected: bool,
+}
+
+pub fn validate_label(label: &str) -> Result<(), String> {
+    let valid = !label.is_empty()
+        && label
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid credential label '{}': use letters, digits, '-' or '_'",
+            label
+        ))
+    }
+}
+
+/// Keychain account holding the key for `provider` under `label`.
+pub fn account(provider: &str, label: &str) -> String {
+    if label == DEFAULT_LABEL {
+        providers::keychain_account(provider)
+    } else {
+        format!("{}:{}", providers::keychain_account(provider), label)
+    }
+}
+
+fn store_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(dir.join("credentials.json"))
+}
+
+pub fn load(app: &AppHandle) -> Vec<Credential> {
+    store_path(app)
+        .and_then(|path| std::fs::read_to_string(path).map_err(|e| e.to_string()))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(app: &AppHandle, credentials: &[Credential]) -> Result<(), String> {
+    let path = store_path(app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string_pretty(credentials).map_err(|e| e.to_string())?;
+    std::fs::write(&path, text).map_err(|e| e.to_string())
+}
+
+/// Add `credential` to the index unless it's already listed.
+pub fn register(app: &AppHandle, credential: Credential) -> Result<(), String> {
+    let mut credentials = load(app);
+    if !credentials.contains(&credential) {
+        credentials.push(credential);
+        credentials.sort_by(|a, b| (&a.provider, &a.label).cmp(&(&b.provider, &b.label)));
+        save(app, &credentials)?;
+    }
+    Ok(())
+}
+
+pub fn unregister(app: &AppHandle, credential: &Credential) -> Result<(), String> {
+    let mut credentials = load(app);
+    credentials.retain(|c| c != credential);
+    save(app, &credentials)
+}
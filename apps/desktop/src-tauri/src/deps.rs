@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::health::ServiceHealth;
+use crate::profiles::ServiceDef;
+
+// ── Service dependencies (start order, blocked services, graph) ─────────────
+
+/// Reject dependencies on unknown services and cycles.
+pub fn validate(services: &[ServiceDef]) -> Result<(), String> {
+    let names: HashSet<&str> = services.iter().map(|s| s.name.as_str()).collect();
+    for service in services {
+        if let Some(missing) = service.depends_on.iter().find(|d| !names.contains(d.as_str())) {
+            return Err(format!("{} depends on unknown service {}", service.name, missing));
+        }
+    }
+    start_order(services).map(|_| ())
+}
+
+/// Services with every dependency before its dependents (stable otherwise).
+pub fn start_order(services: &[ServiceDef]) -> Result<Vec<ServiceDef>, String> {
+    let mut ordered: Vec<ServiceDef> = Vec::with_capacity(services.len());
+    let mut placed: HashSet<&str> = HashSet::new();
+    while ordered.len() < services.len() {
+        // Unknown dependencies don't hold a service back
+        let ready = services.iter().find(|s| {
+            !placed.contains(s.name.as_str())
+                && s.depends_on.iter().all(|d| {
+                    placed.contains(d.as_str()) || !services.iter().any(|o| &o.name == d)
+                })
+        });
+        let Some(ready) = ready else {
+            let stuck: Vec<&str> = services
+                .iter()
+                .map(|s| s.name.as_str())
+                .filter(|name| !placed.contains(name))
+                .collect();
+            return Err(format!("Dependency cycle between: {}", stuck.join(", ")));
+        };
+        placed.insert(&ready.name);
+        ordered.push(ready.clone());
+    }
+    Ok(ordered)
+}
+
+/// For each unhealthy service, its dependencies that are down too: the
+/// likely reason it's down.
+pub fn blocked_by(
+    services: &[ServiceDef],
+    health: &[ServiceHealth],
+) -> HashMap<String, Vec<String>> {
+    let down: HashSet<&str> = health
+        .iter()
+        .filter(|h| !h.healthy)
+        .map(|h| h.name.as_str())
+        .collect();
+    services
+        .iter()
+        .filter(|s| down.contains(s.name.as_str()))
+        .map(|s| {
+            let blockers: Vec<String> = s
+                .depends_on
+                .iter()
+                .filter(|d| down.contains(d.as_str()))
+                .cloned()
+                .collect();
+            (s.name.clone(), blockers)
+        })
+        .filter(|(_, blockers)| !blockers.is_empty())
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphNode {
+    pub name: String,
+    pub healthy: bool,
+    pub blocked_by: Vec<String>,
+}
+
+/// Edges point from a service to what it depends on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<(String, String)>,
+}
+
+pub fn graph(services: &[ServiceDef], health: &[ServiceHealth]) -> ServiceGraph {
+    ServiceGraph {
+        nodes: health
+            .iter()
+            .map(|h| GraphNode {
+                name: h.name.clone(),
+                healthy: h.healthy,
+                blocked_by: h.blocked_by.clone(),
+            })
+            .collect(),
+        edges: services
+            .iter()
+            .flat_map(|s| s.depends_on.iter().map(|d| (s.name.clone(), d.clone())))
+            .collect(),
+    }
+}
@@ -0,0 +1,252 @@
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::net::IpAddr;
+use tauri::{AppHandle, Emitter, Manager};
+use tulsbot_macros::instrumented;
+
+use crate::error::AppError;
+use crate::lan::{self, LanStatus};
+use crate::locks::LockExt;
+use crate::state::AppState;
+
+// ── mDNS advertisement and peer discovery ───────────────────────────────────
+//
+// While LAN access is on, the app announces itself as `_tulsbot._tcp` with
+// its profile, the LAN port of each exposed service and the certificate
+// fingerprint in the TXT record. Another copy of the app browsing for that
+// type lists it as a peer, ready to connect once the user enters the
+// token shown on the exposing machine; the token itself is never announced.
+
+pub const SERVICE_TYPE: &str = "_tulsbot._tcp.local.";
+/// TXT keys of exposed services are this prefix and the service name.
+const SERVICE_KEY_PREFIX: &str = "s:";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PeerService {
+    pub name: String,
+    pub port: u16,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Peer {
+    /// Full mDNS instance name; stable while the peer stays up.
+    pub id: String,
+    pub host: String,
+    pub addresses: Vec<IpAddr>,
+    pub profile: Option<String>,
+    pub services: Vec<PeerService>,
+    /// Compare with the certificate the peer presents.
+    pub fingerprint: Option<String>,
+}
+
+pub enum PeerEvent {
+    Found(Peer),
+    Lost(String),
+}
+
+/// The mDNS responder, what it announces and the peers it found.
+pub struct Discovery {
+    daemon: ServiceDaemon,
+    advertised: Option<String>,
+    pub browsing: bool,
+    pub peers: BTreeMap<String, Peer>,
+}
+
+impl Discovery {
+    pub fn new() -> Result<Self, String> {
+        let daemon = ServiceDaemon::new().map_err(|e| format!("mDNS unavailable: {}", e))?;
+        Ok(Self { daemon, advertised: None, browsing: false, peers: BTreeMap::new() })
+    }
+
+    pub fn daemon(&self) -> ServiceDaemon {
+        self.daemon.clone()
+    }
+
+    /// Announce the exposed services, replacing an earlier announcement.
+    pub fn advertise(&mut self, status: &LanStatus, profile: &str) -> Result<(), String> {
+        self.withdraw();
+        let address = status.address.ok_or("LAN access is off")?;
+        let port = status.services.first().map(|s| s.lan_port).ok_or("No services exposed")?;
+        let host = lan::local_host_name().unwrap_or_else(|| "tulsbot".into());
+        let mut properties: HashMap<String, String> = status
+            .services
+            .iter()
+            .map(|s| (format!("{}{}", SERVICE_KEY_PREFIX, s.name), s.lan_port.to_string()))
+            .collect();
+        properties.insert("profile".into(), profile.to_string());
+        if let Some(fingerprint) = &status.fingerprint {
+            properties.insert("fp".into(), fingerprint.clone());
+        }
+        let info = ServiceInfo::new(
+            SERVICE_TYPE,
+            &host,
+            &format!("{}.local.", host),
+            address,
+            port,
+            properties,
+        )
+        .map_err(|e| e.to_string())?;
+        let name = info.get_fullname().to_string();
+        self.daemon.register(info).map_err(|e| e.to_string())?;
+        self.advertised = Some(name);
+        Ok(())
+    }
+
+    pub fn withdraw(&mut self) {
+        if let Some(name) = self.advertised.take() {
+            if let Err(e) = self.daemon.unregister(&name) {
+                eprintln!("[tulsbot] Failed to withdraw mDNS announcement: {}", e);
+            }
+        }
+    }
+
+    /// Our own announcement, to leave out of the peers.
+    pub fn advertised(&self) -> Option<&str> {
+        self.advertised.as_deref()
+    }
+}
+
+/// Browse until the daemon stops browsing, reporting peers as they come
+/// and go.
+pub async fn browse(
+    daemon: ServiceDaemon,
+    mut report: impl FnMut(PeerEvent),
+) -> Result<(), String> {
+    let events = daemon.browse(SERVICE_TYPE).map_err(|e| e.to_string())?;
+    while let Ok(event) = events.recv_async().await {
+        match event {
+            ServiceEvent::ServiceResolved(info) => report(PeerEvent::Found(peer(&info))),
+            ServiceEvent::ServiceRemoved(_, name) => report(PeerEvent::Lost(name)),
+            ServiceEvent::SearchStopped(_) => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+pub fn stop_browsing(daemon: &ServiceDaemon) {
+    if let Err(e) = daemon.stop_browse(SERVICE_TYPE) {
+        eprintln!("[tulsbot] Failed to stop mDNS browsing: {}", e);
+    }
+}
+
+fn peer(info: &ServiceInfo) -> Peer {
+    let mut addresses: Vec<IpAddr> = info.get_addresses().iter().copied().collect();
+    // IPv4 first: link-local IPv6 needs a zone the URL can't carry
+    addresses.sort_by_key(|a| a.is_ipv6());
+    let host = info.get_hostname().trim_end_matches('.').to_string();
+    let authority = match addresses.first() {
+        Some(IpAddr::V4(v4)) => v4.to_string(),
+        _ => host.clone(),
+    };
+    let mut services: Vec<PeerService> = info
+        .get_properties()
+        .iter()
+        .filter_map(|property| {
+            let name = property.key().strip_prefix(SERVICE_KEY_PREFIX)?;
+            let port: u16 = property.val_str().parse().ok()?;
+            let url = format!("https://{}:{}/", authority, port);
+            Some(PeerService { name: name.to_string(), port, url })
+        })
+        .collect();
+    services.sort_by(|a, b| a.name.cmp(&b.name));
+    Peer {
+        id: info.get_fullname().to_string(),
+        host,
+        addresses,
+        profile: info.get_property_val_str("profile").map(String::from),
+        services,
+        fingerprint: info.get_property_val_str("fp").map(String::from),
+    }
+}
+
+/// Run `f` on the mDNS responder, starting it first if needed.
+pub fn with_discovery<T>(
+    app: &AppHandle,
+    f: impl FnOnce(&mut Discovery) -> Result<T, String>,
+) -> Result<T, String> {
+    let state = app.state::<AppState>();
+    let mut discovery = state.discovery.lock_or_recover();
+    if discovery.is_none() {
+        *discovery = Some(Discovery::new()?);
+    }
+    discovery.as_mut().map(f).unwrap_or_else(|| Err("mDNS unavailable".into()))
+}
+
+pub fn peers(app: &AppHandle) -> Vec<Peer> {
+    let state = app.state::<AppState>();
+    let discovery = state.discovery.lock_or_recover();
+    discovery.as_ref().map(|d| d.peers.values().cloned().collect()).unwrap_or_default()
+}
+
+/// Track a browse result and emit `peer-found` or `peer-lost`. Our own
+/// announcement is skipped.
+pub fn on_peer_event(app: &AppHandle, event: PeerEvent) {
+    let state = app.state::<AppState>();
+    let mut discovery = state.discovery.lock_or_recover();
+    let Some(discovery) = discovery.as_mut() else {
+        return;
+    };
+    match event {
+        PeerEvent::Found(peer) => {
+            if discovery.advertised() == Some(peer.id.as_str()) {
+                return;
+            }
+            discovery.peers.insert(peer.id.clone(), peer.clone());
+            let _ = app.emit("peer-found", &peer);
+        }
+        PeerEvent::Lost(id) => {
+            if discovery.peers.remove(&id).is_some() {
+                let _ = app.emit("peer-lost", serde_json::json!({ "id": id }));
+            }
+        }
+    }
+}
+
+/// Look for other copies of the app exposing their stack on the LAN.
+/// Returns the peers known so far; the rest arrive as `peer-found` events
+/// (again whenever a peer's announcement changes) and `peer-lost`.
+#[instrumented]
+#[tauri::command]
+pub async fn start_peer_discovery(app: AppHandle) -> Result<Vec<Peer>, AppError> {
+    let daemon = with_discovery(&app, |d| {
+        let start = !d.browsing;
+        d.browsing = true;
+        Ok(start.then(|| d.daemon()))
+    })?;
+    if let Some(daemon) = daemon {
+        let handle = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let browsed = browse(daemon, |event| on_peer_event(&handle, event)).await;
+            if let Err(e) = browsed {
+                eprintln!("[tulsbot] mDNS browsing failed: {}", e);
+            }
+            let state = handle.state::<AppState>();
+            let mut discovery = state.discovery.lock_or_recover();
+            if let Some(discovery) = discovery.as_mut() {
+                discovery.browsing = false;
+                discovery.peers.clear();
+            }
+        });
+    }
+    Ok(peers(&app))
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn stop_peer_discovery(app: AppHandle) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    let discovery = state.discovery.lock_or_recover();
+    if let Some(discovery) = discovery.as_ref().filter(|d| d.browsing) {
+        stop_browsing(&discovery.daemon());
+    }
+    Ok(())
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn list_peers(app: AppHandle) -> Result<Vec<Peer>, AppError> {
+    Ok(peers(&app))
+}
@@ -0,0 +1,315 @@
+use serde::{Deserialize, Serialize};
+
+// ── Popover docking ─────────────────────────────────────────────────────────
+//
+// A docked popover spans the full height of the monitor's work area along its
+// left or right edge and stays visible. "Strip" mode narrows it to a compact
+// column the UI fills with shortcuts. Where the platform has the concept, the
+// docked area is reserved so maximized windows leave it free: an AppBar on
+// Windows and a `_NET_WM_STRUT_PARTIAL` hint on X11. macOS and Wayland have no
+// such API; there the popover simply floats on top.
+//
+// The floating popover comes in three size presets. Resizing it by hand
+// overrides the preset on that monitor until another preset is picked.
+
+/// Logical width of the docked panel and of the compact strip.
+pub const PANEL_WIDTH: f64 = 380.0;
+pub const STRIP_WIDTH: f64 = 64.0;
+/// Logical gap between the floating popover and the work area's edge.
+const MARGIN: f64 = 10.0;
+/// A resized popover is remembered no smaller than this.
+const MIN_SIZE: PopoverSize = PopoverSize { width: 320.0, height: 400.0 };
+
+/// Logical size of the floating popover.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PopoverSize {
+    pub width: f64,
+    pub height: f64,
+}
+
+impl PopoverSize {
+    /// Physical size at `scale`, no larger than `work_area`.
+    fn physical(self, work_area: Rect, scale: f64) -> (u32, u32) {
+        let width = ((self.width * scale).round() as u32).min(work_area.width);
+        let height = ((self.height * scale).round() as u32).min(work_area.height);
+        (width, height)
+    }
+
+    /// The logical size of a window `width` by `height` physical pixels at
+    /// `scale`, grown to the minimum where needed.
+    pub fn from_physical(width: u32, height: u32, scale: f64) -> Self {
+        Self {
+            width: (f64::from(width) / scale).round().max(MIN_SIZE.width),
+            height: (f64::from(height) / scale).round().max(MIN_SIZE.height),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SizePreset {
+    #[default]
+    Small,
+    Medium,
+    Large,
+}
+
+impl SizePreset {
+    pub fn size(self) -> PopoverSize {
+        let (width, height) = match self {
+            SizePreset::Small => (380.0, 540.0),
+            SizePreset::Medium => (520.0, 680.0),
+            SizePreset::Large => (720.0, 860.0),
+        };
+        PopoverSize { width, height }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DockEdge {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dock {
+    pub edge: DockEdge,
+    #[serde(default)]
+    pub strip: bool,
+}
+
+/// Physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Settings key for a monitor; unnamed monitors share one entry.
+pub fn monitor_key(name: Option<&String>) -> String {
+    name.cloned().unwrap_or_else(|| "default".into())
+}
+
+/// Where the docked popover goes inside `work_area` (the monitor minus
+/// taskbars and menu bar), at `scale` physical pixels per logical pixel.
+pub fn frame(work_area: Rect, scale: f64, dock: Dock) -> Rect {
+    let logical = if dock.strip { STRIP_WIDTH } else { PANEL_WIDTH };
+    let width = ((logical * scale).round() as u32).min(work_area.width);
+    let x = match dock.edge {
+        DockEdge::Left => work_area.x,
+        DockEdge::Right => work_area.x + (work_area.width - width) as i32,
+    };
+    Rect { x, y: work_area.y, width, height: work_area.height }
+}
+
+/// Where the floating popover of logical `size` goes: the top-right corner
+/// of `work_area`, sized with that monitor's `scale` so it keeps its logical
+/// size on every monitor.
+pub fn floating_frame(work_area: Rect, scale: f64, size: PopoverSize) -> Rect {
+    let (width, height) = size.physical(work_area, scale);
+    let margin = (MARGIN * scale).round() as i32;
+    let x = (work_area.x + work_area.width as i32 - width as i32 - margin).max(work_area.x);
+    Rect { x, y: work_area.y, width, height }
+}
+
+/// Where the floating popover goes when opened from the tray icon at
+/// `anchor`: next to the icon, on the work area's side facing the taskbar,
+/// sized with the `scale` of the icon's monitor.
+pub fn anchored_frame(work_area: Rect, scale: f64, size: PopoverSize, anchor: Rect) -> Rect {
+    let (width, height) = size.physical(work_area, scale);
+    let margin = (MARGIN * scale).round() as i32;
+    let (center_x, center_y) =
+        (anchor.x + anchor.width as i32 / 2, anchor.y + anchor.height as i32 / 2);
+    let place = |center: i32, start: i32, extent: u32, size: u32| {
+        let end = start + extent as i32;
+        let max = (end - size as i32 - margin).max(start);
+        if center >= end {
+            max
+        } else if center <= start {
+            (start + margin).min(max)
+        } else {
+            (center - size as i32 / 2).clamp((start + margin).min(max), max)
+        }
+    };
+    Rect {
+        x: place(center_x, work_area.x, work_area.width, width),
+        y: place(center_y, work_area.y, work_area.height, height),
+        width,
+        height,
+    }
+}
+
+/// `frame` resized to logical `size` at `scale` and moved the least needed
+/// to lie inside `work_area`, for a floating popover that ended up on
+/// another monitor or got a new size.
+pub fn refit(frame: Rect, work_area: Rect, scale: f64, size: PopoverSize) -> Rect {
+    let (width, height) = size.physical(work_area, scale);
+    let max_x = work_area.x + (work_area.width - width) as i32;
+    let max_y = work_area.y + (work_area.height - height) as i32;
+    Rect {
+        x: frame.x.clamp(work_area.x, max_x),
+        y: frame.y.clamp(work_area.y, max_y),
+        width,
+        height,
+    }
+}
+
+// ── Windows: AppBar ─────────────────────────────────────────────────────────
+
+#[cfg(windows)]
+mod appbar {
+    use super::{DockEdge, Rect};
+
+    const ABM_NEW: u32 = 0;
+    const ABM_REMOVE: u32 = 1;
+    const ABM_QUERYPOS: u32 = 2;
+    const ABM_SETPOS: u32 = 3;
+    const ABE_LEFT: u32 = 0;
+    const ABE_RIGHT: u32 = 2;
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct WinRect {
+        left: i32,
+        top: i32,
+        right: i32,
+        bottom: i32,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct AppBarData {
+        cb_size: u32,
+        hwnd: isize,
+        callback_message: u32,
+        edge: u32,
+        rc: WinRect,
+        lparam: isize,
+    }
+
+    #[link(name = "shell32")]
+    extern "system" {
+        fn SHAppBarMessage(message: u32, data: *mut AppBarData) -> usize;
+    }
+
+    fn data(hwnd: isize) -> AppBarData {
+        AppBarData {
+            cb_size: std::mem::size_of::<AppBarData>() as u32,
+            hwnd,
+            ..Default::default()
+        }
+    }
+
+    /// Register `hwnd` as an AppBar on `edge` and return the area Windows
+    /// granted, which may be narrower than asked when other bars exist.
+    pub fn reserve(hwnd: isize, edge: DockEdge, frame: Rect) -> Rect {
+        let mut bar = data(hwnd);
+        bar.edge = match edge {
+            DockEdge::Left => ABE_LEFT,
+            DockEdge::Right => ABE_RIGHT,
+        };
+        bar.rc = WinRect {
+            left: frame.x,
+            top: frame.y,
+            right: frame.x + frame.width as i32,
+            bottom: frame.y + frame.height as i32,
+        };
+        // SAFETY: `bar` is a correctly sized APPBARDATA that outlives the calls.
+        unsafe {
+            // Fails harmlessly when already registered
+            SHAppBarMessage(ABM_NEW, &mut bar);
+            SHAppBarMessage(ABM_QUERYPOS, &mut bar);
+            // QUERYPOS moves the edge we don't own; keep our width from it
+            match edge {
+                DockEdge::Left => bar.rc.right = bar.rc.left + frame.width as i32,
+                DockEdge::Right => bar.rc.left = bar.rc.right - frame.width as i32,
+            }
+            SHAppBarMessage(ABM_SETPOS, &mut bar);
+        }
+        Rect {
+            x: bar.rc.left,
+            y: bar.rc.top,
+            width: (bar.rc.right - bar.rc.left).max(0) as u32,
+            height: (bar.rc.bottom - bar.rc.top).max(0) as u32,
+        }
+    }
+
+    pub fn release(hwnd: isize) {
+        let mut bar = data(hwnd);
+        // SAFETY: as above.
+        unsafe {
+            SHAppBarMessage(ABM_REMOVE, &mut bar);
+        }
+    }
+}
+
+#[cfg(windows)]
+pub use appbar::{release as release_appbar, reserve as reserve_appbar};
+
+// ── X11: strut hint ─────────────────────────────────────────────────────────
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn x11_windows(title: &str) -> Result<Vec<String>, String> {
+    let pattern = format!("^{}$", title);
+    let pid = std::process::id().to_string();
+    let out = std::process::Command::new("xdotool")
+        .args(["search", "--pid", &pid, "--name", &pattern])
+        .output()
+        .map_err(|e| format!("xdotool: {}", e))?;
+    let ids: Vec<String> =
+        String::from_utf8_lossy(&out.stdout).split_whitespace().map(String::from).collect();
+    if ids.is_empty() {
+        return Err(format!("No X11 window titled '{}'", title));
+    }
+    Ok(ids)
+}
+
+/// Set (or with `None`, clear) the strut of our window titled `title` so
+/// window managers keep the docked area free. Does nothing on Wayland.
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn set_strut(title: &str, area: Option<(DockEdge, Rect)>) -> Result<(), String> {
+    if crate::placement::session() == crate::placement::Session::Wayland {
+        return Ok(());
+    }
+    let mut strut = [0i64; 12];
+    if let Some((edge, frame)) = area {
+        let bottom = i64::from(frame.y) + i64::from(frame.height) - 1;
+        match edge {
+            DockEdge::Left => {
+                strut[0] = i64::from(frame.x) + i64::from(frame.width);
+                strut[4] = i64::from(frame.y);
+                strut[5] = bottom;
+            }
+            DockEdge::Right => {
+                // Distance from the right edge of the whole X screen
+                let out = std::process::Command::new("xdotool")
+                    .arg("getdisplaygeometry")
+                    .output()
+                    .map_err(|e| format!("xdotool: {}", e))?;
+                let screen_width: i64 = String::from_utf8_lossy(&out.stdout)
+                    .split_whitespace()
+                    .next()
+                    .and_then(|w| w.parse().ok())
+                    .ok_or("Could not read the X screen size")?;
+                strut[1] = screen_width - i64::from(frame.x);
+                strut[6] = i64::from(frame.y);
+                strut[7] = bottom;
+            }
+        }
+    }
+    let value = strut.iter().map(i64::to_string).collect::<Vec<_>>().join(", ");
+    for id in x11_windows(title)? {
+        let status = std::process::Command::new("xprop")
+            .args(["-id", &id, "-f", "_NET_WM_STRUT_PARTIAL", "32c"])
+            .args(["-set", "_NET_WM_STRUT_PARTIAL", &value])
+            .status()
+            .map_err(|e| format!("xprop: {}", e))?;
+        if !status.success() {
+            return Err(format!("xprop exited with {}", status));
+        }
+    }
+    Ok(())
+}
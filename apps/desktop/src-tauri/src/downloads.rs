@@ -0,0 +1,571 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tulsbot_macros::instrumented;
+
+use crate::conversations;
+use crate::embeddings::{self, models_dir};
+use crate::error::{AppError, ErrorKind};
+use crate::locks::LockExt;
+use crate::proxy::proxy_client_for;
+use crate::state::AppState;
+use crate::tray::begin_work;
+
+// ── Model downloads (resumable, verified) ───────────────────────────────────
+//
+// Every local model lives in `<app data>/models/<id>/` and is shared by all
+// profiles. Files download to `<file>.part` and resume from its length with
+// a Range request; they are renamed only once complete and verified. The
+// expected SHA256 comes from the catalog when pinned, else from Hugging
+// Face's `X-Linked-ETag` (the LFS object hash). Before starting, the
+// remaining bytes are checked against the free space on the volume.
+
+/// Free space kept in reserve on top of the download itself.
+const SPACE_MARGIN: u64 = 256 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ModelKind {
+    Embedding,
+    Whisper,
+    WakeWord,
+}
+
+pub struct RemoteFile {
+    pub name: &'static str,
+    pub url: String,
+    /// Pinned hash; `None` trusts the server's LFS hash, and a file with
+    /// neither is refused.
+    pub sha256: Option<&'static str>,
+}
+
+pub struct CatalogEntry {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub kind: ModelKind,
+    pub files: Vec<RemoteFile>,
+}
+
+/// `(id, name, file)`; all from the whisper.cpp model repository.
+const WHISPER: &[(&str, &str, &str)] = &[
+    ("whisper-base", "Whisper base", "ggml-base.bin"),
+    ("whisper-small", "Whisper small", "ggml-small.bin"),
+    ("whisper-medium", "Whisper medium", "ggml-medium.bin"),
+    ("whisper-large-v3", "Whisper large-v3", "ggml-large-v3.bin"),
+];
+
+const WHISPER_BASE_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+
+const WAKE_WORD: &[(&str, &str)] = &[
+    (
+        "melspectrogram.onnx",
+        "https://github.com/dscripka/openWakeWord/releases/download/v0.5.1/melspectrogram.onnx",
+    ),
+    (
+        "embedding_model.onnx",
+        "https://github.com/dscripka/openWakeWord/releases/download/v0.5.1/embedding_model.onnx",
+    ),
+    (
+        "hey_jarvis.onnx",
+        "https://github.com/dscripka/openWakeWord/releases/download/v0.5.1/hey_jarvis_v0.1.onnx",
+    ),
+];
+
+/// Every model the app knows how to download.
+pub fn catalog() -> Vec<CatalogEntry> {
+    let embedding = embeddings::MODELS.iter().map(|m| CatalogEntry {
+        id: m.id,
+        name: m.name,
+        kind: ModelKind::Embedding,
+        files: m
+            .files
+            .iter()
+            .map(|(name, url)| RemoteFile { name, url: url.to_string(), sha256: None })
+            .collect(),
+    });
+    let whisper = WHISPER.iter().map(|(id, name, file)| CatalogEntry {
+        id,
+        name,
+        kind: ModelKind::Whisper,
+        files: vec![RemoteFile {
+            name: file,
+            url: format!("{}/{}", WHISPER_BASE_URL, file),
+            sha256: None,
+        }],
+    });
+    let wake_word = std::iter::once(CatalogEntry {
+        id: "wake-word-hey-jarvis",
+        name: "Wake word: hey Jarvis",
+        kind: ModelKind::WakeWord,
+        files: WAKE_WORD
+            .iter()
+            .map(|(name, url)| RemoteFile { name, url: url.to_string(), sha256: None })
+            .collect(),
+    });
+    embedding.chain(whisper).chain(wake_word).collect()
+}
+
+fn entry(id: &str) -> Result<CatalogEntry, String> {
+    catalog()
+        .into_iter()
+        .find(|m| m.id == id)
+        .ok_or_else(|| format!("Unknown model: {}", id))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManagedModel {
+    pub id: String,
+    pub name: String,
+    pub kind: ModelKind,
+    pub downloaded: bool,
+    /// An interrupted download that will resume.
+    pub partial: bool,
+    /// Bytes on disk, including partial files.
+    pub size: u64,
+}
+
+pub fn list(root: &Path) -> Vec<ManagedModel> {
+    catalog()
+        .into_iter()
+        .map(|m| {
+            let dir = root.join(m.id);
+            let len = |file: String| std::fs::metadata(dir.join(file)).ok().map(|meta| meta.len());
+            let complete: Vec<Option<u64>> =
+                m.files.iter().map(|f| len(f.name.to_string())).collect();
+            let partial: Vec<u64> =
+                m.files.iter().filter_map(|f| len(format!("{}.part", f.name))).collect();
+            ManagedModel {
+                id: m.id.into(),
+                name: m.name.into(),
+                kind: m.kind,
+                downloaded: complete.iter().all(Option::is_some),
+                partial: !partial.is_empty(),
+                size: complete.iter().flatten().sum::<u64>() + partial.iter().sum::<u64>(),
+            }
+        })
+        .collect()
+}
+
+pub fn delete(root: &Path, id: &str) -> Result<(), String> {
+    let dir = root.join(entry(id)?.id);
+    match std::fs::remove_dir_all(&dir) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+        _ => Ok(()),
+    }
+}
+
+/// Free bytes on the volume holding `dir`, from `df` or PowerShell.
+async fn available_space(dir: &Path) -> Option<u64> {
+    let out = if cfg!(windows) {
+        let path = dir.display().to_string().replace('\'', "''");
+        let script = format!("(Get-Item -LiteralPath '{}').PSDrive.Free", path);
+        tokio::process::Command::new("powershell")
+            .args(["-NoProfile", "-Command", &script])
+            .output()
+            .await
+            .ok()?
+    } else {
+        tokio::process::Command::new("df").arg("-Pk").arg(dir).output().await.ok()?
+    };
+    let text = String::from_utf8_lossy(&out.stdout).into_owned();
+    if cfg!(windows) {
+        text.trim().parse().ok()
+    } else {
+        let kb: u64 = text.lines().nth(1)?.split_whitespace().nth(3)?.parse().ok()?;
+        Some(kb * 1024)
+    }
+}
+
+async fn file_len(path: &Path) -> u64 {
+    tokio::fs::metadata(path).await.map(|m| m.len()).unwrap_or(0)
+}
+
+async fn sha256_of(path: &Path) -> Result<String, String> {
+    let mut file = tokio::fs::File::open(path).await.map_err(|e| e.to_string())?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 1 << 16];
+    loop {
+        let n = file.read(&mut buf).await.map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Size and server-side SHA256 of `url`. Hugging Face puts the LFS object
+/// hash and size on its redirect, so that one is read before following it.
+async fn probe(
+    client: &reqwest::Client,
+    url: &str,
+) -> Result<(Option<u64>, Option<String>), String> {
+    let header = |resp: &reqwest::Response, name: &str| {
+        resp.headers().get(name).and_then(|v| v.to_str().ok()).map(str::to_string)
+    };
+    let first = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(|e| e.to_string())?
+        .head(url)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    let hash = header(&first, "x-linked-etag")
+        .map(|etag| etag.trim_start_matches("W/").trim_matches('"').to_ascii_lowercase())
+        .filter(|etag| etag.len() == 64 && etag.chars().all(|c| c.is_ascii_hexdigit()));
+    let linked_size = header(&first, "x-linked-size").and_then(|s| s.parse().ok());
+    let resp = if first.status().is_redirection() {
+        client.head(url).send().await.map_err(|e| e.to_string())?
+    } else {
+        first
+    };
+    if !resp.status().is_success() {
+        return Err(format!("{} is unavailable: HTTP {}", url, resp.status()));
+    }
+    Ok((linked_size.or(resp.content_length()), hash))
+}
+
+/// Download every missing file of model `id` into `root`, resuming partial
+/// files. `progress(file, received, total)` is called per chunk.
+pub async fn download(
+    root: &Path,
+    id: &str,
+    progress: impl Fn(&str, u64, Option<u64>),
+) -> Result<(), String> {
+    let info = entry(id)?;
+    let dir = root.join(info.id);
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .map_err(|e| e.to_string())?;
+    let client = reqwest::Client::new();
+
+    // Preflight: sizes of what is still missing, against the free space
+    let mut pending = Vec::new();
+    let mut needed = 0u64;
+    for file in &info.files {
+        if dir.join(file.name).exists() {
+            continue;
+        }
+        let (size, server_hash) = probe(&client, &file.url).await?;
+        let resumed = file_len(&dir.join(format!("{}.part", file.name))).await;
+        needed += size.unwrap_or(0).saturating_sub(resumed);
+        let Some(expected) = file.sha256.map(String::from).or(server_hash) else {
+            return Err(format!(
+                "{} has no known SHA256 to verify it against; refusing to download it",
+                file.name
+            ));
+        };
+        pending.push((file, size, expected));
+    }
+    if let Some(free) = available_space(&dir).await {
+        if needed + SPACE_MARGIN > free {
+            return Err(format!(
+                "Not enough disk space for {}: {} MB needed, {} MB free",
+                info.name,
+                (needed + SPACE_MARGIN) / (1024 * 1024),
+                free / (1024 * 1024)
+            ));
+        }
+    }
+
+    for (file, size, expected) in pending {
+        let target = dir.join(file.name);
+        let part = dir.join(format!("{}.part", file.name));
+        let mut offset = file_len(&part).await;
+        if size.is_some_and(|size| offset > size) {
+            offset = 0;
+        }
+        let mut request = client.get(&file.url);
+        if offset > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={}-", offset));
+        }
+        let mut resp = request.send().await.map_err(|e| e.to_string())?;
+        let status = resp.status();
+        let complete = status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE && offset > 0;
+        if !complete {
+            if !status.is_success() {
+                return Err(format!("Download of {} failed: HTTP {}", file.name, status));
+            }
+            // A plain 200 means the server ignored the range: start over
+            let resuming = status == reqwest::StatusCode::PARTIAL_CONTENT;
+            if !resuming {
+                offset = 0;
+            }
+            let mut out = tokio::fs::OpenOptions::new()
+                .create(true)
+                .write(true)
+                .append(resuming)
+                .truncate(!resuming)
+                .open(&part)
+                .await
+                .map_err(|e| e.to_string())?;
+            let total = size.or(resp.content_length().map(|len| len + offset));
+            let mut received = offset;
+            while let Some(chunk) = resp.chunk().await.map_err(|e| e.to_string())? {
+                out.write_all(&chunk).await.map_err(|e| e.to_string())?;
+                received += chunk.len() as u64;
+                progress(file.name, received, total);
+            }
+            out.flush().await.map_err(|e| e.to_string())?;
+        }
+
+        // Also when the server said the part file was already complete
+        let actual = sha256_of(&part).await?;
+        if actual != expected {
+            let _ = tokio::fs::remove_file(&part).await;
+            return Err(format!(
+                "{} failed verification (SHA256 {}, expected {}); download it again",
+                file.name, actual, expected
+            ));
+        }
+        tokio::fs::rename(&part, &target)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+pub async fn download_model_with(app: &AppHandle, id: &str, event: &str) -> Result<(), String> {
+    let root = models_dir(app)?;
+    {
+        let state = app.state::<AppState>();
+        let mut active = state.model_downloads.lock_or_recover();
+        if !active.insert(id.to_string()) {
+            return Err(format!("{} is already downloading", id));
+        }
+    }
+    let progress = |file: &str, received: u64, total: Option<u64>| {
+        let _ = app.emit(
+            event,
+            serde_json::json!({ "model": id, "file": file, "received": received, "total": total }),
+        );
+    };
+    let work = begin_work(app);
+    let result = download(&root, id, progress).await;
+    drop(work);
+    let state = app.state::<AppState>();
+    state.model_downloads.lock_or_recover().remove(id);
+    result
+}
+
+/// Every downloadable local model (embeddings, Whisper, wake word) and what
+/// is on disk for it.
+#[instrumented]
+#[tauri::command]
+pub async fn list_models(app: AppHandle) -> Result<Vec<ManagedModel>, AppError> {
+    Ok(list(&models_dir(&app)?))
+}
+
+/// Download a model, resuming an interrupted download. Emits
+/// `model-download-progress`; fails before starting when disk space is short
+/// and after finishing when the SHA256 does not match.
+#[instrumented]
+#[tauri::command]
+pub async fn download_model(app: AppHandle, id: String) -> Result<(), AppError> {
+    Ok(download_model_with(&app, &id, "model-download-progress").await?)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn delete_model(app: AppHandle, id: String) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    if state.model_downloads.lock_or_recover().contains(&id) {
+        return Err(AppError::new(ErrorKind::Busy, format!("{} is still downloading", id)));
+    }
+    let mut slot = state.embedder.lock_or_recover();
+    if slot.as_ref().is_some_and(|e| e.model == id) {
+        *slot = None;
+    }
+    Ok(delete(&models_dir(&app)?, &id)?)
+}
+
+/// `dest_path` inside the user's downloads folder: relative paths are taken
+/// from there, absolute ones must point into it, and neither `..` nor a
+/// symbolic link may lead out of it.
+fn download_dest(app: &AppHandle, dest_path: &str) -> Result<PathBuf, String> {
+    let path = Path::new(dest_path);
+    if path.file_name().is_none() || path.components().any(|c| c == Component::ParentDir) {
+        return Err(format!("Invalid download path: {}", dest_path));
+    }
+    let root = app.path().download_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&root).map_err(|e| e.to_string())?;
+    let root = root.canonicalize().map_err(|e| e.to_string())?;
+    let dest = root.join(path);
+    let mut existing = dest.as_path();
+    while std::fs::symlink_metadata(existing).is_err() {
+        existing = existing.parent().unwrap_or(&root);
+    }
+    let inside = existing.canonicalize().is_ok_and(|dir| dir.starts_with(&root));
+    if !inside || !dest.starts_with(&root) || dest.is_symlink() {
+        return Err(format!("Downloads can only be saved to {}", root.display()));
+    }
+    Ok(dest)
+}
+
+/// Download `url`, an endpoint of the active profile, straight to
+/// `dest_path` in the downloads folder without passing the body through the
+/// webview. Emits `file-download-progress` with `{ id, received, total }`;
+/// fails when `sha256` is given and doesn't match. `cancel_download(id)`
+/// stops it.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn download_file(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    url: String,
+    dest_path: String,
+    sha256: Option<String>,
+    id: Option<String>,
+) -> Result<FileDownload, AppError> {
+    let allowed = {
+        let store = state.profiles.lock_or_recover();
+        store.active_profile().allows_url(&url)
+    };
+    if !allowed {
+        let message = format!("URL not allowed by the active profile: {}", url);
+        return Err(AppError::new(ErrorKind::Forbidden, message));
+    }
+    let dest = download_dest(&app, &dest_path)
+        .map_err(|e| AppError::new(ErrorKind::Forbidden, e))?;
+    let id = id.unwrap_or_else(conversations::new_id);
+    let cancel = Arc::new(AtomicBool::new(false));
+    {
+        let mut active = state.file_downloads.lock_or_recover();
+        if active.contains_key(&id) {
+            let message = format!("{} is already downloading", id);
+            return Err(AppError::new(ErrorKind::Busy, message));
+        }
+        active.insert(id.clone(), cancel.clone());
+    }
+    let progress = |received: u64, total: Option<u64>| {
+        let _ = app.emit(
+            "file-download-progress",
+            serde_json::json!({ "id": id, "received": received, "total": total }),
+        );
+    };
+    let client = proxy_client_for(&state, &url);
+    let work = begin_work(&app);
+    let result =
+        fetch_to_file(&client, &id, &url, &dest, sha256.as_deref(), &cancel, progress)
+            .await;
+    drop(work);
+    state.file_downloads.lock_or_recover().remove(&id);
+    Ok(result?)
+}
+
+/// Stop the file download `id`; its partial file is removed. Returns whether
+/// such a download was running.
+#[instrumented]
+#[tauri::command]
+pub async fn cancel_download(state: State<'_, AppState>, id: String) -> Result<bool, AppError> {
+    let active = state.file_downloads.lock_or_recover();
+    let cancel = active.get(&id);
+    if let Some(cancel) = cancel {
+        cancel.store(true, Ordering::Relaxed);
+    }
+    Ok(cancel.is_some())
+}
+
+// ── File downloads (proxied) ────────────────────────────────────────────────
+//
+// Large backend responses, exports and the like, are written straight to disk
+// instead of crossing the IPC bridge as a string. The body is hashed while it
+// streams into `<dest>.part`, which is renamed once verified; a cancelled or
+// failed download leaves nothing behind.
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileDownload {
+    pub id: String,
+    pub path: String,
+    pub bytes: u64,
+    pub sha256: String,
+}
+
+/// Stream `url` into `dest`, checking `cancel` between chunks and the SHA256
+/// against `expected` at the end. `progress(received, total)` is called per
+/// chunk.
+pub async fn fetch_to_file(
+    client: &reqwest::Client,
+    id: &str,
+    url: &str,
+    dest: &Path,
+    expected: Option<&str>,
+    cancel: &AtomicBool,
+    progress: impl Fn(u64, Option<u64>),
+) -> Result<FileDownload, String> {
+    if let Some(parent) = dest.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    let mut resp = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+    let status = resp.status().as_u16();
+    if status >= 400 {
+        let body = resp.text().await.unwrap_or_default();
+        return Err(format!("HTTP {}: {}", status, body));
+    }
+    let total = resp.content_length();
+    let part = dest.with_extension(match dest.extension() {
+        Some(ext) => format!("{}.part", ext.to_string_lossy()),
+        None => "part".into(),
+    });
+    let result = async {
+        let mut out = tokio::fs::File::create(&part)
+            .await
+            .map_err(|e| e.to_string())?;
+        let mut hasher = Sha256::new();
+        let mut received = 0u64;
+        while let Some(chunk) = resp
+            .chunk()
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?
+        {
+            if cancel.load(Ordering::Relaxed) {
+                return Err("Download cancelled".to_string());
+            }
+            out.write_all(&chunk).await.map_err(|e| e.to_string())?;
+            hasher.update(&chunk);
+            received += chunk.len() as u64;
+            progress(received, total);
+        }
+        out.flush().await.map_err(|e| e.to_string())?;
+        Ok((received, hex::encode(hasher.finalize())))
+    }
+    .await;
+    let (bytes, actual) = match result {
+        Ok(done) => done,
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&part).await;
+            return Err(e);
+        }
+    };
+    if let Some(expected) = expected {
+        if !actual.eq_ignore_ascii_case(expected.trim()) {
+            let _ = tokio::fs::remove_file(&part).await;
+            return Err(format!(
+                "{} failed verification (SHA256 {}, expected {})",
+                dest.display(),
+                actual,
+                expected
+            ));
+        }
+    }
+    tokio::fs::rename(&part, dest)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(FileDownload {
+        id: id.to_string(),
+        path: dest.display().to_string(),
+        bytes,
+        sha256: actual,
+    })
+}
@@ -0,0 +1,188 @@
+use std::path::PathBuf;
+use std::process::Command;
+use tulsbot_macros::instrumented;
+
+use crate::error::AppError;
+
+// ── Email drafts in the system mail client ──────────────────────────────────
+//
+// Nothing is sent from here: the draft opens prefilled for the user to
+// review and send. Without attachments a `mailto:` URL goes to the default
+// mail client. `mailto:` cannot carry files, so with attachments the draft
+// is created through Mail.app (AppleScript) on macOS, Outlook (COM) on
+// Windows and `xdg-email` on Linux.
+
+pub struct Draft {
+    pub to: Vec<String>,
+    pub subject: String,
+    pub body: String,
+    pub attachments: Vec<PathBuf>,
+}
+
+impl Draft {
+    pub fn validate(&self) -> Result<(), String> {
+        for address in &self.to {
+            let valid = address.contains('@')
+                && !address.chars().any(|c| c.is_whitespace() || matches!(c, ',' | '?' | '&'));
+            if !valid {
+                return Err(format!("Invalid email address: {}", address));
+            }
+        }
+        for path in &self.attachments {
+            if !path.is_file() {
+                return Err(format!("Attachment not found: {}", path.display()));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// `mailto:` URL for a draft without attachments. Line breaks become CRLF
+/// as RFC 6068 asks.
+pub fn mailto(draft: &Draft) -> String {
+    let body = draft.body.replace("\r\n", "\n").replace('\n', "\r\n");
+    format!(
+        "mailto:{}?subject={}&body={}",
+        draft.to.join(","),
+        percent_encode(&draft.subject),
+        percent_encode(&body)
+    )
+}
+
+fn run(program: &str, args: &[String], env: &[(&str, String)]) -> Result<(), String> {
+    let out = Command::new(program)
+        .args(args)
+        .envs(env.iter().map(|(k, v)| (*k, v)))
+        .output()
+        .map_err(|e| format!("{}: {}", program, e))?;
+    if out.status.success() {
+        Ok(())
+    } else {
+        Err(format!("{}: {}", program, String::from_utf8_lossy(&out.stderr).trim()))
+    }
+}
+
+#[cfg(target_os = "macos")]
+const MAIL_SCRIPT: &str = r#"
+on run argv
+    set AppleScript's text item delimiters to ","
+    set addressList to text items of (item 3 of argv)
+    tell application "Mail"
+        set newMessage to make new outgoing message with properties ¬
+            {subject:(item 1 of argv), content:(item 2 of argv), visible:true}
+        tell newMessage
+            repeat with anAddress in addressList
+                if length of anAddress > 0 then
+                    make new to recipient at end of to recipients ¬
+                        with properties {address:(anAddress as text)}
+                end if
+            end repeat
+            repeat with i from 4 to count of argv
+                make new attachment with properties ¬
+                    {file name:((POSIX file (item i of argv)) as alias)} ¬
+                    at after the last paragraph
+            end repeat
+        end tell
+        activate
+    end tell
+end run
+"#;
+
+#[cfg(windows)]
+const OUTLOOK_SCRIPT: &str = r#"
+$mail = (New-Object -ComObject Outlook.Application).CreateItem(0)
+$mail.To = $env:TULSBOT_MAIL_TO -replace ',', ';'
+$mail.Subject = $env:TULSBOT_MAIL_SUBJECT
+$mail.Body = $env:TULSBOT_MAIL_BODY
+if ($env:TULSBOT_MAIL_ATTACHMENTS) {
+    $env:TULSBOT_MAIL_ATTACHMENTS -split '\|' | ForEach-Object { [void]$mail.Attachments.Add($_) }
+}
+$mail.Display()
+"#;
+
+/// Open `draft` in the mail client. Blocking.
+pub fn compose(draft: &Draft) -> Result<(), String> {
+    draft.validate()?;
+    #[cfg(target_os = "macos")]
+    {
+        if draft.attachments.is_empty() {
+            return run("open", &[mailto(draft)], &[]);
+        }
+        let mut args = vec![
+            "-e".to_string(),
+            MAIL_SCRIPT.to_string(),
+            draft.subject.clone(),
+            draft.body.clone(),
+            draft.to.join(","),
+        ];
+        args.extend(draft.attachments.iter().map(|p| p.display().to_string()));
+        run("osascript", &args, &[])
+    }
+    #[cfg(windows)]
+    {
+        if draft.attachments.is_empty() {
+            // Not `cmd /c start`, which would parse the `&` in the URL
+            let args = ["url.dll,FileProtocolHandler".to_string(), mailto(draft)];
+            return run("rundll32", &args, &[]);
+        }
+        // Passed through the environment to avoid quoting the values
+        let attachments: Vec<String> =
+            draft.attachments.iter().map(|p| p.display().to_string()).collect();
+        let env = [
+            ("TULSBOT_MAIL_TO", draft.to.join(",")),
+            ("TULSBOT_MAIL_SUBJECT", draft.subject.clone()),
+            ("TULSBOT_MAIL_BODY", draft.body.clone()),
+            ("TULSBOT_MAIL_ATTACHMENTS", attachments.join("|")),
+        ];
+        let args = ["-NoProfile".to_string(), "-Command".into(), OUTLOOK_SCRIPT.into()];
+        run("powershell", &args, &env)
+    }
+    #[cfg(not(any(target_os = "macos", windows)))]
+    {
+        if draft.attachments.is_empty() {
+            return run("xdg-open", &[mailto(draft)], &[]);
+        }
+        let mut args = vec![
+            "--subject".to_string(),
+            draft.subject.clone(),
+            "--body".into(),
+            draft.body.clone(),
+        ];
+        for path in &draft.attachments {
+            args.extend(["--attach".to_string(), path.display().to_string()]);
+        }
+        args.extend(draft.to.iter().cloned());
+        run("xdg-email", &args, &[])
+    }
+}
+
+/// Open a prefilled draft in the system mail client for the user to send.
+#[instrumented]
+#[tauri::command]
+pub async fn compose_email(
+    to: Vec<String>,
+    subject: Option<String>,
+    body: Option<String>,
+    attachments: Option<Vec<String>>,
+) -> Result<(), AppError> {
+    let draft = Draft {
+        to: to.into_iter().map(|a| a.trim().to_string()).filter(|a| !a.is_empty()).collect(),
+        subject: subject.unwrap_or_default(),
+        body: body.unwrap_or_default(),
+        attachments: attachments.unwrap_or_default().into_iter().map(PathBuf::from).collect(),
+    };
+    Ok(tauri::async_runtime::spawn_blocking(move || compose(&draft))
+        .await
+        .map_err(|e| e.to_string())??)
+}
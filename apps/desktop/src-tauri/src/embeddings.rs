@@ -0,0 +1,302 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+use tulsbot_macros::instrumented;
+
+use crate::downloads::{delete_model, download_model_with};
+use crate::error::AppError;
+use crate::locks::LockExt;
+use crate::state::AppState;
+use crate::supervisor::find_service;
+
+// ── Embeddings (Context Manager or in-process ONNX) ─────────────────────────
+//
+// The local provider runs a small sentence-transformer with ONNX Runtime so
+// indexing keeps working offline or while the Context Manager is down.
+// Models are downloaded into `<app data>/models/<id>/` by `downloads`.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Provider {
+    ContextManager,
+    Local,
+}
+
+/// Used when the settings don't specify an order.
+pub const DEFAULT_ORDER: &[Provider] = &[Provider::ContextManager, Provider::Local];
+
+pub struct ModelInfo {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub dims: usize,
+    /// `(file name, download URL)`
+    pub files: &'static [(&'static str, &'static str)],
+}
+
+pub const MODELS: &[ModelInfo] = &[ModelInfo {
+    id: "all-minilm-l6-v2",
+    name: "all-MiniLM-L6-v2",
+    dims: 384,
+    files: &[
+        (
+            "model.onnx",
+            "https://huggingface.co/sentence-transformers/all-MiniLM-L6-v2/resolve/main/onnx/model.onnx",
+        ),
+        (
+            "tokenizer.json",
+            "https://huggingface.co/sentence-transformers/all-MiniLM-L6-v2/resolve/main/tokenizer.json",
+        ),
+    ],
+}];
+
+pub const DEFAULT_MODEL: &str = "all-minilm-l6-v2";
+
+/// Longest input, in tokens, fed to the local model.
+const MAX_TOKENS: usize = 256;
+
+pub fn model(id: &str) -> Result<&'static ModelInfo, String> {
+    MODELS
+        .iter()
+        .find(|m| m.id == id)
+        .ok_or_else(|| format!("Unknown embedding model: {}", id))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelStatus {
+    pub id: String,
+    pub name: String,
+    pub dims: usize,
+    pub downloaded: bool,
+    /// Bytes on disk.
+    pub size: u64,
+}
+
+pub fn status(root: &Path) -> Vec<ModelStatus> {
+    MODELS
+        .iter()
+        .map(|m| {
+            let dir = root.join(m.id);
+            let sizes: Vec<Option<u64>> = m
+                .files
+                .iter()
+                .map(|(file, _)| std::fs::metadata(dir.join(file)).ok().map(|meta| meta.len()))
+                .collect();
+            ModelStatus {
+                id: m.id.into(),
+                name: m.name.into(),
+                dims: m.dims,
+                downloaded: sizes.iter().all(Option::is_some),
+                size: sizes.iter().flatten().sum(),
+            }
+        })
+        .collect()
+}
+
+/// A loaded local model. Inference is blocking.
+pub struct LocalEmbedder {
+    pub model: String,
+    session: ort::session::Session,
+    tokenizer: tokenizers::Tokenizer,
+}
+
+impl LocalEmbedder {
+    pub fn load(root: &Path, id: &str) -> Result<Self, String> {
+        let dir: PathBuf = root.join(model(id)?.id);
+        let mut tokenizer = tokenizers::Tokenizer::from_file(dir.join("tokenizer.json"))
+            .map_err(|e| format!("Model '{}' is not downloaded: {}", id, e))?;
+        tokenizer.with_padding(Some(tokenizers::PaddingParams::default()));
+        tokenizer
+            .with_truncation(Some(tokenizers::TruncationParams {
+                max_length: MAX_TOKENS,
+                ..Default::default()
+            }))
+            .map_err(|e| e.to_string())?;
+        let session = ort::session::Session::builder()
+            .and_then(|b| b.commit_from_file(dir.join("model.onnx")))
+            .map_err(|e| e.to_string())?;
+        Ok(Self { model: id.to_string(), session, tokenizer })
+    }
+
+    /// Mean-pooled, L2-normalised sentence embeddings.
+    pub fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let encodings = self
+            .tokenizer
+            .encode_batch(texts.to_vec(), true)
+            .map_err(|e| e.to_string())?;
+        let batch = encodings.len();
+        let len = encodings[0].get_ids().len();
+        let tensor = |field: fn(&tokenizers::Encoding) -> &[u32]| {
+            let data = encodings
+                .iter()
+                .flat_map(|e| field(e).iter().map(|&v| i64::from(v)))
+                .collect();
+            ndarray::Array2::from_shape_vec((batch, len), data).map_err(|e| e.to_string())
+        };
+        let ids = tensor(tokenizers::Encoding::get_ids)?;
+        let mask = tensor(tokenizers::Encoding::get_attention_mask)?;
+        let types = tensor(tokenizers::Encoding::get_type_ids)?;
+
+        let inputs = ort::inputs![
+            "input_ids" => ids,
+            "attention_mask" => mask.clone(),
+            "token_type_ids" => types,
+        ]
+        .map_err(|e| e.to_string())?;
+        let outputs = self.session.run(inputs).map_err(|e| e.to_string())?;
+        let hidden = outputs[0]
+            .try_extract_tensor::<f32>()
+            .map_err(|e| e.to_string())?;
+        let hidden = hidden
+            .into_dimensionality::<ndarray::Ix3>()
+            .map_err(|e| e.to_string())?;
+
+        Ok((0..batch)
+            .map(|b| {
+                let dims = hidden.shape()[2];
+                let mut pooled = vec![0f32; dims];
+                let mut count = 0f32;
+                for t in 0..len {
+                    if mask[[b, t]] == 0 {
+                        continue;
+                    }
+                    count += 1.0;
+                    for (d, value) in pooled.iter_mut().enumerate() {
+                        *value += hidden[[b, t, d]];
+                    }
+                }
+                let norm = pooled
+                    .iter()
+                    .map(|v| (v / count.max(1.0)).powi(2))
+                    .sum::<f32>()
+                    .sqrt()
+                    .max(f32::EPSILON);
+                pooled.iter().map(|v| v / count.max(1.0) / norm).collect()
+            })
+            .collect())
+    }
+}
+
+/// Embeddings from the Context Manager: `POST /embed {"texts": [...]}`
+/// answering `{"embeddings": [[...], ...]}`.
+pub async fn remote_embed(port: u16, texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+    let resp = reqwest::Client::new()
+        .post(format!("http://127.0.0.1:{}/embed", port))
+        .json(&serde_json::json!({ "texts": texts }))
+        .timeout(std::time::Duration::from_secs(30))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !resp.status().is_success() {
+        return Err(format!("HTTP {}", resp.status().as_u16()));
+    }
+    #[derive(Deserialize)]
+    struct Response {
+        embeddings: Vec<Vec<f32>>,
+    }
+    let body: Response = resp.json().await.map_err(|e| e.to_string())?;
+    if body.embeddings.len() != texts.len() {
+        return Err("Context Manager returned the wrong number of embeddings".into());
+    }
+    Ok(body.embeddings)
+}
+
+pub fn models_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app.path().app_data_dir().map_err(|e| e.to_string())?.join("models"))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingResult {
+    pub provider: Provider,
+    pub vectors: Vec<Vec<f32>>,
+}
+
+/// The local embedder for `id`, loading it if needed. Blocking.
+pub fn local_embedder(app: &AppHandle, id: &str) -> Result<Arc<LocalEmbedder>, String> {
+    let state = app.state::<AppState>();
+    let mut slot = state.embedder.lock_or_recover();
+    if let Some(embedder) = slot.as_ref().filter(|e| e.model == id) {
+        return Ok(embedder.clone());
+    }
+    let embedder = Arc::new(LocalEmbedder::load(&models_dir(app)?, id)?);
+    *slot = Some(embedder.clone());
+    Ok(embedder)
+}
+
+/// Try each configured provider in order until one succeeds.
+pub async fn embed(app: &AppHandle, texts: Vec<String>) -> Result<EmbeddingResult, String> {
+    let (order, model) = {
+        let state = app.state::<AppState>();
+        let settings = state.settings.lock_or_recover();
+        let order = if settings.embedding_providers.is_empty() {
+            DEFAULT_ORDER.to_vec()
+        } else {
+            settings.embedding_providers.clone()
+        };
+        let model = settings
+            .embedding_model
+            .clone()
+            .unwrap_or_else(|| DEFAULT_MODEL.to_string());
+        (order, model)
+    };
+
+    let mut errors = Vec::new();
+    for provider in order {
+        let result = match provider {
+            Provider::ContextManager => match find_service(app, "Context Manager") {
+                Ok(service) => remote_embed(service.port, &texts).await,
+                Err(e) => Err(e),
+            },
+            Provider::Local => {
+                let (app, model, texts) = (app.clone(), model.clone(), texts.clone());
+                tauri::async_runtime::spawn_blocking(move || {
+                    local_embedder(&app, &model)?.embed(&texts)
+                })
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|r| r)
+            }
+        };
+        match result {
+            Ok(vectors) => return Ok(EmbeddingResult { provider, vectors }),
+            Err(e) => errors.push(format!("{:?}: {}", provider, e)),
+        }
+    }
+    Err(format!("No embedding provider available ({})", errors.join("; ")))
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn embed_text(app: AppHandle, text: String) -> Result<EmbeddingResult, AppError> {
+    Ok(embed(&app, vec![text]).await?)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn embed_texts(app: AppHandle, texts: Vec<String>) -> Result<EmbeddingResult, AppError> {
+    Ok(embed(&app, texts).await?)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn get_embedding_models(app: AppHandle) -> Result<Vec<ModelStatus>, AppError> {
+    Ok(status(&models_dir(&app)?))
+}
+
+/// Download a local model, emitting `embedding-model-progress` as it goes.
+#[instrumented]
+#[tauri::command]
+pub async fn download_embedding_model(app: AppHandle, id: String) -> Result<(), AppError> {
+    model(&id)?;
+    Ok(download_model_with(&app, &id, "embedding-model-progress").await?)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn delete_embedding_model(app: AppHandle, id: String) -> Result<(), AppError> {
+    model(&id)?;
+    delete_model(app, id).await
+}
@@ -0,0 +1,393 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tulsbot_macros::instrumented;
+
+use crate::{deps, keychain, settings, templates};
+use crate::error::{AppError, ErrorKind};
+use crate::locks::LockExt;
+use crate::profiles::{self, active_data_dir, apply_profile, ServiceDef};
+use crate::secrets::{self, Charset, Generated, SecretKind};
+use crate::state::AppState;
+use crate::supervisor::find_service;
+
+// ── Per-service environment (settings + keychain) ───────────────────────────
+//
+// Plain values live in the profile's settings; secret values live in the
+// keychain and settings only remember that the key exists. The resolved
+// environment is passed to every process the supervisor spawns (restart
+// commands, compose, scripts). Containers restarted in place keep the
+// environment they were created with.
+
+/// Service name → variable → value.
+pub type ServiceEnv = BTreeMap<String, BTreeMap<String, EnvVar>>;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnvVar {
+    /// The value, for non-secret variables.
+    pub value: Option<String>,
+    /// Stored in the keychain instead of settings.
+    pub secret: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EnvChange {
+    /// Only set by the service config.
+    Added,
+    /// Set in the shell's environment and replaced by the service config.
+    Overridden,
+    /// Set to the same value in both.
+    Unchanged,
+}
+
+/// One row of the effective-environment diff. Secret values are masked.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvEntry {
+    pub key: String,
+    pub secret: bool,
+    pub inherited: Option<String>,
+    pub configured: Option<String>,
+    pub change: EnvChange,
+}
+
+const MASK: &str = "••••••••";
+
+pub fn validate_key(key: &str) -> Result<(), String> {
+    let valid = key.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid environment variable name: {}", key))
+    }
+}
+
+/// Keys whose values should go to the keychain when imported.
+pub fn looks_secret(key: &str) -> bool {
+    let upper = key.to_ascii_uppercase();
+    ["KEY", "TOKEN", "SECRET", "PASSWORD", "PASSWD", "CREDENTIAL"]
+        .iter()
+        .any(|word| upper.contains(word))
+}
+
+pub fn keychain_account(profile: &str, service: &str, key: &str) -> String {
+    format!("env:{}:{}:{}", profile, service, key)
+}
+
+/// `KEY=value` pairs from a `.env` file: comments, blank lines and `export`
+/// prefixes are skipped, surrounding quotes removed.
+pub fn parse_dotenv(text: &str) -> Result<Vec<(String, String)>, String> {
+    let mut vars = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("Line {}: expected KEY=value", n + 1))?;
+        let key = key.trim();
+        validate_key(key).map_err(|e| format!("Line {}: {}", n + 1, e))?;
+        let value = value.trim();
+        let unquoted = ['"', '\'']
+            .iter()
+            .find_map(|q| value.strip_prefix(*q).and_then(|v| v.strip_suffix(*q)))
+            .unwrap_or(value);
+        vars.push((key.to_string(), unquoted.to_string()));
+    }
+    Ok(vars)
+}
+
+pub fn read_dotenv(path: &Path) -> Result<Vec<(String, String)>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    parse_dotenv(&text)
+}
+
+/// The service's variables with secrets read from the keychain. Blocking.
+pub fn resolve(
+    profile: &str,
+    service: &str,
+    vars: &BTreeMap<String, EnvVar>,
+) -> Result<Vec<(String, String)>, String> {
+    let mut resolved = Vec::new();
+    for (key, var) in vars {
+        let value = if var.secret {
+            keychain::get(&keychain_account(profile, service, key))?
+        } else {
+            var.value.clone()
+        };
+        if let Some(value) = value {
+            resolved.push((key.clone(), value));
+        }
+    }
+    Ok(resolved)
+}
+
+/// Configured variables against the environment the app inherited.
+pub fn diff(
+    vars: &BTreeMap<String, EnvVar>,
+    inherited: &BTreeMap<String, String>,
+) -> Vec<EnvEntry> {
+    vars.iter()
+        .map(|(key, var)| {
+            let inherited = inherited.get(key).cloned();
+            let change = match (&inherited, var.secret) {
+                (None, _) => EnvChange::Added,
+                (Some(old), false) if var.value.as_ref() == Some(old) => EnvChange::Unchanged,
+                (Some(_), _) => EnvChange::Overridden,
+            };
+            let mask = |value: String| if var.secret { MASK.to_string() } else { value };
+            EnvEntry {
+                key: key.clone(),
+                secret: var.secret,
+                inherited: inherited.map(mask),
+                configured: if var.secret { Some(MASK.into()) } else { var.value.clone() },
+                change,
+            }
+        })
+        .collect()
+}
+
+/// The service's configured environment, secrets included. Blocking.
+pub fn service_env(app: &AppHandle, service: &str) -> Result<Vec<(String, String)>, String> {
+    let profile = active_profile_name(app)?;
+    let vars = app
+        .state::<AppState>()
+        .settings
+        .lock()
+        .map_err(|e| e.to_string())?
+        .service_env
+        .get(service)
+        .cloned()
+        .unwrap_or_default();
+    resolve(&profile, service, &vars)
+}
+
+pub fn active_profile_name(app: &AppHandle) -> Result<String, String> {
+    let state = app.state::<AppState>();
+    let store = state.profiles.lock_or_recover();
+    Ok(store.active.clone())
+}
+
+/// The service's variables compared with the environment the app inherited.
+#[instrumented]
+#[tauri::command]
+pub async fn get_service_env(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    service: String,
+) -> Result<Vec<EnvEntry>, AppError> {
+    find_service(&app, &service)?;
+    let vars = state
+        .settings
+        .lock()
+        .map_err(|e| e.to_string())?
+        .service_env
+        .get(&service)
+        .cloned()
+        .unwrap_or_default();
+    let inherited = std::env::vars().collect();
+    Ok(diff(&vars, &inherited))
+}
+
+/// Set (or with no `value`, remove) a variable. Secret values go to the
+/// keychain.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn set_service_env(
+    app: AppHandle,
+    service: String,
+    key: String,
+    value: Option<String>,
+    secret: Option<bool>,
+) -> Result<(), AppError> {
+    validate_key(&key)?;
+    find_service(&app, &service)?;
+    let secret = secret.unwrap_or(false);
+    let profile = active_profile_name(&app)?;
+    let account = keychain_account(&profile, &service, &key);
+    let stored = value.clone();
+    tauri::async_runtime::spawn_blocking(move || match stored {
+        Some(value) if secret => keychain::set(&account, &value),
+        _ => keychain::delete(&account),
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    Ok(update_service_env(&app, &service, |vars| {
+        match value {
+            Some(value) => vars.insert(
+                key,
+                EnvVar { value: (!secret).then_some(value), secret },
+            ),
+            None => vars.remove(&key),
+        };
+    })?)
+}
+
+/// Add every variable of a `.env` file; names that look like credentials
+/// are stored as secrets. Returns the number of variables imported.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn import_env_file(
+    app: AppHandle,
+    service: String,
+    path: String,
+) -> Result<usize, AppError> {
+    find_service(&app, &service)?;
+    let profile = active_profile_name(&app)?;
+    let account_service = service.clone();
+    let vars = tauri::async_runtime::spawn_blocking(move || {
+        let vars = read_dotenv(std::path::Path::new(&path))?;
+        for (key, value) in vars.iter().filter(|(key, _)| looks_secret(key)) {
+            keychain::set(&keychain_account(&profile, &account_service, key), value)?;
+        }
+        Ok::<_, String>(vars)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    let count = vars.len();
+    update_service_env(&app, &service, |configured| {
+        for (key, value) in vars {
+            let secret = looks_secret(&key);
+            configured.insert(key, EnvVar { value: (!secret).then_some(value), secret });
+        }
+    })?;
+    Ok(count)
+}
+
+/// Generate a password, API key or PIN from the OS random source.
+#[instrumented]
+#[tauri::command]
+pub async fn generate_secret(
+    kind: Option<SecretKind>,
+    length: Option<usize>,
+    charset: Option<Charset>,
+) -> Result<Generated, AppError> {
+    secrets::generate(kind.unwrap_or_default(), length, charset)
+        .map_err(|e| AppError::new(ErrorKind::InvalidInput, e))
+}
+
+/// Generate a secret and store it as the secret variable `key` of `service`
+/// in one step. The value goes straight to the keychain and is never sent
+/// to a window.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn generate_service_secret(
+    app: AppHandle,
+    service: String,
+    key: String,
+    kind: Option<SecretKind>,
+    length: Option<usize>,
+    charset: Option<Charset>,
+) -> Result<(), AppError> {
+    validate_key(&key)?;
+    find_service(&app, &service)?;
+    let generated = secrets::generate(kind.unwrap_or_default(), length, charset)
+        .map_err(|e| AppError::new(ErrorKind::InvalidInput, e))?;
+    let account = keychain_account(&active_profile_name(&app)?, &service, &key);
+    tauri::async_runtime::spawn_blocking(move || keychain::set(&account, &generated.secret))
+        .await
+        .map_err(|e| e.to_string())??;
+    Ok(update_service_env(&app, &service, |vars| {
+        vars.insert(key, EnvVar { value: None, secret: true });
+    })?)
+}
+
+pub fn update_service_env(
+    app: &AppHandle,
+    service: &str,
+    change: impl FnOnce(&mut std::collections::BTreeMap<String, EnvVar>),
+) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        let vars = settings.service_env.entry(service.to_string()).or_default();
+        change(vars);
+        if vars.is_empty() {
+            settings.service_env.remove(service);
+        }
+        settings.clone()
+    };
+    settings::save(&active_data_dir(app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    Ok(())
+}
+
+/// Add a service to the active profile from a JSON or TOML definition
+/// (`definition` text, or a file at `path`). Plain env values in the
+/// definition go to the service's environment.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn add_service(
+    app: AppHandle,
+    definition: Option<String>,
+    path: Option<String>,
+    format: Option<String>,
+) -> Result<ServiceDef, AppError> {
+    let template = match (definition, path) {
+        (Some(text), _) => templates::parse(&text, format.as_deref())?,
+        (None, Some(path)) => templates::read(std::path::Path::new(&path))?,
+        (None, None) => {
+            return Err(AppError::new(ErrorKind::InvalidInput, "Pass a definition or a path"))
+        }
+    };
+    let state = app.state::<AppState>();
+    let name = {
+        let mut store = state.profiles.lock_or_recover();
+        let active = store.active.clone();
+        let profile = store
+            .profiles
+            .iter_mut()
+            .find(|p| p.name == active)
+            .ok_or_else(|| format!("Unknown profile: {}", active))?;
+        templates::validate(&template, &profile.services)?;
+        let mut services = profile.services.clone();
+        services.push(template.service.clone());
+        deps::validate(&services)?;
+        profile.services = services;
+        profiles::save(&app, &store)?;
+        active
+    };
+    if !template.env.is_empty() {
+        update_service_env(&app, &template.service.name, |vars| {
+            for (key, value) in template.env {
+                vars.insert(key, EnvVar { value: Some(value), secret: false });
+            }
+        })?;
+    }
+    apply_profile(&app, &name)?;
+    Ok(template.service)
+}
+
+/// Remove a service from the active profile. Services depending on it must
+/// be changed first.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn remove_service(app: AppHandle, service: String) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    let name = {
+        let mut store = state.profiles.lock_or_recover();
+        let active = store.active.clone();
+        let profile = store
+            .profiles
+            .iter_mut()
+            .find(|p| p.name == active)
+            .ok_or_else(|| format!("Unknown profile: {}", active))?;
+        if !profile.services.iter().any(|s| s.name == service) {
+            return Err(AppError::new(ErrorKind::NotFound, format!("Unknown service: {}", service)));
+        }
+        if let Some(dependent) = profile.services.iter().find(|s| s.depends_on.contains(&service)) {
+            let message = format!("{} depends on {}", dependent.name, service);
+            return Err(AppError::new(ErrorKind::Conflict, message));
+        }
+        profile.services.retain(|s| s.name != service);
+        profiles::save(&app, &store)?;
+        active
+    };
+    update_service_env(&app, &service, |vars| vars.clear())?;
+    Ok(apply_profile(&app, &name)?)
+}
@@ -0,0 +1,126 @@
+use serde::Serialize;
+
+// ── Command errors ──────────────────────────────────────────────────────────
+//
+// What every Tauri command fails with. The frontend gets `kind`, `message`,
+// `retryable` and optional `details` instead of a bare string, so it can pick
+// the error UI and decide on a retry without matching message text.
+//
+// Helpers below the commands keep returning `Result<_, String>`; `?` turns
+// their messages into an `AppError`, classifying the wordings the app uses
+// throughout ("Unknown …", "Request failed: …", "HTTP 503: …"). Commands
+// that know better construct the error with its kind directly.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorKind {
+    /// An argument is missing, empty or out of range.
+    InvalidInput,
+    NotFound,
+    /// Clashes with existing state, e.g. a duplicate or something in use.
+    Conflict,
+    /// Another run of the same job is in flight.
+    Busy,
+    /// The stack hasn't come up (yet).
+    NotReady,
+    /// Not allowed by the active profile or settings.
+    Forbidden,
+    /// Destructive; repeat with confirmation.
+    ConfirmationRequired,
+    /// Not available on this platform.
+    Unsupported,
+    /// The request didn't get an answer.
+    Network,
+    /// A backend or provider answered with an error status.
+    Upstream,
+    Timeout,
+    /// Stopped at the user's request.
+    Cancelled,
+    Io,
+    Internal,
+}
+
+impl ErrorKind {
+    /// Whether the same call may succeed when repeated as is.
+    pub fn retryable(self) -> bool {
+        matches!(self, Self::Busy | Self::NotReady | Self::Network | Self::Timeout)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppError {
+    pub kind: ErrorKind,
+    pub message: String,
+    pub retryable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl AppError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self { kind, message: message.into(), retryable: kind.retryable(), details: None }
+    }
+
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    /// An error status from a backend or provider. Rate limits, timeouts
+    /// and server errors are worth retrying; other client errors are not.
+    pub fn http(status: u16, body: &str) -> Self {
+        let mut error = Self::new(ErrorKind::Upstream, format!("HTTP {}: {}", status, body))
+            .with_details(serde_json::json!({ "status": status }));
+        error.retryable = matches!(status, 408 | 429 | 500..=599);
+        error
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        let status = message
+            .strip_prefix("HTTP ")
+            .and_then(|rest| rest.get(..3))
+            .and_then(|code| code.parse::<u16>().ok());
+        if let Some(status) = status {
+            let mut error = Self::http(status, "");
+            error.message = message;
+            return error;
+        }
+        let kind = if message.starts_with("Unknown ") {
+            ErrorKind::NotFound
+        } else if message.starts_with("Request failed") {
+            ErrorKind::Network
+        } else if message.starts_with("Timed out") {
+            ErrorKind::Timeout
+        } else if message.ends_with(" cancelled") {
+            ErrorKind::Cancelled
+        } else if message.contains("(os error") {
+            ErrorKind::Io
+        } else {
+            ErrorKind::Internal
+        };
+        Self::new(kind, message)
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+/// Lets helpers that return `Result<_, String>` call commands with `?`.
+impl From<AppError> for String {
+    fn from(error: AppError) -> Self {
+        error.message
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for AppError {}
@@ -0,0 +1,187 @@
+use parquet::basic::Compression;
+use parquet::data_type::{BoolType, ByteArray, ByteArrayType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::history::HealthSample;
+use crate::usage::UsageRecord;
+
+// ── Usage and health exports (CSV / Parquet) ────────────────────────────────
+//
+// Flat tables for notebooks and spreadsheets. Health history is exported in
+// long form, one row per service per sample, which is what dataframes want.
+// Timestamps are RFC 3339 UTC strings in CSV and UTC millisecond timestamps
+// in Parquet.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Parquet,
+}
+
+enum Values {
+    /// Unix seconds.
+    Timestamp(Vec<u64>),
+    Int(Vec<Option<u64>>),
+    Text(Vec<Option<String>>),
+    Bool(Vec<bool>),
+}
+
+struct Column {
+    name: &'static str,
+    values: Values,
+}
+
+impl Column {
+    fn new(name: &'static str, values: Values) -> Self {
+        Self { name, values }
+    }
+
+    fn cell(&self, row: usize) -> String {
+        match &self.values {
+            Values::Timestamp(v) => chrono::DateTime::from_timestamp(v[row] as i64, 0)
+                .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+                .unwrap_or_default(),
+            Values::Int(v) => v[row].map(|n| n.to_string()).unwrap_or_default(),
+            Values::Text(v) => v[row].clone().unwrap_or_default(),
+            Values::Bool(v) => v[row].to_string(),
+        }
+    }
+
+    fn parquet_type(&self) -> String {
+        match &self.values {
+            Values::Timestamp(_) => {
+                format!("required int64 {} (TIMESTAMP(MILLIS,true));", self.name)
+            }
+            Values::Int(_) => format!("optional int64 {};", self.name),
+            Values::Text(_) => format!("optional binary {} (STRING);", self.name),
+            Values::Bool(_) => format!("required boolean {};", self.name),
+        }
+    }
+}
+
+fn write_csv(columns: &[Column], rows: usize, path: &Path) -> Result<(), String> {
+    let mut writer = csv::Writer::from_path(path).map_err(|e| e.to_string())?;
+    writer.write_record(columns.iter().map(|c| c.name)).map_err(|e| e.to_string())?;
+    for row in 0..rows {
+        writer
+            .write_record(columns.iter().map(|c| c.cell(row)))
+            .map_err(|e| e.to_string())?;
+    }
+    writer.flush().map_err(|e| e.to_string())
+}
+
+/// Present values of an optional column and its definition levels.
+fn optional<T: Clone>(values: &[Option<T>]) -> (Vec<T>, Vec<i16>) {
+    let present = values.iter().flatten().cloned().collect();
+    let levels = values.iter().map(|v| i16::from(v.is_some())).collect();
+    (present, levels)
+}
+
+fn write_parquet(columns: &[Column], path: &Path) -> Result<(), String> {
+    let fields: String = columns.iter().map(Column::parquet_type).collect();
+    let schema = parse_message_type(&format!("message export {{ {} }}", fields))
+        .map_err(|e| e.to_string())?;
+    let props = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+    let file = std::fs::File::create(path).map_err(|e| e.to_string())?;
+    let mut writer = SerializedFileWriter::new(file, Arc::new(schema), Arc::new(props))
+        .map_err(|e| e.to_string())?;
+
+    let mut group = writer.next_row_group().map_err(|e| e.to_string())?;
+    for column in columns {
+        let mut out = group
+            .next_column()
+            .map_err(|e| e.to_string())?
+            .ok_or("Parquet schema and columns disagree")?;
+        let written = match &column.values {
+            Values::Timestamp(v) => {
+                let millis: Vec<i64> = v.iter().map(|s| *s as i64 * 1000).collect();
+                out.typed::<Int64Type>().write_batch(&millis, None, None)
+            }
+            Values::Int(v) => {
+                let (present, levels) = optional(v);
+                let present: Vec<i64> = present.into_iter().map(|n| n as i64).collect();
+                out.typed::<Int64Type>().write_batch(&present, Some(&levels), None)
+            }
+            Values::Text(v) => {
+                let (present, levels) = optional(v);
+                let present: Vec<ByteArray> =
+                    present.into_iter().map(|s| ByteArray::from(s.into_bytes())).collect();
+                out.typed::<ByteArrayType>().write_batch(&present, Some(&levels), None)
+            }
+            Values::Bool(v) => out.typed::<BoolType>().write_batch(v, None, None),
+        };
+        written.map_err(|e| format!("{}: {}", column.name, e))?;
+        out.close().map_err(|e| e.to_string())?;
+    }
+    group.close().map_err(|e| e.to_string())?;
+    writer.close().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn write(
+    columns: &[Column],
+    rows: usize,
+    format: ExportFormat,
+    path: &Path,
+) -> Result<(), String> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    match format {
+        ExportFormat::Csv => write_csv(columns, rows, path),
+        ExportFormat::Parquet => write_parquet(columns, path),
+    }
+}
+
+/// Write `records` to `path`, returning the number of rows. Blocking.
+pub fn usage(records: &[UsageRecord], format: ExportFormat, path: &Path) -> Result<usize, String> {
+    let text = |f: fn(&UsageRecord) -> Option<String>| records.iter().map(f).collect();
+    let columns = [
+        Column::new("at", Values::Timestamp(records.iter().map(|r| r.at).collect())),
+        Column::new("source", Values::Text(text(|r| Some(r.source.clone())))),
+        Column::new("provider", Values::Text(text(|r| Some(r.provider.clone())))),
+        Column::new("model", Values::Text(text(|r| r.model.clone()))),
+        Column::new(
+            "latency_ms",
+            Values::Int(records.iter().map(|r| Some(r.latency_ms)).collect()),
+        ),
+        Column::new("input_tokens", Values::Int(records.iter().map(|r| r.input_tokens).collect())),
+        Column::new(
+            "output_tokens",
+            Values::Int(records.iter().map(|r| r.output_tokens).collect()),
+        ),
+        Column::new("error", Values::Text(text(|r| r.error.clone()))),
+    ];
+    write(&columns, records.len(), format, path)?;
+    Ok(records.len())
+}
+
+/// Write `samples` to `path` with one row per service and sample, returning
+/// the number of rows. Blocking.
+pub fn health(
+    samples: &[HealthSample],
+    format: ExportFormat,
+    path: &Path,
+) -> Result<usize, String> {
+    let rows: Vec<(&HealthSample, &String, bool)> = samples
+        .iter()
+        .flat_map(|s| s.services.iter().map(move |(name, healthy)| (s, name, *healthy)))
+        .collect();
+    let columns = [
+        Column::new("at", Values::Timestamp(rows.iter().map(|r| r.0.at).collect())),
+        Column::new(
+            "overall",
+            Values::Text(rows.iter().map(|r| Some(r.0.overall.clone())).collect()),
+        ),
+        Column::new("service", Values::Text(rows.iter().map(|r| Some(r.1.clone())).collect())),
+        Column::new("healthy", Values::Bool(rows.iter().map(|r| r.2).collect())),
+    ];
+    write(&columns, rows.len(), format, path)?;
+    Ok(rows.len())
+}
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{conversations, credentials, providers};
+use crate::alerts::{evaluate_alerts, Metric, Sample};
+use crate::locks::LockExt;
+use crate::state::AppState;
+
+// ── Latency probes to external LLM providers ────────────────────────────────
+//
+// A HEAD request per endpoint, timed. Any HTTP answer counts as reachable
+// (an unauthenticated probe usually gets 401 or 405); only network errors
+// and timeouts count as failures. Tells local slowness from upstream.
+
+const PROBE_TIMEOUT_SECS: u64 = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Endpoint {
+    pub name: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalHealth {
+    pub name: String,
+    pub url: String,
+    /// Round trip of the probe; `None` when it failed.
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+    /// Unix seconds.
+    pub checked_at: u64,
+}
+
+pub async fn probe(endpoint: &Endpoint, now: u64) -> ExternalHealth {
+    let started = std::time::Instant::now();
+    let result = reqwest::Client::new()
+        .head(&endpoint.url)
+        .timeout(std::time::Duration::from_secs(PROBE_TIMEOUT_SECS))
+        .send()
+        .await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+    ExternalHealth {
+        name: endpoint.name.clone(),
+        url: endpoint.url.clone(),
+        latency_ms: result.is_ok().then_some(latency_ms),
+        error: result.err().map(|e| e.to_string()),
+        checked_at: now,
+    }
+}
+
+/// The configured probe endpoints, or one per provider with a credential.
+pub fn external_endpoints(app: &AppHandle) -> Vec<Endpoint> {
+    let configured = app
+        .state::<AppState>()
+        .settings
+        .lock()
+        .map(|s| s.external_probes.clone())
+        .unwrap_or_default();
+    if !configured.is_empty() {
+        return configured;
+    }
+    let mut endpoints: Vec<Endpoint> = credentials::load(app)
+        .into_iter()
+        .filter_map(|c| {
+            let url = providers::probe_url(&c.provider)?;
+            Some(Endpoint { name: c.provider, url: url.into() })
+        })
+        .collect();
+    endpoints.dedup();
+    endpoints
+}
+
+/// Probe every external endpoint, store the results in the health state and
+/// emit `external-latency`.
+pub async fn probe_external(app: &AppHandle) {
+    let endpoints = external_endpoints(app);
+    let now = conversations::now();
+    let mut results = Vec::new();
+    for endpoint in &endpoints {
+        results.push(probe(endpoint, now).await);
+    }
+    app.state::<AppState>().health.lock_or_recover().external = results.clone();
+    let _ = app.emit("external-latency", &results);
+    let samples = results
+        .iter()
+        .filter_map(|r| {
+            let latency = r.latency_ms.filter(|_| r.error.is_none())?;
+            Some(Sample::new(Metric::ExternalLatency, Some(&r.name), latency as f64))
+        })
+        .collect();
+    evaluate_alerts(app, samples);
+}
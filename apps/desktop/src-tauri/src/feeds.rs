@@ -0,0 +1,605 @@
+use kuchikiki::traits::TendrilSink;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use tauri::{AppHandle, Emitter, Manager};
+use tulsbot_macros::instrumented;
+
+use crate::settings;
+use crate::conversations::{
+    self, effective_config, Conversation, ConversationConfig, ConversationSummary, Message,
+};
+use crate::error::{AppError, ErrorKind};
+use crate::guardrails::{guard_response, report_guardrails};
+use crate::i18n::tr;
+use crate::locks::LockExt;
+use crate::notifications::{Notice, NoticeKind};
+use crate::profiles::active_data_dir;
+use crate::providers::{run_completion, ChatMessage, ChatRequest};
+use crate::relay::{notify_relayed, RelayCategory};
+use crate::state::AppState;
+use crate::transforms::{spawn_unfurl_job, transform_response};
+use crate::weather::current_weather;
+
+// ── RSS / Atom feeds and digests ────────────────────────────────────────────
+//
+// Registered feeds and the items seen so far live in `<data dir>/feeds.json`.
+// A background job polls the feeds and records new items; on the digest
+// schedule the items not yet digested are summarized through the provider
+// layer into a new conversation. RSS 2.0, RSS 1.0 (RDF) and Atom are read.
+
+const FILE_NAME: &str = "feeds.json";
+/// Poll interval when `FeedSettings::poll_minutes` is unset.
+pub const DEFAULT_POLL_MINUTES: u32 = 30;
+/// Items kept per feed; older ones are dropped.
+const MAX_ITEMS_PER_FEED: usize = 200;
+/// Items included in one digest, newest first.
+pub const MAX_DIGEST_ITEMS: usize = 50;
+/// Characters of an item's summary kept, and shown to the model.
+const SUMMARY_CHARS: usize = 500;
+const TIMEOUT_SECS: u64 = 20;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeedSettings {
+    /// Minutes between polls; `None` polls every 30 minutes.
+    pub poll_minutes: Option<u32>,
+    /// Summarize new items this often; `None` disables scheduled digests.
+    pub digest_hours: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Feed {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    /// Unix seconds.
+    pub added_at: u64,
+    pub last_polled: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedItem {
+    /// The entry's guid/id, or its link when it has none.
+    pub id: String,
+    /// Id of the feed it came from.
+    pub feed: String,
+    pub title: String,
+    pub link: Option<String>,
+    /// Plain text, cut to a few hundred characters.
+    pub summary: Option<String>,
+    /// Unix seconds, when the feed dates the entry.
+    pub published: Option<i64>,
+    pub seen_at: u64,
+    /// Already part of a digest.
+    #[serde(default)]
+    pub digested: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeedStore {
+    pub feeds: Vec<Feed>,
+    pub items: Vec<FeedItem>,
+    /// Unix seconds of the last poll and the last digest.
+    pub last_poll: u64,
+    pub last_digest: u64,
+}
+
+/// A feed document as read from the network.
+#[derive(Debug, Default)]
+pub struct Parsed {
+    pub title: Option<String>,
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug, Default)]
+struct Entry {
+    id: Option<String>,
+    title: Option<String>,
+    link: Option<String>,
+    summary: Option<String>,
+    content: Option<String>,
+    published: Option<i64>,
+}
+
+pub fn load(dir: &Path) -> FeedStore {
+    std::fs::read_to_string(dir.join(FILE_NAME))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(dir: &Path, store: &FeedStore) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let text = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(FILE_NAME), text).map_err(|e| e.to_string())
+}
+
+pub fn validate_url(url: &str) -> Result<(), String> {
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err("Feeds must be http(s):// URLs".into());
+    }
+    Ok(())
+}
+
+/// Plain text of an HTML fragment, whitespace collapsed and cut to
+/// `SUMMARY_CHARS`.
+fn plain_text(html: &str) -> Option<String> {
+    let text = kuchikiki::parse_html().one(html).document_node.text_contents();
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return None;
+    }
+    let mut cut: String = text.chars().take(SUMMARY_CHARS).collect();
+    if cut.len() < text.len() {
+        cut.push('…');
+    }
+    Some(cut)
+}
+
+/// RFC 2822 (RSS), RFC 3339 (Atom, Dublin Core) or a bare date.
+fn parse_date(text: &str) -> Option<i64> {
+    let text = text.trim();
+    chrono::DateTime::parse_from_rfc2822(text)
+        .or_else(|_| chrono::DateTime::parse_from_rfc3339(text))
+        .map(|t| t.timestamp())
+        .ok()
+        .or_else(|| {
+            chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|t| t.and_utc().timestamp())
+        })
+}
+
+/// Read an RSS or Atom document.
+pub fn parse(xml: &str) -> Result<Parsed, String> {
+    let mut reader = Reader::from_str(xml);
+    let mut parsed = Parsed::default();
+    let mut path: Vec<String> = Vec::new();
+    let mut entry: Option<Entry> = None;
+    let mut text = String::new();
+    let mut recognized = false;
+    loop {
+        let event = reader.read_event().map_err(|e| format!("Invalid feed XML: {}", e))?;
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_lowercase();
+                if matches!(name.as_str(), "rss" | "feed" | "rdf") {
+                    recognized = true;
+                }
+                if matches!(name.as_str(), "item" | "entry") {
+                    entry = Some(Entry::default());
+                }
+                // Atom links carry the URL in `href`
+                if name == "link" {
+                    let attribute = |key: &str| {
+                        e.try_get_attribute(key)
+                            .ok()
+                            .flatten()
+                            .and_then(|a| a.unescape_value().ok().map(|v| v.into_owned()))
+                    };
+                    let alternate = attribute("rel").is_none_or(|rel| rel == "alternate");
+                    if let (Some(entry), true) = (&mut entry, alternate) {
+                        if let Some(href) = attribute("href") {
+                            entry.link.get_or_insert(href);
+                        }
+                    }
+                }
+                if matches!(event, Event::Start(_)) {
+                    path.push(name);
+                    text.clear();
+                }
+            }
+            Event::Text(e) => text.push_str(&e.decode().map_err(|e| e.to_string())?),
+            Event::CData(e) => text.push_str(&e.decode().map_err(|e| e.to_string())?),
+            Event::GeneralRef(e) => {
+                let name = e.decode().map_err(|e| e.to_string())?;
+                match e.resolve_char_ref().ok().flatten() {
+                    Some(c) => text.push(c),
+                    None => match quick_xml::escape::resolve_xml_entity(&name) {
+                        Some(value) => text.push_str(value),
+                        None => text.push_str(&format!("&{};", name)),
+                    },
+                }
+            }
+            Event::End(_) => {
+                let Some(name) = path.pop() else {
+                    continue;
+                };
+                let value = text.trim().to_string();
+                text.clear();
+                match (&mut entry, name.as_str()) {
+                    (Some(_), "item" | "entry") => {
+                        parsed.entries.extend(entry.take());
+                    }
+                    (Some(_), _) if value.is_empty() => {}
+                    (Some(entry), "title") => entry.title = Some(value),
+                    (Some(entry), "link") => entry.link = Some(value),
+                    (Some(entry), "guid" | "id") => entry.id = Some(value),
+                    (Some(entry), "description" | "summary") => entry.summary = Some(value),
+                    (Some(entry), "encoded" | "content") => entry.content = Some(value),
+                    // Prefer the publication date over later updates
+                    (Some(entry), "updated") if entry.published.is_some() => {}
+                    (Some(entry), "pubdate" | "published" | "updated" | "date") => {
+                        entry.published = parse_date(&value).or(entry.published);
+                    }
+                    (None, "title") => {
+                        let parent = path.last().map(String::as_str);
+                        if matches!(parent, Some("channel" | "feed")) && !value.is_empty() {
+                            parsed.title.get_or_insert(value);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    if !recognized {
+        return Err("Not an RSS or Atom feed".into());
+    }
+    Ok(parsed)
+}
+
+pub async fn fetch(url: &str) -> Result<Parsed, String> {
+    let resp = reqwest::Client::new()
+        .get(url)
+        .header(reqwest::header::USER_AGENT, "Tulsbot/0.1 (feed reader)")
+        .timeout(std::time::Duration::from_secs(TIMEOUT_SECS))
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("HTTP {}", resp.status().as_u16()));
+    }
+    let text = resp.text().await.map_err(|e| e.to_string())?;
+    parse(&text)
+}
+
+impl FeedStore {
+    /// Record entries of `feed` not seen before and return them. Items added
+    /// as `digested` won't appear in the next digest (used for the backlog
+    /// present when a feed is registered).
+    pub fn merge(&mut self, feed: &str, parsed: Parsed, now: u64, digested: bool) -> Vec<FeedItem> {
+        let mut added = Vec::new();
+        for entry in parsed.entries {
+            let Some(id) = entry.id.clone().or_else(|| entry.link.clone()) else {
+                continue;
+            };
+            let known = self.items.iter().chain(&added).any(|i| i.feed == feed && i.id == id);
+            if known {
+                continue;
+            }
+            let summary = entry.summary.or(entry.content).and_then(|html| plain_text(&html));
+            added.push(FeedItem {
+                id,
+                feed: feed.to_string(),
+                title: entry
+                    .title
+                    .and_then(|t| plain_text(&t))
+                    .unwrap_or_else(|| "(untitled)".into()),
+                link: entry.link,
+                summary,
+                published: entry.published,
+                seen_at: now,
+                digested,
+            });
+        }
+        self.items.extend(added.iter().cloned());
+
+        // Oldest items of the feed go first once it has too many
+        let count = self.items.iter().filter(|i| i.feed == feed).count();
+        let mut excess = count.saturating_sub(MAX_ITEMS_PER_FEED);
+        self.items.retain(|item| {
+            if excess > 0 && item.feed == feed {
+                excess -= 1;
+                return false;
+            }
+            true
+        });
+        added
+    }
+
+    /// Items not yet part of a digest, newest first.
+    pub fn pending(&self) -> Vec<&FeedItem> {
+        let mut items: Vec<&FeedItem> = self.items.iter().filter(|i| !i.digested).collect();
+        items.sort_by_key(|i| std::cmp::Reverse(i.published.unwrap_or(i.seen_at as i64)));
+        items.truncate(MAX_DIGEST_ITEMS);
+        items
+    }
+
+    pub fn mark_digested(&mut self, ids: &[(String, String)]) {
+        for item in &mut self.items {
+            if ids.iter().any(|(feed, id)| *feed == item.feed && *id == item.id) {
+                item.digested = true;
+            }
+        }
+    }
+
+    fn feed_title<'a>(&'a self, id: &'a str) -> &'a str {
+        self.feeds.iter().find(|f| f.id == id).map_or(id, |f| f.title.as_str())
+    }
+
+    /// Prompt asking for a digest of `items`, opening with `weather` when
+    /// it is known.
+    pub fn digest_prompt(&self, items: &[&FeedItem], weather: Option<&str>) -> Vec<ChatMessage> {
+        let weather = weather.map(|w| format!("Weather: {}\n\n", w)).unwrap_or_default();
+        let listing: String = items
+            .iter()
+            .map(|item| {
+                let mut line = format!("- [{}] {}", self.feed_title(&item.feed), item.title);
+                if let Some(link) = &item.link {
+                    line.push_str(&format!(" <{}>", link));
+                }
+                if let Some(summary) = &item.summary {
+                    line.push_str(&format!("\n  {}", summary));
+                }
+                line.push('\n');
+                line
+            })
+            .collect();
+        vec![
+            ChatMessage::new(
+                "system",
+                "You write short news digests. Group related items, lead with what matters \
+                 most, give each item a sentence at most and keep its link. Use Markdown. \
+                 When the weather is given, open with one line on it.",
+            ),
+            ChatMessage::new("user", format!("{}New items from my feeds:\n\n{}", weather, listing)),
+        ]
+    }
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn list_feeds(app: AppHandle) -> Result<Vec<Feed>, AppError> {
+    Ok(load(&active_data_dir(&app)?).feeds)
+}
+
+/// Register an RSS/Atom feed. Its current items are recorded as already
+/// digested so the first digest only covers what arrives later.
+#[instrumented]
+#[tauri::command]
+pub async fn add_feed(app: AppHandle, url: String) -> Result<Feed, AppError> {
+    let url = url.trim().to_string();
+    validate_url(&url)?;
+    let parsed = fetch(&url).await?;
+    let dir = active_data_dir(&app)?;
+    let mut store = load(&dir);
+    if store.feeds.iter().any(|f| f.url == url) {
+        return Err(AppError::new(ErrorKind::Conflict, format!("Feed already added: {}", url)));
+    }
+    let now = conversations::now();
+    let feed = Feed {
+        id: conversations::new_id(),
+        title: parsed.title.clone().unwrap_or_else(|| url.clone()),
+        url,
+        added_at: now,
+        last_polled: Some(now),
+        last_error: None,
+    };
+    store.merge(&feed.id, parsed, now, true);
+    store.feeds.push(feed.clone());
+    save(&dir, &store)?;
+    Ok(feed)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn remove_feed(app: AppHandle, id: String) -> Result<(), AppError> {
+    let dir = active_data_dir(&app)?;
+    let mut store = load(&dir);
+    store.feeds.retain(|f| f.id != id);
+    store.items.retain(|i| i.feed != id);
+    Ok(save(&dir, &store)?)
+}
+
+/// Stored items, newest first, optionally of one feed.
+#[instrumented]
+#[tauri::command]
+pub async fn get_feed_items(
+    app: AppHandle,
+    feed: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<FeedItem>, AppError> {
+    let mut items: Vec<FeedItem> = load(&active_data_dir(&app)?)
+        .items
+        .into_iter()
+        .filter(|i| feed.as_ref().is_none_or(|feed| i.feed == *feed))
+        .collect();
+    items.sort_by_key(|i| std::cmp::Reverse(i.published.unwrap_or(i.seen_at as i64)));
+    items.truncate(limit.unwrap_or(100));
+    Ok(items)
+}
+
+/// Poll every feed now and emit `feed-items` with what is new.
+pub async fn poll_feeds(app: &AppHandle) -> Result<Vec<FeedItem>, String> {
+    let state = app.state::<AppState>();
+    if state.polling_feeds.swap(true, Ordering::SeqCst) {
+        return Err("Feeds are already being polled".into());
+    }
+    let result = poll_feeds_once(app).await;
+    state.polling_feeds.store(false, Ordering::SeqCst);
+    let added = result?;
+    if !added.is_empty() {
+        let _ = app.emit("feed-items", &added);
+    }
+    Ok(added)
+}
+
+pub async fn poll_feeds_once(app: &AppHandle) -> Result<Vec<FeedItem>, String> {
+    let dir = active_data_dir(app)?;
+    let mut fetched = Vec::new();
+    for feed in load(&dir).feeds {
+        fetched.push((feed.id, fetch(&feed.url).await));
+    }
+    // Reload: feeds may have been added or removed while we fetched
+    let mut store = load(&dir);
+    let now = conversations::now();
+    let mut added = Vec::new();
+    for (id, result) in fetched {
+        let Some(feed) = store.feeds.iter_mut().find(|f| f.id == id) else {
+            continue;
+        };
+        feed.last_polled = Some(now);
+        let parsed = match result {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                eprintln!("[tulsbot] Polling feed {} failed: {}", feed.url, e);
+                feed.last_error = Some(e);
+                continue;
+            }
+        };
+        feed.last_error = None;
+        added.extend(store.merge(&id, parsed, now, false));
+    }
+    store.last_poll = now;
+    save(&dir, &store)?;
+    Ok(added)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn refresh_feeds(app: AppHandle) -> Result<Vec<FeedItem>, AppError> {
+    Ok(poll_feeds(&app).await?)
+}
+
+/// Summarize the items not yet digested into a new conversation and notify.
+/// `None` when there is nothing new.
+pub async fn generate_feed_digest(app: &AppHandle) -> Result<Option<ConversationSummary>, String> {
+    let dir = active_data_dir(app)?;
+    let store = load(&dir);
+    let pending = store.pending();
+    if pending.is_empty() {
+        return Ok(None);
+    }
+    let config = effective_config(&app.state::<AppState>(), &ConversationConfig::default())?;
+    let (Some(provider), Some(model)) = (config.provider, config.model) else {
+        return Err("No default provider and model set for digests".into());
+    };
+    let weather = current_weather(app, false).await.ok().map(|w| w.summary());
+    let request = ChatRequest {
+        provider: provider.clone(),
+        model: model.clone(),
+        temperature: Some(0.3),
+        max_tokens: Some(1500),
+        messages: store.digest_prompt(&pending, weather.as_deref()),
+        credential: config.credential,
+    };
+    let digested: Vec<(String, String)> =
+        pending.iter().map(|i| (i.feed.clone(), i.id.clone())).collect();
+    let reply = run_completion(app, &request, None).await?;
+
+    let title = tr(app, "feed-digest-title");
+    let mut conversation = Conversation::new(Some(title), ConversationConfig::default());
+    let filtered = guard_response(app, &reply.content);
+    report_guardrails(app, &filtered, &conversation.id);
+    let (content, metadata) = transform_response(app, None, filtered.text);
+    let message = Message {
+        id: conversations::new_id(),
+        role: "assistant".into(),
+        content,
+        created_at: conversation.created_at,
+        provider: Some(provider),
+        model: Some(model),
+        parent: None,
+        attachments: Vec::new(),
+        pinned: false,
+        rating: None,
+        metadata,
+    };
+    conversation.active_leaf = Some(message.id.clone());
+    conversation.messages.push(message);
+    conversation.titled = true;
+    conversations::save(&dir, &conversation)?;
+    if let Some(message) = conversation.messages.last() {
+        spawn_unfurl_job(app, &conversation.id, message);
+    }
+
+    let mut store = load(&dir);
+    store.mark_digested(&digested);
+    store.last_digest = conversations::now();
+    save(&dir, &store)?;
+
+    let summary = conversation.summary();
+    let _ = app.emit("feed-digest", &summary);
+    let state = app.state::<AppState>();
+    let body = {
+        let i18n = state.i18n.lock_or_recover();
+        i18n.t_count("notify-feed-digest", digested.len())
+    };
+    notify_relayed(
+        app,
+        RelayCategory::Scheduled,
+        Notice {
+            kind: NoticeKind::Info,
+            title: tr(app, "notify-title"),
+            body,
+            service: None,
+            actions: Vec::new(),
+        },
+    );
+    Ok(Some(summary))
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn create_feed_digest(app: AppHandle) -> Result<Option<ConversationSummary>, AppError> {
+    Ok(generate_feed_digest(&app).await?)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn set_feed_schedule(
+    app: AppHandle,
+    poll_minutes: Option<u32>,
+    digest_hours: Option<u32>,
+) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        settings.feeds = FeedSettings {
+            poll_minutes: poll_minutes.map(|m| m.max(5)),
+            digest_hours: digest_hours.map(|h| h.max(1)),
+        };
+        settings.clone()
+    };
+    settings::save(&active_data_dir(&app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    Ok(())
+}
+
+/// Poll when the interval has passed, then digest when one is due.
+pub async fn run_feed_jobs(app: &AppHandle) {
+    let Ok(schedule) = app.state::<AppState>().settings.lock().map(|s| s.feeds.clone()) else {
+        return;
+    };
+    let Ok(dir) = active_data_dir(app) else {
+        return;
+    };
+    let store = load(&dir);
+    if store.feeds.is_empty() {
+        return;
+    }
+    let now = conversations::now();
+    let poll_secs = u64::from(schedule.poll_minutes.unwrap_or(DEFAULT_POLL_MINUTES)) * 60;
+    if now.saturating_sub(store.last_poll) >= poll_secs {
+        if let Err(e) = poll_feeds(app).await {
+            eprintln!("[tulsbot] Feed poll failed: {}", e);
+        }
+    }
+    let Some(hours) = schedule.digest_hours else {
+        return;
+    };
+    if now.saturating_sub(store.last_digest) >= u64::from(hours.max(1)) * 3600 {
+        if let Err(e) = generate_feed_digest(app).await {
+            eprintln!("[tulsbot] Feed digest failed: {}", e);
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tulsbot_macros::instrumented;
+
+use crate::conversations;
+use crate::alerts::refresh_tray_title;
+use crate::error::AppError;
+use crate::i18n::tr;
+use crate::locks::LockExt;
+use crate::notifications::{self, notify, notify_held, refresh_dnd, Notice, NoticeKind};
+use crate::state::AppState;
+use crate::tray::refresh_tray_menu;
+
+// ── Focus sessions ──────────────────────────────────────────────────────────
+//
+// A pomodoro-style timer started from the tray or the UI. While a session
+// runs the tray title counts down, non-critical notifications can be held
+// back the way they are during the OS Do Not Disturb, and the OS Do Not
+// Disturb itself can be switched on for the session's length. Sessions live
+// in memory; one running at quit is simply dropped.
+
+const DEFAULT_MINUTES: u32 = 25;
+const MAX_MINUTES: u32 = 4 * 60;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FocusSettings {
+    /// Session length when none is asked for; `None` is 25 minutes.
+    pub minutes: Option<u32>,
+    /// Turn on the OS Do Not Disturb for the session, unless it already is.
+    pub enable_dnd: bool,
+    /// Hold back non-critical notifications until the session ends.
+    pub suppress_notifications: bool,
+}
+
+impl FocusSettings {
+    pub fn minutes(&self) -> u32 {
+        self.minutes.unwrap_or(DEFAULT_MINUTES)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusSession {
+    pub label: Option<String>,
+    pub minutes: u32,
+    /// Unix seconds.
+    pub started_at: u64,
+    pub ends_at: u64,
+    /// The session turned the OS Do Not Disturb on and turns it off again.
+    pub dnd_enabled: bool,
+    pub suppressing: bool,
+}
+
+impl FocusSession {
+    pub fn new(minutes: u32, label: Option<String>, now: u64) -> Self {
+        Self {
+            label,
+            minutes,
+            started_at: now,
+            ends_at: now + u64::from(minutes) * 60,
+            dnd_enabled: false,
+            suppressing: false,
+        }
+    }
+
+    pub fn remaining(&self, now: u64) -> u64 {
+        self.ends_at.saturating_sub(now)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FocusStatus {
+    pub session: Option<FocusSession>,
+    pub remaining_secs: u64,
+}
+
+pub fn status(session: Option<&FocusSession>, now: u64) -> FocusStatus {
+    FocusStatus {
+        session: session.cloned(),
+        remaining_secs: session.map(|s| s.remaining(now)).unwrap_or(0),
+    }
+}
+
+pub fn validate_minutes(minutes: u32) -> Result<u32, String> {
+    if minutes == 0 || minutes > MAX_MINUTES {
+        return Err(format!("Focus sessions last 1 to {} minutes", MAX_MINUTES));
+    }
+    Ok(minutes)
+}
+
+/// `m:ss` for the tray title.
+pub fn countdown(secs: u64) -> String {
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/// Start a session of `minutes` (the configured length when `None`),
+/// replacing a running one.
+pub async fn start_focus_session(
+    app: &AppHandle,
+    minutes: Option<u32>,
+    label: Option<String>,
+) -> Result<FocusStatus, String> {
+    let settings = app.state::<AppState>().settings.lock_or_recover().focus.clone();
+    let minutes = validate_minutes(minutes.unwrap_or(settings.minutes()))?;
+    // A replaced session hands over the DND it turned on
+    let replaced = app.state::<AppState>().focus.lock_or_recover().take();
+    let mut session = FocusSession::new(minutes, label, conversations::now());
+    session.dnd_enabled = replaced.as_ref().is_some_and(|s| s.dnd_enabled);
+    if settings.enable_dnd && !session.dnd_enabled {
+        session.dnd_enabled = tauri::async_runtime::spawn_blocking(|| {
+            !notifications::dnd_active() && notifications::set_dnd(true).is_ok()
+        })
+        .await
+        .unwrap_or(false);
+    } else if !settings.enable_dnd && session.dnd_enabled {
+        switch_dnd_off(app);
+        session.dnd_enabled = false;
+    }
+    session.suppressing = settings.suppress_notifications;
+    let state = app.state::<AppState>();
+    let held = state.notifications.lock_or_recover().set_focus(session.suppressing);
+    if let Some(held) = held {
+        notify_held(app, &held);
+    }
+
+    let started_at = session.started_at;
+    *state.focus.lock_or_recover() = Some(session);
+    publish_focus(app);
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move { run_focus_timer(&handle, started_at).await });
+    Ok(focus_status(app))
+}
+
+/// End the running session; `completed` when its time ran out rather than
+/// it being stopped.
+pub fn end_focus_session(app: &AppHandle, completed: bool) {
+    let state = app.state::<AppState>();
+    let Some(session) = state.focus.lock_or_recover().take() else {
+        return;
+    };
+    if session.dnd_enabled {
+        switch_dnd_off(app);
+    }
+    let held = state.notifications.lock_or_recover().set_focus(false);
+    if let Some(held) = held {
+        notify_held(app, &held);
+    }
+    publish_focus(app);
+    if completed {
+        let body = match (state.i18n.lock(), &session.label) {
+            (Ok(i18n), Some(label)) => i18n.t_args("notify-focus-done-label", &[("label", label)]),
+            (Ok(i18n), None) => i18n.t("notify-focus-done"),
+            (Err(_), _) => "Focus session finished".to_string(),
+        };
+        let title = tr(app, "notify-title");
+        notify(
+            app,
+            Notice { kind: NoticeKind::Info, title, body, service: None, actions: Vec::new() },
+        );
+    }
+}
+
+pub fn switch_dnd_off(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = tauri::async_runtime::spawn_blocking(|| notifications::set_dnd(false)).await;
+        if let Ok(Err(e)) = result {
+            eprintln!("[tulsbot] Failed to turn off Do Not Disturb: {}", e);
+        }
+        refresh_dnd(&app).await;
+    });
+}
+
+/// Count the tray title down each second until the session started at
+/// `started_at` ends or is replaced.
+pub async fn run_focus_timer(app: &AppHandle, started_at: u64) {
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        let remaining = match app.state::<AppState>().focus.lock_or_recover().as_ref() {
+            Some(session) if session.started_at == started_at => {
+                session.remaining(conversations::now())
+            }
+            _ => return,
+        };
+        if remaining == 0 {
+            end_focus_session(app, true);
+            return;
+        }
+        refresh_tray_title(app);
+    }
+}
+
+pub fn focus_status(app: &AppHandle) -> FocusStatus {
+    let session = app.state::<AppState>().focus.lock_or_recover().clone();
+    status(session.as_ref(), conversations::now())
+}
+
+/// Update the tray title and menu and emit `focus-changed`.
+pub fn publish_focus(app: &AppHandle) {
+    refresh_tray_title(app);
+    refresh_tray_menu(app);
+    let _ = app.emit("focus-changed", focus_status(app));
+}
+
+/// Start a focus session of `minutes`, or the configured length. Replaces a
+/// running session.
+#[instrumented]
+#[tauri::command]
+pub async fn start_focus(
+    app: AppHandle,
+    minutes: Option<u32>,
+    label: Option<String>,
+) -> Result<FocusStatus, AppError> {
+    Ok(start_focus_session(&app, minutes, label).await?)
+}
+
+/// Stop the running session early, without the end-of-session notification.
+#[instrumented]
+#[tauri::command]
+pub async fn stop_focus(app: AppHandle) -> Result<FocusStatus, AppError> {
+    end_focus_session(&app, false);
+    Ok(focus_status(&app))
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn get_focus_status(app: AppHandle) -> Result<FocusStatus, AppError> {
+    Ok(focus_status(&app))
+}
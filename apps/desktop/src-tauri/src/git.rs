@@ -0,0 +1,412 @@
+use git2::{BlameOptions, DiffFormat, DiffOptions, Repository, Sort, Status, StatusOptions};
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager};
+use tulsbot_macros::instrumented;
+
+use crate::settings;
+use crate::error::AppError;
+use crate::locks::LockExt;
+use crate::profiles::active_data_dir;
+use crate::state::AppState;
+
+// ── Read-only git queries (git2) ────────────────────────────────────────────
+//
+// The assistant may only look at repositories under roots the user approved
+// (`Settings::git_roots`). Nothing here writes to a repository: no checkout,
+// no fetch, no index refresh written back.
+
+/// Patch text returned at most; longer diffs are cut with a marker.
+const MAX_PATCH: usize = 256 * 1024;
+/// Commits returned by `log` when no limit is given.
+const DEFAULT_LOG_LIMIT: usize = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileStatus {
+    pub path: String,
+    /// `new`, `modified`, `deleted`, `renamed`, `typechange` or `conflicted`.
+    pub index: Option<String>,
+    pub worktree: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoStatus {
+    pub root: String,
+    pub branch: Option<String>,
+    pub head: Option<String>,
+    pub files: Vec<FileStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffSummary {
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub patch: String,
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitInfo {
+    pub id: String,
+    pub summary: String,
+    pub author: String,
+    /// Unix seconds.
+    pub time: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlameLine {
+    /// 1-based.
+    pub line: usize,
+    pub text: String,
+    pub commit: String,
+    pub author: String,
+    pub time: i64,
+}
+
+/// Resolve `path` to a repository work tree inside one of `roots`.
+pub fn open(path: &Path, roots: &[PathBuf]) -> Result<Repository, String> {
+    let repo = Repository::discover(path).map_err(|e| e.message().to_string())?;
+    let workdir = repo
+        .workdir()
+        .ok_or("Bare repositories are not supported")?
+        .canonicalize()
+        .map_err(|e| e.to_string())?;
+    let approved = roots
+        .iter()
+        .filter_map(|root| root.canonicalize().ok())
+        .any(|root| workdir.starts_with(root));
+    if !approved {
+        return Err(format!("{} is not under an approved repository root", workdir.display()));
+    }
+    Ok(repo)
+}
+
+/// The work tree root of the repository containing `path`.
+pub fn root_of(path: &Path) -> Result<PathBuf, String> {
+    let repo = Repository::discover(path).map_err(|e| e.message().to_string())?;
+    let workdir = repo.workdir().ok_or("Bare repositories are not supported")?;
+    workdir.canonicalize().map_err(|e| e.to_string())
+}
+
+/// A repository-relative path with no escapes out of the work tree.
+fn relative(path: &str) -> Result<&Path, String> {
+    let path = Path::new(path);
+    let safe = path.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if path.as_os_str().is_empty() || !safe {
+        return Err(format!("Path must be relative to the repository: {}", path.display()));
+    }
+    Ok(path)
+}
+
+fn change(status: Status, index: bool) -> Option<String> {
+    let flags = if index {
+        [
+            (Status::INDEX_NEW, "new"),
+            (Status::INDEX_MODIFIED, "modified"),
+            (Status::INDEX_DELETED, "deleted"),
+            (Status::INDEX_RENAMED, "renamed"),
+            (Status::INDEX_TYPECHANGE, "typechange"),
+        ]
+    } else {
+        [
+            (Status::WT_NEW, "new"),
+            (Status::WT_MODIFIED, "modified"),
+            (Status::WT_DELETED, "deleted"),
+            (Status::WT_RENAMED, "renamed"),
+            (Status::WT_TYPECHANGE, "typechange"),
+        ]
+    };
+    if status.contains(Status::CONFLICTED) {
+        return Some("conflicted".into());
+    }
+    flags.iter().find(|(flag, _)| status.contains(*flag)).map(|(_, name)| name.to_string())
+}
+
+pub fn status(repo: &Repository) -> Result<RepoStatus, String> {
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(true).recurse_untracked_dirs(true);
+    let statuses = repo.statuses(Some(&mut opts)).map_err(|e| e.message().to_string())?;
+    let files = statuses
+        .iter()
+        .filter_map(|entry| {
+            let status = entry.status();
+            Some(FileStatus {
+                path: entry.path()?.to_string(),
+                index: change(status, true),
+                worktree: change(status, false),
+            })
+        })
+        .collect();
+    let head = repo.head().ok();
+    Ok(RepoStatus {
+        root: repo.workdir().map(|p| p.display().to_string()).unwrap_or_default(),
+        branch: head.as_ref().and_then(|h| h.shorthand().map(String::from)),
+        head: head.as_ref().and_then(|h| h.target()).map(|id| id.to_string()),
+        files,
+    })
+}
+
+/// The newest commit at or before `time` (Unix seconds) on HEAD.
+fn commit_before(repo: &Repository, time: i64) -> Result<Option<git2::Commit<'_>>, String> {
+    let mut walk = repo.revwalk().map_err(|e| e.message().to_string())?;
+    walk.push_head().map_err(|e| e.message().to_string())?;
+    walk.set_sorting(Sort::TIME).map_err(|e| e.message().to_string())?;
+    for id in walk {
+        let commit = repo
+            .find_commit(id.map_err(|e| e.message().to_string())?)
+            .map_err(|e| e.message().to_string())?;
+        if commit.time().seconds() <= time {
+            return Ok(Some(commit));
+        }
+    }
+    Ok(None)
+}
+
+/// Changes of the work tree (staged and unstaged) against HEAD, or against
+/// the state of HEAD at `since` (Unix seconds) when given.
+pub fn diff(
+    repo: &Repository,
+    path: Option<&str>,
+    since: Option<i64>,
+) -> Result<DiffSummary, String> {
+    let mut opts = DiffOptions::new();
+    opts.include_untracked(true).recurse_untracked_dirs(true);
+    if let Some(path) = path {
+        opts.pathspec(relative(path)?);
+    }
+    let base = match since {
+        Some(time) => commit_before(repo, time)?,
+        None => repo.head().ok().and_then(|h| h.peel_to_commit().ok()),
+    };
+    let tree = match &base {
+        Some(commit) => Some(commit.tree().map_err(|e| e.message().to_string())?),
+        None => None,
+    };
+    let diff = repo
+        .diff_tree_to_workdir_with_index(tree.as_ref(), Some(&mut opts))
+        .map_err(|e| e.message().to_string())?;
+    let stats = diff.stats().map_err(|e| e.message().to_string())?;
+
+    let mut patch = String::new();
+    let mut truncated = false;
+    diff.print(DiffFormat::Patch, |_, _, line| {
+        if patch.len() >= MAX_PATCH {
+            truncated = true;
+            return false;
+        }
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            patch.push(line.origin());
+        }
+        patch.push_str(&String::from_utf8_lossy(line.content()));
+        true
+    })
+    .or_else(|e| if truncated { Ok(()) } else { Err(e.message().to_string()) })?;
+    if truncated {
+        patch.push_str("\n[diff truncated]\n");
+    }
+    Ok(DiffSummary {
+        files_changed: stats.files_changed(),
+        insertions: stats.insertions(),
+        deletions: stats.deletions(),
+        patch,
+        truncated,
+    })
+}
+
+fn touches(commit: &git2::Commit<'_>, path: &Path) -> bool {
+    let entry_id = |tree: Option<git2::Tree<'_>>| {
+        tree.and_then(|t| t.get_path(path).ok()).map(|entry| entry.id())
+    };
+    let ours = entry_id(commit.tree().ok());
+    if commit.parent_count() == 0 {
+        return ours.is_some();
+    }
+    commit
+        .parents()
+        .any(|parent| entry_id(parent.tree().ok()) != ours)
+}
+
+/// Commits on HEAD, newest first, optionally only those touching `path` or
+/// made after `since` (Unix seconds).
+pub fn log(
+    repo: &Repository,
+    path: Option<&str>,
+    since: Option<i64>,
+    limit: Option<usize>,
+) -> Result<Vec<CommitInfo>, String> {
+    let path = path.map(relative).transpose()?;
+    let mut walk = repo.revwalk().map_err(|e| e.message().to_string())?;
+    walk.push_head().map_err(|e| e.message().to_string())?;
+    walk.set_sorting(Sort::TIME).map_err(|e| e.message().to_string())?;
+    let mut commits = Vec::new();
+    for id in walk {
+        let commit = repo
+            .find_commit(id.map_err(|e| e.message().to_string())?)
+            .map_err(|e| e.message().to_string())?;
+        let time = commit.time().seconds();
+        if since.is_some_and(|since| time < since) {
+            break;
+        }
+        if path.is_some_and(|path| !touches(&commit, path)) {
+            continue;
+        }
+        commits.push(CommitInfo {
+            id: commit.id().to_string(),
+            summary: commit.summary().unwrap_or_default().to_string(),
+            author: commit.author().name().unwrap_or_default().to_string(),
+            time,
+        });
+        if commits.len() >= limit.unwrap_or(DEFAULT_LOG_LIMIT) {
+            break;
+        }
+    }
+    Ok(commits)
+}
+
+/// Last commit touching each line of `path` as committed on HEAD.
+pub fn blame(repo: &Repository, path: &str) -> Result<Vec<BlameLine>, String> {
+    let path = relative(path)?;
+    let head = repo
+        .head()
+        .and_then(|h| h.peel_to_commit())
+        .map_err(|e| e.message().to_string())?;
+    let blob = head
+        .tree()
+        .and_then(|t| t.get_path(path))
+        .and_then(|entry| entry.to_object(repo))
+        .and_then(|object| object.peel_to_blob())
+        .map_err(|e| e.message().to_string())?;
+    if blob.is_binary() {
+        return Err(format!("{} is a binary file", path.display()));
+    }
+    let text = String::from_utf8_lossy(blob.content()).into_owned();
+    let blame = repo
+        .blame_file(path, Some(&mut BlameOptions::new()))
+        .map_err(|e| e.message().to_string())?;
+    Ok(text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let hunk = blame.get_line(i + 1);
+            let signature = hunk.as_ref().map(|h| h.final_signature());
+            BlameLine {
+                line: i + 1,
+                text: line.to_string(),
+                commit: hunk.as_ref().map(|h| h.final_commit_id().to_string()).unwrap_or_default(),
+                author: signature
+                    .as_ref()
+                    .and_then(|s| s.name().map(String::from))
+                    .unwrap_or_default(),
+                time: signature.as_ref().map_or(0, |s| s.when().seconds()),
+            }
+        })
+        .collect())
+}
+
+pub fn git_roots(app: &AppHandle) -> Result<Vec<PathBuf>, String> {
+    let state = app.state::<AppState>();
+    let settings = state.settings.lock_or_recover();
+    Ok(settings.git_roots.clone())
+}
+
+pub fn update_git_roots(
+    app: &AppHandle,
+    change: impl FnOnce(&mut Vec<PathBuf>),
+) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        change(&mut settings.git_roots);
+        settings.clone()
+    };
+    settings::save(&active_data_dir(app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    Ok(())
+}
+
+/// Run a read-only query against the approved repository containing `repo`.
+pub async fn with_repo<T: Send + 'static>(
+    app: &AppHandle,
+    repo: String,
+    query: impl FnOnce(&git2::Repository) -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    let roots = git_roots(app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        query(&open(std::path::Path::new(&repo), &roots)?)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn list_git_roots(app: AppHandle) -> Result<Vec<PathBuf>, AppError> {
+    Ok(git_roots(&app)?)
+}
+
+/// Approve the repository containing `path` for the git tools.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn add_git_root(app: AppHandle, path: String) -> Result<PathBuf, AppError> {
+    let root =
+        tauri::async_runtime::spawn_blocking(move || root_of(std::path::Path::new(&path)))
+            .await
+            .map_err(|e| e.to_string())??;
+    let added = root.clone();
+    update_git_roots(&app, |roots| {
+        if !roots.contains(&added) {
+            roots.push(added);
+        }
+    })?;
+    Ok(root)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn remove_git_root(app: AppHandle, path: String) -> Result<(), AppError> {
+    let path = PathBuf::from(path);
+    Ok(update_git_roots(&app, |roots| roots.retain(|root| *root != path))?)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn git_status(app: AppHandle, repo: String) -> Result<RepoStatus, AppError> {
+    Ok(with_repo(&app, repo, status).await?)
+}
+
+/// Work tree changes against HEAD, or against HEAD as of `since` (Unix
+/// seconds) for "what changed since yesterday".
+#[instrumented]
+#[tauri::command]
+pub async fn git_diff(
+    app: AppHandle,
+    repo: String,
+    path: Option<String>,
+    since: Option<i64>,
+) -> Result<DiffSummary, AppError> {
+    Ok(with_repo(&app, repo, move |r| diff(r, path.as_deref(), since)).await?)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn git_log(
+    app: AppHandle,
+    repo: String,
+    path: Option<String>,
+    since: Option<i64>,
+    limit: Option<usize>,
+) -> Result<Vec<CommitInfo>, AppError> {
+    Ok(with_repo(&app, repo, move |r| log(r, path.as_deref(), since, limit)).await?)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn git_blame(
+    app: AppHandle,
+    repo: String,
+    path: String,
+) -> Result<Vec<BlameLine>, AppError> {
+    Ok(with_repo(&app, repo, move |r| blame(r, &path)).await?)
+}
@@ -0,0 +1,193 @@
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tulsbot_macros::instrumented;
+
+use crate::error::AppError;
+use crate::locks::LockExt;
+use crate::state::AppState;
+
+// ── Guardrails: filters on provider responses ───────────────────────────────
+//
+// The inbound counterpart of redaction. Replies pass through the profile's
+// filters before they are saved to a conversation or shown in a window:
+// keyword and regex rules that mask or block, then code-only mode, which
+// keeps just the fenced code blocks, then a length cap. Streamed responses
+// are filtered on every update, so a blocked reply never shows its text.
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    /// Replace each match with `[FILTERED:<rule>]`.
+    #[default]
+    Mask,
+    /// Replace the whole response with a notice.
+    Block,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterRule {
+    pub name: String,
+    /// A keyword matched case-insensitively as a whole word, or a regex.
+    pub pattern: String,
+    pub regex: bool,
+    pub action: RuleAction,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuardrailSettings {
+    pub enabled: bool,
+    pub rules: Vec<FilterRule>,
+    /// Keep only fenced code blocks; a reply without any is left whole.
+    pub code_only: bool,
+    /// Characters kept; longer replies are cut off with a marker.
+    pub max_chars: Option<usize>,
+}
+
+/// What the filters did to one response.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Filtered {
+    pub text: String,
+    /// Rules that matched, masking or blocking.
+    pub rules: Vec<String>,
+    pub blocked: bool,
+    pub code_only: bool,
+    pub truncated: bool,
+}
+
+impl Filtered {
+    pub fn changed(&self) -> bool {
+        !self.rules.is_empty() || self.blocked || self.code_only || self.truncated
+    }
+}
+
+/// Reject rules without a name or pattern, or whose regex doesn't compile.
+pub fn validate(settings: &GuardrailSettings) -> Result<(), String> {
+    Guardrails::new(settings).map(drop)
+}
+
+/// Compiled filters for one settings value.
+pub struct Guardrails {
+    rules: Vec<(String, Regex, RuleAction)>,
+    code_only: bool,
+    max_chars: Option<usize>,
+}
+
+impl Guardrails {
+    pub fn new(settings: &GuardrailSettings) -> Result<Self, String> {
+        let mut rules = Vec::new();
+        for rule in &settings.rules {
+            if rule.name.trim().is_empty() || rule.pattern.is_empty() {
+                return Err("Guardrail rules need a name and a pattern".into());
+            }
+            let pattern = if rule.regex {
+                rule.pattern.clone()
+            } else {
+                format!(r"\b{}\b", regex::escape(rule.pattern.trim()))
+            };
+            let regex = RegexBuilder::new(&pattern)
+                .case_insensitive(!rule.regex)
+                .build()
+                .map_err(|e| format!("Rule '{}': {}", rule.name, e))?;
+            rules.push((rule.name.clone(), regex, rule.action));
+        }
+        Ok(Self { rules, code_only: settings.code_only, max_chars: settings.max_chars })
+    }
+
+    pub fn apply(&self, text: &str) -> Filtered {
+        let mut filtered = Filtered { text: text.to_string(), ..Default::default() };
+        for (name, regex, action) in &self.rules {
+            if !regex.is_match(&filtered.text) {
+                continue;
+            }
+            filtered.rules.push(name.clone());
+            if *action == RuleAction::Block {
+                filtered.text = format!("[Response blocked by guardrail '{}']", name);
+                filtered.blocked = true;
+                return filtered;
+            }
+            let mask = format!("[FILTERED:{}]", name);
+            filtered.text = regex.replace_all(&filtered.text, mask.as_str()).into_owned();
+        }
+        if self.code_only {
+            if let Some(code) = code_blocks(&filtered.text) {
+                filtered.code_only = code != filtered.text;
+                filtered.text = code;
+            }
+        }
+        if let Some(max) = self.max_chars {
+            if let Some((cut, _)) = filtered.text.char_indices().nth(max) {
+                filtered.text.truncate(cut);
+                filtered.text.push_str("\n\n[…truncated]");
+                filtered.truncated = true;
+            }
+        }
+        filtered
+    }
+}
+
+/// The fenced code blocks of `text`, fences included, or `None` when it has
+/// none. A block still open at the end (a reply mid-stream) counts.
+fn code_blocks(text: &str) -> Option<String> {
+    let mut blocks: Vec<String> = Vec::new();
+    let mut current: Option<String> = None;
+    for line in text.lines() {
+        let fence = line.trim_start().starts_with("```");
+        if let Some(block) = current.as_mut() {
+            block.push_str(line);
+            if fence {
+                blocks.extend(current.take());
+            } else {
+                block.push('\n');
+            }
+        } else if fence {
+            current = Some(format!("{}\n", line));
+        }
+    }
+    blocks.extend(current);
+    (!blocks.is_empty()).then(|| blocks.join("\n\n"))
+}
+
+/// Run a response through the profile's guardrails. Rules that fail to
+/// compile (settings edited by hand) let the response through unfiltered.
+pub fn guard_response(app: &AppHandle, text: &str) -> Filtered {
+    let settings = app.state::<AppState>().settings.lock_or_recover().guardrails.clone();
+    let unchanged = || Filtered { text: text.to_string(), ..Default::default() };
+    if !settings.enabled {
+        return unchanged();
+    }
+    match Guardrails::new(&settings) {
+        Ok(guardrails) => guardrails.apply(text),
+        Err(e) => {
+            eprintln!("[tulsbot] Guardrails skipped: {}", e);
+            unchanged()
+        }
+    }
+}
+
+/// Emit `guardrail-applied` when the filters changed a response bound for
+/// `destination`.
+pub fn report_guardrails(app: &AppHandle, filtered: &Filtered, destination: &str) {
+    if !filtered.changed() {
+        return;
+    }
+    let _ = app.emit(
+        "guardrail-applied",
+        serde_json::json!({
+            "destination": destination,
+            "rules": filtered.rules,
+            "blocked": filtered.blocked,
+            "code_only": filtered.code_only,
+            "truncated": filtered.truncated,
+        }),
+    );
+}
+
+/// What the guardrails would make of `text`.
+#[instrumented]
+#[tauri::command]
+pub async fn preview_guardrails(app: AppHandle, text: String) -> Result<Filtered, AppError> {
+    Ok(guard_response(&app, &text))
+}
@@ -0,0 +1,267 @@
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use tauri::{AppHandle, Manager};
+use tulsbot_macros::instrumented;
+
+use crate::error::AppError;
+use crate::locks::LockExt;
+use crate::state::AppState;
+
+// ── GPU / accelerator detection for local models ────────────────────────────
+//
+// Asks the platform tools instead of linking vendor SDKs: nvidia-smi for
+// NVIDIA cards (and CUDA), system_profiler on macOS (Metal), CIM on Windows
+// (DirectML runs on any DirectX 12 adapter) and lspci plus sysfs on Linux.
+// Every probe is best effort; a missing tool just means nothing found.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    Cpu,
+    Cuda,
+    Metal,
+    DirectMl,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GpuInfo {
+    pub name: String,
+    /// Dedicated memory; `None` when unknown or shared with the system.
+    pub vram_mb: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recommendations {
+    pub backend: Backend,
+    /// Largest Whisper model that runs comfortably.
+    pub whisper_model: String,
+    /// Whether local LLMs are worth offering at all.
+    pub local_models: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HardwareInfo {
+    pub gpus: Vec<GpuInfo>,
+    pub cuda: bool,
+    pub metal: bool,
+    pub directml: bool,
+    pub cpu_cores: usize,
+    pub memory_mb: Option<u64>,
+    pub recommended: Recommendations,
+}
+
+fn output(program: &str, args: &[&str]) -> Option<String> {
+    let out = Command::new(program).args(args).output().ok()?;
+    out.status
+        .success()
+        .then(|| String::from_utf8_lossy(&out.stdout).into_owned())
+}
+
+/// NVIDIA cards; a working nvidia-smi also means a CUDA-capable driver.
+fn nvidia() -> Vec<GpuInfo> {
+    let Some(text) = output(
+        "nvidia-smi",
+        &["--query-gpu=name,memory.total", "--format=csv,noheader,nounits"],
+    ) else {
+        return Vec::new();
+    };
+    text.lines()
+        .filter_map(|line| {
+            let (name, vram) = line.rsplit_once(',')?;
+            Some(GpuInfo { name: name.trim().into(), vram_mb: vram.trim().parse().ok() })
+        })
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+fn platform_gpus() -> Vec<GpuInfo> {
+    let Some(text) = output("system_profiler", &["SPDisplaysDataType", "-json"]) else {
+        return Vec::new();
+    };
+    let json: serde_json::Value = serde_json::from_str(&text).unwrap_or_default();
+    json["SPDisplaysDataType"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|gpu| GpuInfo {
+            name: gpu["sppci_model"].as_str().unwrap_or("GPU").to_string(),
+            // "8 GB" on discrete cards; Apple silicon shares system memory
+            vram_mb: gpu["spdisplays_vram"]
+                .as_str()
+                .or(gpu["spdisplays_vram_shared"].as_str())
+                .and_then(parse_size_mb),
+        })
+        .collect()
+}
+
+#[cfg(windows)]
+fn platform_gpus() -> Vec<GpuInfo> {
+    let Some(text) = output(
+        "powershell",
+        &[
+            "-NoProfile",
+            "-Command",
+            "Get-CimInstance Win32_VideoController \
+             | Select-Object Name,AdapterRAM | ConvertTo-Json",
+        ],
+    ) else {
+        return Vec::new();
+    };
+    let json: serde_json::Value = serde_json::from_str(&text).unwrap_or_default();
+    // A single adapter comes back as an object, several as an array
+    let adapters = match json {
+        serde_json::Value::Array(items) => items,
+        other => vec![other],
+    };
+    adapters
+        .iter()
+        .filter_map(|gpu| {
+            let name = gpu["Name"].as_str()?;
+            Some(GpuInfo {
+                name: name.to_string(),
+                // AdapterRAM is a uint32 and saturates at 4 GB
+                vram_mb: gpu["AdapterRAM"].as_u64().map(|b| b / (1024 * 1024)),
+            })
+        })
+        .collect()
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn platform_gpus() -> Vec<GpuInfo> {
+    let Some(text) = output("lspci", &[]) else {
+        return Vec::new();
+    };
+    // AMD cards report VRAM through amdgpu's sysfs files
+    let amd_vram: Vec<u64> = std::fs::read_dir("/sys/class/drm")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|card| {
+            let bytes = std::fs::read_to_string(card.path().join("device/mem_info_vram_total"));
+            bytes.ok()?.trim().parse::<u64>().ok()
+        })
+        .map(|b| b / (1024 * 1024))
+        .collect();
+    let mut amd = amd_vram.into_iter();
+    text.lines()
+        .filter(|l| l.contains("VGA compatible controller") || l.contains("3D controller"))
+        .filter_map(|l| l.split_once(": ").map(|(_, name)| name.trim().to_string()))
+        .map(|name| {
+            let is_amd = name.contains("AMD") || name.contains("ATI");
+            GpuInfo { vram_mb: if is_amd { amd.next() } else { None }, name }
+        })
+        .collect()
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_size_mb(text: &str) -> Option<u64> {
+    let (number, unit) = text.trim().split_once(' ')?;
+    let number: u64 = number.parse().ok()?;
+    match unit {
+        "GB" => Some(number * 1024),
+        "MB" => Some(number),
+        _ => None,
+    }
+}
+
+fn memory_mb() -> Option<u64> {
+    if cfg!(target_os = "macos") {
+        output("sysctl", &["-n", "hw.memsize"])?
+            .trim()
+            .parse::<u64>()
+            .ok()
+            .map(|b| b / (1024 * 1024))
+    } else if cfg!(windows) {
+        let query = "(Get-CimInstance Win32_ComputerSystem).TotalPhysicalMemory";
+        output("powershell", &["-NoProfile", "-Command", query])?
+            .trim()
+            .parse::<u64>()
+            .ok()
+            .map(|b| b / (1024 * 1024))
+    } else {
+        let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+        let line = meminfo.lines().find(|l| l.starts_with("MemTotal:"))?;
+        let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+        Some(kb / 1024)
+    }
+}
+
+fn recommend(gpus: &[GpuInfo], backend: Backend, memory_mb: Option<u64>) -> Recommendations {
+    // Apple silicon shares system memory with the GPU; count about half of it
+    let usable_mb = match backend {
+        Backend::Metal => memory_mb.map(|m| m / 2),
+        Backend::Cpu => None,
+        _ => gpus.iter().filter_map(|g| g.vram_mb).max(),
+    }
+    .unwrap_or(0);
+    let whisper_model = match usable_mb {
+        m if m >= 10 * 1024 => "large-v3",
+        m if m >= 5 * 1024 => "medium",
+        m if m >= 2 * 1024 => "small",
+        _ => "base",
+    };
+    Recommendations {
+        backend,
+        whisper_model: whisper_model.into(),
+        local_models: usable_mb >= 4 * 1024,
+    }
+}
+
+/// Probe the machine. Blocking; takes up to a few seconds on first run.
+pub fn detect() -> HardwareInfo {
+    let nvidia = nvidia();
+    let cuda = !nvidia.is_empty();
+    let mut gpus = platform_gpus();
+    // Prefer nvidia-smi's entries, which know the real VRAM
+    if cuda {
+        gpus.retain(|g| !g.name.to_ascii_lowercase().contains("nvidia"));
+        gpus.splice(0..0, nvidia);
+    }
+    let metal = cfg!(target_os = "macos") && !gpus.is_empty();
+    let directml = cfg!(windows) && !gpus.is_empty();
+    let backend = if cuda {
+        Backend::Cuda
+    } else if metal {
+        Backend::Metal
+    } else if directml {
+        Backend::DirectMl
+    } else {
+        Backend::Cpu
+    };
+    let memory_mb = memory_mb();
+    HardwareInfo {
+        recommended: recommend(&gpus, backend, memory_mb),
+        gpus,
+        cuda,
+        metal,
+        directml,
+        cpu_cores: std::thread::available_parallelism().map_or(1, |n| n.get()),
+        memory_mb,
+    }
+}
+
+/// The machine's GPUs and accelerators, probed once and cached. Blocking.
+pub fn hardware_info(app: &AppHandle, refresh: bool) -> Result<HardwareInfo, String> {
+    let state = app.state::<AppState>();
+    if !refresh {
+        if let Some(info) = state.hardware.lock_or_recover().clone() {
+            return Ok(info);
+        }
+    }
+    let info = detect();
+    *state.hardware.lock_or_recover() = Some(info.clone());
+    Ok(info)
+}
+
+/// GPU presence, VRAM and Metal/CUDA/DirectML availability, with the
+/// recommended backend and Whisper model for local features.
+#[instrumented]
+#[tauri::command]
+pub async fn get_hardware_info(
+    app: AppHandle,
+    refresh: Option<bool>,
+) -> Result<HardwareInfo, AppError> {
+    Ok(tauri::async_runtime::spawn_blocking(move || hardware_info(&app, refresh.unwrap_or(false)))
+        .await
+        .map_err(|e| e.to_string())??)
+}
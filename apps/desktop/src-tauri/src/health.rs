@@ -0,0 +1,482 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+use tauri::State;
+use tauri::{image::Image, AppHandle, Emitter, Manager};
+use tulsbot_macros::instrumented;
+
+use crate::{deps, readiness};
+use crate::alerts::{evaluate_alerts, publish_alerts, service_samples, AlertEngine};
+use crate::chat_probe::ChatProbeResult;
+use crate::conversations::now_millis;
+use crate::error::{AppError, ErrorKind};
+use crate::external::ExternalHealth;
+use crate::history::{HealthHistory, HealthSample};
+use crate::hooks::{self, Hook, HookContext, HookResult, HookTrigger};
+use crate::i18n::status_tooltip;
+use crate::locks::LockExt;
+use crate::notifications::health_transitions;
+use crate::profiles::{self, Profile, ServiceDef};
+use crate::relay::{notify_relayed, RelayCategory};
+use crate::remediation::remediate;
+use crate::state::AppState;
+
+// ── Health state ────────────────────────────────────────────────────────────
+//
+// The services of the active profile are polled every few seconds. Each
+// poll rolls up into healthy, degraded or down, which the tray icon,
+// notices, alert rules and automatic recovery all work from. A supervisor
+// restarts the poller when it dies or stops reporting.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceHealth {
+    pub name: String,
+    pub healthy: bool,
+    pub port: u16,
+    /// Healthy and passing its readiness check.
+    #[serde(default)]
+    pub ready: bool,
+    /// Dependencies that are down while this service is: the likely cause.
+    #[serde(default)]
+    pub blocked_by: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthState {
+    pub services: Vec<ServiceHealth>,
+    pub overall: String, // "healthy", "degraded", "down"
+    /// Unix seconds of the poll that produced this snapshot; `None` until the
+    /// first poll of the active profile completes.
+    #[serde(default)]
+    pub checked_at: Option<u64>,
+    /// Latency to external providers, probed on its own schedule.
+    #[serde(default)]
+    pub external: Vec<ExternalHealth>,
+    /// Latest run of each health hook.
+    #[serde(default)]
+    pub hooks: Vec<HookResult>,
+    /// Latest end-to-end chat probe, when enabled.
+    #[serde(default)]
+    pub chat_probe: Option<ChatProbeResult>,
+}
+
+impl HealthState {
+    /// Initial (all down) snapshot for a set of monitored services.
+    pub fn for_services(services: &[ServiceDef]) -> Self {
+        Self {
+            services: services
+                .iter()
+                .map(|s| ServiceHealth {
+                    name: s.name.clone(),
+                    healthy: false,
+                    port: s.port,
+                    ready: false,
+                    blocked_by: Vec::new(),
+                })
+                .collect(),
+            overall: "down".into(),
+            checked_at: None,
+            external: Vec::new(),
+            hooks: Vec::new(),
+            chat_probe: None,
+        }
+    }
+
+    /// Every service is ready (and at least one poll has completed).
+    pub fn ready(&self) -> bool {
+        self.checked_at.is_some() && self.services.iter().all(|s| s.ready)
+    }
+
+    pub fn not_ready(&self) -> Vec<&str> {
+        self.services
+            .iter()
+            .filter(|s| !s.ready)
+            .map(|s| s.name.as_str())
+            .collect()
+    }
+}
+
+impl Default for HealthState {
+    fn default() -> Self {
+        Self::for_services(&Profile::builtin().services)
+    }
+}
+
+/// Return the most recent health snapshot.
+#[instrumented]
+#[tauri::command]
+pub async fn get_health(state: State<'_, AppState>) -> Result<HealthState, AppError> {
+    let health = state.health.lock_or_recover();
+    Ok(health.clone())
+}
+
+/// Emergency reset of the monitoring state, for when what was recovered
+/// from a poisoned lock is wrong: health goes back to unknown for the active
+/// profile's services, history is reloaded from disk, remediation streaks
+/// and alerts start over. Re-polls right away.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn reset_state(app: AppHandle) -> Result<HealthState, AppError> {
+    let state = app.state::<AppState>();
+    let profile = state.profiles.lock_or_recover().active_profile();
+    let fresh = HealthState::for_services(&profile.services);
+    *state.health.lock_or_recover() = fresh.clone();
+    *state.history.lock_or_recover() = HealthHistory::load(&profiles::data_dir(&app, &profile)?);
+    state.remediation.lock_or_recover().reset();
+    *state.alerts.lock_or_recover() = AlertEngine::default();
+    eprintln!("[tulsbot] Monitoring state reset");
+    publish_alerts(&app);
+    let _ = app.emit("health-update", &fresh);
+
+    let poll_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = poll_handle.state::<AppState>();
+        poll_health(poll_handle.clone(), state.inner()).await;
+    });
+    Ok(fresh)
+}
+
+/// Resolve once every service passes its readiness check, or fail after
+/// `timeout_secs` (default 30) naming the services still not ready.
+#[instrumented]
+#[tauri::command]
+pub async fn wait_for_ready(
+    state: State<'_, AppState>,
+    timeout_secs: Option<u64>,
+) -> Result<(), AppError> {
+    let deadline = std::time::Instant::now()
+        + std::time::Duration::from_secs(timeout_secs.unwrap_or(30));
+    loop {
+        let not_ready: Vec<String> = {
+            let health = state.health.lock_or_recover();
+            if health.ready() {
+                return Ok(());
+            }
+            health.not_ready().into_iter().map(String::from).collect()
+        };
+        if std::time::Instant::now() >= deadline {
+            if not_ready.is_empty() {
+                let message = "Health checks haven't completed yet";
+                return Err(AppError::new(ErrorKind::NotReady, message));
+            }
+            let message = format!("Services not ready: {}", not_ready.join(", "));
+            return Err(AppError::new(ErrorKind::NotReady, message)
+                .with_details(serde_json::json!({ "services": not_ready })));
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+    }
+}
+
+// ── Health polling ──────────────────────────────────────────────────────────
+
+/// Overall status of `services`: "healthy" when all are up, "degraded"
+/// when some are, "down" when none are. No services at all count as healthy.
+pub fn rollup(services: &[ServiceHealth]) -> &'static str {
+    let healthy = services.iter().filter(|s| s.healthy).count();
+    if healthy == services.len() {
+        "healthy"
+    } else if healthy > 0 {
+        "degraded"
+    } else {
+        "down"
+    }
+}
+
+/// Colour the tray icon and set its tooltip for `overall`; the spinner
+/// picks the colour up itself.
+pub fn show_in_tray(app: &AppHandle, overall: &str) {
+    let Some(tray) = app.tray_by_id("main-tray") else {
+        return;
+    };
+    if !app.state::<AppState>().tray_animating.load(Ordering::SeqCst) {
+        if let Ok(icon) = Image::from_bytes(health_icon(overall)) {
+            let _ = tray.set_icon(Some(icon));
+            let _ = tray.set_icon_as_template(false);
+        }
+    }
+    let _ = tray.set_tooltip(Some(&status_tooltip(app, overall)));
+}
+
+async fn check_port(port: u16) -> bool {
+    tokio::net::TcpStream::connect(format!("127.0.0.1:{}", port))
+        .await
+        .is_ok()
+}
+
+pub async fn poll_health(app: AppHandle, state: &AppState) {
+    let profile = state.profiles.lock_or_recover().active_profile();
+    let ports = profile.services;
+    let hooks = state.settings.lock_or_recover().health_hooks.clone();
+
+    // Pre-poll hooks run first, side by side; synthetic ones count as
+    // services below
+    let pre_poll = hooks.iter().filter(|h| hooks::applies(h, HookTrigger::PrePoll, None));
+    let hook_results = futures_util::future::join_all(pre_poll.map(|hook| async move {
+        let result = hooks::run(hook, HookTrigger::PrePoll, &HookContext::default()).await;
+        (hook.synthetic, result)
+    }))
+    .await;
+
+    let mut services = Vec::new();
+    for service in &ports {
+        let healthy = check_port(service.port).await;
+        let ready = match &service.readiness {
+            Some(check) if healthy => readiness::check(service.port, check).await,
+            _ => healthy,
+        };
+        services.push(ServiceHealth {
+            name: service.name.clone(),
+            healthy,
+            port: service.port,
+            ready,
+            blocked_by: Vec::new(),
+        });
+    }
+    let mut blocked = deps::blocked_by(&ports, &services);
+    for service in &mut services {
+        service.blocked_by = blocked.remove(&service.name).unwrap_or_default();
+    }
+    for (_, result) in hook_results.iter().filter(|(synthetic, _)| *synthetic) {
+        services.push(ServiceHealth {
+            name: result.hook.clone(),
+            healthy: result.ok,
+            port: 0,
+            ready: result.ok,
+            blocked_by: Vec::new(),
+        });
+    }
+
+    let overall = rollup(&services).to_string();
+
+    show_in_tray(&app, &overall);
+
+    let checked_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .ok();
+    let mut new_health = HealthState {
+        services,
+        overall: overall.clone(),
+        checked_at,
+        external: Vec::new(),
+        hooks: hook_results.into_iter().map(|(_, result)| result).collect(),
+        chat_probe: None,
+    };
+
+    // Drop the result if the profile was switched while we were polling
+    let previous = {
+        let store = state.profiles.lock_or_recover();
+        if store.active != profile.name {
+            return;
+        }
+        if let Some(sample) = HealthSample::from_state(&new_health) {
+            state.history.lock_or_recover().push(sample);
+        }
+        let mut health = state.health.lock_or_recover();
+        new_health.external = health.external.clone();
+        new_health.chat_probe = health.chat_probe.clone();
+        // Results of hooks that run in the background carry over
+        let background = health.hooks.iter().filter(|h| h.trigger != HookTrigger::PrePoll);
+        new_health.hooks.extend(background.cloned());
+        std::mem::replace(&mut *health, new_health.clone())
+    };
+
+    if previous.checked_at.is_some() {
+        for notice in health_transitions(&app, &previous, &new_health) {
+            notify_relayed(&app, RelayCategory::Alerts, notice);
+        }
+    }
+    remediate(&app, state, &ports, &new_health);
+    if new_health.ready() && !previous.ready() {
+        let _ = app.emit("stack-ready", &new_health);
+    }
+
+    // Broadcast to all frontend windows
+    let _ = app.emit("health-update", &new_health);
+    evaluate_alerts(&app, service_samples(&new_health));
+
+    if previous.checked_at.is_some() {
+        run_background_hooks(&app, hooks, &previous, &new_health);
+    }
+}
+
+// ── Health poller supervision ───────────────────────────────────────────────
+//
+// A panic in the poll task, or a poll that never returns, would freeze the
+// health state without any sign. The poller records a heartbeat after every
+// poll; the supervisor restarts it when it died or the heartbeat is older
+// than a few ticks, and reports "monitoring stalled" in the tray until a
+// poll completes again.
+
+const POLL_SECS: u64 = 5;
+/// Heartbeat age after which the poller counts as stalled (six ticks).
+const STALL_SECS: u64 = 30;
+// A poll waits for its pre-poll hooks; a slow hook must not look like a stall
+const _: () = assert!((hooks::PRE_POLL_TIMEOUT_SECS as u64) + POLL_SECS < STALL_SECS);
+
+fn spawn_health_poller(app: &AppHandle) -> tauri::async_runtime::JoinHandle<()> {
+    app.state::<AppState>().poll_heartbeat.store(now_millis(), Ordering::SeqCst);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let state = app.state::<AppState>();
+            poll_health(app.clone(), state.inner()).await;
+            state.poll_heartbeat.store(now_millis(), Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_secs(POLL_SECS)).await;
+        }
+    })
+}
+
+/// Refresh the tray tooltip after the stalled flag changed.
+fn refresh_tray_tooltip(app: &AppHandle) {
+    let overall = app.state::<AppState>().health.lock_or_recover().overall.clone();
+    if let Some(tray) = app.tray_by_id("main-tray") {
+        let _ = tray.set_tooltip(Some(&status_tooltip(app, &overall)));
+    }
+}
+
+/// Run the health poller and restart it whenever it dies or stalls. Emits
+/// `monitoring-stalled` with the reason and `monitoring-resumed`.
+pub async fn supervise_health_poller(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let mut poller = spawn_health_poller(app);
+    let mut restarted_at = 0;
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(POLL_SECS)).await;
+        let heartbeat = state.poll_heartbeat.load(Ordering::SeqCst);
+        let reason = if poller.inner().is_finished() {
+            Some("Health poller stopped")
+        } else if now_millis().saturating_sub(heartbeat) > STALL_SECS * 1000 {
+            Some("Health poll is not returning")
+        } else {
+            None
+        };
+        match reason {
+            Some(reason) => {
+                eprintln!("[tulsbot] {}; restarting it", reason);
+                poller.abort();
+                poller = spawn_health_poller(app);
+                restarted_at = state.poll_heartbeat.load(Ordering::SeqCst);
+                if !state.monitor_stalled.swap(true, Ordering::SeqCst) {
+                    refresh_tray_tooltip(app);
+                    let _ = app.emit("monitoring-stalled", reason);
+                }
+            }
+            None if heartbeat > restarted_at && state.monitor_stalled.load(Ordering::SeqCst) => {
+                state.monitor_stalled.store(false, Ordering::SeqCst);
+                refresh_tray_tooltip(app);
+                let _ = app.emit("monitoring-resumed", ());
+            }
+            None => {}
+        }
+    }
+}
+
+/// Tray icon for an overall health status.
+pub fn health_icon(overall: &str) -> &'static [u8] {
+    match overall {
+        "healthy" => include_bytes!("../icons/tray-green.png"),
+        "degraded" => include_bytes!("../icons/tray-yellow.png"),
+        _ => include_bytes!("../icons/tray-red.png"),
+    }
+}
+
+/// Run post-poll hooks and the transition hooks of services that changed,
+/// without holding up the next poll.
+fn run_background_hooks(
+    app: &AppHandle,
+    hooks: Vec<Hook>,
+    before: &HealthState,
+    after: &HealthState,
+) {
+    let overall = Some(after.overall.clone());
+    let mut runs: Vec<(Hook, HookTrigger, HookContext)> = hooks
+        .iter()
+        .filter(|h| hooks::applies(h, HookTrigger::PostPoll, None))
+        .map(|h| {
+            let context = HookContext { overall: overall.clone(), ..Default::default() };
+            (h.clone(), HookTrigger::PostPoll, context)
+        })
+        .collect();
+    for service in &after.services {
+        let changed = before
+            .services
+            .iter()
+            .any(|b| b.name == service.name && b.healthy != service.healthy);
+        if !changed {
+            continue;
+        }
+        let applies = |h: &&Hook| hooks::applies(h, HookTrigger::Transition, Some(&service.name));
+        for hook in hooks.iter().filter(applies) {
+            let context = HookContext {
+                overall: overall.clone(),
+                service: Some(service.name.clone()),
+                healthy: Some(service.healthy),
+            };
+            runs.push((hook.clone(), HookTrigger::Transition, context));
+        }
+    }
+    if runs.is_empty() {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut results = Vec::new();
+        for (hook, trigger, context) in &runs {
+            results.push(hooks::run(hook, *trigger, context).await);
+        }
+        record_hook_results(&app, results);
+    });
+}
+
+/// Keep `results` as the latest run of their hooks and emit `hook-results`.
+pub fn record_hook_results(app: &AppHandle, results: Vec<HookResult>) {
+    for result in results.iter().filter(|r| !r.ok) {
+        eprintln!(
+            "[tulsbot] Health hook {} failed: {}",
+            result.hook,
+            result.error.as_deref().unwrap_or_default()
+        );
+    }
+    let state = app.state::<AppState>();
+    let mut health = state.health.lock_or_recover();
+    for result in &results {
+        health.hooks.retain(|h| h.hook != result.hook);
+        health.hooks.push(result.clone());
+    }
+    drop(health);
+    let _ = app.emit("hook-results", &results);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(name: &str, healthy: bool) -> ServiceHealth {
+        ServiceHealth {
+            name: name.into(),
+            healthy,
+            port: 0,
+            ready: healthy,
+            blocked_by: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn rollup_is_healthy_only_when_every_service_is() {
+        let services = [service("api", true), service("qdrant", true)];
+        assert_eq!(rollup(&services), "healthy");
+        assert_eq!(rollup(&[]), "healthy");
+    }
+
+    #[test]
+    fn rollup_is_degraded_while_any_service_is_up() {
+        let services = [service("api", true), service("qdrant", false)];
+        assert_eq!(rollup(&services), "degraded");
+    }
+
+    #[test]
+    fn rollup_is_down_when_no_service_is_up() {
+        let services = [service("api", false), service("qdrant", false)];
+        assert_eq!(rollup(&services), "down");
+    }
+}
@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::health::HealthState;
+
+// ── Health history (one sample per poll) ────────────────────────────────────
+
+/// 24 hours at the 5 second poll interval.
+const MAX_SAMPLES: usize = 24 * 60 * 60 / 5;
+const FILE_NAME: &str = "health-history.jsonl";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthSample {
+    /// Unix seconds.
+    pub at: u64,
+    pub overall: String,
+    /// `(service name, healthy)` in profile order.
+    pub services: Vec<(String, bool)>,
+}
+
+impl HealthSample {
+    pub fn from_state(health: &HealthState) -> Option<Self> {
+        Some(Self {
+            at: health.checked_at?,
+            overall: health.overall.clone(),
+            services: health
+                .services
+                .iter()
+                .map(|s| (s.name.clone(), s.healthy))
+                .collect(),
+        })
+    }
+}
+
+/// In-memory ring buffer mirrored to an append-only JSONL file in the
+/// profile's data dir.
+#[derive(Debug, Default)]
+pub struct HealthHistory {
+    samples: VecDeque<HealthSample>,
+    path: Option<PathBuf>,
+}
+
+impl HealthHistory {
+    /// Load the history kept in `dir`, compacting the file if it grew past
+    /// twice the retention.
+    pub fn load(dir: &Path) -> Self {
+        let path = dir.join(FILE_NAME);
+        let mut samples = VecDeque::new();
+        let mut lines = 0;
+        if let Ok(text) = std::fs::read_to_string(&path) {
+            for line in text.lines() {
+                lines += 1;
+                if let Ok(sample) = serde_json::from_str::<HealthSample>(line) {
+                    samples.push_back(sample);
+                }
+            }
+        }
+        while samples.len() > MAX_SAMPLES {
+            samples.pop_front();
+        }
+        let history = Self { samples, path: Some(path) };
+        if lines > MAX_SAMPLES * 2 {
+            history.rewrite();
+        }
+        history
+    }
+
+    fn rewrite(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let text: String = self
+            .samples
+            .iter()
+            .filter_map(|s| serde_json::to_string(s).ok())
+            .map(|line| line + "\n")
+            .collect();
+        if let Err(e) = std::fs::write(path, text) {
+            eprintln!("[tulsbot] Failed to compact health history: {}", e);
+        }
+    }
+
+    pub fn push(&mut self, sample: HealthSample) {
+        if let Some(path) = &self.path {
+            let appended = path
+                .parent()
+                .map(std::fs::create_dir_all)
+                .transpose()
+                .and_then(|_| {
+                    let mut file = std::fs::OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(path)?;
+                    let line = serde_json::to_string(&sample)?;
+                    writeln!(file, "{}", line)
+                });
+            if let Err(e) = appended {
+                eprintln!("[tulsbot] Failed to record health sample: {}", e);
+            }
+        }
+        self.samples.push_back(sample);
+        if self.samples.len() > MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Drop samples taken before `before` (only count them when `dry_run`).
+    pub fn prune(&mut self, before: u64, dry_run: bool) -> usize {
+        let expired = self.samples.iter().take_while(|s| s.at < before).count();
+        if expired > 0 && !dry_run {
+            self.samples.drain(..expired);
+            self.rewrite();
+        }
+        expired
+    }
+
+    /// Samples taken at or after `since` (Unix seconds), oldest first.
+    pub fn since(&self, since: u64) -> Vec<HealthSample> {
+        self.samples
+            .iter()
+            .filter(|s| s.at >= since)
+            .cloned()
+            .collect()
+    }
+}
@@ -0,0 +1,299 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tulsbot_macros::instrumented;
+
+use crate::settings;
+use crate::error::AppError;
+use crate::health::record_hook_results;
+use crate::locks::LockExt;
+use crate::profiles::active_data_dir;
+use crate::state::AppState;
+
+// ── Health check hooks ──────────────────────────────────────────────────────
+//
+// User-registered scripts or HTTP endpoints run around the health poll:
+// before it (a pre-poll hook can stand in for a service the port check can't
+// see, reported as a synthetic service), after it, or when a service changes
+// state. Scripts get the context in `TULSBOT_*` environment variables, HTTP
+// hooks as a JSON POST body. Every run is bounded by a timeout and its
+// output, cut to a few KiB, is kept in the health state.
+
+const DEFAULT_TIMEOUT_SECS: u32 = 10;
+const MAX_TIMEOUT_SECS: u32 = 60;
+/// Pre-poll hooks hold up the health poll, so they run concurrently and get
+/// at most this long; it has to stay well below the poller's stall threshold.
+pub const PRE_POLL_TIMEOUT_SECS: u32 = 15;
+/// Bytes of output kept per run.
+const MAX_OUTPUT: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookTrigger {
+    PrePoll,
+    PostPoll,
+    /// A service went up or down.
+    Transition,
+}
+
+impl HookTrigger {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::PrePoll => "pre-poll",
+            Self::PostPoll => "post-poll",
+            Self::Transition => "transition",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum HookAction {
+    /// Run `command` with `args` directly, without a shell.
+    Script {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    /// POST the context as JSON; any 2xx answer is success.
+    Http { url: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hook {
+    pub name: String,
+    pub trigger: HookTrigger,
+    pub action: HookAction,
+    /// Seconds before the run is killed (default 10, at most 60).
+    #[serde(default)]
+    pub timeout_secs: Option<u32>,
+    /// Transition hooks: only these services; empty means all.
+    #[serde(default)]
+    pub services: Vec<String>,
+    /// Pre-poll hooks: report the result as a service named after the hook.
+    #[serde(default)]
+    pub synthetic: bool,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookResult {
+    pub hook: String,
+    pub trigger: HookTrigger,
+    pub ok: bool,
+    /// Unix seconds.
+    pub ran_at: u64,
+    pub duration_ms: u64,
+    /// Exit code of a script, HTTP status of a request.
+    pub code: Option<i32>,
+    pub output: String,
+    pub error: Option<String>,
+}
+
+/// What a run is about, passed to the hook.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HookContext {
+    pub overall: Option<String>,
+    /// Transition hooks: the service and its new state.
+    pub service: Option<String>,
+    pub healthy: Option<bool>,
+}
+
+pub fn validate(hook: &Hook) -> Result<(), String> {
+    if hook.name.trim().is_empty() {
+        return Err("Hook name is empty".into());
+    }
+    if hook.synthetic && hook.trigger != HookTrigger::PrePoll {
+        return Err("Only pre-poll hooks can report a synthetic service".into());
+    }
+    match &hook.action {
+        HookAction::Script { command, .. } if command.trim().is_empty() => {
+            Err("Hook command is empty".into())
+        }
+        HookAction::Http { url }
+            if !url.starts_with("https://") && !url.starts_with("http://") =>
+        {
+            Err("Hook URLs must be http(s)://".into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Whether `hook` runs for `trigger` (and, for transitions, `service`).
+pub fn applies(hook: &Hook, trigger: HookTrigger, service: Option<&str>) -> bool {
+    hook.enabled
+        && hook.trigger == trigger
+        && (hook.services.is_empty()
+            || service.is_some_and(|s| hook.services.iter().any(|n| n == s)))
+}
+
+fn truncate(mut text: String) -> String {
+    if text.len() > MAX_OUTPUT {
+        let mut cut = MAX_OUTPUT;
+        while !text.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        text.truncate(cut);
+        text.push('…');
+    }
+    text
+}
+
+async fn run_script(
+    command: &str,
+    args: &[String],
+    trigger: HookTrigger,
+    context: &HookContext,
+) -> Result<(Option<i32>, String), String> {
+    let mut cmd = tokio::process::Command::new(command);
+    cmd.args(args).env("TULSBOT_TRIGGER", trigger.as_str()).kill_on_drop(true);
+    if let Some(overall) = &context.overall {
+        cmd.env("TULSBOT_OVERALL", overall);
+    }
+    if let Some(service) = &context.service {
+        cmd.env("TULSBOT_SERVICE", service);
+    }
+    if let Some(healthy) = context.healthy {
+        cmd.env("TULSBOT_HEALTHY", if healthy { "1" } else { "0" });
+    }
+    let out = cmd.output().await.map_err(|e| format!("{}: {}", command, e))?;
+    let mut output = String::from_utf8_lossy(&out.stdout).into_owned();
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    if !stderr.trim().is_empty() {
+        output.push_str(&stderr);
+    }
+    if !out.status.success() {
+        return Err(format!("exited with {}\n{}", out.status, output.trim()));
+    }
+    Ok((out.status.code(), output))
+}
+
+async fn run_http(
+    url: &str,
+    trigger: HookTrigger,
+    context: &HookContext,
+) -> Result<(Option<i32>, String), String> {
+    let mut body = serde_json::to_value(context).map_err(|e| e.to_string())?;
+    body["trigger"] = Value::from(trigger.as_str());
+    let resp = reqwest::Client::new()
+        .post(url)
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+    let status = resp.status();
+    let text = resp.text().await.unwrap_or_default();
+    if !status.is_success() {
+        return Err(format!("HTTP {}\n{}", status.as_u16(), text.trim()));
+    }
+    Ok((Some(i32::from(status.as_u16())), text))
+}
+
+/// Run `hook` once. Never fails: errors and timeouts end up in the result.
+pub async fn run(hook: &Hook, trigger: HookTrigger, context: &HookContext) -> HookResult {
+    let max = match trigger {
+        HookTrigger::PrePoll => PRE_POLL_TIMEOUT_SECS,
+        _ => MAX_TIMEOUT_SECS,
+    };
+    let timeout = hook.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS).clamp(1, max);
+    let started = Instant::now();
+    let ran_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let work = async {
+        match &hook.action {
+            HookAction::Script { command, args } => {
+                run_script(command, args, trigger, context).await
+            }
+            HookAction::Http { url } => run_http(url, trigger, context).await,
+        }
+    };
+    let (ok, code, output, error) =
+        match tokio::time::timeout(Duration::from_secs(u64::from(timeout)), work).await {
+            Ok(Ok((code, output))) => (true, code, output, None),
+            Ok(Err(e)) => (false, None, String::new(), Some(truncate(e))),
+            Err(_) => (false, None, String::new(), Some(format!("Timed out after {}s", timeout))),
+        };
+    HookResult {
+        hook: hook.name.clone(),
+        trigger,
+        ok,
+        ran_at,
+        duration_ms: started.elapsed().as_millis() as u64,
+        code,
+        output: truncate(output),
+        error,
+    }
+}
+
+pub fn update_health_hooks(
+    app: &AppHandle,
+    change: impl FnOnce(&mut Vec<Hook>),
+) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        change(&mut settings.health_hooks);
+        settings.clone()
+    };
+    settings::save(&active_data_dir(app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    Ok(())
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn list_health_hooks(state: State<'_, AppState>) -> Result<Vec<Hook>, AppError> {
+    Ok(state.settings.lock_or_recover().health_hooks.clone())
+}
+
+/// Add a hook, or replace the one with the same name.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn save_health_hook(app: AppHandle, hook: Hook) -> Result<(), AppError> {
+    validate(&hook)?;
+    Ok(update_health_hooks(&app, |hooks| {
+        match hooks.iter_mut().find(|h| h.name == hook.name) {
+            Some(existing) => *existing = hook,
+            None => hooks.push(hook),
+        }
+    })?)
+}
+
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn delete_health_hook(app: AppHandle, name: String) -> Result<(), AppError> {
+    update_health_hooks(&app, |hooks| hooks.retain(|h| h.name != name))?;
+    app.state::<AppState>().health.lock_or_recover().hooks.retain(|h| h.hook != name);
+    Ok(())
+}
+
+/// Run hook `name` now, as its trigger would, and record the result.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn run_health_hook(app: AppHandle, name: String) -> Result<HookResult, AppError> {
+    let (hook, overall) = {
+        let state = app.state::<AppState>();
+        let hook = state
+            .settings
+            .lock_or_recover()
+            .health_hooks
+            .iter()
+            .find(|h| h.name == name)
+            .cloned()
+            .ok_or_else(|| format!("Unknown hook: {}", name))?;
+        let overall = state.health.lock_or_recover().overall.clone();
+        (hook, overall)
+    };
+    let context = HookContext { overall: Some(overall), ..Default::default() };
+    let result = run(&hook, hook.trigger, &context).await;
+    record_hook_results(&app, vec![result.clone()]);
+    Ok(result)
+}
@@ -0,0 +1,211 @@
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tulsbot_macros::instrumented;
+use unic_langid::LanguageIdentifier;
+
+use crate::accessibility::set_accessible_title;
+use crate::error::AppError;
+use crate::locks::LockExt;
+use crate::profiles::active_data_dir;
+use crate::settings::{self, privacy_mode};
+use crate::state::AppState;
+use crate::tray::refresh_tray_menu;
+
+// ── Localisation of native UI strings (Fluent) ─────────────────────────────
+
+pub const FALLBACK_LOCALE: &str = "en-US";
+
+/// Bundled locales; the first entry is the fallback for missing messages.
+const LOCALES: &[(&str, &str)] = &[
+    ("en-US", include_str!("../locales/en-US.ftl")),
+    ("pt-BR", include_str!("../locales/pt-BR.ftl")),
+    ("es", include_str!("../locales/es.ftl")),
+    ("de", include_str!("../locales/de.ftl")),
+    ("fr", include_str!("../locales/fr.ftl")),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocaleInfo {
+    pub locale: String,
+    pub available: Vec<String>,
+}
+
+/// Lives in the app state, shared across threads, hence the concurrent
+/// bundles.
+pub struct I18n {
+    locale: &'static str,
+    bundle: FluentBundle<FluentResource>,
+    fallback: FluentBundle<FluentResource>,
+}
+
+fn build_bundle(locale: &'static str) -> FluentBundle<FluentResource> {
+    let source = LOCALES
+        .iter()
+        .find(|(id, _)| *id == locale)
+        .map(|(_, source)| *source)
+        .unwrap_or(LOCALES[0].1);
+    let langid: LanguageIdentifier = locale.parse().unwrap_or_default();
+
+    let mut bundle = FluentBundle::new_concurrent(vec![langid]);
+    // Unicode isolation marks show up as boxes in tray menus and tooltips.
+    bundle.set_use_isolating(false);
+    let resource = FluentResource::try_new(source.to_string()).unwrap_or_else(|(res, errors)| {
+        eprintln!("[tulsbot] Errors parsing {}.ftl: {:?}", locale, errors);
+        res
+    });
+    if let Err(errors) = bundle.add_resource(resource) {
+        eprintln!("[tulsbot] Errors loading {}.ftl: {:?}", locale, errors);
+    }
+    bundle
+}
+
+/// Pick the best bundled locale for a BCP 47 tag: exact match first, then
+/// same language, then the fallback.
+pub fn negotiate(requested: &str) -> &'static str {
+    let requested = requested.replace('_', "-");
+    let language = requested.split('-').next().unwrap_or_default();
+    LOCALES
+        .iter()
+        .find(|(id, _)| id.eq_ignore_ascii_case(&requested))
+        .or_else(|| {
+            LOCALES.iter().find(|(id, _)| {
+                id.split('-')
+                    .next()
+                    .is_some_and(|l| l.eq_ignore_ascii_case(language))
+            })
+        })
+        .map(|(id, _)| *id)
+        .unwrap_or(FALLBACK_LOCALE)
+}
+
+/// The OS locale, negotiated against the bundled ones.
+pub fn detect_locale() -> &'static str {
+    sys_locale::get_locale()
+        .map(|locale| negotiate(&locale))
+        .unwrap_or(FALLBACK_LOCALE)
+}
+
+impl I18n {
+    /// `None` follows the OS locale.
+    pub fn new(locale: Option<&str>) -> Self {
+        let locale = locale.map(negotiate).unwrap_or_else(detect_locale);
+        Self {
+            locale,
+            bundle: build_bundle(locale),
+            fallback: build_bundle(FALLBACK_LOCALE),
+        }
+    }
+
+    pub fn info(&self) -> LocaleInfo {
+        LocaleInfo {
+            locale: self.locale.to_string(),
+            available: LOCALES.iter().map(|(id, _)| id.to_string()).collect(),
+        }
+    }
+
+    pub fn t(&self, id: &str) -> String {
+        self.format(id, None)
+    }
+
+    pub fn t_args(&self, id: &str, args: &[(&str, &str)]) -> String {
+        let mut fluent_args = FluentArgs::new();
+        for (key, value) in args {
+            fluent_args.set(*key, *value);
+        }
+        self.format(id, Some(&fluent_args))
+    }
+
+    /// Like `t_args` with a numeric `$count`, so plural variants resolve.
+    pub fn t_count(&self, id: &str, count: usize) -> String {
+        let mut fluent_args = FluentArgs::new();
+        fluent_args.set("count", count);
+        self.format(id, Some(&fluent_args))
+    }
+
+    fn format(&self, id: &str, args: Option<&FluentArgs>) -> String {
+        for bundle in [&self.bundle, &self.fallback] {
+            if let Some(pattern) = bundle.get_message(id).and_then(|msg| msg.value()) {
+                let mut errors = Vec::new();
+                return bundle.format_pattern(pattern, args, &mut errors).into_owned();
+            }
+        }
+        id.to_string()
+    }
+}
+
+/// Translate a native UI string into the current locale.
+pub fn tr(app: &AppHandle, id: &str) -> String {
+    match app.state::<AppState>().i18n.lock() {
+        Ok(i18n) => i18n.t(id),
+        Err(_) => id.to_string(),
+    }
+}
+
+pub fn status_tooltip(app: &AppHandle, overall: &str) -> String {
+    let private = privacy_mode(app);
+    let state = app.state::<AppState>();
+    let Ok(i18n) = state.i18n.lock() else {
+        return format!("Tulsbot — {}", overall);
+    };
+    let status = i18n.t(&format!("status-{}", overall));
+    let mut tooltip = i18n.t_args("tray-tooltip-status", &[("status", &status)]);
+    if app.state::<AppState>().monitor_stalled.load(Ordering::SeqCst) {
+        tooltip = i18n.t_args("tray-tooltip-stalled", &[("tooltip", &tooltip)]);
+    }
+    if private {
+        return i18n.t_args("tray-tooltip-private", &[("tooltip", &tooltip)]);
+    }
+    tooltip
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn get_locale(state: State<'_, AppState>) -> Result<LocaleInfo, AppError> {
+    let i18n = state.i18n.lock_or_recover();
+    Ok(i18n.info())
+}
+
+/// Switch the native UI language (`None` follows the OS), persist it, and
+/// emit `locale-changed` so every webview can follow.
+#[instrumented]
+#[tauri::command]
+pub async fn set_locale(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    locale: Option<String>,
+) -> Result<LocaleInfo, AppError> {
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        settings.locale = locale;
+        settings.clone()
+    };
+    settings::save(&active_data_dir(&app)?, &settings)?;
+    Ok(apply_locale(&app, settings.locale.as_deref())?)
+}
+
+pub fn apply_locale(app: &AppHandle, locale: Option<&str>) -> Result<LocaleInfo, String> {
+    let state = app.state::<AppState>();
+    let info = {
+        let mut i18n = state.i18n.lock_or_recover();
+        *i18n = I18n::new(locale);
+        i18n.info()
+    };
+
+    refresh_tray_menu(app);
+    let overall = state
+        .health
+        .lock()
+        .map(|h| h.overall.clone())
+        .unwrap_or_else(|_| "down".into());
+    if let Some(tray) = app.tray_by_id("main-tray") {
+        let _ = tray.set_tooltip(Some(&status_tooltip(app, &overall)));
+    }
+    for window in app.webview_windows().values() {
+        set_accessible_title(app, window);
+    }
+    let _ = app.emit("locale-changed", &info);
+    Ok(info)
+}
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::Path;
+
+// ── Ingestion of shared/opened files ────────────────────────────────────────
+
+/// Text beyond this many bytes is cut off before reaching the webview.
+const MAX_TEXT_BYTES: usize = 256 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngestedFile {
+    pub path: String,
+    pub name: String,
+    pub size: u64,
+    /// UTF-8 content, or `None` for binary files.
+    pub text: Option<String>,
+    pub truncated: bool,
+    /// `text` was condensed by the outbound pipeline.
+    #[serde(default)]
+    pub summarized: bool,
+}
+
+/// Read `path` for use as conversation context.
+pub fn ingest_file(path: &Path) -> Result<IngestedFile, String> {
+    let meta = std::fs::metadata(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    if !meta.is_file() {
+        return Err(format!("{}: not a file", path.display()));
+    }
+
+    let mut buf = Vec::new();
+    std::fs::File::open(path)
+        .and_then(|f| f.take(MAX_TEXT_BYTES as u64 + 1).read_to_end(&mut buf))
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    let truncated = buf.len() > MAX_TEXT_BYTES;
+    buf.truncate(MAX_TEXT_BYTES);
+
+    let text = match String::from_utf8(buf) {
+        Ok(text) => Some(text),
+        // Cut mid-character: keep the valid prefix
+        Err(e) if truncated && e.utf8_error().error_len().is_none() => {
+            let valid = e.utf8_error().valid_up_to();
+            let mut bytes = e.into_bytes();
+            bytes.truncate(valid);
+            String::from_utf8(bytes).ok()
+        }
+        Err(_) => None,
+    };
+
+    Ok(IngestedFile {
+        path: path.display().to_string(),
+        name: path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        size: meta.len(),
+        text,
+        truncated,
+        summarized: false,
+    })
+}
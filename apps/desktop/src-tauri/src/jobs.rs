@@ -0,0 +1,113 @@
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::conversations::{self, effective_config, Message};
+use crate::profiles::active_data_dir;
+use crate::providers::{run_completion, ChatMessage, ChatRequest};
+use crate::state::AppState;
+
+// ── Background jobs: conversation titling ───────────────────────────────────
+
+/// A conversation gets a generated title once it has this many messages.
+pub const TITLE_AFTER_MESSAGES: usize = 4;
+
+/// Characters of each message shown to the model.
+const EXCERPT_CHARS: usize = 1000;
+
+/// Prompt asking for a title and summary of `thread`.
+pub fn title_prompt(thread: &[&Message]) -> Vec<ChatMessage> {
+    let transcript: String = thread
+        .iter()
+        .map(|m| {
+            let excerpt: String = m.content.chars().take(EXCERPT_CHARS).collect();
+            format!("{}: {}\n\n", m.role, excerpt)
+        })
+        .collect();
+    vec![
+        ChatMessage::new(
+            "system",
+            "You name conversations. Reply with JSON only: \
+             {\"title\": \"<at most 6 words>\", \"summary\": \"<one or two sentences>\"}",
+        ),
+        ChatMessage::new("user", transcript),
+    ]
+}
+
+/// `(title, summary)` from the model's reply. Falls back to the first line
+/// as the title when the reply isn't the requested JSON.
+pub fn parse_title(reply: &str) -> Option<(String, Option<String>)> {
+    let json = reply
+        .find('{')
+        .zip(reply.rfind('}'))
+        .filter(|(start, end)| start < end)
+        .and_then(|(start, end)| serde_json::from_str::<serde_json::Value>(&reply[start..=end]).ok());
+    let (title, summary) = match json {
+        Some(json) => (
+            json["title"].as_str().map(String::from),
+            json["summary"].as_str().map(String::from),
+        ),
+        None => (reply.lines().find(|l| !l.trim().is_empty()).map(String::from), None),
+    };
+    let title: String = title?
+        .trim()
+        .trim_matches(|c| c == '"' || c == '\'')
+        .chars()
+        .take(80)
+        .collect();
+    (!title.is_empty()).then_some((title, summary.map(|s| s.trim().to_string())))
+}
+
+/// Ask the conversation's provider for a title and summary, store them and
+/// emit `conversation-titled`. Runs in the background, windows or not.
+pub fn spawn_title_job(app: &AppHandle, id: String) {
+    let state = app.state::<AppState>();
+    let started = state
+        .title_jobs
+        .lock()
+        .map(|mut jobs| jobs.insert(id.clone()))
+        .unwrap_or(false);
+    if !started {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = title_conversation(&app, &id).await {
+            eprintln!("[tulsbot] Titling conversation {} failed: {}", id, e);
+        }
+        if let Ok(mut jobs) = app.state::<AppState>().title_jobs.lock() {
+            jobs.remove(&id);
+        }
+    });
+}
+
+pub async fn title_conversation(app: &AppHandle, id: &str) -> Result<(), String> {
+    let dir = active_data_dir(app)?;
+    let stored = conversations::load(&dir, id)?;
+    let config = effective_config(&app.state::<AppState>(), &stored.config)?;
+    let (Some(provider), Some(model)) = (config.provider, config.model) else {
+        // Nothing configured to ask
+        return Ok(());
+    };
+    let request = ChatRequest {
+        provider,
+        model,
+        temperature: Some(0.2),
+        max_tokens: Some(200),
+        messages: title_prompt(&stored.active_thread()),
+        credential: config.credential,
+    };
+    let reply = run_completion(app, &request, Some(id)).await?;
+    let (title, summary) =
+        parse_title(&reply.content).ok_or("Provider returned no usable title")?;
+
+    // Reload: messages may have been added while we waited
+    let mut stored = conversations::load(&dir, id)?;
+    if stored.titled {
+        return Ok(());
+    }
+    stored.title = title;
+    stored.summary = summary;
+    stored.titled = true;
+    conversations::save(&dir, &stored)?;
+    let _ = app.emit("conversation-titled", stored.summary());
+    Ok(())
+}
@@ -0,0 +1,58 @@
+// ── OS keychain (macOS Keychain, Windows Credential Manager, Secret Service) ─
+//
+// Accounts are prefixed with the OS user name (see `users`) so a keychain or
+// Secret Service collection shared between accounts never hands one user's
+// secrets to another. Entries written before the prefix existed are moved
+// over the first time they are read.
+
+use crate::users;
+
+const SERVICE: &str = "com.tulsbot.desktop";
+
+fn entry(account: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(SERVICE, &users::keychain_account(account)).map_err(|e| e.to_string())
+}
+
+fn legacy_entry(account: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(SERVICE, account).map_err(|e| e.to_string())
+}
+
+fn read(entry: &keyring::Entry) -> Result<Option<String>, String> {
+    match entry.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// The secret stored for `account`, if any. Blocking.
+pub fn get(account: &str) -> Result<Option<String>, String> {
+    if let Some(secret) = read(&entry(account)?)? {
+        return Ok(Some(secret));
+    }
+    let legacy = legacy_entry(account)?;
+    let Some(secret) = read(&legacy)? else {
+        return Ok(None);
+    };
+    set(account, &secret)?;
+    if let Err(e) = legacy.delete_credential() {
+        eprintln!("[tulsbot] Could not remove unscoped keychain entry {}: {}", account, e);
+    }
+    Ok(Some(secret))
+}
+
+/// Store `secret` for `account`, replacing any previous value. Blocking.
+pub fn set(account: &str, secret: &str) -> Result<(), String> {
+    entry(account)?.set_password(secret).map_err(|e| e.to_string())
+}
+
+/// Remove the secret for `account`; missing entries are not an error. Blocking.
+pub fn delete(account: &str) -> Result<(), String> {
+    for entry in [entry(account)?, legacy_entry(account)?] {
+        match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => {}
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,522 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::rustls;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::TlsAcceptor;
+use tulsbot_macros::instrumented;
+
+use crate::{conversations, users};
+use crate::alerts::refresh_tray_title;
+use crate::discovery::with_discovery;
+use crate::error::{AppError, ErrorKind};
+use crate::locks::LockExt;
+use crate::pairing::rebind_companion_listener;
+use crate::profiles::{active_data_dir, Profile};
+use crate::secrets::{self, Charset, SecretKind};
+use crate::state::AppState;
+use crate::tray::refresh_tray_menu;
+
+// ── LAN exposure ────────────────────────────────────────────────────────────
+//
+// Off unless the user turns it on. While on, each selected service of the
+// active profile gets a TLS listener on the LAN interface, at its own port
+// plus `port_offset`, that forwards to the service on loopback. The
+// certificate is self-signed (kept in `lan-tls.json` so phones only have to
+// trust it once) or read from files, e.g. issued by a local ACME CA such as
+// step-ca. Every connection must present the per-session token, as a bearer
+// header, a cookie, or once as `?tulsbot_token=` in the URL, which answers
+// with a redirect setting the cookie. Later requests on an authorized
+// connection pass through unchecked, like the connection itself.
+
+const TLS_FILE: &str = "lan-tls.json";
+pub const TOKEN_PARAM: &str = "tulsbot_token";
+const COOKIE_NAME: &str = "tulsbot_lan";
+/// Longest request head read before the token check.
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+/// Time a client gets for the TLS handshake and its request head.
+const HANDSHAKE_SECS: u64 = 10;
+/// Pause after a failed accept (out of file descriptors, say) before the
+/// next, so the loop doesn't spin.
+pub const ACCEPT_BACKOFF_MS: u64 = 250;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "kebab-case")]
+pub enum TlsSource {
+    /// Generate a certificate for this machine's names and LAN address.
+    #[default]
+    SelfSigned,
+    /// PEM certificate chain and private key.
+    Files { cert_path: PathBuf, key_path: PathBuf },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LanSettings {
+    /// Names of the services to expose.
+    pub services: Vec<String>,
+    /// Added to a service's port for its LAN port.
+    pub port_offset: u16,
+    pub tls: TlsSource,
+    /// Address to listen on; `None` is the interface of the default route.
+    pub bind_address: Option<IpAddr>,
+}
+
+impl Default for LanSettings {
+    fn default() -> Self {
+        Self {
+            services: vec!["Web UI".into(), "Context Manager".into()],
+            port_offset: 10_000,
+            tls: TlsSource::default(),
+            bind_address: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExposedService {
+    pub name: String,
+    pub local_port: u16,
+    pub lan_port: u16,
+    /// Opens the service with the token, for a QR code.
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LanStatus {
+    pub exposed: bool,
+    pub address: Option<IpAddr>,
+    pub services: Vec<ExposedService>,
+    pub token: Option<String>,
+    /// SHA-256 of the certificate, to compare with what a browser shows.
+    pub fingerprint: Option<String>,
+    /// Unix seconds.
+    pub since: Option<u64>,
+}
+
+/// Running listeners; dropping it closes them.
+pub struct Exposure {
+    pub status: LanStatus,
+    tasks: Vec<tokio::task::AbortHandle>,
+}
+
+impl Drop for Exposure {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredTls {
+    names: Vec<String>,
+    cert: String,
+    key: String,
+}
+
+/// Start listening for the selected services of `profile`.
+pub async fn expose(
+    profile: &Profile,
+    settings: &LanSettings,
+    dir: &Path,
+    token: String,
+    now: u64,
+) -> Result<Exposure, String> {
+    let address = match settings.bind_address {
+        Some(address) => address,
+        None => lan_address()?,
+    };
+    let (cert, key) = match &settings.tls {
+        TlsSource::SelfSigned => self_signed(dir, address)?,
+        TlsSource::Files { cert_path, key_path } => (read(cert_path)?, read(key_path)?),
+    };
+    let (acceptor, fingerprint) = acceptor(cert.as_bytes(), key.as_bytes())?;
+    let mut exposure = Exposure {
+        status: LanStatus {
+            exposed: true,
+            address: Some(address),
+            services: Vec::new(),
+            token: Some(token.clone()),
+            fingerprint: Some(fingerprint),
+            since: Some(now),
+        },
+        tasks: Vec::new(),
+    };
+    let token: Arc<str> = token.into();
+    for name in &settings.services {
+        let service = profile
+            .services
+            .iter()
+            .find(|s| &s.name == name)
+            .ok_or_else(|| format!("Unknown service: {}", name))?;
+        let lan_port = service
+            .port
+            .checked_add(settings.port_offset)
+            .ok_or_else(|| format!("Port offset too large for {}", name))?;
+        let listener = TcpListener::bind((address, lan_port))
+            .await
+            .map_err(|e| format!("Can't listen on {}: {}", SocketAddr::new(address, lan_port), e))?;
+        let task = tokio::spawn(serve(listener, acceptor.clone(), service.port, token.clone()));
+        exposure.tasks.push(task.abort_handle());
+        exposure.status.services.push(ExposedService {
+            name: name.clone(),
+            local_port: service.port,
+            lan_port,
+            url: format!(
+                "https://{}/?{}={}",
+                SocketAddr::new(address, lan_port),
+                TOKEN_PARAM,
+                token
+            ),
+        });
+    }
+    if exposure.status.services.is_empty() {
+        return Err("No services selected for LAN access".into());
+    }
+    Ok(exposure)
+}
+
+/// Address of the interface the default route goes through.
+pub fn lan_address() -> Result<IpAddr, String> {
+    let socket = std::net::UdpSocket::bind("0.0.0.0:0").map_err(|e| e.to_string())?;
+    // Connecting a UDP socket only picks a route; nothing is sent to TEST-NET
+    socket.connect("192.0.2.1:9").map_err(|_| "No network interface is up".to_string())?;
+    let address = socket.local_addr().map_err(|e| e.to_string())?.ip();
+    if address.is_loopback() || address.is_unspecified() {
+        return Err("No LAN address found".into());
+    }
+    Ok(address)
+}
+
+/// This machine's name as a DNS label, without `.local`.
+pub fn local_host_name() -> Option<String> {
+    let name = sysinfo::System::host_name()?;
+    let label: String = name
+        .trim_end_matches(".local")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let label = label.trim_matches('-');
+    (!label.is_empty()).then(|| label.to_string())
+}
+
+fn read(path: &Path) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// The stored self-signed certificate, or a new one when this machine's
+/// names or address changed.
+fn self_signed(dir: &Path, address: IpAddr) -> Result<(String, String), String> {
+    let mut names = vec!["localhost".to_string(), address.to_string()];
+    if let Some(host) = local_host_name() {
+        names.push(format!("{}.local", host));
+    }
+    let path = dir.join(TLS_FILE);
+    let stored: Option<StoredTls> =
+        std::fs::read_to_string(&path).ok().and_then(|text| serde_json::from_str(&text).ok());
+    if let Some(stored) = stored.filter(|s| s.names == names) {
+        return Ok((stored.cert, stored.key));
+    }
+    let rcgen::CertifiedKey { cert, key_pair } =
+        rcgen::generate_simple_self_signed(names.clone()).map_err(|e| e.to_string())?;
+    let stored = StoredTls { names, cert: cert.pem(), key: key_pair.serialize_pem() };
+    users::restrict(dir)?;
+    let text = serde_json::to_string_pretty(&stored).map_err(|e| e.to_string())?;
+    std::fs::write(&path, text).map_err(|e| e.to_string())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+    }
+    Ok((stored.cert, stored.key))
+}
+
+/// TLS acceptor for a PEM chain and key, and the leaf's fingerprint.
+fn acceptor(cert: &[u8], key: &[u8]) -> Result<(TlsAcceptor, String), String> {
+    let certs = CertificateDer::pem_slice_iter(cert)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Invalid certificate: {}", e))?;
+    let leaf = certs.first().ok_or("No certificate in the PEM file")?;
+    let fingerprint = Sha256::digest(leaf.as_ref())
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(":");
+    let key =
+        PrivateKeyDer::from_pem_slice(key).map_err(|e| format!("Invalid private key: {}", e))?;
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = rustls::ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| e.to_string())?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| e.to_string())?;
+    Ok((TlsAcceptor::from(Arc::new(config)), fingerprint))
+}
+
+async fn serve(listener: TcpListener, acceptor: TlsAcceptor, port: u16, token: Arc<str>) {
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                eprintln!("[tulsbot] LAN accept failed: {}", e);
+                tokio::time::sleep(std::time::Duration::from_millis(ACCEPT_BACKOFF_MS)).await;
+                continue;
+            }
+        };
+        let (acceptor, token) = (acceptor.clone(), token.clone());
+        tokio::spawn(async move {
+            if let Err(e) = forward(stream, acceptor, port, &token).await {
+                eprintln!("[tulsbot] LAN connection from {}: {}", peer, e);
+            }
+        });
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Access {
+    Allowed,
+    /// Token in the URL: set the cookie and send the browser on without it.
+    SetCookie(String),
+    Denied,
+}
+
+async fn forward(
+    stream: TcpStream,
+    acceptor: TlsAcceptor,
+    port: u16,
+    token: &str,
+) -> Result<(), String> {
+    // A client that connects and goes quiet must not hold the task forever
+    let handshake = async {
+        let mut tls = acceptor.accept(stream).await.map_err(|e| e.to_string())?;
+        let head = read_head(&mut tls).await?;
+        Ok::<_, String>((tls, head))
+    };
+    let (mut tls, head) =
+        tokio::time::timeout(std::time::Duration::from_secs(HANDSHAKE_SECS), handshake)
+            .await
+            .map_err(|_| "Timed out waiting for a request".to_string())??;
+    match access(&head, token) {
+        Access::Allowed => {}
+        Access::SetCookie(location) => {
+            let cookie = format!(
+                "{}={}; Path=/; Secure; HttpOnly; SameSite=Strict",
+                COOKIE_NAME, token
+            );
+            let headers = [("Location", location.as_str()), ("Set-Cookie", cookie.as_str())];
+            return respond(&mut tls, "302 Found", &headers).await;
+        }
+        Access::Denied => {
+            let headers = [("WWW-Authenticate", "Bearer")];
+            return respond(&mut tls, "401 Unauthorized", &headers).await;
+        }
+    }
+    let mut upstream = TcpStream::connect(("127.0.0.1", port))
+        .await
+        .map_err(|e| format!("Service on port {}: {}", port, e))?;
+    upstream.write_all(&head).await.map_err(|e| e.to_string())?;
+    tokio::io::copy_bidirectional(&mut tls, &mut upstream).await.map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Bytes up to the end of the first request head; may include some body.
+async fn read_head(stream: &mut (impl AsyncRead + Unpin)) -> Result<Vec<u8>, String> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 4096];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        if head.len() > MAX_HEAD_BYTES {
+            return Err("Request head too large".into());
+        }
+        let n = stream.read(&mut buf).await.map_err(|e| e.to_string())?;
+        if n == 0 {
+            return Err("Connection closed before a request".into());
+        }
+        head.extend_from_slice(&buf[..n]);
+    }
+    Ok(head)
+}
+
+fn access(head: &[u8], token: &str) -> Access {
+    let text = String::from_utf8_lossy(head);
+    let mut lines = text.split("\r\n");
+    let target = lines.next().and_then(|line| line.split(' ').nth(1)).unwrap_or("/");
+    for line in lines.take_while(|l| !l.is_empty()) {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        let presented = match name.trim().to_ascii_lowercase().as_str() {
+            "authorization" => value.strip_prefix("Bearer ").map(str::trim),
+            "cookie" => value
+                .split(';')
+                .filter_map(|c| c.trim().split_once('='))
+                .find(|(name, _)| *name == COOKIE_NAME)
+                .map(|(_, value)| value),
+            _ => None,
+        };
+        if presented.is_some_and(|p| same(p, token)) {
+            return Access::Allowed;
+        }
+    }
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let (matching, rest): (Vec<&str>, Vec<&str>) =
+        query.split('&').partition(|pair| pair.starts_with(&format!("{}=", TOKEN_PARAM)));
+    let valid = matching
+        .iter()
+        .any(|pair| same(&pair[TOKEN_PARAM.len() + 1..], token));
+    if !valid {
+        return Access::Denied;
+    }
+    let rest: Vec<&str> = rest.into_iter().filter(|pair| !pair.is_empty()).collect();
+    if rest.is_empty() {
+        Access::SetCookie(path.to_string())
+    } else {
+        Access::SetCookie(format!("{}?{}", path, rest.join("&")))
+    }
+}
+
+/// Compare without an early exit, so timing doesn't reveal a prefix.
+fn same(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+async fn respond(
+    stream: &mut (impl AsyncWrite + Unpin),
+    status: &str,
+    headers: &[(&str, &str)],
+) -> Result<(), String> {
+    let mut response = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n", status);
+    for (name, value) in headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str("\r\n");
+    stream.write_all(response.as_bytes()).await.map_err(|e| e.to_string())?;
+    stream.shutdown().await.map_err(|e| e.to_string())
+}
+
+pub fn lan_status(app: &AppHandle) -> LanStatus {
+    let state = app.state::<AppState>();
+    let exposure = state.lan.lock_or_recover();
+    exposure.as_ref().map(|e| e.status.clone()).unwrap_or_default()
+}
+
+/// Update the tray indicator and tell the windows.
+pub fn publish_lan(app: &AppHandle) {
+    refresh_tray_title(app);
+    refresh_tray_menu(app);
+    let _ = app.emit("lan-exposure-changed", lan_status(app));
+}
+
+/// Close the LAN listeners; returns whether any were open.
+pub fn stop_lan_exposure(app: &AppHandle) -> bool {
+    let state = app.state::<AppState>();
+    let stopped = state.lan.lock_or_recover().take().is_some();
+    if stopped {
+        if let Some(discovery) = state.discovery.lock_or_recover().as_mut() {
+            discovery.withdraw();
+        }
+        eprintln!("[tulsbot] LAN access off");
+        rebind_companion_listener(app);
+        publish_lan(app);
+    }
+    stopped
+}
+
+/// Expose the configured services on the LAN over TLS, behind a fresh
+/// token. Stays on until disabled, the profile changes or the app quits.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn enable_lan_exposure(app: AppHandle) -> Result<LanStatus, AppError> {
+    let state = app.state::<AppState>();
+    if state.lan.lock_or_recover().is_some() {
+        return Err(AppError::new(ErrorKind::Conflict, "LAN access is already on"));
+    }
+    let profile = state.profiles.lock_or_recover().active_profile();
+    let config = state.settings.lock_or_recover().lan.clone();
+    let token = secrets::generate(SecretKind::ApiKey, Some(32), Some(Charset::Unambiguous))?;
+    let dir = active_data_dir(&app)?;
+    let exposure = expose(&profile, &config, &dir, token.secret, conversations::now()).await?;
+    let status = exposure.status.clone();
+    *state.lan.lock_or_recover() = Some(exposure);
+    for service in &status.services {
+        eprintln!("[tulsbot] LAN access on: {} at port {}", service.name, service.lan_port);
+    }
+    if let Err(e) = with_discovery(&app, |d| d.advertise(&status, &profile.name)) {
+        eprintln!("[tulsbot] Failed to announce over mDNS: {}", e);
+    }
+    rebind_companion_listener(&app);
+    publish_lan(&app);
+    Ok(status)
+}
+
+/// Close the LAN listeners. Returns whether access was on.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn disable_lan_exposure(app: AppHandle) -> Result<bool, AppError> {
+    Ok(stop_lan_exposure(&app))
+}
+
+/// Address, URLs with the token and certificate fingerprint while exposed.
+#[instrumented]
+#[tauri::command]
+pub async fn get_lan_exposure(app: AppHandle) -> Result<LanStatus, AppError> {
+    Ok(lan_status(&app))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "s3cret-token";
+
+    fn request(target: &str, headers: &[&str]) -> Vec<u8> {
+        let mut head = format!("GET {} HTTP/1.1\r\nHost: example\r\n", target);
+        for header in headers {
+            head.push_str(header);
+            head.push_str("\r\n");
+        }
+        head.push_str("\r\n");
+        head.into_bytes()
+    }
+
+    #[test]
+    fn bearer_token_is_allowed() {
+        let head = request("/", &["Authorization: Bearer s3cret-token"]);
+        assert_eq!(access(&head, TOKEN), Access::Allowed);
+    }
+
+    #[test]
+    fn cookie_is_allowed() {
+        let head = request("/app", &["Cookie: theme=dark; tulsbot_lan=s3cret-token"]);
+        assert_eq!(access(&head, TOKEN), Access::Allowed);
+    }
+
+    #[test]
+    fn query_token_redirects_without_it() {
+        let head = request("/page?a=1&tulsbot_token=s3cret-token&b=2", &[]);
+        assert_eq!(access(&head, TOKEN), Access::SetCookie("/page?a=1&b=2".into()));
+        let head = request("/?tulsbot_token=s3cret-token", &[]);
+        assert_eq!(access(&head, TOKEN), Access::SetCookie("/".into()));
+    }
+
+    #[test]
+    fn wrong_or_partial_tokens_are_denied() {
+        assert_eq!(access(&request("/", &[]), TOKEN), Access::Denied);
+        let wrong = request("/", &["Authorization: Bearer s3cret-tokem"]);
+        assert_eq!(access(&wrong, TOKEN), Access::Denied);
+        let prefix = request("/", &["Cookie: tulsbot_lan=s3cret"]);
+        assert_eq!(access(&prefix, TOKEN), Access::Denied);
+        let prefix = request("/?tulsbot_token=s3cret", &[]);
+        assert_eq!(access(&prefix, TOKEN), Access::Denied);
+        let longer = request("/?tulsbot_token=s3cret-token-and-more", &[]);
+        assert_eq!(access(&longer, TOKEN), Access::Denied);
+    }
+}
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+// ── Window layouts ──────────────────────────────────────────────────────────
+//
+// A named arrangement of the app's windows: which are open, where, how big
+// and whether they stay on top, e.g. a "focus" layout with only the popover
+// and a "monitoring" one with the dashboard. Geometry is kept in physical
+// pixels together with the monitor it was on; when that monitor is gone the
+// window is shown where it is instead of somewhere off screen.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowPlacement {
+    pub label: String,
+    pub visible: bool,
+    /// Always on top.
+    #[serde(default)]
+    pub pinned: bool,
+    /// Monitor name the geometry belongs to.
+    #[serde(default)]
+    pub monitor: Option<String>,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Layout {
+    pub name: String,
+    pub windows: Vec<WindowPlacement>,
+    /// Unix seconds.
+    pub saved_at: u64,
+}
+
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Layout name is empty".into());
+    }
+    Ok(())
+}
+
+/// Add `layout`, replacing the one with the same name.
+pub fn upsert(layouts: &mut Vec<Layout>, layout: Layout) {
+    match layouts.iter_mut().find(|l| l.name == layout.name) {
+        Some(existing) => *existing = layout,
+        None => layouts.push(layout),
+    }
+}
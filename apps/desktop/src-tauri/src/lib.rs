@@ -1,328 +1,685 @@
-use serde::{Deserialize, Serialize};
-use std::sync::Mutex;
-use tauri::{
-    image::Image,
-    menu::{Menu, MenuItem},
-    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    AppHandle, Emitter, Manager, State, WebviewUrl, WebviewWindowBuilder,
-};
+use std::sync::atomic::Ordering;
+use tauri::Manager;
+use tauri_plugin_global_shortcut::ShortcutState;
+
+pub mod accessibility;
+pub mod alerts;
+pub mod app_rules;
+pub mod audit;
+pub mod backup;
+pub mod blobs;
+pub mod blur;
+pub mod budgets;
+pub mod calc;
+pub mod calendar;
+pub mod chat_import;
+pub mod chat_probe;
+pub mod compression;
+pub mod context;
+pub mod context_builder;
+pub mod context_menu;
+pub mod deps;
+pub mod discovery;
+pub mod dock;
+pub mod downloads;
+pub mod email;
+pub mod conversations;
+pub mod credentials;
+pub mod embeddings;
+pub mod env;
+pub mod error;
+pub mod export;
+pub mod external;
+pub mod feeds;
+pub mod focus;
+pub mod git;
+pub mod guardrails;
+pub mod hardware;
+pub mod health;
+pub mod history;
+pub mod hooks;
+pub mod i18n;
+pub mod ingest;
+pub mod jobs;
+pub mod keychain;
+pub mod lan;
+pub mod layouts;
+pub mod locks;
+pub mod memories;
+pub mod middleware;
+pub mod migration;
+#[cfg(feature = "mock-backend")]
+pub mod mock;
+pub mod native_messaging;
+pub mod netdiag;
+pub mod notes;
+pub mod notifications;
+pub mod pairing;
+pub mod placement;
+pub mod postgres;
+pub mod pipeline;
+pub mod profiles;
+pub mod prompts;
+pub mod providers;
+pub mod proxy;
+pub mod qdrant;
+pub mod qr;
+pub mod readiness;
+pub mod redaction;
+pub mod relay;
+pub mod remediation;
+pub mod report;
+pub mod retention;
+pub mod sandbox;
+pub mod secrets;
+pub mod selection;
+pub mod settings;
+pub mod setup;
+pub mod share;
+pub mod shortcuts;
+pub mod snapshot;
+pub mod state;
+pub mod supervisor;
+pub mod sync;
+pub mod system;
+pub mod templates;
+pub mod themes;
+pub mod time_tracking;
+pub mod trace;
+pub mod transforms;
+pub mod tray;
+pub mod tray_anim;
+pub mod typing;
+pub mod upload;
+pub mod usage;
+pub mod users;
+pub mod warmup;
+pub mod watchdog;
+pub mod weather;
+pub mod webpage;
+pub mod windows;
+
+use accessibility::refresh_accessibility;
+use alerts::sample_system_for_alerts;
+use blobs::collect_attachment_garbage;
+use chat_probe::run_chat_probe_job;
+use context::refresh_active_context;
+use conversations::now_millis;
+use external::probe_external;
+use feeds::run_feed_jobs;
+use hardware::hardware_info;
+use health::{show_in_tray, supervise_health_poller, HealthState};
+use history::HealthHistory;
+use i18n::I18n;
+use locks::LockExt;
+use native_messaging::handle_bridge_message;
+use notifications::refresh_dnd;
+use pairing::{app_data_dir, follow_desktop, start_companion_listener};
+use proxy::run_connection_warmup;
+use qdrant::run_scheduled_snapshots;
+use retention::run_retention_job;
+use share::open_share;
+#[cfg(target_os = "macos")]
+use share::SharePayload;
+use snapshot::{resume_from_snapshot, save_snapshot};
+use state::AppState;
+use sync::run_sync_job;
+use themes::refresh_theme;
+use tray::{setup_tray, update_dock_icon, use_dashboard_as_tray};
+use watchdog::{start_watchdog, stop_watchdog};
+use windows::{ensure_window, handle_shortcut, register_shortcuts};
 
-// ── Health state ────────────────────────────────────────────────────────────
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ServiceHealth {
-    pub name: String,
-    pub healthy: bool,
-    pub port: u16,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct HealthState {
-    pub services: Vec<ServiceHealth>,
-    pub overall: String, // "healthy", "degraded", "down"
-}
-
-impl Default for HealthState {
-    fn default() -> Self {
-        Self {
-            services: vec![
-                ServiceHealth { name: "PostgreSQL".into(), healthy: false, port: 5432 },
-                ServiceHealth { name: "Qdrant".into(), healthy: false, port: 6333 },
-                ServiceHealth { name: "Context Manager".into(), healthy: false, port: 3001 },
-                ServiceHealth { name: "Web UI".into(), healthy: false, port: 3100 },
-            ],
-            overall: "down".into(),
-        }
-    }
-}
-
-pub struct AppState {
-    pub health: Mutex<HealthState>,
-}
-
-// ── Tauri commands ──────────────────────────────────────────────────────────
-
-/// Return the most recent health snapshot.
-#[tauri::command]
-async fn get_health(state: State<'_, AppState>) -> Result<HealthState, String> {
-    let health = state.health.lock().map_err(|e| e.to_string())?;
-    Ok(health.clone())
-}
-
-/// Generic HTTP proxy — lets the frontend call any backend endpoint through
-/// the Tauri IPC bridge (required because production CSP blocks localhost).
-#[tauri::command]
-async fn api_proxy(
-    method: String,
-    url: String,
-    body: Option<String>,
-) -> Result<String, String> {
-    let client = reqwest::Client::new();
-
-    let req_method = match method.to_uppercase().as_str() {
-        "GET" => reqwest::Method::GET,
-        "POST" => reqwest::Method::POST,
-        "PUT" => reqwest::Method::PUT,
-        "DELETE" => reqwest::Method::DELETE,
-        "PATCH" => reqwest::Method::PATCH,
-        other => return Err(format!("Unsupported HTTP method: {}", other)),
-    };
-
-    let mut builder = client
-        .request(req_method, &url)
-        .timeout(std::time::Duration::from_secs(60));
-
-    if let Some(json_body) = body {
-        builder = builder
-            .header("content-type", "application/json")
-            .body(json_body);
-    }
-
-    let resp = builder
-        .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
-
-    let status = resp.status().as_u16();
-    let text = resp.text().await.map_err(|e| e.to_string())?;
-
-    if status >= 400 {
-        return Err(format!("HTTP {}: {}", status, text));
-    }
-    Ok(text)
-}
-
-// ── Popover window management ─────────────────────────────────────────────
-
-#[tauri::command]
-async fn toggle_popover(app: AppHandle) -> Result<(), String> {
-    if let Some(window) = app.get_webview_window("chat-popover") {
-        if window.is_visible().unwrap_or(false) {
-            window.hide().map_err(|e| e.to_string())?;
-        } else {
-            // Position near top-right of the primary monitor
-            if let Ok(Some(monitor)) = window.primary_monitor() {
-                let scale = monitor.scale_factor();
-                let screen_w = (monitor.size().width as f64 / scale) as i32;
-                let x = screen_w - 390; // 380px wide + 10px margin
-                let y = 30; // Below menu bar
-                let _ = window.set_position(tauri::Position::Logical(
-                    tauri::LogicalPosition::new(x as f64, y as f64),
-                ));
-            }
-            window.show().map_err(|e| e.to_string())?;
-            window.set_focus().map_err(|e| e.to_string())?;
-        }
-    } else {
-        // Fallback: create the popover if it doesn't exist
-        let _win = WebviewWindowBuilder::new(
-            &app,
-            "chat-popover",
-            WebviewUrl::App("tulsbot.html".into()),
-        )
-        .title("")
-        .inner_size(380.0, 540.0)
-        .decorations(false)
-        .always_on_top(true)
-        .visible(true)
-        .skip_taskbar(true)
-        .build()
-        .map_err(|e: tauri::Error| e.to_string())?;
-    }
-    Ok(())
-}
+// ── App entry ───────────────────────────────────────────────────────────────
 
-#[tauri::command]
-async fn hide_popover(app: AppHandle) -> Result<(), String> {
-    if let Some(window) = app.get_webview_window("chat-popover") {
-        window.hide().map_err(|e| e.to_string())?;
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    // Launched by a browser as the native-messaging host: relay and exit.
+    if native_messaging::is_host_invocation() {
+        native_messaging::run_host();
+        return;
     }
-    Ok(())
-}
-
-#[tauri::command]
-async fn show_dashboard(app: AppHandle) -> Result<(), String> {
-    if let Some(window) = app.get_webview_window("main") {
-        window.show().map_err(|e| e.to_string())?;
-        window.set_focus().map_err(|e| e.to_string())?;
+    // Started as the crash watchdog of a running instance
+    if watchdog::is_invocation() {
+        watchdog::run();
+        return;
     }
-    Ok(())
-}
-
-// ── Health polling ──────────────────────────────────────────────────────────
-
-async fn check_port(port: u16) -> bool {
-    tokio::net::TcpStream::connect(format!("127.0.0.1:{}", port))
-        .await
-        .is_ok()
-}
-
-async fn poll_health(app: AppHandle, state: &AppState) {
-    let ports = vec![
-        ("PostgreSQL", 5432u16),
-        ("Qdrant", 6333),
-        ("Context Manager", 3001),
-        ("Web UI", 3100),
-    ];
-
-    let mut services = Vec::new();
-    let mut healthy_count = 0;
 
-    for (name, port) in &ports {
-        let healthy = check_port(*port).await;
-        if healthy {
-            healthy_count += 1;
-        }
-        services.push(ServiceHealth {
-            name: name.to_string(),
-            healthy,
-            port: *port,
+    let args: Vec<String> = std::env::args().collect();
+
+    // Notification button activated through the tulsbot:// URL scheme
+    if let Some((action, service, nonce)) =
+        args.get(1).and_then(|url| notifications::parse_action_url(url))
+    {
+        let message = serde_json::json!({
+            "type": "notification-action",
+            "action": action,
+            "service": service,
+            "nonce": nonce,
         });
-    }
-
-    let overall = if healthy_count == ports.len() {
-        "healthy"
-    } else if healthy_count > 0 {
-        "degraded"
-    } else {
-        "down"
-    }
-    .to_string();
-
-    // Update tray icon colour based on health
-    if let Some(tray) = app.tray_by_id("main-tray") {
-        let icon_bytes: &[u8] = match overall.as_str() {
-            "healthy" => include_bytes!("../icons/tray-green.png"),
-            "degraded" => include_bytes!("../icons/tray-yellow.png"),
-            _ => include_bytes!("../icons/tray-red.png"),
-        };
-        if let Ok(icon) = Image::from_bytes(icon_bytes) {
-            let _ = tray.set_icon(Some(icon));
-            let _ = tray.set_icon_as_template(false);
+        if native_messaging::relay(message).is_ok() {
+            return;
         }
-        let _ = tray.set_tooltip(Some(&format!("Tulsbot — {}", overall)));
     }
 
-    let new_health = HealthState {
-        services,
-        overall: overall.clone(),
-    };
-
-    if let Ok(mut health) = state.health.lock() {
-        *health = new_health.clone();
+    // Shared content: hand it to a running instance if there is one
+    let shared = share::from_args(&args);
+    if let Some(payload) = &shared {
+        let mut message = serde_json::to_value(payload).unwrap_or_default();
+        message["type"] = "share".into();
+        if native_messaging::relay(message).is_ok() {
+            return;
+        }
     }
 
-    // Broadcast to all frontend windows
-    let _ = app.emit("health-update", &new_health);
-}
-
-// ── Tray setup ──────────────────────────────────────────────────────────────
-
-fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
-    let open_item = MenuItem::with_id(app, "open", "Open Dashboard", true, None::<&str>)?;
-    let sep = MenuItem::with_id(app, "sep", "────────────", false, None::<&str>)?;
-    let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-
-    let menu = Menu::with_items(app, &[&open_item, &sep, &quit_item])?;
-
-    let _tray = TrayIconBuilder::with_id("main-tray")
-        .icon(
-            Image::from_bytes(include_bytes!("../icons/tray-icon.png")).unwrap_or_else(|_| {
-                // Fallback: tiny placeholder (will be replaced by health poll)
-                Image::new_owned(vec![255u8; 16 * 16 * 4], 16, 16)
-            }),
-        )
-        .icon_as_template(true)
-        .menu(&menu)
-        .tooltip("Tulsbot")
-        .on_menu_event(move |app, event| {
-            let app = app.clone();
-            match event.id().as_ref() {
-                "open" => {
-                    if let Some(window) = app.get_webview_window("main") {
-                        let _ = window.show();
-                        let _ = window.set_focus();
-                    }
-                }
-                "quit" => {
-                    app.exit(0);
-                }
-                _ => {}
-            }
-        })
-        .on_tray_icon_event(|tray, event| {
-            if let TrayIconEvent::Click {
-                button: MouseButton::Left,
-                button_state: MouseButtonState::Up,
-                ..
-            } = event
-            {
-                let app = tray.app_handle().clone();
-                tauri::async_runtime::spawn(async move {
-                    let _ = toggle_popover(app).await;
-                });
-            }
-        })
-        .build(app)?;
-
-    Ok(())
-}
-
-// ── App entry ───────────────────────────────────────────────────────────────
-
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    let app_state = AppState {
-        health: Mutex::new(HealthState::default()),
-    };
-
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
-        .manage(app_state)
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(|app, shortcut, event| {
+                    if event.state() == ShortcutState::Pressed {
+                        handle_shortcut(app, shortcut);
+                    }
+                })
+                .build(),
+        )
+        .manage(AppState::default())
         .invoke_handler(tauri::generate_handler![
-            get_health,
-            api_proxy,
-            toggle_popover,
-            hide_popover,
-            show_dashboard,
+            health::get_health,
+            health::wait_for_ready,
+            settings::get_settings,
+            settings::set_settings,
+            profiles::list_profiles,
+            profiles::save_profile,
+            profiles::delete_profile,
+            profiles::switch_profile,
+            migration::export_profile,
+            migration::import_profile,
+            proxy::api_proxy,
+            proxy::get_proxy_trace,
+            proxy::clear_proxy_trace,
+            conversations::list_conversations,
+            conversations::rename_conversation,
+            conversations::list_tags,
+            conversations::set_conversation_tags,
+            conversations::pin_message,
+            conversations::rate_message,
+            conversations::get_conversation,
+            conversations::create_conversation,
+            conversations::delete_conversation,
+            conversations::append_message,
+            conversations::regenerate_message,
+            context_builder::preview_context,
+            prompts::list_system_prompts,
+            prompts::create_system_prompt,
+            prompts::update_system_prompt,
+            prompts::delete_system_prompt,
+            prompts::set_default_system_prompt,
+            prompts::set_conversation_system_prompt,
+            prompts::resolve_system_prompt,
+            prompts::export_system_prompts,
+            prompts::import_system_prompts,
+            context_builder::get_last_context,
+            context_builder::complete_conversation,
+            memories::list_memories,
+            memories::approve_memory,
+            memories::add_memory,
+            memories::delete_memory,
+            memories::extract_memories,
+            providers::set_provider_key,
+            providers::add_credential,
+            providers::remove_credential,
+            providers::list_credentials,
+            providers::select_credential,
+            usage::get_usage_stats,
+            time_tracking::get_time_stats,
+            lan::enable_lan_exposure,
+            lan::disable_lan_exposure,
+            lan::get_lan_exposure,
+            discovery::start_peer_discovery,
+            discovery::stop_peer_discovery,
+            discovery::list_peers,
+            pairing::pair_device,
+            pairing::unpair_device,
+            pairing::list_devices,
+            pairing::join_desktop,
+            pairing::relay_to_desktop,
+            pairing::leave_desktop,
+            relay::set_notification_relay,
+            tray::set_tray_behavior,
+            watchdog::set_watchdog,
+            tray::tray_available,
+            tray::set_menu_bar_mode,
+            tray::quit_app,
+            focus::start_focus,
+            focus::stop_focus,
+            focus::get_focus_status,
+            budgets::get_budget_status,
+            providers::clear_provider_key,
+            blobs::add_attachment,
+            blobs::upload_attachment,
+            blobs::get_attachment,
+            blobs::gc_attachments,
+            conversations::branch_conversation,
+            conversations::select_branch,
+            conversations::list_branches,
+            conversations::get_conversation_config,
+            conversations::set_conversation_config,
+            redaction::redact_text,
+            redaction::get_redaction_log,
+            redaction::set_conversation_redaction,
+            guardrails::preview_guardrails,
+            windows::toggle_popover,
+            windows::hide_popover,
+            windows::dock_popover,
+            windows::set_popover_size,
+            windows::start_popover_resize,
+            windows::open_pip,
+            windows::close_pip,
+            windows::set_pip_click_through,
+            windows::update_pip,
+            windows::get_pip_response,
+            settings::set_privacy_mode,
+            profiles::get_user_info,
+            chat_import::preview_chat_import,
+            chat_import::import_chat_history,
+            usage::export_usage,
+            usage::export_health_history,
+            sync::get_sync_status,
+            sync::enable_sync,
+            sync::disable_sync,
+            sync::sync_now,
+            backup::list_backup_targets,
+            backup::save_backup_target,
+            backup::remove_backup_target,
+            backup::test_backup_target,
+            backup::upload_backup,
+            hooks::list_health_hooks,
+            hooks::save_health_hook,
+            hooks::delete_health_hook,
+            hooks::run_health_hook,
+            chat_probe::probe_chat,
+            alerts::get_alerts,
+            alerts::list_alert_rules,
+            alerts::save_alert_rule,
+            alerts::delete_alert_rule,
+            alerts::silence_alerts,
+            alerts::remove_silence,
+            windows::list_layouts,
+            windows::save_layout,
+            windows::apply_layout,
+            windows::delete_layout,
+            windows::focus_window,
+            windows::focus_next_window,
+            windows::set_shortcuts,
+            windows::set_blur_behavior,
+            health::reset_state,
+            middleware::get_command_metrics,
+            middleware::get_diagnostics,
+            middleware::set_debug_logging,
+            proxy::get_connection_info,
+            proxy::upload_file,
+            downloads::download_file,
+            downloads::cancel_download,
+            retention::preview_retention,
+            retention::run_retention,
+            retention::get_retention_state,
+            notes::list_notes,
+            notes::get_note,
+            notes::create_note,
+            notes::update_note,
+            notes::delete_note,
+            notes::search_notes,
+            notes::link_note,
+            windows::show_dashboard,
+            context::get_active_context,
+            context::insert_text_at_cursor,
+            context::get_selected_text,
+            context::get_active_rule,
+            windows::take_popover_context,
+            i18n::get_locale,
+            i18n::set_locale,
+            accessibility::get_accessibility_prefs,
+            themes::get_theme,
+            themes::set_theme,
+            notifications::get_notification_state,
+            notifications::notification_action,
+            supervisor::restart_service,
+            remediation::get_audit_log,
+            supervisor::start_all_services,
+            supervisor::stop_all_services,
+            supervisor::get_service_graph,
+            env::get_service_env,
+            env::set_service_env,
+            env::import_env_file,
+            env::generate_secret,
+            env::generate_service_secret,
+            env::add_service,
+            env::remove_service,
+            setup::check_dependencies,
+            setup::install_dependency,
+            setup::get_service_logs,
+            report::generate_status_report,
+            postgres::set_postgres_credentials,
+            postgres::clear_postgres_credentials,
+            postgres::get_postgres_connections,
+            postgres::terminate_postgres_connection,
+            postgres::get_postgres_table_sizes,
+            postgres::run_postgres_maintenance,
+            qdrant::list_qdrant_snapshots,
+            qdrant::create_qdrant_snapshot,
+            qdrant::restore_snapshot,
+            sandbox::run_code,
+            git::list_git_roots,
+            git::add_git_root,
+            git::remove_git_root,
+            git::git_status,
+            git::git_diff,
+            git::git_log,
+            git::git_blame,
+            calendar::add_calendar_feed,
+            calendar::remove_calendar_feed,
+            calendar::get_upcoming_events,
+            weather::get_weather,
+            weather::set_weather_location,
+            qr::generate_qr,
+            calc::evaluate_expression,
+            email::compose_email,
+            webpage::fetch_page,
+            feeds::list_feeds,
+            feeds::add_feed,
+            feeds::remove_feed,
+            feeds::get_feed_items,
+            feeds::refresh_feeds,
+            feeds::create_feed_digest,
+            feeds::set_feed_schedule,
+            system::get_system_snapshot,
+            netdiag::ping_host,
+            netdiag::resolve_host,
+            netdiag::port_scan,
+            hardware::get_hardware_info,
+            embeddings::embed_text,
+            embeddings::embed_texts,
+            embeddings::get_embedding_models,
+            embeddings::download_embedding_model,
+            embeddings::delete_embedding_model,
+            downloads::list_models,
+            downloads::download_model,
+            downloads::delete_model,
+            native_messaging::get_browser_bridges,
+            native_messaging::install_browser_bridge,
+            native_messaging::uninstall_browser_bridge,
+            share::preprocess_text,
+            share::install_share_target,
+            share::uninstall_share_target,
+            share::get_context_menu_installed,
+            share::install_context_menu,
+            share::uninstall_context_menu,
         ])
-        .setup(|app| {
+        .setup(move |app| {
             let handle = app.handle().clone();
+            middleware::init(&handle);
+
+            let store = profiles::load(&handle);
+            let profile = store.active_profile();
+            let state = handle.state::<AppState>();
+            *state.health.lock_or_recover() = HealthState::for_services(&profile.services);
+            *state.profiles.lock_or_recover() = store;
+
+            // Nothing we write should be readable by other accounts
+            let app_dir = handle.path().app_data_dir().map_err(|e| e.to_string());
+            let data_dir = profiles::data_dir(&handle, &profile);
+            for dir in [&app_dir, &data_dir].into_iter().flatten() {
+                if let Err(e) = users::restrict(dir) {
+                    eprintln!("[tulsbot] {}", e);
+                }
+            }
+            if let Ok(dir) = &data_dir {
+                *state.history.lock_or_recover() = HealthHistory::load(dir);
+            }
+            // Pick up where a recent run left off
+            let now = now_millis() / 1000;
+            let snapshot = data_dir.as_ref().ok().and_then(|dir| snapshot::load(dir, now));
+            if let Some(snapshot) = &snapshot {
+                *state.health.lock_or_recover() = snapshot.health.clone();
+                state.notifications.lock_or_recover().queued = snapshot.queued.clone();
+                *state.pip_response.lock_or_recover() = snapshot.pip.clone();
+            }
+            if let Ok(dir) = &data_dir {
+                *state.memories.lock_or_recover() = memories::load(dir);
+            }
+            let loaded = data_dir.map(|dir| settings::load(&dir)).unwrap_or_default();
+            if loaded.headless {
+                state.headless.store(true, Ordering::Relaxed);
+            }
+            *state.i18n.lock_or_recover() = I18n::new(loaded.locale.as_deref());
+            *state.theme.lock_or_recover() =
+                themes::resolve(&loaded.theme, themes::os_prefers_dark(), false);
+            *state.settings.lock_or_recover() = loaded;
+            let headless = state.headless.load(Ordering::Relaxed);
+            if state.settings.lock_or_recover().watchdog {
+                if let Err(e) = start_watchdog(&handle) {
+                    eprintln!("[tulsbot] {}", e);
+                }
+            }
+            let menu_bar_only =
+                cfg!(target_os = "macos") && state.settings.lock_or_recover().menu_bar_only;
 
             // Setup tray icon + menu
             if let Err(e) = setup_tray(&handle) {
                 eprintln!("[tulsbot] Failed to setup tray: {}", e);
             }
-
-            // Show main window
-            if let Some(window) = app.get_webview_window("main") {
-                let _ = window.show();
-                let _ = window.set_focus();
+            if let Some(snapshot) = &snapshot {
+                show_in_tray(&handle, &snapshot.health.overall);
+            }
+            let tray_missing = !headless && !tray::supported();
+            state.tray_missing.store(tray_missing, Ordering::Relaxed);
+            if let Err(e) = register_shortcuts(&handle) {
+                eprintln!("[tulsbot] Failed to register shortcuts: {}", e);
             }
 
-            // Popover: hide on blur (lose focus)
-            if let Some(popover) = app.get_webview_window("chat-popover") {
-                let popover_clone = popover.clone();
-                popover.on_window_event(move |event| {
-                    if let tauri::WindowEvent::Focused(false) = event {
-                        let _ = popover_clone.hide();
+            if headless {
+                eprintln!("[tulsbot] Headless mode: skipping webview creation");
+            } else {
+                // Show main window; the popover is created hidden. A menu bar
+                // app starts with the popover only.
+                let labels: &[&str] =
+                    if menu_bar_only { &["chat-popover"] } else { &["main", "chat-popover"] };
+                update_dock_icon(&handle, !menu_bar_only);
+                for label in labels.iter().copied() {
+                    if let Err(e) = ensure_window(&handle, label) {
+                        eprintln!("[tulsbot] Failed to create window '{}': {}", label, e);
+                    }
+                }
+                // Unless the windows open before a restart come back instead
+                if snapshot.is_none() {
+                    if let Some(window) = app.get_webview_window("main") {
+                        let _ = window.show();
+                        let _ = window.set_focus();
                     }
-                });
+                }
+                if tray_missing {
+                    use_dashboard_as_tray(&handle);
+                }
+            }
+            if let Some(snapshot) = snapshot {
+                resume_from_snapshot(&handle, snapshot, headless);
             }
 
-            // Start health polling (every 5 seconds)
+            // Pre-open backend connections, again after every wake
+            tauri::async_runtime::spawn(run_connection_warmup(handle.clone()));
+
+            // Start health polling (every 5 seconds) under its supervisor
             let poll_handle = handle.clone();
             tauri::async_runtime::spawn(async move {
                 // Initial delay so the UI can render first
                 tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+                supervise_health_poller(&poll_handle).await;
+            });
+
+            // Serve paired companion devices
+            let devices = app_data_dir(&handle).map(|dir| pairing::load_devices(&dir));
+            if devices.is_ok_and(|d| !d.is_empty()) {
+                start_companion_listener(&handle);
+            }
+            // Or, on a companion, follow the desktop it paired with
+            if app_data_dir(&handle).is_ok_and(|dir| pairing::load_link(&dir).is_some()) {
+                follow_desktop(&handle);
+            }
+
+            // Relay messages from the browser extension's native host
+            let bridge_handle = handle.clone();
+            tauri::async_runtime::spawn(async move {
+                let handler = move |message| handle_bridge_message(&bridge_handle, message);
+                if let Err(e) = native_messaging::serve(handler).await {
+                    eprintln!("[tulsbot] Browser bridge unavailable: {}", e);
+                }
+            });
+
+            // Follow OS accessibility, appearance and DND (every 10 seconds)
+            let a11y_handle = handle.clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    refresh_accessibility(&a11y_handle).await;
+                    refresh_theme(&a11y_handle).await;
+                    refresh_dnd(&a11y_handle).await;
+                    tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+                }
+            });
+
+            // External provider latency (every minute)
+            let external_handle = handle.clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    probe_external(&external_handle).await;
+                    tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+                }
+            });
+
+            // System metrics for alert rules (every minute, when a rule needs them)
+            let system_handle = handle.clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    sample_system_for_alerts(&system_handle).await;
+                    tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+                }
+            });
+
+            // End-to-end chat probe (checked every minute, runs when due)
+            let probe_handle = handle.clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+                    run_chat_probe_job(&probe_handle).await;
+                }
+            });
+
+            // Probe GPUs once in the background so the first query is instant
+            let hardware_handle = handle.clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                if let Ok(info) = hardware_info(&hardware_handle, false) {
+                    eprintln!(
+                        "[tulsbot] Hardware: {} GPU(s), backend {:?}",
+                        info.gpus.len(),
+                        info.recommended.backend
+                    );
+                }
+            });
+
+            // Scheduled Qdrant snapshots (checked every 10 minutes)
+            let snapshot_handle = handle.clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    tokio::time::sleep(std::time::Duration::from_secs(600)).await;
+                    run_scheduled_snapshots(&snapshot_handle).await;
+                }
+            });
+
+            // Poll feeds and write digests when due (checked every minute)
+            let feeds_handle = handle.clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+                    run_feed_jobs(&feeds_handle).await;
+                }
+            });
+
+            // Sync conversations through the user's folder when due
+            let sync_handle = handle.clone();
+            tauri::async_runtime::spawn(async move {
                 loop {
-                    let state = poll_handle.state::<AppState>();
-                    poll_health(poll_handle.clone(), state.inner()).await;
-                    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                    tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+                    run_sync_job(&sync_handle).await;
                 }
             });
 
+            // Apply retention limits once a day while idle (checked hourly)
+            let retention_handle = handle.clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    tokio::time::sleep(std::time::Duration::from_secs(3600)).await;
+                    run_retention_job(&retention_handle).await;
+                }
+            });
+
+            // Drop attachment blobs orphaned since the last run
+            let gc_handle = handle.clone();
+            tauri::async_runtime::spawn(async move {
+                match collect_attachment_garbage(&gc_handle).await {
+                    Ok(0) => {}
+                    Ok(n) => eprintln!("[tulsbot] Removed {} unreferenced attachments", n),
+                    Err(e) => eprintln!("[tulsbot] Attachment cleanup failed: {}", e),
+                }
+            });
+
+            // Track the frontmost app while enabled (every 2 seconds)
+            let context_handle = handle.clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    let enabled = context_handle
+                        .state::<AppState>()
+                        .settings
+                        .lock()
+                        .map(|s| s.track_active_context)
+                        .unwrap_or(false);
+                    if enabled {
+                        refresh_active_context(&context_handle).await;
+                    }
+                    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+                }
+            });
+
+            // Content shared at launch (no running instance to relay to)
+            if let Some(payload) = shared {
+                if let Err(e) = open_share(&handle, payload) {
+                    eprintln!("[tulsbot] Failed to open shared content: {}", e);
+                }
+            }
+
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running Tulsbot");
+        .build(tauri::generate_context!())
+        .expect("error while building Tulsbot")
+        .run(|app, event| {
+            if let tauri::RunEvent::ExitRequested { .. } = event {
+                save_snapshot(app);
+                stop_watchdog(app);
+            }
+            // macOS: files dropped on the Dock icon or opened with Tulsbot
+            #[cfg(target_os = "macos")]
+            if let tauri::RunEvent::Opened { urls } = event {
+                let files = urls
+                    .iter()
+                    .filter_map(|url| url.to_file_path().ok())
+                    .map(|path| path.display().to_string())
+                    .collect();
+                let payload = SharePayload {
+                    source: "opened".into(),
+                    files,
+                    ..Default::default()
+                };
+                if let Err(e) = open_share(app, payload) {
+                    eprintln!("[tulsbot] Failed to open shared files: {}", e);
+                }
+            }
+        });
 }
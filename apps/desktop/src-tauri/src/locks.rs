@@ -0,0 +1,31 @@
+use std::sync::{Mutex, MutexGuard};
+
+// ── Poisoned locks ──────────────────────────────────────────────────────────
+//
+// A panic while a lock is held poisons it, and from then on every `lock()`
+// fails: one bad poll would leave `get_health` erroring until the app is
+// restarted. The state behind a poisoned lock is still there, at worst half
+// updated, so we take it over and clear the poison instead. `reset_state`
+// rebuilds the monitoring state when what was recovered is wrong.
+
+pub trait LockExt<T> {
+    /// Lock, recovering the state from a thread that panicked holding it.
+    fn lock_or_recover(&self) -> MutexGuard<'_, T>;
+}
+
+impl<T> LockExt<T> for Mutex<T> {
+    fn lock_or_recover(&self) -> MutexGuard<'_, T> {
+        self.lock().unwrap_or_else(|poisoned| {
+            eprintln!("[tulsbot] Recovering state from a lock poisoned by a panic");
+            self.clear_poison();
+            poisoned.into_inner()
+        })
+    }
+}
+
+// ── Append-only logs ────────────────────────────────────────────────────────
+
+/// Held while a line is appended to the JSONL audit log and while retention
+/// rewrites it, so a prune can't drop lines appended between its read and
+/// its rename.
+pub static LOGS: Mutex<()> = Mutex::new(());
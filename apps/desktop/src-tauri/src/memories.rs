@@ -0,0 +1,274 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tulsbot_macros::instrumented;
+
+use crate::conversations::{self, Message};
+use crate::error::{AppError, ErrorKind};
+use crate::locks::LockExt;
+use crate::profiles::active_data_dir;
+use crate::state::AppState;
+
+// ── Memories: facts the assistant should remember ───────────────────────────
+//
+// Candidates are picked out of the user's own messages and wait for approval;
+// only approved memories reach the context builder.
+
+const FILE_NAME: &str = "memories.json";
+
+/// Sentence openings that usually state a lasting fact or preference.
+const CUES: &[&str] = &[
+    "remember that ",
+    "please remember ",
+    "my name is ",
+    "i am a ",
+    "i'm a ",
+    "i work ",
+    "i live ",
+    "i prefer ",
+    "i always ",
+    "i never ",
+    "i use ",
+    "i don't like ",
+    "i like ",
+    "call me ",
+];
+
+const MAX_MEMORY_CHARS: usize = 300;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MemoryStatus {
+    Pending,
+    Approved,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Memory {
+    pub id: String,
+    pub text: String,
+    pub status: MemoryStatus,
+    /// Unix seconds.
+    pub created_at: u64,
+    pub conversation: Option<String>,
+    pub message: Option<String>,
+}
+
+pub fn load(dir: &Path) -> Vec<Memory> {
+    std::fs::read_to_string(dir.join(FILE_NAME))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(dir: &Path, memories: &[Memory]) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let text = serde_json::to_string_pretty(memories).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(FILE_NAME), text).map_err(|e| e.to_string())
+}
+
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches(['.', '!'])
+        .to_lowercase()
+}
+
+/// Sentences of `text` that look like facts worth remembering.
+pub fn candidates(text: &str) -> Vec<String> {
+    text.split(['.', '!', '?', '\n'])
+        .map(str::trim)
+        .filter(|sentence| {
+            let lower = sentence.to_lowercase();
+            sentence.len() <= MAX_MEMORY_CHARS && CUES.iter().any(|cue| lower.starts_with(cue))
+        })
+        .map(|sentence| {
+            let lower = sentence.to_lowercase();
+            // "Remember that X" → "X"
+            let fact = ["remember that ", "please remember "]
+                .iter()
+                .find(|cue| lower.starts_with(*cue))
+                .map_or(sentence, |cue| &sentence[cue.len()..]);
+            let mut chars = fact.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        })
+        .filter(|fact: &String| !fact.is_empty())
+        .collect()
+}
+
+/// Add pending memories for the candidates in `text` that aren't known yet.
+/// Returns the new ones.
+pub fn propose(
+    memories: &mut Vec<Memory>,
+    text: &str,
+    conversation: Option<&str>,
+    message: Option<&str>,
+    now: u64,
+) -> Vec<Memory> {
+    let mut added = Vec::new();
+    for fact in candidates(text) {
+        let key = normalize(&fact);
+        if memories.iter().any(|m| normalize(&m.text) == key) {
+            continue;
+        }
+        let memory = Memory {
+            id: uuid::Uuid::new_v4().to_string(),
+            text: fact,
+            status: MemoryStatus::Pending,
+            created_at: now,
+            conversation: conversation.map(String::from),
+            message: message.map(String::from),
+        };
+        memories.push(memory.clone());
+        added.push(memory);
+    }
+    added
+}
+
+pub fn approved_memories(app: &AppHandle) -> Result<Vec<String>, String> {
+    let state = app.state::<AppState>();
+    let memories = state.memories.lock_or_recover();
+    Ok(memories
+        .iter()
+        .filter(|m| m.status == MemoryStatus::Approved)
+        .map(|m| m.text.clone())
+        .collect())
+}
+
+/// Queue memory candidates found in a user message for approval and emit
+/// `memories-proposed`.
+pub fn propose_memories(
+    app: &AppHandle,
+    dir: &std::path::Path,
+    conversation: &str,
+    message: &Message,
+) {
+    let state = app.state::<AppState>();
+    let Ok(mut current) = state.memories.lock() else {
+        return;
+    };
+    let added = propose(
+        &mut current,
+        &message.content,
+        Some(conversation),
+        Some(&message.id),
+        message.created_at,
+    );
+    if added.is_empty() {
+        return;
+    }
+    if let Err(e) = save(dir, &current) {
+        eprintln!("[tulsbot] Failed to save memories: {}", e);
+    }
+    let _ = app.emit("memories-proposed", &added);
+}
+
+/// Update the stored memories with `change` and persist them.
+pub fn update_memories<T>(
+    app: &AppHandle,
+    change: impl FnOnce(&mut Vec<Memory>) -> Result<T, String>,
+) -> Result<T, String> {
+    let dir = active_data_dir(app)?;
+    let state = app.state::<AppState>();
+    let mut current = state.memories.lock_or_recover();
+    let result = change(&mut current)?;
+    save(&dir, &current)?;
+    Ok(result)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn list_memories(
+    state: State<'_, AppState>,
+    status: Option<MemoryStatus>,
+) -> Result<Vec<Memory>, AppError> {
+    let memories = state.memories.lock_or_recover();
+    Ok(memories
+        .iter()
+        .filter(|m| status.is_none_or(|s| m.status == s))
+        .cloned()
+        .collect())
+}
+
+/// Approve a memory, optionally correcting its text first.
+#[instrumented]
+#[tauri::command]
+pub async fn approve_memory(
+    app: AppHandle,
+    id: String,
+    text: Option<String>,
+) -> Result<Memory, AppError> {
+    Ok(update_memories(&app, |memories| {
+        let memory = memories
+            .iter_mut()
+            .find(|m| m.id == id)
+            .ok_or_else(|| format!("Unknown memory: {}", id))?;
+        if let Some(text) = text.filter(|t| !t.trim().is_empty()) {
+            memory.text = text.trim().to_string();
+        }
+        memory.status = MemoryStatus::Approved;
+        Ok(memory.clone())
+    })?)
+}
+
+/// Add an approved memory directly.
+#[instrumented]
+#[tauri::command]
+pub async fn add_memory(app: AppHandle, text: String) -> Result<Memory, AppError> {
+    if text.trim().is_empty() {
+        return Err(AppError::new(ErrorKind::InvalidInput, "Memory text is empty"));
+    }
+    Ok(update_memories(&app, |memories| {
+        let memory = Memory {
+            id: conversations::new_id(),
+            text: text.trim().to_string(),
+            status: MemoryStatus::Approved,
+            created_at: conversations::now(),
+            conversation: None,
+            message: None,
+        };
+        memories.push(memory.clone());
+        Ok(memory)
+    })?)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn delete_memory(app: AppHandle, id: String) -> Result<(), AppError> {
+    Ok(update_memories(&app, |memories| {
+        let before = memories.len();
+        memories.retain(|m| m.id != id);
+        if memories.len() == before {
+            return Err(format!("Unknown memory: {}", id));
+        }
+        Ok(())
+    })?)
+}
+
+/// Scan a whole conversation for memory candidates (new ones only).
+#[instrumented]
+#[tauri::command]
+pub async fn extract_memories(
+    app: AppHandle,
+    conversation: String,
+) -> Result<Vec<Memory>, AppError> {
+    let stored = conversations::load(&active_data_dir(&app)?, &conversation)?;
+    let added = update_memories(&app, |memories| {
+        Ok(stored
+            .messages
+            .iter()
+            .filter(|m| m.role == "user")
+            .flat_map(|m| {
+                propose(memories, &m.content, Some(&stored.id), Some(&m.id), m.created_at)
+            })
+            .collect::<Vec<_>>())
+    })?;
+    if !added.is_empty() {
+        let _ = app.emit("memories-proposed", &added);
+    }
+    Ok(added)
+}
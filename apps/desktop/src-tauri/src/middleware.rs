@@ -0,0 +1,216 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::task::{Context, Poll};
+use std::time::Instant;
+use tauri::{AppHandle, Manager};
+use tulsbot_macros::instrumented;
+
+use crate::audit::{self, AuditEntry};
+use crate::env::active_profile_name;
+use crate::error::{AppError, ErrorKind};
+use crate::lan::lan_status;
+use crate::locks::LockExt;
+use crate::pairing::{self, app_data_dir};
+use crate::state::AppState;
+use crate::tray::refresh_tray_menu;
+
+// ── Command middleware ──────────────────────────────────────────────────────
+//
+// Every Tauri command runs through `run`, put in place by `#[instrumented]`
+// from the `tulsbot-macros` crate. It times the invoke, keeps per-command
+// metrics for `get_command_metrics` and logs failures and slow calls. A
+// panicking command used to leave the frontend's promise pending forever;
+// it now answers with an `internal` error. Privileged commands, the ones
+// that change services, credentials or the system, also go to the audit log.
+// With debug logging on, every invoke is logged.
+
+/// Invokes slower than this are logged.
+const SLOW_MS: u64 = 2000;
+
+static APP: OnceLock<AppHandle> = OnceLock::new();
+static METRICS: Mutex<BTreeMap<&'static str, CommandMetrics>> = Mutex::new(BTreeMap::new());
+static DEBUG_LOGGING: AtomicBool = AtomicBool::new(false);
+
+/// Hand the middleware the app, for the audit log. Called once in setup.
+pub fn init(app: &AppHandle) {
+    let _ = APP.set(app.clone());
+}
+
+/// Log every invoke, not only failures and slow ones, until turned off or
+/// the app quits.
+pub fn set_debug(on: bool) {
+    DEBUG_LOGGING.store(on, Ordering::Relaxed);
+}
+
+pub fn debug_logging() -> bool {
+    DEBUG_LOGGING.load(Ordering::Relaxed)
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CommandMetrics {
+    pub calls: u64,
+    pub errors: u64,
+    pub panics: u64,
+    pub total_ms: u64,
+    pub max_ms: u64,
+    pub last_error: Option<String>,
+}
+
+/// Metrics of every command invoked since launch.
+pub fn metrics() -> BTreeMap<String, CommandMetrics> {
+    let metrics = METRICS.lock_or_recover();
+    metrics.iter().map(|(name, m)| (name.to_string(), m.clone())).collect()
+}
+
+/// Polls the command inside `catch_unwind`.
+struct CatchUnwind<F>(Pin<Box<F>>);
+
+impl<F: Future> Future for CatchUnwind<F> {
+    type Output = std::thread::Result<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let command = self.0.as_mut();
+        match std::panic::catch_unwind(AssertUnwindSafe(|| command.poll(cx))) {
+            Ok(Poll::Pending) => Poll::Pending,
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Err(panic) => Poll::Ready(Err(panic)),
+        }
+    }
+}
+
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".into())
+}
+
+pub async fn run<T>(
+    name: &'static str,
+    command: impl Future<Output = Result<T, AppError>>,
+) -> Result<T, AppError> {
+    let started = Instant::now();
+    let outcome = CatchUnwind(Box::pin(command)).await;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    let panicked = outcome.is_err();
+    let result = outcome.unwrap_or_else(|panic| {
+        let message = format!("{} panicked: {}", name, panic_message(&*panic));
+        Err(AppError::new(ErrorKind::Internal, message))
+    });
+
+    match &result {
+        Err(e) => eprintln!("[tulsbot] {} failed after {} ms: {}", name, elapsed_ms, e),
+        Ok(_) if elapsed_ms > SLOW_MS || debug_logging() => {
+            eprintln!("[tulsbot] {} took {} ms", name, elapsed_ms)
+        }
+        Ok(_) => {}
+    }
+    let mut metrics = METRICS.lock_or_recover();
+    let entry = metrics.entry(name).or_default();
+    entry.calls += 1;
+    entry.total_ms += elapsed_ms;
+    entry.max_ms = entry.max_ms.max(elapsed_ms);
+    if let Err(e) = &result {
+        entry.errors += 1;
+        entry.panics += u64::from(panicked);
+        entry.last_error = Some(e.message.clone());
+    }
+    drop(metrics);
+    result
+}
+
+/// `run`, then record the outcome in the audit log as `actor: user`.
+pub async fn run_privileged<T>(
+    name: &'static str,
+    service: Option<String>,
+    command: impl Future<Output = Result<T, AppError>>,
+) -> Result<T, AppError> {
+    let result = run(name, command).await;
+    if let Some(dir) = APP.get().and_then(|app| crate::profiles::active_data_dir(app).ok()) {
+        let entry = AuditEntry {
+            at: crate::conversations::now(),
+            actor: "user".into(),
+            action: name.replace('_', "-"),
+            service,
+            ok: result.is_ok(),
+            detail: result.as_ref().err().map(|e| e.message.clone()),
+        };
+        let _ = tauri::async_runtime::spawn_blocking(move || audit::record(&dir, entry)).await;
+    }
+    result
+}
+
+/// Calls, errors, panics and timings per command since launch.
+#[instrumented]
+#[tauri::command]
+pub async fn get_command_metrics(
+) -> Result<std::collections::BTreeMap<String, CommandMetrics>, AppError> {
+    Ok(metrics())
+}
+
+/// Version, platform, profile, service health and failing commands, as
+/// plain text for bug reports.
+pub fn diagnostics_text(app: &AppHandle) -> String {
+    let mut lines = vec![
+        format!("Tulsbot {}", env!("CARGO_PKG_VERSION")),
+        format!("OS: {} {}", std::env::consts::OS, std::env::consts::ARCH),
+        format!("Profile: {}", active_profile_name(app).unwrap_or_default()),
+    ];
+    let health = app.state::<AppState>().health.lock_or_recover().clone();
+    lines.push(format!("Health: {}", health.overall));
+    for service in &health.services {
+        let status = match (service.healthy, service.ready) {
+            (true, true) => "up",
+            (true, false) => "up, not ready",
+            (false, _) => "down",
+        };
+        let mut line = format!("  {} (:{}) {}", service.name, service.port, status);
+        if !service.blocked_by.is_empty() {
+            line.push_str(&format!(", blocked by {}", service.blocked_by.join(", ")));
+        }
+        lines.push(line);
+    }
+    let lan = lan_status(app);
+    lines.push(match lan.address {
+        Some(address) => format!("LAN access: {}", address),
+        None => "LAN access: off".into(),
+    });
+    let devices = app_data_dir(app).map(|dir| pairing::load_devices(&dir).len()).unwrap_or(0);
+    lines.push(format!("Paired devices: {}", devices));
+    lines.push(format!("Debug logging: {}", debug_logging()));
+    let failing: Vec<String> = metrics()
+        .into_iter()
+        .filter(|(_, m)| m.errors > 0 || m.panics > 0)
+        .map(|(name, m)| {
+            let last = m.last_error.unwrap_or_default();
+            format!("  {}: {} of {} failed; last: {}", name, m.errors + m.panics, m.calls, last)
+        })
+        .collect();
+    if !failing.is_empty() {
+        lines.push("Failing commands:".into());
+        lines.extend(failing);
+    }
+    lines.join("\n")
+}
+
+/// Diagnostics as plain text, for pasting into a bug report.
+#[instrumented]
+#[tauri::command]
+pub async fn get_diagnostics(app: AppHandle) -> Result<String, AppError> {
+    Ok(diagnostics_text(&app))
+}
+
+/// Log every command invoke until turned off or the app quits.
+#[instrumented]
+#[tauri::command]
+pub async fn set_debug_logging(app: AppHandle, enabled: bool) -> Result<(), AppError> {
+    set_debug(enabled);
+    refresh_tray_menu(&app);
+    Ok(())
+}
@@ -0,0 +1,359 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::{AppHandle, Manager};
+use tulsbot_macros::instrumented;
+
+use crate::{deps, postgres};
+use crate::error::AppError;
+use crate::locks::LockExt;
+use crate::profiles::{self, apply_profile, Profile};
+use crate::state::AppState;
+use crate::tray::refresh_tray_menu;
+
+// ── Profile export / import for moving between machines ─────────────────────
+//
+// An export is a directory holding `manifest.json`, a copy of each section's
+// files from the profile's data dir, and `DOCKER-VOLUMES.md` describing how
+// to carry the service volumes over. Secrets never leave the keychain; only
+// the names of the entries to re-create are recorded.
+
+/// Bumped when the bundle layout changes incompatibly.
+pub const FORMAT_VERSION: u32 = 1;
+
+const MANIFEST: &str = "manifest.json";
+const VOLUMES_README: &str = "DOCKER-VOLUMES.md";
+
+/// Section name and the paths (relative to the data dir) it consists of.
+pub const SECTIONS: &[(&str, &[&str])] = &[
+    ("settings", &["settings.json"]),
+    ("chat-history", &["conversations", "chat-history.jsonl"]),
+    ("prompts", &["prompts", "prompt-library.json"]),
+    ("index-roots", &["index-roots.json"]),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolumeRef {
+    pub service: String,
+    pub container: String,
+    pub volumes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format_version: u32,
+    pub app_version: String,
+    /// Unix seconds.
+    pub exported_at: u64,
+    pub profile: Profile,
+    /// Sections present in the bundle.
+    pub sections: Vec<String>,
+    /// Keychain accounts the profile uses; their secrets must be re-entered.
+    #[serde(default)]
+    pub secrets: Vec<String>,
+    #[serde(default)]
+    pub volumes: Vec<VolumeRef>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub profile: String,
+    pub imported: Vec<String>,
+    /// `(section, reason)`
+    pub skipped: Vec<(String, String)>,
+    pub warnings: Vec<String>,
+    /// Keychain entries to fill in again on this machine.
+    pub secrets: Vec<String>,
+}
+
+/// Copy a file or directory tree. Symbolic links are refused rather than
+/// followed, so a crafted bundle can't pull in files from outside it.
+fn copy_recursive(src: &Path, dst: &Path) -> std::io::Result<()> {
+    if std::fs::symlink_metadata(src)?.file_type().is_symlink() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Refusing to copy a symbolic link: {}", src.display()),
+        ));
+    }
+    if src.is_dir() {
+        std::fs::create_dir_all(dst)?;
+        for entry in std::fs::read_dir(src)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &dst.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        if let Some(parent) = dst.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(src, dst).map(|_| ())
+    }
+}
+
+/// Named volumes mounted by `container`. Blocking.
+pub fn container_volumes(container: &str) -> Vec<String> {
+    Command::new("docker")
+        .args([
+            "inspect",
+            "-f",
+            "{{range .Mounts}}{{if .Name}}{{.Name}} {{end}}{{end}}",
+            container,
+        ])
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .map(|out| {
+            String::from_utf8_lossy(&out.stdout)
+                .split_whitespace()
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn volume_instructions(volumes: &[VolumeRef]) -> String {
+    let mut text = String::from(
+        "# Docker volumes\n\n\
+         Service data lives in Docker volumes and is not part of this bundle.\n\
+         Stop the services, then on the old machine run, for each volume:\n\n\
+         ```sh\n\
+         docker run --rm -v <volume>:/data -v \"$PWD\":/backup alpine tar czf /backup/<volume>.tgz -C /data .\n\
+         ```\n\n\
+         and on the new machine, before starting the services:\n\n\
+         ```sh\n\
+         docker volume create <volume>\n\
+         docker run --rm -v <volume>:/data -v \"$PWD\":/backup alpine tar xzf /backup/<volume>.tgz -C /data\n\
+         ```\n\n\
+         Qdrant collections can instead be moved with the snapshots in the Data tab.\n",
+    );
+    if volumes.is_empty() {
+        text.push_str("\nNo container volumes were found for this profile.\n");
+    }
+    for volume in volumes {
+        text.push_str(&format!(
+            "\n## {} (container `{}`)\n\n",
+            volume.service, volume.container
+        ));
+        for name in &volume.volumes {
+            text.push_str(&format!("- `{}`\n", name));
+        }
+    }
+    text
+}
+
+/// Write an export bundle for `profile` into a new directory under `dest`.
+/// Blocking.
+pub fn export(
+    data_dir: &Path,
+    profile: &Profile,
+    secrets: Vec<String>,
+    volumes: Vec<VolumeRef>,
+    dest: &Path,
+    now: u64,
+) -> Result<PathBuf, String> {
+    let bundle = dest.join(format!("tulsbot-{}-{}", profile.name, now));
+    std::fs::create_dir_all(&bundle).map_err(|e| e.to_string())?;
+
+    let mut sections = Vec::new();
+    for (section, paths) in SECTIONS {
+        let mut found = false;
+        for rel in *paths {
+            let src = data_dir.join(rel);
+            if src.exists() {
+                copy_recursive(&src, &bundle.join(section).join(rel))
+                    .map_err(|e| format!("{}: {}", src.display(), e))?;
+                found = true;
+            }
+        }
+        if found {
+            sections.push(section.to_string());
+        }
+    }
+
+    // Data dirs are machine-specific; the import picks a fresh one
+    let mut profile = profile.clone();
+    profile.data_dir = None;
+    let manifest = Manifest {
+        format_version: FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: now,
+        profile,
+        sections,
+        secrets,
+        volumes,
+    };
+    std::fs::write(bundle.join(VOLUMES_README), volume_instructions(&manifest.volumes))
+        .map_err(|e| e.to_string())?;
+    let text = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    std::fs::write(bundle.join(MANIFEST), text).map_err(|e| e.to_string())?;
+    Ok(bundle)
+}
+
+/// Read and version-check a bundle's manifest.
+pub fn read_manifest(bundle: &Path) -> Result<Manifest, String> {
+    let text = std::fs::read_to_string(bundle.join(MANIFEST))
+        .map_err(|e| format!("Not a Tulsbot export ({}): {}", MANIFEST, e))?;
+    let manifest: Manifest = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+    if manifest.format_version > FORMAT_VERSION {
+        return Err(format!(
+            "Export format {} is newer than this app supports ({}); update Tulsbot first",
+            manifest.format_version, FORMAT_VERSION
+        ));
+    }
+    Ok(manifest)
+}
+
+/// Copy the bundle's sections (all, or only `only`) into `data_dir`. A
+/// failing section is reported and the rest still imported. Blocking.
+pub fn import(
+    bundle: &Path,
+    manifest: &Manifest,
+    data_dir: &Path,
+    only: Option<&[String]>,
+) -> ImportReport {
+    let mut report = ImportReport {
+        profile: manifest.profile.name.clone(),
+        secrets: manifest.secrets.clone(),
+        ..Default::default()
+    };
+    if manifest.app_version != env!("CARGO_PKG_VERSION") {
+        report.warnings.push(format!(
+            "Exported by Tulsbot {}, importing into {}",
+            manifest.app_version,
+            env!("CARGO_PKG_VERSION")
+        ));
+    }
+
+    for section in &manifest.sections {
+        if only.is_some_and(|only| !only.contains(section)) {
+            report.skipped.push((section.clone(), "not selected".into()));
+            continue;
+        }
+        let Some((_, paths)) = SECTIONS.iter().find(|(name, _)| name == section) else {
+            report
+                .skipped
+                .push((section.clone(), "unknown to this version".into()));
+            continue;
+        };
+        let result = paths.iter().try_for_each(|rel| {
+            let src = bundle.join(section).join(rel);
+            if src.exists() {
+                copy_recursive(&src, &data_dir.join(rel))
+            } else {
+                Ok(())
+            }
+        });
+        match result {
+            Ok(()) => report.imported.push(section.clone()),
+            Err(e) => report.skipped.push((section.clone(), e.to_string())),
+        }
+    }
+    report
+}
+
+/// Export profile `name` (default: the active one) as a bundle directory
+/// under `destination`, returning its path.
+#[instrumented]
+#[tauri::command]
+pub async fn export_profile(
+    app: AppHandle,
+    name: Option<String>,
+    destination: String,
+) -> Result<String, AppError> {
+    let profile = {
+        let state = app.state::<AppState>();
+        let store = state.profiles.lock_or_recover();
+        match name {
+            Some(name) => store
+                .get(&name)
+                .cloned()
+                .ok_or_else(|| format!("Unknown profile: {}", name))?,
+            None => store.active_profile(),
+        }
+    };
+    let data_dir = profiles::data_dir(&app, &profile)?;
+    let secrets: Vec<String> = postgres::service(&profile)
+        .map(|_| postgres::keychain_account(&profile))
+        .into_iter()
+        .collect();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| e.to_string())?
+        .as_secs();
+
+    let bundle = tauri::async_runtime::spawn_blocking(move || {
+        let volumes = profile
+            .services
+            .iter()
+            .filter_map(|s| {
+                let container = s.container.clone()?;
+                Some(VolumeRef {
+                    service: s.name.clone(),
+                    volumes: container_volumes(&container),
+                    container,
+                })
+            })
+            .collect();
+        let dest = std::path::Path::new(&destination);
+        export(&data_dir, &profile, secrets, volumes, dest, now)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    Ok(bundle.to_string_lossy().into_owned())
+}
+
+/// Import a bundle made by `export_profile`, optionally under another name
+/// and restricted to some `sections`. The profile definition is added or
+/// replaced; each section is restored independently.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn import_profile(
+    app: AppHandle,
+    path: String,
+    sections: Option<Vec<String>>,
+    rename: Option<String>,
+) -> Result<ImportReport, AppError> {
+    let bundle = PathBuf::from(path);
+    let manifest = read_manifest(&bundle)?;
+    let mut profile = manifest.profile.clone();
+    if let Some(name) = rename {
+        profile.name = name;
+    }
+    profiles::validate_name(&profile.name)?;
+    deps::validate(&profile.services)?;
+    // The bundle's data directory is a path on the machine it came from (or
+    // one picked by whoever made it); keep the local profile's, if any
+    profile.data_dir = app
+        .state::<AppState>()
+        .profiles
+        .lock_or_recover()
+        .profiles
+        .iter()
+        .find(|p| p.name == profile.name)
+        .and_then(|p| p.data_dir.clone());
+    let data_dir = profiles::data_dir(&app, &profile)?;
+
+    let mut report = tauri::async_runtime::spawn_blocking(move || {
+        import(&bundle, &manifest, &data_dir, sections.as_deref())
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    report.profile = profile.name.clone();
+
+    let is_active = {
+        let state = app.state::<AppState>();
+        let mut store = state.profiles.lock_or_recover();
+        match store.profiles.iter_mut().find(|p| p.name == profile.name) {
+            Some(existing) => *existing = profile.clone(),
+            None => store.profiles.push(profile.clone()),
+        }
+        profiles::save(&app, &store)?;
+        store.active == profile.name
+    };
+    if is_active {
+        apply_profile(&app, &profile.name)?;
+    } else {
+        refresh_tray_menu(&app);
+    }
+    Ok(report)
+}
@@ -0,0 +1,167 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::providers::{ChatRequest, ChatResponse};
+
+// ── Mock backend for frontend development (`mock-backend` feature) ─────────
+//
+// With the feature compiled in and `Settings::mock_backend` on, `api_proxy`
+// and the provider layer answer from fixture files instead of the network.
+// Fixtures are re-read on every request so they can be edited live.
+//
+// Each `*.json` file in the fixture dir holds one fixture or an array of
+// them; the first match in file-name order wins:
+//
+//     { "method": "GET", "path": "/api/health", "status": 200,
+//       "responses": [{ "ok": true }], "delay_ms": 150 }
+//     { "provider": "openai", "body_contains": "weather",
+//       "responses": ["Sunny.", "Still sunny."] }
+//
+// `responses` are served in turn and the last one repeats. String responses
+// are sent verbatim, anything else as JSON.
+
+pub const FIXTURE_DIR_ENV: &str = "TULSBOT_MOCK_FIXTURES";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Fixture {
+    pub method: Option<String>,
+    /// Matches request URLs whose path starts with this.
+    pub path: Option<String>,
+    /// Matches completions for this provider (`*` for any) instead of
+    /// proxied requests.
+    pub provider: Option<String>,
+    /// Only match when the request body (or last message) contains this.
+    pub body_contains: Option<String>,
+    pub status: u16,
+    pub responses: Vec<serde_json::Value>,
+    pub delay_ms: u64,
+}
+
+impl Default for Fixture {
+    fn default() -> Self {
+        Self {
+            method: None,
+            path: None,
+            provider: None,
+            body_contains: None,
+            status: 200,
+            responses: Vec::new(),
+            delay_ms: 0,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FixtureFile {
+    Many(Vec<Fixture>),
+    One(Fixture),
+}
+
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub body: String,
+    pub delay_ms: u64,
+}
+
+/// Fixture dir: `$TULSBOT_MOCK_FIXTURES` or `mock-fixtures` in the data dir.
+pub fn fixture_dir(data_dir: &Path) -> PathBuf {
+    std::env::var_os(FIXTURE_DIR_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| data_dir.join("mock-fixtures"))
+}
+
+/// Fixtures keyed `<file>#<index>`, in file-name order. Blocking.
+fn load(dir: &Path) -> Vec<(String, Fixture)> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e == "json"))
+        .collect();
+    files.sort();
+    let mut fixtures = Vec::new();
+    for path in files {
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| serde_json::from_str::<FixtureFile>(&text).map_err(|e| e.to_string()));
+        let list = match parsed {
+            Ok(FixtureFile::Many(list)) => list,
+            Ok(FixtureFile::One(fixture)) => vec![fixture],
+            Err(e) => {
+                eprintln!("[tulsbot] Ignoring mock fixture {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        for (i, fixture) in list.into_iter().enumerate() {
+            fixtures.push((format!("{}#{}", name, i), fixture));
+        }
+    }
+    fixtures
+}
+
+/// Tracks how far each fixture's script has been served.
+#[derive(Debug, Default)]
+pub struct MockBackend {
+    served: HashMap<String, usize>,
+}
+
+impl MockBackend {
+    fn serve(&mut self, dir: &Path, matches: impl Fn(&Fixture) -> bool) -> Option<MockResponse> {
+        let (key, fixture) = load(dir).into_iter().find(|(_, f)| matches(f))?;
+        let turn = self.served.entry(key).or_insert(0);
+        let index = (*turn).min(fixture.responses.len().saturating_sub(1));
+        *turn += 1;
+        let body = match fixture.responses.get(index) {
+            Some(serde_json::Value::String(text)) => text.clone(),
+            Some(value) => value.to_string(),
+            None => String::new(),
+        };
+        Some(MockResponse { status: fixture.status, body, delay_ms: fixture.delay_ms })
+    }
+
+    /// Canned answer for a proxied request, if a fixture matches. Blocking.
+    pub fn proxy(
+        &mut self,
+        dir: &Path,
+        method: &str,
+        url: &str,
+        body: Option<&str>,
+    ) -> Option<MockResponse> {
+        let path = reqwest::Url::parse(url).map(|u| u.path().to_string()).unwrap_or_default();
+        self.serve(dir, |f| {
+            f.provider.is_none()
+                && f.method.as_ref().is_none_or(|m| m.eq_ignore_ascii_case(method))
+                && f.path.as_ref().is_none_or(|p| path.starts_with(p.as_str()))
+                && f.body_contains
+                    .as_ref()
+                    .is_none_or(|needle| body.is_some_and(|b| b.contains(needle.as_str())))
+        })
+    }
+
+    /// Canned completion for `request`, if a fixture matches. Blocking.
+    pub fn completion(&mut self, dir: &Path, request: &ChatRequest) -> Option<MockResponse> {
+        let last = request.messages.last().map(|m| m.content.as_str()).unwrap_or_default();
+        self.serve(dir, |f| {
+            f.provider.as_ref().is_some_and(|p| *p == request.provider || p == "*")
+                && f.body_contains.as_ref().is_none_or(|needle| last.contains(needle.as_str()))
+        })
+    }
+}
+
+/// A mock completion reply: the `content` field of a JSON body, or the body.
+pub fn chat_response(response: &MockResponse) -> Result<ChatResponse, String> {
+    if response.status >= 400 {
+        return Err(format!("HTTP {}: {}", response.status, response.body));
+    }
+    let content = serde_json::from_str::<serde_json::Value>(&response.body)
+        .ok()
+        .and_then(|json| json["content"].as_str().map(String::from))
+        .unwrap_or_else(|| response.body.clone());
+    Ok(ChatResponse { content, input_tokens: None, output_tokens: None })
+}
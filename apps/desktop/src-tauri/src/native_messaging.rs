@@ -0,0 +1,396 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+use tauri::AppHandle;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader as AsyncBufReader};
+use tulsbot_macros::instrumented;
+
+use crate::users;
+use crate::error::AppError;
+use crate::notifications::{consume_action_nonce, perform_notice_action};
+use crate::share::{open_share, SharePayload};
+
+// ── Browser extension bridge (native messaging) ─────────────────────────────
+//
+// The browser launches this same binary as a native-messaging host. The host
+// relays each length-prefixed stdin message over loopback TCP to the running
+// app, which writes its port and a per-launch token to `browser-bridge.json`.
+
+pub const HOST_NAME: &str = "com.tulsbot.desktop";
+const IDENTIFIER: &str = "com.tulsbot.desktop";
+const BRIDGE_FILE: &str = "browser-bridge.json";
+/// Chrome rejects host → extension messages larger than 1 MB.
+const MAX_REPLY_BYTES: usize = 1024 * 1024;
+/// Longest message taken from the browser or over loopback; shares are text
+/// and URLs, so anything near this is not one.
+const MAX_MESSAGE_BYTES: usize = 4 * 1024 * 1024;
+
+pub const BROWSERS: &[&str] = &["chrome", "chromium", "edge", "brave", "firefox"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BridgeInfo {
+    port: u16,
+    token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeInstall {
+    pub browser: String,
+    pub manifest_path: String,
+    pub installed: bool,
+}
+
+/// Same directory Tauri resolves as `app_data_dir`, available without an app.
+fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join(IDENTIFIER))
+}
+
+/// Chrome passes the caller origin as the first argument; Firefox passes the
+/// path of our host manifest followed by the extension id.
+pub fn is_host_invocation() -> bool {
+    let Some(first) = std::env::args().nth(1) else {
+        return false;
+    };
+    first.starts_with("chrome-extension://") || first.ends_with(&format!("{}.json", HOST_NAME))
+}
+
+// ── Host side (runs inside the browser-spawned process) ─────────────────────
+
+fn read_message(input: &mut impl Read) -> Option<Value> {
+    let mut len = [0u8; 4];
+    input.read_exact(&mut len).ok()?;
+    let len = u32::from_ne_bytes(len) as usize;
+    if len > MAX_MESSAGE_BYTES {
+        return None;
+    }
+    let mut buf = vec![0u8; len];
+    input.read_exact(&mut buf).ok()?;
+    serde_json::from_slice(&buf).ok()
+}
+
+fn write_message(output: &mut impl Write, message: &Value) -> std::io::Result<()> {
+    let mut bytes = serde_json::to_vec(message)?;
+    if bytes.len() > MAX_REPLY_BYTES {
+        bytes = serde_json::to_vec(&json!({ "ok": false, "error": "Reply too large" }))?;
+    }
+    output.write_all(&(bytes.len() as u32).to_ne_bytes())?;
+    output.write_all(&bytes)?;
+    output.flush()
+}
+
+/// Send one message to the running app and wait for its reply.
+pub fn relay(message: Value) -> Result<Value, String> {
+    let path = data_dir().ok_or("No data directory")?.join(BRIDGE_FILE);
+    let text = std::fs::read_to_string(&path).map_err(|_| "Tulsbot is not running".to_string())?;
+    let info: BridgeInfo = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+
+    let mut stream = TcpStream::connect(("127.0.0.1", info.port))
+        .map_err(|_| "Tulsbot is not running".to_string())?;
+    let mut line = serde_json::to_string(&json!({ "token": info.token, "message": message }))
+        .map_err(|e| e.to_string())?;
+    line.push('\n');
+    stream.write_all(line.as_bytes()).map_err(|e| e.to_string())?;
+
+    let mut reply = String::new();
+    BufReader::new(stream)
+        .read_line(&mut reply)
+        .map_err(|e| e.to_string())?;
+    serde_json::from_str(&reply).map_err(|e| e.to_string())
+}
+
+/// What the browser may ask for: pings, and shares of text and URLs. Notice
+/// button actions and file shares only come from a second app instance, so a
+/// page can neither restart services nor have local files read in.
+fn from_browser(mut message: Value) -> Result<Value, String> {
+    match message["type"].as_str() {
+        Some("ping") => Ok(message),
+        Some("share") => {
+            if let Some(fields) = message.as_object_mut() {
+                fields.remove("files");
+                fields.insert("source".into(), "browser".into());
+            }
+            Ok(message)
+        }
+        other => Err(format!("Not allowed from the browser: {}", other.unwrap_or("none"))),
+    }
+}
+
+/// Serve the browser until it closes stdin.
+pub fn run_host() {
+    let mut stdin = std::io::stdin().lock();
+    let mut stdout = std::io::stdout().lock();
+    while let Some(message) = read_message(&mut stdin) {
+        let reply = from_browser(message)
+            .and_then(relay)
+            .unwrap_or_else(|e| json!({ "ok": false, "error": e }));
+        if write_message(&mut stdout, &reply).is_err() {
+            break;
+        }
+    }
+}
+
+// ── App side ────────────────────────────────────────────────────────────────
+
+fn random_token() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    getrandom::fill(&mut bytes).map_err(|e| format!("No OS randomness: {}", e))?;
+    Ok(hex::encode(bytes))
+}
+
+fn write_bridge_file(info: &BridgeInfo) -> Result<(), String> {
+    let dir = data_dir().ok_or("No data directory")?;
+    // The token is all that stands between other local accounts and the bridge
+    users::restrict(&dir)?;
+    let path = dir.join(BRIDGE_FILE);
+    let text = serde_json::to_string(info).map_err(|e| e.to_string())?;
+    // Created fresh and private, so the token is never readable by others
+    let _ = std::fs::remove_file(&path);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path).map_err(|e| e.to_string())?;
+    file.write_all(text.as_bytes()).map_err(|e| e.to_string())
+}
+
+/// Accept relayed messages on loopback and answer each with `handler`.
+pub async fn serve<F>(handler: F) -> Result<(), String>
+where
+    F: Fn(Value) -> Value + Send + Sync + Clone + 'static,
+{
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0))
+        .await
+        .map_err(|e| e.to_string())?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+    let token = random_token()?;
+    write_bridge_file(&BridgeInfo { port, token: token.clone() })?;
+
+    loop {
+        let Ok((stream, _)) = listener.accept().await else {
+            continue;
+        };
+        let handler = handler.clone();
+        let token = token.clone();
+        tokio::spawn(async move {
+            let (read, mut write) = stream.into_split();
+            let mut line = String::new();
+            let limit = MAX_MESSAGE_BYTES as u64 + 1;
+            let mut reader = AsyncBufReader::new(read.take(limit));
+            if reader.read_line(&mut line).await.is_err() {
+                return;
+            }
+            let reply = match serde_json::from_str::<Value>(&line) {
+                _ if line.len() > MAX_MESSAGE_BYTES => {
+                    json!({ "ok": false, "error": "Message too large" })
+                }
+                Ok(req) if req["token"].as_str() == Some(token.as_str()) => {
+                    handler(req["message"].clone())
+                }
+                Ok(_) => json!({ "ok": false, "error": "Invalid bridge token" }),
+                Err(e) => json!({ "ok": false, "error": e.to_string() }),
+            };
+            let mut out = reply.to_string();
+            out.push('\n');
+            let _ = write.write_all(out.as_bytes()).await;
+        });
+    }
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn get_browser_bridges() -> Result<Vec<BridgeInstall>, AppError> {
+    Ok(status())
+}
+
+/// Register the native-messaging host for `browser`, allowing only the
+/// companion extension with `extension_id`.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn install_browser_bridge(
+    browser: String,
+    extension_id: String,
+) -> Result<BridgeInstall, AppError> {
+    Ok(install(&browser, &extension_id)?)
+}
+
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn uninstall_browser_bridge(browser: String) -> Result<(), AppError> {
+    Ok(uninstall(&browser)?)
+}
+
+/// Handle one message relayed over the bridge (from the browser extension's
+/// host or a second app instance; the host only lets pings and text shares
+/// through). `share` messages carry a `SharePayload` and open the popover
+/// with it; `notification-action` ones need the nonce of a shown
+/// notification.
+pub fn handle_bridge_message(app: &AppHandle, message: serde_json::Value) -> serde_json::Value {
+    match message["type"].as_str() {
+        Some("ping") => serde_json::json!({ "ok": true }),
+        Some("notification-action") => {
+            let nonce = message["nonce"].as_str().unwrap_or_default();
+            if !consume_action_nonce(nonce) {
+                return serde_json::json!({ "ok": false, "error": "Unknown notification" });
+            }
+            let action = message["action"].as_str().unwrap_or_default();
+            let service = message["service"].as_str().map(str::to_string);
+            perform_notice_action(app, action, service);
+            serde_json::json!({ "ok": true })
+        }
+        Some("share") => {
+            let mut payload: SharePayload = serde_json::from_value(message).unwrap_or_default();
+            if payload.source.is_empty() {
+                payload.source = "browser".into();
+            }
+            match open_share(app, payload) {
+                Ok(()) => serde_json::json!({ "ok": true }),
+                Err(e) => serde_json::json!({ "ok": false, "error": e }),
+            }
+        }
+        other => serde_json::json!({
+            "ok": false,
+            "error": format!("Unknown message type: {}", other.unwrap_or("none")),
+        }),
+    }
+}
+
+// ── Host manifest install / uninstall ───────────────────────────────────────
+
+fn is_firefox(browser: &str) -> bool {
+    browser == "firefox"
+}
+
+#[cfg(target_os = "macos")]
+fn manifest_dir(browser: &str) -> Option<PathBuf> {
+    let base = dirs::home_dir()?.join("Library/Application Support");
+    let sub = match browser {
+        "chrome" => "Google/Chrome",
+        "chromium" => "Chromium",
+        "edge" => "Microsoft Edge",
+        "brave" => "BraveSoftware/Brave-Browser",
+        "firefox" => "Mozilla",
+        _ => return None,
+    };
+    Some(base.join(sub).join("NativeMessagingHosts"))
+}
+
+#[cfg(target_os = "windows")]
+fn manifest_dir(browser: &str) -> Option<PathBuf> {
+    // Windows locates manifests through the registry; keep the files with our data.
+    if !BROWSERS.contains(&browser) {
+        return None;
+    }
+    data_dir().map(|d| d.join("native-messaging").join(browser))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn manifest_dir(browser: &str) -> Option<PathBuf> {
+    let home = dirs::home_dir()?;
+    let config = dirs::config_dir()?;
+    Some(match browser {
+        "chrome" => config.join("google-chrome/NativeMessagingHosts"),
+        "chromium" => config.join("chromium/NativeMessagingHosts"),
+        "edge" => config.join("microsoft-edge/NativeMessagingHosts"),
+        "brave" => config.join("BraveSoftware/Brave-Browser/NativeMessagingHosts"),
+        "firefox" => home.join(".mozilla/native-messaging-hosts"),
+        _ => return None,
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn registry_key(browser: &str) -> Option<String> {
+    let vendor = match browser {
+        "chrome" => "Google\\Chrome",
+        "chromium" => "Chromium",
+        "edge" => "Microsoft\\Edge",
+        "brave" => "BraveSoftware\\Brave-Browser",
+        "firefox" => "Mozilla",
+        _ => return None,
+    };
+    Some(format!("HKCU\\Software\\{}\\NativeMessagingHosts\\{}", vendor, HOST_NAME))
+}
+
+fn manifest_path(browser: &str) -> Result<PathBuf, String> {
+    manifest_dir(browser)
+        .map(|dir| dir.join(format!("{}.json", HOST_NAME)))
+        .ok_or_else(|| format!("Unsupported browser: {}", browser))
+}
+
+/// Write the host manifest for `browser`, allowing only `extension_id`.
+pub fn install(browser: &str, extension_id: &str) -> Result<BridgeInstall, String> {
+    let path = manifest_path(browser)?;
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+
+    let mut manifest = json!({
+        "name": HOST_NAME,
+        "description": "Tulsbot browser bridge",
+        "path": exe,
+        "type": "stdio",
+    });
+    if is_firefox(browser) {
+        manifest["allowed_extensions"] = json!([extension_id]);
+    } else {
+        manifest["allowed_origins"] = json!([format!("chrome-extension://{}/", extension_id)]);
+    }
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    std::fs::write(&path, text).map_err(|e| e.to_string())?;
+
+    #[cfg(target_os = "windows")]
+    {
+        let key = registry_key(browser).ok_or("Unsupported browser")?;
+        let status = std::process::Command::new("reg")
+            .args(["add", &key, "/ve", "/t", "REG_SZ", "/d"])
+            .arg(&path)
+            .arg("/f")
+            .status()
+            .map_err(|e| e.to_string())?;
+        if !status.success() {
+            return Err(format!("Failed to register {} host in the registry", browser));
+        }
+    }
+
+    Ok(BridgeInstall {
+        browser: browser.into(),
+        manifest_path: path.display().to_string(),
+        installed: true,
+    })
+}
+
+pub fn uninstall(browser: &str) -> Result<(), String> {
+    let path = manifest_path(browser)?;
+    if path.exists() {
+        std::fs::remove_file(&path).map_err(|e| e.to_string())?;
+    }
+
+    #[cfg(target_os = "windows")]
+    if let Some(key) = registry_key(browser) {
+        let _ = std::process::Command::new("reg")
+            .args(["delete", &key, "/f"])
+            .status();
+    }
+
+    Ok(())
+}
+
+pub fn status() -> Vec<BridgeInstall> {
+    BROWSERS
+        .iter()
+        .filter_map(|browser| {
+            let path = manifest_path(browser).ok()?;
+            Some(BridgeInstall {
+                browser: browser.to_string(),
+                installed: path.exists(),
+                manifest_path: path.display().to_string(),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,218 @@
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
+use std::process::Command;
+use std::time::{Duration, Instant};
+use tulsbot_macros::instrumented;
+
+use crate::error::AppError;
+
+// ── Network diagnostics ─────────────────────────────────────────────────────
+//
+// Ping, DNS resolution and a TCP port check for the troubleshooting panel.
+// Ping shells out to the system `ping` (raw ICMP sockets need privileges);
+// resolution and port checks use the OS resolver and plain TCP connects.
+// Limits keep this a diagnostic and not a scanner: one host at a time, a
+// capped number of ports and probes, short timeouts.
+
+/// Echo requests sent per ping, at most.
+const MAX_PINGS: u32 = 10;
+/// Ports checked per call, at most.
+pub const MAX_PORTS: usize = 64;
+/// Connects in flight at once.
+const PARALLEL_CONNECTS: usize = 16;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+/// How `ping` labels round-trip times, in the locales Windows translates it to.
+const TIME_MARKERS: &[&str] = &["time=", "time<", "Zeit=", "Zeit<", "temps=", "tempo=", "tiempo="];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PingResult {
+    pub host: String,
+    pub sent: u32,
+    pub received: u32,
+    pub loss_percent: f64,
+    pub min_ms: Option<f64>,
+    pub avg_ms: Option<f64>,
+    pub max_ms: Option<f64>,
+    /// The tool's own output, for details the summary leaves out.
+    pub output: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Resolution {
+    pub host: String,
+    pub addresses: Vec<String>,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PortState {
+    Open,
+    /// Actively refused: the host is up but nothing listens.
+    Closed,
+    /// No answer within the timeout: filtered or the host is down.
+    Timeout,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortResult {
+    pub port: u16,
+    pub state: PortState,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+/// A host name or IP literal; anything that could be read as a flag or
+/// carries spaces is refused before it reaches `ping`.
+pub fn validate_host(host: &str) -> Result<&str, String> {
+    let host = host.trim();
+    let valid = !host.is_empty()
+        && host.len() <= 253
+        && !host.starts_with('-')
+        && host.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':' | '_'));
+    if !valid {
+        return Err(format!("Invalid host: {}", host));
+    }
+    Ok(host)
+}
+
+/// Round-trip times in the output of `ping`, whatever the platform's format
+/// (`time=12.3 ms`, `time<1ms`, `Zeit=4ms`…).
+fn round_trips(output: &str) -> Vec<f64> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let at = TIME_MARKERS.iter().find_map(|marker| line.find(marker))?;
+            let rest = &line[at..];
+            let value = rest.split(['=', '<']).nth(1)?;
+            let number: String =
+                value.chars().take_while(|c| c.is_ascii_digit() || *c == '.').collect();
+            number.parse().ok()
+        })
+        .collect()
+}
+
+/// Ping `host` `count` times. Blocking.
+pub fn ping(host: &str, count: u32) -> Result<PingResult, String> {
+    let host = validate_host(host)?;
+    let count = count.clamp(1, MAX_PINGS);
+    let mut command = Command::new("ping");
+    #[cfg(windows)]
+    command.args(["-n", &count.to_string(), "-w", "2000", host]);
+    #[cfg(target_os = "macos")]
+    command.args(["-c", &count.to_string(), "-W", "2000", host]);
+    #[cfg(not(any(windows, target_os = "macos")))]
+    command.args(["-c", &count.to_string(), "-W", "2", host]);
+    let out = command.output().map_err(|e| format!("ping: {}", e))?;
+    let output = String::from_utf8_lossy(&out.stdout).into_owned();
+    if !out.status.success() && output.trim().is_empty() {
+        return Err(format!("ping: {}", String::from_utf8_lossy(&out.stderr).trim()));
+    }
+    let times = round_trips(&output);
+    let received = times.len() as u32;
+    let avg = (!times.is_empty()).then(|| times.iter().sum::<f64>() / times.len() as f64);
+    Ok(PingResult {
+        host: host.to_string(),
+        sent: count,
+        received,
+        loss_percent: f64::from(count - received.min(count)) * 100.0 / f64::from(count),
+        min_ms: times.iter().copied().reduce(f64::min),
+        avg_ms: avg,
+        max_ms: times.iter().copied().reduce(f64::max),
+        output,
+    })
+}
+
+async fn lookup(host: &str) -> Result<Vec<IpAddr>, String> {
+    let mut addresses: Vec<IpAddr> = tokio::net::lookup_host((host, 0))
+        .await
+        .map_err(|e| format!("Could not resolve {}: {}", host, e))?
+        .map(|addr| addr.ip())
+        .collect();
+    addresses.dedup();
+    Ok(addresses)
+}
+
+/// Addresses `host` resolves to through the OS resolver.
+pub async fn resolve(host: &str) -> Result<Resolution, String> {
+    let host = validate_host(host)?;
+    let started = Instant::now();
+    let addresses = lookup(host).await?;
+    Ok(Resolution {
+        host: host.to_string(),
+        addresses: addresses.iter().map(IpAddr::to_string).collect(),
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+async fn check_port(addr: SocketAddr) -> PortResult {
+    let started = Instant::now();
+    let result = tokio::time::timeout(CONNECT_TIMEOUT, tokio::net::TcpStream::connect(addr)).await;
+    let (state, error) = match result {
+        Ok(Ok(_)) => (PortState::Open, None),
+        Ok(Err(e)) if e.kind() == std::io::ErrorKind::ConnectionRefused => {
+            (PortState::Closed, None)
+        }
+        Ok(Err(e)) => (PortState::Timeout, Some(e.to_string())),
+        Err(_) => (PortState::Timeout, None),
+    };
+    PortResult {
+        port: addr.port(),
+        state,
+        duration_ms: started.elapsed().as_millis() as u64,
+        error,
+    }
+}
+
+/// Try a TCP connect to each of `ports` on `host`'s first address.
+pub async fn scan_ports(host: &str, ports: &[u16]) -> Result<Vec<PortResult>, String> {
+    let mut ports = ports.to_vec();
+    ports.sort_unstable();
+    ports.dedup();
+    if ports.is_empty() {
+        return Err("No ports given".into());
+    }
+    if ports.len() > MAX_PORTS {
+        return Err(format!("At most {} ports can be checked at once", MAX_PORTS));
+    }
+    let host = validate_host(host)?;
+    let ip = *lookup(host)
+        .await?
+        .first()
+        .ok_or_else(|| format!("{} has no addresses", host))?;
+
+    let mut results = Vec::with_capacity(ports.len());
+    for chunk in ports.chunks(PARALLEL_CONNECTS) {
+        let mut set = tokio::task::JoinSet::new();
+        for port in chunk {
+            set.spawn(check_port(SocketAddr::new(ip, *port)));
+        }
+        while let Some(result) = set.join_next().await {
+            results.push(result.map_err(|e| e.to_string())?);
+        }
+    }
+    results.sort_by_key(|r| r.port);
+    Ok(results)
+}
+
+/// Ping `host` (default 4 echo requests, at most 10).
+#[instrumented]
+#[tauri::command]
+pub async fn ping_host(host: String, count: Option<u32>) -> Result<PingResult, AppError> {
+    Ok(tauri::async_runtime::spawn_blocking(move || ping(&host, count.unwrap_or(4)))
+        .await
+        .map_err(|e| e.to_string())??)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn resolve_host(host: String) -> Result<Resolution, AppError> {
+    Ok(resolve(&host).await?)
+}
+
+/// TCP connect check of up to 64 `ports` on `host`.
+#[instrumented]
+#[tauri::command]
+pub async fn port_scan(host: String, ports: Vec<u16>) -> Result<Vec<PortResult>, AppError> {
+    Ok(scan_ports(&host, &ports).await?)
+}
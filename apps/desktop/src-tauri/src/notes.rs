@@ -0,0 +1,222 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::{AppHandle, Emitter};
+use tulsbot_macros::instrumented;
+
+use crate::conversations;
+use crate::error::AppError;
+use crate::profiles::active_data_dir;
+
+// ── Notes: a scratchpad that survives restarts ──────────────────────────────
+//
+// Markdown notes jotted down from the quick-capture window or the dashboard,
+// kept in `notes.json` in the profile's data dir. A note can link to
+// conversations. Search is a case-insensitive scan over title and content;
+// a notes file stays small enough that an index wouldn't pay off.
+
+const FILE_NAME: &str = "notes.json";
+/// Characters of a derived title.
+const TITLE_CHARS: usize = 80;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Note {
+    pub id: String,
+    pub title: String,
+    /// Markdown.
+    pub content: String,
+    /// Unix seconds.
+    pub created_at: u64,
+    pub updated_at: u64,
+    /// Ids of linked conversations.
+    #[serde(default)]
+    pub conversations: Vec<String>,
+}
+
+pub fn load(dir: &Path) -> Vec<Note> {
+    std::fs::read_to_string(dir.join(FILE_NAME))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(dir: &Path, notes: &[Note]) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let text = serde_json::to_string_pretty(notes).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(FILE_NAME), text).map_err(|e| e.to_string())
+}
+
+/// Title for a note without one: its first non-empty line, without
+/// markdown heading marks.
+pub fn title_of(content: &str) -> String {
+    let line = content
+        .lines()
+        .map(|l| l.trim().trim_start_matches('#').trim())
+        .find(|l| !l.is_empty())
+        .unwrap_or("Untitled note");
+    line.chars().take(TITLE_CHARS).collect()
+}
+
+/// Notes containing every word of `query`, most hits first, then most
+/// recently updated.
+pub fn search(notes: &[Note], query: &str) -> Vec<Note> {
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    if terms.is_empty() {
+        return Vec::new();
+    }
+    let mut hits: Vec<(usize, &Note)> = notes
+        .iter()
+        .filter_map(|note| {
+            let text = format!("{}\n{}", note.title, note.content).to_lowercase();
+            let counts: Vec<usize> =
+                terms.iter().map(|t| text.matches(t.as_str()).count()).collect();
+            counts.iter().all(|&n| n > 0).then(|| (counts.iter().sum(), note))
+        })
+        .collect();
+    hits.sort_by(|(a, x), (b, y)| b.cmp(a).then(y.updated_at.cmp(&x.updated_at)));
+    hits.into_iter().map(|(_, note)| note.clone()).collect()
+}
+
+/// Load the notes, change them and save them, then emit `notes-changed`.
+pub fn update_notes<T>(
+    app: &AppHandle,
+    change: impl FnOnce(&mut Vec<Note>) -> Result<T, String>,
+) -> Result<T, String> {
+    let dir = active_data_dir(app)?;
+    let mut current = load(&dir);
+    let result = change(&mut current)?;
+    save(&dir, &current)?;
+    let _ = app.emit("notes-changed", ());
+    Ok(result)
+}
+
+/// Notes, most recently updated first; only those linked to `conversation`
+/// when given.
+#[instrumented]
+#[tauri::command]
+pub async fn list_notes(
+    app: AppHandle,
+    conversation: Option<String>,
+) -> Result<Vec<Note>, AppError> {
+    let mut notes = load(&active_data_dir(&app)?);
+    notes.retain(|n| conversation.as_ref().is_none_or(|c| n.conversations.contains(c)));
+    notes.sort_by_key(|note| std::cmp::Reverse(note.updated_at));
+    Ok(notes)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn get_note(app: AppHandle, id: String) -> Result<Note, AppError> {
+    let notes = load(&active_data_dir(&app)?);
+    let note = notes.into_iter().find(|n| n.id == id);
+    Ok(note.ok_or_else(|| format!("Unknown note: {}", id))?)
+}
+
+/// Add a note; without a `title` its first line is used. `conversation`
+/// links it right away, e.g. when jotted down from the popover.
+#[instrumented]
+#[tauri::command]
+pub async fn create_note(
+    app: AppHandle,
+    content: String,
+    title: Option<String>,
+    conversation: Option<String>,
+) -> Result<Note, AppError> {
+    if let Some(conversation) = &conversation {
+        conversations::load(&active_data_dir(&app)?, conversation)?;
+    }
+    let now = conversations::now();
+    let title = title.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    let note = Note {
+        id: conversations::new_id(),
+        title: title.unwrap_or_else(|| title_of(&content)),
+        content,
+        created_at: now,
+        updated_at: now,
+        conversations: conversation.into_iter().collect(),
+    };
+    Ok(update_notes(&app, |notes| {
+        notes.push(note.clone());
+        Ok(note)
+    })?)
+}
+
+/// Replace a note's content and/or title.
+#[instrumented]
+#[tauri::command]
+pub async fn update_note(
+    app: AppHandle,
+    id: String,
+    content: Option<String>,
+    title: Option<String>,
+) -> Result<Note, AppError> {
+    Ok(update_notes(&app, |notes| {
+        let note = notes
+            .iter_mut()
+            .find(|n| n.id == id)
+            .ok_or_else(|| format!("Unknown note: {}", id))?;
+        if let Some(content) = content {
+            note.content = content;
+        }
+        if let Some(title) = title {
+            let title = title.trim();
+            note.title = if title.is_empty() {
+                title_of(&note.content)
+            } else {
+                title.to_string()
+            };
+        }
+        note.updated_at = conversations::now();
+        Ok(note.clone())
+    })?)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn delete_note(app: AppHandle, id: String) -> Result<(), AppError> {
+    Ok(update_notes(&app, |notes| {
+        let before = notes.len();
+        notes.retain(|n| n.id != id);
+        if notes.len() == before {
+            return Err(format!("Unknown note: {}", id));
+        }
+        Ok(())
+    })?)
+}
+
+/// Notes containing every word of `query`, best matches first.
+#[instrumented]
+#[tauri::command]
+pub async fn search_notes(
+    app: AppHandle,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<Note>, AppError> {
+    let mut found = search(&load(&active_data_dir(&app)?), &query);
+    found.truncate(limit.unwrap_or(20));
+    Ok(found)
+}
+
+/// Link a note to a conversation, or unlink it when `linked` is false.
+#[instrumented]
+#[tauri::command]
+pub async fn link_note(
+    app: AppHandle,
+    id: String,
+    conversation: String,
+    linked: bool,
+) -> Result<Note, AppError> {
+    if linked {
+        conversations::load(&active_data_dir(&app)?, &conversation)?;
+    }
+    Ok(update_notes(&app, |notes| {
+        let note = notes
+            .iter_mut()
+            .find(|n| n.id == id)
+            .ok_or_else(|| format!("Unknown note: {}", id))?;
+        note.conversations.retain(|c| *c != conversation);
+        if linked {
+            note.conversations.push(conversation);
+        }
+        Ok(note.clone())
+    })?)
+}
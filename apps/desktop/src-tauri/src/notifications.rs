@@ -0,0 +1,582 @@
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tulsbot_macros::instrumented;
+
+use crate::error::AppError;
+use crate::health::HealthState;
+use crate::i18n::tr;
+use crate::locks::LockExt;
+use crate::settings::privacy_mode;
+use crate::state::AppState;
+use crate::supervisor::restart_service;
+use crate::windows::ensure_window;
+
+// ── Desktop notifications with Do Not Disturb awareness ─────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NoticeKind {
+    ServiceDown,
+    ServiceUp,
+    AllDown,
+    Info,
+    /// A critical alert rule fired.
+    Critical,
+}
+
+/// A button on a notification. `id` is one of `restart`, `ignore`, `logs`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoticeAction {
+    pub id: String,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notice {
+    pub kind: NoticeKind,
+    pub title: String,
+    pub body: String,
+    pub service: Option<String>,
+    #[serde(default)]
+    pub actions: Vec<NoticeAction>,
+}
+
+impl Notice {
+    /// Critical notices break through Do Not Disturb.
+    pub fn is_critical(&self) -> bool {
+        matches!(self.kind, NoticeKind::AllDown | NoticeKind::Critical)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotificationCenter {
+    pub dnd_active: bool,
+    /// A focus session is holding notices back.
+    #[serde(default)]
+    pub focus_active: bool,
+    pub queued: Vec<Notice>,
+    /// Services whose outage the user chose to ignore until they recover.
+    pub ignored: Vec<String>,
+}
+
+impl NotificationCenter {
+    /// Returns the notice if it should be shown now; queues it while DND or
+    /// a focus session is active unless it is critical.
+    pub fn submit(&mut self, notice: Notice, respect_dnd: bool) -> Option<Notice> {
+        if let Some(service) = &notice.service {
+            let ignored = self.ignored.contains(service);
+            if notice.kind == NoticeKind::ServiceUp {
+                self.ignored.retain(|s| s != service);
+            }
+            if ignored {
+                return None;
+            }
+        }
+        let held = (respect_dnd && self.dnd_active) || self.focus_active;
+        if held && !notice.is_critical() {
+            self.queued.push(notice);
+            None
+        } else {
+            Some(notice)
+        }
+    }
+
+    /// Record the OS DND state. When DND ends, returns what was queued so a
+    /// summary can be delivered.
+    pub fn set_dnd(&mut self, active: bool) -> Option<Vec<Notice>> {
+        let ended = self.dnd_active && !active;
+        self.dnd_active = active;
+        self.release(ended)
+    }
+
+    /// Hold notices back for a focus session; like `set_dnd`, returns what
+    /// was queued when it ends.
+    pub fn set_focus(&mut self, active: bool) -> Option<Vec<Notice>> {
+        let ended = self.focus_active && !active;
+        self.focus_active = active;
+        self.release(ended)
+    }
+
+    /// The queue, once neither DND nor a focus session holds it.
+    fn release(&mut self, ended: bool) -> Option<Vec<Notice>> {
+        let held = self.dnd_active || self.focus_active;
+        (ended && !held && !self.queued.is_empty()).then(|| std::mem::take(&mut self.queued))
+    }
+
+    pub fn ignore(&mut self, service: &str) {
+        if !self.ignored.iter().any(|s| s == service) {
+            self.ignored.push(service.to_string());
+        }
+    }
+}
+
+/// Nonces of notifications shown with URL buttons and not acted on yet. Any
+/// web page can open a `tulsbot:` URL, so only URLs carrying one of these
+/// are acted on, once.
+static ACTION_NONCES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+/// Outstanding nonces kept; the oldest notifications' buttons stop working.
+#[cfg(target_os = "windows")]
+const MAX_ACTION_NONCES: usize = 64;
+
+#[cfg(target_os = "windows")]
+fn issue_action_nonce() -> Result<String, String> {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes).map_err(|e| format!("No OS randomness: {}", e))?;
+    let nonce = hex::encode(bytes);
+    let mut nonces = ACTION_NONCES.lock_or_recover();
+    if nonces.len() >= MAX_ACTION_NONCES {
+        nonces.remove(0);
+    }
+    nonces.push(nonce.clone());
+    Ok(nonce)
+}
+
+/// Whether `nonce` belongs to a shown notification; it can't be used again.
+pub fn consume_action_nonce(nonce: &str) -> bool {
+    let mut nonces = ACTION_NONCES.lock_or_recover();
+    match nonces.iter().position(|n| n == nonce) {
+        Some(index) => {
+            // The notification's other buttons go with it
+            nonces.remove(index);
+            true
+        }
+        None => false,
+    }
+}
+
+/// `tulsbot://notification-action?action=<id>&service=<name>&nonce=<n>`,
+/// used where the OS activates buttons through a URL.
+#[cfg(target_os = "windows")]
+pub fn action_url(action: &str, service: Option<&str>, nonce: &str) -> String {
+    let mut url = reqwest::Url::parse("tulsbot://notification-action").expect("static URL");
+    url.query_pairs_mut().append_pair("action", action);
+    if let Some(service) = service {
+        url.query_pairs_mut().append_pair("service", service);
+    }
+    url.query_pairs_mut().append_pair("nonce", nonce);
+    url.to_string()
+}
+
+/// Inverse of `action_url`: `(action, service, nonce)`.
+pub fn parse_action_url(url: &str) -> Option<(String, Option<String>, String)> {
+    let url = reqwest::Url::parse(url).ok()?;
+    if url.scheme() != "tulsbot" || url.host_str() != Some("notification-action") {
+        return None;
+    }
+    let mut action = None;
+    let mut service = None;
+    let mut nonce = None;
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "action" => action = Some(value.into_owned()),
+            "service" => service = Some(value.into_owned()),
+            "nonce" => nonce = Some(value.into_owned()),
+            _ => {}
+        }
+    }
+    Some((action?, service, nonce.unwrap_or_default()))
+}
+
+/// Notices for services that changed state between two polls.
+pub fn health_transitions(
+    app: &AppHandle,
+    before: &HealthState,
+    after: &HealthState,
+) -> Vec<Notice> {
+    if after.overall == "down" && before.overall != "down" {
+        return vec![Notice {
+            kind: NoticeKind::AllDown,
+            title: tr(app, "notify-title"),
+            body: tr(app, "notify-all-down"),
+            service: None,
+            actions: Vec::new(),
+        }];
+    }
+
+    let state = app.state::<AppState>();
+    let Ok(i18n) = state.i18n.lock() else {
+        return Vec::new();
+    };
+    after
+        .services
+        .iter()
+        .filter_map(|svc| {
+            let was = before.services.iter().find(|b| b.name == svc.name)?;
+            let (kind, id) = match (was.healthy, svc.healthy) {
+                (true, false) if !svc.blocked_by.is_empty() => {
+                    (NoticeKind::ServiceDown, "notify-service-blocked")
+                }
+                (true, false) => (NoticeKind::ServiceDown, "notify-service-down"),
+                (false, true) => (NoticeKind::ServiceUp, "notify-service-up"),
+                _ => return None,
+            };
+            let actions = if kind == NoticeKind::ServiceDown {
+                ["restart", "ignore", "logs"]
+                    .iter()
+                    .map(|id| NoticeAction {
+                        id: id.to_string(),
+                        label: i18n.t(&format!("notify-action-{}", id)),
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            Some(Notice {
+                kind,
+                title: i18n.t("notify-title"),
+                body: i18n.t_args(
+                    id,
+                    &[("service", &svc.name), ("dependency", &svc.blocked_by.join(", "))],
+                ),
+                service: Some(svc.name.clone()),
+                actions,
+            })
+        })
+        .collect()
+}
+
+/// Show a notice now, or queue it while the OS is in Do Not Disturb.
+pub fn notify(app: &AppHandle, notice: Notice) {
+    let state = app.state::<AppState>();
+    let (muted, respect_dnd) = state
+        .settings
+        .lock()
+        .map(|s| (s.mute_notifications, !s.ignore_dnd))
+        .unwrap_or((false, true));
+    if muted {
+        return;
+    }
+    let mut notice = notice;
+    if privacy_mode(app) {
+        notice.body = tr(app, "notify-private-body");
+    }
+    let Some(notice) = state
+        .notifications
+        .lock()
+        .ok()
+        .and_then(|mut center| center.submit(notice, respect_dnd))
+    else {
+        return;
+    };
+
+    let _ = app.emit("notification", &notice);
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || match show(&notice) {
+        Ok(Some(action)) => perform_notice_action(&app, &action, notice.service),
+        Ok(None) => {}
+        Err(e) => eprintln!("[tulsbot] Failed to show notification: {}", e),
+    });
+}
+
+/// Dispatch a notification button: restart the service through the
+/// supervisor, ignore its outage until it recovers, or open its logs.
+pub fn perform_notice_action(app: &AppHandle, action: &str, service: Option<String>) {
+    let state = app.state::<AppState>();
+    match (action, service) {
+        ("restart", Some(service)) => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let result = restart_service(app.clone(), service.clone()).await;
+                if let Err(e) = &result {
+                    eprintln!("[tulsbot] Failed to restart {}: {}", service, e);
+                }
+            });
+        }
+        ("ignore", Some(service)) => {
+            if let Ok(mut center) = state.notifications.lock() {
+                center.ignore(&service);
+            }
+        }
+        ("logs", service) => {
+            if let Ok(window) = ensure_window(app, "main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+            let _ = app.emit_to("main", "open-logs", serde_json::json!({ "service": service }));
+        }
+        (other, _) => eprintln!("[tulsbot] Unknown notification action: {}", other),
+    }
+}
+
+/// Entry point for in-app notification buttons (macOS notifications have none).
+#[instrumented]
+#[tauri::command]
+pub async fn notification_action(
+    app: AppHandle,
+    action: String,
+    service: Option<String>,
+) -> Result<(), AppError> {
+    perform_notice_action(&app, &action, service);
+    Ok(())
+}
+
+/// Track the OS DND state; when it ends, summarise what was held back.
+pub async fn refresh_dnd(app: &AppHandle) {
+    let active = tauri::async_runtime::spawn_blocking(dnd_active)
+        .await
+        .unwrap_or(false);
+    let state = app.state::<AppState>();
+    let queued = match state.notifications.lock() {
+        Ok(mut center) => center.set_dnd(active),
+        Err(_) => return,
+    };
+    if let Some(queued) = queued {
+        notify_held(app, &queued);
+    }
+}
+
+/// Summarise the notices held back by DND or a focus session.
+pub fn notify_held(app: &AppHandle, queued: &[Notice]) {
+    let state = app.state::<AppState>();
+    let down = queued
+        .iter()
+        .filter(|n| n.kind == NoticeKind::ServiceDown)
+        .count();
+    let Ok(i18n) = state.i18n.lock() else {
+        return;
+    };
+    let body = if down > 0 {
+        i18n.t_count("notify-dnd-summary-down", down)
+    } else {
+        i18n.t_count("notify-dnd-summary-other", queued.len())
+    };
+    let summary = Notice {
+        kind: NoticeKind::Info,
+        title: i18n.t("notify-dnd-summary-title"),
+        body,
+        service: None,
+        actions: Vec::new(),
+    };
+    drop(i18n);
+    notify(app, summary);
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn get_notification_state(
+    state: State<'_, AppState>,
+) -> Result<NotificationCenter, AppError> {
+    let center = state.notifications.lock_or_recover();
+    Ok(center.clone())
+}
+
+// ── Platform: DND detection ─────────────────────────────────────────────────
+
+/// Whether the OS focus / Do Not Disturb mode is on. Blocking.
+#[cfg(target_os = "macos")]
+pub fn dnd_active() -> bool {
+    // macOS 12+: an active Focus shows up as assertion records
+    if let Some(home) = dirs::home_dir() {
+        let path = home.join("Library/DoNotDisturb/DB/Assertions.json");
+        if let Ok(text) = std::fs::read_to_string(path) {
+            if let Ok(json) = serde_json::from_str::<serde_json::Value>(&text) {
+                return json["data"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .any(|entry| {
+                        entry["storeAssertionRecords"]
+                            .as_array()
+                            .is_some_and(|records| !records.is_empty())
+                    });
+            }
+        }
+    }
+    // macOS 11 and earlier
+    Command::new("defaults")
+        .args(["-currentHost", "read", "com.apple.notificationcenterui", "doNotDisturb"])
+        .output()
+        .map(|out| String::from_utf8_lossy(&out.stdout).trim() == "1")
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "windows")]
+pub fn dnd_active() -> bool {
+    Command::new("reg")
+        .args([
+            "query",
+            "HKCU\\Software\\Microsoft\\Windows\\CurrentVersion\\Notifications\\Settings",
+            "/v",
+            "NOC_GLOBAL_SETTING_TOASTS_ENABLED",
+        ])
+        .output()
+        .map(|out| String::from_utf8_lossy(&out.stdout).trim_end().ends_with("0x0"))
+        .unwrap_or(false)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn dnd_active() -> bool {
+    Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.notifications", "show-banners"])
+        .output()
+        .map(|out| String::from_utf8_lossy(&out.stdout).trim() == "false")
+        .unwrap_or(false)
+}
+
+/// Switch the OS Do Not Disturb on or off, for focus sessions. Blocking.
+///
+/// macOS has no public switch for Focus, so this runs the Shortcuts named
+/// `Tulsbot Focus On` / `Tulsbot Focus Off`, which the user sets up with the
+/// "Set Focus" action.
+#[cfg(target_os = "macos")]
+pub fn set_dnd(on: bool) -> Result<(), String> {
+    let shortcut = if on { "Tulsbot Focus On" } else { "Tulsbot Focus Off" };
+    run_switch(Command::new("shortcuts").args(["run", shortcut]))
+}
+
+#[cfg(target_os = "windows")]
+pub fn set_dnd(on: bool) -> Result<(), String> {
+    run_switch(Command::new("reg").args([
+        "add",
+        "HKCU\\Software\\Microsoft\\Windows\\CurrentVersion\\Notifications\\Settings",
+        "/v",
+        "NOC_GLOBAL_SETTING_TOASTS_ENABLED",
+        "/t",
+        "REG_DWORD",
+        "/d",
+        if on { "0" } else { "1" },
+        "/f",
+    ]))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn set_dnd(on: bool) -> Result<(), String> {
+    let banners = if on { "false" } else { "true" };
+    run_switch(Command::new("gsettings").args([
+        "set",
+        "org.gnome.desktop.notifications",
+        "show-banners",
+        banners,
+    ]))
+}
+
+fn run_switch(command: &mut Command) -> Result<(), String> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let output = command.output().map_err(|e| format!("{}: {}", program, e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{} failed: {}", program, stderr.trim()));
+    }
+    Ok(())
+}
+
+// ── Platform: display ───────────────────────────────────────────────────────
+//
+// Buttons: Linux (notify-send --action) reports the clicked action on stdout;
+// Windows toasts activate `tulsbot://` URLs that reach us via a relaunch;
+// macOS script notifications have no buttons, so the webviews render them
+// from the `notification` event instead.
+
+/// Show a native notification. Blocking; on Linux waits for the user and
+/// returns the chosen action id.
+#[cfg(target_os = "macos")]
+pub fn show(notice: &Notice) -> Result<Option<String>, String> {
+    let status = Command::new("osascript")
+        .args([
+            "-e",
+            "on run argv",
+            "-e",
+            "display notification (item 2 of argv) with title (item 1 of argv)",
+            "-e",
+            "end run",
+            &notice.title,
+            &notice.body,
+        ])
+        .status()
+        .map_err(|e| e.to_string())?;
+    status
+        .success()
+        .then_some(None)
+        .ok_or_else(|| "osascript failed".to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Point the `tulsbot:` URL scheme at this executable so toast buttons can
+/// activate us.
+#[cfg(target_os = "windows")]
+fn register_url_scheme() {
+    let Ok(exe) = std::env::current_exe() else {
+        return;
+    };
+    let key = "HKCU\\Software\\Classes\\tulsbot";
+    let command_key = format!("{}\\shell\\open\\command", key);
+    let command = format!("\"{}\" \"%1\"", exe.display());
+    let entries: [&[&str]; 3] = [
+        &["add", key, "/ve", "/d", "URL:Tulsbot", "/f"],
+        &["add", key, "/v", "URL Protocol", "/d", "", "/f"],
+        &["add", command_key.as_str(), "/ve", "/d", command.as_str(), "/f"],
+    ];
+    for args in entries {
+        let _ = Command::new("reg").args(args).output();
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub fn show(notice: &Notice) -> Result<Option<String>, String> {
+    let nonce = if notice.actions.is_empty() { String::new() } else { issue_action_nonce()? };
+    let actions: String = notice
+        .actions
+        .iter()
+        .map(|action| {
+            format!(
+                "<action content=\"{}\" arguments=\"{}\" activationType=\"protocol\"/>",
+                xml_escape(&action.label),
+                xml_escape(&action_url(&action.id, notice.service.as_deref(), &nonce))
+            )
+        })
+        .collect();
+    if !actions.is_empty() {
+        register_url_scheme();
+    }
+    let xml = format!(
+        "<toast><visual><binding template=\"ToastGeneric\"><text>{}</text><text>{}</text></binding></visual><actions>{}</actions></toast>",
+        xml_escape(&notice.title),
+        xml_escape(&notice.body),
+        actions
+    );
+    let script = format!(
+        "[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] | Out-Null; \
+         [Windows.Data.Xml.Dom.XmlDocument, Windows.Data.Xml.Dom.XmlDocument, ContentType = WindowsRuntime] | Out-Null; \
+         $xml = New-Object Windows.Data.Xml.Dom.XmlDocument; $xml.LoadXml('{}'); \
+         [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('com.tulsbot.desktop').Show([Windows.UI.Notifications.ToastNotification]::new($xml))",
+        xml.replace('\'', "''")
+    );
+    let status = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .status()
+        .map_err(|e| e.to_string())?;
+    status
+        .success()
+        .then_some(None)
+        .ok_or_else(|| "Toast notification failed".to_string())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn show(notice: &Notice) -> Result<Option<String>, String> {
+    let mut cmd = Command::new("notify-send");
+    cmd.args(["--app-name=Tulsbot"]);
+    for action in &notice.actions {
+        cmd.arg(format!("--action={}={}", action.id, action.label));
+    }
+    if !notice.actions.is_empty() {
+        cmd.arg("--wait");
+    }
+    let output = cmd
+        .args([&notice.title, &notice.body])
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err("notify-send failed".into());
+    }
+    let chosen = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok((!chosen.is_empty()).then_some(chosen))
+}
@@ -0,0 +1,726 @@
+use ring::aead::{self, Aad, LessSafeKey, Nonce, UnboundKey};
+use ring::agreement::{self, EphemeralPrivateKey, UnparsedPublicKey, X25519};
+use ring::hkdf;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tulsbot_macros::instrumented;
+
+use crate::{keychain, lan};
+use crate::context_builder::complete_conversation;
+use crate::conversations::{
+    self, append_message, create_conversation, get_conversation, list_conversations,
+};
+use crate::error::{AppError, ErrorKind};
+use crate::locks::LockExt;
+use crate::notifications::{notify, Notice, NoticeKind};
+use crate::relay::{self, Push};
+use crate::state::AppState;
+
+// ── Companion devices ───────────────────────────────────────────────────────
+//
+// Phones running the mobile build pair with the desktop by scanning a QR
+// code and then relay chats through its stack. The QR carries the
+// desktop's address and a pairing secret; nothing else about the pairing
+// crosses the network in the clear.
+//
+// Every connection starts with one JSON line each way exchanging ephemeral
+// X25519 keys (the client names its device id, or none to pair). Both sides
+// derive a key per direction with HKDF-SHA256 over the shared secret, salted
+// with a pre-shared key: the QR's pairing secret while pairing, the device
+// secret handed out at pairing afterwards. Then come length-prefixed
+// ChaCha20-Poly1305 frames of JSON with counter nonces, so a party without
+// the pre-shared key fails on the first frame.
+
+pub const PORT: u16 = 47_310;
+const DEVICES_FILE: &str = "devices.json";
+const LINK_FILE: &str = "desktop_link.json";
+/// Keychain account of this device's secret for its desktop.
+pub const LINK_ACCOUNT: &str = "desktop-link";
+const PROTOCOL: &[u8] = b"tulsbot-companion-v1";
+pub const PAIRING_TTL_SECS: u64 = 5 * 60;
+const MAX_HELLO_BYTES: usize = 512;
+const MAX_FRAME_BYTES: usize = 4 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Device {
+    pub id: String,
+    pub name: String,
+    pub platform: Option<String>,
+    /// Unix seconds.
+    pub paired_at: u64,
+    pub last_seen: Option<u64>,
+}
+
+/// Shown as a QR code until a phone pairs or it expires.
+#[derive(Debug, Clone, Serialize)]
+pub struct PairingOffer {
+    pub uri: String,
+    pub expires_at: u64,
+}
+
+/// The desktop this device paired with, on the companion side.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DesktopLink {
+    pub address: String,
+    pub device: String,
+    /// Unix seconds.
+    pub paired_at: u64,
+}
+
+/// A scanned pairing URI: address and the secret.
+pub struct ScannedOffer {
+    pub address: String,
+    pub secret: Vec<u8>,
+}
+
+/// The desktop's half of an offered pairing. Connections that fail to pair
+/// leave it in place; only a completed pairing uses it up.
+#[derive(Clone, PartialEq, Eq)]
+pub struct PendingPairing {
+    secret: Vec<u8>,
+    pub expires_at: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Hello {
+    device: Option<String>,
+    key: String,
+}
+
+pub fn load_devices(dir: &Path) -> Vec<Device> {
+    std::fs::read_to_string(dir.join(DEVICES_FILE))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_devices(dir: &Path, devices: &[Device]) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let text = serde_json::to_string_pretty(devices).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(DEVICES_FILE), text).map_err(|e| e.to_string())
+}
+
+pub fn load_link(dir: &Path) -> Option<DesktopLink> {
+    let text = std::fs::read_to_string(dir.join(LINK_FILE)).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+pub fn save_link(dir: &Path, link: Option<&DesktopLink>) -> Result<(), String> {
+    let path = dir.join(LINK_FILE);
+    let Some(link) = link else {
+        return match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+            _ => Ok(()),
+        };
+    };
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let text = serde_json::to_string_pretty(link).map_err(|e| e.to_string())?;
+    std::fs::write(path, text).map_err(|e| e.to_string())
+}
+
+/// Parse the URI of a pairing QR code.
+pub fn scan(uri: &str) -> Result<ScannedOffer, String> {
+    let rest = uri.trim().strip_prefix("tulsbot-pair://").ok_or("Not a pairing code")?;
+    let (address, query) = rest.split_once('?').ok_or("Not a pairing code")?;
+    let param = |name: &str| {
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+            .ok_or_else(|| format!("Pairing code without '{}'", name))
+    };
+    Ok(ScannedOffer {
+        address: address.to_string(),
+        secret: hex::decode(param("secret")?).map_err(|_| "Malformed pairing secret")?,
+    })
+}
+
+/// Keychain account of a paired device's secret.
+pub fn keychain_account(device: &str) -> String {
+    format!("device:{}", device)
+}
+
+pub fn random_bytes(len: usize) -> Result<Vec<u8>, String> {
+    let mut bytes = vec![0u8; len];
+    SystemRandom::new().fill(&mut bytes).map_err(|_| "No OS randomness")?;
+    Ok(bytes)
+}
+
+fn ephemeral() -> Result<(EphemeralPrivateKey, Vec<u8>), String> {
+    let private = EphemeralPrivateKey::generate(&X25519, &SystemRandom::new())
+        .map_err(|_| "Failed to generate a key")?;
+    let public = private.compute_public_key().map_err(|_| "Failed to generate a key")?;
+    Ok((private, public.as_ref().to_vec()))
+}
+
+/// A new pairing reachable at `address`.
+pub fn offer(address: SocketAddr, now: u64) -> Result<(PendingPairing, PairingOffer), String> {
+    let secret = random_bytes(16)?;
+    let expires_at = now + PAIRING_TTL_SECS;
+    let uri = format!("tulsbot-pair://{}?secret={}", address, hex::encode(&secret));
+    Ok((PendingPairing { secret, expires_at }, PairingOffer { uri, expires_at }))
+}
+
+/// An encrypted connection with a peer.
+pub struct Channel {
+    stream: TcpStream,
+    send_key: LessSafeKey,
+    recv_key: LessSafeKey,
+    sent: u64,
+    received: u64,
+}
+
+struct KeyLen(usize);
+
+impl hkdf::KeyType for KeyLen {
+    fn len(&self) -> usize {
+        self.0
+    }
+}
+
+/// Keys from client to server and from server to client.
+fn derive_keys(
+    psk: &[u8],
+    shared: &[u8],
+    client: &[u8],
+    server: &[u8],
+) -> Result<(LessSafeKey, LessSafeKey), String> {
+    let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, psk).extract(shared);
+    let info = [PROTOCOL, client, server];
+    let mut okm = [0u8; 64];
+    prk.expand(&info, KeyLen(okm.len()))
+        .and_then(|expanded| expanded.fill(&mut okm))
+        .map_err(|_| "Key derivation failed")?;
+    let key = |bytes: &[u8]| {
+        UnboundKey::new(&aead::CHACHA20_POLY1305, bytes)
+            .map(LessSafeKey::new)
+            .map_err(|_| "Key derivation failed".to_string())
+    };
+    Ok((key(&okm[..32])?, key(&okm[32..])?))
+}
+
+fn shared_secret(private: EphemeralPrivateKey, peer: &[u8]) -> Result<Vec<u8>, String> {
+    agreement::agree_ephemeral(private, &UnparsedPublicKey::new(&X25519, peer), <[u8]>::to_vec)
+        .map_err(|_| "Key agreement failed".to_string())
+}
+
+fn nonce(counter: u64) -> Nonce {
+    let mut bytes = [0u8; aead::NONCE_LEN];
+    bytes[4..].copy_from_slice(&counter.to_be_bytes());
+    Nonce::assume_unique_for_key(bytes)
+}
+
+async fn read_line(stream: &mut TcpStream) -> Result<String, String> {
+    let mut line = Vec::new();
+    loop {
+        let byte = stream.read_u8().await.map_err(|e| e.to_string())?;
+        if byte == b'\n' {
+            return String::from_utf8(line).map_err(|e| e.to_string());
+        }
+        if line.len() == MAX_HELLO_BYTES {
+            return Err("Handshake line too long".into());
+        }
+        line.push(byte);
+    }
+}
+
+async fn write_line(stream: &mut TcpStream, value: &Value) -> Result<(), String> {
+    let mut line = value.to_string();
+    line.push('\n');
+    stream.write_all(line.as_bytes()).await.map_err(|e| e.to_string())
+}
+
+fn decode_key(text: &str) -> Result<Vec<u8>, String> {
+    let key = hex::decode(text).map_err(|_| "Malformed key")?;
+    if key.len() != 32 {
+        return Err("Malformed key".into());
+    }
+    Ok(key)
+}
+
+/// The server's view of a connection before the keys are known.
+pub struct Handshake {
+    stream: TcpStream,
+    /// The paired device connecting, or `None` for a pairing.
+    pub device: Option<String>,
+    client_key: Vec<u8>,
+}
+
+/// Read the client's opening line.
+pub async fn hello(mut stream: TcpStream) -> Result<Handshake, String> {
+    let hello: Hello = serde_json::from_str(&read_line(&mut stream).await?)
+        .map_err(|e| format!("Invalid handshake: {}", e))?;
+    let client_key = decode_key(&hello.key)?;
+    Ok(Handshake { stream, device: hello.device, client_key })
+}
+
+impl Handshake {
+    /// Finish a pairing with the offered secret.
+    pub async fn pair(self, pending: &PendingPairing) -> Result<Channel, String> {
+        self.resume(&pending.secret).await
+    }
+
+    /// Finish a connection from a paired device holding `psk`.
+    pub async fn resume(mut self, psk: &[u8]) -> Result<Channel, String> {
+        let (private, public) = ephemeral()?;
+        write_line(&mut self.stream, &json!({ "key": hex::encode(&public) })).await?;
+        let shared = shared_secret(private, &self.client_key)?;
+        let (recv_key, send_key) = derive_keys(psk, &shared, &self.client_key, &public)?;
+        Ok(Channel { stream: self.stream, send_key, recv_key, sent: 0, received: 0 })
+    }
+}
+
+/// Client side, for the mobile build or a second desktop: open a channel to
+/// a desktop. When pairing, `device` is `None` and `psk` is the secret from
+/// the QR code.
+pub async fn connect(address: &str, device: Option<&str>, psk: &[u8]) -> Result<Channel, String> {
+    let mut stream = TcpStream::connect(address)
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+    let (private, public) = ephemeral()?;
+    write_line(&mut stream, &json!({ "device": device, "key": hex::encode(&public) })).await?;
+    let reply: Value = serde_json::from_str(&read_line(&mut stream).await?)
+        .map_err(|e| format!("Invalid handshake: {}", e))?;
+    let server = decode_key(reply["key"].as_str().unwrap_or_default())?;
+    let shared = shared_secret(private, &server)?;
+    let (send_key, recv_key) = derive_keys(psk, &shared, &public, &server)?;
+    Ok(Channel { stream, send_key, recv_key, sent: 0, received: 0 })
+}
+
+impl Channel {
+    pub async fn send(&mut self, value: &Value) -> Result<(), String> {
+        let mut frame = serde_json::to_vec(value).map_err(|e| e.to_string())?;
+        self.send_key
+            .seal_in_place_append_tag(nonce(self.sent), Aad::empty(), &mut frame)
+            .map_err(|_| "Encryption failed")?;
+        self.sent += 1;
+        let len = u32::try_from(frame.len()).map_err(|_| "Message too large")?;
+        self.stream.write_all(&len.to_be_bytes()).await.map_err(|e| e.to_string())?;
+        self.stream.write_all(&frame).await.map_err(|e| e.to_string())
+    }
+
+    /// The next message, or `None` once the peer closed the connection.
+    pub async fn recv(&mut self) -> Result<Option<Value>, String> {
+        let len = match self.stream.read_u32().await {
+            Ok(len) => len as usize,
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.to_string()),
+        };
+        if len > MAX_FRAME_BYTES {
+            return Err("Message too large".into());
+        }
+        let mut frame = vec![0u8; len];
+        self.stream.read_exact(&mut frame).await.map_err(|e| e.to_string())?;
+        let plain = self
+            .recv_key
+            .open_in_place(nonce(self.received), Aad::empty(), &mut frame)
+            .map_err(|_| "Decryption failed: wrong key or tampered message")?;
+        self.received += 1;
+        serde_json::from_slice(plain).map(Some).map_err(|e| e.to_string())
+    }
+}
+
+/// Devices and the desktop link live with the app, not a profile.
+pub fn app_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path().app_data_dir().map_err(|e| e.to_string())
+}
+
+pub fn update_devices(
+    app: &AppHandle,
+    change: impl FnOnce(&mut Vec<Device>) -> Result<(), String>,
+) -> Result<Vec<Device>, String> {
+    let dir = app_data_dir(app)?;
+    let mut devices = load_devices(&dir);
+    change(&mut devices)?;
+    save_devices(&dir, &devices)?;
+    let _ = app.emit("devices-changed", &devices);
+    Ok(devices)
+}
+
+/// Where companion connections are accepted: the LAN while LAN access is
+/// on, otherwise only this machine.
+pub fn companion_address(app: &AppHandle) -> IpAddr {
+    let state = app.state::<AppState>();
+    let exposure = state.lan.lock_or_recover();
+    match exposure.as_ref() {
+        Some(exposure) => exposure.status.address.unwrap_or(Ipv4Addr::UNSPECIFIED.into()),
+        None => Ipv4Addr::LOCALHOST.into(),
+    }
+}
+
+/// Accept companion connections. Started by the first pairing, or at launch
+/// when devices are paired; runs until quit, moving to the right address
+/// when LAN access is switched.
+pub fn start_companion_listener(app: &AppHandle) {
+    let address = companion_address(app);
+    let state = app.state::<AppState>();
+    let mut listener = state.companion_listener.lock_or_recover();
+    if let Some((bound, task)) = listener.as_ref() {
+        if *bound == address && !task.inner().is_finished() {
+            return;
+        }
+        task.abort();
+    }
+    let app = app.clone();
+    let task = tauri::async_runtime::spawn(async move {
+        let listener = match tokio::net::TcpListener::bind((address, PORT)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                eprintln!("[tulsbot] Companion port {} unavailable: {}", PORT, e);
+                return;
+            }
+        };
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    eprintln!("[tulsbot] Companion accept failed: {}", e);
+                    let backoff = std::time::Duration::from_millis(lan::ACCEPT_BACKOFF_MS);
+                    tokio::time::sleep(backoff).await;
+                    continue;
+                }
+            };
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = serve_companion(&app, stream).await {
+                    eprintln!("[tulsbot] Companion connection from {}: {}", peer, e);
+                }
+            });
+        }
+    });
+    *listener = Some((address, task));
+}
+
+/// Move a running companion listener after LAN access was switched.
+pub fn rebind_companion_listener(app: &AppHandle) {
+    let running = app.state::<AppState>().companion_listener.lock_or_recover().is_some();
+    if running {
+        start_companion_listener(app);
+    }
+}
+
+/// Pair or resume, then answer requests until the device disconnects or
+/// is unpaired.
+pub async fn serve_companion(app: &AppHandle, stream: tokio::net::TcpStream) -> Result<(), String> {
+    let handshake = hello(stream).await?;
+    let (mut channel, device) = match handshake.device.clone() {
+        None => {
+            let pending = app.state::<AppState>().pairing.lock_or_recover().clone();
+            let pending = pending
+                .filter(|p| p.expires_at > conversations::now())
+                .ok_or("No pairing in progress")?;
+            let mut channel = handshake.pair(&pending).await?;
+            let device = accept_pairing(app, &mut channel).await?;
+            // Used up only now, so a connection without the secret can't
+            // cancel the offer
+            let state = app.state::<AppState>();
+            let mut offered = state.pairing.lock_or_recover();
+            if offered.as_ref() == Some(&pending) {
+                *offered = None;
+            }
+            (channel, device)
+        }
+        Some(id) => {
+            let now = conversations::now();
+            update_devices(app, |devices| {
+                let device = devices
+                    .iter_mut()
+                    .find(|d| d.id == id)
+                    .ok_or_else(|| format!("Unknown device: {}", id))?;
+                device.last_seen = Some(now);
+                Ok(())
+            })?;
+            let account = keychain_account(&id);
+            let secret = tauri::async_runtime::spawn_blocking(move || keychain::get(&account))
+                .await
+                .map_err(|e| e.to_string())??
+                .ok_or("No secret saved for this device")?;
+            let secret = hex::decode(secret).map_err(|e| e.to_string())?;
+            (handshake.resume(&secret).await?, id)
+        }
+    };
+    while let Some(request) = channel.recv().await? {
+        let dir = app_data_dir(app)?;
+        if !load_devices(&dir).iter().any(|d| d.id == device) {
+            return Err("Device was unpaired".into());
+        }
+        if request["type"] == "subscribe" {
+            channel.send(&serde_json::json!({ "id": request["id"], "ok": true })).await?;
+            return push_to_companion(app, &mut channel, &device).await;
+        }
+        let reply = match companion_request(app, &request).await {
+            Ok(result) => serde_json::json!({ "id": request["id"], "ok": true, "result": result }),
+            Err(e) => serde_json::json!({ "id": request["id"], "ok": false, "error": e }),
+        };
+        channel.send(&reply).await?;
+    }
+    Ok(())
+}
+
+/// Forward relayed notices to a subscribed device until it disconnects, is
+/// unpaired or subscribes again on another connection.
+pub async fn push_to_companion(
+    app: &AppHandle,
+    channel: &mut Channel,
+    device: &str,
+) -> Result<(), String> {
+    let mut pushes = app.state::<AppState>().relay.lock_or_recover().subscribe(device);
+    let keepalive = std::time::Duration::from_secs(relay::KEEPALIVE_SECS);
+    loop {
+        let message = match tokio::time::timeout(keepalive, pushes.recv()).await {
+            Ok(Some(push)) => serde_json::json!({ "type": "push", "push": push }),
+            Ok(None) => return Ok(()),
+            Err(_) => serde_json::json!({ "type": "ping" }),
+        };
+        channel.send(&message).await?;
+    }
+}
+
+/// Register the device asking to pair and hand it its secret.
+pub async fn accept_pairing(app: &AppHandle, channel: &mut Channel) -> Result<String, String> {
+    let request = channel.recv().await?.ok_or("Connection closed while pairing")?;
+    if request["type"] != "pair" {
+        return Err("Expected a pairing request".into());
+    }
+    let now = conversations::now();
+    let name: String = request["name"].as_str().unwrap_or("Phone").chars().take(64).collect();
+    let device = Device {
+        id: uuid::Uuid::new_v4().to_string(),
+        name,
+        platform: request["platform"].as_str().map(String::from),
+        paired_at: now,
+        last_seen: Some(now),
+    };
+    let secret = hex::encode(random_bytes(32)?);
+    let (account, stored) = (keychain_account(&device.id), secret.clone());
+    tauri::async_runtime::spawn_blocking(move || keychain::set(&account, &stored))
+        .await
+        .map_err(|e| e.to_string())??;
+    let added = device.clone();
+    update_devices(app, |devices| {
+        devices.push(added);
+        Ok(())
+    })?;
+    let reply = serde_json::json!({ "type": "paired", "device": device.id, "secret": secret });
+    channel.send(&reply).await?;
+    eprintln!("[tulsbot] Paired companion device '{}'", device.name);
+    let _ = app.emit("device-paired", &device);
+    Ok(device.id)
+}
+
+/// What a companion device may ask of the stack.
+pub async fn companion_request(
+    app: &AppHandle,
+    request: &serde_json::Value,
+) -> Result<serde_json::Value, AppError> {
+    let text = |key: &str| {
+        request[key].as_str().map(String::from).ok_or_else(|| {
+            AppError::new(ErrorKind::InvalidInput, format!("Missing '{}'", key))
+        })
+    };
+    let value = match request["type"].as_str().unwrap_or_default() {
+        "list_conversations" => {
+            serde_json::to_value(list_conversations(app.clone(), None, None).await?)
+        }
+        "get_conversation" => {
+            serde_json::to_value(get_conversation(app.clone(), text("conversation")?).await?)
+        }
+        "create_conversation" => {
+            let title = request["title"].as_str().map(String::from);
+            serde_json::to_value(create_conversation(app.clone(), title, None).await?)
+        }
+        "send" => {
+            let conversation = text("conversation")?;
+            let content = text("content")?;
+            let (app, state) = (app.clone(), app.state::<AppState>());
+            append_message(
+                app.clone(),
+                state.clone(),
+                conversation.clone(),
+                "user".into(),
+                content,
+                None,
+                None,
+                None,
+            )
+            .await?;
+            serde_json::to_value(complete_conversation(app, state, conversation).await?)
+        }
+        other => {
+            let message = format!("Unknown companion request: {}", other);
+            return Err(AppError::new(ErrorKind::InvalidInput, message));
+        }
+    };
+    Ok(value.map_err(|e| e.to_string())?)
+}
+
+/// Offer a pairing, shown as a QR code of the returned URI, for five
+/// minutes or until a device takes it. Replaces an earlier offer. Needs LAN
+/// access on, as the phone connects over the LAN.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn pair_device(app: AppHandle) -> Result<PairingOffer, AppError> {
+    if app.state::<AppState>().lan.lock_or_recover().is_none() {
+        return Err(AppError::new(ErrorKind::Conflict, "Turn on LAN access to pair a device"));
+    }
+    let address = std::net::SocketAddr::new(companion_address(&app), PORT);
+    let (pending, offer) = offer(address, conversations::now())?;
+    *app.state::<AppState>().pairing.lock_or_recover() = Some(pending);
+    start_companion_listener(&app);
+    Ok(offer)
+}
+
+/// Forget a device and its secret; its open connection closes on its next
+/// request.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn unpair_device(app: AppHandle, id: String) -> Result<(), AppError> {
+    update_devices(&app, |devices| {
+        let before = devices.len();
+        devices.retain(|d| d.id != id);
+        if devices.len() == before {
+            return Err(format!("Unknown device: {}", id));
+        }
+        Ok(())
+    })?;
+    app.state::<AppState>().relay.lock_or_recover().forget(&id);
+    let account = keychain_account(&id);
+    Ok(tauri::async_runtime::spawn_blocking(move || keychain::delete(&account))
+        .await
+        .map_err(|e| e.to_string())??)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn list_devices(app: AppHandle) -> Result<Vec<Device>, AppError> {
+    Ok(load_devices(&app_data_dir(&app)?))
+}
+
+/// Companion side: pair with the desktop whose QR code read as `uri`.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn join_desktop(
+    app: AppHandle,
+    uri: String,
+    name: Option<String>,
+) -> Result<DesktopLink, AppError> {
+    let offer = scan(&uri).map_err(|e| AppError::new(ErrorKind::InvalidInput, e))?;
+    let mut channel =
+        connect(&offer.address, None, &offer.secret).await?;
+    let name = name.or_else(lan::local_host_name).unwrap_or_else(|| "Companion".into());
+    let request = serde_json::json!({
+        "type": "pair",
+        "name": name,
+        "platform": std::env::consts::OS,
+    });
+    channel.send(&request).await?;
+    let reply = channel.recv().await?.ok_or("The desktop closed the connection")?;
+    let (Some(device), Some(secret)) = (reply["device"].as_str(), reply["secret"].as_str()) else {
+        return Err(AppError::new(ErrorKind::Upstream, "The desktop refused to pair"));
+    };
+    let link = DesktopLink {
+        address: offer.address,
+        device: device.to_string(),
+        paired_at: conversations::now(),
+    };
+    let secret = secret.to_string();
+    tauri::async_runtime::spawn_blocking(move || keychain::set(LINK_ACCOUNT, &secret))
+        .await
+        .map_err(|e| e.to_string())??;
+    save_link(&app_data_dir(&app)?, Some(&link))?;
+    follow_desktop(&app);
+    Ok(link)
+}
+
+/// Open a channel to the paired desktop with the secret from the keychain.
+pub async fn connect_desktop(link: &DesktopLink) -> Result<Channel, String> {
+    let secret = tauri::async_runtime::spawn_blocking(|| keychain::get(LINK_ACCOUNT))
+        .await
+        .map_err(|e| e.to_string())??
+        .ok_or("No secret saved for the desktop")?;
+    let secret = hex::decode(secret).map_err(|e| e.to_string())?;
+    connect(&link.address, Some(&link.device), &secret).await
+}
+
+/// Companion side: send one request to the paired desktop and return its
+/// result. Requests are `list_conversations`, `get_conversation`,
+/// `create_conversation` and `send` (a user message, answered by the
+/// conversation's provider).
+#[instrumented]
+#[tauri::command]
+pub async fn relay_to_desktop(
+    app: AppHandle,
+    request: serde_json::Value,
+) -> Result<serde_json::Value, AppError> {
+    let link = load_link(&app_data_dir(&app)?)
+        .ok_or_else(|| AppError::new(ErrorKind::NotFound, "Not paired with a desktop"))?;
+    let mut channel = connect_desktop(&link).await?;
+    channel.send(&request).await?;
+    let reply = channel.recv().await?.ok_or("The desktop closed the connection")?;
+    if reply["ok"] == true {
+        return Ok(reply["result"].clone());
+    }
+    let message = reply["error"]["message"].as_str().unwrap_or("The desktop refused the request");
+    Err(AppError::new(ErrorKind::Upstream, message))
+}
+
+/// Companion side: forget the paired desktop.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn leave_desktop(app: AppHandle) -> Result<(), AppError> {
+    save_link(&app_data_dir(&app)?, None)?;
+    if let Some(task) = app.state::<AppState>().desktop_follow.lock_or_recover().take() {
+        task.abort();
+    }
+    Ok(tauri::async_runtime::spawn_blocking(|| keychain::delete(LINK_ACCOUNT))
+        .await
+        .map_err(|e| e.to_string())??)
+}
+
+/// Companion side: stay subscribed to the paired desktop's pushes and show
+/// them as notifications, reconnecting while the link lasts.
+pub fn follow_desktop(app: &AppHandle) {
+    let handle = app.clone();
+    let task = tauri::async_runtime::spawn(async move {
+        while let Some(link) = app_data_dir(&handle).ok().and_then(|dir| load_link(&dir)) {
+            if let Err(e) = receive_pushes(&handle, &link).await {
+                eprintln!("[tulsbot] Desktop subscription ended: {}", e);
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+        }
+    });
+    if let Some(previous) = app.state::<AppState>().desktop_follow.lock_or_recover().replace(task) {
+        previous.abort();
+    }
+}
+
+pub async fn receive_pushes(app: &AppHandle, link: &DesktopLink) -> Result<(), String> {
+    let mut channel = connect_desktop(link).await?;
+    channel.send(&serde_json::json!({ "type": "subscribe" })).await?;
+    // Pings arrive every keepalive interval, so a long silence means the
+    // desktop is gone
+    let silence = std::time::Duration::from_secs(relay::KEEPALIVE_SECS * 3);
+    loop {
+        let message = tokio::time::timeout(silence, channel.recv())
+            .await
+            .map_err(|_| "The desktop stopped answering")??
+            .ok_or("The desktop closed the connection")?;
+        if message["type"] != "push" {
+            continue;
+        }
+        let Ok(push) = serde_json::from_value::<Push>(message["push"].clone()) else {
+            continue;
+        };
+        let _ = app.emit("desktop-push", &push);
+        let Push { title, body, .. } = push;
+        notify(
+            app,
+            Notice { kind: NoticeKind::Info, title, body, service: None, actions: Vec::new() },
+        );
+    }
+}
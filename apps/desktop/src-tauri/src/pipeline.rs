@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+// ── Outbound content pipeline ───────────────────────────────────────────────
+//
+// Large pasted text and files are condensed on this machine before they go to
+// a remote provider: the text is split into chunks and each chunk is reduced
+// to its most representative sentences (extractive, no model needed). This
+// bounds token cost and keeps most of the raw content local.
+
+/// Target size of one chunk, in characters.
+const CHUNK_CHARS: usize = 4000;
+/// Summary size used when the settings don't give one.
+pub const DEFAULT_TARGET_CHARS: usize = 4000;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct SummarizeConfig {
+    /// Summarize text longer than this many characters; `None` disables.
+    pub over_chars: Option<usize>,
+    /// Approximate length of the summary.
+    pub target_chars: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Preprocessed {
+    pub text: String,
+    pub summarized: bool,
+    pub original_chars: usize,
+    pub chunks: usize,
+}
+
+/// Apply the configured preprocessing to `text`.
+pub fn preprocess(text: &str, config: &SummarizeConfig) -> Preprocessed {
+    let original_chars = text.chars().count();
+    let target = config.target_chars.unwrap_or(DEFAULT_TARGET_CHARS).max(200);
+    match config.over_chars {
+        Some(limit) if original_chars > limit && original_chars > target => {
+            let chunks = chunk(text);
+            let summary = summarize(&chunks, target);
+            Preprocessed {
+                text: format!(
+                    "[Summarized locally from {} characters]\n\n{}",
+                    original_chars, summary
+                ),
+                summarized: true,
+                original_chars,
+                chunks: chunks.len(),
+            }
+        }
+        _ => Preprocessed {
+            text: text.to_string(),
+            summarized: false,
+            original_chars,
+            chunks: 1,
+        },
+    }
+}
+
+/// Split at paragraph boundaries into chunks of about `CHUNK_CHARS`.
+fn chunk(text: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for paragraph in text.split("\n\n") {
+        if !current.is_empty() && current.len() + paragraph.len() > CHUNK_CHARS {
+            chunks.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push_str("\n\n");
+        }
+        current.push_str(paragraph);
+    }
+    if !current.trim().is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+fn sentences(text: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let boundary = c == '\n'
+            || (matches!(c, '.' | '!' | '?')
+                && chars.peek().is_some_and(|(_, next)| next.is_whitespace()));
+        if boundary {
+            let end = i + c.len_utf8();
+            let sentence = text[start..end].trim();
+            if !sentence.is_empty() {
+                out.push(sentence);
+            }
+            start = end;
+        }
+    }
+    let rest = text[start..].trim();
+    if !rest.is_empty() {
+        out.push(rest);
+    }
+    out
+}
+
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() > 3)
+        .map(str::to_lowercase)
+}
+
+/// Keep the highest-scoring sentences of every chunk, in original order,
+/// splitting the budget across chunks by their length.
+fn summarize(chunks: &[String], target: usize) -> String {
+    let mut frequency: HashMap<String, usize> = HashMap::new();
+    for word in chunks.iter().flat_map(|c| words(c)) {
+        *frequency.entry(word).or_default() += 1;
+    }
+    let total: usize = chunks.iter().map(String::len).sum::<usize>().max(1);
+
+    chunks
+        .iter()
+        .map(|chunk| {
+            let budget = target * chunk.len() / total;
+            let sentences = sentences(chunk);
+            let mut ranked: Vec<(usize, f64)> = sentences
+                .iter()
+                .enumerate()
+                .map(|(i, sentence)| {
+                    let unique: HashSet<String> = words(sentence).collect();
+                    let score: usize = unique.iter().map(|w| frequency[w]).sum();
+                    (i, score as f64 / (unique.len().max(1) as f64).sqrt())
+                })
+                .collect();
+            ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+            let mut keep = Vec::new();
+            let mut used = 0;
+            for (i, _) in ranked {
+                if used > 0 && used + sentences[i].len() > budget {
+                    continue;
+                }
+                used += sentences[i].len() + 1;
+                keep.push(i);
+            }
+            keep.sort_unstable();
+            keep.iter()
+                .map(|&i| sentences[i])
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
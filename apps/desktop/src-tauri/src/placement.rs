@@ -0,0 +1,155 @@
+use serde::Serialize;
+use tauri::WebviewWindow;
+
+use crate::dock::Rect;
+
+// ── Window placement per display server ─────────────────────────────────────
+//
+// macOS, Windows and X11 (XWayland included) put a window where it is told.
+// Wayland leaves toplevel placement to the compositor, and most ignore
+// `set_position` or apply it wrongly, which strands the popover in a screen
+// corner. Compositors with wlr-layer-shell (Sway, Hyprland, KDE, …) let a
+// layer surface pick its spot through margins from the output's edges, so
+// with the `layer-shell` feature the popover becomes one. Elsewhere on
+// Wayland (GNOME) only the size is set and the compositor places it.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Session {
+    Wayland,
+    X11,
+    /// macOS and Windows.
+    Native,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Strategy {
+    /// `set_position` works.
+    Absolute,
+    /// A layer surface placed with margins.
+    LayerShell,
+    /// The compositor places the window; only its size is set.
+    Compositor,
+}
+
+/// The display server our windows talk to. GTK picks Wayland when it is
+/// available unless `GDK_BACKEND` says otherwise.
+pub fn session() -> Session {
+    if cfg!(any(target_os = "macos", target_os = "windows")) {
+        return Session::Native;
+    }
+    let backend = std::env::var("GDK_BACKEND").unwrap_or_default();
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some()
+        || std::env::var("XDG_SESSION_TYPE").is_ok_and(|t| t == "wayland");
+    if wayland && !backend.starts_with("x11") {
+        Session::Wayland
+    } else {
+        Session::X11
+    }
+}
+
+/// How to place a window; `layer_surface` says whether it was made one.
+pub fn strategy(layer_surface: bool) -> Strategy {
+    match session() {
+        Session::Wayland if layer_surface => Strategy::LayerShell,
+        Session::Wayland => Strategy::Compositor,
+        Session::X11 | Session::Native => Strategy::Absolute,
+    }
+}
+
+/// Turn a freshly created, hidden `window` into a layer surface when the
+/// session is Wayland and the compositor supports it. Returns whether it is
+/// one now.
+pub fn init_layer_surface(window: &WebviewWindow) -> bool {
+    if session() != Session::Wayland {
+        return false;
+    }
+    layer::init(window)
+}
+
+/// Move a layer surface to `frame` on the output covering `output`, all in
+/// physical pixels at `scale`.
+pub fn place_layer_surface(window: &WebviewWindow, frame: Rect, output: Rect, scale: f64) {
+    layer::place(window, frame, output, scale);
+}
+
+#[cfg(all(target_os = "linux", feature = "layer-shell"))]
+mod layer {
+    use gtk::prelude::*;
+    use gtk_layer_shell::{Edge, KeyboardMode, Layer, LayerShell};
+    use std::sync::mpsc;
+    use std::time::Duration;
+    use tauri::WebviewWindow;
+
+    use super::Rect;
+
+    /// Longest wait for the main thread to set the surface up.
+    const INIT_TIMEOUT: Duration = Duration::from_secs(2);
+
+    pub fn init(window: &WebviewWindow) -> bool {
+        if !gtk_layer_shell::is_supported() {
+            return false;
+        }
+        let target = window.clone();
+        let (done, outcome) = mpsc::channel();
+        let result = window.run_on_main_thread(move || {
+            let Ok(gtk) = target.gtk_window() else {
+                let _ = done.send(false);
+                return;
+            };
+            if !gtk.is_layer_window() {
+                // Layer surfaces have to be set up before GTK realizes them
+                if gtk.is_realized() {
+                    gtk.unrealize();
+                }
+                gtk.init_layer_shell();
+                gtk.set_namespace("tulsbot-popover");
+                gtk.set_layer(Layer::Top);
+                gtk.set_keyboard_mode(KeyboardMode::OnDemand);
+                gtk.set_anchor(Edge::Top, true);
+                gtk.set_anchor(Edge::Left, true);
+            }
+            let _ = done.send(gtk.is_layer_window());
+        });
+        if let Err(e) = result {
+            eprintln!("[tulsbot] Failed to make a layer surface: {}", e);
+            return false;
+        }
+        // Run in place when called on the main thread, so this doesn't wait
+        outcome.recv_timeout(INIT_TIMEOUT).unwrap_or_else(|_| {
+            eprintln!("[tulsbot] Timed out making a layer surface");
+            false
+        })
+    }
+
+    pub fn place(window: &WebviewWindow, frame: Rect, output: Rect, scale: f64) {
+        // Margins are logical pixels from the anchored edges of the output
+        let left = (f64::from(frame.x - output.x) / scale).round() as i32;
+        let top = (f64::from(frame.y - output.y) / scale).round() as i32;
+        let target = window.clone();
+        let result = window.run_on_main_thread(move || {
+            if let Ok(gtk) = target.gtk_window() {
+                gtk.set_layer_shell_margin(Edge::Left, left.max(0));
+                gtk.set_layer_shell_margin(Edge::Top, top.max(0));
+            }
+        });
+        if let Err(e) = result {
+            eprintln!("[tulsbot] Failed to move layer surface: {}", e);
+        }
+    }
+}
+
+/// Built without layer-shell support, or not on Linux.
+#[cfg(not(all(target_os = "linux", feature = "layer-shell")))]
+mod layer {
+    use tauri::WebviewWindow;
+
+    use super::Rect;
+
+    pub fn init(_window: &WebviewWindow) -> bool {
+        false
+    }
+
+    pub fn place(_window: &WebviewWindow, _frame: Rect, _output: Rect, _scale: f64) {}
+}
@@ -0,0 +1,300 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio_postgres::{Client, NoTls};
+use tulsbot_macros::instrumented;
+
+use crate::keychain;
+use crate::error::{AppError, ErrorKind};
+use crate::locks::LockExt;
+use crate::profiles::{Profile, ServiceDef};
+use crate::state::AppState;
+
+// ── PostgreSQL maintenance (VACUUM / ANALYZE, connections, table sizes) ─────
+
+/// Login for the profile's database, kept in the OS keychain as JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Credentials {
+    pub user: String,
+    pub password: String,
+    pub database: String,
+}
+
+/// Keychain account holding the credentials for `profile`.
+pub fn keychain_account(profile: &Profile) -> String {
+    format!("postgres:{}", profile.name)
+}
+
+/// The profile's PostgreSQL service, by name or by the default port.
+pub fn service(profile: &Profile) -> Option<&ServiceDef> {
+    profile
+        .services
+        .iter()
+        .find(|s| s.name.eq_ignore_ascii_case("postgresql"))
+        .or_else(|| profile.services.iter().find(|s| s.port == 5432))
+}
+
+pub async fn connect(port: u16, credentials: &Credentials) -> Result<Client, String> {
+    let mut config = tokio_postgres::Config::new();
+    config
+        .host("127.0.0.1")
+        .port(port)
+        .user(&credentials.user)
+        .password(&credentials.password)
+        .dbname(&credentials.database)
+        .application_name("tulsbot-desktop")
+        .connect_timeout(std::time::Duration::from_secs(5));
+    let (client, connection) = config.connect(NoTls).await.map_err(|e| e.to_string())?;
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            eprintln!("[tulsbot] PostgreSQL connection closed: {}", e);
+        }
+    });
+    Ok(client)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveConnection {
+    pub pid: i32,
+    pub user: Option<String>,
+    pub database: Option<String>,
+    pub client_addr: Option<String>,
+    pub application: Option<String>,
+    pub state: Option<String>,
+    pub query: Option<String>,
+    /// Seconds since the backend started.
+    pub age_secs: Option<f64>,
+}
+
+/// Client backends other than our own.
+pub async fn connections(client: &Client) -> Result<Vec<ActiveConnection>, String> {
+    let rows = client
+        .query(
+            "SELECT pid, usename::text, datname::text, client_addr::text, application_name, \
+                    state, query, EXTRACT(EPOCH FROM now() - backend_start)::float8 \
+             FROM pg_stat_activity \
+             WHERE backend_type = 'client backend' AND pid <> pg_backend_pid() \
+             ORDER BY backend_start",
+            &[],
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(rows
+        .iter()
+        .map(|row| ActiveConnection {
+            pid: row.get(0),
+            user: row.get(1),
+            database: row.get(2),
+            client_addr: row.get(3),
+            application: row.get(4),
+            state: row.get(5),
+            query: row.get(6),
+            age_secs: row.get(7),
+        })
+        .collect())
+}
+
+/// Terminate a client backend. Only pids listed by `connections` are
+/// accepted, so server processes and our own session can't be killed.
+pub async fn terminate(client: &Client, pid: i32) -> Result<bool, String> {
+    if !connections(client).await?.iter().any(|c| c.pid == pid) {
+        return Err(format!("No client connection with pid {}", pid));
+    }
+    let row = client
+        .query_one("SELECT pg_terminate_backend($1)", &[&pid])
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(row.get(0))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableSize {
+    pub schema: String,
+    pub name: String,
+    pub total_bytes: i64,
+    pub table_bytes: i64,
+    pub index_bytes: i64,
+    pub rows_estimate: i64,
+}
+
+impl TableSize {
+    pub fn qualified_name(&self) -> String {
+        format!("{}.{}", quote_ident(&self.schema), quote_ident(&self.name))
+    }
+}
+
+/// User tables, largest first.
+pub async fn table_sizes(client: &Client) -> Result<Vec<TableSize>, String> {
+    let rows = client
+        .query(
+            "SELECT schemaname::text, relname::text, pg_total_relation_size(relid), \
+                    pg_relation_size(relid), pg_indexes_size(relid), n_live_tup \
+             FROM pg_stat_user_tables \
+             ORDER BY 3 DESC",
+            &[],
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(rows
+        .iter()
+        .map(|row| TableSize {
+            schema: row.get(0),
+            name: row.get(1),
+            total_bytes: row.get(2),
+            table_bytes: row.get(3),
+            index_bytes: row.get(4),
+            rows_estimate: row.get(5),
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Maintenance {
+    /// `VACUUM (ANALYZE)`
+    Vacuum,
+    Analyze,
+}
+
+/// Run one maintenance statement on `table` (already quoted).
+pub async fn maintain(client: &Client, op: Maintenance, table: &str) -> Result<(), String> {
+    let statement = match op {
+        Maintenance::Vacuum => format!("VACUUM (ANALYZE) {}", table),
+        Maintenance::Analyze => format!("ANALYZE {}", table),
+    };
+    client
+        .batch_execute(&statement)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+/// Active profile, its PostgreSQL port and keychain account.
+pub fn postgres_target(app: &AppHandle) -> Result<(u16, String), String> {
+    let state = app.state::<AppState>();
+    let profile = state.profiles.lock_or_recover().active_profile();
+    let port = service(&profile)
+        .map(|s| s.port)
+        .ok_or_else(|| format!("Profile '{}' has no PostgreSQL service", profile.name))?;
+    Ok((port, keychain_account(&profile)))
+}
+
+pub async fn postgres_client(app: &AppHandle) -> Result<tokio_postgres::Client, String> {
+    let (port, account) = postgres_target(app)?;
+    let secret = tauri::async_runtime::spawn_blocking(move || keychain::get(&account))
+        .await
+        .map_err(|e| e.to_string())??
+        .ok_or("No PostgreSQL credentials saved for this profile")?;
+    let credentials: Credentials =
+        serde_json::from_str(&secret).map_err(|e| e.to_string())?;
+    connect(port, &credentials).await
+}
+
+/// Save the active profile's database login to the OS keychain.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn set_postgres_credentials(
+    app: AppHandle,
+    credentials: Credentials,
+) -> Result<(), AppError> {
+    let (_, account) = postgres_target(&app)?;
+    let secret = serde_json::to_string(&credentials).map_err(|e| e.to_string())?;
+    Ok(tauri::async_runtime::spawn_blocking(move || keychain::set(&account, &secret))
+        .await
+        .map_err(|e| e.to_string())??)
+}
+
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn clear_postgres_credentials(app: AppHandle) -> Result<(), AppError> {
+    let (_, account) = postgres_target(&app)?;
+    Ok(tauri::async_runtime::spawn_blocking(move || keychain::delete(&account))
+        .await
+        .map_err(|e| e.to_string())??)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn get_postgres_connections(app: AppHandle) -> Result<Vec<ActiveConnection>, AppError> {
+    let client = postgres_client(&app).await?;
+    Ok(connections(&client).await?)
+}
+
+/// Terminate a client connection. Destructive, so the UI must pass
+/// `confirm: true` after asking the user.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn terminate_postgres_connection(
+    app: AppHandle,
+    pid: i32,
+    confirm: bool,
+) -> Result<bool, AppError> {
+    if !confirm {
+        let message = "Terminating a connection requires confirmation";
+        return Err(AppError::new(ErrorKind::ConfirmationRequired, message));
+    }
+    let client = postgres_client(&app).await?;
+    Ok(terminate(&client, pid).await?)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn get_postgres_table_sizes(app: AppHandle) -> Result<Vec<TableSize>, AppError> {
+    let client = postgres_client(&app).await?;
+    Ok(table_sizes(&client).await?)
+}
+
+/// VACUUM or ANALYZE the given tables (`schema.table`), or every user table.
+/// Runs table by table and streams `postgres-maintenance` progress events;
+/// returns the number of tables processed without error.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn run_postgres_maintenance(
+    app: AppHandle,
+    operation: Maintenance,
+    tables: Option<Vec<String>>,
+    confirm: bool,
+) -> Result<usize, AppError> {
+    if !confirm {
+        let message = "Maintenance requires confirmation";
+        return Err(AppError::new(ErrorKind::ConfirmationRequired, message));
+    }
+    let client = postgres_client(&app).await?;
+    let mut targets = table_sizes(&client).await?;
+    if let Some(tables) = tables {
+        targets.retain(|t| tables.contains(&format!("{}.{}", t.schema, t.name)));
+    }
+
+    let total = targets.len();
+    let mut succeeded = 0;
+    for (index, table) in targets.iter().enumerate() {
+        let _ = app.emit(
+            "postgres-maintenance",
+            serde_json::json!({
+                "operation": operation,
+                "table": format!("{}.{}", table.schema, table.name),
+                "index": index,
+                "total": total,
+                "status": "running",
+            }),
+        );
+        let result = maintain(&client, operation, &table.qualified_name()).await;
+        if result.is_ok() {
+            succeeded += 1;
+        }
+        let _ = app.emit(
+            "postgres-maintenance",
+            serde_json::json!({
+                "operation": operation,
+                "table": format!("{}.{}", table.schema, table.name),
+                "index": index,
+                "total": total,
+                "status": if result.is_ok() { "done" } else { "failed" },
+                "error": result.err(),
+            }),
+        );
+    }
+    Ok(succeeded)
+}
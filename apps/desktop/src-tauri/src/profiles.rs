@@ -0,0 +1,318 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tulsbot_macros::instrumented;
+
+use crate::{deps, memories, settings, users};
+use crate::context::apply_app_rules;
+use crate::error::{AppError, ErrorKind};
+use crate::health::{poll_health, HealthState};
+use crate::history::HealthHistory;
+use crate::i18n::apply_locale;
+use crate::lan::stop_lan_exposure;
+use crate::locks::LockExt;
+use crate::proxy::warm_connections;
+use crate::readiness::Readiness;
+use crate::remediation::Remediation;
+use crate::state::AppState;
+use crate::themes::refresh_theme;
+use crate::tray::{apply_tray_behavior, refresh_tray_menu};
+use crate::windows::register_shortcuts;
+
+// ── Named profiles (service sets, settings, data dirs) ──────────────────────
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ServiceDef {
+    pub name: String,
+    pub port: u16,
+    /// Docker container backing the service (used for restart and logs).
+    #[serde(default)]
+    pub container: Option<String>,
+    /// Explicit restart command (argv); takes precedence over `container`.
+    #[serde(default)]
+    pub restart: Option<Vec<String>>,
+    /// Explicit start / stop commands (argv); take precedence over
+    /// `container`.
+    #[serde(default)]
+    pub start: Option<Vec<String>>,
+    #[serde(default)]
+    pub stop: Option<Vec<String>>,
+    /// Check that the service is ready to serve, not just listening.
+    #[serde(default)]
+    pub readiness: Option<Readiness>,
+    /// Services that must be up before this one starts.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Automatic recovery after repeated failed checks.
+    #[serde(default)]
+    pub remediation: Option<Remediation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub services: Vec<ServiceDef>,
+    /// Where this profile keeps its settings and data. Defaults to the app
+    /// data dir for the default profile and `profiles/<name>` below it for
+    /// every other profile.
+    #[serde(default)]
+    pub data_dir: Option<PathBuf>,
+}
+
+impl Profile {
+    pub fn builtin() -> Self {
+        let services: [(&str, u16, &[&str]); 4] = [
+            ("PostgreSQL", 5432, &[]),
+            ("Qdrant", 6333, &[]),
+            ("Context Manager", 3001, &["PostgreSQL", "Qdrant"]),
+            ("Web UI", 3100, &["Context Manager"]),
+        ];
+        Self {
+            name: DEFAULT_PROFILE.into(),
+            services: services
+                .iter()
+                .map(|(name, port, depends_on)| ServiceDef {
+                    name: name.to_string(),
+                    port: *port,
+                    // Qdrant reports ready once its collections are loaded
+                    readiness: (*name == "Qdrant").then(|| Readiness::Http {
+                        path: "/readyz".into(),
+                    }),
+                    depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+                    ..Default::default()
+                })
+                .collect(),
+            data_dir: None,
+        }
+    }
+
+    /// The API proxy may only reach this profile's services on loopback.
+    pub fn allows_url(&self, url: &str) -> bool {
+        let Ok(parsed) = reqwest::Url::parse(url) else {
+            return false;
+        };
+        let local = matches!(
+            parsed.host_str(),
+            Some("localhost") | Some("127.0.0.1") | Some("[::1]")
+        );
+        let port = parsed.port_or_known_default();
+        local && self.services.iter().any(|s| Some(s.port) == port)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileStore {
+    pub active: String,
+    pub profiles: Vec<Profile>,
+}
+
+impl Default for ProfileStore {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_PROFILE.into(),
+            profiles: vec![Profile::builtin()],
+        }
+    }
+}
+
+impl ProfileStore {
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.name == name)
+    }
+
+    /// The active profile, or the first one if `active` points nowhere.
+    pub fn active_profile(&self) -> Profile {
+        self.get(&self.active)
+            .or_else(|| self.profiles.first())
+            .cloned()
+            .unwrap_or_else(Profile::builtin)
+    }
+}
+
+pub fn validate_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid profile name '{}': use letters, digits, '-' or '_'",
+            name
+        ))
+    }
+}
+
+/// Resolve the data directory for `profile`.
+pub fn data_dir(app: &AppHandle, profile: &Profile) -> Result<PathBuf, String> {
+    if let Some(dir) = &profile.data_dir {
+        return Ok(users::scoped(dir));
+    }
+    let base = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    if profile.name == DEFAULT_PROFILE {
+        Ok(base)
+    } else {
+        Ok(base.join("profiles").join(&profile.name))
+    }
+}
+
+fn store_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(dir.join("profiles.json"))
+}
+
+pub fn load(app: &AppHandle) -> ProfileStore {
+    let Ok(path) = store_path(app) else {
+        return ProfileStore::default();
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+            eprintln!("[tulsbot] Ignoring malformed {}: {}", path.display(), e);
+            ProfileStore::default()
+        }),
+        Err(_) => ProfileStore::default(),
+    }
+}
+
+pub fn save(app: &AppHandle, store: &ProfileStore) -> Result<(), String> {
+    let path = store_path(app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    std::fs::write(&path, text).map_err(|e| e.to_string())
+}
+
+/// Data directory of the active profile.
+pub fn active_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let state = app.state::<AppState>();
+    let profile = state.profiles.lock_or_recover().active_profile();
+    data_dir(app, &profile)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn list_profiles(state: State<'_, AppState>) -> Result<ProfileStore, AppError> {
+    let store = state.profiles.lock_or_recover();
+    Ok(store.clone())
+}
+
+/// Create or replace a profile. Editing the active profile re-applies it.
+#[instrumented]
+#[tauri::command]
+pub async fn save_profile(app: AppHandle, profile: Profile) -> Result<(), AppError> {
+    validate_name(&profile.name)?;
+    deps::validate(&profile.services)?;
+    let is_active = {
+        let state = app.state::<AppState>();
+        let mut store = state.profiles.lock_or_recover();
+        match store.profiles.iter_mut().find(|p| p.name == profile.name) {
+            Some(existing) => *existing = profile.clone(),
+            None => store.profiles.push(profile.clone()),
+        }
+        save(&app, &store)?;
+        store.active == profile.name
+    };
+    if is_active {
+        apply_profile(&app, &profile.name)?;
+    } else {
+        refresh_tray_menu(&app);
+    }
+    Ok(())
+}
+
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn delete_profile(app: AppHandle, name: String) -> Result<(), AppError> {
+    {
+        let state = app.state::<AppState>();
+        let mut store = state.profiles.lock_or_recover();
+        if store.active == name {
+            return Err(AppError::new(ErrorKind::Conflict, "Cannot delete the active profile"));
+        }
+        store.profiles.retain(|p| p.name != name);
+        save(&app, &store)?;
+    }
+    refresh_tray_menu(&app);
+    Ok(())
+}
+
+/// OS account the app runs as and the profile active for it, for the
+/// profile switcher's header.
+#[derive(Debug, Clone, Serialize)]
+pub struct UserInfo {
+    pub user: String,
+    pub profile: String,
+    pub data_dir: String,
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn get_user_info(app: AppHandle) -> Result<UserInfo, AppError> {
+    let profile = {
+        let state = app.state::<AppState>();
+        let store = state.profiles.lock_or_recover();
+        store.active.clone()
+    };
+    Ok(UserInfo {
+        user: users::current(),
+        profile,
+        data_dir: active_data_dir(&app)?.display().to_string(),
+    })
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn switch_profile(app: AppHandle, name: String) -> Result<(), AppError> {
+    Ok(apply_profile(&app, &name)?)
+}
+
+/// Make `name` the active profile: swap the monitored services (and with
+/// them the proxy allowlist) under one lock, reload settings from the
+/// profile's data dir, then rebuild the tray and re-poll immediately.
+pub fn apply_profile(app: &AppHandle, name: &str) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let profile = {
+        let mut store = state.profiles.lock_or_recover();
+        let profile = store
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("Unknown profile: {}", name))?;
+        store.active = profile.name.clone();
+        save(app, &store)?;
+        *state.health.lock_or_recover() = HealthState::for_services(&profile.services);
+        profile
+    };
+
+    let data_dir = data_dir(app, &profile)?;
+    users::restrict(&data_dir)?;
+    *state.history.lock_or_recover() = HealthHistory::load(&data_dir);
+    *state.memories.lock_or_recover() = memories::load(&data_dir);
+    state.remediation.lock_or_recover().reset();
+    let settings = settings::load(&data_dir);
+    *state.settings.lock_or_recover() = settings.clone();
+    // Also rebuilds the tray menu with the new profile checked
+    apply_locale(app, settings.locale.as_deref())?;
+    if let Err(e) = register_shortcuts(app) {
+        eprintln!("[tulsbot] Failed to register shortcuts: {}", e);
+    }
+    apply_app_rules(app);
+    apply_tray_behavior(app, settings.tray);
+    // The listeners forward to the old profile's services
+    stop_lan_exposure(app);
+
+    let _ = app.emit("profile-changed", &profile);
+    let _ = app.emit("settings-changed", &settings);
+
+    let poll_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        refresh_theme(&poll_handle).await;
+        let state = poll_handle.state::<AppState>();
+        poll_health(poll_handle.clone(), state.inner()).await;
+        warm_connections(&poll_handle).await;
+    });
+    Ok(())
+}
@@ -0,0 +1,326 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager};
+use tulsbot_macros::instrumented;
+
+use crate::settings;
+use crate::conversations::{self, Conversation};
+use crate::error::AppError;
+use crate::locks::LockExt;
+use crate::profiles::active_data_dir;
+use crate::state::AppState;
+
+// ── System prompts (personas) ───────────────────────────────────────────────
+//
+// Named system prompts kept in `system_prompts.json` in the profile's data
+// dir. A conversation can pick one, the profile's settings name a default,
+// and the context builder sends whichever `resolve` settles on. Prompts move
+// between profiles and machines as a JSON array through import and export.
+
+const FILE_NAME: &str = "system_prompts.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemPrompt {
+    pub id: String,
+    pub name: String,
+    pub content: String,
+    /// Unix seconds.
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+/// Where the prompt in effect comes from, strongest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PromptSource {
+    /// Picked for the conversation.
+    Conversation,
+    /// The per-application rule for the frontmost app.
+    AppRule,
+    /// The profile's default prompt.
+    Profile,
+    /// The inline prompt of the context settings.
+    Settings,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedPrompt {
+    pub source: PromptSource,
+    /// Id of the stored prompt; `None` for inline prompts.
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub content: String,
+}
+
+pub fn load(dir: &Path) -> Vec<SystemPrompt> {
+    std::fs::read_to_string(dir.join(FILE_NAME))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(dir: &Path, prompts: &[SystemPrompt]) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let text = serde_json::to_string_pretty(prompts).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(FILE_NAME), text).map_err(|e| e.to_string())
+}
+
+pub fn validate(name: &str, content: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("System prompt name must not be empty".into());
+    }
+    if content.trim().is_empty() {
+        return Err(format!("System prompt '{}' is empty", name.trim()));
+    }
+    Ok(())
+}
+
+pub fn find<'a>(prompts: &'a [SystemPrompt], id: &str) -> Result<&'a SystemPrompt, String> {
+    prompts.iter().find(|p| p.id == id).ok_or_else(|| format!("Unknown system prompt: {}", id))
+}
+
+/// The first prompt in effect, trying each source from the strongest. A
+/// conversation or profile pointing at a deleted prompt falls through.
+pub fn resolve(
+    prompts: &[SystemPrompt],
+    conversation: Option<&str>,
+    app_rule: Option<&str>,
+    profile: Option<&str>,
+    inline: Option<&str>,
+) -> Option<ResolvedPrompt> {
+    let stored = |source, id: Option<&str>| {
+        let prompt = find(prompts, id?).ok()?;
+        Some(ResolvedPrompt {
+            source,
+            id: Some(prompt.id.clone()),
+            name: Some(prompt.name.clone()),
+            content: prompt.content.clone(),
+        })
+    };
+    let given = |source, content: Option<&str>| {
+        let content = content.filter(|c| !c.trim().is_empty())?;
+        Some(ResolvedPrompt { source, id: None, name: None, content: content.to_string() })
+    };
+    stored(PromptSource::Conversation, conversation)
+        .or_else(|| given(PromptSource::AppRule, app_rule))
+        .or_else(|| stored(PromptSource::Profile, profile))
+        .or_else(|| given(PromptSource::Settings, inline))
+}
+
+pub fn export(prompts: &[SystemPrompt], path: &Path) -> Result<usize, String> {
+    let text = serde_json::to_string_pretty(prompts).map_err(|e| e.to_string())?;
+    std::fs::write(path, text).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(prompts.len())
+}
+
+/// Read an exported array from `path` and add its prompts to `prompts`.
+/// A prompt whose id exists already replaces the stored one when it is
+/// newer; prompts identical to a stored one by name and content are
+/// skipped. Returns how many were added or replaced.
+pub fn import(prompts: &mut Vec<SystemPrompt>, path: &Path) -> Result<usize, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let incoming: Vec<SystemPrompt> =
+        serde_json::from_str(&text).map_err(|e| format!("Invalid system prompt file: {}", e))?;
+    let mut changed = 0;
+    for prompt in incoming {
+        validate(&prompt.name, &prompt.content)?;
+        if let Some(existing) = prompts.iter_mut().find(|p| p.id == prompt.id) {
+            if prompt.updated_at > existing.updated_at {
+                *existing = prompt;
+                changed += 1;
+            }
+        } else if !prompts.iter().any(|p| p.name == prompt.name && p.content == prompt.content) {
+            prompts.push(prompt);
+            changed += 1;
+        }
+    }
+    Ok(changed)
+}
+
+/// Load the stored prompts, change them and save them, then emit
+/// `system-prompts-changed`.
+pub fn update_prompts<T>(
+    app: &AppHandle,
+    change: impl FnOnce(&mut Vec<SystemPrompt>) -> Result<T, String>,
+) -> Result<T, String> {
+    let dir = active_data_dir(app)?;
+    let mut current = load(&dir);
+    let result = change(&mut current)?;
+    save(&dir, &current)?;
+    let _ = app.emit("system-prompts-changed", ());
+    Ok(result)
+}
+
+/// The system prompt in effect for `conversation`, or for a new one.
+pub fn resolve_prompt(
+    app: &AppHandle,
+    conversation: Option<&Conversation>,
+) -> Result<Option<ResolvedPrompt>, String> {
+    let state = app.state::<AppState>();
+    let (profile, inline) = {
+        let settings = state.settings.lock_or_recover();
+        (settings.default_system_prompt.clone(), settings.context.system_prompt.clone())
+    };
+    let rule = state.active_rule.lock_or_recover().clone().and_then(|r| r.system_prompt);
+    Ok(resolve(
+        &load(&active_data_dir(app)?),
+        conversation.and_then(|c| c.system_prompt.as_deref()),
+        rule.as_deref(),
+        profile.as_deref(),
+        inline.as_deref(),
+    ))
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn list_system_prompts(app: AppHandle) -> Result<Vec<SystemPrompt>, AppError> {
+    let mut prompts = load(&active_data_dir(&app)?);
+    prompts.sort_by_key(|p| p.name.to_lowercase());
+    Ok(prompts)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn create_system_prompt(
+    app: AppHandle,
+    name: String,
+    content: String,
+) -> Result<SystemPrompt, AppError> {
+    validate(&name, &content)?;
+    let now = conversations::now();
+    let prompt = SystemPrompt {
+        id: conversations::new_id(),
+        name: name.trim().to_string(),
+        content,
+        created_at: now,
+        updated_at: now,
+    };
+    Ok(update_prompts(&app, |prompts| {
+        prompts.push(prompt.clone());
+        Ok(prompt)
+    })?)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn update_system_prompt(
+    app: AppHandle,
+    id: String,
+    name: Option<String>,
+    content: Option<String>,
+) -> Result<SystemPrompt, AppError> {
+    Ok(update_prompts(&app, |prompts| {
+        let prompt = prompts
+            .iter_mut()
+            .find(|p| p.id == id)
+            .ok_or_else(|| format!("Unknown system prompt: {}", id))?;
+        let name = name.map(|n| n.trim().to_string()).unwrap_or_else(|| prompt.name.clone());
+        let content = content.unwrap_or_else(|| prompt.content.clone());
+        validate(&name, &content)?;
+        prompt.name = name;
+        prompt.content = content;
+        prompt.updated_at = conversations::now();
+        Ok(prompt.clone())
+    })?)
+}
+
+/// Delete a stored prompt. Conversations that picked it fall back to the
+/// next source; the profile default is cleared if it was this one.
+#[instrumented]
+#[tauri::command]
+pub async fn delete_system_prompt(app: AppHandle, id: String) -> Result<(), AppError> {
+    update_prompts(&app, |prompts| {
+        let before = prompts.len();
+        prompts.retain(|p| p.id != id);
+        if prompts.len() == before {
+            return Err(format!("Unknown system prompt: {}", id));
+        }
+        Ok(())
+    })?;
+    let state = app.state::<AppState>();
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        if settings.default_system_prompt.as_deref() != Some(id.as_str()) {
+            return Ok(());
+        }
+        settings.default_system_prompt = None;
+        settings.clone()
+    };
+    settings::save(&active_data_dir(&app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    Ok(())
+}
+
+/// Make a stored prompt the active profile's default; `None` clears it.
+#[instrumented]
+#[tauri::command]
+pub async fn set_default_system_prompt(app: AppHandle, id: Option<String>) -> Result<(), AppError> {
+    if let Some(id) = &id {
+        find(&load(&active_data_dir(&app)?), id)?;
+    }
+    let state = app.state::<AppState>();
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        settings.default_system_prompt = id;
+        settings.clone()
+    };
+    settings::save(&active_data_dir(&app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    Ok(())
+}
+
+/// Pick a stored prompt for the conversation; `None` goes back to the
+/// default.
+#[instrumented]
+#[tauri::command]
+pub async fn set_conversation_system_prompt(
+    app: AppHandle,
+    conversation: String,
+    id: Option<String>,
+) -> Result<Option<ResolvedPrompt>, AppError> {
+    let dir = active_data_dir(&app)?;
+    if let Some(id) = &id {
+        find(&load(&dir), id)?;
+    }
+    let mut stored = conversations::load(&dir, &conversation)?;
+    stored.system_prompt = id;
+    stored.updated_at = conversations::now();
+    conversations::save(&dir, &stored)?;
+    let resolved = resolve_prompt(&app, Some(&stored))?;
+    let _ = app.emit(
+        "conversation-system-prompt-changed",
+        serde_json::json!({ "conversation": conversation, "prompt": resolved }),
+    );
+    Ok(resolved)
+}
+
+/// The system prompt the context builder would send for `conversation`
+/// (or a new conversation), and where it comes from.
+#[instrumented]
+#[tauri::command]
+pub async fn resolve_system_prompt(
+    app: AppHandle,
+    conversation: Option<String>,
+) -> Result<Option<ResolvedPrompt>, AppError> {
+    let stored = match conversation {
+        Some(id) => Some(conversations::load(&active_data_dir(&app)?, &id)?),
+        None => None,
+    };
+    Ok(resolve_prompt(&app, stored.as_ref())?)
+}
+
+/// Write every stored prompt to `path` as JSON, returning how many.
+#[instrumented]
+#[tauri::command]
+pub async fn export_system_prompts(app: AppHandle, path: String) -> Result<usize, AppError> {
+    let prompts = load(&active_data_dir(&app)?);
+    Ok(export(&prompts, &PathBuf::from(path))?)
+}
+
+/// Add the prompts of an exported file, returning how many were added or
+/// updated.
+#[instrumented]
+#[tauri::command]
+pub async fn import_system_prompts(app: AppHandle, path: String) -> Result<usize, AppError> {
+    Ok(update_prompts(&app, |prompts| import(prompts, &PathBuf::from(path)))?)
+}
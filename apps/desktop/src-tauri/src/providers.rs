@@ -0,0 +1,372 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tulsbot_macros::instrumented;
+
+use crate::{conversations, keychain};
+use crate::budgets::check_budget;
+use crate::credentials::{self, Credential, CredentialInfo};
+use crate::error::{AppError, ErrorKind};
+use crate::locks::LockExt;
+use crate::profiles::active_data_dir;
+use crate::proxy::mock_completion;
+use crate::redaction::{redact_outbound, Redactor};
+use crate::settings::{self, Settings};
+use crate::state::AppState;
+use crate::time_tracking::begin_generation;
+use crate::tray::{begin_work, refresh_tray_menu};
+use crate::usage::{record_usage, UsageRecord};
+
+// ── LLM providers (OpenAI-compatible, Anthropic, Ollama) ────────────────────
+//
+// Non-streaming chat completions for work the shell does on its own (titles,
+// summaries, background jobs). API keys come from the OS keychain.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: String,
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn new(role: &str, content: impl Into<String>) -> Self {
+        Self { role: role.into(), content: content.into() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatRequest {
+    pub provider: String,
+    pub model: String,
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+    pub messages: Vec<ChatMessage>,
+    /// Credential label whose key to use; `None` uses the profile's choice.
+    #[serde(default)]
+    pub credential: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatResponse {
+    pub content: String,
+    pub input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    OpenAi,
+    Anthropic,
+    Ollama,
+}
+
+fn kind(provider: &str) -> Result<Kind, String> {
+    match provider {
+        "openai" => Ok(Kind::OpenAi),
+        "anthropic" => Ok(Kind::Anthropic),
+        "ollama" => Ok(Kind::Ollama),
+        other => Err(format!("Unknown provider: {}", other)),
+    }
+}
+
+/// Whether `provider` needs an API key.
+pub fn needs_key(provider: &str) -> bool {
+    kind(provider).is_ok_and(|k| k != Kind::Ollama)
+}
+
+/// Provider behind an API host, for requests made through the proxy.
+pub fn for_host(host: &str) -> Option<&'static str> {
+    match host {
+        "api.openai.com" => Some("openai"),
+        "api.anthropic.com" => Some("anthropic"),
+        _ => None,
+    }
+}
+
+/// Endpoint probed to measure latency to `provider`'s API.
+pub fn probe_url(provider: &str) -> Option<&'static str> {
+    match kind(provider).ok()? {
+        Kind::OpenAi => Some("https://api.openai.com/v1/models"),
+        Kind::Anthropic => Some("https://api.anthropic.com/v1/models"),
+        Kind::Ollama => None,
+    }
+}
+
+/// Keychain account holding the API key for `provider`.
+pub fn keychain_account(provider: &str) -> String {
+    format!("provider:{}", provider)
+}
+
+fn tokens(value: &serde_json::Value) -> Option<u64> {
+    value.as_u64()
+}
+
+/// Run a chat completion. `api_key` is required for hosted providers.
+pub async fn complete(
+    request: &ChatRequest,
+    api_key: Option<&str>,
+) -> Result<ChatResponse, String> {
+    let kind = kind(&request.provider)?;
+    if kind != Kind::Ollama && api_key.is_none() {
+        return Err(format!("No API key saved for {}", request.provider));
+    }
+    let client = reqwest::Client::new();
+    let builder = match kind {
+        Kind::OpenAi => client
+            .post("https://api.openai.com/v1/chat/completions")
+            .bearer_auth(api_key.unwrap_or_default())
+            .json(&json!({
+                "model": request.model,
+                "messages": request.messages,
+                "temperature": request.temperature,
+                "max_tokens": request.max_tokens,
+            })),
+        Kind::Anthropic => {
+            let system: Vec<&str> = request
+                .messages
+                .iter()
+                .filter(|m| m.role == "system")
+                .map(|m| m.content.as_str())
+                .collect();
+            let messages: Vec<&ChatMessage> =
+                request.messages.iter().filter(|m| m.role != "system").collect();
+            let mut body = json!({
+                "model": request.model,
+                "max_tokens": request.max_tokens.unwrap_or(1024),
+                "messages": messages,
+            });
+            if !system.is_empty() {
+                body["system"] = json!(system.join("\n\n"));
+            }
+            if let Some(t) = request.temperature {
+                body["temperature"] = json!(t);
+            }
+            client
+                .post("https://api.anthropic.com/v1/messages")
+                .header("x-api-key", api_key.unwrap_or_default())
+                .header("anthropic-version", "2023-06-01")
+                .json(&body)
+        }
+        Kind::Ollama => client.post("http://127.0.0.1:11434/api/chat").json(&json!({
+            "model": request.model,
+            "messages": request.messages,
+            "stream": false,
+            "options": { "temperature": request.temperature },
+        })),
+    };
+
+    let resp = builder
+        .timeout(std::time::Duration::from_secs(120))
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+    let status = resp.status().as_u16();
+    let text = resp.text().await.map_err(|e| e.to_string())?;
+    if status >= 400 {
+        return Err(format!("HTTP {}: {}", status, text));
+    }
+    let body: serde_json::Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+
+    let response = match kind {
+        Kind::OpenAi => ChatResponse {
+            content: body["choices"][0]["message"]["content"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            input_tokens: tokens(&body["usage"]["prompt_tokens"]),
+            output_tokens: tokens(&body["usage"]["completion_tokens"]),
+        },
+        Kind::Anthropic => ChatResponse {
+            content: body["content"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|block| block["text"].as_str())
+                .collect(),
+            input_tokens: tokens(&body["usage"]["input_tokens"]),
+            output_tokens: tokens(&body["usage"]["output_tokens"]),
+        },
+        Kind::Ollama => ChatResponse {
+            content: body["message"]["content"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            input_tokens: tokens(&body["prompt_eval_count"]),
+            output_tokens: tokens(&body["eval_count"]),
+        },
+    };
+    Ok(response)
+}
+
+/// Save the provider's `default` key.
+#[instrumented]
+#[tauri::command]
+pub async fn set_provider_key(
+    app: AppHandle,
+    provider: String,
+    key: String,
+) -> Result<(), AppError> {
+    add_credential(app, provider, credentials::DEFAULT_LABEL.into(), key).await
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn clear_provider_key(app: AppHandle, provider: String) -> Result<(), AppError> {
+    remove_credential(app, provider, credentials::DEFAULT_LABEL.into()).await
+}
+
+/// Store a key for `provider` under `label` (e.g. `work`, `personal`).
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn add_credential(
+    app: AppHandle,
+    provider: String,
+    label: String,
+    key: String,
+) -> Result<(), AppError> {
+    credentials::validate_label(&label)?;
+    if key.trim().is_empty() {
+        return Err(AppError::new(ErrorKind::InvalidInput, "API key is empty"));
+    }
+    let account = credentials::account(&provider, &label);
+    tauri::async_runtime::spawn_blocking(move || keychain::set(&account, key.trim()))
+        .await
+        .map_err(|e| e.to_string())??;
+    credentials::register(&app, Credential { provider, label })?;
+    refresh_tray_menu(&app);
+    Ok(())
+}
+
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn remove_credential(
+    app: AppHandle,
+    provider: String,
+    label: String,
+) -> Result<(), AppError> {
+    let account = credentials::account(&provider, &label);
+    tauri::async_runtime::spawn_blocking(move || keychain::delete(&account))
+        .await
+        .map_err(|e| e.to_string())??;
+    credentials::unregister(&app, &Credential { provider, label })?;
+    refresh_tray_menu(&app);
+    Ok(())
+}
+
+/// Saved credentials, marking the one the active profile uses per provider.
+#[instrumented]
+#[tauri::command]
+pub async fn list_credentials(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<CredentialInfo>, AppError> {
+    let settings = state.settings.lock_or_recover();
+    Ok(credential_infos(&app, &settings))
+}
+
+pub fn credential_infos(app: &AppHandle, settings: &Settings) -> Vec<CredentialInfo> {
+    credentials::load(app)
+        .into_iter()
+        .map(|c| CredentialInfo {
+            selected: selected_credential(settings, &c.provider) == c.label,
+            provider: c.provider,
+            label: c.label,
+        })
+        .collect()
+}
+
+pub fn selected_credential<'a>(settings: &'a Settings, provider: &str) -> &'a str {
+    settings
+        .provider_credentials
+        .get(provider)
+        .map_or(credentials::DEFAULT_LABEL, String::as_str)
+}
+
+/// Make `label` the active profile's credential for `provider`.
+#[instrumented]
+#[tauri::command]
+pub async fn select_credential(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    provider: String,
+    label: String,
+) -> Result<(), AppError> {
+    let known = credentials::load(&app)
+        .iter()
+        .any(|c| c.provider == provider && c.label == label);
+    if !known {
+        let message = format!("No credential '{}' saved for {}", label, provider);
+        return Err(AppError::new(ErrorKind::NotFound, message));
+    }
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        if label == credentials::DEFAULT_LABEL {
+            settings.provider_credentials.remove(&provider);
+        } else {
+            settings.provider_credentials.insert(provider, label);
+        }
+        settings.clone()
+    };
+    settings::save(&active_data_dir(&app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    refresh_tray_menu(&app);
+    Ok(())
+}
+
+/// Run `request` with the provider's key from the keychain, recording its
+/// usage. The messages are redacted first, per `conversation`'s setting.
+pub async fn run_completion(
+    app: &AppHandle,
+    request: &ChatRequest,
+    conversation: Option<&str>,
+) -> Result<ChatResponse, String> {
+    let mut redacted = request.clone();
+    let state = app.state::<AppState>();
+    for message in &mut redacted.messages {
+        let (text, provider) = (&message.content, request.provider.as_str());
+        message.content = redact_outbound(&state, text, Redactor::redact, conversation, provider)?;
+    }
+    let request = &redacted;
+    if let Some(mocked) = mock_completion(app, request).await {
+        return mocked;
+    }
+    let _work = begin_work(app);
+    let key = if needs_key(&request.provider) {
+        let label = match &request.credential {
+            Some(label) => label.clone(),
+            None => {
+                let state = app.state::<AppState>();
+                let settings = state.settings.lock_or_recover();
+                selected_credential(&settings, &request.provider).to_string()
+            }
+        };
+        let account = credentials::account(&request.provider, &label);
+        tauri::async_runtime::spawn_blocking(move || keychain::get(&account))
+            .await
+            .map_err(|e| e.to_string())??
+    } else {
+        None
+    };
+    check_budget(app, &request.provider).await?;
+    let started = std::time::Instant::now();
+    let generating = begin_generation(app);
+    let result = complete(request, key.as_deref()).await;
+    drop(generating);
+    let reply = result.as_ref().ok();
+    record_usage(
+        app,
+        UsageRecord {
+            at: conversations::now(),
+            source: "provider".into(),
+            provider: request.provider.clone(),
+            model: Some(request.model.clone()),
+            latency_ms: started.elapsed().as_millis() as u64,
+            input_tokens: reply.and_then(|r| r.input_tokens),
+            output_tokens: reply.and_then(|r| r.output_tokens),
+            error: result.as_ref().err().cloned(),
+        },
+    );
+    result
+}
@@ -0,0 +1,555 @@
+use futures_util::StreamExt;
+use serde::Serialize;
+#[cfg(feature = "mock-backend")]
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tulsbot_macros::instrumented;
+
+use crate::{compression, conversations, providers, trace, upload, usage};
+use crate::budgets::check_budget;
+use crate::error::{AppError, ErrorKind};
+use crate::locks::LockExt;
+use crate::providers::{ChatRequest, ChatResponse};
+use crate::redaction::{redact_outbound, Redactor};
+use crate::state::AppState;
+use crate::trace::TraceEntry;
+use crate::usage::{record_usage, UsageRecord};
+use crate::warmup::{self, ConnectionInfo};
+#[cfg(feature = "mock-backend")]
+use crate::{mock, profiles::active_data_dir};
+
+// ── Backend proxy ───────────────────────────────────────────────────────────
+//
+// The frontend reaches the active profile's backends through `api_proxy`,
+// since the production CSP blocks localhost. Clients are warmed up per
+// backend and protocol; with the `mock-backend` feature, requests are
+// answered from fixture files instead.
+
+/// Proxied requests without a `timeout_ms` give up after this long.
+const PROXY_TIMEOUT_MS: u64 = 60_000;
+/// Longest `timeout_ms` honored unless `Settings::proxy_max_timeout_ms` says
+/// otherwise.
+const PROXY_MAX_TIMEOUT_MS: u64 = 600_000;
+
+/// Timeout for a proxied request asking for `requested` milliseconds, capped
+/// at `max` (the setting, when set).
+pub fn timeout_ms(requested: Option<u64>, max: Option<u64>) -> u64 {
+    let max = max.unwrap_or(PROXY_MAX_TIMEOUT_MS).max(1);
+    requested.unwrap_or(PROXY_TIMEOUT_MS).clamp(1, max)
+}
+
+/// The HTTP method named `name`, in any case. Only the methods the
+/// backends take are proxied.
+pub fn method(name: &str) -> Result<reqwest::Method, AppError> {
+    match name.to_uppercase().as_str() {
+        "GET" => Ok(reqwest::Method::GET),
+        "POST" => Ok(reqwest::Method::POST),
+        "PUT" => Ok(reqwest::Method::PUT),
+        "DELETE" => Ok(reqwest::Method::DELETE),
+        "PATCH" => Ok(reqwest::Method::PATCH),
+        other => {
+            let message = format!("Unsupported HTTP method: {}", other);
+            Err(AppError::new(ErrorKind::InvalidInput, message))
+        }
+    }
+}
+
+/// The provider usage through `url` is recorded under: a known provider's
+/// name, otherwise the host.
+pub fn provider_for_url(url: &str) -> String {
+    let host = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(String::from))
+        .unwrap_or_default();
+    providers::for_host(&host).map_or(host, String::from)
+}
+
+/// The proxy client for `url`'s backend, over the protocol it was warmed
+/// up with.
+pub fn proxy_client_for(state: &AppState, url: &str) -> reqwest::Client {
+    let port = reqwest::Url::parse(url).ok().and_then(|u| u.port_or_known_default());
+    let connections = state.connections.lock_or_recover();
+    let protocol = connections.iter().find(|c| Some(c.port) == port).and_then(|c| c.protocol);
+    state.proxy_clients.get(protocol)
+}
+
+/// Generic HTTP proxy — lets the frontend call any backend endpoint of the
+/// active profile through the Tauri IPC bridge (required because production
+/// CSP blocks localhost). `timeout_ms` (default 60 s) is capped at the
+/// configured maximum. Returns the decoded body along with the encoding it
+/// arrived in and its size on the wire.
+#[instrumented]
+#[tauri::command]
+pub async fn api_proxy(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    method: String,
+    url: String,
+    body: Option<String>,
+    conversation: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<ProxyResponse, AppError> {
+    let allowed = {
+        let store = state.profiles.lock_or_recover();
+        store.active_profile().allows_url(&url)
+    };
+    if !allowed {
+        let message = format!("URL not allowed by the active profile: {}", url);
+        return Err(AppError::new(ErrorKind::Forbidden, message));
+    }
+    let body = match body {
+        Some(body) => {
+            let conversation = conversation.as_deref();
+            Some(redact_outbound(&state, &body, Redactor::redact_json, conversation, &url)?)
+        }
+        None => None,
+    };
+    if let Some(mocked) = mock_proxy(&app, &method, &url, body.as_deref()).await {
+        let body = mocked?;
+        let wire_bytes = body.len();
+        return Ok(ProxyResponse {
+            status: 200,
+            headers: Vec::new(),
+            body,
+            encoding: None,
+            wire_bytes,
+        });
+    }
+    let provider = provider_for_url(&url);
+    check_budget(&app, &provider).await?;
+    let model = body.as_deref().and_then(usage::model_from_body);
+
+    let client = proxy_client_for(&state, &url);
+
+    let (tracing, max_timeout_ms, compress) = {
+        let settings = state.settings.lock_or_recover();
+        (
+            settings.proxy_trace,
+            settings.proxy_max_timeout_ms,
+            settings.proxy_compress_requests,
+        )
+    };
+    let timeout_ms = self::timeout_ms(timeout_ms, max_timeout_ms);
+    let req_method = self::method(&method)?;
+
+    let mut builder = client
+        .request(req_method, &url)
+        .header("accept-encoding", compression::ACCEPT_ENCODING)
+        .timeout(std::time::Duration::from_millis(timeout_ms));
+
+    let mut request_encoding = None;
+    if let Some(json_body) = &body {
+        builder = builder.header("content-type", "application/json");
+        builder = if compress && json_body.len() >= compression::COMPRESS_MIN_BYTES {
+            request_encoding = Some("gzip".to_string());
+            builder
+                .header("content-encoding", "gzip")
+                .body(compression::gzip(json_body.as_bytes())?)
+        } else {
+            builder.body(json_body.clone())
+        };
+    }
+    let request = builder.build().map_err(|e| e.to_string())?;
+    let request_headers = tracing.then(|| trace::scrub_headers(request.headers()));
+
+    let started = std::time::Instant::now();
+    let response = send_proxied(&client, request, timeout_ms).await;
+    let result = match &response {
+        Ok(r) if r.status >= 400 => Err(format!("HTTP {}: {}", r.status, r.body)),
+        Ok(r) => Ok(r.body.clone()),
+        Err(e) => Err(e.message.clone()),
+    };
+    if let Some(request_headers) = request_headers {
+        let ok = response.as_ref().ok();
+        let entry = TraceEntry {
+            id: 0,
+            at: conversations::now(),
+            method: method.to_uppercase(),
+            url: url.clone(),
+            request_headers,
+            request_body: body.as_deref().map(trace::scrub_body),
+            request_encoding,
+            status: ok.map(|r| r.status),
+            response_headers: ok.map(|r| r.headers.clone()).unwrap_or_default(),
+            response_body: ok.map(|r| trace::scrub_body(&r.body)),
+            response_encoding: ok.and_then(|r| r.encoding.clone()),
+            response_wire_bytes: ok.map(|r| r.wire_bytes),
+            duration_ms: started.elapsed().as_millis() as u64,
+            error: result.as_ref().err().cloned(),
+        };
+        state.proxy_trace.lock_or_recover().record(entry);
+    }
+
+    let (input_tokens, output_tokens) = match &result {
+        Ok(text) => usage::tokens_from_body(text),
+        Err(_) => (None, None),
+    };
+    record_usage(
+        &app,
+        UsageRecord {
+            at: conversations::now(),
+            source: "proxy".into(),
+            provider,
+            model,
+            latency_ms: started.elapsed().as_millis() as u64,
+            input_tokens,
+            output_tokens,
+            error: result.as_ref().err().cloned(),
+        },
+    );
+    let response = response?;
+    if response.status >= 400 {
+        return Err(AppError::http(response.status, &response.body));
+    }
+    Ok(response)
+}
+
+/// Upload a file as multipart form data to an endpoint of the active profile,
+/// e.g. the Context Manager's upload endpoint. The file is streamed from
+/// `path`, emitting `upload-progress`, or sent from `bytes`; `extra_fields`
+/// go along as text fields. Text fields and text files are redacted, and the
+/// upload is timed and recorded like `api_proxy`. Returns the response body.
+#[instrumented]
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn upload_file(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    url: String,
+    field: String,
+    path: Option<String>,
+    bytes: Option<Vec<u8>>,
+    file_name: Option<String>,
+    mime: Option<String>,
+    extra_fields: Option<std::collections::BTreeMap<String, String>>,
+    upload_id: Option<String>,
+    conversation: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<String, AppError> {
+    let allowed = {
+        let store = state.profiles.lock_or_recover();
+        store.active_profile().allows_url(&url)
+    };
+    if !allowed {
+        let message = format!("URL not allowed by the active profile: {}", url);
+        return Err(AppError::new(ErrorKind::Forbidden, message));
+    }
+    let redact = |text: &str| {
+        redact_outbound(&state, text, Redactor::redact, conversation.as_deref(), &url)
+    };
+    let upload_id = upload_id.unwrap_or_else(conversations::new_id);
+    let (body, total, default_name) = match (path, bytes) {
+        (Some(path), None) => {
+            let name = std::path::Path::new(&path)
+                .file_name()
+                .map_or_else(|| "upload".into(), |n| n.to_string_lossy().into_owned());
+            let text = if upload::is_text(mime.as_deref(), file_name.as_ref().unwrap_or(&name)) {
+                tokio::fs::read_to_string(&path).await.ok()
+            } else {
+                None
+            };
+            let (stream, total) = match text {
+                Some(text) => {
+                    let text = redact(&text)?.into_bytes();
+                    let total = text.len() as u64;
+                    let reader = std::io::Cursor::new(text);
+                    (upload::progress_stream(app.clone(), upload_id, reader, total).boxed(), total)
+                }
+                None => {
+                    let file = tokio::fs::File::open(&path)
+                        .await
+                        .map_err(|e| format!("Could not open {}: {}", path, e))?;
+                    let total = file.metadata().await.map_err(|e| e.to_string())?.len();
+                    (upload::progress_stream(app.clone(), upload_id, file, total).boxed(), total)
+                }
+            };
+            (reqwest::Body::wrap_stream(stream), total, name)
+        }
+        (None, Some(bytes)) => {
+            let name = file_name.as_deref().unwrap_or("upload");
+            let bytes = match String::from_utf8(bytes) {
+                Ok(text) if upload::is_text(mime.as_deref(), name) => redact(&text)?.into_bytes(),
+                Ok(text) => text.into_bytes(),
+                Err(e) => e.into_bytes(),
+            };
+            let total = bytes.len() as u64;
+            (reqwest::Body::from(bytes), total, "upload".to_string())
+        }
+        _ => {
+            let message = "Pass either a path or bytes to upload";
+            return Err(AppError::new(ErrorKind::InvalidInput, message));
+        }
+    };
+
+    let mut part = reqwest::multipart::Part::stream_with_length(body, total)
+        .file_name(file_name.unwrap_or(default_name));
+    if let Some(mime) = mime {
+        part = part.mime_str(&mime).map_err(|e| {
+            AppError::new(ErrorKind::InvalidInput, format!("Invalid MIME type: {}", e))
+        })?;
+    }
+    let mut form = reqwest::multipart::Form::new();
+    for (name, value) in extra_fields.unwrap_or_default() {
+        form = form.text(name, redact(&value)?);
+    }
+    let form = form.part(field, part);
+
+    let provider = provider_for_url(&url);
+    check_budget(&app, &provider).await?;
+    let max_timeout_ms = state.settings.lock_or_recover().proxy_max_timeout_ms;
+    let timeout_ms = self::timeout_ms(timeout_ms, max_timeout_ms);
+    let client = proxy_client_for(&state, &url);
+    let request = client
+        .post(&url)
+        .header("accept-encoding", compression::ACCEPT_ENCODING)
+        .timeout(std::time::Duration::from_millis(timeout_ms))
+        .multipart(form)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let started = std::time::Instant::now();
+    let response = send_proxied(&client, request, timeout_ms).await;
+    let error = match &response {
+        Ok(r) if r.status >= 400 => Some(format!("HTTP {}: {}", r.status, r.body)),
+        Ok(_) => None,
+        Err(e) => Some(e.message.clone()),
+    };
+    let (input_tokens, output_tokens) = match &response {
+        Ok(r) if error.is_none() => usage::tokens_from_body(&r.body),
+        _ => (None, None),
+    };
+    record_usage(
+        &app,
+        UsageRecord {
+            at: conversations::now(),
+            source: "upload".into(),
+            provider,
+            model: None,
+            latency_ms: started.elapsed().as_millis() as u64,
+            input_tokens,
+            output_tokens,
+            error,
+        },
+    );
+    let response = response?;
+    if response.status >= 400 {
+        return Err(AppError::http(response.status, &response.body));
+    }
+    Ok(response.body)
+}
+
+// ── Mock backend ────────────────────────────────────────────────────────────
+
+#[cfg(feature = "mock-backend")]
+fn mock_dir(app: &AppHandle) -> Option<PathBuf> {
+    let state = app.state::<AppState>();
+    if !state.settings.lock().ok()?.mock_backend {
+        return None;
+    }
+    Some(mock::fixture_dir(&active_data_dir(app).ok()?))
+}
+
+/// Fixture answer for a proxied request while mock mode is on.
+#[cfg(feature = "mock-backend")]
+pub async fn mock_proxy(
+    app: &AppHandle,
+    method: &str,
+    url: &str,
+    body: Option<&str>,
+) -> Option<Result<String, String>> {
+    let dir = mock_dir(app)?;
+    let (app, method, url) = (app.clone(), method.to_string(), url.to_string());
+    let body = body.map(String::from);
+    let response = tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let mut mock = state.mock.lock().ok()?;
+        mock.proxy(&dir, &method, &url, body.as_deref())
+    })
+    .await
+    .ok()??;
+    tokio::time::sleep(std::time::Duration::from_millis(response.delay_ms)).await;
+    if response.status >= 400 {
+        return Some(Err(format!("HTTP {}: {}", response.status, response.body)));
+    }
+    Some(Ok(response.body))
+}
+
+#[cfg(not(feature = "mock-backend"))]
+pub async fn mock_proxy(
+    _app: &AppHandle,
+    _method: &str,
+    _url: &str,
+    _body: Option<&str>,
+) -> Option<Result<String, String>> {
+    None
+}
+
+/// Fixture answer for a completion while mock mode is on.
+#[cfg(feature = "mock-backend")]
+pub async fn mock_completion(
+    app: &AppHandle,
+    request: &ChatRequest,
+) -> Option<Result<ChatResponse, String>> {
+    let dir = mock_dir(app)?;
+    let (app, request) = (app.clone(), request.clone());
+    let response = tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let mut mock = state.mock.lock().ok()?;
+        mock.completion(&dir, &request)
+    })
+    .await
+    .ok()??;
+    tokio::time::sleep(std::time::Duration::from_millis(response.delay_ms)).await;
+    Some(mock::chat_response(&response))
+}
+
+#[cfg(not(feature = "mock-backend"))]
+pub async fn mock_completion(
+    _app: &AppHandle,
+    _request: &ChatRequest,
+) -> Option<Result<ChatResponse, String>> {
+    None
+}
+
+/// A proxied response, its body decoded. What `api_proxy` returns.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxyResponse {
+    pub status: u16,
+    /// Scrubbed for the trace.
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    /// `Content-Encoding` the body arrived with; `None` when uncompressed.
+    pub encoding: Option<String>,
+    /// Body size on the wire, before decoding.
+    pub wire_bytes: usize,
+}
+
+/// Send a proxied request and decode the response. Running out of
+/// `timeout_ms` is a `timeout` error rather than a network one.
+pub async fn send_proxied(
+    client: &reqwest::Client,
+    request: reqwest::Request,
+    timeout_ms: u64,
+) -> Result<ProxyResponse, AppError> {
+    let failed = |e: reqwest::Error| {
+        if e.is_timeout() {
+            AppError::new(ErrorKind::Timeout, format!("Timed out after {} ms", timeout_ms))
+                .with_details(serde_json::json!({ "timeout_ms": timeout_ms }))
+        } else {
+            AppError::new(ErrorKind::Network, format!("Request failed: {}", e))
+        }
+    };
+    let resp = client.execute(request).await.map_err(failed)?;
+
+    let status = resp.status().as_u16();
+    let headers = trace::scrub_headers(resp.headers());
+    let encoding = resp
+        .headers()
+        .get(reqwest::header::CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from)
+        .filter(|e| !e.eq_ignore_ascii_case("identity"));
+    let bytes = resp.bytes().await.map_err(failed)?;
+    let body = compression::decode(encoding.as_deref(), &bytes)?;
+    Ok(ProxyResponse {
+        status,
+        headers,
+        body: String::from_utf8_lossy(&body).into_owned(),
+        encoding,
+        wire_bytes: bytes.len(),
+    })
+}
+
+// ── Connection warm-up ──────────────────────────────────────────────────────
+
+/// Open pooled connections to every service of the active profile and emit
+/// `connections-warmed` with the results.
+pub async fn warm_connections(app: &AppHandle) -> Vec<ConnectionInfo> {
+    let state = app.state::<AppState>();
+    let services = state.profiles.lock_or_recover().active_profile().services;
+    let now = conversations::now();
+    let mut infos = Vec::new();
+    for service in &services {
+        infos.push(warmup::warm(&state.proxy_clients, &service.name, service.port, now).await);
+    }
+    *state.connections.lock_or_recover() = infos.clone();
+    let _ = app.emit("connections-warmed", &infos);
+    infos
+}
+
+/// Warm up at startup, then again whenever the machine wakes from sleep.
+pub async fn run_connection_warmup(app: AppHandle) {
+    // Same head start as the first health poll, so services can come up
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+    warm_connections(&app).await;
+    loop {
+        let before = std::time::SystemTime::now();
+        tokio::time::sleep(warmup::WAKE_CHECK).await;
+        let slept = before.elapsed().unwrap_or_default() > warmup::WAKE_CHECK * 3;
+        if slept {
+            eprintln!("[tulsbot] Woke from sleep; warming backend connections");
+            warm_connections(&app).await;
+        }
+    }
+}
+
+/// Protocol and warm-up latency per backend; `refresh` warms up again first.
+#[instrumented]
+#[tauri::command]
+pub async fn get_connection_info(
+    app: AppHandle,
+    refresh: Option<bool>,
+) -> Result<Vec<ConnectionInfo>, AppError> {
+    if refresh.unwrap_or(false) {
+        return Ok(warm_connections(&app).await);
+    }
+    Ok(app.state::<AppState>().connections.lock_or_recover().clone())
+}
+
+/// Captured proxy exchanges, newest first (empty unless tracing is on).
+#[instrumented]
+#[tauri::command]
+pub async fn get_proxy_trace(state: State<'_, AppState>) -> Result<Vec<TraceEntry>, AppError> {
+    Ok(state.proxy_trace.lock_or_recover().entries())
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn clear_proxy_trace(state: State<'_, AppState>) -> Result<(), AppError> {
+    state.proxy_trace.lock_or_recover().clear();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeout_defaults_and_caps() {
+        assert_eq!(timeout_ms(None, None), PROXY_TIMEOUT_MS);
+        assert_eq!(timeout_ms(Some(5_000), None), 5_000);
+        assert_eq!(timeout_ms(Some(u64::MAX), None), PROXY_MAX_TIMEOUT_MS);
+        assert_eq!(timeout_ms(Some(90_000), Some(60_000)), 60_000);
+        assert_eq!(timeout_ms(None, Some(1_000)), 1_000);
+    }
+
+    #[test]
+    fn timeout_is_never_zero() {
+        assert_eq!(timeout_ms(Some(0), None), 1);
+        assert_eq!(timeout_ms(Some(5_000), Some(0)), 1);
+    }
+
+    #[test]
+    fn method_ignores_case() {
+        assert_eq!(method("get").unwrap(), reqwest::Method::GET);
+        assert_eq!(method("Post").unwrap(), reqwest::Method::POST);
+        assert_eq!(method("PATCH").unwrap(), reqwest::Method::PATCH);
+    }
+
+    #[test]
+    fn method_rejects_the_rest() {
+        let error = method("TRACE").unwrap_err();
+        assert_eq!(error.kind, ErrorKind::InvalidInput);
+        assert!(error.message.contains("TRACE"));
+    }
+}
@@ -0,0 +1,341 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::AsyncWriteExt;
+use tulsbot_macros::instrumented;
+
+use crate::backup::upload_backups;
+use crate::error::AppError;
+use crate::locks::LockExt;
+use crate::profiles::{self, Profile, ServiceDef};
+use crate::relay::{relay_info, RelayCategory};
+use crate::state::AppState;
+
+// ── Qdrant snapshots (create, keep locally, restore) ────────────────────────
+//
+// Snapshots are created through the collection snapshot API, downloaded to
+// `<data dir>/snapshots/qdrant/<collection>/` and then deleted on the server,
+// so the local copies are the only ones and can be carried to a new machine.
+
+const EXTENSION: &str = "snapshot";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotFile {
+    pub collection: String,
+    pub name: String,
+    pub path: PathBuf,
+    pub size: u64,
+    /// Unix seconds (file modification time).
+    pub created_at: u64,
+}
+
+/// The profile's Qdrant service, by name or by the default port.
+pub fn service(profile: &Profile) -> Option<&ServiceDef> {
+    profile
+        .services
+        .iter()
+        .find(|s| s.name.eq_ignore_ascii_case("qdrant"))
+        .or_else(|| profile.services.iter().find(|s| s.port == 6333))
+}
+
+fn base_url(port: u16) -> String {
+    format!("http://127.0.0.1:{}", port)
+}
+
+async fn check(resp: reqwest::Response) -> Result<reqwest::Response, String> {
+    let status = resp.status();
+    if status.is_success() {
+        Ok(resp)
+    } else {
+        let text = resp.text().await.unwrap_or_default();
+        Err(format!("Qdrant HTTP {}: {}", status.as_u16(), text))
+    }
+}
+
+pub async fn collections(port: u16) -> Result<Vec<String>, String> {
+    let resp = reqwest::get(format!("{}/collections", base_url(port)))
+        .await
+        .map_err(|e| e.to_string())?;
+    let json: serde_json::Value = check(resp).await?.json().await.map_err(|e| e.to_string())?;
+    Ok(json["result"]["collections"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|c| c["name"].as_str().map(String::from))
+        .collect())
+}
+
+/// A point returned by a similarity search, with its text payload.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoredChunk {
+    pub collection: String,
+    pub id: String,
+    pub score: f32,
+    pub text: String,
+}
+
+/// The `limit` points of `collection` closest to `vector`. The chunk text is
+/// read from the `text` (or `content`) payload field.
+pub async fn search(
+    port: u16,
+    collection: &str,
+    vector: &[f32],
+    limit: usize,
+) -> Result<Vec<ScoredChunk>, String> {
+    let resp = reqwest::Client::new()
+        .post(format!("{}/collections/{}/points/search", base_url(port), collection))
+        .json(&serde_json::json!({ "vector": vector, "limit": limit, "with_payload": true }))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    let json: serde_json::Value = check(resp).await?.json().await.map_err(|e| e.to_string())?;
+    Ok(json["result"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|point| {
+            let payload = &point["payload"];
+            let text = payload["text"].as_str().or_else(|| payload["content"].as_str())?;
+            Some(ScoredChunk {
+                collection: collection.to_string(),
+                id: point["id"].to_string().trim_matches('"').to_string(),
+                score: point["score"].as_f64().unwrap_or(0.0) as f32,
+                text: text.to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Snapshot `collection` and download it into `dir`.
+pub async fn snapshot(port: u16, collection: &str, dir: &Path) -> Result<SnapshotFile, String> {
+    let client = reqwest::Client::new();
+    let base = format!("{}/collections/{}/snapshots", base_url(port), collection);
+    let resp = client.post(&base).send().await.map_err(|e| e.to_string())?;
+    let json: serde_json::Value = check(resp).await?.json().await.map_err(|e| e.to_string())?;
+    let name = json["result"]["name"]
+        .as_str()
+        .ok_or("Qdrant returned no snapshot name")?
+        .to_string();
+
+    let target_dir = dir.join(collection);
+    tokio::fs::create_dir_all(&target_dir)
+        .await
+        .map_err(|e| e.to_string())?;
+    let path = target_dir.join(&name);
+    let mut resp = check(
+        client
+            .get(format!("{}/{}", base, name))
+            .send()
+            .await
+            .map_err(|e| e.to_string())?,
+    )
+    .await?;
+    let mut file = tokio::fs::File::create(&path)
+        .await
+        .map_err(|e| e.to_string())?;
+    while let Some(chunk) = resp.chunk().await.map_err(|e| e.to_string())? {
+        file.write_all(&chunk).await.map_err(|e| e.to_string())?;
+    }
+    file.flush().await.map_err(|e| e.to_string())?;
+
+    // The local copy is the one we keep
+    if let Err(e) = client.delete(format!("{}/{}", base, name)).send().await {
+        eprintln!("[tulsbot] Failed to delete server snapshot {}: {}", name, e);
+    }
+
+    list(dir)
+        .into_iter()
+        .find(|s| s.path == path)
+        .ok_or_else(|| "Snapshot vanished after download".to_string())
+}
+
+/// Upload `file` and recover `collection` from it, replacing its data.
+pub async fn restore(port: u16, collection: &str, file: &Path) -> Result<(), String> {
+    let bytes = tokio::fs::read(file).await.map_err(|e| e.to_string())?;
+    let file_name = file
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| format!("{}.{}", collection, EXTENSION));
+    let form = reqwest::multipart::Form::new().part(
+        "snapshot",
+        reqwest::multipart::Part::bytes(bytes).file_name(file_name),
+    );
+    let resp = reqwest::Client::new()
+        .post(format!(
+            "{}/collections/{}/snapshots/upload?priority=snapshot",
+            base_url(port),
+            collection
+        ))
+        .multipart(form)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    check(resp).await.map(|_| ())
+}
+
+/// Local snapshots under `dir`, newest first.
+pub fn list(dir: &Path) -> Vec<SnapshotFile> {
+    let mut snapshots = Vec::new();
+    let Ok(collections) = std::fs::read_dir(dir) else {
+        return snapshots;
+    };
+    for collection in collections.flatten() {
+        let Ok(files) = std::fs::read_dir(collection.path()) else {
+            continue;
+        };
+        for file in files.flatten() {
+            let path = file.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(EXTENSION) {
+                continue;
+            }
+            let Ok(meta) = file.metadata() else {
+                continue;
+            };
+            let created_at = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            snapshots.push(SnapshotFile {
+                collection: collection.file_name().to_string_lossy().into_owned(),
+                name: file.file_name().to_string_lossy().into_owned(),
+                path,
+                size: meta.len(),
+                created_at,
+            });
+        }
+    }
+    snapshots.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+    snapshots
+}
+
+/// Delete all but the newest `keep` snapshots of each collection. Returns
+/// how many were removed.
+pub fn prune(dir: &Path, keep: usize) -> usize {
+    let mut seen: std::collections::HashMap<String, usize> = Default::default();
+    let mut removed = 0;
+    for snapshot in list(dir) {
+        let count = seen.entry(snapshot.collection.clone()).or_default();
+        *count += 1;
+        if *count > keep {
+            match std::fs::remove_file(&snapshot.path) {
+                Ok(()) => removed += 1,
+                Err(e) => eprintln!(
+                    "[tulsbot] Failed to remove snapshot {}: {}",
+                    snapshot.path.display(),
+                    e
+                ),
+            }
+        }
+    }
+    removed
+}
+
+/// Qdrant port and local snapshot dir of the active profile.
+pub fn qdrant_target(app: &AppHandle) -> Result<(u16, PathBuf), String> {
+    let state = app.state::<AppState>();
+    let profile = state.profiles.lock_or_recover().active_profile();
+    let port = service(&profile)
+        .map(|s| s.port)
+        .ok_or_else(|| format!("Profile '{}' has no Qdrant service", profile.name))?;
+    let dir = profiles::data_dir(app, &profile)?.join("snapshots").join("qdrant");
+    Ok((port, dir))
+}
+
+/// Snapshot `collection` (or every collection), then apply retention.
+pub async fn snapshot_qdrant(
+    app: &AppHandle,
+    collection: Option<String>,
+) -> Result<Vec<SnapshotFile>, String> {
+    let (port, dir) = qdrant_target(app)?;
+    let collections = match collection {
+        Some(name) => vec![name],
+        None => collections(port).await?,
+    };
+    let mut created = Vec::new();
+    for name in collections {
+        match snapshot(port, &name, &dir).await {
+            Ok(file) => created.push(file),
+            Err(e) => eprintln!("[tulsbot] Snapshot of {} failed: {}", name, e),
+        }
+    }
+    let keep = app
+        .state::<AppState>()
+        .settings
+        .lock()
+        .map(|s| s.qdrant_snapshot_keep.unwrap_or(7))
+        .unwrap_or(7)
+        .max(1);
+    prune(&dir, keep);
+    let _ = app.emit("qdrant-snapshots-changed", &created);
+    if !created.is_empty() {
+        upload_backups(app, &created).await;
+    }
+    Ok(created)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn list_qdrant_snapshots(app: AppHandle) -> Result<Vec<SnapshotFile>, AppError> {
+    let (_, dir) = qdrant_target(&app)?;
+    Ok(list(&dir))
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn create_qdrant_snapshot(
+    app: AppHandle,
+    collection: Option<String>,
+) -> Result<Vec<SnapshotFile>, AppError> {
+    Ok(snapshot_qdrant(&app, collection).await?)
+}
+
+/// Recover `collection` from a snapshot file, e.g. one copied over from
+/// another machine. Replaces the collection's current data.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn restore_snapshot(
+    app: AppHandle,
+    collection: String,
+    file: String,
+) -> Result<(), AppError> {
+    let (port, _) = qdrant_target(&app)?;
+    Ok(restore(port, &collection, std::path::Path::new(&file)).await?)
+}
+
+/// Take scheduled snapshots when the newest local one is older than the
+/// configured interval.
+pub async fn run_scheduled_snapshots(app: &AppHandle) {
+    let Some(hours) = app
+        .state::<AppState>()
+        .settings
+        .lock()
+        .ok()
+        .and_then(|s| s.qdrant_snapshot_hours)
+    else {
+        return;
+    };
+    let Ok((_, dir)) = qdrant_target(app) else {
+        return;
+    };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let newest = list(&dir).first().map(|s| s.created_at).unwrap_or(0);
+    if now.saturating_sub(newest) >= u64::from(hours.max(1)) * 3600 {
+        match snapshot_qdrant(app, None).await {
+            Ok(created) if !created.is_empty() => {
+                let body = app
+                    .state::<AppState>()
+                    .i18n
+                    .lock_or_recover()
+                    .t_count("notify-snapshots-done", created.len());
+                relay_info(app, RelayCategory::Scheduled, body);
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("[tulsbot] Scheduled Qdrant snapshot failed: {}", e),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use qrcode::{Color, EcLevel, QrCode};
+use tulsbot_macros::instrumented;
+
+use crate::error::{AppError, ErrorKind};
+
+// ── QR codes ────────────────────────────────────────────────────────────────
+//
+// Black-on-white PNGs of short texts, mostly URLs of the local stack, so the
+// dashboard can hand the Web UI address to a phone on the same network.
+
+/// Light modules around the code, as the spec asks for.
+const QUIET_ZONE: usize = 4;
+/// Pixels per module when the caller doesn't say.
+pub const DEFAULT_SCALE: u32 = 8;
+const MAX_SCALE: u32 = 32;
+
+/// `text` as a QR code (error correction level M), `scale` pixels a module.
+pub fn render(text: &str, scale: u32) -> Result<Vec<u8>, String> {
+    if text.is_empty() {
+        return Err("Nothing to encode".into());
+    }
+    if scale == 0 || scale > MAX_SCALE {
+        return Err(format!("QR scale must be 1 to {}", MAX_SCALE));
+    }
+    let code = QrCode::with_error_correction_level(text.as_bytes(), EcLevel::M)
+        .map_err(|e| format!("Can't encode as a QR code: {}", e))?;
+    let modules = code.width();
+    let colors = code.to_colors();
+    let scale = scale as usize;
+    let side = (modules + 2 * QUIET_ZONE) * scale;
+    let mut pixels = vec![255u8; side * side];
+    for (i, color) in colors.iter().enumerate() {
+        if *color != Color::Dark {
+            continue;
+        }
+        let (x, y) = (i % modules + QUIET_ZONE, i / modules + QUIET_ZONE);
+        for row in y * scale..(y + 1) * scale {
+            pixels[row * side + x * scale..row * side + (x + 1) * scale].fill(0);
+        }
+    }
+    encode_png(&pixels, side as u32)
+}
+
+fn encode_png(gray: &[u8], side: u32) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut out, side, side);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+        writer.write_image_data(gray).map_err(|e| e.to_string())?;
+    }
+    Ok(out)
+}
+
+/// `text`, usually a local URL, as a QR code PNG (an `ArrayBuffer` in the
+/// webview).
+#[instrumented]
+#[tauri::command]
+pub async fn generate_qr(
+    text: String,
+    scale: Option<u32>,
+) -> Result<tauri::ipc::Response, AppError> {
+    let png = tauri::async_runtime::spawn_blocking(move || {
+        render(&text, scale.unwrap_or(DEFAULT_SCALE))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| AppError::new(ErrorKind::InvalidInput, e))?;
+    Ok(tauri::ipc::Response::new(png))
+}
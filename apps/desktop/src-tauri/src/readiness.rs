@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+// ── Readiness checks (beyond an open port) ──────────────────────────────────
+//
+// A listening port only means the process is up; migrations or index loading
+// may still be running. Services with a readiness check are "ready" once it
+// passes; the rest are ready as soon as their port accepts connections.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum Readiness {
+    /// `GET http://127.0.0.1:<port><path>` answers with a 2xx status.
+    Http { path: String },
+}
+
+/// Whether the service on `port` passes `check`.
+pub async fn check(port: u16, check: &Readiness) -> bool {
+    match check {
+        Readiness::Http { path } => {
+            let url = format!("http://127.0.0.1:{}{}", port, path);
+            reqwest::Client::new()
+                .get(&url)
+                .timeout(std::time::Duration::from_secs(3))
+                .send()
+                .await
+                .is_ok_and(|resp| resp.status().is_success())
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use tauri::{AppHandle, Emitter, State};
+use tulsbot_macros::instrumented;
+
+use crate::settings;
+use crate::error::AppError;
+use crate::locks::LockExt;
+use crate::profiles::active_data_dir;
+use crate::state::AppState;
+
+// ── Redaction of outbound content ───────────────────────────────────────────
+
+/// Built-in rules: name and pattern.
+const BUILTINS: &[(&str, &str)] = &[
+    ("email", r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}"),
+    (
+        "api-key",
+        r"\b(?:sk-(?:ant-|proj-)?[A-Za-z0-9_-]{20,}|AKIA[0-9A-Z]{16}|gh[pousr]_[A-Za-z0-9]{36,}|xox[abpr]-[A-Za-z0-9-]{10,}|AIza[0-9A-Za-z_-]{35})\b",
+    ),
+    ("credit-card", r"\b(?:\d[ -]?){12,18}\d\b"),
+];
+
+/// Entries kept in the in-memory log.
+const LOG_LIMIT: usize = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomRule {
+    pub name: String,
+    pub pattern: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedactionConfig {
+    pub enabled: bool,
+    /// Built-in rules to apply (`email`, `api-key`, `credit-card`).
+    pub builtins: Vec<String>,
+    pub custom: Vec<CustomRule>,
+    /// Conversation id → redaction on/off, overriding `enabled`.
+    pub conversation_overrides: HashMap<String, bool>,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            builtins: BUILTINS.iter().map(|(name, _)| name.to_string()).collect(),
+            custom: Vec::new(),
+            conversation_overrides: HashMap::new(),
+        }
+    }
+}
+
+impl RedactionConfig {
+    pub fn enabled_for(&self, conversation: Option<&str>) -> bool {
+        conversation
+            .and_then(|id| self.conversation_overrides.get(id).copied())
+            .unwrap_or(self.enabled)
+    }
+}
+
+/// Reject custom rules that don't compile.
+pub fn validate(config: &RedactionConfig) -> Result<(), String> {
+    for rule in &config.custom {
+        Regex::new(&rule.pattern).map_err(|e| format!("Rule '{}': {}", rule.name, e))?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Redaction {
+    pub rule: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Unix seconds.
+    pub at: u64,
+    pub conversation: Option<String>,
+    /// Where the content was going.
+    pub destination: String,
+    pub redactions: Vec<Redaction>,
+}
+
+/// Compiled rules for one config.
+pub struct Redactor {
+    rules: Vec<(String, Regex)>,
+}
+
+/// Luhn checksum, so only plausible card numbers are redacted.
+fn luhn(candidate: &str) -> bool {
+    let digits: Vec<u32> = candidate.chars().filter_map(|c| c.to_digit(10)).collect();
+    if !(13..=19).contains(&digits.len()) {
+        return false;
+    }
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| match (i % 2, d * 2) {
+            (1, doubled) if doubled > 9 => doubled - 9,
+            (1, doubled) => doubled,
+            _ => d,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+impl Redactor {
+    pub fn new(config: &RedactionConfig) -> Result<Self, String> {
+        let mut rules = Vec::new();
+        for (name, pattern) in BUILTINS {
+            if config.builtins.iter().any(|b| b == name) {
+                rules.push((name.to_string(), Regex::new(pattern).map_err(|e| e.to_string())?));
+            }
+        }
+        for rule in &config.custom {
+            let regex =
+                Regex::new(&rule.pattern).map_err(|e| format!("Rule '{}': {}", rule.name, e))?;
+            rules.push((rule.name.clone(), regex));
+        }
+        Ok(Self { rules })
+    }
+
+    /// Replace every match with `[REDACTED:<rule>]`.
+    pub fn redact(&self, text: &str) -> (String, Vec<Redaction>) {
+        let mut text = text.to_string();
+        let mut redactions = Vec::new();
+        for (name, regex) in &self.rules {
+            let mut count = 0;
+            let replaced = regex.replace_all(&text, |caps: &regex::Captures| {
+                let found = &caps[0];
+                if name == "credit-card" && !luhn(found) {
+                    return found.to_string();
+                }
+                count += 1;
+                format!("[REDACTED:{}]", name)
+            });
+            if count > 0 {
+                text = replaced.into_owned();
+                redactions.push(Redaction { rule: name.clone(), count });
+            }
+        }
+        (text, redactions)
+    }
+
+    /// Redact the string values of a JSON `body`, leaving its structure (and
+    /// numbers, which a rule like `credit-card` would otherwise match) alone
+    /// so it stays valid JSON. A body that isn't JSON is redacted as text.
+    pub fn redact_json(&self, body: &str) -> (String, Vec<Redaction>) {
+        let Ok(mut value) = serde_json::from_str::<Value>(body) else {
+            return self.redact(body);
+        };
+        let mut redactions = Vec::new();
+        self.redact_strings(&mut value, &mut redactions);
+        if redactions.is_empty() {
+            return (body.to_string(), redactions);
+        }
+        (value.to_string(), redactions)
+    }
+
+    fn redact_strings(&self, value: &mut Value, redactions: &mut Vec<Redaction>) {
+        match value {
+            Value::String(text) => {
+                let (redacted, found) = self.redact(text);
+                if found.is_empty() {
+                    return;
+                }
+                *text = redacted;
+                for Redaction { rule, count } in found {
+                    match redactions.iter_mut().find(|r| r.rule == rule) {
+                        Some(existing) => existing.count += count,
+                        None => redactions.push(Redaction { rule, count }),
+                    }
+                }
+            }
+            Value::Array(items) => {
+                items.iter_mut().for_each(|item| self.redact_strings(item, redactions))
+            }
+            Value::Object(fields) => {
+                fields.values_mut().for_each(|field| self.redact_strings(field, redactions))
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RedactionLog {
+    entries: VecDeque<LogEntry>,
+}
+
+impl RedactionLog {
+    pub fn record(&mut self, entry: LogEntry) {
+        self.entries.push_back(entry);
+        if self.entries.len() > LOG_LIMIT {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Newest first.
+    pub fn entries(&self) -> Vec<LogEntry> {
+        self.entries.iter().rev().cloned().collect()
+    }
+}
+
+/// Redact `text` on its way to `destination` with `redact` (as text, or
+/// `Redactor::redact_json` for a JSON body) per the settings and the
+/// conversation's override, logging what was removed.
+pub fn redact_outbound(
+    state: &AppState,
+    text: &str,
+    redact: fn(&Redactor, &str) -> (String, Vec<Redaction>),
+    conversation: Option<&str>,
+    destination: &str,
+) -> Result<String, String> {
+    let config = state.settings.lock_or_recover().redaction.clone();
+    if !config.enabled_for(conversation) {
+        return Ok(text.to_string());
+    }
+    let (redacted, redactions) = redact(&Redactor::new(&config)?, text);
+    if !redactions.is_empty() {
+        let at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        state.redaction_log.lock_or_recover().record(LogEntry {
+            at,
+            conversation: conversation.map(String::from),
+            destination: destination.to_string(),
+            redactions,
+        });
+    }
+    Ok(redacted)
+}
+
+/// Preview what redaction would do to `text`, without logging it.
+#[instrumented]
+#[tauri::command]
+pub async fn redact_text(
+    state: State<'_, AppState>,
+    text: String,
+    conversation: Option<String>,
+) -> Result<(String, Vec<Redaction>), AppError> {
+    let config = state.settings.lock_or_recover().redaction.clone();
+    if !config.enabled_for(conversation.as_deref()) {
+        return Ok((text, Vec::new()));
+    }
+    Ok(Redactor::new(&config)?.redact(&text))
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn get_redaction_log(state: State<'_, AppState>) -> Result<Vec<LogEntry>, AppError> {
+    Ok(state.redaction_log.lock_or_recover().entries())
+}
+
+/// Turn redaction on or off for one conversation; `None` removes the override.
+#[instrumented]
+#[tauri::command]
+pub async fn set_conversation_redaction(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    conversation: String,
+    enabled: Option<bool>,
+) -> Result<(), AppError> {
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        let overrides = &mut settings.redaction.conversation_overrides;
+        match enabled {
+            Some(enabled) => overrides.insert(conversation, enabled),
+            None => overrides.remove(&conversation),
+        };
+        settings.clone()
+    };
+    settings::save(&active_data_dir(&app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    Ok(())
+}
@@ -0,0 +1,248 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::process::Command;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::mpsc;
+use tulsbot_macros::instrumented;
+
+use crate::conversations;
+use crate::error::AppError;
+use crate::i18n::tr;
+use crate::locks::LockExt;
+use crate::notifications::{notify, Notice, NoticeKind};
+use crate::pairing::{self, app_data_dir};
+use crate::profiles::active_data_dir;
+use crate::settings::{self, privacy_mode};
+use crate::state::AppState;
+
+// ── Notification relay to companion devices ─────────────────────────────────
+//
+// When a long job or scheduled task finishes while nobody is at the
+// desktop, its notice also goes to the paired devices. A device subscribes
+// over its encrypted channel and gets pushes while that connection stays
+// open; pushes for a device that isn't connected wait in a short queue
+// until it subscribes again. Every category is opt-in.
+
+/// Pushes kept for a device that isn't connected; older ones are dropped.
+const MAX_QUEUED: usize = 20;
+/// The desktop pings an idle subscription this often, keeping NAT entries
+/// alive and letting the device notice a desktop that went away.
+pub const KEEPALIVE_SECS: u64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RelayCategory {
+    /// Chat imports and backup uploads.
+    Jobs,
+    /// Feed digests and scheduled snapshots.
+    Scheduled,
+    /// Services going down and alert rules firing.
+    Alerts,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RelaySettings {
+    /// Categories relayed to paired devices; none until the user opts in.
+    pub categories: Vec<RelayCategory>,
+    /// Seconds without keyboard or mouse input before the user counts as
+    /// away. 0 relays whether or not anyone is at the desktop.
+    pub away_after_secs: u64,
+}
+
+impl Default for RelaySettings {
+    fn default() -> Self {
+        Self { categories: Vec::new(), away_after_secs: 300 }
+    }
+}
+
+/// A notice as a device receives it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Push {
+    pub category: RelayCategory,
+    pub title: String,
+    pub body: String,
+    /// Unix seconds.
+    pub at: u64,
+}
+
+/// Open subscriptions and the pushes waiting for devices without one.
+#[derive(Default)]
+pub struct Relay {
+    subscribers: HashMap<String, mpsc::UnboundedSender<Push>>,
+    queued: HashMap<String, VecDeque<Push>>,
+}
+
+impl Relay {
+    /// Start delivering to `device`, queued pushes first. Replaces an
+    /// earlier subscription of the same device, whose receiver then ends.
+    pub fn subscribe(&mut self, device: &str) -> mpsc::UnboundedReceiver<Push> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        for push in self.queued.remove(device).unwrap_or_default() {
+            let _ = sender.send(push);
+        }
+        self.subscribers.insert(device.to_string(), sender);
+        receiver
+    }
+
+    /// Hand `push` to each of `devices`, queueing it for those not
+    /// connected.
+    pub fn send(&mut self, devices: &[String], push: &Push) {
+        for device in devices {
+            let delivered = self
+                .subscribers
+                .get(device)
+                .is_some_and(|sender| sender.send(push.clone()).is_ok());
+            if delivered {
+                continue;
+            }
+            self.subscribers.remove(device);
+            let queue = self.queued.entry(device.clone()).or_default();
+            if queue.len() == MAX_QUEUED {
+                queue.pop_front();
+            }
+            queue.push_back(push.clone());
+        }
+    }
+
+    /// Drop an unpaired device's subscription and queue.
+    pub fn forget(&mut self, device: &str) {
+        self.subscribers.remove(device);
+        self.queued.remove(device);
+    }
+}
+
+/// Send `notice` to the paired devices when its category is opted in and
+/// the user has been away from the desktop long enough.
+pub fn relay_notice(app: &AppHandle, category: RelayCategory, notice: &Notice) {
+    let state = app.state::<AppState>();
+    let away_after = state
+        .settings
+        .lock()
+        .ok()
+        .filter(|s| s.relay.categories.contains(&category))
+        .map(|s| s.relay.away_after_secs);
+    let (Some(away_after), Ok(dir)) = (away_after, app_data_dir(app)) else {
+        return;
+    };
+    let devices: Vec<String> = pairing::load_devices(&dir).into_iter().map(|d| d.id).collect();
+    if devices.is_empty() {
+        return;
+    }
+    let body = if privacy_mode(app) { tr(app, "notify-private-body") } else { notice.body.clone() };
+    let push = Push { category, title: notice.title.clone(), body, at: conversations::now() };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if away_after > 0 {
+            let idle = tauri::async_runtime::spawn_blocking(idle_secs).await;
+            // Without an idle time there is no telling; the desktop shows it
+            if idle.ok().flatten().is_none_or(|secs| secs < away_after) {
+                return;
+            }
+        }
+        app.state::<AppState>().relay.lock_or_recover().send(&devices, &push);
+    });
+}
+
+/// Relay an informational notice that only paired devices get; the
+/// desktop doesn't show it.
+pub fn relay_info(app: &AppHandle, category: RelayCategory, body: String) {
+    let title = tr(app, "notify-title");
+    let notice = Notice { kind: NoticeKind::Info, title, body, service: None, actions: Vec::new() };
+    relay_notice(app, category, &notice);
+}
+
+/// Show a notice on the desktop and relay it to paired devices.
+pub fn notify_relayed(app: &AppHandle, category: RelayCategory, notice: Notice) {
+    relay_notice(app, category, &notice);
+    notify(app, notice);
+}
+
+/// Choose which notices go to paired devices and after how many idle
+/// seconds the user counts as away.
+#[instrumented]
+#[tauri::command]
+pub async fn set_notification_relay(
+    app: AppHandle,
+    categories: Vec<RelayCategory>,
+    away_after_secs: Option<u64>,
+) -> Result<RelaySettings, AppError> {
+    let state = app.state::<AppState>();
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        let all = [RelayCategory::Jobs, RelayCategory::Scheduled, RelayCategory::Alerts];
+        let categories = all.into_iter().filter(|c| categories.contains(c)).collect();
+        settings.relay = RelaySettings {
+            categories,
+            away_after_secs: away_after_secs.unwrap_or(settings.relay.away_after_secs),
+        };
+        settings.clone()
+    };
+    settings::save(&active_data_dir(&app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    Ok(settings.relay)
+}
+
+// ── Platform: idle time ─────────────────────────────────────────────────────
+
+/// Seconds since the last keyboard or mouse input, when the OS tells.
+/// Blocking.
+#[cfg(target_os = "macos")]
+pub fn idle_secs() -> Option<u64> {
+    let out = Command::new("ioreg").args(["-c", "IOHIDSystem", "-d", "4"]).output().ok()?;
+    let text = String::from_utf8_lossy(&out.stdout);
+    // "HIDIdleTime" = 1234567890 (nanoseconds)
+    let line = text.lines().find(|line| line.contains("\"HIDIdleTime\""))?;
+    let nanos: u64 = line.rsplit('=').next()?.trim().parse().ok()?;
+    Some(nanos / 1_000_000_000)
+}
+
+#[cfg(target_os = "windows")]
+pub fn idle_secs() -> Option<u64> {
+    const SCRIPT: &str = "Add-Type -TypeDefinition 'using System.Runtime.InteropServices; \
+        public struct LastInput { public uint Size; public uint Time; } \
+        public static class Idle { [DllImport(\"user32.dll\")] \
+        public static extern bool GetLastInputInfo(ref LastInput info); }'; \
+        $info = New-Object LastInput; $info.Size = 8; \
+        [void][Idle]::GetLastInputInfo([ref]$info); \
+        [Environment]::TickCount64 % 4294967296 - $info.Time";
+    let out = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+        .output()
+        .ok()?;
+    let millis: i64 = String::from_utf8_lossy(&out.stdout).trim().parse().ok()?;
+    // The tick count wraps every 49.7 days
+    Some(millis.rem_euclid(1 << 32) as u64 / 1000)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn idle_secs() -> Option<u64> {
+    // X11 with xprintidle, else GNOME's idle monitor (works on Wayland)
+    if let Ok(out) = Command::new("xprintidle").output() {
+        if let Ok(millis) = String::from_utf8_lossy(&out.stdout).trim().parse::<u64>() {
+            return Some(millis / 1000);
+        }
+    }
+    let out = Command::new("gdbus")
+        .args([
+            "call",
+            "--session",
+            "--dest",
+            "org.gnome.Mutter.IdleMonitor",
+            "--object-path",
+            "/org/gnome/Mutter/IdleMonitor/Core",
+            "--method",
+            "org.gnome.Mutter.IdleMonitor.GetIdletime",
+        ])
+        .output()
+        .ok()?;
+    // (uint64 12345,)
+    let text = String::from_utf8_lossy(&out.stdout);
+    let millis: u64 = text
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .nth(1)?
+        .parse()
+        .ok()?;
+    Some(millis / 1000)
+}
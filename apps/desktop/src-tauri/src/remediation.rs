@@ -0,0 +1,203 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter};
+use tulsbot_macros::instrumented;
+
+use crate::{conversations, supervisor};
+use crate::audit::{self, AuditEntry};
+use crate::env::service_env;
+use crate::error::AppError;
+use crate::health::HealthState;
+use crate::locks::LockExt;
+use crate::profiles::{active_data_dir, ServiceDef};
+use crate::state::AppState;
+
+// ── Automatic recovery on repeated failures ─────────────────────────────────
+//
+// A service with a `remediation` config gets its action run once it has
+// failed `after_failures` checks in a row, at most `max_attempts` times per
+// outage and never more often than `cooldown_secs`. The counters reset once
+// the service is healthy again.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum RemediationAction {
+    /// Restart through the supervisor (restart command or container).
+    Restart,
+    /// Delete a file, e.g. a stale lock or pid file.
+    RemoveFile { path: PathBuf },
+    /// Run a command (argv).
+    Script { argv: Vec<String> },
+}
+
+impl RemediationAction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Restart => "restart",
+            Self::RemoveFile { .. } => "remove-file",
+            Self::Script { .. } => "script",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Remediation {
+    pub action: RemediationAction,
+    #[serde(default = "default_after_failures")]
+    pub after_failures: u32,
+    #[serde(default = "default_cooldown_secs")]
+    pub cooldown_secs: u64,
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+}
+
+fn default_after_failures() -> u32 {
+    3
+}
+
+fn default_cooldown_secs() -> u64 {
+    300
+}
+
+fn default_max_attempts() -> u32 {
+    3
+}
+
+#[derive(Debug, Default)]
+struct Track {
+    failures: u32,
+    attempts: u32,
+    last_attempt: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+pub struct Tracker {
+    services: HashMap<String, Track>,
+}
+
+impl Tracker {
+    /// Record a check result for `service`. Returns the action to run now,
+    /// if its failure threshold, cooldown and attempt limit allow one.
+    pub fn observe(
+        &mut self,
+        service: &ServiceDef,
+        healthy: bool,
+        now: u64,
+    ) -> Option<RemediationAction> {
+        let config = service.remediation.as_ref()?;
+        let track = self.services.entry(service.name.clone()).or_default();
+        if healthy {
+            *track = Track::default();
+            return None;
+        }
+        track.failures += 1;
+        let cooled_down = track
+            .last_attempt
+            .is_none_or(|at| now.saturating_sub(at) >= config.cooldown_secs);
+        if track.failures < config.after_failures.max(1)
+            || track.attempts >= config.max_attempts
+            || !cooled_down
+        {
+            return None;
+        }
+        track.attempts += 1;
+        track.last_attempt = Some(now);
+        Some(config.action.clone())
+    }
+
+    /// Forget all counters, e.g. after switching profiles.
+    pub fn reset(&mut self) {
+        self.services.clear();
+    }
+}
+
+/// Run `action` for `service` with its environment. Blocking.
+pub fn run(
+    service: &ServiceDef,
+    action: &RemediationAction,
+    env: &[(String, String)],
+) -> Result<String, String> {
+    match action {
+        RemediationAction::Restart => supervisor::restart(service, env).map(|_| String::new()),
+        RemediationAction::RemoveFile { path } => match std::fs::remove_file(path) {
+            Ok(()) => Ok(format!("Removed {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Ok(format!("{} was already gone", path.display()))
+            }
+            Err(e) => Err(format!("{}: {}", path.display(), e)),
+        },
+        RemediationAction::Script { argv } => supervisor::run(argv, env),
+    }
+}
+
+/// Run configured recovery actions for services that keep failing. Every
+/// action goes to the audit log and out as a `remediation-action` event.
+pub fn remediate(app: &AppHandle, state: &AppState, services: &[ServiceDef], health: &HealthState) {
+    let now = health.checked_at.unwrap_or(0);
+    let due: Vec<(ServiceDef, RemediationAction)> = {
+        let mut tracker = state.remediation.lock_or_recover();
+        services
+            .iter()
+            .zip(&health.services)
+            .filter_map(|(def, h)| {
+                tracker.observe(def, h.healthy, now).map(|action| (def.clone(), action))
+            })
+            .collect()
+    };
+    if due.is_empty() {
+        return;
+    }
+    let dir = match active_data_dir(app) {
+        Ok(dir) => dir,
+        Err(e) => {
+            eprintln!("[tulsbot] Skipping remediation: {}", e);
+            return;
+        }
+    };
+    for (service, action) in due {
+        let (app, dir) = (app.clone(), dir.clone());
+        tauri::async_runtime::spawn_blocking(move || {
+            eprintln!("[tulsbot] Remediating {}: {}", service.name, action.name());
+            let result = service_env(&app, &service.name)
+                .and_then(|env| run(&service, &action, &env));
+            let detail = match &result {
+                Ok(output) => Some(output.trim().to_string()).filter(|o| !o.is_empty()),
+                Err(e) => Some(e.clone()),
+            };
+            audit::record(
+                &dir,
+                AuditEntry {
+                    at: conversations::now(),
+                    actor: "remediation".into(),
+                    action: action.name().into(),
+                    service: Some(service.name.clone()),
+                    ok: result.is_ok(),
+                    detail,
+                },
+            );
+            let _ = app.emit(
+                "remediation-action",
+                serde_json::json!({
+                    "service": service.name,
+                    "action": action.name(),
+                    "error": result.as_ref().err(),
+                }),
+            );
+        });
+    }
+}
+
+/// Recent audit log entries, newest first.
+#[instrumented]
+#[tauri::command]
+pub async fn get_audit_log(
+    app: AppHandle,
+    limit: Option<usize>,
+) -> Result<Vec<AuditEntry>, AppError> {
+    let dir = active_data_dir(&app)?;
+    let limit = limit.unwrap_or(200);
+    Ok(tauri::async_runtime::spawn_blocking(move || audit::recent(&dir, limit))
+        .await
+        .map_err(|e| e.to_string())?)
+}
@@ -0,0 +1,255 @@
+use plotters::prelude::*;
+use std::io::Write;
+use tauri::{AppHandle, State};
+use tulsbot_macros::instrumented;
+
+use crate::profiles;
+use crate::error::AppError;
+use crate::health::HealthState;
+use crate::history::HealthSample;
+use crate::locks::LockExt;
+use crate::state::AppState;
+
+// ── Status report rendering (PNG / PDF) ─────────────────────────────────────
+
+const WIDTH: u32 = 1200;
+const HEIGHT: u32 = 800;
+const HEADER_HEIGHT: u32 = 220;
+
+const GREEN: RGBColor = RGBColor(34, 197, 94);
+const RED: RGBColor = RGBColor(239, 68, 68);
+const GREY: RGBColor = RGBColor(107, 114, 128);
+
+/// Render the current snapshot plus a per-service availability timeline
+/// into an RGB buffer.
+fn render(
+    title: &str,
+    current: &HealthState,
+    history: &[HealthSample],
+    window_secs: u64,
+    now: u64,
+) -> Result<Vec<u8>, String> {
+    let mut buf = vec![0u8; (WIDTH * HEIGHT * 3) as usize];
+    {
+        let root = BitMapBackend::with_buffer(&mut buf, (WIDTH, HEIGHT)).into_drawing_area();
+        root.fill(&WHITE).map_err(|e| e.to_string())?;
+        let (header, body) = root.split_vertically(HEADER_HEIGHT);
+
+        // Header: title and current state
+        let heading = ("sans-serif", 30).into_font().color(&BLACK);
+        let text = ("sans-serif", 20).into_font().color(&BLACK);
+        header
+            .draw_text(title, &heading, (30, 20))
+            .map_err(|e| e.to_string())?;
+        header
+            .draw_text(&format!("Overall: {}", current.overall), &text, (30, 65))
+            .map_err(|e| e.to_string())?;
+        for (i, svc) in current.services.iter().enumerate() {
+            let x = 30 + (i as i32 % 2) * 560;
+            let y = 105 + (i as i32 / 2) * 32;
+            let color = if svc.healthy { GREEN } else { RED };
+            header
+                .draw(&Circle::new((x + 8, y + 10), 8, color.filled()))
+                .map_err(|e| e.to_string())?;
+            let label = format!(
+                "{} (:{}) — {}",
+                svc.name,
+                svc.port,
+                if svc.healthy { "up" } else { "down" }
+            );
+            header
+                .draw_text(&label, &text, (x + 26, y))
+                .map_err(|e| e.to_string())?;
+        }
+
+        // Body: one timeline row per service, green while up, red while down
+        let names: Vec<&str> = current.services.iter().map(|s| s.name.as_str()).collect();
+        let rows = names.len().max(1) as f64;
+        let start = now.saturating_sub(window_secs) as f64;
+        let mut chart = ChartBuilder::on(&body)
+            .margin(30)
+            .caption(
+                format!("Availability — last {} h", window_secs / 3600),
+                ("sans-serif", 22),
+            )
+            .x_label_area_size(40)
+            .y_label_area_size(180)
+            .build_cartesian_2d(start - now as f64..0f64, 0f64..rows)
+            .map_err(|e| e.to_string())?;
+        chart
+            .configure_mesh()
+            .disable_y_mesh()
+            .y_labels(names.len().max(1))
+            .y_label_formatter(&|y| {
+                names
+                    .get(*y as usize)
+                    .map(|n| n.to_string())
+                    .unwrap_or_default()
+            })
+            .x_label_formatter(&|x| format!("{:.0} min", x / 60.0))
+            .x_desc("time")
+            .draw()
+            .map_err(|e| e.to_string())?;
+
+        for (row, name) in names.iter().enumerate() {
+            let spans = history.windows(2).filter_map(|pair| {
+                let healthy = pair[0].services.iter().find(|(n, _)| n == name)?.1;
+                let x0 = pair[0].at as f64 - now as f64;
+                let x1 = pair[1].at as f64 - now as f64;
+                let color = if healthy { GREEN } else { RED };
+                Some(Rectangle::new(
+                    [(x0, row as f64 + 0.15), (x1, row as f64 + 0.85)],
+                    color.filled(),
+                ))
+            });
+            chart.draw_series(spans).map_err(|e| e.to_string())?;
+        }
+        if history.len() < 2 {
+            body.draw_text(
+                "Not enough history yet",
+                &("sans-serif", 20).into_font().color(&GREY),
+                (WIDTH as i32 / 2 - 110, (HEIGHT - HEADER_HEIGHT) as i32 / 2),
+            )
+            .map_err(|e| e.to_string())?;
+        }
+
+        root.present().map_err(|e| e.to_string())?;
+    }
+    Ok(buf)
+}
+
+fn encode_png(rgb: &[u8]) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut out, WIDTH, HEIGHT);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+        writer.write_image_data(rgb).map_err(|e| e.to_string())?;
+    }
+    Ok(out)
+}
+
+/// A single-page PDF showing the rendered image (Flate-compressed RGB).
+fn encode_pdf(rgb: &[u8]) -> Result<Vec<u8>, String> {
+    let mut deflate = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    deflate.write_all(rgb).map_err(|e| e.to_string())?;
+    let image = deflate.finish().map_err(|e| e.to_string())?;
+
+    // Page in points: fit the image to the width of an A4 landscape page.
+    let page_w = 842.0_f64;
+    let page_h = page_w * HEIGHT as f64 / WIDTH as f64;
+    let content = format!("q {:.2} 0 0 {:.2} 0 0 cm /Im0 Do Q", page_w, page_h);
+
+    let mut pdf: Vec<u8> = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+    let mut offsets = Vec::new();
+    let mut object = |pdf: &mut Vec<u8>, body: &[u8]| {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", offsets.len()).as_bytes());
+        pdf.extend_from_slice(body);
+        pdf.extend_from_slice(b"\nendobj\n");
+    };
+
+    object(&mut pdf, b"<< /Type /Catalog /Pages 2 0 R >>");
+    object(&mut pdf, b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>");
+    object(
+        &mut pdf,
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] \
+             /Resources << /XObject << /Im0 4 0 R >> >> /Contents 5 0 R >>",
+            page_w, page_h
+        )
+        .as_bytes(),
+    );
+    let mut image_obj = format!(
+        "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB \
+         /BitsPerComponent 8 /Filter /FlateDecode /Length {} >>\nstream\n",
+        WIDTH,
+        HEIGHT,
+        image.len()
+    )
+    .into_bytes();
+    image_obj.extend_from_slice(&image);
+    image_obj.extend_from_slice(b"\nendstream");
+    object(&mut pdf, &image_obj);
+    object(
+        &mut pdf,
+        format!("<< /Length {} >>\nstream\n{}\nendstream", content.len(), content).as_bytes(),
+    );
+
+    let xref_at = pdf.len();
+    pdf.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", offsets.len() + 1).as_bytes());
+    for offset in &offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            offsets.len() + 1,
+            xref_at
+        )
+        .as_bytes(),
+    );
+    Ok(pdf)
+}
+
+/// Render a report in `format` ("png" or "pdf").
+pub fn generate(
+    format: &str,
+    title: &str,
+    current: &HealthState,
+    history: &[HealthSample],
+    window_secs: u64,
+    now: u64,
+) -> Result<Vec<u8>, String> {
+    let rgb = render(title, current, history, window_secs, now)?;
+    match format {
+        "png" => encode_png(&rgb),
+        "pdf" => encode_pdf(&rgb),
+        other => Err(format!("Unsupported report format: {}", other)),
+    }
+}
+
+/// Render current and recent health as `format` ("png" or "pdf") into the
+/// active profile's `reports` dir and return the file path, ready to attach
+/// to an incident chat.
+#[instrumented]
+#[tauri::command]
+pub async fn generate_status_report(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    format: String,
+    hours: Option<u32>,
+) -> Result<String, AppError> {
+    let format = format.to_lowercase();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| e.to_string())?
+        .as_secs();
+    let window_secs = u64::from(hours.unwrap_or(1).clamp(1, 24)) * 3600;
+    let (profile, current, samples) = {
+        let store = state.profiles.lock_or_recover();
+        let current = state.health.lock_or_recover().clone();
+        let samples = state
+            .history
+            .lock()
+            .map_err(|e| e.to_string())?
+            .since(now.saturating_sub(window_secs));
+        (store.active_profile(), current, samples)
+    };
+    let stamp = chrono::DateTime::from_timestamp(now as i64, 0)
+        .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_default();
+    let title = format!("Tulsbot status — {} — {}", profile.name, stamp);
+    let dir = profiles::data_dir(&app, &profile)?.join("reports");
+    let path = dir.join(format!("status-{}.{}", now, format));
+
+    let bytes = tauri::async_runtime::spawn_blocking(move || {
+        generate(&format, &title, &current, &samples, window_secs, now)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    std::fs::write(&path, bytes).map_err(|e| e.to_string())?;
+    Ok(path.to_string_lossy().into_owned())
+}
@@ -0,0 +1,243 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use tauri::{AppHandle, Manager};
+use tulsbot_macros::instrumented;
+
+use crate::{audit, blobs, time_tracking, usage};
+use crate::conversations::{self, Conversation};
+use crate::error::AppError;
+use crate::locks::{LOGS, LockExt};
+use crate::profiles::active_data_dir;
+use crate::state::AppState;
+
+// ── Retention (idle cleanup of stored data) ─────────────────────────────────
+//
+// Conversations, usage and time records and the append-only audit log
+// otherwise grow forever. Each store has its own age limit, `None` keeping it
+// for good; conversations with a pinned message are kept whatever their age.
+// Usage and time records are deleted from their SQLite tables; the log is
+// rewritten without its expired lines, which is what reclaims its space.
+// Blobs left unreferenced by the deletions go in the same pass.
+// Health history lives in memory and is pruned by the caller.
+
+/// Background runs are at least this far apart.
+pub const INTERVAL_SECS: u64 = 24 * 60 * 60;
+const STATE_FILE: &str = "retention.json";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionSettings {
+    /// Apply the limits daily in the background, while the app is idle.
+    pub enabled: bool,
+    /// Conversations not updated for this many days are deleted.
+    pub conversation_days: Option<u32>,
+    /// Health samples older than this many hours are dropped; the history
+    /// never holds more than a day.
+    pub health_history_hours: Option<u32>,
+    /// Applies to the usage log and the time-tracking log.
+    pub usage_days: Option<u32>,
+    pub audit_days: Option<u32>,
+}
+
+/// What a run removed, or would remove when `dry_run`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionReport {
+    pub dry_run: bool,
+    /// Unix seconds.
+    pub at: u64,
+    /// Ids of the expired conversations.
+    pub conversations: Vec<String>,
+    /// Expired conversations kept for their pinned messages.
+    pub kept_pinned: usize,
+    pub health_samples: usize,
+    pub usage_records: usize,
+    pub time_intervals: usize,
+    pub audit_entries: usize,
+    pub attachments: usize,
+    pub errors: Vec<String>,
+}
+
+/// Per-profile bookkeeping in `<data dir>/retention.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionState {
+    pub last_run: Option<u64>,
+    pub last_report: Option<RetentionReport>,
+}
+
+pub fn load_state(dir: &Path) -> RetentionState {
+    std::fs::read_to_string(dir.join(STATE_FILE))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_state(dir: &Path, state: &RetentionState) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(STATE_FILE), text).map_err(|e| e.to_string())
+}
+
+/// Oldest timestamp kept under a limit of `count` units of `unit_secs`.
+pub fn cutoff(now: u64, count: Option<u32>, unit_secs: u64) -> Option<u64> {
+    count.map(|count| now.saturating_sub(u64::from(count) * unit_secs))
+}
+
+/// Ids of conversations last updated before `before`, and how many of
+/// those are kept for their pinned messages.
+fn expired(conversations: &[Conversation], before: u64) -> (Vec<String>, usize) {
+    let stale = conversations.iter().filter(|c| c.updated_at < before);
+    let (pinned, expired): (Vec<_>, Vec<_>) =
+        stale.partition(|c| c.messages.iter().any(|m| m.pinned));
+    (expired.into_iter().map(|c| c.id.clone()).collect(), pinned.len())
+}
+
+/// Drop the lines of the JSONL log at `path` whose `at` is before `before`
+/// (only count them when `dry_run`). Lines that don't parse are kept.
+fn prune_log(path: &Path, before: u64, dry_run: bool) -> Result<usize, String> {
+    let _rewriting = LOGS.lock_or_recover();
+    let Ok(text) = std::fs::read_to_string(path) else {
+        return Ok(0);
+    };
+    let expired = |line: &str| {
+        serde_json::from_str::<serde_json::Value>(line)
+            .ok()
+            .and_then(|v| v["at"].as_u64())
+            .is_some_and(|at| at < before)
+    };
+    let kept: String = text
+        .lines()
+        .filter(|line| !expired(line))
+        .map(|line| format!("{}\n", line))
+        .collect();
+    let removed = text.lines().count() - kept.lines().count();
+    if removed > 0 && !dry_run {
+        let tmp = path.with_extension("jsonl.tmp");
+        std::fs::write(&tmp, kept).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, path).map_err(|e| e.to_string())?;
+    }
+    Ok(removed)
+}
+
+/// Apply `settings` to the stores in `dir`, or only report when `dry_run`.
+pub fn apply(
+    dir: &Path,
+    settings: &RetentionSettings,
+    now: u64,
+    dry_run: bool,
+) -> RetentionReport {
+    let mut report = RetentionReport { dry_run, at: now, ..Default::default() };
+    let mut all = conversations::list(dir);
+    if let Some(before) = cutoff(now, settings.conversation_days, 24 * 60 * 60) {
+        let (expired, kept_pinned) = expired(&all, before);
+        report.kept_pinned = kept_pinned;
+        for id in expired.iter().filter(|_| !dry_run) {
+            if let Err(e) = conversations::delete(dir, id) {
+                report.errors.push(format!("Conversation {}: {}", id, e));
+            }
+        }
+        all.retain(|c| !expired.contains(&c.id));
+        report.conversations = expired;
+    }
+
+    if let Some(before) = cutoff(now, settings.usage_days, 24 * 60 * 60) {
+        match usage::prune(dir, before, dry_run) {
+            Ok(n) => report.usage_records = n,
+            Err(e) => report.errors.push(format!("{}: {}", usage::DB_FILE, e)),
+        }
+        match time_tracking::prune(dir, before, dry_run) {
+            Ok(n) => report.time_intervals = n,
+            Err(e) => report.errors.push(format!("{}: {}", usage::DB_FILE, e)),
+        }
+    }
+    if let Some(before) = cutoff(now, settings.audit_days, 24 * 60 * 60) {
+        match prune_log(&dir.join(audit::FILE_NAME), before, dry_run) {
+            Ok(n) => report.audit_entries = n,
+            Err(e) => report.errors.push(format!("{}: {}", audit::FILE_NAME, e)),
+        }
+    }
+
+    let referenced: HashSet<String> = all
+        .into_iter()
+        .flat_map(|c| c.messages)
+        .flat_map(|m| m.attachments)
+        .map(|a| a.id)
+        .collect();
+    report.attachments = if dry_run {
+        blobs::garbage(dir, &referenced).len()
+    } else {
+        blobs::collect_garbage(dir, &referenced)
+    };
+    report
+}
+
+/// Apply the retention limits to the active profile's data, or only report
+/// what they would remove when `dry_run`.
+pub async fn apply_retention(app: &AppHandle, dry_run: bool) -> Result<RetentionReport, String> {
+    let state = app.state::<AppState>();
+    let settings = state.settings.lock_or_recover().retention.clone();
+    let dir = active_data_dir(app)?;
+    let now = conversations::now();
+    let (blocking_dir, blocking_settings) = (dir.clone(), settings.clone());
+    let mut report = tauri::async_runtime::spawn_blocking(move || {
+        apply(&blocking_dir, &blocking_settings, now, dry_run)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    if let Some(before) = cutoff(now, settings.health_history_hours, 60 * 60) {
+        report.health_samples = state.history.lock_or_recover().prune(before, dry_run);
+    }
+    if !dry_run {
+        let run = RetentionState { last_run: Some(now), last_report: Some(report.clone()) };
+        save_state(&dir, &run)?;
+    }
+    Ok(report)
+}
+
+/// What the retention limits would remove right now, without removing it.
+#[instrumented]
+#[tauri::command]
+pub async fn preview_retention(app: AppHandle) -> Result<RetentionReport, AppError> {
+    Ok(apply_retention(&app, true).await?)
+}
+
+/// Apply the retention limits now, whether or not the background job is on.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn run_retention(app: AppHandle) -> Result<RetentionReport, AppError> {
+    Ok(apply_retention(&app, false).await?)
+}
+
+/// When retention last ran in the background or by hand, and what it removed.
+#[instrumented]
+#[tauri::command]
+pub async fn get_retention_state(app: AppHandle) -> Result<RetentionState, AppError> {
+    Ok(load_state(&active_data_dir(&app)?))
+}
+
+/// Background pass when retention is enabled, a day has passed since the
+/// last run and nothing else is working.
+pub async fn run_retention_job(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    if !state.settings.lock_or_recover().retention.enabled
+        || state.active_work.load(Ordering::SeqCst) > 0
+    {
+        return;
+    }
+    let Ok(dir) = active_data_dir(app) else {
+        return;
+    };
+    let last = load_state(&dir).last_run.unwrap_or(0);
+    if conversations::now().saturating_sub(last) < INTERVAL_SECS {
+        return;
+    }
+    match apply_retention(app, false).await {
+        Ok(report) if !report.errors.is_empty() => {
+            eprintln!("[tulsbot] Retention finished with errors: {}", report.errors.join("; "))
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("[tulsbot] Retention failed: {}", e),
+    }
+}
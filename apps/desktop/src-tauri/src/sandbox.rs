@@ -0,0 +1,398 @@
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tulsbot_macros::instrumented;
+
+use crate::{conversations, users};
+use crate::audit::{self, AuditEntry};
+use crate::error::{AppError, ErrorKind};
+use crate::profiles::active_data_dir;
+
+// ── Sandboxed code execution ────────────────────────────────────────────────
+//
+// Snippets run in a fresh temp directory with a scrubbed environment, a wall
+// clock limit and an address-space limit (`ulimit -v`; Node gets a V8 heap
+// cap instead, since V8 reserves far more address space than it uses). The
+// network is cut off, writes are confined to the temp directory and the
+// user's home can't be read: with private user, mount, network and PID
+// namespaces on Linux (`unshare`, remounting everything else read-only and
+// a tmpfs over the home), and a Seatbelt profile on macOS (`sandbox-exec`).
+// Where neither works the run is refused rather than executed unconfined.
+// The snippet runs in its own process group, which a timeout kills whole.
+
+pub const DEFAULT_TIMEOUT_SECS: u64 = 10;
+pub const MAX_TIMEOUT_SECS: u64 = 120;
+pub const DEFAULT_MEMORY_MB: u64 = 512;
+/// Output kept (and streamed) per stream; the rest is drained and dropped.
+const MAX_OUTPUT: usize = 1024 * 1024;
+/// How long output is still collected once the snippet is gone; a process
+/// that escaped its group may hold the pipes open for good.
+const DRAIN_GRACE: Duration = Duration::from_secs(1);
+
+/// Later rules win: writes only to the run's directory (and the null and
+/// terminal devices), no reads under the home.
+const SEATBELT_PROFILE: &str = r#"(version 1)
+(allow default)
+(deny network*)
+(deny file-write*)
+(allow file-write* (subpath (param "SCRATCH")) (literal "/dev/null") (regex #"^/dev/(fd/|tty)"))
+(deny file-read* (subpath (param "HOME")))
+(allow file-read* (subpath (param "SCRATCH")))"#;
+
+/// Run inside the namespaces as `sh -c MOUNT_SETUP sh <scratch> <home>
+/// <command...>`: keep the run's directory writable, hide the home and make
+/// every other mount read-only, or refuse to run.
+const MOUNT_SETUP: &str = r#"scratch=$1; home=$2; shift 2
+fail() { echo "sandbox: $1" >&2; exit 125; }
+mount --bind "$scratch" "$scratch" && cd "$scratch" || fail "can't mount $scratch"
+if [ -n "$home" ] && [ -d "$home" ]; then
+  mount -t tmpfs -o ro,mode=755 tmpfs "$home" || fail "can't hide $home"
+fi
+for m in $(cut -d' ' -f5 /proc/self/mountinfo); do
+  m=$(printf '%b' "$m")
+  [ "$m" = "$scratch" ] && continue
+  mount -o remount,bind,ro "$m" 2>/dev/null || fail "can't make $m read-only"
+done
+exec "$@""#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    Python,
+    JavaScript,
+    Shell,
+}
+
+impl Language {
+    fn file_name(self) -> &'static str {
+        match self {
+            Self::Python => "main.py",
+            Self::JavaScript => "main.js",
+            Self::Shell => "main.sh",
+        }
+    }
+
+    fn command(self, memory_mb: u64) -> Vec<String> {
+        match self {
+            Self::Python => vec!["python3".into(), "-I".into()],
+            Self::JavaScript => vec!["node".into(), format!("--max-old-space-size={}", memory_mb)],
+            Self::Shell => vec!["sh".into()],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunResult {
+    pub run_id: String,
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub stdout: String,
+    pub stderr: String,
+    /// Output past `MAX_OUTPUT` was dropped.
+    pub truncated: bool,
+    pub duration_ms: u64,
+}
+
+pub struct Limits {
+    pub timeout: Duration,
+    pub memory_mb: u64,
+}
+
+/// The wrapper that confines a run in `dir`, if this platform has one.
+fn isolation(dir: &Path) -> Result<Vec<String>, String> {
+    let home = std::env::var("HOME").unwrap_or_default();
+    let scratch = dir.display().to_string();
+    if cfg!(target_os = "macos") {
+        return Ok(vec![
+            "sandbox-exec".into(),
+            "-D".into(),
+            format!("SCRATCH={}", scratch),
+            "-D".into(),
+            format!("HOME={}", home),
+            "-p".into(),
+            SEATBELT_PROFILE.into(),
+        ]);
+    }
+    if cfg!(target_os = "linux") {
+        // A private PID namespace too, so killing it takes forked children along
+        let argv = [
+            "unshare",
+            "--user",
+            "--map-root-user",
+            "--mount",
+            "--net",
+            "--pid",
+            "--fork",
+            "--kill-child",
+        ];
+        let works = Command::new(argv[0])
+            .args(&argv[1..])
+            .arg("true")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|s| s.success());
+        if works {
+            let mut argv: Vec<String> = argv.iter().map(|s| s.to_string()).collect();
+            argv.extend(["sh".into(), "-c".into(), MOUNT_SETUP.into(), "sh".into(), scratch, home]);
+            return Ok(argv);
+        }
+        return Err("Sandboxing is unavailable (unprivileged user namespaces are \
+                    disabled), so code execution is off"
+            .into());
+    }
+    Err("Sandboxed code execution is not supported on this platform".into())
+}
+
+fn argv(
+    language: Language,
+    dir: &Path,
+    file: &Path,
+    limits: &Limits,
+) -> Result<Vec<String>, String> {
+    let mut argv = isolation(dir)?;
+    // Best effort: macOS ignores -v, hence the silenced error
+    let ulimit = if language == Language::JavaScript {
+        String::new()
+    } else {
+        format!("ulimit -v {} 2>/dev/null; ", limits.memory_mb * 1024)
+    };
+    argv.extend([
+        "sh".into(),
+        "-c".into(),
+        format!("{}exec \"$@\"", ulimit),
+        "sh".into(),
+    ]);
+    argv.extend(language.command(limits.memory_mb));
+    argv.push(file.display().to_string());
+    Ok(argv)
+}
+
+/// Run `source` and wait for it, passing output chunks to `on_output` as
+/// they arrive. Blocking.
+pub fn run(
+    run_id: &str,
+    language: Language,
+    source: &str,
+    stdin: Option<&str>,
+    limits: &Limits,
+    on_output: impl Fn(Stream, &str) + Send + Sync,
+) -> Result<RunResult, String> {
+    let dir = users::scratch_dir()?.join(format!("run-{}", run_id));
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    // Seatbelt matches resolved paths (/private/var, not /var)
+    let dir = dir.canonicalize().map_err(|e| e.to_string())?;
+    let result = run_in(&dir, run_id, language, source, stdin, limits, on_output);
+    let _ = std::fs::remove_dir_all(&dir);
+    result
+}
+
+fn run_in(
+    dir: &Path,
+    run_id: &str,
+    language: Language,
+    source: &str,
+    stdin: Option<&str>,
+    limits: &Limits,
+    on_output: impl Fn(Stream, &str) + Send + Sync,
+) -> Result<RunResult, String> {
+    let file = dir.join(language.file_name());
+    std::fs::write(&file, source).map_err(|e| e.to_string())?;
+    let argv = argv(language, dir, &file, limits)?;
+
+    let started = Instant::now();
+    let mut command = Command::new(&argv[0]);
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    let mut child = command
+        .args(&argv[1..])
+        .current_dir(dir)
+        .env_clear()
+        .env("PATH", std::env::var("PATH").unwrap_or_default())
+        .env("HOME", dir)
+        .env("TMPDIR", dir)
+        .env("LANG", "C.UTF-8")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("{}: {}", argv[0], e))?;
+    if let Some(mut pipe) = child.stdin.take() {
+        // A snippet that never reads would block a large write; ignore EPIPE
+        let input = stdin.unwrap_or_default().to_string();
+        std::thread::spawn(move || {
+            let _ = pipe.write_all(input.as_bytes());
+        });
+    }
+
+    // Readers own the pipes and hand chunks over, so they can be left
+    // behind if something keeps a pipe open after the snippet is gone
+    let (chunks, received) = mpsc::channel();
+    let mut readers = 0;
+    let pipes: [(Option<Box<dyn Read + Send>>, Stream); 2] = [
+        (child.stdout.take().map(|p| Box::new(p) as _), Stream::Stdout),
+        (child.stderr.take().map(|p| Box::new(p) as _), Stream::Stderr),
+    ];
+    for (pipe, stream) in pipes {
+        let Some(mut pipe) = pipe else {
+            continue;
+        };
+        let chunks = chunks.clone();
+        readers += 1;
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            while let Ok(n) = pipe.read(&mut buf) {
+                if n == 0 || chunks.send((stream, Some(buf[..n].to_vec()))).is_err() {
+                    break;
+                }
+            }
+            let _ = chunks.send((stream, None));
+        });
+    }
+    drop(chunks);
+
+    let (mut out, mut err) = (Vec::new(), Vec::new());
+    let mut truncated = false;
+    let mut timed_out = false;
+    let mut status = None;
+    let mut exited_at = None;
+    while readers > 0 || exited_at.is_none() {
+        if exited_at.is_none() {
+            match child.try_wait() {
+                Ok(Some(done)) => {
+                    status = Some(done);
+                    exited_at = Some(Instant::now());
+                }
+                Ok(None) if started.elapsed() >= limits.timeout => {
+                    timed_out = true;
+                    kill_group(&mut child);
+                    status = child.wait().ok();
+                    exited_at = Some(Instant::now());
+                }
+                Ok(None) => {}
+                Err(_) => exited_at = Some(Instant::now()),
+            }
+        }
+        if exited_at.is_some_and(|at| at.elapsed() >= DRAIN_GRACE) {
+            break;
+        }
+        match received.recv_timeout(Duration::from_millis(20)) {
+            Ok((_, None)) => readers -= 1,
+            Ok((stream, Some(chunk))) => {
+                let kept = if stream == Stream::Stdout { &mut out } else { &mut err };
+                let room = MAX_OUTPUT.saturating_sub(kept.len()).min(chunk.len());
+                if room < chunk.len() {
+                    truncated = true;
+                }
+                if room > 0 {
+                    kept.extend_from_slice(&chunk[..room]);
+                    on_output(stream, &String::from_utf8_lossy(&chunk[..room]));
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => readers = 0,
+        }
+    }
+
+    Ok(RunResult {
+        run_id: run_id.to_string(),
+        exit_code: status.and_then(|s| s.code()),
+        timed_out,
+        stdout: String::from_utf8_lossy(&out).into_owned(),
+        stderr: String::from_utf8_lossy(&err).into_owned(),
+        truncated,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// Kill the snippet's process group, so whatever it forked goes too.
+#[cfg(unix)]
+fn kill_group(child: &mut Child) {
+    let group = format!("-{}", child.id());
+    let killed = Command::new("kill")
+        .args(["-s", "KILL", "--", &group])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success());
+    if !killed {
+        let _ = child.kill();
+    }
+}
+
+/// No process groups here (nor a sandbox to run in); kill the snippet itself.
+#[cfg(not(unix))]
+fn kill_group(child: &mut Child) {
+    let _ = child.kill();
+}
+
+/// Run a user-approved snippet in the sandbox (no network, writes only to
+/// its temp dir, no reading the home, time and memory limits). Emits
+/// `code-run-started` with the run id, then `code-output` chunks as they
+/// arrive.
+#[instrumented]
+#[tauri::command]
+pub async fn run_code(
+    app: AppHandle,
+    language: Language,
+    source: String,
+    stdin: Option<String>,
+    timeout_secs: Option<u64>,
+) -> Result<RunResult, AppError> {
+    if !cfg!(any(target_os = "linux", target_os = "macos")) {
+        let message = "Sandboxed code execution is not supported on this platform";
+        return Err(AppError::new(ErrorKind::Unsupported, message));
+    }
+    let run_id = uuid::Uuid::new_v4().to_string();
+    let limits = Limits {
+        timeout: std::time::Duration::from_secs(
+            timeout_secs
+                .unwrap_or(DEFAULT_TIMEOUT_SECS)
+                .clamp(1, MAX_TIMEOUT_SECS),
+        ),
+        memory_mb: DEFAULT_MEMORY_MB,
+    };
+    let _ = app.emit("code-run-started", serde_json::json!({ "run_id": run_id }));
+    let run_app = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let on_output = |stream, text: &str| {
+            let _ = run_app.emit(
+                "code-output",
+                serde_json::json!({ "run_id": run_id, "stream": stream, "text": text }),
+            );
+        };
+        run(&run_id, language, &source, stdin.as_deref(), &limits, on_output)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    if let Ok(dir) = active_data_dir(&app) {
+        let entry = AuditEntry {
+            at: conversations::now(),
+            actor: "user".into(),
+            action: format!("run-code:{:?}", language).to_lowercase(),
+            service: None,
+            ok: result.as_ref().is_ok_and(|r| r.exit_code == Some(0)),
+            detail: match &result {
+                Ok(r) if r.timed_out => Some("timed out".into()),
+                Ok(r) => r.exit_code.map(|code| format!("exit {}", code)),
+                Err(e) => Some(e.clone()),
+            },
+        };
+        let _ = tauri::async_runtime::spawn_blocking(move || audit::record(&dir, entry)).await;
+    }
+    Ok(result?)
+}
@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+
+// ── Secret generation ───────────────────────────────────────────────────────
+//
+// Passwords, API keys and PINs drawn from the OS random source, so nobody
+// asks a model for one and sends it through a provider's API. Characters
+// are picked by rejection sampling (no modulo bias); passwords get at least
+// one character of each class their charset has.
+
+const MIN_LENGTH: usize = 4;
+const MAX_LENGTH: usize = 256;
+
+const LOWER: &str = "abcdefghijklmnopqrstuvwxyz";
+const UPPER: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &str = "0123456789";
+const SYMBOLS: &str = "!#$%&()*+,-./:;<=>?@[]^_{|}~";
+const HEX: &str = "0123456789abcdef";
+/// Letters and digits without look-alikes (0/O, 1/l/I).
+const UNAMBIGUOUS: &str = "abcdefghijkmnopqrstuvwxyzABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SecretKind {
+    /// 20 characters of letters, digits and symbols.
+    #[default]
+    Password,
+    /// 40 letters and digits behind a `tb_` prefix.
+    ApiKey,
+    /// 6 digits.
+    Pin,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Charset {
+    /// Letters, digits and symbols.
+    Full,
+    Alphanumeric,
+    /// Letters and digits that can't be mistaken for one another.
+    Unambiguous,
+    Hex,
+    Digits,
+}
+
+impl Charset {
+    fn classes(self) -> &'static [&'static str] {
+        match self {
+            Charset::Full => &[LOWER, UPPER, DIGITS, SYMBOLS],
+            Charset::Alphanumeric => &[LOWER, UPPER, DIGITS],
+            Charset::Unambiguous => &[UNAMBIGUOUS],
+            Charset::Hex => &[HEX],
+            Charset::Digits => &[DIGITS],
+        }
+    }
+}
+
+impl SecretKind {
+    fn defaults(self) -> (usize, Charset) {
+        match self {
+            SecretKind::Password => (20, Charset::Full),
+            SecretKind::ApiKey => (40, Charset::Alphanumeric),
+            SecretKind::Pin => (6, Charset::Digits),
+        }
+    }
+
+    fn prefix(self) -> &'static str {
+        match self {
+            SecretKind::ApiKey => "tb_",
+            _ => "",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Generated {
+    pub secret: String,
+    pub kind: SecretKind,
+    pub charset: Charset,
+    /// Random characters, not counting a prefix.
+    pub length: usize,
+    pub entropy_bits: f64,
+}
+
+/// A new secret of `kind`; `length` and `charset` override its defaults.
+pub fn generate(
+    kind: SecretKind,
+    length: Option<usize>,
+    charset: Option<Charset>,
+) -> Result<Generated, String> {
+    let (default_length, default_charset) = kind.defaults();
+    let length = length.unwrap_or(default_length);
+    let charset = charset.unwrap_or(default_charset);
+    if !(MIN_LENGTH..=MAX_LENGTH).contains(&length) {
+        return Err(format!("Secrets are {} to {} characters long", MIN_LENGTH, MAX_LENGTH));
+    }
+    let classes: Vec<Vec<char>> = charset.classes().iter().map(|c| c.chars().collect()).collect();
+    let alphabet: Vec<char> = classes.concat();
+    let mut chars: Vec<char> = Vec::with_capacity(length);
+    // One of each class first, then shuffle them in among the rest
+    if kind == SecretKind::Password && length >= classes.len() {
+        for class in &classes {
+            chars.push(class[random_below(class.len())?]);
+        }
+    }
+    while chars.len() < length {
+        chars.push(alphabet[random_below(alphabet.len())?]);
+    }
+    for i in (1..chars.len()).rev() {
+        chars.swap(i, random_below(i + 1)?);
+    }
+    Ok(Generated {
+        secret: format!("{}{}", kind.prefix(), chars.into_iter().collect::<String>()),
+        kind,
+        charset,
+        length,
+        entropy_bits: length as f64 * (alphabet.len() as f64).log2(),
+    })
+}
+
+/// A uniform integer in `0..bound`.
+fn random_below(bound: usize) -> Result<usize, String> {
+    let bound = bound as u64;
+    // Largest multiple of `bound` a u64 holds; values above it would bias
+    let zone = u64::MAX - u64::MAX % bound;
+    loop {
+        let mut bytes = [0u8; 8];
+        getrandom::fill(&mut bytes).map_err(|e| format!("No OS randomness: {}", e))?;
+        let value = u64::from_le_bytes(bytes);
+        if value < zone {
+            return Ok((value % bound) as usize);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::typing;
+
+// ── Reading the selection of the frontmost app ──────────────────────────────
+//
+// Lets "explain this" work on whatever is selected without the user copying
+// it first. The selection is read through accessibility where the OS offers
+// it: the focused element's `AXSelectedText` on macOS (Accessibility
+// permission), UI Automation's text pattern on Windows, and the PRIMARY
+// selection on Linux. Apps that don't expose it (many Electron and
+// terminal apps) get a simulated copy, with the clipboard's text put back
+// afterwards. Blocking, so call from a blocking task.
+
+/// Time for the app to fill the clipboard after the copy keystroke.
+const COPY_DELAY: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SelectionSource {
+    /// macOS and Windows.
+    #[cfg_attr(not(any(target_os = "macos", windows)), allow(dead_code))]
+    Accessibility,
+    /// The X11/Wayland PRIMARY selection.
+    Primary,
+    /// A simulated copy.
+    Copy,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SelectedText {
+    pub text: String,
+    pub source: SelectionSource,
+}
+
+/// Selected text of the app with `target_pid`, or of the frontmost app.
+/// Falls back to a simulated copy when `copy_fallback` and the app doesn't
+/// expose its selection; `None` when nothing is selected.
+pub fn read(target_pid: Option<u32>, copy_fallback: bool) -> Option<SelectedText> {
+    let found = accessible(target_pid)
+        .filter(|text| !text.trim().is_empty())
+        .map(|text| SelectedText { text, source: accessible_source() });
+    if found.is_some() || !copy_fallback {
+        return found;
+    }
+    copied().map(|text| SelectedText { text, source: SelectionSource::Copy })
+}
+
+/// Copy the selection and read it off the clipboard, restoring the
+/// clipboard's previous text.
+fn copied() -> Option<String> {
+    let saved = typing::read_clipboard().filter(|s| !s.is_empty());
+    // Cleared first so a copy with nothing selected isn't mistaken for one
+    typing::write_clipboard("").ok()?;
+    let copied = typing::send_command_key('c').ok().and_then(|_| {
+        std::thread::sleep(COPY_DELAY);
+        typing::read_clipboard()
+    });
+    let _ = typing::write_clipboard(saved.as_deref().unwrap_or(""));
+    copied.filter(|text| !text.trim().is_empty())
+}
+
+#[cfg(target_os = "macos")]
+fn accessible_source() -> SelectionSource {
+    SelectionSource::Accessibility
+}
+
+#[cfg(target_os = "macos")]
+fn accessible(target_pid: Option<u32>) -> Option<String> {
+    let process = match target_pid {
+        Some(pid) => format!("first application process whose unix id is {}", pid),
+        None => "first application process whose frontmost is true".to_string(),
+    };
+    let script = format!(
+        r#"tell application "System Events"
+    set p to {}
+    return value of attribute "AXSelectedText" of (value of attribute "AXFocusedUIElement" of p)
+end tell"#,
+        process
+    );
+    typing::osascript(&script)
+        .ok()
+        .map(|out| out.trim_end_matches('\n').to_string())
+        .filter(|text| text != "missing value")
+}
+
+#[cfg(target_os = "windows")]
+fn accessible_source() -> SelectionSource {
+    SelectionSource::Accessibility
+}
+
+/// UI Automation reads the focused element, so `target_pid` isn't needed.
+#[cfg(target_os = "windows")]
+fn accessible(_target_pid: Option<u32>) -> Option<String> {
+    const SCRIPT: &str = r#"
+Add-Type -AssemblyName UIAutomationClient, UIAutomationTypes
+$el = [System.Windows.Automation.AutomationElement]::FocusedElement
+$p = $null
+if ($el -and $el.TryGetCurrentPattern([System.Windows.Automation.TextPattern]::Pattern, [ref]$p)) {
+    ($p.GetSelection() | ForEach-Object { $_.GetText(-1) }) -join ''
+}"#;
+    typing::powershell(SCRIPT, None)
+        .ok()
+        .map(|out| out.trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn accessible_source() -> SelectionSource {
+    SelectionSource::Primary
+}
+
+/// Whatever is selected anywhere is the PRIMARY selection, so
+/// `target_pid` isn't needed.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn accessible(_target_pid: Option<u32>) -> Option<String> {
+    let mut command = if typing::wayland() {
+        let mut command = std::process::Command::new("wl-paste");
+        command.args(["--primary", "--no-newline"]);
+        command
+    } else {
+        let mut command = std::process::Command::new("xclip");
+        command.args(["-selection", "primary", "-o"]);
+        command
+    };
+    typing::run(&mut command).ok()
+}
@@ -0,0 +1,265 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tulsbot_macros::instrumented;
+
+use crate::alerts::AlertSettings;
+use crate::app_rules::{self, AppRule};
+use crate::backup::BackupTarget;
+use crate::blur::BlurBehavior;
+use crate::budgets::Budget;
+use crate::calendar::CalendarSettings;
+use crate::chat_probe::ChatProbeSettings;
+use crate::context::apply_app_rules;
+use crate::context_builder::ContextSettings;
+use crate::conversations::ConversationConfig;
+use crate::dock::{Dock, PopoverSize, SizePreset};
+use crate::embeddings::Provider;
+use crate::env::ServiceEnv;
+use crate::error::AppError;
+use crate::external::Endpoint;
+use crate::feeds::FeedSettings;
+use crate::focus::FocusSettings;
+use crate::guardrails::{self, GuardrailSettings};
+use crate::hooks::Hook;
+use crate::i18n::{apply_locale, status_tooltip, tr};
+use crate::lan::LanSettings;
+use crate::layouts::Layout;
+use crate::locks::LockExt;
+use crate::pipeline::SummarizeConfig;
+use crate::profiles::active_data_dir;
+use crate::redaction::{self, RedactionConfig};
+use crate::relay::RelaySettings;
+use crate::retention::RetentionSettings;
+use crate::shortcuts::ShortcutSettings;
+use crate::state::AppState;
+use crate::sync::SyncSettings;
+use crate::themes::{refresh_theme, ThemeChoice};
+use crate::transforms::TransformSettings;
+use crate::tray::{refresh_tray_menu, TraySettings};
+use crate::weather::WeatherSettings;
+use crate::windows::register_shortcuts;
+
+// ── Persisted user settings ─────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Run without any webviews: tray, health monitor and commands only.
+    pub headless: bool,
+    /// Watch the frontmost app and emit `active-context-changed`.
+    pub track_active_context: bool,
+    /// Include the frontmost document path (and its project) in the context.
+    pub share_document_path: bool,
+    /// Provider and system prompt per frontmost app; the first match applies.
+    pub app_rules: Vec<AppRule>,
+    /// UI language as a BCP 47 tag; `None` follows the OS.
+    pub locale: Option<String>,
+    pub theme: ThemeChoice,
+    /// Never show desktop notifications.
+    pub mute_notifications: bool,
+    /// Deliver notifications even while the OS is in Do Not Disturb.
+    pub ignore_dnd: bool,
+    /// Length of focus sessions and what they silence.
+    pub focus: FocusSettings,
+    /// Snapshot every Qdrant collection this often; `None` disables it.
+    pub qdrant_snapshot_hours: Option<u32>,
+    /// Local snapshots kept per collection (default 7).
+    pub qdrant_snapshot_keep: Option<usize>,
+    /// Where new snapshots are copied after they are taken.
+    pub backup_targets: Vec<BackupTarget>,
+    /// Embedding providers to try, in order; empty uses the default order.
+    pub embedding_providers: Vec<Provider>,
+    /// Local embedding model id; `None` uses the default model.
+    pub embedding_model: Option<String>,
+    /// Local summarization of large content before it is sent out.
+    pub summarize: SummarizeConfig,
+    /// Redaction applied to content leaving the machine.
+    pub redaction: RedactionConfig,
+    /// Filters applied to responses before they are saved or shown.
+    pub guardrails: GuardrailSettings,
+    /// Post-processing run on responses before they are stored.
+    pub transforms: TransformSettings,
+    /// Provider, model and temperature for conversations that don't set
+    /// their own.
+    pub conversation_defaults: ConversationConfig,
+    /// Prompt budget, system prompt and retrieval for the context builder.
+    pub context: ContextSettings,
+    /// Id of the stored system prompt used where a conversation picks none.
+    pub default_system_prompt: Option<String>,
+    /// Monthly token / spend limits per provider.
+    pub budgets: Vec<Budget>,
+    /// Credential label used per provider when a conversation doesn't pick
+    /// one; missing providers use `default`.
+    pub provider_credentials: BTreeMap<String, String>,
+    /// Keep recent proxied requests and responses for the inspector.
+    pub proxy_trace: bool,
+    /// Longest timeout a proxied request may ask for, in milliseconds;
+    /// `None` allows up to 10 minutes.
+    pub proxy_max_timeout_ms: Option<u64>,
+    /// Gzip large proxied request bodies. Only for backends that accept
+    /// compressed requests.
+    pub proxy_compress_requests: bool,
+    /// Answer proxy and provider calls from fixtures. Only honored in
+    /// builds with the `mock-backend` feature.
+    pub mock_backend: bool,
+    /// External endpoints probed for latency; empty probes every provider
+    /// with a saved credential.
+    pub external_probes: Vec<Endpoint>,
+    /// Scripts and HTTP hooks run around health polls.
+    pub health_hooks: Vec<Hook>,
+    /// Scheduled end-to-end probe of the chat pipeline.
+    pub chat_probe: ChatProbeSettings,
+    /// Alert rules over health and system metrics, and their silences.
+    pub alerts: AlertSettings,
+    /// Environment passed to each service's processes.
+    pub service_env: ServiceEnv,
+    /// Services, ports and certificate for LAN access, while it is on.
+    pub lan: LanSettings,
+    /// Which notices go to paired devices while the user is away.
+    pub relay: RelaySettings,
+    /// Repository roots the assistant may read with the git tools.
+    pub git_roots: Vec<PathBuf>,
+    /// Sources for `get_upcoming_events`.
+    pub calendar: CalendarSettings,
+    /// Place, units and provider for `get_weather` and the feed digest.
+    pub weather: WeatherSettings,
+    /// Feed poll interval and digest schedule.
+    pub feeds: FeedSettings,
+    /// Popover docking per monitor name; monitors missing here float it.
+    pub popover_dock: BTreeMap<String, Dock>,
+    /// Size of the floating popover.
+    pub popover_size: SizePreset,
+    /// Last size the popover was resized to by hand, per monitor name;
+    /// overrides the preset there.
+    pub popover_sizes: BTreeMap<String, PopoverSize>,
+    /// Clicks pass through the picture-in-picture response window.
+    pub pip_click_through: bool,
+    /// Saved window arrangements, applied from the tray.
+    pub window_layouts: Vec<Layout>,
+    /// Global shortcut bindings.
+    pub shortcuts: ShortcutSettings,
+    /// What clicking and double-clicking the tray icon do.
+    pub tray: TraySettings,
+    /// What each window does on blur, by label; missing windows use the
+    /// default (the popover hides, others stay).
+    pub blur_behavior: BTreeMap<String, BlurBehavior>,
+    /// Presenting/streaming: windows hidden from screen capture and
+    /// notifications shown without their text.
+    pub privacy_mode: bool,
+    /// macOS: live in the menu bar, with a Dock icon only while the
+    /// dashboard is open.
+    pub menu_bar_only: bool,
+    /// Conversation sync folder and interval.
+    pub sync: SyncSettings,
+    /// Age limits for stored data and the background cleanup.
+    pub retention: RetentionSettings,
+    /// Relaunch the app after a crash, through a watchdog process.
+    pub watchdog: bool,
+}
+
+const FILE_NAME: &str = "settings.json";
+
+/// Load settings from `dir`, falling back to defaults if the file is missing
+/// or unreadable.
+pub fn load(dir: &Path) -> Settings {
+    let path = dir.join(FILE_NAME);
+    match std::fs::read_to_string(&path) {
+        Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+            eprintln!("[tulsbot] Ignoring malformed {}: {}", path.display(), e);
+            Settings::default()
+        }),
+        Err(_) => Settings::default(),
+    }
+}
+
+pub fn save(dir: &Path, settings: &Settings) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let text = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(FILE_NAME), text).map_err(|e| e.to_string())
+}
+
+pub fn privacy_mode(app: &AppHandle) -> bool {
+    app.state::<AppState>().settings.lock().map(|s| s.privacy_mode).unwrap_or(false)
+}
+
+/// While presenting or streaming: keep our windows out of screen capture,
+/// show notifications without their text, and mark the tray. The UI blurs
+/// conversation content on `privacy-mode-changed`.
+#[instrumented]
+#[tauri::command]
+pub async fn set_privacy_mode(app: AppHandle, enabled: bool) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        settings.privacy_mode = enabled;
+        settings.clone()
+    };
+    save(&active_data_dir(&app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    for window in app.webview_windows().values() {
+        if let Err(e) = window.set_content_protected(enabled) {
+            eprintln!("[tulsbot] Failed to protect window {}: {}", window.label(), e);
+        }
+    }
+    refresh_tray_menu(&app);
+    if let Some(tray) = app.tray_by_id("main-tray") {
+        let overall = state.health.lock_or_recover().overall.clone();
+        let tooltip = if overall.is_empty() {
+            tr(&app, "tray-tooltip")
+        } else {
+            status_tooltip(&app, &overall)
+        };
+        let _ = tray.set_tooltip(Some(&tooltip));
+    }
+    let _ = app.emit("privacy-mode-changed", enabled);
+    Ok(())
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn get_settings(state: State<'_, AppState>) -> Result<Settings, AppError> {
+    let settings = state.settings.lock_or_recover();
+    Ok(settings.clone())
+}
+
+/// Persist new settings and broadcast them. Changing `headless` takes effect
+/// on the next launch.
+#[instrumented]
+#[tauri::command]
+pub async fn set_settings(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    mut settings: Settings,
+) -> Result<(), AppError> {
+    redaction::validate(&settings.redaction)?;
+    guardrails::validate(&settings.guardrails)?;
+    app_rules::validate(&settings.app_rules)?;
+    if let Some(location) = settings.weather.location.take() {
+        location.validate()?;
+        settings.weather.location = Some(location.coarse());
+    }
+    save(&active_data_dir(&app)?, &settings)?;
+    let previous = std::mem::replace(
+        &mut *state.settings.lock_or_recover(),
+        settings.clone(),
+    );
+    if previous.locale != settings.locale {
+        apply_locale(&app, settings.locale.as_deref())?;
+    }
+    if previous.shortcuts != settings.shortcuts {
+        register_shortcuts(&app)?;
+    }
+    if !settings.proxy_trace {
+        state.proxy_trace.lock_or_recover().clear();
+    }
+    if previous.app_rules != settings.app_rules {
+        apply_app_rules(&app);
+    }
+    let _ = app.emit("settings-changed", &settings);
+    if previous.theme != settings.theme {
+        refresh_theme(&app).await;
+    }
+    Ok(())
+}
@@ -0,0 +1,307 @@
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::atomic::Ordering;
+use tauri::{AppHandle, Emitter, Manager};
+use tulsbot_macros::instrumented;
+
+use crate::conversations;
+use crate::audit::{self, AuditEntry};
+use crate::error::{AppError, ErrorKind};
+use crate::profiles::active_data_dir;
+use crate::state::AppState;
+use crate::supervisor::{self, find_service};
+use crate::tray::begin_work;
+
+// ── Dependency detection and guided installation ────────────────────────────
+//
+// The setup wizard checks for the tools the stack needs and installs missing
+// ones through the platform package manager: Homebrew on macOS, Scoop on
+// Windows, apt or dnf on Linux (through pkexec, which asks for the password
+// in a system dialog).
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageManager {
+    Homebrew,
+    Scoop,
+    Apt,
+    Dnf,
+}
+
+struct Dependency {
+    name: &'static str,
+    /// Command printing the version; its success means installed.
+    verify: &'static [&'static str],
+    homebrew: &'static str,
+    scoop: &'static str,
+    apt: &'static str,
+    dnf: &'static str,
+}
+
+const DEPENDENCIES: &[Dependency] = &[
+    Dependency {
+        name: "docker",
+        verify: &["docker", "--version"],
+        homebrew: "--cask docker",
+        scoop: "docker",
+        apt: "docker.io",
+        dnf: "moby-engine",
+    },
+    Dependency {
+        name: "git",
+        verify: &["git", "--version"],
+        homebrew: "git",
+        scoop: "git",
+        apt: "git",
+        dnf: "git",
+    },
+    Dependency {
+        name: "node",
+        verify: &["node", "--version"],
+        homebrew: "node",
+        scoop: "nodejs-lts",
+        apt: "nodejs",
+        dnf: "nodejs",
+    },
+    Dependency {
+        name: "ollama",
+        verify: &["ollama", "--version"],
+        homebrew: "ollama",
+        scoop: "ollama",
+        apt: "",
+        dnf: "",
+    },
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyStatus {
+    pub name: String,
+    pub installed: bool,
+    /// First line of the version output.
+    pub version: Option<String>,
+    /// Whether the detected package manager can install it.
+    pub installable: bool,
+}
+
+fn on_path(program: &str) -> bool {
+    Command::new(program)
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok()
+}
+
+/// The platform package manager, if one is installed. Blocking.
+pub fn detect_package_manager() -> Option<PackageManager> {
+    if cfg!(target_os = "macos") {
+        on_path("brew").then_some(PackageManager::Homebrew)
+    } else if cfg!(windows) {
+        // Scoop is a PowerShell script behind a .cmd shim
+        Command::new("cmd")
+            .args(["/C", "scoop", "--version"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|s| s.success())
+            .then_some(PackageManager::Scoop)
+    } else if on_path("apt-get") {
+        Some(PackageManager::Apt)
+    } else if on_path("dnf") {
+        Some(PackageManager::Dnf)
+    } else {
+        None
+    }
+}
+
+fn find(name: &str) -> Result<&'static Dependency, String> {
+    DEPENDENCIES
+        .iter()
+        .find(|d| d.name == name)
+        .ok_or_else(|| format!("Unknown dependency: {}", name))
+}
+
+fn package(dependency: &Dependency, manager: PackageManager) -> Option<&'static str> {
+    let package = match manager {
+        PackageManager::Homebrew => dependency.homebrew,
+        PackageManager::Scoop => dependency.scoop,
+        PackageManager::Apt => dependency.apt,
+        PackageManager::Dnf => dependency.dnf,
+    };
+    (!package.is_empty()).then_some(package)
+}
+
+/// Installed state of one dependency. Blocking.
+pub fn check(name: &str, manager: Option<PackageManager>) -> Result<DependencyStatus, String> {
+    let dependency = find(name)?;
+    let (program, args) = dependency.verify.split_first().ok_or("Empty verify command")?;
+    let output = Command::new(program).args(args).output().ok().filter(|o| o.status.success());
+    let version = output.as_ref().and_then(|o| {
+        let text = String::from_utf8_lossy(&o.stdout);
+        text.lines().next().map(|l| l.trim().to_string())
+    });
+    Ok(DependencyStatus {
+        name: dependency.name.to_string(),
+        installed: output.is_some(),
+        version,
+        installable: manager.is_some_and(|m| package(dependency, m).is_some()),
+    })
+}
+
+/// Every known dependency. Blocking.
+pub fn check_all(manager: Option<PackageManager>) -> Vec<DependencyStatus> {
+    DEPENDENCIES
+        .iter()
+        .filter_map(|d| check(d.name, manager).ok())
+        .collect()
+}
+
+fn install_command(manager: PackageManager, package: &str) -> Vec<String> {
+    let package: Vec<String> = package.split_whitespace().map(String::from).collect();
+    let base: &[&str] = match manager {
+        PackageManager::Homebrew => &["brew", "install"],
+        PackageManager::Scoop => &["cmd", "/C", "scoop", "install"],
+        PackageManager::Apt => &["pkexec", "apt-get", "install", "-y"],
+        PackageManager::Dnf => &["pkexec", "dnf", "install", "-y"],
+    };
+    base.iter().map(|s| s.to_string()).chain(package).collect()
+}
+
+/// Install `name` with `manager`, passing each output line to `on_line`,
+/// then check it again. Blocking.
+pub fn install(
+    name: &str,
+    manager: PackageManager,
+    on_line: impl Fn(&str) + Send + Sync,
+) -> Result<DependencyStatus, String> {
+    let dependency = find(name)?;
+    let package = package(dependency, manager)
+        .ok_or_else(|| format!("{} can't be installed with {:?}", name, manager))?;
+    let argv = install_command(manager, package);
+    on_line(&format!("$ {}", argv.join(" ")));
+
+    let mut child = Command::new(&argv[0])
+        .args(&argv[1..])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("{}: {}", argv[0], e))?;
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    std::thread::scope(|scope| {
+        if let Some(stderr) = stderr {
+            let on_line = &on_line;
+            scope.spawn(move || {
+                for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                    on_line(&line);
+                }
+            });
+        }
+        if let Some(stdout) = stdout {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                on_line(&line);
+            }
+        }
+    });
+    let status = child.wait().map_err(|e| e.to_string())?;
+    if !status.success() {
+        return Err(format!("{} exited with {}", argv.join(" "), status));
+    }
+
+    let status = check(name, Some(manager))?;
+    if !status.installed {
+        return Err(format!(
+            "{} was installed but `{}` still fails; a new login session may be needed",
+            name,
+            dependency.verify.join(" ")
+        ));
+    }
+    Ok(status)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyReport {
+    pub package_manager: Option<PackageManager>,
+    pub dependencies: Vec<DependencyStatus>,
+}
+
+/// The platform package manager and the installed state of each dependency.
+#[instrumented]
+#[tauri::command]
+pub async fn check_dependencies() -> Result<DependencyReport, AppError> {
+    Ok(tauri::async_runtime::spawn_blocking(|| {
+        let package_manager = detect_package_manager();
+        DependencyReport {
+            package_manager,
+            dependencies: check_all(package_manager),
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())?)
+}
+
+/// Install a missing dependency with the platform package manager. Output is
+/// streamed as `dependency-install-output` events; the dependency is checked
+/// again afterwards and the result emitted as `dependency-installed`.
+#[instrumented]
+#[tauri::command]
+pub async fn install_dependency(
+    app: AppHandle,
+    name: String,
+) -> Result<DependencyStatus, AppError> {
+    let state = app.state::<AppState>();
+    if state.installing.swap(true, Ordering::SeqCst) {
+        return Err(AppError::new(ErrorKind::Busy, "Another install is still running"));
+    }
+    let work = begin_work(&app);
+    let install_app = app.clone();
+    let install_name = name.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let manager = detect_package_manager()
+            .ok_or("No supported package manager found (Homebrew, Scoop, apt or dnf)")?;
+        install(&install_name, manager, |line| {
+            let _ = install_app.emit(
+                "dependency-install-output",
+                serde_json::json!({ "name": install_name, "line": line }),
+            );
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|r| r);
+    drop(work);
+    state.installing.store(false, Ordering::SeqCst);
+    if let Ok(dir) = active_data_dir(&app) {
+        let entry = AuditEntry {
+            at: conversations::now(),
+            actor: "user".into(),
+            action: format!("install:{}", name),
+            service: None,
+            ok: result.is_ok(),
+            detail: result.as_ref().err().cloned(),
+        };
+        let _ = tauri::async_runtime::spawn_blocking(move || audit::record(&dir, entry)).await;
+    }
+    let _ = app.emit(
+        "dependency-installed",
+        serde_json::json!({ "name": name, "error": result.as_ref().err() }),
+    );
+    Ok(result?)
+}
+
+/// Recent log lines for the log viewer.
+#[instrumented]
+#[tauri::command]
+pub async fn get_service_logs(
+    app: AppHandle,
+    service: String,
+    lines: Option<usize>,
+) -> Result<String, AppError> {
+    let def = find_service(&app, &service)?;
+    let lines = lines.unwrap_or(200);
+    Ok(tauri::async_runtime::spawn_blocking(move || supervisor::logs(&def, lines))
+        .await
+        .map_err(|e| e.to_string())??)
+}
@@ -0,0 +1,207 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tulsbot_macros::instrumented;
+
+use crate::{context_menu, ingest};
+use crate::error::AppError;
+use crate::pipeline::{self, Preprocessed};
+use crate::state::AppState;
+use crate::windows::open_popover_with_context;
+
+// ── Share target (activation arguments) ─────────────────────────────────────
+//
+// Other apps hand content over by launching us with
+//   --share-text <text>        shared text
+//   --share <file>...          shared files (Windows "Send to" appends paths)
+//   --ask <file>...            "Ask Tulsbot about this file" context menu
+// If an instance is already running the payload is relayed to it.
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SharePayload {
+    /// "browser", "share-target", "context-menu" or "opened".
+    pub source: String,
+    pub text: Option<String>,
+    pub url: Option<String>,
+    pub title: Option<String>,
+    pub files: Vec<String>,
+}
+
+impl SharePayload {
+    pub fn is_empty(&self) -> bool {
+        self.text.is_none() && self.url.is_none() && self.files.is_empty()
+    }
+}
+
+/// Parse share arguments from the process command line.
+pub fn from_args(args: &[String]) -> Option<SharePayload> {
+    let mut payload = SharePayload {
+        source: "share-target".into(),
+        ..Default::default()
+    };
+    let mut iter = args.iter().skip(1);
+    let mut collecting_files = false;
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--share-text" => {
+                payload.text = iter.next().cloned();
+                collecting_files = false;
+            }
+            "--share" => collecting_files = true,
+            "--ask" => {
+                payload.source = "context-menu".into();
+                collecting_files = true;
+            }
+            other if collecting_files && !other.starts_with("--") => {
+                payload.files.push(absolute(other));
+            }
+            _ => collecting_files = false,
+        }
+    }
+    (!payload.is_empty()).then_some(payload)
+}
+
+fn absolute(path: &str) -> String {
+    std::path::absolute(path)
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|_| path.to_string())
+}
+
+/// Route shared content into the popover. Files go through ingestion first;
+/// anything not coming from the browser starts a new conversation.
+pub fn open_share(app: &AppHandle, payload: SharePayload) -> Result<(), String> {
+    let config = summarize_config(app);
+    let files: Vec<_> = payload
+        .files
+        .iter()
+        .filter_map(|path| match ingest::ingest_file(std::path::Path::new(path)) {
+            Ok(mut file) => {
+                if let Some(text) = &file.text {
+                    let processed = pipeline::preprocess(text, &config);
+                    file.summarized = processed.summarized;
+                    file.text = Some(processed.text);
+                }
+                Some(file)
+            }
+            Err(e) => {
+                eprintln!("[tulsbot] Skipping shared file {}", e);
+                None
+            }
+        })
+        .collect();
+    let text = payload
+        .text
+        .as_deref()
+        .map(|text| pipeline::preprocess(text, &config).text);
+
+    let context = serde_json::json!({
+        "source": payload.source,
+        "text": text,
+        "url": payload.url,
+        "title": payload.title,
+        "files": files,
+        "new_conversation": payload.source != "browser",
+    });
+    open_popover_with_context(app, context)
+}
+
+pub fn summarize_config(app: &AppHandle) -> pipeline::SummarizeConfig {
+    app.state::<AppState>()
+        .settings
+        .lock()
+        .map(|s| s.summarize)
+        .unwrap_or_default()
+}
+
+/// Run pasted text through the outbound pipeline before it is sent to a
+/// remote provider.
+#[instrumented]
+#[tauri::command]
+pub async fn preprocess_text(app: AppHandle, text: String) -> Result<Preprocessed, AppError> {
+    let config = summarize_config(&app);
+    Ok(tauri::async_runtime::spawn_blocking(move || pipeline::preprocess(&text, &config))
+        .await
+        .map_err(|e| e.to_string())?)
+}
+
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn install_share_target() -> Result<(), AppError> {
+    Ok(install()?)
+}
+
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn uninstall_share_target() -> Result<(), AppError> {
+    Ok(uninstall()?)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn get_context_menu_installed() -> Result<bool, AppError> {
+    Ok(context_menu::is_installed())
+}
+
+/// Add "Ask Tulsbot about this file" to Finder (Quick Action), Explorer
+/// (shell verb) or Nautilus (script).
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn install_context_menu() -> Result<(), AppError> {
+    Ok(context_menu::install()?)
+}
+
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn uninstall_context_menu() -> Result<(), AppError> {
+    Ok(context_menu::uninstall()?)
+}
+
+// ── Windows "Send to" registration ──────────────────────────────────────────
+
+#[cfg(target_os = "windows")]
+fn send_to_shortcut() -> Result<std::path::PathBuf, String> {
+    let appdata = std::env::var("APPDATA").map_err(|e| e.to_string())?;
+    Ok(std::path::PathBuf::from(appdata).join("Microsoft\\Windows\\SendTo\\Tulsbot.lnk"))
+}
+
+/// Add Tulsbot to the Explorer "Send to" menu.
+#[cfg(target_os = "windows")]
+pub fn install() -> Result<(), String> {
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let link = send_to_shortcut()?;
+    let script = format!(
+        "$s = (New-Object -ComObject WScript.Shell).CreateShortcut('{}'); \
+         $s.TargetPath = '{}'; $s.Arguments = '--share'; $s.Save()",
+        link.display().to_string().replace('\'', "''"),
+        exe.display().to_string().replace('\'', "''"),
+    );
+    let status = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .status()
+        .map_err(|e| e.to_string())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err("Failed to create the Send to shortcut".into())
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub fn uninstall() -> Result<(), String> {
+    let link = send_to_shortcut()?;
+    if link.exists() {
+        std::fs::remove_file(&link).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn install() -> Result<(), String> {
+    // macOS delivers shared/opened files through the `Opened` run event.
+    Err("Share target registration is only needed on Windows".into())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn uninstall() -> Result<(), String> {
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+// ── Global shortcuts ────────────────────────────────────────────────────────
+//
+// System-wide key combinations, registered with the OS while the app runs.
+// Accelerators use the `Modifier+Key` syntax of the global shortcut plugin,
+// e.g. `CommandOrControl+Alt+Backquote`. An unset binding uses its default;
+// an empty one is not registered at all.
+//
+// A registered shortcut never reaches other apps, so anything we don't act
+// on is handed back to the OS by unregistering it. Escape is only held while
+// the popover has focus, so it can always close the popover even when the
+// webview lost track of keyboard focus, and stays free everywhere else.
+
+/// Cycles focus through the app's windows, which Alt-Tab can't always reach
+/// (the borderless popover is left out of the task switcher).
+pub const DEFAULT_CYCLE_WINDOWS: &str = "CommandOrControl+Alt+Backquote";
+
+/// Hides the popover while it has focus.
+pub const ESCAPE: &str = "Escape";
+
+/// Fixed order windows are cycled in; other windows follow by label.
+pub const WINDOW_ORDER: [&str; 3] = ["main", "chat-popover", "response-pip"];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShortcutSettings {
+    pub cycle_windows: Option<String>,
+}
+
+impl ShortcutSettings {
+    /// The accelerator for cycling windows, `None` when disabled.
+    pub fn cycle_windows(&self) -> Option<&str> {
+        match self.cycle_windows.as_deref() {
+            None => Some(DEFAULT_CYCLE_WINDOWS),
+            Some(accelerator) if accelerator.trim().is_empty() => None,
+            Some(accelerator) => Some(accelerator.trim()),
+        }
+    }
+}
+
+/// Sort key of a window label for cycling.
+fn rank(label: &str) -> (usize, &str) {
+    let position = WINDOW_ORDER.iter().position(|l| *l == label);
+    (position.unwrap_or(WINDOW_ORDER.len()), label)
+}
+
+/// The label after `current` among `labels`, wrapping around; the first one
+/// when `current` is `None` or not among them.
+pub fn next_window(labels: &[String], current: Option<&str>) -> Option<String> {
+    let mut sorted: Vec<&String> = labels.iter().collect();
+    sorted.sort_by(|a, b| rank(a).cmp(&rank(b)));
+    let index = current
+        .and_then(|c| sorted.iter().position(|l| *l == c))
+        .map_or(0, |i| (i + 1) % sorted.len());
+    sorted.get(index).map(|l| l.to_string())
+}
@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::{AppHandle, Manager};
+
+use crate::conversations::now_millis;
+use crate::downloads::download_model_with;
+use crate::health::HealthState;
+use crate::jobs::spawn_title_job;
+use crate::locks::LockExt;
+use crate::notifications::Notice;
+use crate::profiles::active_data_dir;
+use crate::state::AppState;
+use crate::windows::{ensure_window, open_pip, show_popover, PipResponse};
+
+// ── State snapshot across restarts ──────────────────────────────────────────
+//
+// Written to the profile's data dir when the app exits and read back on the
+// next start, so a restart (after an update, say) resumes where it left off:
+// the tray shows the last known health instead of "down" until the first
+// poll, held-back notices stay unread, interrupted titling jobs and model
+// downloads start again and the windows that were open reopen. A snapshot
+// only counts when it is recent; after that the world has moved on.
+
+const FILE_NAME: &str = "snapshot.json";
+/// Older snapshots are ignored.
+const MAX_AGE_SECS: u64 = 10 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Unix seconds.
+    pub saved_at: u64,
+    pub health: HealthState,
+    /// Notices held back by DND or a focus session, not yet seen.
+    #[serde(default)]
+    pub queued: Vec<Notice>,
+    /// Conversations with a titling job in flight.
+    #[serde(default)]
+    pub title_jobs: Vec<String>,
+    /// Model ids with a download in flight.
+    #[serde(default)]
+    pub model_downloads: Vec<String>,
+    /// Labels of the windows that were showing.
+    #[serde(default)]
+    pub windows: Vec<String>,
+    /// What the picture-in-picture window was showing.
+    #[serde(default)]
+    pub pip: Option<PipResponse>,
+}
+
+impl Snapshot {
+    /// Saved no more than `MAX_AGE_SECS` before `now` (Unix seconds).
+    pub fn is_fresh(&self, now: u64) -> bool {
+        now.saturating_sub(self.saved_at) <= MAX_AGE_SECS
+    }
+}
+
+/// The snapshot in `dir`, if there is a fresh one at `now`.
+pub fn load(dir: &Path, now: u64) -> Option<Snapshot> {
+    let path = dir.join(FILE_NAME);
+    let text = std::fs::read_to_string(&path).ok()?;
+    match serde_json::from_str::<Snapshot>(&text) {
+        Ok(snapshot) => snapshot.is_fresh(now).then_some(snapshot),
+        Err(e) => {
+            eprintln!("[tulsbot] Ignoring malformed {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+pub fn save(dir: &Path, snapshot: &Snapshot) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let text = serde_json::to_string(snapshot).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(FILE_NAME), text).map_err(|e| e.to_string())
+}
+
+/// Snapshot what a restart should resume into the profile's data dir.
+pub fn save_snapshot(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let mut windows: Vec<String> = app
+        .webview_windows()
+        .into_iter()
+        .filter(|(_, window)| window.is_visible().unwrap_or(false))
+        .map(|(label, _)| label)
+        .collect();
+    windows.sort();
+    let snapshot = Snapshot {
+        saved_at: now_millis() / 1000,
+        health: state.health.lock_or_recover().clone(),
+        queued: state.notifications.lock_or_recover().queued.clone(),
+        title_jobs: state.title_jobs.lock_or_recover().iter().cloned().collect(),
+        model_downloads: state.model_downloads.lock_or_recover().iter().cloned().collect(),
+        windows,
+        pip: state.pip_response.lock_or_recover().clone(),
+    };
+    let saved = active_data_dir(app).and_then(|dir| save(&dir, &snapshot));
+    if let Err(e) = saved {
+        eprintln!("[tulsbot] Failed to save state snapshot: {}", e);
+    }
+}
+
+/// Reopen the windows of `snapshot` and restart its interrupted jobs.
+pub fn resume_from_snapshot(app: &AppHandle, snapshot: Snapshot, headless: bool) {
+    if !headless {
+        for label in &snapshot.windows {
+            let shown = match label.as_str() {
+                "chat-popover" => show_popover(app).map(|_| ()),
+                "response-pip" => {
+                    tauri::async_runtime::spawn(open_pip(app.clone()));
+                    Ok(())
+                }
+                _ => ensure_window(app, label).and_then(|window| {
+                    window.show().and_then(|_| window.set_focus()).map_err(|e| e.to_string())
+                }),
+            };
+            if let Err(e) = shown {
+                eprintln!("[tulsbot] Failed to reopen window '{}': {}", label, e);
+            }
+        }
+    }
+    for id in snapshot.title_jobs {
+        spawn_title_job(app, id);
+    }
+    for id in snapshot.model_downloads {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = download_model_with(&app, &id, "model-download-progress").await {
+                eprintln!("[tulsbot] Resuming the download of {} failed: {}", id, e);
+            }
+        });
+    }
+}
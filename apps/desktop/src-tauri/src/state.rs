@@ -0,0 +1,224 @@
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize};
+use std::sync::{Arc, Mutex};
+
+use crate::accessibility::AccessibilityPrefs;
+use crate::alerts::AlertEngine;
+use crate::app_rules::AppRule;
+use crate::calc::Rates;
+use crate::context::ActiveContext;
+use crate::context_builder::BuiltContext;
+use crate::discovery::Discovery;
+use crate::embeddings::LocalEmbedder;
+use crate::focus::FocusSession;
+use crate::hardware::HardwareInfo;
+use crate::health::HealthState;
+use crate::history::HealthHistory;
+use crate::i18n::I18n;
+use crate::lan::Exposure;
+use crate::memories::Memory;
+use crate::notifications::NotificationCenter;
+use crate::pairing::PendingPairing;
+use crate::profiles::ProfileStore;
+use crate::redaction::RedactionLog;
+use crate::relay::Relay;
+use crate::remediation::Tracker;
+use crate::settings::Settings;
+use crate::themes::{self, ResolvedTheme, ThemeChoice};
+use crate::time_tracking::{GenerationGuard, TimeTracker};
+use crate::trace::ProxyTrace;
+use crate::tray::WorkGuard;
+use crate::warmup::{self, ConnectionInfo};
+use crate::weather::CachedWeather;
+use crate::windows::PipResponse;
+#[cfg(feature = "mock-backend")]
+use crate::mock;
+
+// ── App state ───────────────────────────────────────────────────────────────
+//
+// Everything the commands, the tray and the background tasks share. It is
+// managed by Tauri once at startup and reached through `app.state()`.
+
+pub struct AppState {
+    pub health: Mutex<HealthState>,
+    /// Recent health samples of the active profile, for status reports.
+    pub history: Mutex<HealthHistory>,
+    /// The active profile's memories (pending and approved).
+    pub memories: Mutex<Vec<Memory>>,
+    /// Consecutive failures and recovery attempts per service.
+    pub remediation: Mutex<Tracker>,
+    /// Lock order: `profiles` before `health`, `history` and `settings`. The monitor and
+    /// proxy both read the active profile under this lock, so switching it
+    /// swaps service set and proxy allowlist in one step.
+    pub profiles: Mutex<ProfileStore>,
+    pub settings: Mutex<Settings>,
+    /// Last frontmost app other than Tulsbot itself.
+    pub active_context: Mutex<Option<ActiveContext>>,
+    /// Rule of `Settings::app_rules` matching that app.
+    pub active_rule: Mutex<Option<AppRule>>,
+    pub i18n: Mutex<I18n>,
+    pub accessibility: Mutex<AccessibilityPrefs>,
+    pub theme: Mutex<ResolvedTheme>,
+    pub notifications: Mutex<NotificationCenter>,
+    /// GPU and memory probe results, detected on first use.
+    pub hardware: Mutex<Option<HardwareInfo>>,
+    /// Model ids with a download in flight.
+    pub model_downloads: Mutex<std::collections::HashSet<String>>,
+    /// Cancel flags of the file downloads in flight, by download id.
+    pub file_downloads: Mutex<std::collections::HashMap<String, Arc<AtomicBool>>>,
+    /// Local embedding model, loaded on first use.
+    pub embedder: Mutex<Option<Arc<LocalEmbedder>>>,
+    pub redaction_log: Mutex<RedactionLog>,
+    /// Recent proxy exchanges, while `Settings::proxy_trace` is on.
+    pub proxy_trace: Mutex<ProxyTrace>,
+    /// Shared by proxied requests, so connections to backends are reused.
+    pub proxy_clients: warmup::Clients,
+    /// Result of the last warm-up per service of the active profile.
+    pub connections: Mutex<Vec<ConnectionInfo>>,
+    #[cfg(feature = "mock-backend")]
+    pub mock: Mutex<mock::MockBackend>,
+    /// Conversations with a titling job in flight.
+    pub title_jobs: Mutex<std::collections::HashSet<String>>,
+    /// Last prompt sent to a provider, per conversation.
+    pub last_context: Mutex<std::collections::HashMap<String, BuiltContext>>,
+    /// Budget warnings already sent, as `provider:month:percent`.
+    pub budget_warnings: Mutex<std::collections::HashSet<String>>,
+    /// Context handed to the popover but not yet picked up by it.
+    pub popover_context: Mutex<Option<serde_json::Value>>,
+    /// True when running without webviews (`--headless` or the setting).
+    pub headless: AtomicBool,
+    /// The desktop shows no tray icon (GNOME without AppIndicator), so the
+    /// dashboard and the window hotkey stand in for it.
+    pub tray_missing: AtomicBool,
+    /// Set while a dependency install runs; package managers take a global lock.
+    pub installing: AtomicBool,
+    /// Set while the feeds are being polled.
+    pub polling_feeds: AtomicBool,
+    /// The popover is docked to a screen edge and stays up when it loses focus.
+    pub popover_docked: AtomicBool,
+    /// The popover is a Wayland layer surface, placed through margins.
+    pub popover_layer_surface: AtomicBool,
+    /// Unix milliseconds of the last time we moved or sized the popover;
+    /// resizes soon after are ours, not the user's.
+    pub popover_framed_at: AtomicU64,
+    /// Popover resizes so far; a size is remembered once no resize came
+    /// after it for a moment.
+    pub popover_resizes: AtomicU64,
+    /// Set while conversations are being synced.
+    pub syncing: AtomicBool,
+    /// Latest response mirrored to the picture-in-picture window.
+    pub pip_response: Mutex<Option<PipResponse>>,
+    /// Alert rule streaks and firing alerts.
+    pub alerts: Mutex<AlertEngine>,
+    /// Responses generating and long jobs running; the tray spins while > 0.
+    pub active_work: AtomicUsize,
+    /// Set while the tray spinner owns the tray icon.
+    pub tray_animating: AtomicBool,
+    /// Held while the chat webview streams a response.
+    pub streaming: Mutex<Option<(WorkGuard, GenerationGuard)>>,
+    /// Popover and generation time not yet recorded.
+    pub time_tracker: Mutex<TimeTracker>,
+    /// Unix milliseconds the health poller last finished a poll.
+    pub poll_heartbeat: AtomicU64,
+    /// The health poller missed its ticks and was restarted; cleared once it
+    /// completes a poll again.
+    pub monitor_stalled: AtomicBool,
+    /// The running focus session.
+    pub focus: Mutex<Option<FocusSession>>,
+    /// Badge-routed alerts, shown in the tray title.
+    pub alert_badge: AtomicUsize,
+    /// Last forecast fetched.
+    pub weather: Mutex<Option<CachedWeather>>,
+    /// Exchange rates for the calculator, refetched daily.
+    pub currency_rates: Mutex<Option<Rates>>,
+    /// LAN listeners, while the stack is exposed.
+    pub lan: Mutex<Option<Exposure>>,
+    /// mDNS responder, started the first time it is needed.
+    pub discovery: Mutex<Option<Discovery>>,
+    /// The offered pairing, until a device takes it.
+    pub pairing: Mutex<Option<PendingPairing>>,
+    /// The companion listener's task and the address it is bound to.
+    pub companion_listener: Mutex<Option<(IpAddr, tauri::async_runtime::JoinHandle<()>)>>,
+    /// Subscriptions of paired devices and pushes waiting for them.
+    pub relay: Mutex<Relay>,
+    /// Companion side: the subscription to the paired desktop's pushes.
+    pub desktop_follow: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+    /// Tray clicks so far; a pending single click acts only if no click
+    /// came after it.
+    pub tray_clicks: AtomicU64,
+    /// Unix milliseconds of the last tray double click and of the last time
+    /// the popover hid on blur.
+    pub tray_double_clicked_at: AtomicU64,
+    pub popover_blurred_at: AtomicU64,
+    /// The tray shows the Option-click power menu (macOS) instead of the
+    /// regular one.
+    pub tray_power_menu: AtomicBool,
+    /// The crash watchdog, while `Settings::watchdog` is on.
+    pub watchdog: Mutex<Option<std::process::Child>>,
+}
+
+/// Empty state for a fresh start; `run` fills it in from disk during setup.
+impl Default for AppState {
+    fn default() -> Self {
+        AppState {
+            health: Mutex::new(HealthState::default()),
+            history: Mutex::new(HealthHistory::default()),
+            memories: Mutex::new(Vec::new()),
+            remediation: Mutex::new(Tracker::default()),
+            profiles: Mutex::new(ProfileStore::default()),
+            settings: Mutex::new(Settings::default()),
+            active_context: Mutex::new(None),
+            active_rule: Mutex::new(None),
+            popover_context: Mutex::new(None),
+            i18n: Mutex::new(I18n::new(None)),
+            accessibility: Mutex::new(AccessibilityPrefs::default()),
+            theme: Mutex::new(themes::resolve(&ThemeChoice::default(), false, false)),
+            notifications: Mutex::new(NotificationCenter::default()),
+            hardware: Mutex::new(None),
+            model_downloads: Mutex::new(Default::default()),
+            file_downloads: Mutex::new(Default::default()),
+            embedder: Mutex::new(None),
+            redaction_log: Mutex::new(RedactionLog::default()),
+            proxy_trace: Mutex::new(ProxyTrace::default()),
+            proxy_clients: warmup::Clients::default(),
+            connections: Mutex::new(Vec::new()),
+            #[cfg(feature = "mock-backend")]
+            mock: Mutex::new(mock::MockBackend::default()),
+            title_jobs: Mutex::new(Default::default()),
+            last_context: Mutex::new(Default::default()),
+            budget_warnings: Mutex::new(Default::default()),
+            headless: AtomicBool::new(std::env::args().any(|arg| arg == "--headless")),
+            tray_missing: AtomicBool::new(false),
+            installing: AtomicBool::new(false),
+            polling_feeds: AtomicBool::new(false),
+            syncing: AtomicBool::new(false),
+            popover_docked: AtomicBool::new(false),
+            popover_layer_surface: AtomicBool::new(false),
+            popover_framed_at: AtomicU64::new(0),
+            popover_resizes: AtomicU64::new(0),
+            pip_response: Mutex::new(None),
+            alerts: Mutex::new(AlertEngine::default()),
+            active_work: AtomicUsize::new(0),
+            tray_animating: AtomicBool::new(false),
+            streaming: Mutex::new(None),
+            time_tracker: Mutex::new(TimeTracker::default()),
+            poll_heartbeat: AtomicU64::new(0),
+            monitor_stalled: AtomicBool::new(false),
+            focus: Mutex::new(None),
+            alert_badge: AtomicUsize::new(0),
+            weather: Mutex::new(None),
+            currency_rates: Mutex::new(None),
+            lan: Mutex::new(None),
+            discovery: Mutex::new(None),
+            pairing: Mutex::new(None),
+            companion_listener: Mutex::new(None),
+            relay: Mutex::new(Relay::default()),
+            desktop_follow: Mutex::new(None),
+            tray_clicks: AtomicU64::new(0),
+            tray_power_menu: AtomicBool::new(false),
+            watchdog: Mutex::new(None),
+            tray_double_clicked_at: AtomicU64::new(0),
+            popover_blurred_at: AtomicU64::new(0),
+        }
+    }
+}
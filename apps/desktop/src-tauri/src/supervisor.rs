@@ -0,0 +1,204 @@
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tulsbot_macros::instrumented;
+
+use crate::conversations;
+use crate::audit::{self, AuditEntry};
+use crate::deps::{self, ServiceGraph};
+use crate::env::service_env;
+use crate::error::AppError;
+use crate::locks::LockExt;
+use crate::profiles::{active_data_dir, ServiceDef};
+use crate::state::AppState;
+
+// ── Service supervisor (restart / logs) ─────────────────────────────────────
+//
+// A service is controlled through its explicit `restart` / `start` / `stop`
+// commands if it has them, otherwise through Docker when it names a
+// `container`. `env` is added
+// to the environment of every process started here.
+
+/// Run `argv` and return its output. Blocking.
+pub fn run(argv: &[String], env: &[(String, String)]) -> Result<String, String> {
+    let (program, args) = argv.split_first().ok_or("Empty command")?;
+    let output = Command::new(program)
+        .args(args)
+        .envs(env.iter().map(|(k, v)| (k, v)))
+        .output()
+        .map_err(|e| format!("{}: {}", program, e))?;
+    if output.status.success() {
+        // docker logs writes the container's stderr to ours
+        let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+        text.push_str(&String::from_utf8_lossy(&output.stderr));
+        Ok(text)
+    } else {
+        Err(format!(
+            "{} exited with {}: {}",
+            program,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Restart `service`. Blocking.
+pub fn restart(service: &ServiceDef, env: &[(String, String)]) -> Result<(), String> {
+    if let Some(argv) = &service.restart {
+        return run(argv, env).map(|_| ());
+    }
+    let container = service
+        .container
+        .as_ref()
+        .ok_or_else(|| format!("No restart command or container configured for {}", service.name))?;
+    run(&["docker".into(), "restart".into(), container.clone()], env).map(|_| ())
+}
+
+fn container(service: &ServiceDef) -> Result<&String, String> {
+    service
+        .container
+        .as_ref()
+        .ok_or_else(|| format!("No container configured for {}", service.name))
+}
+
+/// Start the service. Blocking.
+pub fn start(service: &ServiceDef, env: &[(String, String)]) -> Result<(), String> {
+    if let Some(argv) = &service.start {
+        return run(argv, env).map(|_| ());
+    }
+    run(&["docker".into(), "start".into(), container(service)?.clone()], env).map(|_| ())
+}
+
+/// Stop the service. Blocking.
+pub fn stop(service: &ServiceDef, env: &[(String, String)]) -> Result<(), String> {
+    if let Some(argv) = &service.stop {
+        return run(argv, env).map(|_| ());
+    }
+    run(&["docker".into(), "stop".into(), container(service)?.clone()], env).map(|_| ())
+}
+
+/// The last `lines` lines of the service's logs. Blocking.
+pub fn logs(service: &ServiceDef, lines: usize) -> Result<String, String> {
+    let argv = [
+        "docker".into(),
+        "logs".into(),
+        "--tail".into(),
+        lines.to_string(),
+        container(service)?.clone(),
+    ];
+    run(&argv, &[])
+}
+
+pub fn find_service(app: &AppHandle, name: &str) -> Result<ServiceDef, String> {
+    let state = app.state::<AppState>();
+    let profile = state.profiles.lock_or_recover().active_profile();
+    profile
+        .services
+        .into_iter()
+        .find(|s| s.name == name)
+        .ok_or_else(|| format!("Unknown service: {}", name))
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn restart_service(app: AppHandle, service: String) -> Result<(), AppError> {
+    let def = find_service(&app, &service)?;
+    let _ = app.emit("service-restarting", &service);
+    let env_app = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let env = service_env(&env_app, &def.name)?;
+        restart(&def, &env)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    if let Ok(dir) = active_data_dir(&app) {
+        let entry = AuditEntry {
+            at: conversations::now(),
+            actor: "user".into(),
+            action: "restart".into(),
+            service: Some(service.clone()),
+            ok: result.is_ok(),
+            detail: result.as_ref().err().cloned(),
+        };
+        let _ = tauri::async_runtime::spawn_blocking(move || audit::record(&dir, entry)).await;
+    }
+    let _ = app.emit(
+        "service-restarted",
+        serde_json::json!({ "service": service, "error": result.as_ref().err() }),
+    );
+    Ok(result?)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceActionResult {
+    pub service: String,
+    pub error: Option<String>,
+}
+
+/// Start every service of the active profile, dependencies first.
+#[instrumented]
+#[tauri::command]
+pub async fn start_all_services(app: AppHandle) -> Result<Vec<ServiceActionResult>, AppError> {
+    Ok(run_in_order(app, false).await?)
+}
+
+/// Stop every service of the active profile, dependents first.
+#[instrumented]
+#[tauri::command]
+pub async fn stop_all_services(app: AppHandle) -> Result<Vec<ServiceActionResult>, AppError> {
+    Ok(run_in_order(app, true).await?)
+}
+
+pub async fn run_in_order(app: AppHandle, stop: bool) -> Result<Vec<ServiceActionResult>, String> {
+    let services = {
+        let state = app.state::<AppState>();
+        let store = state.profiles.lock_or_recover();
+        store.active_profile().services
+    };
+    let mut ordered = deps::start_order(&services)?;
+    let (action, event) = if stop {
+        ordered.reverse();
+        ("stop", "service-stopping")
+    } else {
+        ("start", "service-starting")
+    };
+    let dir = active_data_dir(&app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        ordered
+            .iter()
+            .map(|service| {
+                let _ = app.emit(event, &service.name);
+                let result = service_env(&app, &service.name).and_then(|env| {
+                    if stop {
+                        self::stop(service, &env)
+                    } else {
+                        start(service, &env)
+                    }
+                });
+                audit::record(
+                    &dir,
+                    AuditEntry {
+                        at: conversations::now(),
+                        actor: "user".into(),
+                        action: action.into(),
+                        service: Some(service.name.clone()),
+                        ok: result.is_ok(),
+                        detail: result.as_ref().err().cloned(),
+                    },
+                );
+                ServiceActionResult { service: service.name.clone(), error: result.err() }
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Services, their health and dependency edges for the dashboard graph.
+#[instrumented]
+#[tauri::command]
+pub async fn get_service_graph(state: State<'_, AppState>) -> Result<ServiceGraph, AppError> {
+    let services = state.profiles.lock_or_recover().active_profile().services;
+    let health = state.health.lock_or_recover();
+    Ok(deps::graph(&services, &health.services))
+}
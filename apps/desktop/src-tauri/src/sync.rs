@@ -0,0 +1,415 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use tauri::{AppHandle, Emitter, Manager};
+use tulsbot_macros::instrumented;
+
+use crate::settings;
+use crate::conversations::{self, Conversation};
+use crate::error::AppError;
+use crate::locks::LockExt;
+use crate::profiles::active_data_dir;
+use crate::state::AppState;
+use crate::tray::begin_work;
+
+// ── Conversation sync through a user folder ─────────────────────────────────
+//
+// Devices meet in a folder the user already syncs (iCloud Drive, Dropbox,
+// Syncthing…); no server of ours is involved. Each device writes only below
+// `<folder>/tulsbot-sync/devices/<device id>/`, so the file sync tool never
+// sees two writers on one file and never produces conflicted copies.
+//
+// A sync pass merges every device's copy of each conversation with the local
+// one. Message ids are UUIDs, so messages from different devices never
+// collide and merge as a grow-only set; the conversation's own fields
+// (title, tags, config, active branch) and per-message flags take the most
+// recently updated copy. A deletion is a tombstone that wins over any
+// copy not updated after it. Attachment blobs are not synced; messages keep
+// their references and show the file as missing on other devices.
+
+const ROOT: &str = "tulsbot-sync";
+const STATE_FILE: &str = "sync-state.json";
+const TOMBSTONES: &str = "deleted.json";
+/// Tombstones are forgotten after this long; a device offline for longer
+/// brings deleted conversations back.
+const TOMBSTONE_DAYS: u64 = 90;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncSettings {
+    /// Shared folder; `None` disables sync.
+    pub folder: Option<PathBuf>,
+    /// Minutes between background passes; `None` syncs every 5 minutes.
+    pub interval_minutes: Option<u32>,
+}
+
+pub const DEFAULT_INTERVAL_MINUTES: u32 = 5;
+
+/// Per-profile bookkeeping in `<data dir>/sync-state.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncState {
+    pub device_id: String,
+    /// `updated_at` of each conversation as of the last pass, to tell local
+    /// deletions from conversations that never existed here.
+    pub synced: BTreeMap<String, u64>,
+    /// Conversation id → Unix seconds it was deleted on any device.
+    pub tombstones: BTreeMap<String, u64>,
+    pub last_sync: Option<u64>,
+    pub last_error: Option<String>,
+    pub last_report: Option<SyncReport>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncReport {
+    /// Conversations written to, or changed in, the local store.
+    pub pulled: usize,
+    /// Conversations written to this device's folder.
+    pub pushed: usize,
+    /// Conversations changed on more than one device and merged.
+    pub merged: usize,
+    /// Conversations removed here because another device deleted them.
+    pub deleted: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncStatus {
+    pub enabled: bool,
+    pub folder: Option<String>,
+    pub device_id: String,
+    /// Other devices seen in the folder.
+    pub devices: Vec<String>,
+    pub syncing: bool,
+    pub last_sync: Option<u64>,
+    pub last_error: Option<String>,
+    pub last_report: Option<SyncReport>,
+}
+
+pub fn load_state(data_dir: &Path) -> SyncState {
+    let mut state: SyncState = std::fs::read_to_string(data_dir.join(STATE_FILE))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default();
+    if state.device_id.is_empty() {
+        state.device_id = conversations::new_id();
+    }
+    state
+}
+
+pub fn save_state(data_dir: &Path, state: &SyncState) -> Result<(), String> {
+    std::fs::create_dir_all(data_dir).map_err(|e| e.to_string())?;
+    let text = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
+    std::fs::write(data_dir.join(STATE_FILE), text).map_err(|e| e.to_string())
+}
+
+fn devices_dir(folder: &Path) -> PathBuf {
+    folder.join(ROOT).join("devices")
+}
+
+/// Ids of the devices with a directory under `folder`.
+pub fn devices(folder: &Path) -> Vec<String> {
+    let mut ids: Vec<String> = std::fs::read_dir(devices_dir(folder))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .collect();
+    ids.sort();
+    ids
+}
+
+pub fn status(settings: &SyncSettings, data_dir: &Path, syncing: bool) -> SyncStatus {
+    let state = load_state(data_dir);
+    let devices = settings
+        .folder
+        .as_deref()
+        .map(devices)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|id| *id != state.device_id)
+        .collect();
+    SyncStatus {
+        enabled: settings.folder.is_some(),
+        folder: settings.folder.as_ref().map(|f| f.display().to_string()),
+        device_id: state.device_id,
+        devices,
+        syncing,
+        last_sync: state.last_sync,
+        last_error: state.last_error,
+        last_report: state.last_report,
+    }
+}
+
+/// Check that `folder` exists and is writable before enabling sync on it.
+pub fn validate_folder(folder: &Path) -> Result<(), String> {
+    if !folder.is_dir() {
+        return Err(format!("Not a folder: {}", folder.display()));
+    }
+    let probe = folder.join(ROOT).join(".write-test");
+    std::fs::create_dir_all(folder.join(ROOT))
+        .and_then(|_| std::fs::write(&probe, b"ok"))
+        .and_then(|_| std::fs::remove_file(&probe))
+        .map_err(|e| format!("Cannot write to {}: {}", folder.display(), e))
+}
+
+fn read_tombstones(dir: &Path) -> BTreeMap<String, u64> {
+    std::fs::read_to_string(dir.join(TOMBSTONES))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+/// Ordering key of a copy: the most recently updated wins, and on a tie
+/// the content decides, so every device picks the same copy.
+fn precedence(c: &Conversation) -> (u64, usize, String) {
+    (c.updated_at, c.messages.len(), serde_json::to_string(c).unwrap_or_default())
+}
+
+/// Merge two copies of one conversation.
+pub fn merge(a: &Conversation, b: &Conversation) -> Conversation {
+    let (newer, older) = if precedence(b) > precedence(a) { (b, a) } else { (a, b) };
+    let mut merged = newer.clone();
+    let missing: Vec<_> =
+        older.messages.iter().filter(|m| newer.message(&m.id).is_none()).cloned().collect();
+    if !missing.is_empty() {
+        merged.messages.extend(missing);
+        merged.messages.sort_by_key(|m| m.created_at);
+    }
+    merged.created_at = a.created_at.min(b.created_at);
+    merged
+}
+
+fn valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+fn remove_copy(dir: &Path, id: &str) {
+    let _ = std::fs::remove_file(dir.join("conversations").join(format!("{}.json", id)));
+}
+
+/// Whether the two copies differ in anything a merge would carry over.
+fn differs(a: &Conversation, b: &Conversation) -> bool {
+    serde_json::to_value(a).ok() != serde_json::to_value(b).ok()
+}
+
+/// One sync pass between the store in `data_dir` and `folder`. Blocking.
+pub fn sync(data_dir: &Path, folder: &Path, state: &mut SyncState) -> Result<SyncReport, String> {
+    validate_folder(folder)?;
+    let now = conversations::now();
+    let own_dir = devices_dir(folder).join(&state.device_id);
+    std::fs::create_dir_all(&own_dir).map_err(|e| e.to_string())?;
+    let mut report = SyncReport::default();
+
+    // Local deletions since the last pass become tombstones
+    let local: HashMap<String, Conversation> =
+        conversations::list(data_dir).into_iter().map(|c| (c.id.clone(), c)).collect();
+    for id in state.synced.keys() {
+        if !local.contains_key(id) {
+            state.tombstones.entry(id.clone()).or_insert(now);
+        }
+    }
+
+    // Every device's copies and tombstones
+    let mut remote: HashMap<String, Vec<Conversation>> = HashMap::new();
+    let mut own: HashMap<String, Conversation> = HashMap::new();
+    for device in devices(folder) {
+        let dir = devices_dir(folder).join(&device);
+        for (id, at) in read_tombstones(&dir) {
+            let entry = state.tombstones.entry(id).or_insert(at);
+            *entry = (*entry).max(at);
+        }
+        for conversation in conversations::list(&dir) {
+            if !valid_id(&conversation.id) {
+                continue;
+            }
+            if device == state.device_id {
+                own.insert(conversation.id.clone(), conversation);
+            } else {
+                remote.entry(conversation.id.clone()).or_default().push(conversation);
+            }
+        }
+    }
+
+    let mut ids: Vec<&String> = local.keys().chain(remote.keys()).chain(own.keys()).collect();
+    ids.sort();
+    ids.dedup();
+    state.synced.clear();
+    for id in ids {
+        let mut copies: Vec<&Conversation> = remote.get(id).into_iter().flatten().collect();
+        copies.extend(local.get(id));
+        let Some(first) = copies.first() else {
+            // Only our own stale copy is left
+            continue;
+        };
+        let merged = copies[1..].iter().fold((*first).clone(), |acc, c| merge(&acc, c));
+        let divergent = copies.iter().filter(|c| differs(c, &merged)).count() > 1;
+
+        let deleted_at = state.tombstones.get(id).copied();
+        if deleted_at.is_some_and(|at| at >= merged.updated_at) {
+            if local.contains_key(id) {
+                conversations::delete(data_dir, id)?;
+                report.deleted += 1;
+            }
+            remove_copy(&own_dir, id);
+            continue;
+        }
+        // Edited after the deletion: the conversation comes back
+        state.tombstones.remove(id);
+
+        if local.get(id).is_none_or(|c| differs(c, &merged)) {
+            conversations::save(data_dir, &merged)?;
+            report.pulled += 1;
+        }
+        if own.get(id).is_none_or(|c| differs(c, &merged)) {
+            conversations::save(&own_dir, &merged)?;
+            report.pushed += 1;
+        }
+        if divergent {
+            report.merged += 1;
+        }
+        state.synced.insert(id.clone(), merged.updated_at);
+    }
+
+    // Our stale copies of conversations deleted everywhere
+    for id in own.keys().filter(|id| !state.synced.contains_key(*id)) {
+        remove_copy(&own_dir, id);
+    }
+    let cutoff = now.saturating_sub(TOMBSTONE_DAYS * 24 * 60 * 60);
+    state.tombstones.retain(|_, at| *at >= cutoff);
+
+    let text = serde_json::to_string_pretty(&state.tombstones).map_err(|e| e.to_string())?;
+    std::fs::write(own_dir.join(TOMBSTONES), text).map_err(|e| e.to_string())?;
+    state.last_sync = Some(now);
+    state.last_error = None;
+    state.last_report = Some(report.clone());
+    Ok(report)
+}
+
+pub fn sync_status(app: &AppHandle) -> Result<SyncStatus, String> {
+    let state = app.state::<AppState>();
+    let settings = state.settings.lock_or_recover().sync.clone();
+    let syncing = state.syncing.load(Ordering::SeqCst);
+    Ok(status(&settings, &active_data_dir(app)?, syncing))
+}
+
+pub fn emit_sync_status(app: &AppHandle) {
+    match sync_status(app) {
+        Ok(status) => {
+            let _ = app.emit("sync-status", &status);
+        }
+        Err(e) => eprintln!("[tulsbot] Sync status unavailable: {}", e),
+    }
+}
+
+/// Run one sync pass with the configured folder, emitting `sync-status`
+/// when it starts and ends.
+pub async fn sync_conversations(app: &AppHandle) -> Result<SyncReport, String> {
+    let state = app.state::<AppState>();
+    let folder = state
+        .settings
+        .lock()
+        .map_err(|e| e.to_string())?
+        .sync
+        .folder
+        .clone()
+        .ok_or("Sync is not enabled")?;
+    let data_dir = active_data_dir(app)?;
+    if state.syncing.swap(true, Ordering::SeqCst) {
+        return Err("A sync is already running".into());
+    }
+    emit_sync_status(app);
+    let work = begin_work(app);
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let mut sync_state = load_state(&data_dir);
+        let result = sync(&data_dir, &folder, &mut sync_state);
+        if let Err(e) = &result {
+            sync_state.last_error = Some(e.clone());
+        }
+        save_state(&data_dir, &sync_state)?;
+        result
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|r| r);
+    drop(work);
+    state.syncing.store(false, Ordering::SeqCst);
+    emit_sync_status(app);
+    result
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn get_sync_status(app: AppHandle) -> Result<SyncStatus, AppError> {
+    Ok(sync_status(&app)?)
+}
+
+/// Sync conversations through `folder` (a directory the user already syncs
+/// between devices) and run a first pass right away.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn enable_sync(
+    app: AppHandle,
+    folder: String,
+    interval_minutes: Option<u32>,
+) -> Result<SyncReport, AppError> {
+    let folder = PathBuf::from(folder);
+    let probe = folder.clone();
+    tauri::async_runtime::spawn_blocking(move || validate_folder(&probe))
+        .await
+        .map_err(|e| e.to_string())??;
+    let settings = {
+        let state = app.state::<AppState>();
+        let mut settings = state.settings.lock_or_recover();
+        settings.sync.folder = Some(folder);
+        settings.sync.interval_minutes = interval_minutes.map(|m| m.max(1));
+        settings.clone()
+    };
+    settings::save(&active_data_dir(&app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    Ok(sync_conversations(&app).await?)
+}
+
+/// Stop syncing. Copies already in the folder stay there.
+#[instrumented(privileged)]
+#[tauri::command]
+pub async fn disable_sync(app: AppHandle) -> Result<(), AppError> {
+    let settings = {
+        let state = app.state::<AppState>();
+        let mut settings = state.settings.lock_or_recover();
+        settings.sync.folder = None;
+        settings.clone()
+    };
+    settings::save(&active_data_dir(&app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    emit_sync_status(&app);
+    Ok(())
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn sync_now(app: AppHandle) -> Result<SyncReport, AppError> {
+    Ok(sync_conversations(&app).await?)
+}
+
+/// Background pass when sync is enabled and the interval has elapsed.
+pub async fn run_sync_job(app: &AppHandle) {
+    let Ok(settings) = app.state::<AppState>().settings.lock().map(|s| s.sync.clone()) else {
+        return;
+    };
+    if settings.folder.is_none() {
+        return;
+    }
+    let Ok(dir) = active_data_dir(app) else {
+        return;
+    };
+    let interval = settings.interval_minutes.unwrap_or(DEFAULT_INTERVAL_MINUTES);
+    let last = load_state(&dir).last_sync.unwrap_or(0);
+    if conversations::now().saturating_sub(last) < u64::from(interval) * 60 {
+        return;
+    }
+    if let Err(e) = sync_conversations(app).await {
+        eprintln!("[tulsbot] Conversation sync failed: {}", e);
+    }
+}
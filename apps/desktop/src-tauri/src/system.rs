@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use sysinfo::{Disks, Networks, ProcessesToUpdate, System};
+use tulsbot_macros::instrumented;
+
+use crate::error::AppError;
+
+// ── System snapshot for troubleshooting ─────────────────────────────────────
+//
+// What the assistant needs to answer "why is my machine slow": OS and uptime,
+// load, memory, the heaviest processes, disk space and network throughput.
+// CPU usage and network rates need two samples, so taking a snapshot blocks
+// for about a second.
+
+/// Processes listed per ranking (CPU and memory).
+const TOP_PROCESSES: usize = 10;
+/// Time between the two samples used for CPU and network rates.
+const SAMPLE_MS: u64 = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    /// Percent of one core; can exceed 100 on multi-core machines.
+    pub cpu_percent: f32,
+    pub memory_mb: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskInfo {
+    pub name: String,
+    pub mount_point: String,
+    pub file_system: String,
+    pub total_gb: f64,
+    pub available_gb: f64,
+    pub removable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkInfo {
+    pub interface: String,
+    /// Bytes per second over the sampling interval.
+    pub received_per_sec: u64,
+    pub transmitted_per_sec: u64,
+    /// Bytes since the interface came up.
+    pub total_received: u64,
+    pub total_transmitted: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemSnapshot {
+    pub os_name: Option<String>,
+    pub os_version: Option<String>,
+    pub kernel_version: Option<String>,
+    pub host_name: Option<String>,
+    pub uptime_secs: u64,
+    /// 1, 5 and 15 minute load averages (zero on Windows).
+    pub load_average: [f64; 3],
+    pub cpu_count: usize,
+    pub cpu_percent: f32,
+    pub memory_total_mb: u64,
+    pub memory_used_mb: u64,
+    pub swap_total_mb: u64,
+    pub swap_used_mb: u64,
+    pub top_cpu: Vec<ProcessInfo>,
+    pub top_memory: Vec<ProcessInfo>,
+    pub disks: Vec<DiskInfo>,
+    pub networks: Vec<NetworkInfo>,
+}
+
+const MB: u64 = 1024 * 1024;
+const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+fn top(mut processes: Vec<ProcessInfo>, key: impl Fn(&ProcessInfo) -> f64) -> Vec<ProcessInfo> {
+    processes.sort_by(|a, b| key(b).total_cmp(&key(a)));
+    processes.truncate(TOP_PROCESSES);
+    processes
+}
+
+/// Sample the system. Blocking (about a second).
+pub fn snapshot() -> SystemSnapshot {
+    let mut sys = System::new_all();
+    let mut networks = Networks::new_with_refreshed_list();
+    let interval =
+        std::time::Duration::from_millis(SAMPLE_MS).max(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+    std::thread::sleep(interval);
+    sys.refresh_cpu_usage();
+    sys.refresh_processes(ProcessesToUpdate::All, true);
+    networks.refresh(true);
+
+    let processes: Vec<ProcessInfo> = sys
+        .processes()
+        .values()
+        .map(|p| ProcessInfo {
+            pid: p.pid().as_u32(),
+            name: p.name().to_string_lossy().into_owned(),
+            cpu_percent: p.cpu_usage(),
+            memory_mb: p.memory() / MB,
+        })
+        .collect();
+
+    let disks = Disks::new_with_refreshed_list()
+        .list()
+        .iter()
+        .map(|d| DiskInfo {
+            name: d.name().to_string_lossy().into_owned(),
+            mount_point: d.mount_point().display().to_string(),
+            file_system: d.file_system().to_string_lossy().into_owned(),
+            total_gb: d.total_space() as f64 / GB,
+            available_gb: d.available_space() as f64 / GB,
+            removable: d.is_removable(),
+        })
+        .collect();
+
+    let seconds = interval.as_secs_f64();
+    let mut networks: Vec<NetworkInfo> = networks
+        .iter()
+        .map(|(name, data)| NetworkInfo {
+            interface: name.clone(),
+            received_per_sec: (data.received() as f64 / seconds) as u64,
+            transmitted_per_sec: (data.transmitted() as f64 / seconds) as u64,
+            total_received: data.total_received(),
+            total_transmitted: data.total_transmitted(),
+        })
+        .filter(|n| n.total_received > 0 || n.total_transmitted > 0)
+        .collect();
+    networks.sort_by(|a, b| a.interface.cmp(&b.interface));
+
+    let load = System::load_average();
+    SystemSnapshot {
+        os_name: System::name(),
+        os_version: System::long_os_version().or_else(System::os_version),
+        kernel_version: System::kernel_version(),
+        host_name: System::host_name(),
+        uptime_secs: System::uptime(),
+        load_average: [load.one, load.five, load.fifteen],
+        cpu_count: sys.cpus().len(),
+        cpu_percent: sys.global_cpu_usage(),
+        memory_total_mb: sys.total_memory() / MB,
+        memory_used_mb: sys.used_memory() / MB,
+        swap_total_mb: sys.total_swap() / MB,
+        swap_used_mb: sys.used_swap() / MB,
+        top_cpu: top(processes.clone(), |p| f64::from(p.cpu_percent)),
+        top_memory: top(processes, |p| p.memory_mb as f64),
+        disks,
+        networks,
+    }
+}
+
+/// OS, uptime, load, memory, top processes, disks and network throughput,
+/// for answering performance questions with real numbers.
+#[instrumented]
+#[tauri::command]
+pub async fn get_system_snapshot() -> Result<SystemSnapshot, AppError> {
+    Ok(tauri::async_runtime::spawn_blocking(snapshot)
+        .await
+        .map_err(|e| e.to_string())?)
+}
@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::profiles::ServiceDef;
+use crate::readiness::Readiness;
+
+// ── User-defined services from JSON / TOML definitions ──────────────────────
+//
+// A definition is a `ServiceDef` plus plain environment values:
+//
+//     name = "Redis"
+//     port = 6379
+//     container = "redis"
+//     depends_on = []
+//     readiness = { type = "http", path = "/health" }
+//     [env]
+//     REDIS_ARGS = "--save 60 1"
+//
+// Once added it is an ordinary service of the profile: polled, shown in the
+// tray, started/stopped with the others and covered by remediation.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceTemplate {
+    #[serde(flatten)]
+    pub service: ServiceDef,
+    /// Plain environment values; add secrets through the env editor.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+/// Parse a definition. `format` is `json` or `toml`; without one, text
+/// starting with `{` is JSON and anything else TOML.
+pub fn parse(text: &str, format: Option<&str>) -> Result<ServiceTemplate, String> {
+    let json = match format {
+        Some("json") => true,
+        Some("toml") => false,
+        Some(other) => return Err(format!("Unknown definition format: {}", other)),
+        None => text.trim_start().starts_with('{'),
+    };
+    if json {
+        serde_json::from_str(text).map_err(|e| format!("Invalid JSON definition: {}", e))
+    } else {
+        toml::from_str(text).map_err(|e| format!("Invalid TOML definition: {}", e))
+    }
+}
+
+/// Read a definition file; the extension picks the format.
+pub fn read(path: &Path) -> Result<ServiceTemplate, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let format = path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase);
+    parse(&text, format.as_deref())
+}
+
+fn validate_argv(field: &str, argv: &Option<Vec<String>>) -> Result<(), String> {
+    match argv {
+        Some(argv) if argv.first().is_none_or(|p| p.trim().is_empty()) => {
+            Err(format!("`{}` needs a program to run", field))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Check `template` on its own and against the profile's `existing` services.
+pub fn validate(template: &ServiceTemplate, existing: &[ServiceDef]) -> Result<(), String> {
+    let service = &template.service;
+    if service.name.trim().is_empty() || service.name.contains(':') {
+        return Err("Service name must be non-empty and contain no ':'".into());
+    }
+    if service.port == 0 {
+        return Err(format!("{}: port must be set", service.name));
+    }
+    if existing.iter().any(|s| s.name == service.name) {
+        return Err(format!("A service named {} already exists", service.name));
+    }
+    if let Some(other) = existing.iter().find(|s| s.port == service.port) {
+        return Err(format!("Port {} is already used by {}", service.port, other.name));
+    }
+    validate_argv("restart", &service.restart)?;
+    validate_argv("start", &service.start)?;
+    validate_argv("stop", &service.stop)?;
+    let controllable = service.container.is_some() || service.start.is_some();
+    if !controllable {
+        return Err(format!("{}: set a container or a start command", service.name));
+    }
+    if let Some(container) = &service.container {
+        let valid = !container.is_empty()
+            && container
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            return Err(format!("Invalid container name: {}", container));
+        }
+    }
+    if let Some(Readiness::Http { path }) = &service.readiness {
+        if !path.starts_with('/') {
+            return Err(format!("Readiness path must start with '/': {}", path));
+        }
+    }
+    for key in template.env.keys() {
+        crate::env::validate_key(key)?;
+    }
+    Ok(())
+}
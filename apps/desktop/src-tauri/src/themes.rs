@@ -0,0 +1,271 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::{image::Image, AppHandle, Emitter, Manager, State, WebviewWindow};
+use tulsbot_macros::instrumented;
+
+use crate::settings;
+use crate::error::AppError;
+use crate::locks::LockExt;
+use crate::profiles::active_data_dir;
+use crate::state::AppState;
+
+// ── Theme choices and resolved design tokens ────────────────────────────────
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeMode {
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeChoice {
+    pub mode: ThemeMode,
+    /// `#rrggbb`
+    pub accent: String,
+    /// Multiplier applied to the base font size (0.75 – 2.0).
+    pub font_scale: f32,
+}
+
+impl Default for ThemeChoice {
+    fn default() -> Self {
+        Self {
+            mode: ThemeMode::System,
+            accent: "#6366f1".into(),
+            font_scale: 1.0,
+        }
+    }
+}
+
+/// What every window renders: the user's choice resolved against OS state.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResolvedTheme {
+    pub choice: ThemeChoice,
+    pub dark: bool,
+    pub high_contrast: bool,
+    /// CSS custom property values keyed by token name (without `--`).
+    pub tokens: BTreeMap<String, String>,
+}
+
+fn parse_hex(color: &str) -> Option<(u8, u8, u8)> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// Reject malformed input before it is persisted.
+pub fn validate(choice: &ThemeChoice) -> Result<(), String> {
+    if parse_hex(&choice.accent).is_none() {
+        return Err(format!("Invalid accent colour '{}': expected #rrggbb", choice.accent));
+    }
+    if !(0.75..=2.0).contains(&choice.font_scale) {
+        return Err(format!("Font scale {} out of range (0.75 – 2.0)", choice.font_scale));
+    }
+    Ok(())
+}
+
+/// Black or white, whichever reads better on `accent`.
+fn contrast_text(accent: (u8, u8, u8)) -> &'static str {
+    let (r, g, b) = accent;
+    let luminance = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+    if luminance > 150.0 {
+        "#000000"
+    } else {
+        "#ffffff"
+    }
+}
+
+pub fn resolve(choice: &ThemeChoice, os_dark: bool, high_contrast: bool) -> ResolvedTheme {
+    let dark = match choice.mode {
+        ThemeMode::System => os_dark,
+        ThemeMode::Light => false,
+        ThemeMode::Dark => true,
+    };
+    let accent = parse_hex(&choice.accent).unwrap_or((0x63, 0x66, 0xf1));
+
+    let palette: [(&str, &str); 5] = match (dark, high_contrast) {
+        (true, false) => [
+            ("background", "#111318"),
+            ("surface", "#1b1e25"),
+            ("text", "#e6e8ee"),
+            ("text-muted", "#9aa1b1"),
+            ("border", "#2c313c"),
+        ],
+        (false, false) => [
+            ("background", "#ffffff"),
+            ("surface", "#f4f5f8"),
+            ("text", "#15171c"),
+            ("text-muted", "#5b6272"),
+            ("border", "#dde0e7"),
+        ],
+        (true, true) => [
+            ("background", "#000000"),
+            ("surface", "#000000"),
+            ("text", "#ffffff"),
+            ("text-muted", "#ffffff"),
+            ("border", "#ffffff"),
+        ],
+        (false, true) => [
+            ("background", "#ffffff"),
+            ("surface", "#ffffff"),
+            ("text", "#000000"),
+            ("text-muted", "#000000"),
+            ("border", "#000000"),
+        ],
+    };
+
+    let mut tokens: BTreeMap<String, String> = palette
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+    tokens.insert(
+        "accent".into(),
+        format!("#{:02x}{:02x}{:02x}", accent.0, accent.1, accent.2),
+    );
+    tokens.insert("accent-text".into(), contrast_text(accent).into());
+    tokens.insert("font-scale".into(), choice.font_scale.to_string());
+    tokens.insert("color-scheme".into(), if dark { "dark" } else { "light" }.into());
+
+    ResolvedTheme {
+        choice: choice.clone(),
+        dark,
+        high_contrast,
+        tokens,
+    }
+}
+
+/// Whether the OS is in dark mode. Blocking: shells out on every platform.
+#[cfg(target_os = "macos")]
+pub fn os_prefers_dark() -> bool {
+    std::process::Command::new("defaults")
+        .args(["read", "-g", "AppleInterfaceStyle"])
+        .output()
+        .map(|out| String::from_utf8_lossy(&out.stdout).trim() == "Dark")
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "windows")]
+pub fn os_prefers_dark() -> bool {
+    std::process::Command::new("reg")
+        .args([
+            "query",
+            "HKCU\\Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize",
+            "/v",
+            "AppsUseLightTheme",
+        ])
+        .output()
+        .map(|out| String::from_utf8_lossy(&out.stdout).trim_end().ends_with("0x0"))
+        .unwrap_or(false)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn os_prefers_dark() -> bool {
+    std::process::Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.interface", "color-scheme"])
+        .output()
+        .map(|out| String::from_utf8_lossy(&out.stdout).contains("dark"))
+        .unwrap_or(false)
+}
+
+/// Invert a monochrome RGBA icon so it stays visible on dark trays that do
+/// not support template images.
+pub fn invert_rgba(rgba: &[u8]) -> Vec<u8> {
+    rgba.chunks_exact(4)
+        .flat_map(|px| [255 - px[0], 255 - px[1], 255 - px[2], px[3]])
+        .collect()
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn get_theme(state: State<'_, AppState>) -> Result<ResolvedTheme, AppError> {
+    let theme = state.theme.lock_or_recover();
+    Ok(theme.clone())
+}
+
+/// Store the user's theme choice and return the resolved theme.
+#[instrumented]
+#[tauri::command]
+pub async fn set_theme(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    choice: ThemeChoice,
+) -> Result<ResolvedTheme, AppError> {
+    validate(&choice)?;
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        settings.theme = choice;
+        settings.clone()
+    };
+    settings::save(&active_data_dir(&app)?, &settings)?;
+    Ok(refresh_theme(&app).await)
+}
+
+/// Re-resolve the theme against the OS appearance and high-contrast setting;
+/// on change, restyle native windows and emit `theme-changed`.
+pub async fn refresh_theme(app: &AppHandle) -> ResolvedTheme {
+    let state = app.state::<AppState>();
+    let choice = state
+        .settings
+        .lock()
+        .map(|s| s.theme.clone())
+        .unwrap_or_default();
+    let high_contrast = state
+        .accessibility
+        .lock()
+        .map(|p| p.high_contrast)
+        .unwrap_or(false);
+    let os_dark = tauri::async_runtime::spawn_blocking(os_prefers_dark)
+        .await
+        .unwrap_or(false);
+    let resolved = resolve(&choice, os_dark, high_contrast);
+
+    let changed = match state.theme.lock() {
+        Ok(mut current) if *current != resolved => {
+            *current = resolved.clone();
+            true
+        }
+        _ => false,
+    };
+    if changed {
+        for window in app.webview_windows().values() {
+            apply_theme_to(&resolved, window);
+        }
+        let _ = app.emit("theme-changed", &resolved);
+    }
+    resolved
+}
+
+/// Native chrome follows the OS in system mode and is pinned otherwise.
+pub fn apply_theme_to(theme: &ResolvedTheme, window: &WebviewWindow) {
+    let native = match theme.choice.mode {
+        ThemeMode::System => None,
+        ThemeMode::Light => Some(tauri::Theme::Light),
+        ThemeMode::Dark => Some(tauri::Theme::Dark),
+    };
+    let _ = window.set_theme(native);
+}
+
+/// The monochrome tray glyph. macOS tints it as a template image; elsewhere
+/// it is inverted for dark themes.
+pub fn tray_base_icon(app: &AppHandle) -> Image<'static> {
+    let icon = Image::from_bytes(include_bytes!("../icons/tray-icon.png")).unwrap_or_else(|_| {
+        // Fallback: tiny placeholder (will be replaced by health poll)
+        Image::new_owned(vec![255u8; 16 * 16 * 4], 16, 16)
+    });
+    let dark = app
+        .state::<AppState>()
+        .theme
+        .lock()
+        .map(|t| t.dark)
+        .unwrap_or(false);
+    if cfg!(target_os = "macos") || !dark {
+        return icon;
+    }
+    Image::new_owned(invert_rgba(icon.rgba()), icon.width(), icon.height())
+}
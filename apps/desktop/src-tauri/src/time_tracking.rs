@@ -0,0 +1,304 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::time::Instant;
+use tauri::{AppHandle, Manager};
+use tulsbot_macros::instrumented;
+
+use crate::{conversations, usage};
+use crate::error::AppError;
+use crate::locks::LockExt;
+use crate::profiles::active_data_dir;
+use crate::state::AppState;
+use crate::usage::UsageRange;
+
+// ── Time spent with the assistant ───────────────────────────────────────────
+//
+// How long the popover is open and focused and how long responses take to
+// generate. Each finished stretch is stored in the `time_intervals` table of
+// the profile's usage database, next to the usage records, and aggregated
+// per local day or ISO week on demand. A stretch counts towards the day or
+// week it started in.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Activity {
+    PopoverOpen,
+    PopoverFocused,
+    /// A completion or a streamed response.
+    Generating,
+}
+
+impl Activity {
+    const ALL: [Activity; 3] = [Self::PopoverOpen, Self::PopoverFocused, Self::Generating];
+
+    /// Name stored in the database, as serialized.
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::PopoverOpen => "popover-open",
+            Self::PopoverFocused => "popover-focused",
+            Self::Generating => "generating",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.as_str() == name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interval {
+    pub activity: Activity,
+    /// Unix seconds it started.
+    pub at: u64,
+    pub duration_ms: u64,
+}
+
+struct Running {
+    at: u64,
+    started: Instant,
+    count: usize,
+}
+
+/// Activities in progress.
+#[derive(Default)]
+pub struct TimeTracker {
+    running: HashMap<Activity, Running>,
+}
+
+impl TimeTracker {
+    /// Start an activity that may overlap with itself (several generations
+    /// at once); it lasts until the last of them ends.
+    pub fn begin(&mut self, activity: Activity, now: u64) {
+        let running = self.running.entry(activity).or_insert_with(|| Running {
+            at: now,
+            started: Instant::now(),
+            count: 0,
+        });
+        running.count += 1;
+    }
+
+    /// End one `begin`. Returns the finished interval once none is left.
+    pub fn end(&mut self, activity: Activity) -> Option<Interval> {
+        let running = self.running.get_mut(&activity)?;
+        running.count = running.count.saturating_sub(1);
+        if running.count > 0 {
+            return None;
+        }
+        let running = self.running.remove(&activity)?;
+        Some(Interval {
+            activity,
+            at: running.at,
+            duration_ms: running.started.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Track an on/off state such as the popover being open. Returns the
+    /// finished interval when it turns off.
+    pub fn set(&mut self, activity: Activity, on: bool, now: u64) -> Option<Interval> {
+        match (on, self.running.contains_key(&activity)) {
+            (true, false) => {
+                self.begin(activity, now);
+                None
+            }
+            (false, true) => {
+                self.running.remove(&activity).map(|running| Interval {
+                    activity,
+                    at: running.at,
+                    duration_ms: running.started.elapsed().as_millis() as u64,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The usage database, with the `time_intervals` table created if missing.
+fn open(dir: &Path) -> Result<Connection, String> {
+    let db = usage::open(dir)?;
+    db.execute_batch(
+        "CREATE TABLE IF NOT EXISTS time_intervals (
+             activity TEXT NOT NULL,
+             at INTEGER NOT NULL,
+             duration_ms INTEGER NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS time_intervals_at ON time_intervals (at);",
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(db)
+}
+
+pub fn record(dir: &Path, interval: &Interval) {
+    let inserted = open(dir).and_then(|db| {
+        db.execute(
+            "INSERT INTO time_intervals (activity, at, duration_ms) VALUES (?1, ?2, ?3)",
+            params![interval.activity.as_str(), interval.at, interval.duration_ms],
+        )
+        .map_err(|e| e.to_string())
+    });
+    if let Err(e) = inserted {
+        eprintln!("[tulsbot] Failed to record time: {}", e);
+    }
+}
+
+/// Intervals started at or after `since`, oldest first.
+pub fn load_since(dir: &Path, since: u64) -> Vec<Interval> {
+    let loaded = open(dir).and_then(|db| {
+        let mut query = db
+            .prepare(
+                "SELECT activity, at, duration_ms FROM time_intervals
+                 WHERE at >= ?1 ORDER BY at",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = query
+            .query_map([since], |row| {
+                Ok((row.get::<_, String>(0)?, row.get(1)?, row.get(2)?))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+    });
+    let rows = loaded.unwrap_or_else(|e| {
+        eprintln!("[tulsbot] Failed to read time records: {}", e);
+        Vec::new()
+    });
+    rows.into_iter()
+        .filter_map(|(activity, at, duration_ms)| {
+            Some(Interval { activity: Activity::from_name(&activity)?, at, duration_ms })
+        })
+        .collect()
+}
+
+/// Delete the intervals started before `before`, or only count them when
+/// `dry_run`.
+pub fn prune(dir: &Path, before: u64, dry_run: bool) -> Result<usize, String> {
+    let db = open(dir)?;
+    let removed = if dry_run {
+        db.query_row("SELECT COUNT(*) FROM time_intervals WHERE at < ?1", [before], |row| {
+            row.get(0)
+        })
+    } else {
+        db.execute("DELETE FROM time_intervals WHERE at < ?1", [before])
+    };
+    removed.map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeGroup {
+    /// Local calendar day, `YYYY-MM-DD`.
+    Day,
+    /// ISO week, `YYYY-Www`.
+    Week,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TimeStats {
+    pub key: String,
+    pub popover_open_secs: u64,
+    pub popover_focused_secs: u64,
+    pub generating_secs: u64,
+    /// Times the popover was opened.
+    pub sessions: u64,
+    pub generations: u64,
+}
+
+/// Per-day or per-week totals, ordered by key.
+pub fn aggregate(intervals: &[Interval], group: TimeGroup) -> Vec<TimeStats> {
+    let mut groups: BTreeMap<String, TimeStats> = BTreeMap::new();
+    for interval in intervals {
+        let local = chrono::DateTime::from_timestamp(interval.at as i64, 0)
+            .unwrap_or_default()
+            .with_timezone(&chrono::Local);
+        let key = match group {
+            TimeGroup::Day => local.format("%Y-%m-%d").to_string(),
+            TimeGroup::Week => local.format("%G-W%V").to_string(),
+        };
+        let stats = groups.entry(key.clone()).or_insert_with(|| TimeStats {
+            key,
+            ..Default::default()
+        });
+        let secs = interval.duration_ms / 1000;
+        match interval.activity {
+            Activity::PopoverOpen => {
+                stats.popover_open_secs += secs;
+                stats.sessions += 1;
+            }
+            Activity::PopoverFocused => stats.popover_focused_secs += secs,
+            Activity::Generating => {
+                stats.generating_secs += secs;
+                stats.generations += 1;
+            }
+        }
+    }
+    groups.into_values().collect()
+}
+
+pub fn record_time(app: &AppHandle, interval: Interval) {
+    let dir = match active_data_dir(app) {
+        Ok(dir) => dir,
+        Err(e) => {
+            eprintln!("[tulsbot] Failed to record time: {}", e);
+            return;
+        }
+    };
+    tauri::async_runtime::spawn_blocking(move || record(&dir, &interval));
+}
+
+/// Counts as generating time until dropped.
+pub struct GenerationGuard(AppHandle);
+
+impl Drop for GenerationGuard {
+    fn drop(&mut self) {
+        let state = self.0.state::<AppState>();
+        let finished = state.time_tracker.lock_or_recover().end(Activity::Generating);
+        if let Some(interval) = finished {
+            record_time(&self.0, interval);
+        }
+    }
+}
+
+pub fn begin_generation(app: &AppHandle) -> GenerationGuard {
+    let state = app.state::<AppState>();
+    state.time_tracker.lock_or_recover().begin(Activity::Generating, conversations::now());
+    GenerationGuard(app.clone())
+}
+
+/// Start or finish the popover's open and focused stretches to match the
+/// window. Called when it gains or loses focus and when it is toggled or
+/// hidden.
+pub fn track_popover(app: &AppHandle) {
+    let window = app.get_webview_window("chat-popover");
+    let open = window.as_ref().is_some_and(|w| w.is_visible().unwrap_or(false));
+    let focused = open && window.as_ref().is_some_and(|w| w.is_focused().unwrap_or(false));
+    let now = conversations::now();
+    let state = app.state::<AppState>();
+    let finished: Vec<Interval> = {
+        let mut tracker = state.time_tracker.lock_or_recover();
+        [(Activity::PopoverOpen, open), (Activity::PopoverFocused, focused)]
+            .into_iter()
+            .filter_map(|(activity, on)| tracker.set(activity, on, now))
+            .collect()
+    };
+    for interval in finished {
+        record_time(app, interval);
+    }
+}
+
+/// Time the popover was open and focused and responses were generating
+/// over `range`, per local day or ISO week, for the personal analytics view.
+#[instrumented]
+#[tauri::command]
+pub async fn get_time_stats(
+    app: AppHandle,
+    range: UsageRange,
+    group: TimeGroup,
+) -> Result<Vec<TimeStats>, AppError> {
+    let dir = active_data_dir(&app)?;
+    let since = range.since(conversations::now());
+    Ok(tauri::async_runtime::spawn_blocking(move || {
+        aggregate(&load_since(&dir, since), group)
+    })
+    .await
+    .map_err(|e| e.to_string())?)
+}
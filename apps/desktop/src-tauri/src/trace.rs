@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use crate::redaction::{CustomRule, RedactionConfig, Redactor};
+
+// ── Proxy request/response inspector (opt-in debug mode) ────────────────────
+//
+// Recent `api_proxy` exchanges, kept in memory only. Credentials are scrubbed
+// before anything is stored: sensitive headers are masked and bodies go
+// through the api-key rule plus a rule for secret-looking JSON fields.
+
+/// Exchanges kept in the ring buffer.
+const TRACE_LIMIT: usize = 100;
+/// Body characters kept per request and response.
+const BODY_LIMIT: usize = 4096;
+
+const SENSITIVE_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+    "api-key",
+];
+
+const SECRET_FIELD: &str =
+    r#"(?i)"(?:api_?key|apikey|access_?token|refresh_?token|token|secret|password)"\s*:\s*"[^"]*""#;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceEntry {
+    pub id: u64,
+    /// Unix seconds.
+    pub at: u64,
+    pub method: String,
+    pub url: String,
+    pub request_headers: Vec<(String, String)>,
+    pub request_body: Option<String>,
+    /// `Content-Encoding` of the request body as sent; `None` when sent as is.
+    #[serde(default)]
+    pub request_encoding: Option<String>,
+    /// `None` when no response arrived.
+    pub status: Option<u16>,
+    pub response_headers: Vec<(String, String)>,
+    pub response_body: Option<String>,
+    /// `Content-Encoding` the response arrived with, before it was decoded.
+    #[serde(default)]
+    pub response_encoding: Option<String>,
+    /// Response body bytes on the wire.
+    #[serde(default)]
+    pub response_wire_bytes: Option<usize>,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct ProxyTrace {
+    entries: VecDeque<TraceEntry>,
+    next_id: u64,
+}
+
+impl ProxyTrace {
+    /// Store `entry` (its `id` is assigned here).
+    pub fn record(&mut self, mut entry: TraceEntry) {
+        self.next_id += 1;
+        entry.id = self.next_id;
+        self.entries.push_back(entry);
+        if self.entries.len() > TRACE_LIMIT {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Newest first.
+    pub fn entries(&self) -> Vec<TraceEntry> {
+        self.entries.iter().rev().cloned().collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Header pairs with credentials masked.
+pub fn scrub_headers(headers: &reqwest::header::HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let name = name.as_str().to_string();
+            let value = if SENSITIVE_HEADERS.contains(&name.as_str()) {
+                "[REDACTED]".to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            (name, value)
+        })
+        .collect()
+}
+
+/// `body` with secrets replaced, truncated to the trace limit.
+pub fn scrub_body(body: &str) -> String {
+    let config = RedactionConfig {
+        enabled: true,
+        builtins: vec!["api-key".into()],
+        custom: vec![CustomRule {
+            name: "secret-field".into(),
+            pattern: SECRET_FIELD.into(),
+        }],
+        ..Default::default()
+    };
+    let scrubbed = match Redactor::new(&config) {
+        Ok(redactor) => redactor.redact(body).0,
+        Err(_) => return "[unavailable]".into(),
+    };
+    let total = scrubbed.chars().count();
+    if total <= BODY_LIMIT {
+        return scrubbed;
+    }
+    let kept: String = scrubbed.chars().take(BODY_LIMIT).collect();
+    format!("{}… [{} more characters]", kept, total - BODY_LIMIT)
+}
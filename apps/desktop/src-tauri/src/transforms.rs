@@ -0,0 +1,281 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::context_builder::ChunkRef;
+use crate::conversations::{self, Message};
+use crate::locks::LockExt;
+use crate::profiles::active_data_dir;
+use crate::state::AppState;
+
+// ── Post-processing of completed responses ──────────────────────────────────
+//
+// Transforms run in order on a finished assistant message before it is
+// stored. `Markdown` tidies the text itself; the others leave it alone and
+// fill in the message's `metadata`: the links it mentions (unfurled to a
+// title and description in the background, since that takes a fetch per
+// link), the retrieved chunks it was given and which of them it cited, and
+// the language it is written in. A transform is an entry in `Transform`
+// plus its arm in `run`.
+
+/// Links unfurled per message, at most.
+const MAX_LINKS: usize = 5;
+/// Stopword hits a language needs before it is reported.
+const MIN_LANGUAGE_HITS: usize = 3;
+
+/// Frequent short words per language, for `detect_language`.
+const STOPWORDS: &[(&str, &[&str])] = &[
+    ("en", &["the", "and", "is", "of", "to", "in", "that", "it", "with", "for", "you", "this"]),
+    (
+        "de",
+        &["der", "die", "und", "das", "ist", "nicht", "mit", "ein", "eine", "ich", "auf", "sie"],
+    ),
+    ("es", &["el", "la", "que", "de", "los", "las", "es", "por", "una", "con", "para", "pero"]),
+    ("fr", &["le", "la", "les", "et", "est", "des", "une", "que", "pas", "pour", "dans", "vous"]),
+    ("pt", &["o", "a", "que", "de", "não", "uma", "os", "para", "com", "é", "mas", "você"]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Transform {
+    /// Line endings, blank-line runs, bullet markers and unclosed fences.
+    Markdown,
+    /// Collect the links and unfurl them.
+    Links,
+    /// Record the retrieved chunks and which `[n]` references cite them.
+    Citations,
+    Language,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransformSettings {
+    /// Transforms run on each response, in this order.
+    pub enabled: Vec<Transform>,
+}
+
+impl Default for TransformSettings {
+    fn default() -> Self {
+        Self { enabled: vec![Transform::Markdown, Transform::Citations, Transform::Language] }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LinkPreview {
+    pub url: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub site_name: Option<String>,
+    /// Why the link couldn't be unfurled.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Citation {
+    /// `n` of the `[n]` the chunk was labelled with in the prompt.
+    pub number: usize,
+    pub collection: String,
+    pub id: String,
+    pub score: f32,
+    /// The response refers to it.
+    pub cited: bool,
+}
+
+/// What the transforms found out about a response.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MessageMetadata {
+    /// Transforms that ran, in order.
+    pub transforms: Vec<Transform>,
+    pub links: Vec<LinkPreview>,
+    pub citations: Vec<Citation>,
+    /// ISO 639-1 code, when one language clearly dominates.
+    pub language: Option<String>,
+}
+
+/// Run `enabled` on `content`. `chunks` are the retrieved chunks of the
+/// prompt, in the order they were numbered. Links come back without their
+/// previews; `unfurl` fills them in.
+pub fn run(enabled: &[Transform], content: &str, chunks: &[ChunkRef]) -> (String, MessageMetadata) {
+    let mut text = content.to_string();
+    let mut metadata = MessageMetadata::default();
+    for transform in enabled {
+        if metadata.transforms.contains(transform) {
+            continue;
+        }
+        match transform {
+            Transform::Markdown => text = normalize_markdown(&text),
+            Transform::Links => {
+                metadata.links = extract_links(&text)
+                    .into_iter()
+                    .map(|url| LinkPreview { url, ..Default::default() })
+                    .collect();
+            }
+            Transform::Citations => metadata.citations = citations(&text, chunks),
+            Transform::Language => metadata.language = detect_language(&text),
+        }
+        metadata.transforms.push(*transform);
+    }
+    (text, metadata)
+}
+
+/// Fetch a title and description for each link.
+pub async fn unfurl(links: &mut [LinkPreview]) {
+    for link in links {
+        match crate::webpage::fetch(&link.url).await {
+            Ok(page) => {
+                link.title = page.title;
+                link.description = page.metadata.description;
+                link.site_name = page.metadata.site_name;
+            }
+            Err(e) => link.error = Some(e),
+        }
+    }
+}
+
+/// Tidy Markdown without touching fenced code: CRLF line endings, runs of
+/// blank lines, `*`/`+` bullets and a fence left open at the end.
+pub fn normalize_markdown(text: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut in_fence = false;
+    let mut blank_run = 0;
+    for line in text.replace("\r\n", "\n").split('\n') {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            blank_run = 0;
+            lines.push(line.to_string());
+            continue;
+        }
+        if in_fence {
+            lines.push(line.to_string());
+            continue;
+        }
+        if line.trim().is_empty() {
+            blank_run += 1;
+            if blank_run == 1 {
+                lines.push(String::new());
+            }
+            continue;
+        }
+        blank_run = 0;
+        let indent = line.len() - line.trim_start().len();
+        let rest = &line[indent..];
+        let line = match rest.strip_prefix("* ").or_else(|| rest.strip_prefix("+ ")) {
+            Some(item) => format!("{}- {}", &line[..indent], item),
+            None => line.to_string(),
+        };
+        lines.push(line);
+    }
+    let mut text = lines.join("\n").trim().to_string();
+    if in_fence {
+        text.push_str("\n```");
+    }
+    text
+}
+
+/// The distinct http(s) URLs in `text`, in order of appearance.
+pub fn extract_links(text: &str) -> Vec<String> {
+    let pattern = Regex::new(r#"https?://[^\s<>()\[\]"'`]+"#).expect("static regex");
+    let mut links: Vec<String> = Vec::new();
+    for found in pattern.find_iter(text) {
+        let url = found.as_str().trim_end_matches(['.', ',', ';', ':', '!', '?']);
+        if !links.iter().any(|l| l == url) {
+            links.push(url.to_string());
+        }
+        if links.len() == MAX_LINKS {
+            break;
+        }
+    }
+    links
+}
+
+fn citations(text: &str, chunks: &[ChunkRef]) -> Vec<Citation> {
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(i, chunk)| Citation {
+            number: i + 1,
+            collection: chunk.collection.clone(),
+            id: chunk.id.clone(),
+            score: chunk.score,
+            cited: text.contains(&format!("[{}]", i + 1)),
+        })
+        .collect()
+}
+
+/// The language whose stopwords occur most often, outside code.
+pub fn detect_language(text: &str) -> Option<String> {
+    let prose: String = text
+        .split("```")
+        .step_by(2)
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    let words: Vec<&str> = prose
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .collect();
+    STOPWORDS
+        .iter()
+        .map(|(code, stopwords)| {
+            let hits = words.iter().filter(|w| stopwords.contains(w)).count();
+            (hits, *code)
+        })
+        .filter(|(hits, _)| *hits >= MIN_LANGUAGE_HITS)
+        .max_by_key(|(hits, _)| *hits)
+        .map(|(_, code)| code.to_string())
+}
+
+/// Run the configured transforms on a finished response. Citations refer to
+/// the chunks of the context last sent for `conversation`.
+pub fn transform_response(
+    app: &AppHandle,
+    conversation: Option<&str>,
+    text: String,
+) -> (String, Option<MessageMetadata>) {
+    let state = app.state::<AppState>();
+    let enabled = state.settings.lock_or_recover().transforms.enabled.clone();
+    if enabled.is_empty() {
+        return (text, None);
+    }
+    let chunks = conversation
+        .and_then(|id| state.last_context.lock_or_recover().get(id).map(|c| c.chunks.clone()))
+        .unwrap_or_default();
+    let (text, metadata) = run(&enabled, &text, &chunks);
+    (text, Some(metadata))
+}
+
+/// Unfurl the links of a stored message in the background, then save them
+/// and emit `message-metadata-changed`.
+pub fn spawn_unfurl_job(app: &AppHandle, conversation: &str, message: &Message) {
+    let Some(mut metadata) = message.metadata.clone().filter(|m| !m.links.is_empty()) else {
+        return;
+    };
+    let (app, conversation, id) = (app.clone(), conversation.to_string(), message.id.clone());
+    tauri::async_runtime::spawn(async move {
+        unfurl(&mut metadata.links).await;
+        let saved = active_data_dir(&app).and_then(|dir| {
+            let mut stored = conversations::load(&dir, &conversation)?;
+            let message = stored
+                .messages
+                .iter_mut()
+                .find(|m| m.id == id)
+                .ok_or_else(|| format!("Unknown message: {}", id))?;
+            message.metadata = Some(metadata.clone());
+            conversations::save(&dir, &stored)
+        });
+        match saved {
+            Ok(()) => {
+                let _ = app.emit(
+                    "message-metadata-changed",
+                    serde_json::json!({
+                        "conversation": conversation,
+                        "message": id,
+                        "metadata": metadata,
+                    }),
+                );
+            }
+            Err(e) => eprintln!("[tulsbot] Failed to save link previews: {}", e),
+        }
+    });
+}
@@ -0,0 +1,743 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+use tauri::{
+    image::Image,
+    menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, Submenu},
+    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
+    AppHandle, Emitter, Manager, State,
+};
+use tulsbot_macros::instrumented;
+
+use crate::{dock, shortcuts, tray_anim, typing};
+use crate::conversations::now_millis;
+use crate::error::{AppError, ErrorKind};
+use crate::focus::{end_focus_session, start_focus_session};
+use crate::health::health_icon;
+use crate::i18n::tr;
+use crate::lan::{lan_status, stop_lan_exposure};
+use crate::locks::LockExt;
+use crate::middleware::{self, diagnostics_text};
+use crate::profiles::{active_data_dir, apply_profile};
+use crate::providers::{credential_infos, select_credential};
+use crate::settings::{self, privacy_mode, set_privacy_mode};
+use crate::state::AppState;
+use crate::supervisor::restart_service;
+use crate::themes::tray_base_icon;
+use crate::time_tracking::track_popover;
+use crate::windows::{apply_layout, ensure_window, show_popover_at};
+
+// ── Tray icon clicks ────────────────────────────────────────────────────────
+//
+// What a left click and a double click on the tray icon do is configurable.
+// Windows delivers the clicks of a double click too, so there a single
+// click waits out the double-click time before acting. Clicking the icon
+// also blurs an open popover, which hides it before the click arrives; a
+// click right after such a hide counts as the one that closed it instead
+// of opening it again.
+
+/// A click this soon after the popover hid on blur is taken to have
+/// caused the blur.
+pub const BLUR_GRACE_MS: u64 = 300;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TrayAction {
+    /// Toggle the chat popover.
+    Popover,
+    /// Show the main window.
+    Dashboard,
+    /// Show the main window on its settings page.
+    Settings,
+    /// Open the tray menu (left click only; Windows and macOS).
+    Menu,
+    Nothing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TraySettings {
+    pub click: TrayAction,
+    /// Windows only; other platforms don't report double clicks.
+    pub double_click: TrayAction,
+}
+
+impl Default for TraySettings {
+    fn default() -> Self {
+        Self { click: TrayAction::Popover, double_click: TrayAction::Dashboard }
+    }
+}
+
+/// How long a single click waits for a second one, in milliseconds.
+#[cfg(windows)]
+pub fn double_click_ms() -> u64 {
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetDoubleClickTime() -> u32;
+    }
+    // SAFETY: takes no arguments and only reads a system setting.
+    u64::from(unsafe { GetDoubleClickTime() })
+}
+
+/// Without double-click events a click acts right away.
+#[cfg(not(windows))]
+pub fn double_click_ms() -> u64 {
+    0
+}
+
+// ── Linux: StatusNotifier support ───────────────────────────────────────────
+
+/// Whether the desktop shows tray icons. Linux tray icons are
+/// StatusNotifier items, which GNOME only shows with the AppIndicator
+/// extension; without a watcher on the session bus the icon never appears.
+/// Assumes support when the bus can't be asked. Blocking.
+#[cfg(target_os = "linux")]
+pub fn supported() -> bool {
+    let out = std::process::Command::new("gdbus")
+        .args([
+            "call",
+            "--session",
+            "--dest",
+            "org.freedesktop.DBus",
+            "--object-path",
+            "/org/freedesktop/DBus",
+            "--method",
+            "org.freedesktop.DBus.NameHasOwner",
+            "org.kde.StatusNotifierWatcher",
+        ])
+        .output();
+    match out {
+        // (true,) or (false,)
+        Ok(out) if out.status.success() => !String::from_utf8_lossy(&out.stdout).contains("false"),
+        _ => true,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn supported() -> bool {
+    true
+}
+
+// ── macOS: Option-click ─────────────────────────────────────────────────────
+
+/// Whether Option is held down right now. The tray swaps in the power-user
+/// menu while the pointer rests on the icon with Option held, since macOS
+/// opens the menu on mouse down, before the click reaches us.
+#[cfg(target_os = "macos")]
+pub fn option_held() -> bool {
+    const COMBINED_SESSION_STATE: i32 = 0;
+    const ALTERNATE_MASK: u64 = 0x0008_0000;
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGEventSourceFlagsState(state_id: i32) -> u64;
+    }
+    // SAFETY: takes a plain enum value and only reads the modifier state.
+    unsafe { CGEventSourceFlagsState(COMBINED_SESSION_STATE) & ALTERNATE_MASK != 0 }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn option_held() -> bool {
+    false
+}
+
+// ── Tray activity ───────────────────────────────────────────────────────────
+
+/// Counts as active work until dropped.
+pub struct WorkGuard(AppHandle);
+
+impl Drop for WorkGuard {
+    fn drop(&mut self) {
+        self.0.state::<AppState>().active_work.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Mark work as running until the guard is dropped, starting the tray
+/// spinner if it isn't running yet.
+pub fn begin_work(app: &AppHandle) -> WorkGuard {
+    let state = app.state::<AppState>();
+    state.active_work.fetch_add(1, Ordering::SeqCst);
+    if !state.tray_animating.swap(true, Ordering::SeqCst) {
+        let handle = app.clone();
+        tauri::async_runtime::spawn(async move { animate_tray(&handle).await });
+    }
+    WorkGuard(app.clone())
+}
+
+/// Cycle spinner frames over the health icon while work is active, then
+/// put the plain health icon back. With reduced motion the first frame is
+/// shown without cycling.
+async fn animate_tray(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let mut frames: Option<(String, Vec<Image<'static>>)> = None;
+    let mut shown: Option<(String, usize)> = None;
+    let mut next = 0;
+    loop {
+        if state.active_work.load(Ordering::SeqCst) == 0 {
+            state.tray_animating.store(false, Ordering::SeqCst);
+            // Work that started while stopping keeps this spinner going,
+            // unless it already started another one
+            if state.active_work.load(Ordering::SeqCst) == 0
+                || state.tray_animating.swap(true, Ordering::SeqCst)
+            {
+                break;
+            }
+        }
+        let overall = state.health.lock_or_recover().overall.clone();
+        if frames.as_ref().is_none_or(|(o, _)| *o != overall) {
+            let Ok(base) = Image::from_bytes(health_icon(&overall)) else {
+                break;
+            };
+            frames = Some((overall.clone(), tray_anim::frames(&base)));
+        }
+        let reduced = state.accessibility.lock().map(|p| p.reduced_motion).unwrap_or(false);
+        let frame = if reduced { 0 } else { next };
+        if shown.as_ref() != Some(&(overall.clone(), frame)) {
+            if let (Some(tray), Some((_, images))) = (app.tray_by_id("main-tray"), &frames) {
+                let _ = tray.set_icon(Some(images[frame].clone()));
+                let _ = tray.set_icon_as_template(false);
+            }
+            shown = Some((overall, frame));
+        }
+        next = (next + 1) % tray_anim::FRAMES;
+        tokio::time::sleep(std::time::Duration::from_millis(tray_anim::FRAME_MS)).await;
+    }
+    if !state.tray_animating.load(Ordering::SeqCst) {
+        let overall = state.health.lock_or_recover().overall.clone();
+        if let (Some(tray), Ok(icon)) =
+            (app.tray_by_id("main-tray"), Image::from_bytes(health_icon(&overall)))
+        {
+            let _ = tray.set_icon(Some(icon));
+            let _ = tray.set_icon_as_template(false);
+        }
+    }
+}
+
+// ── Tray setup ──────────────────────────────────────────────────────────────
+
+fn build_tray_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
+    let open_item = MenuItem::with_id(
+        app,
+        "open",
+        tr(app, "tray-open-dashboard"),
+        true,
+        None::<&str>,
+    )?;
+    let settings_item =
+        MenuItem::with_id(app, "settings", tr(app, "tray-settings"), true, None::<&str>)?;
+
+    let profile_menu = Submenu::with_id(app, "profiles", tr(app, "tray-profile"), true)?;
+    let store = app
+        .state::<AppState>()
+        .profiles
+        .lock()
+        .map(|store| store.clone())
+        .unwrap_or_default();
+    for profile in &store.profiles {
+        let item = CheckMenuItem::with_id(
+            app,
+            format!("profile:{}", profile.name),
+            &profile.name,
+            true,
+            profile.name == store.active,
+            None::<&str>,
+        )?;
+        profile_menu.append(&item)?;
+    }
+
+    let credential_menu =
+        Submenu::with_id(app, "credentials", tr(app, "tray-credentials"), true)?;
+    let infos = app
+        .state::<AppState>()
+        .settings
+        .lock()
+        .map(|settings| credential_infos(app, &settings))
+        .unwrap_or_default();
+    let mut providers: Vec<&str> = infos.iter().map(|c| c.provider.as_str()).collect();
+    providers.dedup();
+    for provider in providers {
+        let submenu = Submenu::new(app, provider, true)?;
+        for info in infos.iter().filter(|c| c.provider == provider) {
+            let item = CheckMenuItem::with_id(
+                app,
+                format!("credential:{}:{}", info.provider, info.label),
+                &info.label,
+                true,
+                info.selected,
+                None::<&str>,
+            )?;
+            submenu.append(&item)?;
+        }
+        credential_menu.append(&submenu)?;
+    }
+
+    let service_menu = Submenu::with_id(app, "services", tr(app, "tray-services"), true)?;
+    for service in &store.active_profile().services {
+        let label = match app.state::<AppState>().i18n.lock() {
+            Ok(i18n) => i18n.t_args("tray-restart-service", &[("service", &service.name)]),
+            Err(_) => service.name.clone(),
+        };
+        let item = MenuItem::with_id(
+            app,
+            format!("restart:{}", service.name),
+            label,
+            true,
+            None::<&str>,
+        )?;
+        service_menu.append(&item)?;
+    }
+
+    let privacy_item = CheckMenuItem::with_id(
+        app,
+        "privacy",
+        tr(app, "tray-privacy-mode"),
+        true,
+        privacy_mode(app),
+        None::<&str>,
+    )?;
+
+    let focus_label = if app.state::<AppState>().focus.lock_or_recover().is_some() {
+        tr(app, "tray-focus-stop")
+    } else {
+        let minutes = app.state::<AppState>().settings.lock_or_recover().focus.minutes();
+        match app.state::<AppState>().i18n.lock() {
+            Ok(i18n) => i18n.t_args("tray-focus-start", &[("minutes", &minutes.to_string())]),
+            Err(_) => format!("Start Focus ({} min)", minutes),
+        }
+    };
+    let focus_item = MenuItem::with_id(app, "focus", focus_label, true, None::<&str>)?;
+
+    let lan_address = lan_status(app).address;
+    let lan_label = match (app.state::<AppState>().i18n.lock(), lan_address) {
+        (Ok(i18n), Some(address)) => {
+            i18n.t_args("tray-lan-stop", &[("address", &address.to_string())])
+        }
+        _ => "Stop LAN Access".to_string(),
+    };
+    let lan_item = MenuItem::with_id(app, "lan-stop", lan_label, true, None::<&str>)?;
+
+    let layout_menu = Submenu::with_id(app, "layouts", tr(app, "tray-layouts"), true)?;
+    let layout_names: Vec<String> = app
+        .state::<AppState>()
+        .settings
+        .lock()
+        .map(|s| s.window_layouts.iter().map(|l| l.name.clone()).collect())
+        .unwrap_or_default();
+    for name in &layout_names {
+        let item = MenuItem::with_id(app, format!("layout:{}", name), name, true, None::<&str>)?;
+        layout_menu.append(&item)?;
+    }
+
+    let sep = MenuItem::with_id(app, "sep", "────────────", false, None::<&str>)?;
+    let quit_item = MenuItem::with_id(app, "quit", tr(app, "tray-quit"), true, None::<&str>)?;
+
+    let mut items: Vec<&dyn IsMenuItem<tauri::Wry>> =
+        vec![&open_item, &settings_item, &profile_menu, &service_menu];
+    if !infos.is_empty() {
+        items.push(&credential_menu);
+    }
+    if !layout_names.is_empty() {
+        items.push(&layout_menu);
+    }
+    items.push(&focus_item);
+    if lan_address.is_some() {
+        items.push(&lan_item);
+    }
+    items.push(&privacy_item);
+    items.push(&sep);
+    items.push(&quit_item);
+    Menu::with_items(app, &items)
+}
+
+/// The Option-click menu (macOS): actions for troubleshooting.
+fn build_power_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
+    let restart_item =
+        MenuItem::with_id(app, "restart-all", tr(app, "tray-restart-all"), true, None::<&str>)?;
+    let diagnostics_item = MenuItem::with_id(
+        app,
+        "copy-diagnostics",
+        tr(app, "tray-copy-diagnostics"),
+        true,
+        None::<&str>,
+    )?;
+    let logging_item = CheckMenuItem::with_id(
+        app,
+        "debug-logging",
+        tr(app, "tray-debug-logging"),
+        true,
+        middleware::debug_logging(),
+        None::<&str>,
+    )?;
+    let sep = MenuItem::with_id(app, "sep", "────────────", false, None::<&str>)?;
+    let quit_item = MenuItem::with_id(app, "quit", tr(app, "tray-quit"), true, None::<&str>)?;
+    Menu::with_items(app, &[&restart_item, &diagnostics_item, &logging_item, &sep, &quit_item])
+}
+
+pub fn refresh_tray_menu(app: &AppHandle) {
+    if let Some(tray) = app.tray_by_id("main-tray") {
+        let menu = if app.state::<AppState>().tray_power_menu.load(Ordering::SeqCst) {
+            build_power_menu(app)
+        } else {
+            build_tray_menu(app)
+        };
+        match menu {
+            Ok(menu) => {
+                let _ = tray.set_menu(Some(menu));
+            }
+            Err(e) => eprintln!("[tulsbot] Failed to rebuild tray menu: {}", e),
+        }
+    }
+}
+
+/// In menu bar mode (macOS) the app is an accessory, without a Dock icon or
+/// app switcher entry, except while the dashboard is open.
+#[cfg(target_os = "macos")]
+pub fn update_dock_icon(app: &AppHandle, dashboard_open: bool) {
+    let menu_bar_only = app.state::<AppState>().settings.lock().is_ok_and(|s| s.menu_bar_only);
+    let policy = if menu_bar_only && !dashboard_open {
+        tauri::ActivationPolicy::Accessory
+    } else {
+        tauri::ActivationPolicy::Regular
+    };
+    if let Err(e) = app.set_activation_policy(policy) {
+        eprintln!("[tulsbot] Failed to set activation policy: {}", e);
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn update_dock_icon(_app: &AppHandle, _dashboard_open: bool) {}
+
+/// No tray icon will show: keep the dashboard around in its place. Closing
+/// it minimizes it instead, and `tray-unavailable` tells the UI to offer
+/// what the tray menu would (the frontend can also ask `tray_available`).
+pub fn use_dashboard_as_tray(app: &AppHandle) {
+    let hotkey = app
+        .state::<AppState>()
+        .settings
+        .lock()
+        .ok()
+        .and_then(|s| s.shortcuts.cycle_windows().map(String::from))
+        .unwrap_or_else(|| shortcuts::DEFAULT_CYCLE_WINDOWS.to_string());
+    eprintln!(
+        "[tulsbot] No StatusNotifier host, so no tray icon: keeping the dashboard open \
+         ({} cycles windows)",
+        hotkey
+    );
+    if let Some(window) = app.get_webview_window("main") {
+        let main = window.clone();
+        window.on_window_event(move |event| {
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                api.prevent_close();
+                let _ = main.minimize();
+            }
+        });
+    }
+    let _ = app.emit("tray-unavailable", serde_json::json!({ "shortcut": hotkey }));
+}
+
+/// Show the main window, on its settings page when `settings` is set.
+fn open_dashboard(app: &AppHandle, settings: bool) {
+    if let Ok(window) = ensure_window(app, "main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    if settings {
+        let _ = app.emit_to("main", "open-settings", ());
+    }
+}
+
+/// Do what a tray click is set to do. `icon` is where the tray icon is, for
+/// placing the popover; `clicked_at` is in Unix milliseconds.
+fn run_tray_action(app: &AppHandle, action: TrayAction, icon: Option<dock::Rect>, clicked_at: u64) {
+    match action {
+        TrayAction::Popover => {
+            let visible = app
+                .get_webview_window("chat-popover")
+                .is_some_and(|w| w.is_visible().unwrap_or(false));
+            // The click blurred the popover, which hid it: it is closed now
+            let blurred_at = app.state::<AppState>().popover_blurred_at.load(Ordering::SeqCst);
+            let just_hid = clicked_at.abs_diff(blurred_at) < BLUR_GRACE_MS;
+            if visible {
+                if let Some(window) = app.get_webview_window("chat-popover") {
+                    let _ = window.hide();
+                }
+            } else if !just_hid {
+                if let Err(e) = show_popover_at(app, icon) {
+                    eprintln!("[tulsbot] Failed to show popover: {}", e);
+                }
+            }
+            track_popover(app);
+        }
+        TrayAction::Dashboard => open_dashboard(app, false),
+        TrayAction::Settings => open_dashboard(app, true),
+        // The menu opens by itself
+        TrayAction::Menu | TrayAction::Nothing => {}
+    }
+}
+
+/// A left click on the tray icon. Where double clicks are reported and do
+/// something, it waits to see whether this one becomes one.
+fn on_tray_click(app: &AppHandle, icon: dock::Rect) {
+    let state = app.state::<AppState>();
+    let clicked_at = now_millis();
+    let wait = double_click_ms();
+    // The second click of a double click
+    if clicked_at.saturating_sub(state.tray_double_clicked_at.load(Ordering::SeqCst)) < wait {
+        return;
+    }
+    let behavior = state.settings.lock().map(|s| s.tray).unwrap_or_default();
+    let click = state.tray_clicks.fetch_add(1, Ordering::SeqCst) + 1;
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if behavior.double_click != TrayAction::Nothing && wait > 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(wait)).await;
+            if app.state::<AppState>().tray_clicks.load(Ordering::SeqCst) != click {
+                return;
+            }
+        }
+        run_tray_action(&app, behavior.click, Some(icon), clicked_at);
+    });
+}
+
+/// A double click on the tray icon (Windows); cancels the pending click.
+fn on_tray_double_click(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let now = now_millis();
+    state.tray_double_clicked_at.store(now, Ordering::SeqCst);
+    state.tray_clicks.fetch_add(1, Ordering::SeqCst);
+    let action = state.settings.lock().map(|s| s.tray.double_click).unwrap_or(TrayAction::Nothing);
+    run_tray_action(app, action, None, now);
+}
+
+/// The tray icon's position and size in physical pixels.
+fn tray_icon_rect(app: &AppHandle, rect: tauri::Rect) -> dock::Rect {
+    // Windows and macOS report physical pixels already
+    let scale = app.primary_monitor().ok().flatten().map(|m| m.scale_factor()).unwrap_or(1.0);
+    let position = rect.position.to_physical::<i32>(scale);
+    let size = rect.size.to_physical::<u32>(scale);
+    dock::Rect { x: position.x, y: position.y, width: size.width, height: size.height }
+}
+
+/// Open the tray menu on left click only when that is what clicks do, or
+/// when the power menu is up.
+pub fn apply_tray_behavior(app: &AppHandle, behavior: TraySettings) {
+    let power = app.state::<AppState>().tray_power_menu.load(Ordering::SeqCst);
+    let on_left_click = power || behavior.click == TrayAction::Menu;
+    if let Some(tray) = app.tray_by_id("main-tray") {
+        if let Err(e) = tray.set_show_menu_on_left_click(on_left_click) {
+            eprintln!("[tulsbot] Failed to set tray click behavior: {}", e);
+        }
+    }
+}
+
+/// Swap in the power menu while Option is held over the tray icon, and
+/// back when it is released or the pointer leaves. macOS opens the menu on
+/// mouse down, before the click event arrives, so this has to happen on
+/// hover.
+fn sync_tray_menu(app: &AppHandle, hovering: bool) {
+    let state = app.state::<AppState>();
+    let power = hovering && option_held();
+    if state.tray_power_menu.swap(power, Ordering::SeqCst) == power {
+        return;
+    }
+    refresh_tray_menu(app);
+    apply_tray_behavior(app, state.settings.lock().map(|s| s.tray).unwrap_or_default());
+}
+
+/// Restart every service of the active profile, one after another.
+async fn restart_all_services(app: AppHandle) {
+    let services: Vec<String> = app
+        .state::<AppState>()
+        .profiles
+        .lock_or_recover()
+        .active_profile()
+        .services
+        .iter()
+        .map(|s| s.name.clone())
+        .collect();
+    for service in services {
+        if let Err(e) = restart_service(app.clone(), service.clone()).await {
+            eprintln!("[tulsbot] Failed to restart {}: {}", service, e);
+        }
+    }
+}
+
+pub fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let menu = build_tray_menu(app)?;
+    let behavior = app.state::<AppState>().settings.lock().map(|s| s.tray).unwrap_or_default();
+
+    let _tray = TrayIconBuilder::with_id("main-tray")
+        .icon(tray_base_icon(app))
+        .icon_as_template(true)
+        .menu(&menu)
+        .show_menu_on_left_click(behavior.click == TrayAction::Menu)
+        .tooltip(tr(app, "tray-tooltip"))
+        .on_menu_event(move |app, event| {
+            let app = app.clone();
+            match event.id().as_ref() {
+                "open" => open_dashboard(&app, false),
+                "settings" => open_dashboard(&app, true),
+                "quit" => {
+                    app.exit(0);
+                }
+                "focus" => {
+                    if app.state::<AppState>().focus.lock_or_recover().is_some() {
+                        end_focus_session(&app, false);
+                        return;
+                    }
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = start_focus_session(&app, None, None).await {
+                            eprintln!("[tulsbot] Failed to start focus session: {}", e);
+                        }
+                    });
+                }
+                "lan-stop" => {
+                    stop_lan_exposure(&app);
+                }
+                "restart-all" => {
+                    tauri::async_runtime::spawn(restart_all_services(app));
+                }
+                "copy-diagnostics" => {
+                    let text = diagnostics_text(&app);
+                    tauri::async_runtime::spawn_blocking(move || {
+                        if let Err(e) = typing::write_clipboard(&text) {
+                            eprintln!("[tulsbot] Failed to copy diagnostics: {}", e);
+                        }
+                    });
+                }
+                "debug-logging" => {
+                    middleware::set_debug(!middleware::debug_logging());
+                    refresh_tray_menu(&app);
+                }
+                "privacy" => {
+                    let enabled = !privacy_mode(&app);
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = set_privacy_mode(app, enabled).await {
+                            eprintln!("[tulsbot] Failed to toggle privacy mode: {}", e);
+                        }
+                    });
+                }
+                other => {
+                    if let Some(name) = other.strip_prefix("profile:") {
+                        if let Err(e) = apply_profile(&app, name) {
+                            eprintln!("[tulsbot] Failed to switch profile: {}", e);
+                        }
+                    } else if let Some(name) = other.strip_prefix("layout:") {
+                        let name = name.to_string();
+                        tauri::async_runtime::spawn(async move {
+                            if let Err(e) = apply_layout(app, name).await {
+                                eprintln!("[tulsbot] Failed to apply layout: {}", e);
+                            }
+                        });
+                    } else if let Some(service) = other.strip_prefix("restart:") {
+                        let service = service.to_string();
+                        tauri::async_runtime::spawn(async move {
+                            if let Err(e) = restart_service(app, service.clone()).await {
+                                eprintln!("[tulsbot] Failed to restart {}: {}", service, e);
+                            }
+                        });
+                    } else if let Some(choice) = other.strip_prefix("credential:") {
+                        let Some((provider, label)) = choice.split_once(':') else {
+                            return;
+                        };
+                        let (provider, label) = (provider.to_string(), label.to_string());
+                        tauri::async_runtime::spawn(async move {
+                            let state = app.state::<AppState>();
+                            let result =
+                                select_credential(app.clone(), state, provider, label).await;
+                            if let Err(e) = result {
+                                eprintln!("[tulsbot] Failed to switch credential: {}", e);
+                            }
+                        });
+                    }
+                }
+            }
+        })
+        .on_tray_icon_event(|tray, event| {
+            let app = tray.app_handle();
+            // Headless: windows are only opened explicitly, never by the tray
+            if app.state::<AppState>().headless.load(Ordering::Relaxed) {
+                return;
+            }
+            match event {
+                TrayIconEvent::Enter { .. } | TrayIconEvent::Move { .. } => {
+                    sync_tray_menu(app, true)
+                }
+                TrayIconEvent::Leave { .. } => sync_tray_menu(app, false),
+                TrayIconEvent::Click {
+                    button: MouseButton::Left,
+                    button_state: MouseButtonState::Up,
+                    rect,
+                    ..
+                } if !app.state::<AppState>().tray_power_menu.load(Ordering::SeqCst) => {
+                    // Otherwise an Option-click opened the power menu
+                    on_tray_click(app, tray_icon_rect(app, rect))
+                }
+                TrayIconEvent::DoubleClick { button: MouseButton::Left, .. } => {
+                    on_tray_double_click(app)
+                }
+                _ => {}
+            }
+        })
+        .build(app)?;
+
+    Ok(())
+}
+
+/// Run from the menu bar only (macOS), or with a Dock icon as usual.
+#[instrumented]
+#[tauri::command]
+pub async fn set_menu_bar_mode(app: AppHandle, enabled: bool) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        settings.menu_bar_only = enabled;
+        settings.clone()
+    };
+    settings::save(&active_data_dir(&app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    let dashboard_open =
+        app.get_webview_window("main").is_some_and(|w| w.is_visible().unwrap_or(false));
+    update_dock_icon(&app, dashboard_open);
+    Ok(())
+}
+
+/// False when the desktop shows no tray icon and the dashboard stands in
+/// for it.
+#[instrumented]
+#[tauri::command]
+pub async fn tray_available(state: State<'_, AppState>) -> Result<bool, AppError> {
+    Ok(!state.tray_missing.load(Ordering::Relaxed))
+}
+
+/// Quit the app, for when there is no tray menu to do it from.
+#[instrumented]
+#[tauri::command]
+pub async fn quit_app(app: AppHandle) -> Result<(), AppError> {
+    app.exit(0);
+    Ok(())
+}
+
+/// Set what clicking and double-clicking the tray icon do. Double clicks
+/// are only reported on Windows.
+#[instrumented]
+#[tauri::command]
+pub async fn set_tray_behavior(
+    app: AppHandle,
+    click: TrayAction,
+    double_click: TrayAction,
+) -> Result<TraySettings, AppError> {
+    if double_click == TrayAction::Menu {
+        let message = "The menu can't open on double click";
+        return Err(AppError::new(ErrorKind::InvalidInput, message));
+    }
+    let behavior = TraySettings { click, double_click };
+    let state = app.state::<AppState>();
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        settings.tray = behavior;
+        settings.clone()
+    };
+    settings::save(&active_data_dir(&app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    apply_tray_behavior(&app, behavior);
+    Ok(behavior)
+}
@@ -0,0 +1,56 @@
+use tauri::image::Image;
+
+// ── Tray activity spinner ───────────────────────────────────────────────────
+//
+// While a response is generating or a long job runs, the tray icon shows a
+// ring of dots circling the health-colored icon, so the work is visible with
+// every window hidden. Frames are composited here from the current health
+// icon rather than shipped as files, so the spinner always matches it.
+
+pub const FRAMES: usize = 8;
+/// Delay between frames.
+pub const FRAME_MS: u64 = 120;
+
+/// Opacity of the dots behind the leading one, leading first.
+const TRAIL: [f32; FRAMES] = [1.0, 0.7, 0.45, 0.25, 0.12, 0.12, 0.12, 0.12];
+
+/// Draw `alpha` worth of white over the pixel at `i`.
+fn blend(rgba: &mut [u8], i: usize, alpha: f32) {
+    for c in &mut rgba[i..i + 3] {
+        *c = (f32::from(*c) * (1.0 - alpha) + 255.0 * alpha).round() as u8;
+    }
+    let a = f32::from(rgba[i + 3]) / 255.0;
+    rgba[i + 3] = ((a + alpha * (1.0 - a)) * 255.0).round() as u8;
+}
+
+/// Frame `frame` of the spinner over `base`.
+pub fn frame(base: &Image<'_>, frame: usize) -> Image<'static> {
+    let (w, h) = (base.width(), base.height());
+    let mut rgba = base.rgba().to_vec();
+    let size = w.min(h) as f32;
+    let (cx, cy) = (w as f32 / 2.0, h as f32 / 2.0);
+    let ring = size / 2.0 - size * 0.09;
+    let dot = (size * 0.09).max(1.0);
+    for (n, opacity) in TRAIL.iter().enumerate() {
+        // The leading dot moves clockwise one step per frame
+        let step = (frame + FRAMES - n) % FRAMES;
+        let angle = step as f32 / FRAMES as f32 * std::f32::consts::TAU;
+        let (dx, dy) = (cx + ring * angle.sin(), cy - ring * angle.cos());
+        for y in 0..h {
+            for x in 0..w {
+                let dist = ((x as f32 + 0.5 - dx).powi(2) + (y as f32 + 0.5 - dy).powi(2)).sqrt();
+                // One pixel of anti-aliasing at the edge
+                let coverage = (dot - dist + 0.5).clamp(0.0, 1.0);
+                if coverage > 0.0 {
+                    blend(&mut rgba, ((y * w + x) * 4) as usize, coverage * opacity);
+                }
+            }
+        }
+    }
+    Image::new_owned(rgba, w, h)
+}
+
+/// Every frame over `base`.
+pub fn frames(base: &Image<'_>) -> Vec<Image<'static>> {
+    (0..FRAMES).map(|n| frame(base, n)).collect()
+}
@@ -0,0 +1,291 @@
+use serde::Deserialize;
+use std::process::Command;
+use std::time::Duration;
+
+// ── Inserting text into the app the user was typing in ──────────────────────
+//
+// A popover action hands the reply back to the app that was frontmost before
+// Tulsbot: that app is brought forward again, then the text is pasted through
+// the clipboard (whose text is put back afterwards) or, for fields that block
+// pasting, typed key by key. Keystrokes go through the same tools the context
+// detection uses: System Events on macOS, which needs the Accessibility
+// permission, SendKeys on Windows, and `xdotool` on X11 or `wtype` on
+// Wayland. Blocking, so call from a blocking task.
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InsertMode {
+    #[default]
+    Paste,
+    Type,
+}
+
+/// Time for the target app to take focus before keys are sent.
+const FOCUS_DELAY: Duration = Duration::from_millis(200);
+/// Time for the target app to read the clipboard before it is restored.
+const PASTE_DELAY: Duration = Duration::from_millis(300);
+
+#[cfg(target_os = "macos")]
+const ACCESSIBILITY_SETTINGS: &str =
+    "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility";
+
+pub fn run(command: &mut Command) -> Result<String, String> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let output = command.output().map_err(|e| format!("{}: {}", program, e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{} failed: {}", program, stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Run `program` with `input` on stdin. Output is discarded rather than
+/// captured: clipboard tools fork a daemon that keeps it open.
+#[cfg(not(target_os = "windows"))]
+fn run_with_stdin(program: &str, args: &[&str], input: &str) -> Result<(), String> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("{}: {}", program, e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.as_bytes()).map_err(|e| e.to_string())?;
+    }
+    let status = child.wait().map_err(|e| e.to_string())?;
+    if !status.success() {
+        return Err(format!("{} failed", program));
+    }
+    Ok(())
+}
+
+/// Bring the app with `target_pid` forward and insert `text` at its cursor.
+pub fn insert(text: &str, target_pid: Option<u32>, mode: InsertMode) -> Result<(), String> {
+    if let Some(pid) = target_pid {
+        activate(pid)?;
+    }
+    std::thread::sleep(FOCUS_DELAY);
+    match mode {
+        InsertMode::Paste => {
+            let saved = read_clipboard().filter(|s| !s.is_empty());
+            write_clipboard(text)?;
+            send_command_key('v')?;
+            std::thread::sleep(PASTE_DELAY);
+            if let Some(saved) = saved {
+                let _ = write_clipboard(&saved);
+            }
+            Ok(())
+        }
+        InsertMode::Type => type_text(text),
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn osascript(script: &str) -> Result<String, String> {
+    run(Command::new("osascript").args(["-e", script]))
+}
+
+/// Whether the OS lets Tulsbot send keystrokes to other apps.
+#[cfg(target_os = "macos")]
+pub fn permitted() -> bool {
+    osascript(r#"tell application "System Events" to get UI elements enabled"#)
+        .is_ok_and(|out| out.trim() == "true")
+}
+
+/// Open the Accessibility pane of System Settings, where the user grants
+/// the permission.
+#[cfg(target_os = "macos")]
+pub fn request_permission() {
+    let _ = Command::new("open").arg(ACCESSIBILITY_SETTINGS).status();
+}
+
+#[cfg(target_os = "macos")]
+fn activate(pid: u32) -> Result<(), String> {
+    let script = format!(
+        "tell application \"System Events\" to set frontmost of \
+         (first process whose unix id is {}) to true",
+        pid
+    );
+    osascript(&script).map(drop)
+}
+
+#[cfg(target_os = "macos")]
+pub fn read_clipboard() -> Option<String> {
+    run(&mut Command::new("pbpaste")).ok()
+}
+
+#[cfg(target_os = "macos")]
+pub fn write_clipboard(text: &str) -> Result<(), String> {
+    run_with_stdin("pbcopy", &[], text)
+}
+
+/// Press `key` with Command (Control elsewhere) in the frontmost app.
+#[cfg(target_os = "macos")]
+pub fn send_command_key(key: char) -> Result<(), String> {
+    let script = format!(
+        r#"tell application "System Events" to keystroke "{}" using command down"#,
+        key
+    );
+    osascript(&script).map(drop)
+}
+
+#[cfg(target_os = "macos")]
+fn type_text(text: &str) -> Result<(), String> {
+    // Passed as an argument so the text needs no AppleScript quoting
+    run(Command::new("osascript").args([
+        "-e",
+        "on run argv",
+        "-e",
+        r#"tell application "System Events" to keystroke (item 1 of argv)"#,
+        "-e",
+        "end run",
+        "--",
+        text,
+    ]))
+    .map(drop)
+}
+
+#[cfg(target_os = "windows")]
+pub fn powershell(script: &str, text: Option<&str>) -> Result<String, String> {
+    let mut command = Command::new("powershell");
+    command.args(["-NoProfile", "-NonInteractive", "-Command", script]);
+    // Handed over in the environment so the text needs no quoting
+    if let Some(text) = text {
+        command.env("TULSBOT_TEXT", text);
+    }
+    run(&mut command)
+}
+
+#[cfg(target_os = "windows")]
+pub fn permitted() -> bool {
+    true
+}
+
+#[cfg(target_os = "windows")]
+pub fn request_permission() {}
+
+#[cfg(target_os = "windows")]
+fn activate(pid: u32) -> Result<(), String> {
+    let script = format!("(New-Object -ComObject WScript.Shell).AppActivate({})", pid);
+    powershell(&script, None).map(drop)
+}
+
+#[cfg(target_os = "windows")]
+pub fn read_clipboard() -> Option<String> {
+    powershell("Get-Clipboard -Raw", None).ok()
+}
+
+#[cfg(target_os = "windows")]
+pub fn write_clipboard(text: &str) -> Result<(), String> {
+    powershell("Set-Clipboard -Value $env:TULSBOT_TEXT", Some(text)).map(drop)
+}
+
+#[cfg(target_os = "windows")]
+fn send_keys(keys: &str) -> Result<(), String> {
+    let script = "Add-Type -AssemblyName System.Windows.Forms; \
+                  [System.Windows.Forms.SendKeys]::SendWait($env:TULSBOT_TEXT)";
+    powershell(script, Some(keys)).map(drop)
+}
+
+#[cfg(target_os = "windows")]
+pub fn send_command_key(key: char) -> Result<(), String> {
+    send_keys(&format!("^{}", key))
+}
+
+#[cfg(target_os = "windows")]
+fn type_text(text: &str) -> Result<(), String> {
+    // SendKeys treats these as modifiers and groupings unless braced
+    let keys: String = text
+        .chars()
+        .filter(|&c| c != '\r')
+        .map(|c| match c {
+            '+' | '^' | '%' | '~' | '(' | ')' | '{' | '}' | '[' | ']' => format!("{{{}}}", c),
+            '\n' => "{ENTER}".to_string(),
+            c => c.to_string(),
+        })
+        .collect();
+    send_keys(&keys)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn wayland() -> bool {
+    std::env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn permitted() -> bool {
+    true
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn request_permission() {}
+
+/// Wayland doesn't let clients raise other apps' windows; the target gets
+/// focus back when the popover hides.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn activate(pid: u32) -> Result<(), String> {
+    if wayland() {
+        return Ok(());
+    }
+    let windows = run(Command::new("xdotool").args(["search", "--pid", &pid.to_string()]))?;
+    let Some(window) = windows.lines().last() else {
+        return Ok(());
+    };
+    run(Command::new("xdotool").args(["windowactivate", "--sync", window])).map(drop)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn read_clipboard() -> Option<String> {
+    let mut command = if wayland() {
+        let mut command = Command::new("wl-paste");
+        command.arg("--no-newline");
+        command
+    } else {
+        let mut command = Command::new("xclip");
+        command.args(["-selection", "clipboard", "-o"]);
+        command
+    };
+    run(&mut command).ok()
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn write_clipboard(text: &str) -> Result<(), String> {
+    if wayland() {
+        run_with_stdin("wl-copy", &[], text)
+    } else {
+        run_with_stdin("xclip", &["-selection", "clipboard"], text)
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn send_command_key(key: char) -> Result<(), String> {
+    let key = key.to_string();
+    let mut command = if wayland() {
+        let mut command = Command::new("wtype");
+        command.args(["-M", "ctrl", &key, "-m", "ctrl"]);
+        command
+    } else {
+        let mut command = Command::new("xdotool");
+        command.args(["key", "--clearmodifiers", &format!("ctrl+{}", key)]);
+        command
+    };
+    run(&mut command).map(drop)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn type_text(text: &str) -> Result<(), String> {
+    let mut command = if wayland() {
+        let mut command = Command::new("wtype");
+        command.args(["--", text]);
+        command
+    } else {
+        let mut command = Command::new("xdotool");
+        command.args(["type", "--clearmodifiers", "--delay", "2", "--", text]);
+        command
+    };
+    run(&mut command).map(drop)
+}
@@ -0,0 +1,64 @@
+use futures_util::Stream;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+// ── Multipart uploads ───────────────────────────────────────────────────────
+//
+// `api_proxy` only speaks JSON, but the Context Manager's upload endpoint
+// wants multipart form data. Files are streamed from disk in chunks instead
+// of being read into memory first, and the webview follows along through
+// `upload-progress` events. Text files are the exception: they are read
+// whole so redaction can run over them, like a proxied body.
+
+const CHUNK: usize = 64 * 1024;
+/// Extensions of files read as text and redacted before upload.
+const TEXT_EXTENSIONS: &[&str] =
+    &["txt", "md", "markdown", "json", "jsonl", "csv", "tsv", "yaml", "yml", "xml", "html", "log"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UploadProgress {
+    pub upload_id: String,
+    pub sent: u64,
+    pub total: u64,
+}
+
+/// Whether a file named `name` of type `mime` is text to redact rather than
+/// bytes to stream as they are.
+pub fn is_text(mime: Option<&str>, name: &str) -> bool {
+    if let Some(mime) = mime {
+        let mime = mime.to_ascii_lowercase();
+        return mime.starts_with("text/") || mime.ends_with("json") || mime.ends_with("xml");
+    }
+    let extension = std::path::Path::new(name).extension().and_then(|e| e.to_str());
+    extension.is_some_and(|e| TEXT_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+/// Stream `file` in chunks, emitting `upload-progress` whenever another
+/// percent of `total` has been read, and once more at the end.
+pub fn progress_stream(
+    app: AppHandle,
+    upload_id: String,
+    file: impl AsyncRead + Unpin + Send + 'static,
+    total: u64,
+) -> impl Stream<Item = std::io::Result<Vec<u8>>> + Send + 'static {
+    futures_util::stream::try_unfold((file, 0u64, 0u64), move |(mut file, sent, reported)| {
+        let (app, upload_id) = (app.clone(), upload_id.clone());
+        async move {
+            let mut chunk = vec![0; CHUNK];
+            let n = file.read(&mut chunk).await?;
+            if n == 0 {
+                return Ok(None);
+            }
+            chunk.truncate(n);
+            let sent = sent + n as u64;
+            let percent = (sent * 100).checked_div(total).unwrap_or(100);
+            let mut reported = reported;
+            if percent > reported || sent >= total {
+                let _ = app.emit("upload-progress", UploadProgress { upload_id, sent, total });
+                reported = percent;
+            }
+            Ok(Some((chunk, (file, sent, reported))))
+        }
+    })
+}
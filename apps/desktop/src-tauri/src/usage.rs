@@ -0,0 +1,311 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
+use tulsbot_macros::instrumented;
+
+use crate::conversations;
+use crate::budgets::warn_about_budget;
+use crate::error::AppError;
+use crate::export::{self, ExportFormat};
+use crate::locks::LockExt;
+use crate::profiles::active_data_dir;
+use crate::state::AppState;
+
+// ── Usage log (one record per provider / proxy request) ─────────────────────
+//
+// Stored in the `usage` table of `usage.db`, a SQLite database in the
+// profile's data dir, and aggregated on demand for the dashboard's analytics
+// page.
+
+pub const DB_FILE: &str = "usage.db";
+/// How long a write waits for another connection to finish with the file.
+const BUSY_TIMEOUT_SECS: u64 = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    /// Unix seconds.
+    pub at: u64,
+    /// `provider` for shell-side completions, `proxy` for `api_proxy` calls,
+    /// `upload` for `upload_file`.
+    pub source: String,
+    /// Provider name, or the host for proxied requests.
+    pub provider: String,
+    pub model: Option<String>,
+    pub latency_ms: u64,
+    pub input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageRange {
+    Hour,
+    Day,
+    Week,
+    Month,
+    All,
+}
+
+impl UsageRange {
+    /// Oldest timestamp included when asking at `now`.
+    pub fn since(self, now: u64) -> u64 {
+        let secs = match self {
+            Self::Hour => 60 * 60,
+            Self::Day => 24 * 60 * 60,
+            Self::Week => 7 * 24 * 60 * 60,
+            Self::Month => 30 * 24 * 60 * 60,
+            Self::All => return 0,
+        };
+        now.saturating_sub(secs)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    Provider,
+    Model,
+    /// Local calendar day, `YYYY-MM-DD`.
+    Day,
+    /// Local hour, `YYYY-MM-DD HH:00`.
+    Hour,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageStats {
+    pub key: String,
+    pub requests: u64,
+    pub errors: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub avg_latency_ms: u64,
+    pub p95_latency_ms: u64,
+}
+
+/// Open the profile's usage database, creating it and the `usage` table
+/// when missing. Other analytics tables live in the same file.
+pub fn open(dir: &Path) -> Result<Connection, String> {
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let db = Connection::open(dir.join(DB_FILE)).map_err(|e| e.to_string())?;
+    // Records are written from blocking tasks that may overlap
+    db.busy_timeout(std::time::Duration::from_secs(BUSY_TIMEOUT_SECS))
+        .map_err(|e| e.to_string())?;
+    db.execute_batch(
+        "CREATE TABLE IF NOT EXISTS usage (
+             at INTEGER NOT NULL,
+             source TEXT NOT NULL,
+             provider TEXT NOT NULL,
+             model TEXT,
+             latency_ms INTEGER NOT NULL,
+             input_tokens INTEGER,
+             output_tokens INTEGER,
+             error TEXT
+         );
+         CREATE INDEX IF NOT EXISTS usage_at ON usage (at);",
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(db)
+}
+
+pub fn record(dir: &Path, record: &UsageRecord) {
+    let inserted = open(dir).and_then(|db| {
+        db.execute(
+            "INSERT INTO usage (at, source, provider, model, latency_ms, input_tokens,
+                                output_tokens, error)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                record.at,
+                record.source,
+                record.provider,
+                record.model,
+                record.latency_ms,
+                record.input_tokens,
+                record.output_tokens,
+                record.error,
+            ],
+        )
+        .map_err(|e| e.to_string())
+    });
+    if let Err(e) = inserted {
+        eprintln!("[tulsbot] Failed to record usage: {}", e);
+    }
+}
+
+/// Records at or after `since`, oldest first.
+pub fn load_since(dir: &Path, since: u64) -> Vec<UsageRecord> {
+    let loaded = open(dir).and_then(|db| {
+        let mut query = db
+            .prepare(
+                "SELECT at, source, provider, model, latency_ms, input_tokens, output_tokens,
+                        error
+                 FROM usage WHERE at >= ?1 ORDER BY at",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = query
+            .query_map([since], |row| {
+                Ok(UsageRecord {
+                    at: row.get(0)?,
+                    source: row.get(1)?,
+                    provider: row.get(2)?,
+                    model: row.get(3)?,
+                    latency_ms: row.get(4)?,
+                    input_tokens: row.get(5)?,
+                    output_tokens: row.get(6)?,
+                    error: row.get(7)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+    });
+    loaded.unwrap_or_else(|e| {
+        eprintln!("[tulsbot] Failed to read usage: {}", e);
+        Vec::new()
+    })
+}
+
+/// Delete the records older than `before`, or only count them when
+/// `dry_run`.
+pub fn prune(dir: &Path, before: u64, dry_run: bool) -> Result<usize, String> {
+    let db = open(dir)?;
+    let removed = if dry_run {
+        db.query_row("SELECT COUNT(*) FROM usage WHERE at < ?1", [before], |row| row.get(0))
+    } else {
+        db.execute("DELETE FROM usage WHERE at < ?1", [before])
+    };
+    removed.map_err(|e| e.to_string())
+}
+
+/// Token counts from a provider response body, whichever API shape it has
+/// (OpenAI, Anthropic or Ollama).
+pub fn tokens_from_body(body: &str) -> (Option<u64>, Option<u64>) {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(body) else {
+        return (None, None);
+    };
+    let first = |paths: &[&str]| paths.iter().find_map(|p| json.pointer(p)?.as_u64());
+    (
+        first(&["/usage/prompt_tokens", "/usage/input_tokens", "/prompt_eval_count"]),
+        first(&["/usage/completion_tokens", "/usage/output_tokens", "/eval_count"]),
+    )
+}
+
+/// The `model` field of a JSON request body, if any.
+pub fn model_from_body(body: &str) -> Option<String> {
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()?
+        .get("model")?
+        .as_str()
+        .map(String::from)
+}
+
+fn group_key(record: &UsageRecord, group_by: GroupBy) -> String {
+    let local = || {
+        chrono::DateTime::from_timestamp(record.at as i64, 0)
+            .unwrap_or_default()
+            .with_timezone(&chrono::Local)
+    };
+    match group_by {
+        GroupBy::Provider => record.provider.clone(),
+        GroupBy::Model => record.model.clone().unwrap_or_else(|| "unknown".into()),
+        GroupBy::Day => local().format("%Y-%m-%d").to_string(),
+        GroupBy::Hour => local().format("%Y-%m-%d %H:00").to_string(),
+    }
+}
+
+/// Per-group totals, ordered by key.
+pub fn aggregate(records: &[UsageRecord], group_by: GroupBy) -> Vec<UsageStats> {
+    let mut groups: BTreeMap<String, Vec<&UsageRecord>> = BTreeMap::new();
+    for record in records {
+        groups.entry(group_key(record, group_by)).or_default().push(record);
+    }
+    groups
+        .into_iter()
+        .map(|(key, records)| {
+            let mut latencies: Vec<u64> = records.iter().map(|r| r.latency_ms).collect();
+            latencies.sort_unstable();
+            let p95 = latencies[(latencies.len() * 95).div_ceil(100).saturating_sub(1)];
+            UsageStats {
+                key,
+                requests: records.len() as u64,
+                errors: records.iter().filter(|r| r.error.is_some()).count() as u64,
+                input_tokens: records.iter().filter_map(|r| r.input_tokens).sum(),
+                output_tokens: records.iter().filter_map(|r| r.output_tokens).sum(),
+                avg_latency_ms: latencies.iter().sum::<u64>() / latencies.len() as u64,
+                p95_latency_ms: p95,
+            }
+        })
+        .collect()
+}
+
+pub fn record_usage(app: &AppHandle, record: UsageRecord) {
+    let dir = match active_data_dir(app) {
+        Ok(dir) => dir,
+        Err(e) => {
+            eprintln!("[tulsbot] Failed to record usage: {}", e);
+            return;
+        }
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        self::record(&dir, &record);
+        warn_about_budget(&app, &record.provider);
+    });
+}
+
+/// Request counts, tokens, errors and latency over `range`, grouped for the
+/// analytics page.
+#[instrumented]
+#[tauri::command]
+pub async fn get_usage_stats(
+    app: AppHandle,
+    range: UsageRange,
+    group_by: GroupBy,
+) -> Result<Vec<UsageStats>, AppError> {
+    let dir = active_data_dir(&app)?;
+    let since = range.since(conversations::now());
+    Ok(tauri::async_runtime::spawn_blocking(move || {
+        aggregate(&load_since(&dir, since), group_by)
+    })
+    .await
+    .map_err(|e| e.to_string())?)
+}
+
+/// Write the usage records of `range` to `path` as CSV or Parquet, returning
+/// the number of rows written.
+#[instrumented]
+#[tauri::command]
+pub async fn export_usage(
+    app: AppHandle,
+    range: UsageRange,
+    format: ExportFormat,
+    path: String,
+) -> Result<usize, AppError> {
+    let dir = active_data_dir(&app)?;
+    let since = range.since(conversations::now());
+    Ok(tauri::async_runtime::spawn_blocking(move || {
+        export::usage(&load_since(&dir, since), format, &PathBuf::from(path))
+    })
+    .await
+    .map_err(|e| e.to_string())??)
+}
+
+/// Write the health samples of `range` (at most the last 24 hours are kept)
+/// to `path`, one row per service and sample.
+#[instrumented]
+#[tauri::command]
+pub async fn export_health_history(
+    state: State<'_, AppState>,
+    range: UsageRange,
+    format: ExportFormat,
+    path: String,
+) -> Result<usize, AppError> {
+    let since = range.since(conversations::now());
+    let samples = state.history.lock_or_recover().since(since);
+    Ok(tauri::async_runtime::spawn_blocking(move || {
+        export::health(&samples, format, &PathBuf::from(path))
+    })
+    .await
+    .map_err(|e| e.to_string())??)
+}
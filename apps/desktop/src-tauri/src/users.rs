@@ -0,0 +1,78 @@
+use std::path::{Path, PathBuf};
+
+// ── Per-OS-user isolation ───────────────────────────────────────────────────
+//
+// The app data dir already lives under the user's home, so on a shared machine
+// the risks are elsewhere: permissive modes on that dir, a profile whose data
+// dir points at a shared location, keychain entries visible to a shared
+// Secret Service collection, and scratch files under the system temp dir.
+// Everything here is keyed by the OS account; profiles are switched on top of
+// it and never cross accounts.
+
+/// Name of the OS account running the app, reduced to `[A-Za-z0-9._-]` so it
+/// can be used in paths and keychain account names.
+pub fn current() -> String {
+    let name = ["USER", "USERNAME", "LOGNAME"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|v| !v.trim().is_empty()))
+        .or_else(|| dirs::home_dir()?.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "default".into());
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') { c } else { '_' })
+        .collect()
+}
+
+/// Make `dir` private to the current account (mode 0700). A no-op on Windows,
+/// where the profile directory ACLs already exclude other users.
+pub fn restrict(dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))
+            .map_err(|e| format!("Could not restrict {}: {}", dir.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Whether `path` belongs to the current account, judged by comparing its
+/// owner with the home directory's. Missing paths count as ours.
+#[cfg(unix)]
+fn owned(path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    let Ok(meta) = std::fs::metadata(path) else {
+        return true;
+    };
+    dirs::home_dir()
+        .and_then(|home| std::fs::metadata(home).ok())
+        .is_none_or(|home| home.uid() == meta.uid())
+}
+
+#[cfg(not(unix))]
+fn owned(_path: &Path) -> bool {
+    true
+}
+
+/// Data dir to use for a profile configured with `dir`: the dir itself when
+/// this account owns it, otherwise a per-account subdirectory so two users
+/// pointing at the same shared folder never read each other's files.
+pub fn scoped(dir: &Path) -> PathBuf {
+    if owned(dir) {
+        dir.to_path_buf()
+    } else {
+        dir.join("users").join(current())
+    }
+}
+
+/// Private scratch dir under the system temp dir, which is shared by every
+/// account on Unix.
+pub fn scratch_dir() -> Result<PathBuf, String> {
+    let dir = std::env::temp_dir().join(format!("tulsbot-{}", current()));
+    restrict(&dir)?;
+    Ok(dir)
+}
+
+/// Keychain account name of `account` for the current OS user.
+pub fn keychain_account(account: &str) -> String {
+    format!("{}/{}", current(), account)
+}
@@ -0,0 +1,97 @@
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+// ── Connection warm-up ──────────────────────────────────────────────────────
+//
+// The first proxied request to a backend used to pay for opening the
+// connection on top of its own latency, which is what the popover's first
+// message felt. At startup, after a profile switch and after the machine
+// wakes, every service of the active profile gets a `HEAD /` so the proxy's
+// pool already holds a connection. Local backends are cleartext, where
+// HTTP/2 can't be negotiated: it is tried with prior knowledge and kept when
+// the backend answers, otherwise the service is reached over HTTP/1.1.
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+/// Idle pooled connections are kept this long.
+const POOL_IDLE: Duration = Duration::from_secs(300);
+/// How often the clock is checked for a sleep; a tick that took three
+/// times as long means the machine was suspended in between.
+pub const WAKE_CHECK: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Protocol {
+    #[serde(rename = "http/1.1")]
+    Http1,
+    #[serde(rename = "h2")]
+    Http2,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionInfo {
+    pub service: String,
+    pub port: u16,
+    /// `None` when the backend couldn't be reached.
+    pub protocol: Option<Protocol>,
+    /// Time to connect and answer the warm-up request.
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+    /// Unix seconds.
+    pub warmed_at: u64,
+}
+
+/// The proxy's HTTP clients, one per protocol.
+pub struct Clients {
+    http1: reqwest::Client,
+    http2: reqwest::Client,
+}
+
+impl Default for Clients {
+    fn default() -> Self {
+        let builder = || reqwest::Client::builder().pool_idle_timeout(POOL_IDLE);
+        Self {
+            http1: builder().build().unwrap_or_default(),
+            http2: builder().http2_prior_knowledge().build().unwrap_or_default(),
+        }
+    }
+}
+
+impl Clients {
+    /// Client for a backend that speaks `protocol` (HTTP/1.1 when unknown).
+    pub fn get(&self, protocol: Option<Protocol>) -> reqwest::Client {
+        match protocol {
+            Some(Protocol::Http2) => self.http2.clone(),
+            _ => self.http1.clone(),
+        }
+    }
+}
+
+/// Open a pooled connection to the service on `port`, preferring HTTP/2.
+/// `localhost` is warmed as well when it answers, since the frontend may use
+/// either name.
+pub async fn warm(clients: &Clients, service: &str, port: u16, now: u64) -> ConnectionInfo {
+    let mut info = ConnectionInfo {
+        service: service.to_string(),
+        port,
+        protocol: None,
+        latency_ms: None,
+        error: None,
+        warmed_at: now,
+    };
+    for protocol in [Protocol::Http2, Protocol::Http1] {
+        let client = clients.get(Some(protocol));
+        let started = Instant::now();
+        let url = format!("http://127.0.0.1:{}/", port);
+        match client.head(url).timeout(TIMEOUT).send().await {
+            Ok(_) => {
+                info.protocol = Some(protocol);
+                info.latency_ms = Some(started.elapsed().as_millis() as u64);
+                info.error = None;
+                let url = format!("http://localhost:{}/", port);
+                let _ = client.head(url).timeout(TIMEOUT).send().await;
+                break;
+            }
+            Err(e) => info.error = Some(format!("Request failed: {}", e)),
+        }
+    }
+    info
+}
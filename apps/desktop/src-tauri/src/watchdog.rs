@@ -0,0 +1,158 @@
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tulsbot_macros::instrumented;
+
+use crate::settings;
+use crate::error::AppError;
+use crate::locks::LockExt;
+use crate::pairing::app_data_dir;
+use crate::profiles::active_data_dir;
+use crate::state::AppState;
+
+// ── Crash watchdog ──────────────────────────────────────────────────────────
+//
+// With `Settings::watchdog` on, the app starts a copy of itself with
+// `--watchdog` that does nothing but wait for it to go away. The two are
+// joined by a pipe on the watchdog's stdin: the app writes "stop" to it
+// before a clean exit (or when the setting is switched off), while a crash
+// closes it without a word. After a crash the watchdog relaunches the app,
+// which starts a watchdog of its own, and exits. Relaunches are recorded in
+// the app data dir, and a watchdog that finds too many recent ones gives up
+// rather than feed a crash loop.
+
+const FLAG: &str = "--watchdog";
+const FILE_NAME: &str = "relaunches.json";
+/// Relaunches allowed within `WINDOW_SECS` before the watchdog gives up.
+const MAX_RELAUNCHES: usize = 3;
+const WINDOW_SECS: u64 = 10 * 60;
+/// Lets the crashed instance's ports and files be released first.
+const RELAUNCH_DELAY: Duration = Duration::from_secs(2);
+
+/// Started by the app as its watchdog.
+pub fn is_invocation() -> bool {
+    std::env::args().nth(1).as_deref() == Some(FLAG)
+}
+
+/// Start a watchdog for this process, keeping its relaunch record in
+/// `dir`. The app's flags (`--headless`) are passed on to a relaunch.
+pub fn spawn(dir: &Path) -> Result<Child, String> {
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    Command::new(exe)
+        .arg(FLAG)
+        .arg(dir)
+        .args(std::env::args().skip(1).filter(|arg| arg.starts_with("--")))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to start the watchdog: {}", e))
+}
+
+/// Tell the watchdog `child` the app is going away on purpose.
+pub fn stop(mut child: Child) {
+    if let Some(mut pipe) = child.stdin.take() {
+        let _ = pipe.write_all(b"stop");
+    }
+    // Reap it once it has read that
+    std::thread::spawn(move || child.wait());
+}
+
+/// Whether a crash at `now` (Unix seconds) may be followed by a relaunch,
+/// given the earlier relaunches in `recent`. Drops the ones that are too old
+/// to count and, when allowed, adds this one.
+pub fn allow_relaunch(recent: &mut Vec<u64>, now: u64) -> bool {
+    recent.retain(|at| now.saturating_sub(*at) < WINDOW_SECS);
+    if recent.len() >= MAX_RELAUNCHES {
+        return false;
+    }
+    recent.push(now);
+    true
+}
+
+/// Relaunch the app after a crash. Takes effect right away.
+#[instrumented]
+#[tauri::command]
+pub async fn set_watchdog(app: AppHandle, enabled: bool) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        settings.watchdog = enabled;
+        settings.clone()
+    };
+    settings::save(&active_data_dir(&app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    if enabled {
+        start_watchdog(&app)?;
+    } else {
+        stop_watchdog(&app);
+    }
+    Ok(())
+}
+
+/// Start the crash watchdog unless it is running.
+pub fn start_watchdog(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let mut running = state.watchdog.lock_or_recover();
+    if running.is_none() {
+        *running = Some(spawn(&app_data_dir(app)?)?);
+    }
+    Ok(())
+}
+
+/// Stop the crash watchdog, for a clean exit or when it is switched off.
+pub fn stop_watchdog(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let running = state.watchdog.lock_or_recover().take();
+    if let Some(child) = running {
+        stop(child);
+    }
+}
+
+// ── Watchdog side (runs in the `--watchdog` process) ────────────────────────
+
+/// Wait for the app to exit and relaunch it if it crashed.
+pub fn run() {
+    let mut args = std::env::args().skip(2);
+    let Some(dir) = args.next().map(PathBuf::from) else {
+        return;
+    };
+    let flags: Vec<String> = args.collect();
+
+    // Returns once the app's end of the pipe is closed, whichever way it went
+    let mut message = String::new();
+    let _ = std::io::stdin().read_to_string(&mut message);
+    if message.trim() == "stop" {
+        return;
+    }
+
+    let path = dir.join(FILE_NAME);
+    let mut recent: Vec<u64> = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    if !allow_relaunch(&mut recent, now) {
+        eprintln!(
+            "[tulsbot] Crashed {} times within {} minutes; not relaunching",
+            MAX_RELAUNCHES + 1,
+            WINDOW_SECS / 60
+        );
+        return;
+    }
+    if let Ok(text) = serde_json::to_string(&recent) {
+        let _ = std::fs::write(&path, text);
+    }
+
+    eprintln!("[tulsbot] The app exited unexpectedly; relaunching");
+    std::thread::sleep(RELAUNCH_DELAY);
+    let relaunched = std::env::current_exe()
+        .and_then(|exe| Command::new(exe).args(&flags).stdin(Stdio::null()).spawn());
+    if let Err(e) = relaunched {
+        eprintln!("[tulsbot] Failed to relaunch: {}", e);
+    }
+}
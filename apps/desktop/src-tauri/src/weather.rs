@@ -0,0 +1,316 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tulsbot_macros::instrumented;
+
+use crate::{conversations, settings};
+use crate::error::{AppError, ErrorKind};
+use crate::locks::LockExt;
+use crate::profiles::active_data_dir;
+use crate::state::AppState;
+
+// ── Weather and coarse location ─────────────────────────────────────────────
+//
+// Current conditions and today's forecast for one place, for the feed digest
+// and as an assistant tool. The place is never read from the OS location
+// services: the user types it in, or asks once for a lookup by IP address.
+// Either way it is rounded to a tenth of a degree (about 11 km) before it is
+// stored or sent anywhere.
+//
+// The forecast comes from Open-Meteo, or from any server answering in its
+// format (a self-hosted instance or a mirror) when `provider_url` is set.
+// Answers are cached in memory so briefings and tool calls don't refetch.
+
+const DEFAULT_PROVIDER_URL: &str = "https://api.open-meteo.com/v1/forecast";
+const GEOCODING_URL: &str = "https://geocoding-api.open-meteo.com/v1/search";
+const IP_LOCATION_URL: &str = "https://ipapi.co/json/";
+/// Cache lifetime when `WeatherSettings::cache_minutes` is unset.
+const DEFAULT_CACHE_MINUTES: u32 = 30;
+const TIMEOUT_SECS: u64 = 15;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Units {
+    #[default]
+    Metric,
+    Imperial,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+    /// Place name shown to the user and the model.
+    pub name: Option<String>,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Location {
+    /// The same place to about 11 km.
+    pub fn coarse(self) -> Self {
+        let round = |degrees: f64| (degrees * 10.0).round() / 10.0;
+        Self { name: self.name, latitude: round(self.latitude), longitude: round(self.longitude) }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(-90.0..=90.0).contains(&self.latitude) || !(-180.0..=180.0).contains(&self.longitude)
+        {
+            return Err("Latitude or longitude out of range".into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WeatherSettings {
+    /// Fetch weather at all; the digest includes it when set.
+    pub enabled: bool,
+    pub location: Option<Location>,
+    pub units: Units,
+    /// An Open-Meteo compatible forecast endpoint; `None` uses Open-Meteo.
+    pub provider_url: Option<String>,
+    /// Minutes a fetched forecast is reused; `None` is 30.
+    pub cache_minutes: Option<u32>,
+}
+
+impl WeatherSettings {
+    pub fn cache_secs(&self) -> u64 {
+        u64::from(self.cache_minutes.unwrap_or(DEFAULT_CACHE_MINUTES)) * 60
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Weather {
+    pub location: Location,
+    pub units: Units,
+    pub temperature: f64,
+    pub feels_like: f64,
+    /// Plain-language condition, e.g. "light rain".
+    pub condition: String,
+    pub wind_speed: f64,
+    pub high: Option<f64>,
+    pub low: Option<f64>,
+    /// Percent.
+    pub precipitation_chance: Option<f64>,
+    /// Unix seconds.
+    pub fetched_at: u64,
+}
+
+impl Weather {
+    /// One line for a briefing prompt.
+    pub fn summary(&self) -> String {
+        let (degrees, speed) = match self.units {
+            Units::Metric => ("°C", "km/h"),
+            Units::Imperial => ("°F", "mph"),
+        };
+        let mut line = format!(
+            "{}: {:.0}{}, {} (feels like {:.0}{}), wind {:.0} {}",
+            self.location.name.as_deref().unwrap_or("Here"),
+            self.temperature,
+            degrees,
+            self.condition,
+            self.feels_like,
+            degrees,
+            self.wind_speed,
+            speed
+        );
+        if let (Some(low), Some(high)) = (self.low, self.high) {
+            line.push_str(&format!("; today {:.0}–{:.0}{}", low, high, degrees));
+        }
+        if let Some(chance) = self.precipitation_chance {
+            line.push_str(&format!(", {:.0}% chance of precipitation", chance));
+        }
+        line
+    }
+}
+
+/// A fetched forecast and the request it answers.
+#[derive(Debug, Clone)]
+pub struct CachedWeather {
+    pub url: String,
+    pub weather: Weather,
+}
+
+impl CachedWeather {
+    pub fn fresh(&self, url: &str, now: u64, max_age: u64) -> bool {
+        self.url == url && now.saturating_sub(self.weather.fetched_at) < max_age
+    }
+}
+
+/// Forecast request URL for the configured place and units.
+pub fn forecast_url(settings: &WeatherSettings) -> Result<String, String> {
+    let location = settings.location.as_ref().ok_or("No weather location set")?;
+    let base = settings.provider_url.as_deref().unwrap_or(DEFAULT_PROVIDER_URL);
+    let mut url = reqwest::Url::parse(base).map_err(|e| format!("Invalid provider URL: {}", e))?;
+    url.query_pairs_mut()
+        .append_pair("latitude", &location.latitude.to_string())
+        .append_pair("longitude", &location.longitude.to_string())
+        .append_pair("current", "temperature_2m,apparent_temperature,weather_code,wind_speed_10m")
+        .append_pair(
+            "daily",
+            "temperature_2m_max,temperature_2m_min,precipitation_probability_max",
+        )
+        .append_pair("forecast_days", "1")
+        .append_pair("timezone", "auto");
+    if settings.units == Units::Imperial {
+        url.query_pairs_mut()
+            .append_pair("temperature_unit", "fahrenheit")
+            .append_pair("wind_speed_unit", "mph");
+    }
+    Ok(url.to_string())
+}
+
+async fn get_json(url: &str) -> Result<serde_json::Value, String> {
+    let resp = reqwest::Client::new()
+        .get(url)
+        .header(reqwest::header::USER_AGENT, "Tulsbot/0.1 (weather)")
+        .timeout(std::time::Duration::from_secs(TIMEOUT_SECS))
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("HTTP {}", resp.status().as_u16()));
+    }
+    resp.json().await.map_err(|e| e.to_string())
+}
+
+pub async fn fetch(
+    url: &str,
+    location: &Location,
+    units: Units,
+    now: u64,
+) -> Result<Weather, String> {
+    let json = get_json(url).await?;
+    let current = &json["current"];
+    let daily = &json["daily"];
+    let first = |key: &str| daily[key].get(0).and_then(|v| v.as_f64());
+    let temperature = current["temperature_2m"]
+        .as_f64()
+        .ok_or("Unexpected weather response: no current temperature")?;
+    Ok(Weather {
+        location: location.clone(),
+        units,
+        temperature,
+        feels_like: current["apparent_temperature"].as_f64().unwrap_or(temperature),
+        condition: condition(current["weather_code"].as_u64().unwrap_or(0)).to_string(),
+        wind_speed: current["wind_speed_10m"].as_f64().unwrap_or(0.0),
+        high: first("temperature_2m_max"),
+        low: first("temperature_2m_min"),
+        precipitation_chance: first("precipitation_probability_max"),
+        fetched_at: now,
+    })
+}
+
+/// The best match for a place name.
+pub async fn geocode(name: &str) -> Result<Location, String> {
+    let mut url = reqwest::Url::parse(GEOCODING_URL).expect("static URL");
+    url.query_pairs_mut().append_pair("name", name.trim()).append_pair("count", "1");
+    let json = get_json(url.as_str()).await?;
+    let place = json["results"].get(0).ok_or_else(|| format!("Unknown place '{}'", name))?;
+    let label = [&place["name"], &place["country"]]
+        .iter()
+        .filter_map(|v| v.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    location_from(place, label)
+}
+
+/// Approximate location of this machine's public IP address.
+pub async fn locate_by_ip() -> Result<Location, String> {
+    let json = get_json(IP_LOCATION_URL).await?;
+    let label = [&json["city"], &json["country_name"]]
+        .iter()
+        .filter_map(|v| v.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    location_from(&json, label)
+}
+
+fn location_from(json: &serde_json::Value, label: String) -> Result<Location, String> {
+    let (Some(latitude), Some(longitude)) = (json["latitude"].as_f64(), json["longitude"].as_f64())
+    else {
+        return Err("Location lookup returned no coordinates".into());
+    };
+    let name = (!label.is_empty()).then_some(label);
+    Ok(Location { name, latitude, longitude }.coarse())
+}
+
+/// WMO weather interpretation code in words.
+fn condition(code: u64) -> &'static str {
+    match code {
+        0 => "clear sky",
+        1 => "mainly clear",
+        2 => "partly cloudy",
+        3 => "overcast",
+        45 | 48 => "fog",
+        51..=55 => "drizzle",
+        56 | 57 => "freezing drizzle",
+        61 | 80 => "light rain",
+        63 | 81 => "rain",
+        65 | 82 => "heavy rain",
+        66 | 67 => "freezing rain",
+        71 | 85 => "light snow",
+        73 => "snow",
+        75 | 86 => "heavy snow",
+        77 => "snow grains",
+        95 => "thunderstorm",
+        96 | 99 => "thunderstorm with hail",
+        _ => "unknown conditions",
+    }
+}
+
+/// The forecast for the configured place, from the cache while it is fresh
+/// unless `refresh`.
+pub async fn current_weather(app: &AppHandle, refresh: bool) -> Result<Weather, String> {
+    let config = app.state::<AppState>().settings.lock_or_recover().weather.clone();
+    if !config.enabled {
+        return Err("Weather is turned off".into());
+    }
+    let url = forecast_url(&config)?;
+    let now = conversations::now();
+    if !refresh {
+        let cached = app.state::<AppState>().weather.lock_or_recover().clone();
+        if let Some(cached) = cached.filter(|c| c.fresh(&url, now, config.cache_secs())) {
+            return Ok(cached.weather);
+        }
+    }
+    let location = config.location.clone().ok_or("No weather location set")?;
+    let found = fetch(&url, &location, config.units, now).await?;
+    let cached = CachedWeather { url, weather: found.clone() };
+    *app.state::<AppState>().weather.lock_or_recover() = Some(cached);
+    Ok(found)
+}
+
+/// Current conditions and today's forecast where the user is, for the
+/// assistant. Fails with `forbidden` while weather is turned off.
+#[instrumented]
+#[tauri::command]
+pub async fn get_weather(app: AppHandle, refresh: Option<bool>) -> Result<Weather, AppError> {
+    let enabled = app.state::<AppState>().settings.lock_or_recover().weather.enabled;
+    if !enabled {
+        return Err(AppError::new(ErrorKind::Forbidden, "Weather is turned off"));
+    }
+    Ok(current_weather(&app, refresh.unwrap_or(false)).await?)
+}
+
+/// Set the weather location from a place name, or from this machine's
+/// public IP address when `place` is `None`. Only a coarse position is kept.
+#[instrumented]
+#[tauri::command]
+pub async fn set_weather_location(
+    app: AppHandle,
+    place: Option<String>,
+) -> Result<Location, AppError> {
+    let location = match place.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+        Some(place) => geocode(place).await?,
+        None => locate_by_ip().await?,
+    };
+    let state = app.state::<AppState>();
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        settings.weather.location = Some(location.clone());
+        settings.clone()
+    };
+    settings::save(&active_data_dir(&app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    Ok(location)
+}
@@ -0,0 +1,540 @@
+use kuchikiki::traits::TendrilSink;
+use kuchikiki::NodeRef;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::redirect;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use tulsbot_macros::instrumented;
+
+use crate::error::AppError;
+
+// ── Page fetching and readability extraction ────────────────────────────────
+//
+// `fetch_page` downloads a page outside the webview (whose CSP blocks
+// arbitrary origins), honours robots.txt for our user agent, and reduces the
+// HTML to its main text with a readability-style scorer: paragraphs vote for
+// their parent and grandparent by length and comma count, candidates are
+// weighed by class/id hints and link density, and the winner is returned
+// together with its qualifying siblings.
+
+const USER_AGENT: &str = "Tulsbot/0.1 (+page reader)";
+const ROBOTS_AGENT: &str = "tulsbot";
+const TIMEOUT_SECS: u64 = 15;
+const MAX_REDIRECTS: usize = 10;
+/// Bodies larger than this are cut off before parsing.
+const MAX_BODY: usize = 5 * 1024 * 1024;
+/// Paragraphs shorter than this don't vote.
+const MIN_PARAGRAPH: usize = 25;
+
+const REMOVED_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "canvas", "iframe", "form", "nav", "aside",
+    "footer", "header", "button", "select", "input",
+];
+
+const UNLIKELY: &[&str] = &[
+    "comment", "sidebar", "footer", "nav", "menu", "share", "social", "sponsor", "promo", "ad-",
+    "advert", "related", "cookie", "banner", "popup", "modal", "subscribe", "newsletter",
+];
+
+const LIKELY: &[&str] = &["article", "body", "content", "main", "post", "entry", "text", "story"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PageMetadata {
+    pub description: Option<String>,
+    pub author: Option<String>,
+    pub site_name: Option<String>,
+    pub published: Option<String>,
+    pub language: Option<String>,
+    pub canonical_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page {
+    /// Final URL after redirects.
+    pub url: String,
+    pub title: Option<String>,
+    pub text: String,
+    pub word_count: usize,
+    pub metadata: PageMetadata,
+}
+
+/// Loopback, private, link-local, unspecified and unique-local addresses,
+/// including IPv4 addresses wrapped in IPv6.
+fn is_local_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_local_ip(IpAddr::V4(v4)),
+            None => {
+                let first = ip.segments()[0];
+                ip.is_loopback()
+                    || ip.is_unspecified()
+                    || first & 0xfe00 == 0xfc00
+                    || first & 0xffc0 == 0xfe80
+            }
+        },
+    }
+}
+
+/// Refuse schemes other than http(s) and obviously local hosts, so the
+/// assistant can't be pointed at the local services. Checked for the URL
+/// and every redirect hop; names are checked again once resolved.
+fn validate(url: &reqwest::Url) -> Result<(), String> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("Only http(s) URLs can be fetched: {}", url));
+    }
+    let host = url.host_str().unwrap_or_default();
+    let local = match host.trim_start_matches('[').trim_end_matches(']').parse() {
+        Ok(ip) => is_local_ip(ip),
+        Err(_) => {
+            let host = host.to_ascii_lowercase();
+            host.is_empty() || host == "localhost" || host.ends_with(".localhost")
+        }
+    };
+    if local {
+        return Err(format!("Refusing to fetch a local address: {}", url));
+    }
+    Ok(())
+}
+
+/// Resolver that drops local addresses, so a public name pointing at
+/// 127.0.0.1 (or rebinding to it) can't reach the local services either.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| !is_local_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("Refusing to fetch a local address: {}", host).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Client whose redirects and connections are all held to `validate`.
+fn client() -> Result<reqwest::Client, String> {
+    let policy = redirect::Policy::custom(|attempt| {
+        if attempt.previous().len() >= MAX_REDIRECTS {
+            return attempt.error("Too many redirects");
+        }
+        match validate(attempt.url()) {
+            Ok(()) => attempt.follow(),
+            Err(e) => attempt.error(e),
+        }
+    });
+    reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .timeout(std::time::Duration::from_secs(TIMEOUT_SECS))
+        .redirect(policy)
+        .dns_resolver(Arc::new(PublicResolver))
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Download `url` (honouring robots.txt) and return its readable text and
+/// metadata for the assistant to summarize.
+#[instrumented]
+#[tauri::command]
+pub async fn fetch_page(url: String) -> Result<Page, AppError> {
+    Ok(fetch(url.trim()).await?)
+}
+
+// ── robots.txt ──────────────────────────────────────────────────────────────
+
+/// User agents of a robots.txt group and its (allow, path pattern) rules.
+type RobotsGroup = (Vec<String>, Vec<(bool, String)>);
+
+/// Whether robots.txt `text` lets `agent` fetch `path`: rules from the most
+/// specific matching group, longest match wins, Allow wins ties.
+pub fn robots_allows(text: &str, agent: &str, path: &str) -> bool {
+    let mut groups: Vec<RobotsGroup> = Vec::new();
+    let mut in_agents = false;
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (key, value) = (key.trim().to_ascii_lowercase(), value.trim());
+        match key.as_str() {
+            "user-agent" => {
+                if !in_agents {
+                    groups.push((Vec::new(), Vec::new()));
+                }
+                in_agents = true;
+                if let Some(group) = groups.last_mut() {
+                    group.0.push(value.to_ascii_lowercase());
+                }
+            }
+            "allow" | "disallow" => {
+                in_agents = false;
+                if let Some(group) = groups.last_mut() {
+                    if !value.is_empty() {
+                        group.1.push((key == "allow", value.to_string()));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    let agent = agent.to_ascii_lowercase();
+    let rules = groups
+        .iter()
+        .find(|(agents, _)| agents.iter().any(|a| agent.contains(a.as_str()) && a != "*"))
+        .or_else(|| groups.iter().find(|(agents, _)| agents.iter().any(|a| a == "*")))
+        .map(|(_, rules)| rules.as_slice())
+        .unwrap_or_default();
+    let matches = |pattern: &str| {
+        // `*` wildcards and a trailing `$` anchor
+        let (pattern, anchored) = match pattern.strip_suffix('$') {
+            Some(p) => (p, true),
+            None => (pattern, false),
+        };
+        let parts: Vec<&str> = pattern.split('*').collect();
+        let mut rest = path;
+        for (i, part) in parts.iter().enumerate() {
+            if i == 0 {
+                let Some(after) = rest.strip_prefix(part) else {
+                    return false;
+                };
+                rest = after;
+            } else if let Some(at) = rest.find(part) {
+                rest = &rest[at + part.len()..];
+            } else {
+                return false;
+            }
+        }
+        !anchored || rest.is_empty() || pattern.ends_with('*')
+    };
+    rules
+        .iter()
+        .filter(|(_, pattern)| matches(pattern))
+        .max_by_key(|(allow, pattern)| (pattern.len(), *allow))
+        .is_none_or(|(allow, _)| *allow)
+}
+
+async fn check_robots(client: &reqwest::Client, url: &reqwest::Url) -> Result<(), String> {
+    let Ok(robots) = url.join("/robots.txt") else {
+        return Ok(());
+    };
+    // Missing or unreachable robots.txt means no restrictions
+    let Ok(resp) = client.get(robots).send().await else {
+        return Ok(());
+    };
+    if !resp.status().is_success() {
+        return Ok(());
+    }
+    let text = resp.text().await.unwrap_or_default();
+    let mut path = url.path().to_string();
+    if let Some(query) = url.query() {
+        path = format!("{}?{}", path, query);
+    }
+    if robots_allows(&text, ROBOTS_AGENT, &path) {
+        Ok(())
+    } else {
+        let host = url.host_str().unwrap_or("");
+        Err(format!("robots.txt of {} disallows fetching {}", host, path))
+    }
+}
+
+// ── Extraction ──────────────────────────────────────────────────────────────
+
+fn tag(node: &NodeRef) -> Option<String> {
+    node.as_element().map(|e| e.name.local.to_string())
+}
+
+fn attr(node: &NodeRef, name: &str) -> Option<String> {
+    let element = node.as_element()?;
+    let attributes = element.attributes.borrow();
+    attributes.get(name).map(str::to_string)
+}
+
+fn meta(document: &NodeRef, keys: &[&str]) -> Option<String> {
+    let metas = document.select("meta").ok()?;
+    for meta in metas {
+        let attributes = meta.attributes.borrow();
+        let key = attributes.get("property").or(attributes.get("name")).unwrap_or("");
+        if keys.iter().any(|k| k.eq_ignore_ascii_case(key)) {
+            if let Some(content) = attributes.get("content").map(str::trim) {
+                if !content.is_empty() {
+                    return Some(content.to_string());
+                }
+            }
+        }
+    }
+    None
+}
+
+fn class_weight(node: &NodeRef) -> f64 {
+    let hints = format!(
+        "{} {}",
+        attr(node, "class").unwrap_or_default(),
+        attr(node, "id").unwrap_or_default()
+    )
+    .to_ascii_lowercase();
+    let mut weight = 0.0;
+    if UNLIKELY.iter().any(|h| hints.contains(h)) {
+        weight -= 25.0;
+    }
+    if LIKELY.iter().any(|h| hints.contains(h)) {
+        weight += 25.0;
+    }
+    weight
+}
+
+fn base_score(node: &NodeRef) -> f64 {
+    let tag_score = match tag(node).as_deref() {
+        Some("article") | Some("main") => 10.0,
+        Some("div") | Some("section") => 5.0,
+        Some("pre") | Some("td") | Some("blockquote") => 3.0,
+        Some("ol") | Some("ul") | Some("dl") | Some("th") => -3.0,
+        Some(h) if h.len() == 2 && h.starts_with('h') => -5.0,
+        _ => 0.0,
+    };
+    tag_score + class_weight(node)
+}
+
+fn normalized_len(text: &str) -> usize {
+    text.split_whitespace().map(|w| w.len() + 1).sum()
+}
+
+fn link_density(node: &NodeRef) -> f64 {
+    let total = normalized_len(&node.text_contents());
+    if total == 0 {
+        return 0.0;
+    }
+    let links: usize = node
+        .select("a")
+        .map(|links| links.map(|a| normalized_len(&a.text_contents())).sum())
+        .unwrap_or(0);
+    links as f64 / total as f64
+}
+
+/// Drop tags that never hold content and blocks whose class/id marks them as
+/// chrome (unless they also look like the content).
+fn prune(body: &NodeRef) {
+    let doomed: Vec<NodeRef> = body
+        .descendants()
+        .filter(|node| {
+            let Some(name) = tag(node) else {
+                return node.as_comment().is_some();
+            };
+            if REMOVED_TAGS.contains(&name.as_str()) {
+                return true;
+            }
+            let hidden = attr(node, "hidden").is_some()
+                || attr(node, "aria-hidden").as_deref() == Some("true");
+            let container = matches!(name.as_str(), "body" | "article" | "main");
+            hidden || (!container && class_weight(node) < 0.0)
+        })
+        .collect();
+    for node in doomed {
+        node.detach();
+    }
+}
+
+const BLOCKS: &[&str] = &[
+    "p", "div", "section", "article", "li", "pre", "blockquote", "h1", "h2", "h3", "h4", "h5",
+    "h6", "tr", "br", "table", "ul", "ol", "dd", "dt", "figcaption",
+];
+
+/// Text with line breaks at block boundaries and collapsed whitespace.
+fn block_text(node: &NodeRef) -> String {
+    fn walk(node: &NodeRef, out: &mut String) {
+        if let Some(text) = node.as_text() {
+            out.push_str(&text.borrow());
+            return;
+        }
+        let block = tag(node).is_some_and(|t| BLOCKS.contains(&t.as_str()));
+        if block {
+            out.push('\n');
+        }
+        if tag(node).as_deref() == Some("li") {
+            out.push_str("• ");
+        }
+        for child in node.children() {
+            walk(&child, out);
+        }
+        if block {
+            out.push('\n');
+        }
+    }
+    let mut raw = String::new();
+    walk(node, &mut raw);
+    let lines: Vec<String> = raw
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect();
+    let mut text = String::new();
+    let mut blank = true;
+    for line in lines {
+        if line.is_empty() || line == "•" {
+            if !blank {
+                text.push('\n');
+            }
+            blank = true;
+        } else {
+            text.push_str(&line);
+            text.push('\n');
+            blank = false;
+        }
+    }
+    text.trim().to_string()
+}
+
+/// The element most likely to hold the article, with its score.
+fn best_candidate(body: &NodeRef) -> Option<(NodeRef, f64)> {
+    let mut scores: HashMap<*const kuchikiki::Node, (NodeRef, f64)> = HashMap::new();
+    let paragraphs = body.select("p, pre, td, blockquote").ok()?;
+    for paragraph in paragraphs {
+        let node = paragraph.as_node();
+        let text = node.text_contents();
+        let len = normalized_len(&text);
+        if len < MIN_PARAGRAPH {
+            continue;
+        }
+        let score = 1.0 + text.matches(',').count() as f64 + (len as f64 / 100.0).min(3.0);
+        for (depth, ancestor) in node.ancestors().take(2).enumerate() {
+            if ancestor.as_element().is_none() {
+                break;
+            }
+            let key = std::rc::Rc::as_ptr(&ancestor.0);
+            let entry = scores
+                .entry(key)
+                .or_insert_with(|| (ancestor.clone(), base_score(&ancestor)));
+            entry.1 += if depth == 0 { score } else { score / 2.0 };
+        }
+    }
+    scores
+        .into_values()
+        .map(|(node, score)| {
+            let density = link_density(&node);
+            (node, score * (1.0 - density))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/// Title, main text and metadata of an HTML document.
+pub fn extract(html: &str, url: &str) -> Page {
+    let document = kuchikiki::parse_html().one(html).document_node;
+    let title = meta(&document, &["og:title", "twitter:title"]).or_else(|| {
+        document
+            .select_first("title")
+            .ok()
+            .map(|t| t.text_contents().split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|t| !t.is_empty())
+    });
+    let metadata = PageMetadata {
+        description: meta(&document, &["description", "og:description"]),
+        author: meta(&document, &["author", "article:author"]),
+        site_name: meta(&document, &["og:site_name", "application-name"]),
+        published: meta(&document, &["article:published_time", "date", "pubdate"]),
+        language: document
+            .select_first("html")
+            .ok()
+            .and_then(|html| attr(html.as_node(), "lang")),
+        canonical_url: document
+            .select_first("link[rel=canonical]")
+            .ok()
+            .and_then(|link| attr(link.as_node(), "href")),
+    };
+
+    let body = document
+        .select_first("body")
+        .map(|b| b.as_node().clone())
+        .unwrap_or_else(|_| document.clone());
+    prune(&body);
+    let text = match best_candidate(&body) {
+        Some((top, score)) => {
+            // Siblings that score well or read like prose belong to the article
+            let threshold = (score * 0.2).max(10.0);
+            match top.parent() {
+                Some(parent) => parent
+                    .children()
+                    .filter(|sibling| {
+                        if *sibling == top {
+                            return true;
+                        }
+                        if sibling.as_element().is_none() {
+                            return false;
+                        }
+                        let text = sibling.text_contents();
+                        let len = normalized_len(&text);
+                        let prose = tag(sibling).as_deref() == Some("p")
+                            && len > 80
+                            && link_density(sibling) < 0.25;
+                        prose || base_score(sibling) + (len as f64 / 100.0).min(3.0) > threshold
+                    })
+                    .map(|node| block_text(&node))
+                    .filter(|text| !text.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("\n\n"),
+                None => block_text(&top),
+            }
+        }
+        None => block_text(&body),
+    };
+    Page {
+        url: url.to_string(),
+        title,
+        word_count: text.split_whitespace().count(),
+        text,
+        metadata,
+    }
+}
+
+/// Download `url` and extract its readable text.
+pub async fn fetch(url: &str) -> Result<Page, String> {
+    let url = reqwest::Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+    validate(&url)?;
+    let client = client()?;
+    check_robots(&client, &url).await?;
+
+    let mut resp = client.get(url).send().await.map_err(|e| e.to_string())?;
+    validate(resp.url())?;
+    if !resp.status().is_success() {
+        return Err(format!("HTTP {}", resp.status().as_u16()));
+    }
+    let content_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("text/html")
+        .to_ascii_lowercase();
+    let final_url = resp.url().to_string();
+    let mut body = Vec::new();
+    while let Some(chunk) = resp.chunk().await.map_err(|e| e.to_string())? {
+        body.extend_from_slice(&chunk);
+        if body.len() >= MAX_BODY {
+            body.truncate(MAX_BODY);
+            break;
+        }
+    }
+    let text = String::from_utf8_lossy(&body);
+    if content_type.starts_with("text/plain") {
+        return Ok(Page {
+            url: final_url,
+            title: None,
+            word_count: text.split_whitespace().count(),
+            text: text.into_owned(),
+            metadata: PageMetadata::default(),
+        });
+    }
+    if !content_type.contains("html") {
+        return Err(format!("Not a web page: {}", content_type));
+    }
+    Ok(extract(&text, &final_url))
+}
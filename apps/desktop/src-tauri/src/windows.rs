@@ -0,0 +1,895 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+use tauri::{AppHandle, Emitter, Manager, State, WebviewWindow, WebviewWindowBuilder};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+use tauri_runtime::ResizeDirection;
+use tulsbot_macros::instrumented;
+
+use crate::placement;
+use crate::accessibility::{apply_accessibility_to, set_accessible_title};
+use crate::blur::{self, BlurBehavior};
+use crate::conversations::{self, now_millis};
+use crate::dock::{self, Dock, DockEdge, PopoverSize, SizePreset};
+use crate::error::AppError;
+use crate::guardrails::{guard_response, report_guardrails};
+use crate::layouts::{self, Layout, WindowPlacement};
+use crate::locks::LockExt;
+use crate::profiles::active_data_dir;
+use crate::settings::{self, privacy_mode};
+use crate::shortcuts::{self, ShortcutSettings};
+use crate::state::AppState;
+use crate::themes::{apply_theme_to, refresh_theme};
+use crate::time_tracking::{begin_generation, track_popover};
+use crate::tray::{begin_work, refresh_tray_menu, update_dock_icon};
+
+// ── Popover window management ───────────────────────────────────────────────
+//
+// The chat popover, the picture-in-picture strip and the dashboard: creating
+// them on demand, placing, docking and sizing the popover, saved layouts and
+// the hotkeys that move focus between them.
+
+/// Return the window with `label`, creating it from its `tauri.conf.json`
+/// entry if it does not exist yet. Windows are declared with `create: false`
+/// so headless mode can skip them entirely.
+pub fn ensure_window(app: &AppHandle, label: &str) -> Result<WebviewWindow, String> {
+    if let Some(window) = app.get_webview_window(label) {
+        return Ok(window);
+    }
+
+    let config = app
+        .config()
+        .app
+        .windows
+        .iter()
+        .find(|w| w.label == label)
+        .cloned()
+        .ok_or_else(|| format!("No window configured with label '{}'", label))?;
+
+    let window = WebviewWindowBuilder::from_config(app, &config)
+        .and_then(|builder| builder.build())
+        .map_err(|e: tauri::Error| e.to_string())?;
+
+    set_accessible_title(app, &window);
+    apply_accessibility_to(app, &window);
+    if privacy_mode(app) {
+        let _ = window.set_content_protected(true);
+    }
+    if let Ok(theme) = app.state::<AppState>().theme.lock() {
+        apply_theme_to(&theme, &window);
+    }
+
+    // Re-resolve "system" themes when the OS appearance flips
+    let theme_handle = app.clone();
+    window.on_window_event(move |event| {
+        if let tauri::WindowEvent::ThemeChanged(_) = event {
+            let app = theme_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                refresh_theme(&app).await;
+            });
+        }
+    });
+
+    // Hide, dim or leave the window on blur, as configured at that moment
+    let blur_handle = app.clone();
+    let blur_window = window.clone();
+    window.on_window_event(move |event| {
+        if let tauri::WindowEvent::Focused(focused) = event {
+            apply_blur_behavior(&blur_handle, &blur_window, *focused);
+        }
+    });
+
+    // Dashboard: in menu bar mode it brings the Dock icon along
+    if label == "main" {
+        let main_handle = app.clone();
+        window.on_window_event(move |event| match event {
+            tauri::WindowEvent::Focused(true) => update_dock_icon(&main_handle, true),
+            tauri::WindowEvent::Destroyed => update_dock_icon(&main_handle, false),
+            _ => {}
+        });
+    }
+
+    // Popover: hold Escape while focused, refit it when it lands on a
+    // monitor with another scale factor and remember sizes the user picks
+    if label == "chat-popover" {
+        let layer_surface = placement::init_layer_surface(&window);
+        app.state::<AppState>().popover_layer_surface.store(layer_surface, Ordering::SeqCst);
+        let popover = window.clone();
+        let dock_handle = app.clone();
+        window.on_window_event(move |event| match event {
+            tauri::WindowEvent::Focused(focused) => {
+                set_escape_hook(&dock_handle, *focused);
+                track_popover(&dock_handle);
+            }
+            tauri::WindowEvent::ScaleFactorChanged { .. } => {
+                let (app, popover) = (dock_handle.clone(), popover.clone());
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = refit_popover(&app, &popover) {
+                        eprintln!("[tulsbot] Failed to refit popover: {}", e);
+                    }
+                });
+            }
+            tauri::WindowEvent::Resized(_) => on_popover_resized(&dock_handle, &popover),
+            _ => {}
+        });
+    }
+
+    Ok(window)
+}
+
+/// Run the window's blur behavior when it loses focus and undo the dimming
+/// when it gets focus back. A docked popover never hides.
+fn apply_blur_behavior(app: &AppHandle, window: &WebviewWindow, focused: bool) {
+    let label = window.label();
+    let behavior = match app.state::<AppState>().settings.lock() {
+        Ok(settings) => blur::behavior(&settings.blur_behavior, label),
+        Err(_) => return,
+    };
+    match behavior {
+        BlurBehavior::Hide if !focused => {
+            let docked = app.state::<AppState>().popover_docked.load(Ordering::SeqCst);
+            if !(label == "chat-popover" && docked) {
+                let _ = window.hide();
+            }
+            if label == "chat-popover" && !docked {
+                app.state::<AppState>().popover_blurred_at.store(now_millis(), Ordering::SeqCst);
+            }
+        }
+        BlurBehavior::Dim => {
+            let _ = app.emit_to(label, "window-dimmed", !focused);
+        }
+        _ => {}
+    }
+}
+
+/// Show the popover near the top-right of the monitor the pointer is on
+/// (where the tray icon or hotkey was used) and focus it.
+/// When it was docked on the monitor it last appeared on, it goes back there.
+pub fn show_popover(app: &AppHandle) -> Result<WebviewWindow, String> {
+    show_popover_at(app, None)
+}
+
+/// `show_popover`, but a floating popover opened from the tray goes next
+/// to the icon at `anchor` (physical pixels), sized for that monitor.
+pub fn show_popover_at(
+    app: &AppHandle,
+    anchor: Option<dock::Rect>,
+) -> Result<WebviewWindow, String> {
+    let window = ensure_window(app, "chat-popover")?;
+    let docked = match popover_dock(app, &window) {
+        Some((monitor, dock)) => {
+            apply_dock(app, &window, &monitor, dock)?;
+            dock.is_some()
+        }
+        None => false,
+    };
+    // Top-right of the monitor under the pointer, or by the tray icon on its
+    // monitor, sized for that monitor's scale factor
+    if !docked {
+        let icon_monitor = anchor.and_then(|icon| {
+            let (x, y) = (icon.x + icon.width as i32 / 2, icon.y + icon.height as i32 / 2);
+            let monitor = window.monitor_from_point(f64::from(x), f64::from(y)).ok().flatten()?;
+            Some((monitor, icon))
+        });
+        let frame = match icon_monitor {
+            Some((monitor, icon)) => {
+                let size = popover_size(app, &monitor);
+                Some(dock::anchored_frame(work_area(&monitor), monitor.scale_factor(), size, icon))
+            }
+            None => pointer_monitor(&window).map(|monitor| {
+                let size = popover_size(app, &monitor);
+                dock::floating_frame(work_area(&monitor), monitor.scale_factor(), size)
+            }),
+        };
+        if let Some(frame) = frame {
+            set_frame(&window, frame)?;
+        }
+    }
+    window.show().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())?;
+    Ok(window)
+}
+
+/// Open the popover with external context (browser selection, shared text…).
+/// The payload is kept until the popover takes it, since a freshly created
+/// webview may not be listening yet when the event fires.
+pub fn open_popover_with_context(
+    app: &AppHandle,
+    context: serde_json::Value,
+) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    *state.popover_context.lock_or_recover() = Some(context.clone());
+    show_popover(app)?;
+    let _ = app.emit_to("chat-popover", "popover-context", &context);
+    Ok(())
+}
+
+/// Set what window `label` does when it loses focus. Applies to the open
+/// window right away.
+#[instrumented]
+#[tauri::command]
+pub async fn set_blur_behavior(
+    app: AppHandle,
+    label: String,
+    behavior: BlurBehavior,
+) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        settings.blur_behavior.insert(label.clone(), behavior);
+        settings.clone()
+    };
+    settings::save(&active_data_dir(&app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    // Don't leave the window dimmed under a behavior that never undims it
+    if behavior != BlurBehavior::Dim {
+        let _ = app.emit_to(label.as_str(), "window-dimmed", false);
+    }
+    Ok(())
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn toggle_popover(app: AppHandle) -> Result<(), AppError> {
+    let window = ensure_window(&app, "chat-popover")?;
+    if window.is_visible().unwrap_or(false) {
+        window.hide().map_err(|e| e.to_string())?;
+    } else {
+        show_popover(&app)?;
+    }
+    track_popover(&app);
+    Ok(())
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn take_popover_context(
+    state: State<'_, AppState>,
+) -> Result<Option<serde_json::Value>, AppError> {
+    let mut context = state.popover_context.lock_or_recover();
+    Ok(context.take())
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn hide_popover(app: AppHandle) -> Result<(), AppError> {
+    if let Some(window) = app.get_webview_window("chat-popover") {
+        window.hide().map_err(|e| e.to_string())?;
+    }
+    track_popover(&app);
+    Ok(())
+}
+
+// ── Picture-in-picture response window ──────────────────────────────────────
+//
+// A small borderless, always-on-top strip that only shows the response being
+// streamed for the latest request, like subtitles. The chat webview pushes
+// the text as it streams; this side keeps the latest state so a window opened
+// mid-response can catch up, and relays each update as `pip-response`.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipResponse {
+    pub request_id: String,
+    pub text: String,
+    pub done: bool,
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn open_pip(app: AppHandle) -> Result<(), AppError> {
+    let window = ensure_window(&app, "response-pip")?;
+    // Bottom center of the primary monitor, above the dock/taskbar
+    if let Ok(Some(monitor)) = window.primary_monitor() {
+        let scale = monitor.scale_factor();
+        let screen_w = monitor.size().width as f64 / scale;
+        let screen_h = monitor.size().height as f64 / scale;
+        let size = window.outer_size().map_err(|e| e.to_string())?.to_logical::<f64>(scale);
+        let _ = window.set_position(tauri::Position::Logical(tauri::LogicalPosition::new(
+            (screen_w - size.width) / 2.0,
+            screen_h - size.height - 80.0,
+        )));
+    }
+    let click_through = app
+        .state::<AppState>()
+        .settings
+        .lock()
+        .map(|s| s.pip_click_through)
+        .unwrap_or(false);
+    window.set_ignore_cursor_events(click_through).map_err(|e| e.to_string())?;
+    Ok(window.show().map_err(|e| e.to_string())?)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn close_pip(app: AppHandle) -> Result<(), AppError> {
+    if let Some(window) = app.get_webview_window("response-pip") {
+        window.hide().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Let clicks pass through the window to whatever is underneath.
+#[instrumented]
+#[tauri::command]
+pub async fn set_pip_click_through(app: AppHandle, enabled: bool) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        settings.pip_click_through = enabled;
+        settings.clone()
+    };
+    settings::save(&active_data_dir(&app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    if let Some(window) = app.get_webview_window("response-pip") {
+        window.set_ignore_cursor_events(enabled).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Called by the chat webview as a response streams. A new `request_id`
+/// replaces the previous response; otherwise `text` is the full text so far.
+#[instrumented]
+#[tauri::command]
+pub async fn update_pip(
+    app: AppHandle,
+    request_id: String,
+    text: String,
+    done: Option<bool>,
+) -> Result<(), AppError> {
+    // Filtered on every update; reported once, when the response is done
+    let filtered = guard_response(&app, &text);
+    let done = done.unwrap_or(false);
+    if done {
+        report_guardrails(&app, &filtered, "response-pip");
+    }
+    let response = PipResponse { request_id, text: filtered.text, done };
+    let state = app.state::<AppState>();
+    {
+        let mut streaming = state.streaming.lock_or_recover();
+        if response.done {
+            streaming.take();
+        } else if streaming.is_none() {
+            *streaming = Some((begin_work(&app), begin_generation(&app)));
+        }
+    }
+    *state.pip_response.lock_or_recover() = Some(response.clone());
+    let _ = app.emit_to("response-pip", "pip-response", &response);
+    Ok(())
+}
+
+/// The latest response, for a window that just opened.
+#[instrumented]
+#[tauri::command]
+pub async fn get_pip_response(state: State<'_, AppState>) -> Result<Option<PipResponse>, AppError> {
+    Ok(state.pip_response.lock_or_recover().clone())
+}
+
+// ── Popover docking ─────────────────────────────────────────────────────────
+
+/// `monitor` minus taskbars and menu bar, in physical pixels.
+pub fn work_area(monitor: &tauri::Monitor) -> dock::Rect {
+    let area = monitor.work_area();
+    dock::Rect {
+        x: area.position.x,
+        y: area.position.y,
+        width: area.size.width,
+        height: area.size.height,
+    }
+}
+
+/// Move and resize `window` in physical pixels, so the result doesn't depend
+/// on which monitor's scale factor a logical size would be resolved with.
+/// Place `window` as well as the display server allows (see placement.rs).
+pub fn set_frame(window: &WebviewWindow, frame: dock::Rect) -> Result<(), String> {
+    let state = window.state::<AppState>();
+    let popover = window.label() == "chat-popover";
+    if popover {
+        state.popover_framed_at.store(now_millis(), Ordering::SeqCst);
+    }
+    let layer_surface = popover && state.popover_layer_surface.load(Ordering::SeqCst);
+    let size = tauri::Size::Physical(tauri::PhysicalSize::new(frame.width, frame.height));
+    match placement::strategy(layer_surface) {
+        placement::Strategy::Absolute => {
+            // Move first: on Windows, landing on a monitor with another DPI
+            // rescales the window, which would undo a size set before the move
+            let position = tauri::PhysicalPosition::new(frame.x, frame.y);
+            window.set_position(tauri::Position::Physical(position)).map_err(|e| e.to_string())?;
+            window.set_size(size).map_err(|e| e.to_string())
+        }
+        placement::Strategy::LayerShell => {
+            window.set_size(size).map_err(|e| e.to_string())?;
+            let (x, y) = (frame.x + frame.width as i32 / 2, frame.y + frame.height as i32 / 2);
+            let monitor = window.monitor_from_point(f64::from(x), f64::from(y)).ok().flatten();
+            if let Some(monitor) = monitor {
+                let (position, extent) = (monitor.position(), monitor.size());
+                let output = dock::Rect {
+                    x: position.x,
+                    y: position.y,
+                    width: extent.width,
+                    height: extent.height,
+                };
+                placement::place_layer_surface(window, frame, output, monitor.scale_factor());
+            }
+            Ok(())
+        }
+        placement::Strategy::Compositor => window.set_size(size).map_err(|e| e.to_string()),
+    }
+}
+
+/// The monitor under the pointer, or the primary one.
+fn pointer_monitor(window: &WebviewWindow) -> Option<tauri::Monitor> {
+    window
+        .cursor_position()
+        .ok()
+        .and_then(|p| window.monitor_from_point(p.x, p.y).ok().flatten())
+        .or_else(|| window.primary_monitor().ok().flatten())
+}
+
+/// After the popover moved to a monitor with another scale factor: a docked
+/// popover docks again on the new monitor (or floats if it has no dock
+/// there); a floating one gets its logical size back and is pulled inside
+/// the new work area.
+pub fn refit_popover(app: &AppHandle, window: &WebviewWindow) -> Result<(), String> {
+    let (monitor, dock) = popover_dock(app, window).ok_or("No monitor found")?;
+    if app.state::<AppState>().popover_docked.load(Ordering::SeqCst) {
+        return apply_dock(app, window, &monitor, dock);
+    }
+    let position = window.outer_position().map_err(|e| e.to_string())?;
+    let size = window.outer_size().map_err(|e| e.to_string())?;
+    let current =
+        dock::Rect { x: position.x, y: position.y, width: size.width, height: size.height };
+    let size = popover_size(app, &monitor);
+    set_frame(window, dock::refit(current, work_area(&monitor), monitor.scale_factor(), size))
+}
+
+/// The floating popover's logical size on `monitor`: the size it was last
+/// resized to there, else the preset.
+pub fn popover_size(app: &AppHandle, monitor: &tauri::Monitor) -> PopoverSize {
+    let key = dock::monitor_key(monitor.name());
+    match app.state::<AppState>().settings.lock() {
+        Ok(s) => s.popover_sizes.get(&key).copied().unwrap_or_else(|| s.popover_size.size()),
+        Err(_) => SizePreset::default().size(),
+    }
+}
+
+/// Resizes this soon after we sized the popover are taken to be ours; a
+/// resize by hand is remembered once it paused this long.
+const POPOVER_FRAME_SETTLE_MS: u64 = 500;
+
+/// A floating popover was resized. Unless we did it, remember the size for
+/// its monitor once the user stops dragging.
+fn on_popover_resized(app: &AppHandle, window: &WebviewWindow) {
+    let state = app.state::<AppState>();
+    let ours = now_millis().saturating_sub(state.popover_framed_at.load(Ordering::SeqCst))
+        < POPOVER_FRAME_SETTLE_MS;
+    if ours || state.popover_docked.load(Ordering::SeqCst) || !window.is_visible().unwrap_or(false)
+    {
+        return;
+    }
+    let resize = state.popover_resizes.fetch_add(1, Ordering::SeqCst) + 1;
+    let (app, window) = (app.clone(), window.clone());
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(POPOVER_FRAME_SETTLE_MS)).await;
+        if app.state::<AppState>().popover_resizes.load(Ordering::SeqCst) != resize {
+            return;
+        }
+        if let Err(e) = remember_popover_size(&app, &window) {
+            eprintln!("[tulsbot] Failed to save popover size: {}", e);
+        }
+    });
+}
+
+fn remember_popover_size(app: &AppHandle, window: &WebviewWindow) -> Result<(), String> {
+    let monitor = window.current_monitor().map_err(|e| e.to_string())?.ok_or("No monitor found")?;
+    let physical = window.outer_size().map_err(|e| e.to_string())?;
+    let size = PopoverSize::from_physical(physical.width, physical.height, monitor.scale_factor());
+    let state = app.state::<AppState>();
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        settings.popover_sizes.insert(dock::monitor_key(monitor.name()), size);
+        settings.clone()
+    };
+    settings::save(&active_data_dir(app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    Ok(())
+}
+
+/// The monitor the popover is on (or the primary one) and its saved dock.
+pub fn popover_dock(
+    app: &AppHandle,
+    window: &WebviewWindow,
+) -> Option<(tauri::Monitor, Option<Dock>)> {
+    let monitor = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .or_else(|| window.primary_monitor().ok().flatten())?;
+    let key = dock::monitor_key(monitor.name());
+    let dock = app
+        .state::<AppState>()
+        .settings
+        .lock()
+        .ok()
+        .and_then(|s| s.popover_dock.get(&key).copied());
+    Some((monitor, dock))
+}
+
+/// Dock the popover on `monitor` (reserving the area where the platform
+/// allows), or return it to a floating window with `None`.
+fn apply_dock(
+    app: &AppHandle,
+    window: &WebviewWindow,
+    monitor: &tauri::Monitor,
+    dock: Option<Dock>,
+) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let was_docked = state.popover_docked.swap(dock.is_some(), Ordering::SeqCst);
+    let Some(dock) = dock else {
+        if !was_docked {
+            return Ok(());
+        }
+        #[cfg(windows)]
+        if let Ok(hwnd) = window.hwnd() {
+            dock::release_appbar(hwnd.0 as isize);
+        }
+        #[cfg(all(unix, not(target_os = "macos")))]
+        if let Err(e) = dock::set_strut(&window.title().unwrap_or_default(), None) {
+            eprintln!("[tulsbot] Failed to release docked area: {}", e);
+        }
+        let size = popover_size(app, monitor);
+        set_frame(window, dock::floating_frame(work_area(monitor), monitor.scale_factor(), size))?;
+        let _ = app.emit("popover-docked", Option::<Dock>::None);
+        return Ok(());
+    };
+    #[allow(unused_mut)]
+    let mut frame = dock::frame(work_area(monitor), monitor.scale_factor(), dock);
+    #[cfg(windows)]
+    if let Ok(hwnd) = window.hwnd() {
+        frame = dock::reserve_appbar(hwnd.0 as isize, dock.edge, frame);
+    }
+    set_frame(window, frame)?;
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if let Err(e) = dock::set_strut(&window.title().unwrap_or_default(), Some((dock.edge, frame))) {
+        eprintln!("[tulsbot] Failed to reserve docked area: {}", e);
+    }
+    let _ = app.emit("popover-docked", Some(dock));
+    Ok(())
+}
+
+/// Size the floating popover to `preset`. It replaces a hand-picked size on
+/// the popover's current monitor; other monitors keep theirs.
+#[instrumented]
+#[tauri::command]
+pub async fn set_popover_size(app: AppHandle, preset: SizePreset) -> Result<PopoverSize, AppError> {
+    let window = ensure_window(&app, "chat-popover")?;
+    let (monitor, _) = popover_dock(&app, &window).ok_or("No monitor found")?;
+    let state = app.state::<AppState>();
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        settings.popover_size = preset;
+        settings.popover_sizes.remove(&dock::monitor_key(monitor.name()));
+        settings.clone()
+    };
+    settings::save(&active_data_dir(&app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    if window.is_visible().unwrap_or(false) && !state.popover_docked.load(Ordering::SeqCst) {
+        refit_popover(&app, &window)?;
+    }
+    Ok(preset.size())
+}
+
+/// Start resizing the borderless popover from its resize handle; the drag
+/// follows the pointer until the button is released.
+#[instrumented]
+#[tauri::command]
+pub async fn start_popover_resize(
+    app: AppHandle,
+    direction: ResizeDirection,
+) -> Result<(), AppError> {
+    let window = app.get_webview_window("chat-popover").ok_or("The popover is not open")?;
+    window.as_ref().window().start_resize_dragging(direction).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Dock the popover to the `left` or `right` edge of its monitor, optionally
+/// as a compact strip, or undock it when `edge` is absent. Remembered per
+/// monitor.
+#[instrumented]
+#[tauri::command]
+pub async fn dock_popover(
+    app: AppHandle,
+    edge: Option<DockEdge>,
+    strip: Option<bool>,
+) -> Result<(), AppError> {
+    let window = ensure_window(&app, "chat-popover")?;
+    let (monitor, _) = popover_dock(&app, &window).ok_or("No monitor found")?;
+    let dock = edge.map(|edge| Dock { edge, strip: strip.unwrap_or(false) });
+    let state = app.state::<AppState>();
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        let key = dock::monitor_key(monitor.name());
+        match dock {
+            Some(dock) => settings.popover_dock.insert(key, dock),
+            None => settings.popover_dock.remove(&key),
+        };
+        settings.clone()
+    };
+    settings::save(&active_data_dir(&app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    show_popover(&app)?;
+    Ok(())
+}
+
+// ── Window layouts ──────────────────────────────────────────────────────────
+
+pub fn capture_window(window: &WebviewWindow) -> Result<WindowPlacement, String> {
+    let position = window.outer_position().map_err(|e| e.to_string())?;
+    let size = window.outer_size().map_err(|e| e.to_string())?;
+    Ok(WindowPlacement {
+        label: window.label().to_string(),
+        visible: window.is_visible().unwrap_or(false),
+        pinned: window.is_always_on_top().unwrap_or(false),
+        monitor: window.current_monitor().ok().flatten().and_then(|m| m.name().cloned()),
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    })
+}
+
+pub fn update_layouts(
+    app: &AppHandle,
+    change: impl FnOnce(&mut Vec<Layout>),
+) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        change(&mut settings.window_layouts);
+        settings.clone()
+    };
+    settings::save(&active_data_dir(app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    refresh_tray_menu(app);
+    Ok(())
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn list_layouts(state: State<'_, AppState>) -> Result<Vec<Layout>, AppError> {
+    Ok(state.settings.lock_or_recover().window_layouts.clone())
+}
+
+/// Save which windows are open, their geometry and pinned state as `name`,
+/// replacing a layout with the same name.
+#[instrumented]
+#[tauri::command]
+pub async fn save_layout(app: AppHandle, name: String) -> Result<Layout, AppError> {
+    layouts::validate_name(&name)?;
+    let mut windows: Vec<WebviewWindow> = app.webview_windows().into_values().collect();
+    windows.sort_by(|a, b| a.label().cmp(b.label()));
+    let layout = Layout {
+        name: name.trim().to_string(),
+        windows: windows.iter().map(capture_window).collect::<Result<_, _>>()?,
+        saved_at: conversations::now(),
+    };
+    let saved = layout.clone();
+    update_layouts(&app, |layouts| layouts::upsert(layouts, saved))?;
+    Ok(layout)
+}
+
+/// Show, place and pin the windows of layout `name` and hide the others.
+/// Geometry saved on a monitor that is no longer connected is skipped, and
+/// a docked popover stays docked.
+#[instrumented]
+#[tauri::command]
+pub async fn apply_layout(app: AppHandle, name: String) -> Result<(), AppError> {
+    let layout = app
+        .state::<AppState>()
+        .settings
+        .lock()
+        .map_err(|e| e.to_string())?
+        .window_layouts
+        .iter()
+        .find(|l| l.name == name)
+        .cloned()
+        .ok_or_else(|| format!("Unknown layout: {}", name))?;
+    let monitors: Vec<String> = app
+        .available_monitors()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .filter_map(|m| m.name().cloned())
+        .collect();
+    let docked = app.state::<AppState>().popover_docked.load(Ordering::SeqCst);
+    for (label, window) in app.webview_windows() {
+        if !layout.windows.iter().any(|w| w.label == label && w.visible) {
+            window.hide().map_err(|e| e.to_string())?;
+        }
+    }
+    for placement in layout.windows.iter().filter(|w| w.visible) {
+        let window = ensure_window(&app, &placement.label)?;
+        let on_screen = placement.monitor.as_ref().is_some_and(|m| monitors.contains(m));
+        if on_screen && !(placement.label == "chat-popover" && docked) {
+            let frame = dock::Rect {
+                x: placement.x,
+                y: placement.y,
+                width: placement.width,
+                height: placement.height,
+            };
+            set_frame(&window, frame)?;
+        }
+        window.set_always_on_top(placement.pinned).map_err(|e| e.to_string())?;
+        window.show().map_err(|e| e.to_string())?;
+    }
+    let _ = app.emit("layout-applied", &layout.name);
+    Ok(())
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn delete_layout(app: AppHandle, name: String) -> Result<(), AppError> {
+    Ok(update_layouts(&app, |layouts| layouts.retain(|l| l.name != name))?)
+}
+
+// ── Window focus ────────────────────────────────────────────────────────────
+
+pub fn focus_window_now(app: &AppHandle, label: &str) -> Result<(), String> {
+    if app.state::<AppState>().headless.load(Ordering::Relaxed) {
+        return Err("No windows in headless mode".into());
+    }
+    if label == "chat-popover" {
+        return show_popover(app).map(|_| ());
+    }
+    let window = ensure_window(app, label)?;
+    let _ = window.unminimize();
+    window.show().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())
+}
+
+/// Register the configured global shortcuts, replacing any registered before.
+pub fn register_shortcuts(app: &AppHandle) -> Result<(), String> {
+    let global = app.global_shortcut();
+    global.unregister_all().map_err(|e| e.to_string())?;
+    if app.state::<AppState>().headless.load(Ordering::Relaxed) {
+        return Ok(());
+    }
+    let settings = app
+        .state::<AppState>()
+        .settings
+        .lock()
+        .map_err(|e| e.to_string())?
+        .shortcuts
+        .clone();
+    if let Some(accelerator) = settings.cycle_windows().or_else(|| tray_fallback_shortcut(app)) {
+        let shortcut: Shortcut = accelerator
+            .parse()
+            .map_err(|e| format!("Invalid shortcut '{}': {}", accelerator, e))?;
+        global.register(shortcut).map_err(|e| format!("{}: {}", accelerator, e))?;
+    }
+    let popover_focused = app
+        .get_webview_window("chat-popover")
+        .is_some_and(|w| w.is_focused().unwrap_or(false));
+    set_escape_hook(app, popover_focused);
+    Ok(())
+}
+
+/// Without a tray icon the window-cycling shortcut is the way back to the
+/// app, so it stays registered with its default even when turned off.
+fn tray_fallback_shortcut(app: &AppHandle) -> Option<&'static str> {
+    let missing = app.state::<AppState>().tray_missing.load(Ordering::Relaxed);
+    missing.then_some(shortcuts::DEFAULT_CYCLE_WINDOWS)
+}
+
+fn escape_shortcut() -> Option<Shortcut> {
+    shortcuts::ESCAPE.parse().ok()
+}
+
+/// Hold Escape while the popover has focus and release it otherwise.
+fn set_escape_hook(app: &AppHandle, active: bool) {
+    let Some(escape) = escape_shortcut() else {
+        return;
+    };
+    let global = app.global_shortcut();
+    let result = match (active, global.is_registered(escape)) {
+        (true, false) => global.register(escape),
+        (false, true) => global.unregister(escape),
+        _ => Ok(()),
+    };
+    if let Err(e) = result {
+        eprintln!("[tulsbot] Failed to update the Escape hook: {}", e);
+    }
+}
+
+pub fn handle_shortcut(app: &AppHandle, shortcut: &Shortcut) {
+    let settings = match app.state::<AppState>().settings.lock() {
+        Ok(settings) => settings.shortcuts.clone(),
+        Err(_) => return,
+    };
+    let cycle = settings
+        .cycle_windows()
+        .or_else(|| tray_fallback_shortcut(app))
+        .and_then(|a| a.parse::<Shortcut>().ok());
+    if cycle.as_ref() == Some(shortcut) {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = focus_next_window(app).await {
+                eprintln!("[tulsbot] Failed to cycle windows: {}", e);
+            }
+        });
+        return;
+    }
+    if escape_shortcut().as_ref() == Some(shortcut) {
+        let popover = app
+            .get_webview_window("chat-popover")
+            .filter(|w| w.is_focused().unwrap_or(false));
+        let docked = app.state::<AppState>().popover_docked.load(Ordering::SeqCst);
+        match popover {
+            Some(popover) if !docked => {
+                let _ = popover.hide();
+            }
+            // Focus moved on without a blur event, or the popover is docked
+            // and stays up: Escape belongs to whatever has focus
+            _ => set_escape_hook(app, false),
+        }
+        return;
+    }
+    // Left over from an earlier binding: stop swallowing it
+    eprintln!("[tulsbot] Passing unbound shortcut {} back to the OS", shortcut.into_string());
+    if let Err(e) = app.global_shortcut().unregister(*shortcut) {
+        eprintln!("[tulsbot] Failed to release shortcut: {}", e);
+    }
+}
+
+/// Show and focus the window with `label`, creating it if needed.
+#[instrumented]
+#[tauri::command]
+pub async fn focus_window(app: AppHandle, label: String) -> Result<(), AppError> {
+    Ok(focus_window_now(&app, &label)?)
+}
+
+/// Focus the window after the focused one among the open windows (and the
+/// popover, which hides itself on blur), wrapping around. Returns its label.
+#[instrumented]
+#[tauri::command]
+pub async fn focus_next_window(app: AppHandle) -> Result<String, AppError> {
+    let windows = app.webview_windows();
+    let labels: Vec<String> = windows
+        .iter()
+        .filter(|(label, w)| *label == "chat-popover" || w.is_visible().unwrap_or(false))
+        .map(|(label, _)| label.clone())
+        .collect();
+    let current = windows
+        .iter()
+        .find(|(_, w)| w.is_focused().unwrap_or(false))
+        .map(|(label, _)| label.as_str());
+    let next = shortcuts::next_window(&labels, current).ok_or("No windows to focus")?;
+    focus_window_now(&app, &next)?;
+    Ok(next)
+}
+
+/// Replace the global shortcut bindings and register them.
+#[instrumented]
+#[tauri::command]
+pub async fn set_shortcuts(app: AppHandle, shortcuts: ShortcutSettings) -> Result<(), AppError> {
+    if let Some(accelerator) = shortcuts.cycle_windows() {
+        accelerator
+            .parse::<Shortcut>()
+            .map_err(|e| format!("Invalid shortcut '{}': {}", accelerator, e))?;
+    }
+    let state = app.state::<AppState>();
+    let settings = {
+        let mut settings = state.settings.lock_or_recover();
+        settings.shortcuts = shortcuts;
+        settings.clone()
+    };
+    settings::save(&active_data_dir(&app)?, &settings)?;
+    let _ = app.emit("settings-changed", &settings);
+    Ok(register_shortcuts(&app)?)
+}
+
+#[instrumented]
+#[tauri::command]
+pub async fn show_dashboard(app: AppHandle) -> Result<(), AppError> {
+    let window = ensure_window(&app, "main")?;
+    window.show().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())?;
+    Ok(())
+}